@@ -1,10 +1,10 @@
-use crate::adapters::llm::{LLMAdapter, LLMRequest, LLMResponse, ModelConfig, Usage};
+use crate::adapters::llm::{
+    send_with_retry, LLMAdapter, LLMRequest, LLMResponse, ModelConfig, Usage,
+};
 use anyhow::{Context, Result};
 use async_trait::async_trait;
-use reqwest::{Client, StatusCode};
+use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use std::time::Duration;
-use tokio::time::sleep;
 
 pub struct AnthropicAdapter {
     client: Client,
@@ -19,13 +19,28 @@ struct AnthropicRequest {
     messages: Vec<Message>,
     max_tokens: usize,
     temperature: f32,
-    system: String,
+    system: Vec<ContentBlock>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize)]
 struct Message {
     role: String,
-    content: String,
+    content: Vec<ContentBlock>,
+}
+
+#[derive(Serialize)]
+struct ContentBlock {
+    #[serde(rename = "type")]
+    block_type: &'static str,
+    text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cache_control: Option<CacheControl>,
+}
+
+#[derive(Serialize)]
+struct CacheControl {
+    #[serde(rename = "type")]
+    cache_type: &'static str,
 }
 
 #[derive(Deserialize)]
@@ -46,6 +61,10 @@ struct Content {
 struct AnthropicUsage {
     input_tokens: usize,
     output_tokens: usize,
+    #[serde(default)]
+    cache_creation_input_tokens: Option<usize>,
+    #[serde(default)]
+    cache_read_input_tokens: Option<usize>,
 }
 
 impl AnthropicAdapter {
@@ -60,7 +79,9 @@ impl AnthropicAdapter {
             .unwrap_or_else(|| "https://api.anthropic.com/v1".to_string());
 
         let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(60))
+            .timeout(std::time::Duration::from_secs(
+                config.timeout_secs.unwrap_or(60),
+            ))
             .build()?;
 
         Ok(Self {
@@ -70,79 +91,43 @@ impl AnthropicAdapter {
             base_url,
         })
     }
-
-    async fn send_with_retry<F>(&self, mut make_request: F) -> Result<reqwest::Response>
-    where
-        F: FnMut() -> reqwest::RequestBuilder,
-    {
-        const MAX_RETRIES: usize = 2;
-        const BASE_DELAY_MS: u64 = 250;
-
-        for attempt in 0..=MAX_RETRIES {
-            match make_request().send().await {
-                Ok(response) => {
-                    if response.status().is_success() {
-                        return Ok(response);
-                    }
-
-                    let status = response.status();
-                    let body = response.text().await.unwrap_or_default();
-                    if is_retryable_status(status) && attempt < MAX_RETRIES {
-                        sleep(Duration::from_millis(BASE_DELAY_MS * (attempt as u64 + 1))).await;
-                        continue;
-                    }
-
-                    anyhow::bail!("Anthropic API error ({}): {}", status, body);
-                }
-                Err(err) => {
-                    if attempt < MAX_RETRIES {
-                        sleep(Duration::from_millis(BASE_DELAY_MS * (attempt as u64 + 1))).await;
-                        continue;
-                    }
-                    return Err(err.into());
-                }
-            }
-        }
-
-        anyhow::bail!("Anthropic request failed after retries");
-    }
 }
 
 #[async_trait]
 impl LLMAdapter for AnthropicAdapter {
     async fn complete(&self, request: LLMRequest) -> Result<LLMResponse> {
-        let messages = vec![Message {
-            role: "user".to_string(),
-            content: request.user_prompt,
-        }];
-
-        let anthropic_request = AnthropicRequest {
-            model: self.config.model_name.clone(),
-            messages,
-            max_tokens: request.max_tokens.unwrap_or(self.config.max_tokens),
-            temperature: request.temperature.unwrap_or(self.config.temperature),
-            system: request.system_prompt,
-        };
+        let anthropic_request = build_anthropic_request(&self.config, request);
 
         let url = format!("{}/messages", self.base_url);
-        let response = self
-            .send_with_retry(|| {
-                self.client
-                    .post(&url)
-                    .header("x-api-key", &self.api_key)
-                    .header("anthropic-version", "2023-06-01")
-                    .header("anthropic-beta", "messages-2023-12-15")
-                    .header("Content-Type", "application/json")
-                    .json(&anthropic_request)
-            })
-            .await
-            .context("Failed to send request to Anthropic")?;
+        let response = send_with_retry(&self.config.retry_policy, "Anthropic", || {
+            self.client
+                .post(&url)
+                .header("x-api-key", &self.api_key)
+                .header("anthropic-version", "2023-06-01")
+                .header(
+                    "anthropic-beta",
+                    "messages-2023-12-15,prompt-caching-2024-07-31",
+                )
+                .header("Content-Type", "application/json")
+                .json(&anthropic_request)
+        })
+        .await
+        .context("Failed to send request to Anthropic")?;
 
         let anthropic_response: AnthropicResponse = response
             .json()
             .await
             .context("Failed to parse Anthropic response")?;
 
+        let usage = &anthropic_response.usage;
+        if usage.cache_creation_input_tokens.is_some() || usage.cache_read_input_tokens.is_some() {
+            tracing::debug!(
+                "Anthropic prompt cache: {} token(s) written, {} token(s) read",
+                usage.cache_creation_input_tokens.unwrap_or(0),
+                usage.cache_read_input_tokens.unwrap_or(0)
+            );
+        }
+
         let content = anthropic_response
             .content
             .first()
@@ -173,6 +158,97 @@ impl LLMAdapter for AnthropicAdapter {
     }
 }
 
-fn is_retryable_status(status: StatusCode) -> bool {
-    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+fn build_anthropic_request(config: &ModelConfig, request: LLMRequest) -> AnthropicRequest {
+    let cache_control = || {
+        config.anthropic_prompt_caching.then_some(CacheControl {
+            cache_type: "ephemeral",
+        })
+    };
+
+    // Static blocks go first and carry cache breakpoints so repeated requests
+    // (one per file) can reuse the cached prefix.
+    let mut system = Vec::new();
+    if !request.system_prompt.trim().is_empty() {
+        system.push(ContentBlock {
+            block_type: "text",
+            text: request.system_prompt,
+            cache_control: cache_control(),
+        });
+    }
+
+    let mut content = Vec::new();
+    if let Some(context) = request.cached_context {
+        if !context.trim().is_empty() {
+            content.push(ContentBlock {
+                block_type: "text",
+                text: context,
+                cache_control: cache_control(),
+            });
+        }
+    }
+    content.push(ContentBlock {
+        block_type: "text",
+        text: request.user_prompt,
+        cache_control: None,
+    });
+
+    AnthropicRequest {
+        model: config.model_name.clone(),
+        messages: vec![Message {
+            role: "user".to_string(),
+            content,
+        }],
+        max_tokens: request.max_tokens.unwrap_or(config.max_tokens),
+        temperature: request.temperature.unwrap_or(config.temperature),
+        system,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_request_marks_static_blocks_cacheable() {
+        let config = ModelConfig {
+            model_name: "claude-3-5-sonnet-20241022".to_string(),
+            ..ModelConfig::default()
+        };
+        let request = LLMRequest {
+            system_prompt: "system".to_string(),
+            user_prompt: "diff".to_string(),
+            temperature: None,
+            max_tokens: None,
+            cached_context: Some("context".to_string()),
+        };
+
+        let body = serde_json::to_value(build_anthropic_request(&config, request)).unwrap();
+        assert_eq!(body["system"][0]["cache_control"]["type"], "ephemeral");
+        let content = body["messages"][0]["content"].as_array().unwrap();
+        assert_eq!(content.len(), 2);
+        assert_eq!(content[0]["text"], "context");
+        assert_eq!(content[0]["cache_control"]["type"], "ephemeral");
+        assert_eq!(content[1]["text"], "diff");
+        assert!(content[1].get("cache_control").is_none());
+    }
+
+    #[test]
+    fn build_request_without_caching_omits_cache_control() {
+        let config = ModelConfig {
+            model_name: "claude-3-5-sonnet-20241022".to_string(),
+            anthropic_prompt_caching: false,
+            ..ModelConfig::default()
+        };
+        let request = LLMRequest {
+            system_prompt: "system".to_string(),
+            user_prompt: "diff".to_string(),
+            temperature: None,
+            max_tokens: None,
+            cached_context: None,
+        };
+
+        let body = serde_json::to_value(build_anthropic_request(&config, request)).unwrap();
+        assert!(body["system"][0].get("cache_control").is_none());
+        assert_eq!(body["messages"][0]["content"].as_array().unwrap().len(), 1);
+    }
 }
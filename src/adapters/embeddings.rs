@@ -0,0 +1,146 @@
+use crate::adapters::llm::{send_with_retry, ModelConfig};
+use crate::adapters::ollama::OLLAMA_DEFAULT_BASE_URL;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+/// Turns texts into vectors for semantic search.
+#[async_trait]
+pub trait Embedder: Send + Sync {
+    /// One vector per text, in order.
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>>;
+
+    fn model_name(&self) -> &str;
+}
+
+#[derive(Serialize)]
+struct EmbeddingRequest<'a> {
+    model: &'a str,
+    input: &'a [String],
+}
+
+#[derive(Deserialize)]
+struct OpenAIEmbeddingResponse {
+    data: Vec<OpenAIEmbedding>,
+}
+
+#[derive(Deserialize)]
+struct OpenAIEmbedding {
+    embedding: Vec<f32>,
+    #[serde(default)]
+    index: usize,
+}
+
+#[derive(Deserialize)]
+struct OllamaEmbeddingResponse {
+    embeddings: Vec<Vec<f32>>,
+}
+
+/// An embeddings endpoint: Ollama's `/api/embed` for `ollama:` models, otherwise an
+/// OpenAI-compatible `/embeddings`.
+pub struct HttpEmbedder {
+    client: Client,
+    config: ModelConfig,
+    api_key: Option<String>,
+    base_url: String,
+}
+
+impl HttpEmbedder {
+    pub fn new(config: ModelConfig) -> Result<Self> {
+        let local = config.model_name.starts_with("ollama:");
+        let api_key = config
+            .api_key
+            .clone()
+            .or_else(|| std::env::var("OPENAI_API_KEY").ok());
+        if api_key.is_none() && !local && config.base_url.is_none() {
+            anyhow::bail!("No API key for embeddings. Set OPENAI_API_KEY or embeddings.api_key");
+        }
+        let default_base_url = if local {
+            OLLAMA_DEFAULT_BASE_URL
+        } else {
+            "https://api.openai.com/v1"
+        };
+        let base_url = config
+            .base_url
+            .clone()
+            .unwrap_or_else(|| default_base_url.to_string())
+            .trim_end_matches('/')
+            .to_string();
+        let client = Client::builder()
+            .timeout(std::time::Duration::from_secs(
+                config.timeout_secs.unwrap_or(120),
+            ))
+            .build()?;
+        Ok(Self {
+            client,
+            config,
+            api_key,
+            base_url,
+        })
+    }
+}
+
+#[async_trait]
+impl Embedder for HttpEmbedder {
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+        let vectors = match self.config.model_name.strip_prefix("ollama:") {
+            Some(model) => {
+                let url = format!("{}/api/embed", self.base_url);
+                let request = EmbeddingRequest {
+                    model,
+                    input: texts,
+                };
+                let response = send_with_retry(&self.config.retry_policy, "Ollama", || {
+                    self.client.post(&url).json(&request)
+                })
+                .await?;
+                let parsed: OllamaEmbeddingResponse = response
+                    .json()
+                    .await
+                    .context("Failed to parse Ollama embeddings")?;
+                parsed.embeddings
+            }
+            None => {
+                let url = format!("{}/embeddings", self.base_url);
+                let request = EmbeddingRequest {
+                    model: &self.config.model_name,
+                    input: texts,
+                };
+                let response = send_with_retry(&self.config.retry_policy, "Embeddings", || {
+                    let builder = self.client.post(&url).json(&request);
+                    match &self.api_key {
+                        Some(key) => builder.header("Authorization", format!("Bearer {}", key)),
+                        None => builder,
+                    }
+                })
+                .await?;
+                let mut parsed: OpenAIEmbeddingResponse = response
+                    .json()
+                    .await
+                    .context("Failed to parse embeddings")?;
+                parsed.data.sort_by_key(|embedding| embedding.index);
+                parsed
+                    .data
+                    .into_iter()
+                    .map(|embedding| embedding.embedding)
+                    .collect()
+            }
+        };
+        if vectors.len() != texts.len() {
+            anyhow::bail!(
+                "Embeddings API returned {} vectors for {} texts",
+                vectors.len(),
+                texts.len()
+            );
+        }
+        Ok(vectors)
+    }
+
+    fn model_name(&self) -> &str {
+        &self.config.model_name
+    }
+}
@@ -1,6 +1,12 @@
 use anyhow::Result;
 use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelConfig {
@@ -10,6 +16,14 @@ pub struct ModelConfig {
     pub temperature: f32,
     pub max_tokens: usize,
     pub openai_use_responses: Option<bool>,
+    pub anthropic_prompt_caching: bool,
+    pub retry_policy: RetryPolicy,
+    pub llm_log_dir: Option<PathBuf>,
+    pub llm_replay_dir: Option<PathBuf>,
+    pub ollama_auto_pull: bool,
+    pub api_key_required: bool,
+    pub timeout_secs: Option<u64>,
+    pub rate_limits: HashMap<String, RateLimit>,
 }
 
 impl Default for ModelConfig {
@@ -21,16 +35,236 @@ impl Default for ModelConfig {
             temperature: 0.2,
             max_tokens: 4000,
             openai_use_responses: None,
+            anthropic_prompt_caching: true,
+            retry_policy: RetryPolicy::default(),
+            llm_log_dir: None,
+            llm_replay_dir: None,
+            ollama_auto_pull: false,
+            api_key_required: true,
+            timeout_secs: None,
+            rate_limits: HashMap::new(),
         }
     }
 }
 
+/// Retry and backoff behavior shared by all HTTP adapters.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    #[serde(default = "default_max_retries")]
+    pub max_retries: usize,
+
+    #[serde(default = "default_base_delay_ms")]
+    pub base_delay_ms: u64,
+
+    #[serde(default = "default_max_delay_ms")]
+    pub max_delay_ms: u64,
+
+    #[serde(default = "default_backoff_multiplier")]
+    pub backoff_multiplier: f64,
+
+    #[serde(default = "default_true")]
+    pub jitter: bool,
+
+    #[serde(default = "default_true")]
+    pub respect_retry_after: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: default_max_retries(),
+            base_delay_ms: default_base_delay_ms(),
+            max_delay_ms: default_max_delay_ms(),
+            backoff_multiplier: default_backoff_multiplier(),
+            jitter: true,
+            respect_retry_after: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn normalize(&mut self) {
+        if !self.backoff_multiplier.is_finite() || self.backoff_multiplier < 1.0 {
+            self.backoff_multiplier = default_backoff_multiplier();
+        }
+        if self.max_delay_ms < self.base_delay_ms {
+            self.max_delay_ms = self.base_delay_ms;
+        }
+    }
+
+    /// Exponential backoff delay before retry number `attempt` (0-based), capped at `max_delay_ms`.
+    pub fn backoff_delay(&self, attempt: usize) -> Duration {
+        let exponent = attempt.min(32) as i32;
+        let delay = self.base_delay_ms as f64 * self.backoff_multiplier.powi(exponent);
+        let delay = delay.min(self.max_delay_ms as f64) as u64;
+        let delay = if self.jitter && delay > 0 {
+            // Equal jitter: keep half the delay, randomize the other half.
+            let half = delay / 2;
+            half + random_u64() % (delay - half + 1)
+        } else {
+            delay
+        };
+        Duration::from_millis(delay)
+    }
+
+    fn retry_after_delay(&self, headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+        if !self.respect_retry_after {
+            return None;
+        }
+        let value = headers.get(reqwest::header::RETRY_AFTER)?.to_str().ok()?;
+        let delay = parse_retry_after(value)?;
+        Some(delay.min(Duration::from_millis(self.max_delay_ms)))
+    }
+}
+
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let millis = (date.with_timezone(&chrono::Utc) - chrono::Utc::now()).num_milliseconds();
+    Some(Duration::from_millis(millis.max(0) as u64))
+}
+
+fn random_u64() -> u64 {
+    use std::collections::hash_map::RandomState;
+    use std::hash::{BuildHasher, Hasher};
+    RandomState::new().build_hasher().finish()
+}
+
+pub fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// An error status returned by a provider's API once retries are exhausted.
+#[derive(Debug)]
+pub struct ApiError {
+    pub provider: String,
+    pub status: StatusCode,
+    pub body: String,
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} API error ({}): {}",
+            self.provider, self.status, self.body
+        )
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+/// Whether `err` would fail every other request the same way (a rejected key, a missing
+/// permission, an unknown model), so a review should stop instead of skipping the file.
+pub fn is_fatal(err: &anyhow::Error) -> bool {
+    err.chain()
+        .filter_map(|cause| cause.downcast_ref::<ApiError>())
+        .any(|error| {
+            matches!(
+                error.status,
+                StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN | StatusCode::NOT_FOUND
+            )
+        })
+}
+
+/// Sends a request built by `make_request`, retrying transport errors and
+/// retryable HTTP statuses according to `policy`.
+pub async fn send_with_retry<F>(
+    policy: &RetryPolicy,
+    provider: &str,
+    mut make_request: F,
+) -> Result<reqwest::Response>
+where
+    F: FnMut() -> reqwest::RequestBuilder,
+{
+    for attempt in 0..=policy.max_retries {
+        match make_request().send().await {
+            Ok(response) => {
+                if response.status().is_success() {
+                    return Ok(response);
+                }
+
+                let status = response.status();
+                let retry_after = policy.retry_after_delay(response.headers());
+                let body = response.text().await.unwrap_or_default();
+                if is_retryable_status(status) && attempt < policy.max_retries {
+                    let delay = retry_after.unwrap_or_else(|| policy.backoff_delay(attempt));
+                    tracing::debug!(
+                        "{} API returned {}; retrying in {}ms",
+                        provider,
+                        status,
+                        delay.as_millis()
+                    );
+                    tokio::time::sleep(delay).await;
+                    continue;
+                }
+
+                return Err(ApiError {
+                    provider: provider.to_string(),
+                    status,
+                    body,
+                }
+                .into());
+            }
+            Err(err) => {
+                if attempt < policy.max_retries {
+                    tokio::time::sleep(policy.backoff_delay(attempt)).await;
+                    continue;
+                }
+                return Err(err.into());
+            }
+        }
+    }
+
+    anyhow::bail!("{} request failed after retries", provider);
+}
+
+fn default_max_retries() -> usize {
+    2
+}
+
+fn default_base_delay_ms() -> u64 {
+    250
+}
+
+fn default_max_delay_ms() -> u64 {
+    30_000
+}
+
+fn default_backoff_multiplier() -> f64 {
+    2.0
+}
+
+fn default_true() -> bool {
+    true
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LLMRequest {
     pub system_prompt: String,
     pub user_prompt: String,
     pub temperature: Option<f32>,
     pub max_tokens: Option<usize>,
+    /// Context shared by every request of a review (such as the repository map), placed ahead
+    /// of `user_prompt`; providers that support prompt caching mark it cacheable, others simply
+    /// prepend it. Per-file context belongs in `user_prompt`, where it does not spoil the cache.
+    #[serde(default)]
+    pub cached_context: Option<String>,
+}
+
+impl LLMRequest {
+    /// User prompt with any cacheable context prepended, for providers without prompt caching.
+    pub fn flattened_user_prompt(&self) -> String {
+        match self.cached_context.as_deref() {
+            Some(context) if !context.trim().is_empty() => {
+                format!("{}\n\n{}", context.trim_end(), self.user_prompt)
+            }
+            _ => self.user_prompt.clone(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -53,7 +287,323 @@ pub trait LLMAdapter: Send + Sync {
     fn _model_name(&self) -> &str;
 }
 
+/// OpenAI-compatible hosted providers that only differ by endpoint and API key.
+pub struct ProviderPreset {
+    pub name: &'static str,
+    pub model_prefixes: &'static [&'static str],
+    pub routing_prefix: &'static str,
+    pub base_url: &'static str,
+    pub api_key_env: &'static str,
+}
+
+pub const PROVIDER_PRESETS: &[ProviderPreset] = &[
+    ProviderPreset {
+        name: "Mistral",
+        model_prefixes: &[
+            "mistral-",
+            "open-mistral-",
+            "open-mixtral-",
+            "codestral-",
+            "ministral-",
+            "pixtral-",
+        ],
+        routing_prefix: "mistral:",
+        base_url: "https://api.mistral.ai/v1",
+        api_key_env: "MISTRAL_API_KEY",
+    },
+    ProviderPreset {
+        name: "Groq",
+        model_prefixes: &[],
+        routing_prefix: "groq:",
+        base_url: "https://api.groq.com/openai/v1",
+        api_key_env: "GROQ_API_KEY",
+    },
+    ProviderPreset {
+        name: "DeepSeek",
+        model_prefixes: &["deepseek-"],
+        routing_prefix: "deepseek:",
+        base_url: "https://api.deepseek.com/v1",
+        api_key_env: "DEEPSEEK_API_KEY",
+    },
+];
+
+pub fn find_provider_preset(model_name: &str) -> Option<&'static ProviderPreset> {
+    PROVIDER_PRESETS.iter().find(|preset| {
+        model_name.starts_with(preset.routing_prefix)
+            || preset
+                .model_prefixes
+                .iter()
+                .any(|prefix| model_name.starts_with(prefix))
+    })
+}
+
+fn apply_provider_preset(config: &ModelConfig, preset: &ProviderPreset) -> Result<ModelConfig> {
+    let mut resolved = config.clone();
+    if let Some(stripped) = config.model_name.strip_prefix(preset.routing_prefix) {
+        resolved.model_name = stripped.to_string();
+    }
+
+    if resolved.api_key.is_none() {
+        resolved.api_key = std::env::var(preset.api_key_env).ok();
+    }
+
+    if resolved.base_url.is_none() {
+        resolved.base_url = Some(preset.base_url.to_string());
+        if resolved.api_key.is_none() {
+            anyhow::bail!(
+                "{} API key not found. Set {} environment variable or provide in config",
+                preset.name,
+                preset.api_key_env
+            );
+        }
+    }
+
+    // These providers only implement chat completions.
+    resolved.openai_use_responses = Some(false);
+    Ok(resolved)
+}
+
+const LOCAL_DEFAULT_BASE_URL: &str = "http://localhost:8000/v1";
+const LOCAL_TIMEOUT_SECS: u64 = 600;
+
+/// Resolves `local:<model>` to a keyless OpenAI-compatible server (vLLM, LM Studio, llama.cpp).
+fn apply_local_preset(config: &ModelConfig) -> ModelConfig {
+    let mut resolved = config.clone();
+    if let Some(stripped) = config.model_name.strip_prefix("local:") {
+        resolved.model_name = stripped.to_string();
+    }
+    if resolved.base_url.is_none() {
+        resolved.base_url = Some(
+            std::env::var("LOCAL_LLM_BASE_URL")
+                .unwrap_or_else(|_| LOCAL_DEFAULT_BASE_URL.to_string()),
+        );
+    }
+    if resolved.api_key.is_none() {
+        resolved.api_key = std::env::var("LOCAL_LLM_API_KEY").ok();
+    }
+    resolved.api_key_required = false;
+    resolved.openai_use_responses = Some(false);
+    // Local servers can take minutes on large diffs, especially on first load.
+    resolved.timeout_secs = Some(config.timeout_secs.unwrap_or(LOCAL_TIMEOUT_SECS));
+    resolved
+}
+
+/// Per-provider request and token budgets, enforced over a sliding one-minute window.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimit {
+    #[serde(default)]
+    pub requests_per_minute: Option<u32>,
+
+    #[serde(default)]
+    pub tokens_per_minute: Option<u32>,
+}
+
+impl RateLimit {
+    fn is_unlimited(&self) -> bool {
+        self.requests_per_minute.unwrap_or(0) == 0 && self.tokens_per_minute.unwrap_or(0) == 0
+    }
+}
+
+const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(60);
+
+/// Shared limiter for every adapter talking to one provider. Reservations count the
+/// estimated prompt tokens plus `max_tokens`, which is how providers meter TPM.
+pub struct RateLimiter {
+    limit: RateLimit,
+    window: Mutex<VecDeque<(Instant, u64)>>,
+}
+
+impl RateLimiter {
+    pub fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            window: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Returns a limiter shared by all callers in this process for `provider`.
+    pub fn shared(provider: &str, limit: &RateLimit) -> Arc<RateLimiter> {
+        static LIMITERS: Lazy<Mutex<HashMap<String, Arc<RateLimiter>>>> =
+            Lazy::new(|| Mutex::new(HashMap::new()));
+        let mut limiters = LIMITERS.lock().unwrap();
+        match limiters.get(provider) {
+            Some(limiter) if limiter.limit == *limit => limiter.clone(),
+            _ => {
+                let limiter = Arc::new(RateLimiter::new(limit.clone()));
+                limiters.insert(provider.to_string(), limiter.clone());
+                limiter
+            }
+        }
+    }
+
+    pub async fn acquire(&self, tokens: u64) {
+        loop {
+            let wait = self.try_reserve(Instant::now(), tokens);
+            match wait {
+                Ok(()) => return,
+                Err(delay) => {
+                    tracing::debug!("Rate limit reached; waiting {}ms", delay.as_millis());
+                    tokio::time::sleep(delay).await;
+                }
+            }
+        }
+    }
+
+    /// Reserves capacity at `now`, or returns how long to wait before the oldest entry expires.
+    fn try_reserve(&self, now: Instant, tokens: u64) -> std::result::Result<(), Duration> {
+        let mut window = self.window.lock().unwrap();
+        while window
+            .front()
+            .is_some_and(|(at, _)| now.duration_since(*at) >= RATE_LIMIT_WINDOW)
+        {
+            window.pop_front();
+        }
+
+        let requests_ok = match self.limit.requests_per_minute {
+            Some(rpm) if rpm > 0 => window.len() < rpm as usize,
+            _ => true,
+        };
+        let tokens_ok = match self.limit.tokens_per_minute {
+            // A single oversized request is let through once the window is empty.
+            Some(tpm) if tpm > 0 => {
+                let used: u64 = window.iter().map(|(_, t)| *t).sum();
+                window.is_empty() || used + tokens <= tpm as u64
+            }
+            _ => true,
+        };
+
+        if requests_ok && tokens_ok {
+            window.push_back((now, tokens));
+            return Ok(());
+        }
+
+        let oldest = window.front().map(|(at, _)| *at).unwrap_or(now);
+        Err((oldest + RATE_LIMIT_WINDOW).saturating_duration_since(now) + Duration::from_millis(10))
+    }
+}
+
+/// Wraps a provider adapter so every call waits on the provider's shared `RateLimiter`.
+pub struct RateLimitedAdapter {
+    inner: Box<dyn LLMAdapter>,
+    limiter: Arc<RateLimiter>,
+    max_tokens: usize,
+}
+
+#[async_trait]
+impl LLMAdapter for RateLimitedAdapter {
+    async fn complete(&self, request: LLMRequest) -> Result<LLMResponse> {
+        let tokenizer = crate::core::tokenizer::Tokenizer::for_model(self.inner._model_name());
+        let prompt_tokens = tokenizer.count_tokens(&request.system_prompt)
+            + tokenizer.count_tokens(&request.flattened_user_prompt());
+        let reserved = prompt_tokens + request.max_tokens.unwrap_or(self.max_tokens);
+        self.limiter.acquire(reserved as u64).await;
+        self.inner.complete(request).await
+    }
+
+    fn _model_name(&self) -> &str {
+        self.inner._model_name()
+    }
+}
+
+/// Requests and tokens this process has used with one model.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ModelUsage {
+    pub requests: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub total_tokens: u64,
+}
+
+static USAGE: Lazy<Mutex<BTreeMap<String, ModelUsage>>> = Lazy::new(|| Mutex::new(BTreeMap::new()));
+
+/// Usage of every adapter created by `create_adapter` so far, keyed by model name.
+pub fn usage_totals() -> BTreeMap<String, ModelUsage> {
+    USAGE.lock().unwrap().clone()
+}
+
+fn record_usage(model: &str, usage: Option<&Usage>) {
+    let mut totals = USAGE.lock().unwrap();
+    let entry = totals.entry(model.to_string()).or_default();
+    entry.requests += 1;
+    if let Some(usage) = usage {
+        entry.prompt_tokens += usage.prompt_tokens as u64;
+        entry.completion_tokens += usage.completion_tokens as u64;
+        entry.total_tokens += usage.total_tokens as u64;
+    }
+}
+
+/// Adds the usage of each completed request to `usage_totals`.
+struct MeteredAdapter {
+    inner: Box<dyn LLMAdapter>,
+}
+
+#[async_trait]
+impl LLMAdapter for MeteredAdapter {
+    async fn complete(&self, request: LLMRequest) -> Result<LLMResponse> {
+        let response = self.inner.complete(request).await?;
+        record_usage(self.inner._model_name(), response.usage.as_ref());
+        Ok(response)
+    }
+
+    fn _model_name(&self) -> &str {
+        self.inner._model_name()
+    }
+}
+
+/// Key used to look up `rate_limits`; mirrors the routing in `create_provider_adapter`.
+pub fn provider_key(config: &ModelConfig) -> String {
+    let name = config.model_name.as_str();
+    if name.starts_with("claude") {
+        "anthropic".to_string()
+    } else if name.starts_with("openrouter:") {
+        "openrouter".to_string()
+    } else if name.starts_with("ollama:")
+        || config
+            .base_url
+            .as_ref()
+            .is_some_and(|u| u.contains("11434"))
+    {
+        "ollama".to_string()
+    } else if name.starts_with("local:") {
+        "local".to_string()
+    } else if let Some(preset) = find_provider_preset(name) {
+        preset.name.to_lowercase()
+    } else {
+        "openai".to_string()
+    }
+}
+
 pub fn create_adapter(config: &ModelConfig) -> Result<Box<dyn LLMAdapter>> {
+    let adapter: Box<dyn LLMAdapter> = match config.llm_replay_dir.as_ref() {
+        Some(dir) => Box::new(crate::adapters::replay::ReplayAdapter::from_dir(
+            &config.model_name,
+            dir,
+        )?),
+        None => {
+            let adapter = create_provider_adapter(config)?;
+            let provider = provider_key(config);
+            match config.rate_limits.get(&provider) {
+                Some(limit) if !limit.is_unlimited() => Box::new(RateLimitedAdapter {
+                    inner: adapter,
+                    limiter: RateLimiter::shared(&provider, limit),
+                    max_tokens: config.max_tokens,
+                }),
+                _ => adapter,
+            }
+        }
+    };
+
+    let adapter: Box<dyn LLMAdapter> = match config.llm_log_dir.as_ref() {
+        Some(dir) => Box::new(crate::adapters::replay::RecordingAdapter::new(
+            adapter,
+            dir.clone(),
+        )?),
+        None => adapter,
+    };
+    Ok(Box::new(MeteredAdapter { inner: adapter }))
+}
+
+fn create_provider_adapter(config: &ModelConfig) -> Result<Box<dyn LLMAdapter>> {
     match config.model_name.as_str() {
         // Anthropic Claude models (all versions)
         name if name.starts_with("claude-") => Ok(Box::new(
@@ -70,6 +620,10 @@ pub fn create_adapter(config: &ModelConfig) -> Result<Box<dyn LLMAdapter>> {
         name if name.starts_with("o1-") => Ok(Box::new(crate::adapters::OpenAIAdapter::new(
             config.clone(),
         )?)),
+        // OpenRouter models (e.g. openrouter:anthropic/claude-3.5-sonnet)
+        name if name.starts_with("openrouter:") => Ok(Box::new(
+            crate::adapters::OpenRouterAdapter::new(config.clone())?,
+        )),
         // Ollama models
         name if name.starts_with("ollama:") => Ok(Box::new(crate::adapters::OllamaAdapter::new(
             config.clone(),
@@ -84,9 +638,151 @@ pub fn create_adapter(config: &ModelConfig) -> Result<Box<dyn LLMAdapter>> {
                 config.clone(),
             )?))
         }
+        // Self-hosted OpenAI-compatible servers
+        name if name.starts_with("local:") => Ok(Box::new(crate::adapters::OpenAIAdapter::new(
+            apply_local_preset(config),
+        )?)),
+        // Mistral, Groq, DeepSeek and other OpenAI-compatible presets
+        name if find_provider_preset(name).is_some() => {
+            let preset = find_provider_preset(name).unwrap();
+            Ok(Box::new(crate::adapters::OpenAIAdapter::new(
+                apply_provider_preset(config, preset)?,
+            )?))
+        }
         // Default to OpenAI for unknown models
         _ => Ok(Box::new(crate::adapters::OpenAIAdapter::new(
             config.clone(),
         )?)),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn provider_presets_match_prefixes() {
+        assert_eq!(
+            find_provider_preset("mistral-large-latest").map(|p| p.name),
+            Some("Mistral")
+        );
+        assert_eq!(
+            find_provider_preset("codestral-latest").map(|p| p.name),
+            Some("Mistral")
+        );
+        assert_eq!(
+            find_provider_preset("groq:llama-3.1-70b-versatile").map(|p| p.name),
+            Some("Groq")
+        );
+        assert_eq!(
+            find_provider_preset("deepseek-chat").map(|p| p.name),
+            Some("DeepSeek")
+        );
+        assert!(find_provider_preset("gpt-4o").is_none());
+    }
+
+    #[test]
+    fn apply_provider_preset_fills_endpoint_and_strips_routing_prefix() {
+        let config = ModelConfig {
+            model_name: "groq:llama-3.1-8b-instant".to_string(),
+            api_key: Some("test-key".to_string()),
+            ..ModelConfig::default()
+        };
+        let preset = find_provider_preset(&config.model_name).unwrap();
+        let resolved = apply_provider_preset(&config, preset).unwrap();
+
+        assert_eq!(resolved.model_name, "llama-3.1-8b-instant");
+        assert_eq!(
+            resolved.base_url.as_deref(),
+            Some("https://api.groq.com/openai/v1")
+        );
+        assert_eq!(resolved.openai_use_responses, Some(false));
+    }
+
+    #[test]
+    fn local_preset_disables_auth_and_responses_api() {
+        let config = ModelConfig {
+            model_name: "local:Qwen/Qwen2.5-Coder-32B-Instruct".to_string(),
+            base_url: Some("http://gpu-box:8000/v1".to_string()),
+            ..ModelConfig::default()
+        };
+        let resolved = apply_local_preset(&config);
+
+        assert_eq!(resolved.model_name, "Qwen/Qwen2.5-Coder-32B-Instruct");
+        assert_eq!(resolved.base_url.as_deref(), Some("http://gpu-box:8000/v1"));
+        assert!(!resolved.api_key_required);
+        assert_eq!(resolved.openai_use_responses, Some(false));
+        assert_eq!(resolved.timeout_secs, Some(LOCAL_TIMEOUT_SECS));
+        assert!(crate::adapters::OpenAIAdapter::new(resolved).is_ok());
+    }
+
+    #[test]
+    fn rate_limiter_enforces_request_and_token_budgets() {
+        let limiter = RateLimiter::new(RateLimit {
+            requests_per_minute: Some(2),
+            tokens_per_minute: Some(1000),
+        });
+        let start = Instant::now();
+        assert!(limiter.try_reserve(start, 400).is_ok());
+        // Token budget: 400 + 700 exceeds 1000.
+        let wait = limiter.try_reserve(start, 700).unwrap_err();
+        assert!(wait > Duration::from_secs(59));
+        assert!(limiter.try_reserve(start, 500).is_ok());
+        // Request budget: third request within the minute must wait.
+        assert!(limiter.try_reserve(start, 1).is_err());
+        assert!(limiter
+            .try_reserve(start + Duration::from_secs(61), 700)
+            .is_ok());
+    }
+
+    #[test]
+    fn provider_key_matches_adapter_routing() {
+        let key = |model_name: &str| {
+            provider_key(&ModelConfig {
+                model_name: model_name.to_string(),
+                ..ModelConfig::default()
+            })
+        };
+
+        assert_eq!(key("groq:llama-3.1-8b-instant"), "groq");
+        assert_eq!(key("claude-3-5-sonnet-20241022"), "anthropic");
+        assert_eq!(key("openrouter:anthropic/claude-3.5-sonnet"), "openrouter");
+        assert_eq!(key("ollama:codellama"), "ollama");
+        assert_eq!(key("gpt-4o"), "openai");
+    }
+
+    #[test]
+    fn retry_policy_backoff_grows_and_caps() {
+        let policy = RetryPolicy {
+            max_retries: 5,
+            base_delay_ms: 100,
+            max_delay_ms: 350,
+            backoff_multiplier: 2.0,
+            jitter: false,
+            respect_retry_after: true,
+        };
+
+        assert_eq!(policy.backoff_delay(0), Duration::from_millis(100));
+        assert_eq!(policy.backoff_delay(1), Duration::from_millis(200));
+        assert_eq!(policy.backoff_delay(2), Duration::from_millis(350));
+
+        let jittered = RetryPolicy {
+            jitter: true,
+            ..policy
+        };
+        for _ in 0..20 {
+            let delay = jittered.backoff_delay(1).as_millis();
+            assert!((100..=200).contains(&delay));
+        }
+    }
+
+    #[test]
+    fn parse_retry_after_accepts_seconds() {
+        assert_eq!(parse_retry_after("3"), Some(Duration::from_secs(3)));
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after("soon"), None);
+    }
+}
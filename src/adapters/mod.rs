@@ -1,8 +1,12 @@
 pub mod anthropic;
+pub mod embeddings;
 pub mod llm;
 pub mod ollama;
 pub mod openai;
+pub mod openrouter;
+pub mod replay;
 
 pub use anthropic::AnthropicAdapter;
 pub use ollama::OllamaAdapter;
 pub use openai::OpenAIAdapter;
+pub use openrouter::OpenRouterAdapter;
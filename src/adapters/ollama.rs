@@ -1,136 +1,218 @@
-use crate::adapters::llm::{LLMAdapter, LLMRequest, LLMResponse, ModelConfig, Usage};
+use crate::adapters::llm::{
+    send_with_retry, LLMAdapter, LLMRequest, LLMResponse, ModelConfig, Usage,
+};
 use anyhow::{Context, Result};
 use async_trait::async_trait;
-use reqwest::{Client, StatusCode};
+use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use std::time::Duration;
-use tokio::time::sleep;
+use tokio::sync::OnceCell;
+
+pub const OLLAMA_DEFAULT_BASE_URL: &str = "http://localhost:11434";
 
 pub struct OllamaAdapter {
     client: Client,
     config: ModelConfig,
     base_url: String,
+    model_ready: OnceCell<()>,
 }
 
 #[derive(Serialize)]
-struct OllamaRequest {
+struct OllamaChatRequest {
     model: String,
-    prompt: String,
-    system: String,
+    messages: Vec<Message>,
+    stream: bool,
+    options: OllamaOptions,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Message {
+    role: String,
+    content: String,
+}
+
+#[derive(Serialize)]
+struct OllamaOptions {
     temperature: f32,
     num_predict: usize,
-    stream: bool,
 }
 
 #[derive(Deserialize)]
-struct OllamaResponse {
-    response: String,
+struct OllamaChatResponse {
     model: String,
+    #[serde(default)]
+    message: Option<Message>,
+    #[serde(default)]
     done: bool,
-    _context: Option<Vec<i32>>,
-    _total_duration: Option<u64>,
     prompt_eval_count: Option<usize>,
     eval_count: Option<usize>,
 }
 
+#[derive(Serialize)]
+struct OllamaPullRequest {
+    model: String,
+    stream: bool,
+}
+
+#[derive(Deserialize)]
+struct OllamaTagsResponse {
+    #[serde(default)]
+    models: Vec<OllamaModel>,
+}
+
+/// A model available on the local Ollama server, as reported by `/api/tags`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct OllamaModel {
+    pub name: String,
+    #[serde(default)]
+    pub size: u64,
+    #[serde(default)]
+    pub modified_at: Option<String>,
+}
+
 impl OllamaAdapter {
     pub fn new(config: ModelConfig) -> Result<Self> {
         let base_url = config
             .base_url
             .clone()
-            .unwrap_or_else(|| "http://localhost:11434".to_string());
+            .unwrap_or_else(|| OLLAMA_DEFAULT_BASE_URL.to_string())
+            .trim_end_matches('/')
+            .to_string();
 
         let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(300))
+            .timeout(std::time::Duration::from_secs(
+                config.timeout_secs.unwrap_or(300),
+            ))
             .build()?;
 
         Ok(Self {
             client,
             config,
             base_url,
+            model_ready: OnceCell::new(),
         })
     }
 
-    async fn send_with_retry<F>(&self, mut make_request: F) -> Result<reqwest::Response>
-    where
-        F: FnMut() -> reqwest::RequestBuilder,
-    {
-        const MAX_RETRIES: usize = 2;
-        const BASE_DELAY_MS: u64 = 250;
-
-        for attempt in 0..=MAX_RETRIES {
-            match make_request().send().await {
-                Ok(response) => {
-                    if response.status().is_success() {
-                        return Ok(response);
-                    }
+    fn model_name(&self) -> &str {
+        self.config
+            .model_name
+            .strip_prefix("ollama:")
+            .unwrap_or(&self.config.model_name)
+    }
 
-                    let status = response.status();
-                    let body = response.text().await.unwrap_or_default();
-                    if is_retryable_status(status) && attempt < MAX_RETRIES {
-                        sleep(Duration::from_millis(BASE_DELAY_MS * (attempt as u64 + 1))).await;
-                        continue;
-                    }
+    pub async fn list_models(&self) -> Result<Vec<OllamaModel>> {
+        let url = format!("{}/api/tags", self.base_url);
+        let response = send_with_retry(&self.config.retry_policy, "Ollama", || {
+            self.client.get(&url)
+        })
+        .await
+        .with_context(|| format!("Failed to reach Ollama at {}", self.base_url))?;
 
-                    anyhow::bail!("Ollama API error ({}): {}", status, body);
-                }
-                Err(err) => {
-                    if attempt < MAX_RETRIES {
-                        sleep(Duration::from_millis(BASE_DELAY_MS * (attempt as u64 + 1))).await;
-                        continue;
+        let tags: OllamaTagsResponse = response
+            .json()
+            .await
+            .context("Failed to parse Ollama model list")?;
+        Ok(tags.models)
+    }
+
+    async fn pull_model(&self, model: &str) -> Result<()> {
+        tracing::info!("Pulling Ollama model {} (this may take a while)", model);
+        let url = format!("{}/api/pull", self.base_url);
+        let pull_request = OllamaPullRequest {
+            model: model.to_string(),
+            stream: false,
+        };
+        send_with_retry(&self.config.retry_policy, "Ollama", || {
+            self.client
+                .post(&url)
+                .timeout(std::time::Duration::from_secs(3600))
+                .json(&pull_request)
+        })
+        .await
+        .with_context(|| format!("Failed to pull Ollama model {}", model))?;
+        Ok(())
+    }
+
+    /// Checks once per adapter that the model is pulled, pulling it if `ollama_auto_pull` is set.
+    async fn ensure_model_available(&self) -> Result<()> {
+        self.model_ready
+            .get_or_try_init(|| async {
+                let model = self.model_name();
+                let models = match self.list_models().await {
+                    Ok(models) => models,
+                    Err(err) => {
+                        // Ollama-compatible servers may not expose /api/tags; let the chat call decide.
+                        tracing::debug!("Skipping Ollama model check: {:#}", err);
+                        return Ok(());
                     }
-                    return Err(err.into());
+                };
+                if models.iter().any(|m| model_matches(&m.name, model)) {
+                    return Ok(());
                 }
-            }
-        }
+                if self.config.ollama_auto_pull {
+                    return self.pull_model(model).await;
+                }
+                anyhow::bail!(
+                    "Ollama model '{}' is not available at {}. Run `ollama pull {}`, set `ollama_auto_pull: true`, or run `diffscope models` to see installed models",
+                    model,
+                    self.base_url,
+                    model
+                )
+            })
+            .await
+            .map(|_| ())
+    }
+}
 
-        anyhow::bail!("Ollama request failed after retries");
+/// Ollama reports untagged models with an implicit `:latest` tag.
+fn model_matches(installed: &str, requested: &str) -> bool {
+    fn with_tag(name: &str) -> String {
+        if name.contains(':') {
+            name.to_string()
+        } else {
+            format!("{}:latest", name)
+        }
     }
+    with_tag(installed) == with_tag(requested)
 }
 
 #[async_trait]
 impl LLMAdapter for OllamaAdapter {
     async fn complete(&self, request: LLMRequest) -> Result<LLMResponse> {
-        let model_name = self
-            .config
-            .model_name
-            .strip_prefix("ollama:")
-            .unwrap_or(&self.config.model_name);
-
-        let ollama_request = OllamaRequest {
-            model: model_name.to_string(),
-            prompt: request.user_prompt,
-            system: request.system_prompt,
-            temperature: request.temperature.unwrap_or(self.config.temperature),
-            num_predict: request.max_tokens.unwrap_or(self.config.max_tokens),
+        self.ensure_model_available().await?;
+
+        let user_prompt = request.flattened_user_prompt();
+        let ollama_request = OllamaChatRequest {
+            model: self.model_name().to_string(),
+            messages: vec![
+                Message {
+                    role: "system".to_string(),
+                    content: request.system_prompt,
+                },
+                Message {
+                    role: "user".to_string(),
+                    content: user_prompt,
+                },
+            ],
             stream: false,
+            options: OllamaOptions {
+                temperature: request.temperature.unwrap_or(self.config.temperature),
+                num_predict: request.max_tokens.unwrap_or(self.config.max_tokens),
+            },
         };
 
-        let url = format!("{}/api/generate", self.base_url);
-        let response = self
-            .send_with_retry(|| self.client.post(&url).json(&ollama_request))
-            .await
-            .context("Failed to send request to Ollama")?;
+        let url = format!("{}/api/chat", self.base_url);
+        let response = send_with_retry(&self.config.retry_policy, "Ollama", || {
+            self.client.post(&url).json(&ollama_request)
+        })
+        .await
+        .context("Failed to send request to Ollama")?;
 
-        let ollama_response: OllamaResponse = response
+        let ollama_response: OllamaChatResponse = response
             .json()
             .await
             .context("Failed to parse Ollama response")?;
 
-        Ok(LLMResponse {
-            content: ollama_response.response,
-            model: ollama_response.model,
-            usage: if ollama_response.done {
-                Some(Usage {
-                    prompt_tokens: ollama_response.prompt_eval_count.unwrap_or(0),
-                    completion_tokens: ollama_response.eval_count.unwrap_or(0),
-                    total_tokens: ollama_response.prompt_eval_count.unwrap_or(0)
-                        + ollama_response.eval_count.unwrap_or(0),
-                })
-            } else {
-                None
-            },
-        })
+        Ok(parse_chat_response(ollama_response))
     }
 
     fn _model_name(&self) -> &str {
@@ -138,6 +220,42 @@ impl LLMAdapter for OllamaAdapter {
     }
 }
 
-fn is_retryable_status(status: StatusCode) -> bool {
-    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+fn parse_chat_response(response: OllamaChatResponse) -> LLMResponse {
+    let prompt_tokens = response.prompt_eval_count.unwrap_or(0);
+    let completion_tokens = response.eval_count.unwrap_or(0);
+    LLMResponse {
+        content: response.message.map(|m| m.content).unwrap_or_default(),
+        model: response.model,
+        usage: if response.done {
+            Some(Usage {
+                prompt_tokens,
+                completion_tokens,
+                total_tokens: prompt_tokens + completion_tokens,
+            })
+        } else {
+            None
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn model_matching_applies_implicit_latest_tag() {
+        assert!(model_matches("codellama:latest", "codellama"));
+        assert!(model_matches("llama3.1:8b", "llama3.1:8b"));
+        assert!(!model_matches("llama3.1:70b", "llama3.1:8b"));
+        assert!(!model_matches("llama3.1:8b", "llama3.1"));
+    }
+
+    #[test]
+    fn parses_chat_response() {
+        let body = r#"{"model": "codellama", "message": {"role": "assistant", "content": "Line 3: Bug - off by one"}, "done": true, "prompt_eval_count": 120, "eval_count": 30}"#;
+        let response: OllamaChatResponse = serde_json::from_str(body).unwrap();
+        let parsed = parse_chat_response(response);
+        assert_eq!(parsed.content, "Line 3: Bug - off by one");
+        assert_eq!(parsed.usage.unwrap().total_tokens, 150);
+    }
 }
@@ -1,15 +1,15 @@
-use crate::adapters::llm::{LLMAdapter, LLMRequest, LLMResponse, ModelConfig, Usage};
+use crate::adapters::llm::{
+    send_with_retry, LLMAdapter, LLMRequest, LLMResponse, ModelConfig, Usage,
+};
 use anyhow::{Context, Result};
 use async_trait::async_trait;
-use reqwest::{Client, StatusCode};
+use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use std::time::Duration;
-use tokio::time::sleep;
 
 pub struct OpenAIAdapter {
     client: Client,
     config: ModelConfig,
-    api_key: String,
+    api_key: Option<String>,
     base_url: String,
 }
 
@@ -87,9 +87,13 @@ struct OpenAIResponsesUsage {
 
 impl OpenAIAdapter {
     pub fn new(config: ModelConfig) -> Result<Self> {
-        let api_key = config.api_key.clone()
-            .or_else(|| std::env::var("OPENAI_API_KEY").ok())
-            .context("OpenAI API key not found. Set OPENAI_API_KEY environment variable or provide in config")?;
+        let api_key = config
+            .api_key
+            .clone()
+            .or_else(|| std::env::var("OPENAI_API_KEY").ok());
+        if api_key.is_none() && config.api_key_required {
+            anyhow::bail!("OpenAI API key not found. Set OPENAI_API_KEY environment variable or provide in config");
+        }
 
         let base_url = config
             .base_url
@@ -97,7 +101,9 @@ impl OpenAIAdapter {
             .unwrap_or_else(|| "https://api.openai.com/v1".to_string());
 
         let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(60))
+            .timeout(std::time::Duration::from_secs(
+                config.timeout_secs.unwrap_or(60),
+            ))
             .build()?;
 
         Ok(Self {
@@ -107,42 +113,6 @@ impl OpenAIAdapter {
             base_url,
         })
     }
-
-    async fn send_with_retry<F>(&self, mut make_request: F) -> Result<reqwest::Response>
-    where
-        F: FnMut() -> reqwest::RequestBuilder,
-    {
-        const MAX_RETRIES: usize = 2;
-        const BASE_DELAY_MS: u64 = 250;
-
-        for attempt in 0..=MAX_RETRIES {
-            match make_request().send().await {
-                Ok(response) => {
-                    if response.status().is_success() {
-                        return Ok(response);
-                    }
-
-                    let status = response.status();
-                    let body = response.text().await.unwrap_or_default();
-                    if is_retryable_status(status) && attempt < MAX_RETRIES {
-                        sleep(Duration::from_millis(BASE_DELAY_MS * (attempt as u64 + 1))).await;
-                        continue;
-                    }
-
-                    anyhow::bail!("OpenAI API error ({}): {}", status, body);
-                }
-                Err(err) => {
-                    if attempt < MAX_RETRIES {
-                        sleep(Duration::from_millis(BASE_DELAY_MS * (attempt as u64 + 1))).await;
-                        continue;
-                    }
-                    return Err(err.into());
-                }
-            }
-        }
-
-        anyhow::bail!("OpenAI request failed after retries");
-    }
 }
 
 #[async_trait]
@@ -160,10 +130,6 @@ impl LLMAdapter for OpenAIAdapter {
     }
 }
 
-fn is_retryable_status(status: StatusCode) -> bool {
-    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
-}
-
 fn should_use_responses_api(config: &ModelConfig) -> bool {
     if let Some(flag) = config.openai_use_responses {
         return flag;
@@ -179,7 +145,15 @@ fn should_use_responses_api(config: &ModelConfig) -> bool {
 }
 
 impl OpenAIAdapter {
+    fn authorized(&self, builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match self.api_key.as_ref() {
+            Some(key) => builder.header("Authorization", format!("Bearer {}", key)),
+            None => builder,
+        }
+    }
+
     async fn complete_chat_completions(&self, request: LLMRequest) -> Result<LLMResponse> {
+        let user_prompt = request.flattened_user_prompt();
         let messages = vec![
             Message {
                 role: "system".to_string(),
@@ -187,7 +161,7 @@ impl OpenAIAdapter {
             },
             Message {
                 role: "user".to_string(),
-                content: request.user_prompt,
+                content: user_prompt,
             },
         ];
 
@@ -199,16 +173,13 @@ impl OpenAIAdapter {
         };
 
         let url = format!("{}/chat/completions", self.base_url);
-        let response = self
-            .send_with_retry(|| {
-                self.client
-                    .post(&url)
-                    .header("Authorization", format!("Bearer {}", self.api_key))
-                    .header("Content-Type", "application/json")
-                    .json(&openai_request)
-            })
-            .await
-            .context("Failed to send request to OpenAI")?;
+        let response = send_with_retry(&self.config.retry_policy, "OpenAI", || {
+            self.authorized(self.client.post(&url))
+                .header("Content-Type", "application/json")
+                .json(&openai_request)
+        })
+        .await
+        .context("Failed to send request to OpenAI")?;
 
         let openai_response: OpenAIResponse = response
             .json()
@@ -235,23 +206,20 @@ impl OpenAIAdapter {
     async fn complete_responses(&self, request: LLMRequest) -> Result<LLMResponse> {
         let openai_request = OpenAIResponsesRequest {
             model: self.config.model_name.clone(),
-            input: request.user_prompt,
+            input: request.flattened_user_prompt(),
             instructions: request.system_prompt,
             temperature: request.temperature.unwrap_or(self.config.temperature),
             max_output_tokens: request.max_tokens.unwrap_or(self.config.max_tokens),
         };
 
         let url = format!("{}/responses", self.base_url);
-        let response = self
-            .send_with_retry(|| {
-                self.client
-                    .post(&url)
-                    .header("Authorization", format!("Bearer {}", self.api_key))
-                    .header("Content-Type", "application/json")
-                    .json(&openai_request)
-            })
-            .await
-            .context("Failed to send request to OpenAI")?;
+        let response = send_with_retry(&self.config.retry_policy, "OpenAI", || {
+            self.authorized(self.client.post(&url))
+                .header("Content-Type", "application/json")
+                .json(&openai_request)
+        })
+        .await
+        .context("Failed to send request to OpenAI")?;
 
         let openai_response: OpenAIResponsesResponse = response
             .json()
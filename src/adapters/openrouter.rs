@@ -0,0 +1,210 @@
+use crate::adapters::llm::{
+    send_with_retry, LLMAdapter, LLMRequest, LLMResponse, ModelConfig, Usage,
+};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+const OPENROUTER_BASE_URL: &str = "https://openrouter.ai/api/v1";
+const OPENROUTER_REFERER: &str = "https://github.com/Haasonsaas/diffscope";
+const OPENROUTER_TITLE: &str = "diffscope";
+
+pub struct OpenRouterAdapter {
+    client: Client,
+    config: ModelConfig,
+    api_key: String,
+    base_url: String,
+}
+
+#[derive(Serialize)]
+struct OpenRouterRequest {
+    model: String,
+    messages: Vec<Message>,
+    temperature: f32,
+    max_tokens: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Message {
+    role: String,
+    content: String,
+}
+
+#[derive(Deserialize)]
+struct OpenRouterResponse {
+    #[serde(default)]
+    choices: Vec<Choice>,
+    #[serde(default)]
+    model: Option<String>,
+    #[serde(default)]
+    usage: Option<OpenRouterUsage>,
+    #[serde(default)]
+    error: Option<OpenRouterError>,
+}
+
+#[derive(Deserialize)]
+struct Choice {
+    message: ChoiceMessage,
+}
+
+#[derive(Deserialize)]
+struct ChoiceMessage {
+    #[serde(default)]
+    content: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct OpenRouterUsage {
+    prompt_tokens: usize,
+    completion_tokens: usize,
+    total_tokens: usize,
+}
+
+#[derive(Deserialize)]
+struct OpenRouterError {
+    message: String,
+    #[serde(default)]
+    code: Option<serde_json::Value>,
+}
+
+impl OpenRouterAdapter {
+    pub fn new(config: ModelConfig) -> Result<Self> {
+        let api_key = config.api_key.clone()
+            .or_else(|| std::env::var("OPENROUTER_API_KEY").ok())
+            .context("OpenRouter API key not found. Set OPENROUTER_API_KEY environment variable or provide in config")?;
+
+        let base_url = config
+            .base_url
+            .clone()
+            .unwrap_or_else(|| OPENROUTER_BASE_URL.to_string());
+
+        let client = Client::builder()
+            .timeout(std::time::Duration::from_secs(
+                config.timeout_secs.unwrap_or(120),
+            ))
+            .build()?;
+
+        Ok(Self {
+            client,
+            config,
+            api_key,
+            base_url,
+        })
+    }
+}
+
+#[async_trait]
+impl LLMAdapter for OpenRouterAdapter {
+    async fn complete(&self, request: LLMRequest) -> Result<LLMResponse> {
+        let model_name = openrouter_model_name(&self.config.model_name);
+        let user_prompt = request.flattened_user_prompt();
+
+        let openrouter_request = OpenRouterRequest {
+            model: model_name.to_string(),
+            messages: vec![
+                Message {
+                    role: "system".to_string(),
+                    content: request.system_prompt,
+                },
+                Message {
+                    role: "user".to_string(),
+                    content: user_prompt,
+                },
+            ],
+            temperature: request.temperature.unwrap_or(self.config.temperature),
+            max_tokens: request.max_tokens.unwrap_or(self.config.max_tokens),
+        };
+
+        let url = format!("{}/chat/completions", self.base_url);
+        let response = send_with_retry(&self.config.retry_policy, "OpenRouter", || {
+            self.client
+                .post(&url)
+                .header("Authorization", format!("Bearer {}", self.api_key))
+                .header("HTTP-Referer", OPENROUTER_REFERER)
+                .header("X-Title", OPENROUTER_TITLE)
+                .header("Content-Type", "application/json")
+                .json(&openrouter_request)
+        })
+        .await
+        .context("Failed to send request to OpenRouter")?;
+
+        let openrouter_response: OpenRouterResponse = response
+            .json()
+            .await
+            .context("Failed to parse OpenRouter response")?;
+
+        parse_openrouter_response(openrouter_response, model_name)
+    }
+
+    fn _model_name(&self) -> &str {
+        &self.config.model_name
+    }
+}
+
+fn openrouter_model_name(model_name: &str) -> &str {
+    model_name.strip_prefix("openrouter:").unwrap_or(model_name)
+}
+
+fn parse_openrouter_response(
+    response: OpenRouterResponse,
+    requested_model: &str,
+) -> Result<LLMResponse> {
+    // OpenRouter can report upstream provider failures inside a 200 response body.
+    if let Some(error) = response.error {
+        match error.code {
+            Some(code) => anyhow::bail!("OpenRouter provider error ({}): {}", code, error.message),
+            None => anyhow::bail!("OpenRouter provider error: {}", error.message),
+        }
+    }
+
+    let content = response
+        .choices
+        .first()
+        .and_then(|c| c.message.content.clone())
+        .unwrap_or_default();
+
+    Ok(LLMResponse {
+        content,
+        model: response
+            .model
+            .unwrap_or_else(|| requested_model.to_string()),
+        usage: response.usage.map(|usage| Usage {
+            prompt_tokens: usage.prompt_tokens,
+            completion_tokens: usage.completion_tokens,
+            total_tokens: usage.total_tokens,
+        }),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_openrouter_prefix() {
+        assert_eq!(
+            openrouter_model_name("openrouter:anthropic/claude-3.5-sonnet"),
+            "anthropic/claude-3.5-sonnet"
+        );
+        assert_eq!(
+            openrouter_model_name("meta-llama/llama-3-70b"),
+            "meta-llama/llama-3-70b"
+        );
+    }
+
+    #[test]
+    fn parse_response_surfaces_provider_errors() {
+        let body = r#"{"error": {"message": "upstream timeout", "code": 502}}"#;
+        let response: OpenRouterResponse = serde_json::from_str(body).unwrap();
+        let err = parse_openrouter_response(response, "x").unwrap_err();
+        assert!(err.to_string().contains("upstream timeout"));
+
+        let body = r#"{"model": "openai/gpt-4o", "choices": [{"message": {"role": "assistant", "content": "Line 1: ok"}}]}"#;
+        let response: OpenRouterResponse = serde_json::from_str(body).unwrap();
+        let parsed = parse_openrouter_response(response, "x").unwrap();
+        assert_eq!(parsed.content, "Line 1: ok");
+        assert_eq!(parsed.model, "openai/gpt-4o");
+        assert!(parsed.usage.is_none());
+    }
+}
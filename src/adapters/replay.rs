@@ -0,0 +1,201 @@
+use crate::adapters::llm::{LLMAdapter, LLMRequest, LLMResponse};
+use crate::core::cache::stable_hash;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// One recorded LLM exchange, stored as a standalone JSON file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LLMExchange {
+    pub request_hash: String,
+    pub recorded_at: String,
+    pub model: String,
+    pub request: LLMRequest,
+    #[serde(default)]
+    pub response: Option<LLMResponse>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// Wraps another adapter and writes every request/response pair to `log_dir`.
+pub struct RecordingAdapter {
+    inner: Box<dyn LLMAdapter>,
+    log_dir: PathBuf,
+    run_id: String,
+    sequence: AtomicUsize,
+}
+
+impl RecordingAdapter {
+    pub fn new(inner: Box<dyn LLMAdapter>, log_dir: PathBuf) -> Result<Self> {
+        std::fs::create_dir_all(&log_dir)
+            .with_context(|| format!("Failed to create LLM log directory {}", log_dir.display()))?;
+        Ok(Self {
+            inner,
+            log_dir,
+            run_id: chrono::Local::now().format("%Y%m%dT%H%M%S").to_string(),
+            sequence: AtomicUsize::new(0),
+        })
+    }
+
+    fn write_exchange(&self, exchange: &LLMExchange) -> Result<()> {
+        let sequence = self.sequence.fetch_add(1, Ordering::SeqCst);
+        let file_name = format!(
+            "{}-{:04}-{}.json",
+            self.run_id, sequence, exchange.request_hash
+        );
+        let content = serde_json::to_string_pretty(exchange)?;
+        std::fs::write(self.log_dir.join(file_name), content)?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl LLMAdapter for RecordingAdapter {
+    async fn complete(&self, request: LLMRequest) -> Result<LLMResponse> {
+        let result = self.inner.complete(request.clone()).await;
+        let exchange = LLMExchange {
+            request_hash: request_hash(&request),
+            recorded_at: chrono::Local::now().to_rfc3339(),
+            model: self.inner._model_name().to_string(),
+            request,
+            response: result.as_ref().ok().cloned(),
+            error: result.as_ref().err().map(|err| format!("{:#}", err)),
+        };
+        if let Err(err) = self.write_exchange(&exchange) {
+            tracing::warn!("Failed to record LLM exchange: {}", err);
+        }
+        result
+    }
+
+    fn _model_name(&self) -> &str {
+        self.inner._model_name()
+    }
+}
+
+/// Serves responses previously written by `RecordingAdapter`, matched by request content.
+pub struct ReplayAdapter {
+    model_name: String,
+    recordings: Mutex<HashMap<String, VecDeque<LLMExchange>>>,
+}
+
+impl ReplayAdapter {
+    pub fn from_dir(model_name: &str, replay_dir: &Path) -> Result<Self> {
+        let mut paths = Vec::new();
+        for entry in std::fs::read_dir(replay_dir).with_context(|| {
+            format!(
+                "Failed to read LLM replay directory {}",
+                replay_dir.display()
+            )
+        })? {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) == Some("json") {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+
+        let mut recordings: HashMap<String, VecDeque<LLMExchange>> = HashMap::new();
+        for path in paths {
+            let content = std::fs::read_to_string(&path)?;
+            let exchange: LLMExchange = serde_json::from_str(&content)
+                .with_context(|| format!("Invalid LLM recording {}", path.display()))?;
+            // Re-hash so hand-edited request bodies still match.
+            let hash = request_hash(&exchange.request);
+            recordings.entry(hash).or_default().push_back(exchange);
+        }
+
+        Ok(Self {
+            model_name: model_name.to_string(),
+            recordings: Mutex::new(recordings),
+        })
+    }
+}
+
+#[async_trait]
+impl LLMAdapter for ReplayAdapter {
+    async fn complete(&self, request: LLMRequest) -> Result<LLMResponse> {
+        let hash = request_hash(&request);
+        let exchange = {
+            let mut recordings = self.recordings.lock().unwrap();
+            let queue = recordings
+                .get_mut(&hash)
+                .ok_or_else(|| anyhow::anyhow!("No recorded LLM response for request {}", hash))?;
+            // Identical requests are served in recorded order; the last one repeats.
+            if queue.len() > 1 {
+                queue.pop_front().unwrap()
+            } else {
+                queue.front().cloned().unwrap()
+            }
+        };
+
+        match (exchange.response, exchange.error) {
+            (Some(response), _) => Ok(response),
+            (None, Some(error)) => anyhow::bail!("Recorded LLM error: {}", error),
+            (None, None) => anyhow::bail!("Recorded LLM exchange {} has no response", hash),
+        }
+    }
+
+    fn _model_name(&self) -> &str {
+        &self.model_name
+    }
+}
+
+/// Stable hash of the prompt content, independent of Rust's randomized hasher.
+pub fn request_hash(request: &LLMRequest) -> String {
+    stable_hash(&[
+        &request.system_prompt,
+        request.cached_context.as_deref().unwrap_or(""),
+        &request.user_prompt,
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct EchoAdapter;
+
+    #[async_trait]
+    impl LLMAdapter for EchoAdapter {
+        async fn complete(&self, request: LLMRequest) -> Result<LLMResponse> {
+            Ok(LLMResponse {
+                content: format!("echo: {}", request.user_prompt),
+                model: "echo".to_string(),
+                usage: None,
+            })
+        }
+
+        fn _model_name(&self) -> &str {
+            "echo"
+        }
+    }
+
+    fn request(user_prompt: &str) -> LLMRequest {
+        LLMRequest {
+            system_prompt: "system".to_string(),
+            user_prompt: user_prompt.to_string(),
+            temperature: None,
+            max_tokens: None,
+            cached_context: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn recorded_exchanges_replay_by_request_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let recorder =
+            RecordingAdapter::new(Box::new(EchoAdapter), dir.path().to_path_buf()).unwrap();
+        recorder.complete(request("first")).await.unwrap();
+        recorder.complete(request("second")).await.unwrap();
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 2);
+
+        let replay = ReplayAdapter::from_dir("echo", dir.path()).unwrap();
+        let response = replay.complete(request("second")).await.unwrap();
+        assert_eq!(response.content, "echo: second");
+        assert!(replay.complete(request("unknown")).await.is_err());
+    }
+}
@@ -1,4 +1,12 @@
-use anyhow::Result;
+use crate::adapters::llm::{provider_key, ModelConfig, RateLimit, RetryPolicy};
+use crate::core::comment::Severity;
+use crate::core::cost::ModelPrice;
+use crate::core::policy::ReviewPolicy;
+use crate::core::tokenizer::{self, PromptLimits};
+use crate::providers::bitbucket::BitbucketConfig;
+use crate::providers::github::GitHubConfig;
+use crate::providers::gitlab::GitLabConfig;
+use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
@@ -20,9 +28,30 @@ pub struct Config {
     #[serde(default = "default_max_diff_chars")]
     pub max_diff_chars: usize,
 
+    #[serde(default)]
+    pub max_context_tokens: usize,
+
+    #[serde(default)]
+    pub max_diff_tokens: usize,
+
+    #[serde(default)]
+    pub context_window: Option<usize>,
+
+    #[serde(default = "default_true")]
+    pub function_scope_context: bool,
+
     #[serde(default = "default_min_confidence")]
     pub min_confidence: f32,
 
+    /// Adjust each finding's confidence to how often its category was accepted at that
+    /// confidence in the feedback store, before `min_confidence` filtering.
+    #[serde(default = "default_true")]
+    pub confidence_calibration: bool,
+
+    /// Confidence multipliers by category (e.g. `style: 0.5`), applied after calibration.
+    #[serde(default)]
+    pub category_weights: HashMap<String, f32>,
+
     #[serde(default)]
     pub review_profile: Option<String>,
 
@@ -35,6 +64,15 @@ pub struct Config {
     #[serde(default)]
     pub smart_review_diagram: bool,
 
+    /// One-line AI description of each file in the smart-review change walkthrough.
+    #[serde(default = "default_true")]
+    pub smart_review_walkthrough: bool,
+
+    /// Optional smart-review sections, in output order: `summary`, `breaking_changes`,
+    /// `walkthrough`, `impact`, `coverage`, `risk`, `breakdown`, and `actions`.
+    #[serde(default = "default_smart_review_sections")]
+    pub smart_review_sections: Vec<String>,
+
     #[serde(default = "default_true")]
     pub symbol_index: bool,
 
@@ -53,58 +91,744 @@ pub struct Config {
     #[serde(default)]
     pub symbol_index_lsp_command: Option<String>,
 
+    #[serde(default = "default_max_reference_sites")]
+    pub max_reference_sites: usize,
+
     #[serde(default = "default_symbol_index_lsp_languages")]
     pub symbol_index_lsp_languages: HashMap<String, String>,
 
-    #[serde(default = "default_feedback_path")]
-    pub feedback_path: PathBuf,
+    /// Language server command per file extension, overriding `symbol_index_lsp_command`.
+    #[serde(default)]
+    pub symbol_index_lsp_commands: HashMap<String, String>,
+
+    /// Language servers (and parsing jobs) run at once while indexing.
+    #[serde(default = "default_symbol_index_lsp_workers")]
+    pub symbol_index_lsp_workers: usize,
+
+    /// Seconds a language server may take to answer before it is stopped and its files
+    /// are parsed instead.
+    #[serde(default = "default_symbol_index_lsp_timeout_secs")]
+    pub symbol_index_lsp_timeout_secs: u64,
+
+    #[serde(default = "default_feedback_path")]
+    pub feedback_path: PathBuf,
+
+    /// SQLite database that keeps every feedback decision instead of `feedback_path`
+    /// (requires the `sqlite-feedback` feature).
+    #[serde(default)]
+    pub feedback_db: Option<PathBuf>,
+
+    /// Word similarity (0-1) at which a new comment counts as a rejected one reworded, for
+    /// rejections in the same file and category. 0 suppresses exact repeats only.
+    #[serde(default = "default_feedback_similarity")]
+    pub feedback_similarity: f32,
+
+    /// Team feedback store shared by everyone's reviews: an HTTP(S) URL or `s3://bucket/key`.
+    /// Local decisions are pushed to it and its decisions are merged into local ones.
+    #[serde(default)]
+    pub feedback_remote: Option<String>,
+
+    /// Bearer token for an HTTP `feedback_remote` (or `DIFFSCOPE_FEEDBACK_TOKEN`).
+    #[serde(default)]
+    pub feedback_remote_token: Option<String>,
+
+    #[serde(default = "default_true")]
+    pub review_cache: bool,
+
+    #[serde(default)]
+    pub review_cache_dir: Option<PathBuf>,
+
+    /// Save each `review` run to `.diffscope/run-<id>/` so `--resume` can continue it.
+    #[serde(default)]
+    pub review_checkpoints: bool,
+
+    /// Jinja (minijinja) template for the review request, used instead of the built-in prompt.
+    #[serde(default)]
+    pub prompt_template: Option<PathBuf>,
+
+    /// The ticket or issue the change implements, available to `prompt_template`.
+    #[serde(default)]
+    pub ticket: Option<String>,
+
+    /// Jinja (minijinja) template used instead of the built-in markdown and smart-review layout.
+    #[serde(default)]
+    pub report_template: Option<PathBuf>,
+
+    /// Write `json` output as `{comments, skipped}` so files the model could not review are
+    /// listed; the default is the bare comment array.
+    #[serde(default)]
+    pub json_include_skipped: bool,
+
+    /// Jinja (minijinja) template for `diffscope changelog`, used instead of `--format`.
+    #[serde(default)]
+    pub changelog_template: Option<PathBuf>,
+
+    /// Exit non-zero when any finding is at or above this severity.
+    #[serde(default)]
+    pub fail_on: Option<FailOn>,
+
+    /// Exit non-zero when a review produces more findings than this.
+    #[serde(default)]
+    pub max_issues: Option<usize>,
+
+    /// Per-category/per-path routing rules applied to findings after filtering.
+    #[serde(default)]
+    pub policies: Vec<ReviewPolicy>,
+
+    /// Shared rule packs (URLs or local paths) whose instructions and rules are merged into
+    /// this config; URLs are cached under the user cache directory.
+    #[serde(default)]
+    pub rule_packs: Vec<String>,
+
+    /// How long a fetched rule pack is used before it is downloaded again.
+    #[serde(default = "default_rule_pack_ttl_secs")]
+    pub rule_pack_ttl_secs: u64,
+
+    /// Send each finding at a `verify_findings_severities` level back to the model with its
+    /// hunk, and drop the ones it retracts.
+    #[serde(default)]
+    pub verify_findings: bool,
+
+    #[serde(default = "default_verify_findings_severities")]
+    pub verify_findings_severities: Vec<String>,
+
+    pub system_prompt: Option<String>,
+    pub api_key: Option<String>,
+    pub base_url: Option<String>,
+
+    #[serde(default)]
+    pub openai_use_responses: Option<bool>,
+
+    #[serde(default = "default_true")]
+    pub anthropic_prompt_caching: bool,
+
+    #[serde(default)]
+    pub retry: RetryPolicy,
+
+    #[serde(default)]
+    pub llm_log_dir: Option<PathBuf>,
+
+    #[serde(default)]
+    pub llm_replay_dir: Option<PathBuf>,
+
+    #[serde(default)]
+    pub ollama_auto_pull: bool,
+
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+
+    #[serde(default)]
+    pub rate_limits: HashMap<String, RateLimit>,
+
+    /// USD per million input/output tokens by model name, for `review --dry-run` estimates of
+    /// models missing from the built-in price list.
+    #[serde(default)]
+    pub model_pricing: HashMap<String, ModelPrice>,
+
+    #[serde(default)]
+    pub plugins: PluginConfig,
+
+    #[serde(default)]
+    pub provider: ProviderConfig,
+
+    #[serde(default)]
+    pub webhook_secret: Option<String>,
+
+    /// Bearer token required by `serve --local` (or `DIFFSCOPE_LOCAL_TOKEN`).
+    #[serde(default)]
+    pub local_api_token: Option<String>,
+
+    /// Webhook reviews that run at once; further deliveries wait their turn.
+    #[serde(default = "default_webhook_max_concurrent")]
+    pub webhook_max_concurrent: usize,
+
+    /// GitHub author associations (`OWNER`, `MEMBER`, `COLLABORATOR`, `CONTRIBUTOR`, ...) whose
+    /// `@diffscope` comment commands are answered. Commands start model requests on the
+    /// operator's key, so comments from anyone else are ignored.
+    #[serde(default = "default_comment_command_associations")]
+    pub comment_command_associations: Vec<String>,
+
+    #[serde(default)]
+    pub exclude_patterns: Vec<String>,
+
+    /// Language-based filters, applied along with `exclude_patterns`.
+    #[serde(default)]
+    pub languages: LanguageFilter,
+
+    #[serde(default)]
+    pub codeowners: CodeOwnersConfig,
+
+    #[serde(default)]
+    pub hotspots: HotspotConfig,
+
+    #[serde(default)]
+    pub labels: LabelMap,
+
+    #[serde(default)]
+    pub batching: BatchingConfig,
+
+    #[serde(default)]
+    pub triage: TriageConfig,
+
+    #[serde(default)]
+    pub language_guidance: LanguageGuidanceConfig,
+
+    #[serde(default)]
+    pub follow_up: FollowUpConfig,
+
+    #[serde(default)]
+    pub repo_map: RepoMapConfig,
+
+    #[serde(default)]
+    pub embeddings: EmbeddingsConfig,
+
+    #[serde(default)]
+    pub paths: HashMap<String, PathConfig>,
+
+    /// Named sets of overrides (e.g. `ci`, `local-fast`) applied with `--profile <name>`.
+    #[serde(default)]
+    pub profiles: HashMap<String, serde_yaml::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct PathConfig {
+    #[serde(default)]
+    pub focus: Vec<String>,
+
+    #[serde(default)]
+    pub ignore_patterns: Vec<String>,
+
+    #[serde(default)]
+    pub extra_context: Vec<String>,
+
+    pub system_prompt: Option<String>,
+
+    pub review_instructions: Option<String>,
+
+    #[serde(default)]
+    pub severity_overrides: HashMap<String, String>,
+}
+
+/// Which files to review by language (`rust`, `python`, ...) or category (`lockfile`,
+/// `minified`, `vendored`, `generated`); see `core::languages`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LanguageFilter {
+    /// Only review files matching one of these; empty reviews every language.
+    #[serde(default)]
+    pub include: Vec<String>,
+
+    #[serde(default)]
+    pub exclude: Vec<String>,
+
+    /// Skip lockfiles, minified bundles, vendored directories, and generated code unless
+    /// `include` names them.
+    #[serde(default = "default_true")]
+    pub auto_skip: bool,
+}
+
+impl Default for LanguageFilter {
+    fn default() -> Self {
+        Self {
+            include: Vec::new(),
+            exclude: Vec::new(),
+            auto_skip: true,
+        }
+    }
+}
+
+/// How findings use the repository's CODEOWNERS file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CodeOwnersConfig {
+    /// Attach each file's owners to its findings and group the markdown report by owner.
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Mention the owners of files with Error findings in posted GitHub reviews.
+    #[serde(default)]
+    pub mention_on_error: bool,
+}
+
+impl Default for CodeOwnersConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            mention_on_error: false,
+        }
+    }
+}
+
+/// Flagging files with a history of bug fixes (`diffscope hotspots`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HotspotConfig {
+    /// Tell the model when a reviewed file is a hotspot. Off by default, since it mines the
+    /// history window on every review.
+    #[serde(default)]
+    pub context: bool,
+
+    /// History mined, in days.
+    #[serde(default = "default_hotspot_days")]
+    pub days: u32,
+
+    /// Bug-fix commits in the window that make a file a hotspot.
+    #[serde(default = "default_hotspot_min_fixes")]
+    pub min_fixes: usize,
+}
+
+impl Default for HotspotConfig {
+    fn default() -> Self {
+        Self {
+            context: false,
+            days: default_hotspot_days(),
+            min_fixes: default_hotspot_min_fixes(),
+        }
+    }
+}
+
+/// Labels `pr --apply-labels` adds for each signal; an empty name adds none.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LabelMap {
+    /// Change type from the PR summary.
+    pub feature: String,
+    pub fix: String,
+    pub refactor: String,
+    pub docs: String,
+    pub test: String,
+    pub chore: String,
+    /// A Security finding at Warning or above.
+    pub security: String,
+    /// A Testing finding, e.g. from `missing_tests` or coverage.
+    pub needs_tests: String,
+    /// A breaking API change found by `breaking_changes` or noted in the PR summary.
+    pub breaking_change: String,
+}
+
+impl Default for LabelMap {
+    fn default() -> Self {
+        Self {
+            feature: "feature".to_string(),
+            fix: "bugfix".to_string(),
+            refactor: "refactor".to_string(),
+            docs: "documentation".to_string(),
+            test: "tests".to_string(),
+            chore: "chore".to_string(),
+            security: "security".to_string(),
+            needs_tests: "needs-tests".to_string(),
+            breaking_change: "breaking-change".to_string(),
+        }
+    }
+}
+
+/// Reviewing several small files in one model request instead of one request each.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchingConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Files whose pending diff is at most this many characters count as small.
+    #[serde(default = "default_batch_max_file_chars")]
+    pub max_file_chars: usize,
+
+    /// Files sharing one request; more files make findings harder to attribute.
+    #[serde(default = "default_batch_max_files")]
+    pub max_files: usize,
+}
+
+impl Default for BatchingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_file_chars: default_batch_max_file_chars(),
+            max_files: default_batch_max_files(),
+        }
+    }
+}
+
+/// Screening hunks with a cheap model so only risky ones reach the review model (`--triage`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriageConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// The screening model, e.g. `gpt-4o-mini` or `ollama:qwen2.5-coder`.
+    #[serde(default = "default_triage_model")]
+    pub model: String,
+
+    /// Endpoint of the screening model; defaults to `base_url`.
+    #[serde(default)]
+    pub base_url: Option<String>,
+
+    /// Key for the screening model; defaults to `api_key`.
+    #[serde(default)]
+    pub api_key: Option<String>,
+
+    /// Risk score (0.0-1.0) at or above which a hunk goes on to the review model.
+    #[serde(default = "default_triage_threshold")]
+    pub threshold: f32,
+}
+
+impl Default for TriageConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            model: default_triage_model(),
+            base_url: None,
+            api_key: None,
+            threshold: default_triage_threshold(),
+        }
+    }
+}
+
+/// Semantic search over the repository's code: chunks are embedded once (and cached), and the
+/// code most similar to each hunk is added to the review's context.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingsConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// An OpenAI-compatible embeddings model, or `ollama:<model>` for a local one.
+    #[serde(default = "default_embeddings_model")]
+    pub model: String,
+
+    /// Endpoint of the embeddings API; defaults to OpenAI's, or the local Ollama server.
+    #[serde(default)]
+    pub base_url: Option<String>,
+
+    /// Key for the embeddings API; defaults to `OPENAI_API_KEY`.
+    #[serde(default)]
+    pub api_key: Option<String>,
+
+    /// Similar chunks retrieved per hunk.
+    #[serde(default = "default_embeddings_top_k")]
+    pub top_k: usize,
+
+    /// Cosine similarity (0.0-1.0) a chunk needs to be retrieved.
+    #[serde(default = "default_embeddings_min_similarity")]
+    pub min_similarity: f32,
+
+    /// Lines per indexed chunk.
+    #[serde(default = "default_embeddings_chunk_lines")]
+    pub chunk_lines: usize,
+
+    /// Chunks indexed at most; files past the limit are not searched.
+    #[serde(default = "default_embeddings_max_chunks")]
+    pub max_chunks: usize,
+
+    /// Where embeddings are cached between runs (default: the user cache directory).
+    #[serde(default)]
+    pub cache_dir: Option<PathBuf>,
+}
+
+impl Default for EmbeddingsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            model: default_embeddings_model(),
+            base_url: None,
+            api_key: None,
+            top_k: default_embeddings_top_k(),
+            min_similarity: default_embeddings_min_similarity(),
+            chunk_lines: default_embeddings_chunk_lines(),
+            max_chunks: default_embeddings_max_chunks(),
+            cache_dir: None,
+        }
+    }
+}
+
+/// An outline of the repository's files and symbols, drawn from the symbol index and added
+/// to every review's context.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepoMapConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Size of the map; files nearest the reviewed files are kept when the repository has more.
+    #[serde(default = "default_repo_map_max_chars")]
+    pub max_chars: usize,
+}
+
+impl Default for RepoMapConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_chars: default_repo_map_max_chars(),
+        }
+    }
+}
+
+/// Letting the model ask for more files and symbols after its first review of a file, then
+/// revise the review with them (`--follow-up`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FollowUpConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Ask-fetch-revise rounds per request; each costs two model calls.
+    #[serde(default = "default_follow_up_max_rounds")]
+    pub max_rounds: usize,
+
+    /// Tokens of fetched code added across all rounds of one request.
+    #[serde(default = "default_follow_up_max_context_tokens")]
+    pub max_context_tokens: usize,
+}
+
+impl Default for FollowUpConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_rounds: default_follow_up_max_rounds(),
+            max_context_tokens: default_follow_up_max_context_tokens(),
+        }
+    }
+}
+
+/// Built-in review advice for each file's language, appended to its system prompt.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LanguageGuidanceConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Advice replacing the built-in advice, by language (`rust`, `python`, `sql`, ...). An
+    /// empty string turns a language's advice off.
+    #[serde(default)]
+    pub overrides: HashMap<String, String>,
+}
+
+impl Default for LanguageGuidanceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            overrides: HashMap::new(),
+        }
+    }
+}
+
+/// Lowest finding severity that fails a review run (`fail_on`, `--fail-on`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum FailOn {
+    Error,
+    Warning,
+    Info,
+}
+
+impl FailOn {
+    pub fn is_triggered_by(&self, severity: &Severity) -> bool {
+        let rank = |severity: &Severity| match severity {
+            Severity::Error => 3,
+            Severity::Warning => 2,
+            Severity::Info => 1,
+            Severity::Suggestion => 0,
+        };
+        let threshold = match self {
+            FailOn::Error => 3,
+            FailOn::Warning => 2,
+            FailOn::Info => 1,
+        };
+        rank(severity) >= threshold
+    }
+}
+
+/// Code host that `diffscope pr` fetches diffs from and posts reviews to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderKind {
+    #[default]
+    Github,
+    Bitbucket,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ProviderConfig {
+    #[serde(default)]
+    pub kind: ProviderKind,
+
+    #[serde(default)]
+    pub github: GitHubConfig,
+
+    #[serde(default)]
+    pub gitlab: GitLabConfig,
+
+    #[serde(default)]
+    pub bitbucket: BitbucketConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct PluginConfig {
+    #[serde(default = "default_true")]
+    pub eslint: bool,
+
+    #[serde(default = "default_true")]
+    pub semgrep: bool,
+
+    #[serde(default = "default_true")]
+    pub ruff: bool,
+
+    #[serde(default = "default_true")]
+    pub golangci: bool,
+
+    /// Terraform, CloudFormation, and Kubernetes checks (tflint/checkov or built-in rules).
+    #[serde(default = "default_true")]
+    pub iac: bool,
+
+    /// Flags touched functions whose cyclomatic complexity or length crosses a threshold.
+    #[serde(default = "default_true")]
+    pub complexity: bool,
+
+    /// Note source files that change without a matching test file change.
+    #[serde(default = "default_true")]
+    pub missing_tests: bool,
+
+    /// Flag public API changes whose doc comments or README/docs mentions were not updated.
+    #[serde(default = "default_true")]
+    pub doc_drift: bool,
+
+    /// Typos in docs and code comments, and broken relative markdown links, on added lines.
+    #[serde(default = "default_true")]
+    pub spelling: bool,
+
+    #[serde(default = "default_true")]
+    pub secret_scan: bool,
+
+    #[serde(default = "default_true")]
+    pub secrets: bool,
+
+    #[serde(default = "default_true")]
+    pub duplicate_filter: bool,
+
+    /// Merge near-identical comments across files into one that lists every location.
+    #[serde(default = "default_true")]
+    pub comment_clusters: bool,
+
+    /// Cap comments per file and per category and demote repeated style findings.
+    #[serde(default = "default_true")]
+    pub calibration: bool,
+
+    /// Apply code suggestions to a copy of the file and drop those that do not apply or parse.
+    #[serde(default = "default_true")]
+    pub suggestion_check: bool,
+
+    #[serde(default = "default_true")]
+    pub dependency_impact: bool,
+
+    /// Look up dependency versions added to lockfiles in the OSV vulnerability database. Off
+    /// by default, since it sends dependency names (private ones included) to OSV.
+    #[serde(default)]
+    pub dependency_audit: bool,
+
+    /// Report removed, renamed, and incompatibly changed public functions by comparing
+    /// tree-sitter signatures before and after the diff.
+    #[serde(default = "default_true")]
+    pub breaking_changes: bool,
+
+    /// Add `git blame` for the lines around each hunk to the context, and note changes to
+    /// long-untouched or recently reverted code.
+    #[serde(default = "default_true")]
+    pub blame: bool,
+
+    /// lcov or Cobertura report; added lines it shows as unexecuted become Testing findings.
+    #[serde(default)]
+    pub coverage_report: Option<PathBuf>,
+
+    /// Directory of WASM plugins, one subdirectory with a `plugin.yml` manifest per plugin.
+    #[serde(default)]
+    pub plugins_dir: Option<PathBuf>,
+
+    /// External programs that exchange JSON with diffscope over stdin/stdout.
+    #[serde(default)]
+    pub commands: Vec<CommandPluginConfig>,
+
+    /// Regex or ast-grep rules reported on added lines, from this file or rule packs.
+    #[serde(default)]
+    pub rules: Vec<CustomRule>,
+
+    /// Per-plugin settings keyed by plugin id, e.g. `semgrep: { config: p/owasp-top-ten }`.
+    #[serde(default)]
+    pub settings: HashMap<String, PluginSettings>,
+}
+
+impl PluginConfig {
+    /// This configuration without the analyzers that run external linters (eslint, ruff,
+    /// golangci-lint, semgrep, tflint, checkov, ast-grep). Those tools load configuration,
+    /// and with it plugins and code, from the directory they run in, so they must not run in
+    /// a checkout of untrusted code. IaC and regex rules still run on their built-in matchers.
+    pub fn without_repo_tools(&self) -> Self {
+        let mut plugins = self.clone();
+        plugins.eslint = false;
+        plugins.ruff = false;
+        plugins.golangci = false;
+        plugins.semgrep = false;
+        if plugins.iac {
+            let iac = plugins.settings.entry("iac".to_string()).or_default();
+            iac.insert("tflint".to_string(), false.into());
+            iac.insert("checkov".to_string(), false.into());
+        }
+        plugins.rules.retain(|rule| rule.ast_grep.is_none());
+        plugins
+    }
+}
+
+pub type PluginSettings = HashMap<String, serde_json::Value>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandPluginConfig {
+    pub id: String,
 
-    pub system_prompt: Option<String>,
-    pub api_key: Option<String>,
-    pub base_url: Option<String>,
+    /// Program and arguments, split like a shell command line (no shell is involved).
+    pub command: String,
 
+    /// File globs the plugin runs for; empty means every file.
     #[serde(default)]
-    pub openai_use_responses: Option<bool>,
+    pub applies_to: Vec<String>,
 
-    #[serde(default)]
-    pub plugins: PluginConfig,
+    #[serde(default = "default_command_plugin_timeout_secs")]
+    pub timeout_secs: u64,
 
     #[serde(default)]
-    pub exclude_patterns: Vec<String>,
+    pub stage: CommandPluginStage,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomRule {
+    pub id: String,
+
+    /// Finding text shown to the author.
+    pub message: String,
 
+    /// Regex matched against each added line.
     #[serde(default)]
-    pub paths: HashMap<String, PathConfig>,
-}
+    pub pattern: Option<String>,
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
-pub struct PathConfig {
+    /// ast-grep pattern, run with the `ast-grep` CLI when it is installed.
     #[serde(default)]
-    pub focus: Vec<String>,
+    pub ast_grep: Option<String>,
 
+    /// ast-grep language (`rust`, `ts`, `python`, ...); inferred from the file when unset.
     #[serde(default)]
-    pub ignore_patterns: Vec<String>,
+    pub language: Option<String>,
 
+    /// File globs the rule applies to; empty means every file.
     #[serde(default)]
-    pub extra_context: Vec<String>,
+    pub paths: Vec<String>,
 
-    pub system_prompt: Option<String>,
+    #[serde(default = "default_rule_severity")]
+    pub severity: String,
 
-    pub review_instructions: Option<String>,
+    #[serde(default = "default_rule_category")]
+    pub category: String,
 
     #[serde(default)]
-    pub severity_overrides: HashMap<String, String>,
+    pub suggestion: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
-pub struct PluginConfig {
-    #[serde(default = "default_true")]
-    pub eslint: bool,
-
-    #[serde(default = "default_true")]
-    pub semgrep: bool,
-
-    #[serde(default = "default_true")]
-    pub duplicate_filter: bool,
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CommandPluginStage {
+    /// Receives each file's `UnifiedDiff`, prints context chunks.
+    #[default]
+    PreAnalyzer,
+    /// Receives the review comments, prints the comments to keep.
+    PostProcessor,
 }
 
 impl Default for Config {
@@ -115,60 +839,201 @@ impl Default for Config {
             max_tokens: default_max_tokens(),
             max_context_chars: default_max_context_chars(),
             max_diff_chars: default_max_diff_chars(),
+            max_context_tokens: 0,
+            max_diff_tokens: 0,
+            context_window: None,
+            function_scope_context: true,
             min_confidence: default_min_confidence(),
+            confidence_calibration: true,
+            category_weights: HashMap::new(),
             review_profile: None,
             review_instructions: None,
             smart_review_summary: true,
             smart_review_diagram: false,
+            smart_review_walkthrough: true,
+            smart_review_sections: default_smart_review_sections(),
             symbol_index: true,
             symbol_index_provider: default_symbol_index_provider(),
             symbol_index_max_files: default_symbol_index_max_files(),
             symbol_index_max_bytes: default_symbol_index_max_bytes(),
             symbol_index_max_locations: default_symbol_index_max_locations(),
             symbol_index_lsp_command: None,
+            max_reference_sites: default_max_reference_sites(),
             symbol_index_lsp_languages: default_symbol_index_lsp_languages(),
+            symbol_index_lsp_commands: HashMap::new(),
+            symbol_index_lsp_workers: default_symbol_index_lsp_workers(),
+            symbol_index_lsp_timeout_secs: default_symbol_index_lsp_timeout_secs(),
             feedback_path: default_feedback_path(),
+            feedback_db: None,
+            feedback_similarity: default_feedback_similarity(),
+            feedback_remote: None,
+            feedback_remote_token: None,
+            review_cache: true,
+            review_cache_dir: None,
+            review_checkpoints: false,
+            prompt_template: None,
+            ticket: None,
+            report_template: None,
+            json_include_skipped: false,
+            changelog_template: None,
+            fail_on: None,
+            max_issues: None,
+            policies: Vec::new(),
+            rule_packs: Vec::new(),
+            rule_pack_ttl_secs: default_rule_pack_ttl_secs(),
+            verify_findings: false,
+            verify_findings_severities: default_verify_findings_severities(),
             system_prompt: None,
             api_key: None,
             base_url: None,
             openai_use_responses: None,
+            anthropic_prompt_caching: true,
+            retry: RetryPolicy::default(),
+            llm_log_dir: None,
+            llm_replay_dir: None,
+            ollama_auto_pull: false,
+            timeout_secs: None,
+            rate_limits: HashMap::new(),
+            model_pricing: HashMap::new(),
             plugins: PluginConfig::default(),
+            provider: ProviderConfig::default(),
+            webhook_secret: None,
+            local_api_token: None,
+            webhook_max_concurrent: default_webhook_max_concurrent(),
+            comment_command_associations: default_comment_command_associations(),
             exclude_patterns: Vec::new(),
+            languages: LanguageFilter::default(),
+            codeowners: CodeOwnersConfig::default(),
+            hotspots: HotspotConfig::default(),
+            labels: LabelMap::default(),
+            batching: BatchingConfig::default(),
+            triage: TriageConfig::default(),
+            language_guidance: LanguageGuidanceConfig::default(),
+            follow_up: FollowUpConfig::default(),
+            repo_map: RepoMapConfig::default(),
+            embeddings: EmbeddingsConfig::default(),
             paths: HashMap::new(),
+            profiles: HashMap::new(),
         }
     }
 }
 
 impl Config {
-    pub fn load() -> Result<Self> {
-        // Try to load from .diffscope.yml in current directory
-        let config_path = PathBuf::from(".diffscope.yml");
-        if config_path.exists() {
-            let content = std::fs::read_to_string(&config_path)?;
-            let config: Config = serde_yaml::from_str(&content)?;
-            return Ok(config);
-        }
-
-        // Try alternative names
-        let alt_config_path = PathBuf::from(".diffscope.yaml");
-        if alt_config_path.exists() {
-            let content = std::fs::read_to_string(&alt_config_path)?;
-            let config: Config = serde_yaml::from_str(&content)?;
-            return Ok(config);
-        }
-
-        // Try in home directory
-        if let Some(home_dir) = dirs::home_dir() {
-            let home_config = home_dir.join(".diffscope.yml");
-            if home_config.exists() {
-                let content = std::fs::read_to_string(&home_config)?;
-                let config: Config = serde_yaml::from_str(&content)?;
-                return Ok(config);
+    /// Config files `load` merges, lowest precedence first: the system file
+    /// (`/etc/diffscope/config.yml`), the user file (`$XDG_CONFIG_HOME/diffscope/config.yml`,
+    /// then `~/.diffscope.yml`), and `.diffscope.yml` in each directory from the repository
+    /// root down to the current one.
+    pub fn layer_files() -> Vec<PathBuf> {
+        let config_home = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| dirs::home_dir().map(|home| home.join(".config")));
+        let mut files: Vec<PathBuf> = [
+            Some(PathBuf::from(SYSTEM_CONFIG)),
+            config_home.map(|dir| dir.join("diffscope").join("config.yml")),
+            dirs::home_dir().map(|home| home.join(".diffscope.yml")),
+        ]
+        .into_iter()
+        .flatten()
+        .filter(|path| path.is_file())
+        .collect();
+        if let Ok(cwd) = std::env::current_dir() {
+            files.extend(directory_files(&cwd));
+        }
+        files.dedup();
+        files
+    }
+
+    /// Every local file `load` reads: the layer files, each preceded by the files it `extends`.
+    pub fn source_files() -> Vec<PathBuf> {
+        fn visit(path: &Path, files: &mut Vec<PathBuf>) {
+            // Missing bases are reported by `load`.
+            if !path.is_file() || files.iter().any(|seen| seen == path) {
+                return;
+            }
+            if let Ok(value) = read_yaml(path) {
+                for base in extends_of(&value, &LayerSource::File(path.to_path_buf())) {
+                    if let LayerSource::File(base) = base {
+                        visit(&base, files);
+                    }
+                }
             }
+            files.push(path.to_path_buf());
         }
+        let mut files = Vec::new();
+        for path in Self::layer_files() {
+            visit(&path, &mut files);
+        }
+        files
+    }
+
+    pub fn load() -> Result<Self> {
+        Self::load_with_profile(None, None)
+    }
+
+    /// `load`, then the settings of `profiles.<profile>` merged over the result.
+    /// `review_profile` overrides the configured `review_profile`.
+    pub fn load_with_profile(profile: Option<&str>, review_profile: Option<&str>) -> Result<Self> {
+        Self::load_layers(&Self::layer_files(), profile, review_profile)
+    }
 
-        // Return default config if no file found
-        Ok(Config::default())
+    /// Merges `files` in order, later files overriding earlier ones key by key (nested
+    /// sections merge; lists and values are replaced), after resolving their `extends`.
+    /// `profile` names an entry of `profiles` applied last; naming a missing one is an error.
+    /// When the resulting `review_profile` (or `review_profile`, if given) is a preset, its
+    /// settings go beneath everything else.
+    pub fn load_layers(
+        files: &[PathBuf],
+        profile: Option<&str>,
+        review_profile: Option<&str>,
+    ) -> Result<Self> {
+        let mut merged = serde_yaml::Value::Mapping(Default::default());
+        for path in files {
+            let layer = read_layer(&LayerSource::File(path.clone()), &mut Vec::new())?;
+            merge_yaml(&mut merged, layer);
+        }
+        if let Some(name) = profile {
+            let overrides = merged
+                .get("profiles")
+                .and_then(|profiles| profiles.get(name))
+                .cloned();
+            let Some(mut overrides) = overrides else {
+                let mut defined: Vec<&str> = merged
+                    .get("profiles")
+                    .and_then(serde_yaml::Value::as_mapping)
+                    .map(|profiles| profiles.keys().filter_map(|key| key.as_str()).collect())
+                    .unwrap_or_default();
+                defined.sort_unstable();
+                anyhow::bail!(
+                    "unknown config profile `{}` (defined: {})",
+                    name,
+                    if defined.is_empty() {
+                        "none".to_string()
+                    } else {
+                        defined.join(", ")
+                    }
+                );
+            };
+            if let Some(mapping) = overrides.as_mapping_mut() {
+                mapping.remove("profiles");
+            }
+            merge_yaml(&mut merged, overrides);
+        }
+        if let Some(name) = review_profile {
+            if let Some(mapping) = merged.as_mapping_mut() {
+                mapping.insert("review_profile".into(), name.into());
+            }
+        }
+        let preset = merged
+            .get("review_profile")
+            .and_then(serde_yaml::Value::as_str)
+            .map(|name| name.trim().to_lowercase())
+            .and_then(|name| crate::core::review_presets::find(&name));
+        if let Some(preset) = preset {
+            let mut base: serde_yaml::Value = serde_yaml::from_str(preset.settings)?;
+            merge_yaml(&mut base, merged);
+            merged = base;
+        }
+        serde_yaml::from_value(merged).context("Invalid configuration")
     }
 
     pub fn merge_with_cli(&mut self, cli_model: Option<String>, cli_prompt: Option<String>) {
@@ -180,6 +1045,100 @@ impl Config {
         }
     }
 
+    pub fn prompt_limits(&self) -> PromptLimits {
+        let window = self
+            .context_window
+            .unwrap_or_else(|| tokenizer::context_window(&self.model));
+        let mut limits = PromptLimits::for_model(&self.model, window, self.max_tokens);
+        limits.max_context_chars = self.max_context_chars;
+        limits.max_diff_chars = self.max_diff_chars;
+        if self.max_context_tokens > 0 {
+            limits.max_context_tokens = self.max_context_tokens;
+        }
+        if self.max_diff_tokens > 0 {
+            limits.max_diff_tokens = self.max_diff_tokens;
+        }
+        limits
+    }
+
+    /// Whether `@diffscope` commands from a GitHub commenter with `association` (the
+    /// comment's `author_association`) are answered.
+    pub fn answers_commands_from(&self, association: &str) -> bool {
+        self.comment_command_associations
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(association))
+    }
+
+    /// Severities re-checked by the `verify_findings` pass; empty when it is disabled.
+    pub fn verified_severities(&self) -> Vec<Severity> {
+        if !self.verify_findings {
+            return Vec::new();
+        }
+        self.verify_findings_severities
+            .iter()
+            .filter_map(|severity| crate::core::policy::parse_severity(severity))
+            .collect()
+    }
+
+    pub fn model_config(&self) -> ModelConfig {
+        ModelConfig {
+            model_name: self.model.clone(),
+            api_key: self.api_key.clone(),
+            base_url: self.base_url.clone(),
+            temperature: self.temperature,
+            max_tokens: self.max_tokens,
+            openai_use_responses: self.openai_use_responses,
+            anthropic_prompt_caching: self.anthropic_prompt_caching,
+            retry_policy: self.retry.clone(),
+            llm_log_dir: self.llm_log_dir.clone(),
+            llm_replay_dir: self.llm_replay_dir.clone(),
+            ollama_auto_pull: self.ollama_auto_pull,
+            api_key_required: true,
+            timeout_secs: self.timeout_secs,
+            rate_limits: self.rate_limits.clone(),
+        }
+    }
+
+    /// The screening model of `triage`, with the review model's retry, logging, and rate
+    /// limit settings. The review model's key and endpoint are only inherited when both
+    /// models go to the same provider, so a Claude key never reaches OpenAI.
+    pub fn triage_model_config(&self) -> ModelConfig {
+        let main = self.model_config();
+        let same_provider = provider_key(&ModelConfig {
+            model_name: self.triage.model.clone(),
+            ..main.clone()
+        }) == provider_key(&main);
+        let inherited = |value: &Option<String>| value.clone().filter(|_| same_provider);
+        ModelConfig {
+            model_name: self.triage.model.clone(),
+            api_key: self
+                .triage
+                .api_key
+                .clone()
+                .or_else(|| inherited(&self.api_key)),
+            base_url: self
+                .triage
+                .base_url
+                .clone()
+                .or_else(|| inherited(&self.base_url)),
+            temperature: 0.0,
+            max_tokens: 1000,
+            ..main
+        }
+    }
+
+    /// The embeddings model of `embeddings`, with the review model's retry and timeout
+    /// settings. The review model's key and endpoint are not reused, since they usually
+    /// belong to a different provider.
+    pub fn embeddings_model_config(&self) -> ModelConfig {
+        ModelConfig {
+            model_name: self.embeddings.model.clone(),
+            api_key: self.embeddings.api_key.clone(),
+            base_url: self.embeddings.base_url.clone(),
+            ..self.model_config()
+        }
+    }
+
     pub fn normalize(&mut self) {
         if self.model.trim().is_empty() {
             self.model = default_model();
@@ -193,6 +1152,21 @@ impl Config {
             self.max_tokens = default_max_tokens();
         }
 
+        if self.context_window == Some(0) {
+            self.context_window = None;
+        }
+
+        if self.timeout_secs == Some(0) {
+            self.timeout_secs = None;
+        }
+
+        self.rate_limits = std::mem::take(&mut self.rate_limits)
+            .into_iter()
+            .map(|(provider, limit)| (provider.trim().to_lowercase(), limit))
+            .collect();
+
+        self.retry.normalize();
+
         if self.symbol_index_max_files == 0 {
             self.symbol_index_max_files = default_symbol_index_max_files();
         }
@@ -216,6 +1190,18 @@ impl Config {
             }
         }
 
+        self.symbol_index_lsp_commands
+            .retain(|_, command| !command.trim().is_empty());
+        if self.webhook_max_concurrent == 0 {
+            self.webhook_max_concurrent = default_webhook_max_concurrent();
+        }
+        if self.symbol_index_lsp_workers == 0 {
+            self.symbol_index_lsp_workers = default_symbol_index_lsp_workers();
+        }
+        if self.symbol_index_lsp_timeout_secs == 0 {
+            self.symbol_index_lsp_timeout_secs = default_symbol_index_lsp_timeout_secs();
+        }
+
         if self.symbol_index_provider == "lsp" && self.symbol_index_lsp_languages.is_empty() {
             self.symbol_index_lsp_languages = default_symbol_index_lsp_languages();
         }
@@ -226,11 +1212,38 @@ impl Config {
             self.min_confidence = self.min_confidence.clamp(0.0, 1.0);
         }
 
+        if self.embeddings.model.trim().is_empty() {
+            self.embeddings.model = default_embeddings_model();
+        }
+        if !self.embeddings.min_similarity.is_finite() {
+            self.embeddings.min_similarity = default_embeddings_min_similarity();
+        } else {
+            self.embeddings.min_similarity = self.embeddings.min_similarity.clamp(0.0, 1.0);
+        }
+        if self.embeddings.chunk_lines == 0 {
+            self.embeddings.chunk_lines = default_embeddings_chunk_lines();
+        }
+
+        if self.triage.model.trim().is_empty() {
+            self.triage.model = default_triage_model();
+        }
+        if !self.triage.threshold.is_finite() {
+            self.triage.threshold = default_triage_threshold();
+        } else {
+            self.triage.threshold = self.triage.threshold.clamp(0.0, 1.0);
+        }
+
+        if !self.feedback_similarity.is_finite() {
+            self.feedback_similarity = default_feedback_similarity();
+        } else {
+            self.feedback_similarity = self.feedback_similarity.clamp(0.0, 1.0);
+        }
+
         if let Some(profile) = &self.review_profile {
             let normalized = profile.trim().to_lowercase();
             self.review_profile = if normalized.is_empty() {
                 None
-            } else if matches!(normalized.as_str(), "balanced" | "chill" | "assertive") {
+            } else if crate::core::review_presets::profile_names().contains(&normalized.as_str()) {
                 Some(normalized)
             } else {
                 None
@@ -262,7 +1275,38 @@ impl Config {
         best_match.map(|(_, config)| config)
     }
 
+    /// `should_exclude`, plus files under `repo_root` that `.gitattributes` or a generator's
+    /// header marks as generated, unless `languages` keeps generated code in reviews.
+    pub fn exclusion_filter(&self, repo_root: &Path) -> impl Fn(&Path) -> bool + '_ {
+        let generated = crate::core::languages::skips(
+            "generated",
+            &self.languages.include,
+            &self.languages.exclude,
+            self.languages.auto_skip,
+        )
+        .then(|| crate::core::generated::GeneratedFiles::load(repo_root));
+        move |path| {
+            self.should_exclude(path)
+                || generated
+                    .as_ref()
+                    .is_some_and(|generated| generated.is_generated(path))
+        }
+    }
+
     pub fn should_exclude(&self, file_path: &Path) -> bool {
+        self.excludes(file_path, self.languages.auto_skip)
+    }
+
+    /// Whether `path` is a lockfile that only `languages.auto_skip` keeps out of reviews.
+    /// Lockfile-aware analyzers such as `dependency_audit` still run on it.
+    pub fn is_auto_skipped_lockfile(&self, path: &Path) -> bool {
+        self.languages.auto_skip
+            && crate::core::languages::categories(path).contains(&"lockfile")
+            && self.should_exclude(path)
+            && !self.excludes(path, false)
+    }
+
+    fn excludes(&self, file_path: &Path, auto_skip: bool) -> bool {
         let file_path_str = file_path.to_string_lossy();
 
         // Check global exclude patterns
@@ -272,6 +1316,15 @@ impl Config {
             }
         }
 
+        if crate::core::languages::is_filtered(
+            file_path,
+            &self.languages.include,
+            &self.languages.exclude,
+            auto_skip,
+        ) {
+            return true;
+        }
+
         // Check path-specific ignore patterns
         if let Some(path_config) = self.get_path_config(file_path) {
             for pattern in &path_config.ignore_patterns {
@@ -305,12 +1358,14 @@ mod tests {
 
     #[test]
     fn normalize_clamps_values() {
-        let mut config = Config::default();
-        config.model = "   ".to_string();
-        config.temperature = 5.0;
-        config.max_tokens = 0;
-        config.min_confidence = 2.0;
-        config.review_profile = Some("ASSERTIVE".to_string());
+        let mut config = Config {
+            model: "   ".to_string(),
+            temperature: 5.0,
+            max_tokens: 0,
+            min_confidence: 2.0,
+            review_profile: Some("ASSERTIVE".to_string()),
+            ..Config::default()
+        };
 
         config.normalize();
 
@@ -320,6 +1375,202 @@ mod tests {
         assert_eq!(config.min_confidence, 1.0);
         assert_eq!(config.review_profile.as_deref(), Some("assertive"));
     }
+
+    #[test]
+    fn triage_model_config_inherits_credentials_only_for_same_provider() {
+        let mut config = Config {
+            model: "claude-3-5-sonnet-20241022".to_string(),
+            api_key: Some("sk-ant".to_string()),
+            base_url: Some("https://api.anthropic.com".to_string()),
+            ..Config::default()
+        };
+        let triage = config.triage_model_config();
+        assert_eq!((triage.api_key, triage.base_url), (None, None));
+        config.triage.model = "claude-3-5-haiku-latest".to_string();
+        assert_eq!(
+            config.triage_model_config().api_key.as_deref(),
+            Some("sk-ant")
+        );
+    }
+
+    #[test]
+    fn validate_reports_type_errors_and_unknown_keys_with_lines() {
+        assert_eq!(
+            validate("model: gpt-4o\nplugins:\n  eslint: sometimes\n"),
+            vec![ConfigIssue {
+                line: Some(3),
+                message: "plugins.eslint: invalid type: string \"sometimes\", expected a boolean"
+                    .to_string(),
+            }]
+        );
+
+        let issues = validate(
+            "model: gpt-4o\nmax_token: 100\npaths:\n  src/:\n    focus: [security]\nplugins:\n  settings:\n    eslint: {}\n  eslnt: false\n",
+        );
+        let rendered: Vec<String> = issues.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            rendered,
+            [
+                "line 2: unknown key `max_token` (did you mean `max_tokens`?)",
+                "line 9: unknown key `plugins.eslnt` (did you mean `eslint`?)",
+            ]
+        );
+        assert_eq!(
+            validate("languages:\n  include: [Rust, pyhton]\n")
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            ["line 2: unknown language `pyhton` in languages.include (did you mean `python`?)"]
+        );
+
+        let config = Config {
+            api_key: Some("sk-live".to_string()),
+            ..Config::default()
+        };
+        let yaml = to_redacted_yaml(&config).unwrap();
+        assert!(yaml.contains("api_key: <redacted>") && !yaml.contains("sk-live"));
+        assert!(yaml.contains("webhook_secret: null"));
+    }
+
+    #[test]
+    fn layers_merge_from_repo_root_down_with_extends() {
+        let repo = tempfile::tempdir().unwrap();
+        let root = repo.path();
+        let api = root.join("packages/api");
+        std::fs::create_dir_all(root.join(".git")).unwrap();
+        std::fs::create_dir_all(&api).unwrap();
+        std::fs::write(
+            root.join("base.yml"),
+            "model: gpt-4o-mini\ntemperature: 0.5\nplugins:\n  eslint: true\n  ruff: true\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join(".diffscope.yml"),
+            "extends: base.yml\nmodel: claude-3-5-sonnet-20241022\nexclude_patterns: [\"dist/**\"]\n",
+        )
+        .unwrap();
+        std::fs::write(
+            api.join(".diffscope.yml"),
+            "max_tokens: 1234\nexclude_patterns: [\"gen/**\"]\nplugins:\n  ruff: false\n",
+        )
+        .unwrap();
+
+        let files = directory_files(&api);
+        assert_eq!(
+            files,
+            vec![root.join(".diffscope.yml"), api.join(".diffscope.yml")]
+        );
+        let config = Config::load_layers(&files, None, None).unwrap();
+        assert_eq!(config.model, "claude-3-5-sonnet-20241022");
+        assert_eq!(config.temperature, 0.5);
+        assert_eq!(config.max_tokens, 1234);
+        assert_eq!(config.exclude_patterns, vec!["gen/**".to_string()]);
+        assert!(config.plugins.eslint && !config.plugins.ruff);
+        assert!(validate("extends: base.yml\n").is_empty());
+
+        std::fs::write(root.join("base.yml"), "extends: .diffscope.yml\n").unwrap();
+        let err = Config::load_layers(&files, None, None).unwrap_err();
+        assert!(format!("{:#}", err).contains("extends itself"));
+    }
+
+    #[test]
+    fn review_presets_sit_beneath_the_config_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".diffscope.yml");
+        std::fs::write(
+            &path,
+            "review_profile: performance
+category_weights:
+  style: 0.8
+plugins:
+  spelling: true
+",
+        )
+        .unwrap();
+        let files = [path];
+
+        let config = Config::load_layers(&files, None, None).unwrap();
+        assert!(config
+            .system_prompt
+            .unwrap()
+            .contains("performance engineer"));
+        assert_eq!(config.category_weights["style"], 0.8);
+        assert_eq!(config.category_weights["testing"], 0.6);
+        assert!(config.plugins.spelling && config.plugins.complexity);
+        assert_eq!(config.smart_review_sections[1], "impact");
+
+        let config = Config::load_layers(&files, None, Some("security-audit")).unwrap();
+        assert_eq!(config.review_profile.as_deref(), Some("security-audit"));
+        assert!(config.system_prompt.unwrap().contains("security auditor"));
+        assert!(config.plugins.semgrep);
+    }
+
+    #[test]
+    fn profiles_override_the_merged_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".diffscope.yml");
+        let content = "model: gpt-4o\nmin_confidence: 0.3\nplugins:\n  eslint: true\n  semgrep: true\nprofiles:\n  local-fast:\n    model: ollama:codellama\n    plugins:\n      semgrep: false\n  security-deep:\n    min_confidence: 0.1\n    system_prompt: Focus on security.\n";
+        std::fs::write(&path, content).unwrap();
+        let files = [path];
+
+        let config = Config::load_layers(&files, Some("local-fast"), None).unwrap();
+        assert_eq!(config.model, "ollama:codellama");
+        assert_eq!(config.min_confidence, 0.3);
+        assert!(config.plugins.eslint && !config.plugins.semgrep);
+
+        let config = Config::load_layers(&files, Some("security-deep"), None).unwrap();
+        assert_eq!(config.model, "gpt-4o");
+        assert_eq!(config.system_prompt.as_deref(), Some("Focus on security."));
+
+        let err = Config::load_layers(&files, Some("ci"), None).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "unknown config profile `ci` (defined: local-fast, security-deep)"
+        );
+
+        assert!(validate(content).is_empty());
+        let issues: Vec<String> =
+            validate("profiles:\n  ci:\n    modle: gpt-4o\n    max_tokens: lots\n")
+                .iter()
+                .map(ToString::to_string)
+                .collect();
+        assert_eq!(issues.len(), 2);
+        assert!(issues[0].starts_with("line 2: profiles.ci: invalid type: string \"lots\""));
+        assert_eq!(
+            issues[1],
+            "line 3: unknown key `profiles.ci.modle` (did you mean `model`?)"
+        );
+    }
+
+    #[test]
+    fn interpolates_environment_variables_in_strings() {
+        let lookup = |name: &str| match name {
+            "OPENAI_KEY" => Some("sk-test".to_string()),
+            "EMPTY" => Some(String::new()),
+            _ => None,
+        };
+        assert_eq!(expand_env("${OPENAI_KEY}", &lookup), "sk-test");
+        assert_eq!(
+            expand_env("http://${HOST:-localhost}:${PORT:-8080}/v1", &lookup),
+            "http://localhost:8080/v1"
+        );
+        assert_eq!(expand_env("${EMPTY:-fallback}", &lookup), "fallback");
+        assert_eq!(
+            expand_env("cost $5, $${LITERAL}, ${open", &lookup),
+            "cost $5, ${LITERAL}, ${open"
+        );
+
+        let mut value: serde_yaml::Value = serde_yaml::from_str(
+            "api_key: ${OPENAI_KEY}\nbase_url: ${MISSING}\nprovider:\n  github:\n    token: ${GH_TOKEN:-ghp_default}\nexclude_patterns: [\"${DIR:-dist}/**\"]\n",
+        )
+        .unwrap();
+        interpolate_env(&mut value, &lookup);
+        let config: Config = serde_yaml::from_value(value).unwrap();
+        assert_eq!(config.api_key.as_deref(), Some("sk-test"));
+        assert_eq!(config.base_url, None);
+        assert_eq!(config.provider.github.token.as_deref(), Some("ghp_default"));
+        assert_eq!(config.exclude_patterns, vec!["dist/**".to_string()]);
+    }
 }
 
 fn default_model() -> String {
@@ -358,6 +1609,10 @@ fn default_symbol_index_max_locations() -> usize {
     5
 }
 
+fn default_max_reference_sites() -> usize {
+    10
+}
+
 fn default_symbol_index_provider() -> String {
     "regex".to_string()
 }
@@ -368,10 +1623,559 @@ fn default_symbol_index_lsp_languages() -> HashMap<String, String> {
     map
 }
 
+fn default_webhook_max_concurrent() -> usize {
+    2
+}
+
+fn default_comment_command_associations() -> Vec<String> {
+    ["OWNER", "MEMBER", "COLLABORATOR"]
+        .iter()
+        .map(|association| association.to_string())
+        .collect()
+}
+
+fn default_symbol_index_lsp_workers() -> usize {
+    4
+}
+
+fn default_symbol_index_lsp_timeout_secs() -> u64 {
+    30
+}
+
+fn default_feedback_similarity() -> f32 {
+    0.8
+}
+
 fn default_feedback_path() -> PathBuf {
     PathBuf::from(".diffscope.feedback.json")
 }
 
+fn default_rule_pack_ttl_secs() -> u64 {
+    3600
+}
+
+fn default_verify_findings_severities() -> Vec<String> {
+    vec!["error".to_string(), "warning".to_string()]
+}
+
+fn default_rule_severity() -> String {
+    "warning".to_string()
+}
+
+fn default_rule_category() -> String {
+    "best_practice".to_string()
+}
+
+fn default_command_plugin_timeout_secs() -> u64 {
+    30
+}
+
+fn default_hotspot_days() -> u32 {
+    180
+}
+
+fn default_hotspot_min_fixes() -> usize {
+    3
+}
+
+fn default_batch_max_file_chars() -> usize {
+    2000
+}
+
+fn default_batch_max_files() -> usize {
+    8
+}
+
+fn default_triage_model() -> String {
+    "gpt-4o-mini".to_string()
+}
+
+fn default_triage_threshold() -> f32 {
+    0.3
+}
+
+fn default_embeddings_model() -> String {
+    "text-embedding-3-small".to_string()
+}
+
+fn default_embeddings_top_k() -> usize {
+    3
+}
+
+fn default_embeddings_min_similarity() -> f32 {
+    0.5
+}
+
+fn default_embeddings_chunk_lines() -> usize {
+    40
+}
+
+fn default_embeddings_max_chunks() -> usize {
+    5000
+}
+
+fn default_repo_map_max_chars() -> usize {
+    2000
+}
+
+fn default_follow_up_max_rounds() -> usize {
+    2
+}
+
+fn default_follow_up_max_context_tokens() -> usize {
+    8000
+}
+
+fn default_smart_review_sections() -> Vec<String> {
+    [
+        "summary",
+        "breaking_changes",
+        "walkthrough",
+        "impact",
+        "coverage",
+        "risk",
+        "breakdown",
+        "actions",
+    ]
+    .into_iter()
+    .map(str::to_string)
+    .collect()
+}
+
 fn default_true() -> bool {
     true
 }
+
+const SYSTEM_CONFIG: &str = "/etc/diffscope/config.yml";
+const CONFIG_FILE_NAMES: &[&str] = &[".diffscope.yml", ".diffscope.yaml"];
+
+/// The config file in each directory from the repository containing `dir` down to `dir`
+/// (just `dir` outside a repository), outermost first.
+fn directory_files(dir: &Path) -> Vec<PathBuf> {
+    let root = dir
+        .ancestors()
+        .find(|ancestor| ancestor.join(".git").exists())
+        .unwrap_or(dir);
+    let mut dirs: Vec<&Path> = dir
+        .ancestors()
+        .take_while(|ancestor| ancestor.starts_with(root))
+        .collect();
+    dirs.reverse();
+    dirs.into_iter()
+        .filter_map(|dir| {
+            CONFIG_FILE_NAMES
+                .iter()
+                .map(|name| dir.join(name))
+                .find(|path| path.is_file())
+        })
+        .collect()
+}
+
+fn read_yaml(path: &Path) -> Result<serde_yaml::Value> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    parse_layer(&content, &path.display().to_string())
+}
+
+fn parse_layer(content: &str, name: &str) -> Result<serde_yaml::Value> {
+    let mut value: serde_yaml::Value =
+        serde_yaml::from_str(content).with_context(|| format!("Failed to parse {}", name))?;
+    interpolate_env(&mut value, &|name| std::env::var(name).ok());
+    // An empty file is an empty layer.
+    Ok(match value {
+        serde_yaml::Value::Null => serde_yaml::Value::Mapping(Default::default()),
+        value => value,
+    })
+}
+
+/// Expands `${VAR}` and `${VAR:-fallback}` in every string of `value`. A mapping entry
+/// that expands to nothing is removed, so the setting keeps its default (for `api_key`,
+/// the provider's environment variable).
+fn interpolate_env(value: &mut serde_yaml::Value, lookup: &dyn Fn(&str) -> Option<String>) {
+    match value {
+        serde_yaml::Value::String(text) if text.contains('$') => *text = expand_env(text, lookup),
+        serde_yaml::Value::Sequence(items) => {
+            for item in items {
+                interpolate_env(item, lookup);
+            }
+        }
+        serde_yaml::Value::Mapping(mapping) => {
+            let mut unset = Vec::new();
+            for (key, item) in mapping.iter_mut() {
+                let referenced = item.as_str().is_some_and(|text| text.contains("${"));
+                interpolate_env(item, lookup);
+                if referenced && item.as_str() == Some("") {
+                    unset.push(key.clone());
+                }
+            }
+            for key in unset {
+                mapping.remove(&key);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// `text` with `${VAR}` replaced by the variable (empty when unset), `${VAR:-fallback}` by
+/// the fallback when the variable is unset or empty, and `$${` by a literal `${`.
+fn expand_env(text: &str, lookup: &dyn Fn(&str) -> Option<String>) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('$') {
+        out.push_str(&rest[..start]);
+        let tail = &rest[start..];
+        if let Some(escaped) = tail.strip_prefix("$${") {
+            out.push_str("${");
+            rest = escaped;
+        } else if let Some((reference, after)) = tail
+            .strip_prefix("${")
+            .and_then(|body| body.split_once('}'))
+        {
+            let (name, fallback) = match reference.split_once(":-") {
+                Some((name, fallback)) => (name, Some(fallback)),
+                None => (reference, None),
+            };
+            let value = lookup(name.trim()).filter(|value| !value.is_empty());
+            out.push_str(&value.unwrap_or_else(|| fallback.unwrap_or_default().to_string()));
+            rest = after;
+        } else {
+            out.push('$');
+            rest = &tail[1..];
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Where a config layer is read from.
+#[derive(Debug, Clone, PartialEq)]
+enum LayerSource {
+    File(PathBuf),
+    /// A URL (see `remote_config::remote_url`), optionally pinned to the content's sha256.
+    Remote {
+        url: String,
+        sha256: Option<String>,
+    },
+}
+
+impl std::fmt::Display for LayerSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LayerSource::File(path) => write!(f, "{}", path.display()),
+            LayerSource::Remote { url, .. } => f.write_str(url),
+        }
+    }
+}
+
+/// Configs named by the `extends` key of the config read from `from`. Entries are a path,
+/// URL, or GitHub `owner/repo@ref`, or a mapping of `source` and a `sha256` pin; local
+/// paths resolve against the extending file's directory (or URL).
+fn extends_of(value: &serde_yaml::Value, from: &LayerSource) -> Vec<LayerSource> {
+    let entry = |entry: &serde_yaml::Value| match entry {
+        serde_yaml::Value::String(source) => Some((source.clone(), None)),
+        serde_yaml::Value::Mapping(_) => Some((
+            entry.get("source")?.as_str()?.to_string(),
+            entry
+                .get("sha256")
+                .and_then(serde_yaml::Value::as_str)
+                .map(str::to_string),
+        )),
+        _ => None,
+    };
+    let entries: Vec<(String, Option<String>)> = match value.get("extends") {
+        Some(serde_yaml::Value::Sequence(entries)) => entries.iter().filter_map(entry).collect(),
+        Some(single) => entry(single).into_iter().collect(),
+        None => Vec::new(),
+    };
+    entries
+        .into_iter()
+        .map(|(source, sha256)| {
+            use crate::core::remote_config::{join_url, remote_url};
+            match (remote_url(&source), from) {
+                (Some(url), _) => LayerSource::Remote { url, sha256 },
+                (None, LayerSource::Remote { url, .. }) => LayerSource::Remote {
+                    url: join_url(url, &source),
+                    sha256,
+                },
+                (None, LayerSource::File(path)) => {
+                    LayerSource::File(path.parent().unwrap_or_else(|| Path::new(".")).join(source))
+                }
+            }
+        })
+        .collect()
+}
+
+/// The config from `source` merged over the configs it extends; `chain` holds the sources
+/// being resolved, to reject cycles.
+fn read_layer(source: &LayerSource, chain: &mut Vec<String>) -> Result<serde_yaml::Value> {
+    let (key, mut value) = match source {
+        LayerSource::File(path) => (
+            path.canonicalize()
+                .unwrap_or_else(|_| path.to_path_buf())
+                .display()
+                .to_string(),
+            read_yaml(path)?,
+        ),
+        LayerSource::Remote { url, sha256 } => {
+            use crate::core::remote_config;
+            let content = remote_config::load(
+                url,
+                sha256.as_deref(),
+                &remote_config::default_cache_dir(),
+                remote_config::CACHE_TTL,
+            )?;
+            (url.clone(), parse_layer(&content, url)?)
+        }
+    };
+    if chain.contains(&key) {
+        anyhow::bail!("{} extends itself", source);
+    }
+    let bases = extends_of(&value, source);
+    if let Some(mapping) = value.as_mapping_mut() {
+        mapping.remove("extends");
+    }
+
+    chain.push(key);
+    let mut merged = serde_yaml::Value::Mapping(Default::default());
+    for base in bases {
+        let base =
+            read_layer(&base, chain).with_context(|| format!("In `extends` of {}", source))?;
+        merge_yaml(&mut merged, base);
+    }
+    chain.pop();
+    merge_yaml(&mut merged, value);
+    Ok(merged)
+}
+
+/// Overlays `layer` on `base`: mappings merge key by key, anything else replaces.
+fn merge_yaml(base: &mut serde_yaml::Value, layer: serde_yaml::Value) {
+    match (base, layer) {
+        (serde_yaml::Value::Mapping(base), serde_yaml::Value::Mapping(layer)) => {
+            for (key, value) in layer {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_yaml(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, layer) => *base = layer,
+    }
+}
+
+/// Settings whose keys are chosen by the user (extensions, paths, providers, plugin ids), so
+/// `validate` does not treat their entries as unknown settings.
+const FREE_FORM_KEYS: &[&str] = &[
+    "symbol_index_lsp_languages",
+    "symbol_index_lsp_commands",
+    "rate_limits",
+    "model_pricing",
+    "category_weights",
+    "language_guidance.overrides",
+    "paths",
+    "plugins.settings",
+    "profiles",
+];
+
+/// A problem `validate` found in a config file, with its 1-based line when known.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigIssue {
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl std::fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}: {}", line, self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+/// Strictly checks config file `content`: syntax and type errors, which make `Config::load`
+/// fail, and keys no setting reads, which would otherwise be ignored without a word.
+pub fn validate(content: &str) -> Vec<ConfigIssue> {
+    if let Err(err) = serde_yaml::from_str::<Config>(content) {
+        static LOCATION: once_cell::sync::Lazy<regex::Regex> =
+            once_cell::sync::Lazy::new(|| regex::Regex::new(r" at line \d+ column \d+").unwrap());
+        return vec![ConfigIssue {
+            line: err.location().map(|location| location.line()),
+            message: LOCATION.replace(&err.to_string(), "").into_owned(),
+        }];
+    }
+    let (Ok(known), Ok(actual)) = (
+        serde_yaml::to_value(Config::default()),
+        serde_yaml::from_str::<serde_yaml::Value>(content),
+    ) else {
+        return Vec::new();
+    };
+    let mut issues = Vec::new();
+    let mut unknown = Vec::new();
+    collect_unknown_keys(&known, &actual, "", &mut unknown);
+    // Each profile holds top-level settings.
+    if let Some(profiles) = actual
+        .get("profiles")
+        .and_then(serde_yaml::Value::as_mapping)
+    {
+        for (name, profile) in profiles {
+            let path = format!("profiles.{}", name.as_str().unwrap_or_default());
+            if let Err(err) = serde_yaml::from_value::<Config>(profile.clone()) {
+                issues.push(ConfigIssue {
+                    line: key_line(content, &path),
+                    message: format!("{}: {}", path, err),
+                });
+            }
+            collect_unknown_keys(&known, profile, &path, &mut unknown);
+        }
+    }
+    issues.extend(unknown.into_iter().map(|(path, candidates)| {
+        let key = path.rsplit('.').next().unwrap_or(&path);
+        let message = match closest_key(key, &candidates) {
+            Some(suggestion) => {
+                format!("unknown key `{}` (did you mean `{}`?)", path, suggestion)
+            }
+            None => format!("unknown key `{}`", path),
+        };
+        ConfigIssue {
+            line: key_line(content, &path),
+            message,
+        }
+    }));
+    let known_languages: Vec<String> = crate::core::languages::names()
+        .into_iter()
+        .map(str::to_string)
+        .collect();
+    for list in ["include", "exclude"] {
+        let names = actual
+            .get("languages")
+            .and_then(|languages| languages.get(list))
+            .and_then(serde_yaml::Value::as_sequence);
+        for name in names
+            .into_iter()
+            .flatten()
+            .filter_map(serde_yaml::Value::as_str)
+        {
+            if known_languages
+                .iter()
+                .any(|known| known.eq_ignore_ascii_case(name))
+            {
+                continue;
+            }
+            let message = match closest_key(&name.to_lowercase(), &known_languages) {
+                Some(suggestion) => format!(
+                    "unknown language `{}` in languages.{} (did you mean `{}`?)",
+                    name, list, suggestion
+                ),
+                None => format!("unknown language `{}` in languages.{}", name, list),
+            };
+            issues.push(ConfigIssue {
+                line: key_line(content, &format!("languages.{}", list)),
+                message,
+            });
+        }
+    }
+    issues
+}
+
+/// Dotted paths of keys in `actual` missing from `known`, each with the keys that were valid
+/// at its level.
+fn collect_unknown_keys(
+    known: &serde_yaml::Value,
+    actual: &serde_yaml::Value,
+    prefix: &str,
+    unknown: &mut Vec<(String, Vec<String>)>,
+) {
+    let (Some(known), Some(actual)) = (known.as_mapping(), actual.as_mapping()) else {
+        return;
+    };
+    for (key, value) in actual {
+        let Some(key) = key.as_str() else { continue };
+        let path = if prefix.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        match known.get(key) {
+            None if path == "extends" => {}
+            Some(_) if FREE_FORM_KEYS.contains(&path.as_str()) => {}
+            Some(expected) => collect_unknown_keys(expected, value, &path, unknown),
+            None => unknown.push((
+                path,
+                known
+                    .keys()
+                    .filter_map(|key| key.as_str().map(str::to_string))
+                    .collect(),
+            )),
+        }
+    }
+}
+
+/// The candidate within two edits of `key`, for typos like `max_token`.
+fn closest_key<'a>(key: &str, candidates: &'a [String]) -> Option<&'a str> {
+    let distance = |a: &str, b: &str| {
+        let b: Vec<char> = b.chars().collect();
+        let mut row: Vec<usize> = (0..=b.len()).collect();
+        for (i, ca) in a.chars().enumerate() {
+            let mut previous = row[0];
+            row[0] = i + 1;
+            for (j, cb) in b.iter().enumerate() {
+                let substitution = previous + usize::from(ca != *cb);
+                previous = row[j + 1];
+                row[j + 1] = substitution.min(row[j] + 1).min(row[j + 1] + 1);
+            }
+        }
+        row[b.len()]
+    };
+    candidates
+        .iter()
+        .map(|candidate| (distance(key, candidate), candidate))
+        .filter(|(distance, _)| *distance <= 2)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate.as_str())
+}
+
+/// 1-based line of the block-style key at dotted `path`, found by following indentation.
+fn key_line(content: &str, path: &str) -> Option<usize> {
+    let target: Vec<&str> = path.split('.').collect();
+    let mut stack: Vec<(usize, &str)> = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with('-') {
+            continue;
+        }
+        let Some((key, _)) = trimmed.split_once(':') else {
+            continue;
+        };
+        let indent = line.len() - trimmed.len();
+        while stack.last().is_some_and(|(depth, _)| *depth >= indent) {
+            stack.pop();
+        }
+        stack.push((indent, key.trim().trim_matches(['"', '\''])));
+        if stack.iter().map(|(_, key)| *key).eq(target.iter().copied()) {
+            return Some(index + 1);
+        }
+    }
+    None
+}
+
+/// `config` as YAML with API keys, tokens, and secrets masked, for `diffscope config show`.
+pub fn to_redacted_yaml(config: &Config) -> Result<String> {
+    fn redact(value: &mut serde_yaml::Value) {
+        if let Some(mapping) = value.as_mapping_mut() {
+            for (key, value) in mapping.iter_mut() {
+                let key = key.as_str().unwrap_or_default();
+                let secret = key == "api_key" || key.ends_with("token") || key.ends_with("secret");
+                if secret && !value.is_null() {
+                    *value = serde_yaml::Value::String("<redacted>".to_string());
+                } else {
+                    redact(value);
+                }
+            }
+        }
+    }
+    let mut value = serde_yaml::to_value(config)?;
+    redact(&mut value);
+    Ok(serde_yaml::to_string(&value)?)
+}
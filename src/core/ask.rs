@@ -0,0 +1,76 @@
+use crate::core::prompt::{PromptBuilder, PromptConfig};
+use crate::core::{LLMContextChunk, PromptLimits, UnifiedDiff};
+use anyhow::Result;
+
+const SYSTEM_PROMPT: &str = "You are a senior engineer helping a colleague understand a code change. Answer the question using the diff and the surrounding code provided. Cite files and line numbers when they support the answer, and say so plainly when the change does not answer the question.";
+
+const FILE_TEMPLATE: &str = "<diff>\n{diff}</diff>\n\n<context>\n{context}\n</context>";
+
+/// Each diff in `files` followed by the context fetched for it, formatted like review
+/// prompts and held to the same `limits` per file.
+pub fn format_files(
+    files: &[(UnifiedDiff, Vec<LLMContextChunk>)],
+    limits: &PromptLimits,
+) -> Result<String> {
+    let builder = PromptBuilder::new(PromptConfig {
+        user_prompt_template: FILE_TEMPLATE.to_string(),
+        limits: *limits,
+        ..PromptConfig::default()
+    });
+    let mut output = String::new();
+    for (diff, context) in files {
+        let (_, section) = builder.build_prompt(diff, context)?;
+        output.push_str(&section);
+        output.push_str("\n\n");
+    }
+    Ok(output)
+}
+
+/// The system and user prompt for a free-form `question` about `files`.
+pub fn build_prompt(
+    question: &str,
+    files: &[(UnifiedDiff, Vec<LLMContextChunk>)],
+    limits: &PromptLimits,
+) -> Result<(String, String)> {
+    let user_prompt = format!(
+        "{}<question>\n{}\n</question>",
+        format_files(files, limits)?,
+        question.trim()
+    );
+    Ok((SYSTEM_PROMPT.to_string(), user_prompt))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{ContextType, DiffParser};
+
+    #[test]
+    fn includes_every_file_its_context_and_the_question() {
+        let diffs = DiffParser::parse_unified_diff(
+            "--- a/src/retry.rs\n+++ b/src/retry.rs\n@@ -1,3 +1,1 @@\n-for _ in 0..3 {\n-    send()?;\n-}\n+send()?;\n--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1,1 +1,1 @@\n-mod retry;\n+pub mod retry;\n",
+        )
+        .unwrap();
+        let context = vec![LLMContextChunk {
+            content: "fn send() -> Result<()>".to_string(),
+            context_type: ContextType::Definition,
+            file_path: "src/net.rs".into(),
+            line_range: Some((10, 10)),
+        }];
+        let files: Vec<_> = diffs.into_iter().zip([context, Vec::new()]).collect();
+
+        let (system, user) = build_prompt(
+            " Why was the retry loop removed? ",
+            &files,
+            &PromptLimits::default(),
+        )
+        .unwrap();
+
+        assert_eq!(system, SYSTEM_PROMPT);
+        assert!(user.contains("File: src/retry.rs\n"));
+        assert!(user.contains("-for _ in 0..3 {\n"));
+        assert!(user.contains("File: src/lib.rs\n"));
+        assert!(user.contains("[Definition - src/net.rs:10-10]\nfn send() -> Result<()>"));
+        assert!(user.ends_with("<question>\nWhy was the retry loop removed?\n</question>"));
+    }
+}
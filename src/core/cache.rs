@@ -0,0 +1,182 @@
+use crate::core::comment::Comment;
+use crate::core::diff_parser::{ChangeType, DiffHunk, UnifiedDiff};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Bump when prompt templates or comment parsing change in ways that invalidate cached reviews.
+pub const REVIEW_PROMPT_VERSION: &str = "1";
+
+/// Comments for one reviewed hunk. Line numbers are stored relative to the hunk start so a
+/// cached review still lands on the right lines after the hunk moves (e.g. after a rebase).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedHunkReview {
+    key: String,
+    file_path: PathBuf,
+    comments: Vec<CachedComment>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedComment {
+    line_offset: usize,
+    comment: Comment,
+}
+
+/// On-disk, content-addressed cache of review comments keyed by
+/// file path + hunk content + model + prompt version.
+pub struct ReviewCache {
+    dir: PathBuf,
+    model: String,
+}
+
+impl ReviewCache {
+    pub fn new(dir: PathBuf, model: &str) -> Self {
+        Self {
+            dir,
+            model: model.to_string(),
+        }
+    }
+
+    pub fn default_dir() -> PathBuf {
+        dirs::cache_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join("diffscope")
+            .join("reviews")
+    }
+
+    /// Splits `diff` into comments already cached for unchanged hunks and a diff holding
+    /// only the hunks that still need an LLM review. `prompt` fingerprints the prompt in use.
+    pub fn partition(&self, diff: &UnifiedDiff, prompt: &str) -> (Vec<Comment>, UnifiedDiff) {
+        let mut cached = Vec::new();
+        let mut pending = diff.clone();
+        pending.hunks.clear();
+
+        for hunk in &diff.hunks {
+            match self.load(&self.hunk_key(&diff.file_path, hunk, prompt)) {
+                Some(entry) => {
+                    cached.extend(entry.comments.into_iter().map(|cached| {
+                        let mut comment = cached.comment;
+                        comment.file_path = diff.file_path.clone();
+                        comment.line_number = hunk.new_start + cached.line_offset;
+                        comment
+                    }));
+                }
+                None => pending.hunks.push(hunk.clone()),
+            }
+        }
+
+        (cached, pending)
+    }
+
+    /// Records the comments produced for `diff`, including hunks that produced none.
+    pub fn store(&self, diff: &UnifiedDiff, prompt: &str, comments: &[Comment]) -> Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        for hunk in &diff.hunks {
+            let key = self.hunk_key(&diff.file_path, hunk, prompt);
+            let entry = CachedHunkReview {
+                key: key.clone(),
+                file_path: diff.file_path.clone(),
+                comments: comments
+                    .iter()
+                    .filter(|comment| hunk_contains_line(hunk, comment.line_number))
+                    .map(|comment| CachedComment {
+                        line_offset: comment.line_number.saturating_sub(hunk.new_start),
+                        comment: comment.clone(),
+                    })
+                    .collect(),
+            };
+            std::fs::write(self.entry_path(&key), serde_json::to_string_pretty(&entry)?)?;
+        }
+        Ok(())
+    }
+
+    fn load(&self, key: &str) -> Option<CachedHunkReview> {
+        let content = std::fs::read_to_string(self.entry_path(key)).ok()?;
+        let entry: CachedHunkReview = serde_json::from_str(&content).ok()?;
+        (entry.key == key).then_some(entry)
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", key))
+    }
+
+    fn hunk_key(&self, file_path: &Path, hunk: &DiffHunk, prompt: &str) -> String {
+        // Only line content goes into the key, so moved-but-identical hunks still hit.
+        let mut changes = String::new();
+        for line in &hunk.changes {
+            changes.push(match line.change_type {
+                ChangeType::Added => '+',
+                ChangeType::Removed => '-',
+                ChangeType::Context => ' ',
+            });
+            changes.push_str(&line.content);
+            changes.push('\n');
+        }
+        let path = file_path.to_string_lossy();
+        stable_hash(&[REVIEW_PROMPT_VERSION, &self.model, prompt, &path, &changes])
+    }
+}
+
+fn hunk_contains_line(hunk: &DiffHunk, line_number: usize) -> bool {
+    hunk.changes
+        .iter()
+        .any(|line| line.new_line_no == Some(line_number))
+}
+
+/// FNV-1a hash of `parts`, stable across runs and Rust versions (unlike `DefaultHasher`).
+pub fn stable_hash(parts: &[&str]) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for part in parts {
+        for &byte in part.as_bytes().iter().chain(std::iter::once(&0u8)) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+    format!("{:016x}", hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::comment::test_comment;
+    use crate::core::DiffParser;
+
+    fn comment(file_path: &str, line_number: usize) -> Comment {
+        Comment {
+            confidence: 0.8,
+            ..test_comment(file_path, line_number, "Possible panic on empty input")
+        }
+    }
+
+    #[test]
+    fn cached_hunks_are_skipped_and_rebased_after_moving() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ReviewCache::new(dir.path().to_path_buf(), "gpt-4o");
+        let original = DiffParser::parse_unified_diff(
+            "--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -10,2 +10,3 @@\n fn run() {\n+    let first = items[0];\n }\n",
+        )
+        .unwrap()
+        .remove(0);
+
+        let (cached, pending) = cache.partition(&original, "prompt");
+        assert!(cached.is_empty());
+        assert_eq!(pending.hunks.len(), 1);
+        cache
+            .store(&pending, "prompt", &[comment("src/lib.rs", 11)])
+            .unwrap();
+
+        // Same hunk content, shifted down by 5 lines.
+        let moved = DiffParser::parse_unified_diff(
+            "--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -15,2 +15,3 @@\n fn run() {\n+    let first = items[0];\n }\n",
+        )
+        .unwrap()
+        .remove(0);
+        let (cached, pending) = cache.partition(&moved, "prompt");
+        assert!(pending.hunks.is_empty());
+        assert_eq!(cached.len(), 1);
+        assert_eq!(cached[0].line_number, 16);
+
+        let (_, pending) = cache.partition(&moved, "different prompt");
+        assert_eq!(pending.hunks.len(), 1);
+    }
+}
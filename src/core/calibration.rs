@@ -0,0 +1,152 @@
+use crate::core::feedback::FeedbackStore;
+use crate::core::Comment;
+use std::collections::HashMap;
+
+/// Confidence bands each category's curve is learned over: [0, 0.2), [0.2, 0.4), ... [0.8, 1].
+const BANDS: usize = 5;
+
+/// Decisions a category needs before its findings are recalibrated.
+const MIN_DECISIONS: usize = 5;
+
+/// How many decisions the stated confidence is worth. Sparse history moves confidence a
+/// little; a long record of rejections moves it most of the way to the observed rate.
+const PRIOR_WEIGHT: f32 = 10.0;
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Tally {
+    accepted: usize,
+    total: usize,
+}
+
+impl Tally {
+    fn add(&mut self, accepted: bool) {
+        self.total += 1;
+        if accepted {
+            self.accepted += 1;
+        }
+    }
+
+    /// The acceptance rate, pulled toward `prior` when there are few decisions.
+    fn rate(&self, prior: f32) -> f32 {
+        (self.accepted as f32 + PRIOR_WEIGHT * prior) / (self.total as f32 + PRIOR_WEIGHT)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Curve {
+    overall: Tally,
+    bands: [Tally; BANDS],
+}
+
+fn band(confidence: f32) -> usize {
+    ((confidence.clamp(0.0, 1.0) * BANDS as f32) as usize).min(BANDS - 1)
+}
+
+/// Per-category calibration curves learned from accept/reject history: how often findings of
+/// each category, posted at each confidence, were actually accepted.
+#[derive(Debug, Clone, Default)]
+pub struct CalibrationCurves {
+    categories: HashMap<String, Curve>,
+}
+
+impl CalibrationCurves {
+    /// Learns curves from the decided findings in `store`. Findings decided before their
+    /// confidence was recorded count toward their category's overall rate only.
+    pub fn from_feedback(store: &FeedbackStore) -> Self {
+        let mut categories: HashMap<String, Curve> = HashMap::new();
+        for (id, finding) in &store.decided {
+            let accepted = store.accept.contains(id);
+            if !accepted && !store.suppress.contains(id) {
+                continue;
+            }
+            let curve = categories.entry(finding.category.clone()).or_default();
+            curve.overall.add(accepted);
+            if let Some(confidence) = finding.confidence {
+                curve.bands[band(confidence)].add(accepted);
+            }
+        }
+        categories.retain(|_, curve| curve.overall.total >= MIN_DECISIONS);
+        Self { categories }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.categories.is_empty()
+    }
+
+    /// The calibrated confidence of a finding in `category` stated at `confidence`: its
+    /// band's acceptance rate, which falls back on the category's overall rate and then on
+    /// `confidence` itself where decisions are sparse. Unknown categories keep `confidence`.
+    pub fn calibrate(&self, category: &str, confidence: f32) -> f32 {
+        let Some(curve) = self.categories.get(category) else {
+            return confidence;
+        };
+        let overall = curve.overall.rate(confidence);
+        curve.bands[band(confidence)].rate(overall)
+    }
+
+    /// Replaces each comment's confidence with its calibrated value; returns how many changed.
+    pub fn apply(&self, comments: &mut [Comment]) -> usize {
+        let mut changed = 0;
+        for comment in comments {
+            let calibrated = self.calibrate(&format!("{:?}", comment.category), comment.confidence);
+            if (calibrated - comment.confidence).abs() > f32::EPSILON {
+                comment.confidence = calibrated;
+                changed += 1;
+            }
+        }
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::feedback::DecidedFinding;
+
+    fn decide(store: &mut FeedbackStore, id: &str, category: &str, confidence: f32, accept: bool) {
+        store.decided.insert(
+            id.to_string(),
+            DecidedFinding {
+                category: category.to_string(),
+                confidence: Some(confidence),
+            },
+        );
+        if accept {
+            store.accept.insert(id.to_string());
+        } else {
+            store.suppress.insert(id.to_string());
+        }
+    }
+
+    #[test]
+    fn downweights_categories_that_are_usually_rejected() {
+        let mut store = FeedbackStore::default();
+        for i in 0..50 {
+            decide(&mut store, &format!("style{}", i), "Style", 0.9, i % 5 == 0);
+            decide(&mut store, &format!("bug{}", i), "Bug", 0.9, i % 10 != 0);
+        }
+        for i in 0..3 {
+            decide(
+                &mut store,
+                &format!("docs{}", i),
+                "Documentation",
+                0.9,
+                false,
+            );
+        }
+
+        let curves = CalibrationCurves::from_feedback(&store);
+
+        // 10 of 50 accepted: a 90% Style finding is worth about 22%.
+        let style = curves.calibrate("Style", 0.9);
+        assert!((style - 0.22).abs() < 0.01, "{}", style);
+        let bug = curves.calibrate("Bug", 0.9);
+        assert!((bug - 0.9).abs() < 0.01, "{}", bug);
+        // Too few decisions to learn from, or no history at all.
+        assert_eq!(curves.calibrate("Documentation", 0.9), 0.9);
+        assert_eq!(curves.calibrate("Security", 0.7), 0.7);
+        // A band without history leans on the category's overall rate.
+        let low_style = curves.calibrate("Style", 0.3);
+        assert!(low_style < 0.3, "{}", low_style);
+    }
+}
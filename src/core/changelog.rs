@@ -1,21 +1,30 @@
 use anyhow::Result;
 use chrono::{DateTime, Local};
 use git2::Repository;
+use once_cell::sync::Lazy;
 use regex::Regex;
+use serde::{Serialize, Serializer};
 use std::collections::HashMap;
+use std::path::PathBuf;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ChangelogEntry {
     pub commit_hash: String,
     pub message: String,
     pub author: String,
-    pub _date: DateTime<Local>,
+    #[serde(serialize_with = "serialize_date")]
+    pub date: DateTime<Local>,
     pub change_type: ChangeType,
     pub scope: Option<String>,
     pub breaking: bool,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+fn serialize_date<S: Serializer>(date: &DateTime<Local>, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&date.to_rfc3339())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ChangeType {
     Feature,
     Fix,
@@ -81,9 +90,28 @@ impl ChangeType {
     }
 }
 
+/// Order of the per-type sections in the changelog.
+const TYPE_ORDER: [ChangeType; 11] = [
+    ChangeType::Feature,
+    ChangeType::Fix,
+    ChangeType::Perf,
+    ChangeType::Refactor,
+    ChangeType::Revert,
+    ChangeType::Docs,
+    ChangeType::Test,
+    ChangeType::Build,
+    ChangeType::Ci,
+    ChangeType::Style,
+    ChangeType::Chore,
+];
+
 pub struct ChangelogGenerator {
     repo: Repository,
     conventional_regex: Regex,
+    /// Only commits touching these paths (relative to the repository root); all when empty.
+    paths: Vec<PathBuf>,
+    /// Only commits with this conventional-commit scope.
+    scope: Option<String>,
 }
 
 impl ChangelogGenerator {
@@ -96,9 +124,37 @@ impl ChangelogGenerator {
         Ok(Self {
             repo,
             conventional_regex,
+            paths: Vec::new(),
+            scope: None,
         })
     }
 
+    /// Limits the changelog to commits touching `paths`, e.g. one package of a monorepo.
+    pub fn with_paths(mut self, paths: Vec<PathBuf>) -> Self {
+        self.paths = paths;
+        self
+    }
+
+    /// Limits the changelog to commits with the conventional-commit `scope`.
+    pub fn with_scope(mut self, scope: Option<String>) -> Self {
+        self.scope = scope;
+        self
+    }
+
+    /// Distinct conventional-commit scopes of the commits after `from_tag` up to `to_ref`, in
+    /// order of first appearance.
+    pub fn scopes(&self, from_tag: Option<&str>, to_ref: &str) -> Result<Vec<String>> {
+        let mut scopes: Vec<String> = Vec::new();
+        for entry in self.collect_entries(from_tag, to_ref)? {
+            if let Some(scope) = entry.scope {
+                if !scopes.contains(&scope) {
+                    scopes.push(scope);
+                }
+            }
+        }
+        Ok(scopes)
+    }
+
     pub fn generate_changelog(&self, from_tag: Option<&str>, to_ref: &str) -> Result<String> {
         let entries = self.collect_entries(from_tag, to_ref)?;
         Ok(self.format_changelog(&entries, from_tag, to_ref))
@@ -109,7 +165,12 @@ impl ChangelogGenerator {
         Ok(self.format_release_notes(&entries, version))
     }
 
-    fn collect_entries(&self, from_tag: Option<&str>, to_ref: &str) -> Result<Vec<ChangelogEntry>> {
+    /// Commits after `from_tag` up to `to_ref`, oldest first, skipping merges.
+    pub fn collect_entries(
+        &self,
+        from_tag: Option<&str>,
+        to_ref: &str,
+    ) -> Result<Vec<ChangelogEntry>> {
         let mut revwalk = self.repo.revwalk()?;
 
         // Start from the target ref
@@ -135,9 +196,14 @@ impl ChangelogGenerator {
             if commit.parent_count() > 1 {
                 continue;
             }
+            if !self.paths.is_empty() && !self.touches_paths(&commit)? {
+                continue;
+            }
 
             if let Some(entry) = self.parse_commit(&commit)? {
-                entries.push(entry);
+                if self.scope.is_none() || entry.scope == self.scope {
+                    entries.push(entry);
+                }
             }
         }
 
@@ -145,6 +211,23 @@ impl ChangelogGenerator {
         Ok(entries)
     }
 
+    fn touches_paths(&self, commit: &git2::Commit) -> Result<bool> {
+        let mut options = git2::DiffOptions::new();
+        for path in &self.paths {
+            options.pathspec(path);
+        }
+        let parent = match commit.parents().next() {
+            Some(parent) => Some(parent.tree()?),
+            None => None,
+        };
+        let diff = self.repo.diff_tree_to_tree(
+            parent.as_ref(),
+            Some(&commit.tree()?),
+            Some(&mut options),
+        )?;
+        Ok(diff.deltas().len() > 0)
+    }
+
     fn parse_commit(&self, commit: &git2::Commit) -> Result<Option<ChangelogEntry>> {
         let message = commit.message().unwrap_or("");
         let first_line = message.lines().next().unwrap_or("");
@@ -160,7 +243,7 @@ impl ChangelogGenerator {
                 commit_hash: format!("{:.7}", commit.id()),
                 message: description,
                 author: commit.author().name().unwrap_or("Unknown").to_string(),
-                _date: DateTime::from_timestamp(commit.time().seconds(), 0)
+                date: DateTime::from_timestamp(commit.time().seconds(), 0)
                     .unwrap_or_default()
                     .with_timezone(&Local),
                 change_type,
@@ -181,7 +264,7 @@ impl ChangelogGenerator {
                 commit_hash: format!("{:.7}", commit.id()),
                 message: first_line.to_string(),
                 author: commit.author().name().unwrap_or("Unknown").to_string(),
-                _date: DateTime::from_timestamp(commit.time().seconds(), 0)
+                date: DateTime::from_timestamp(commit.time().seconds(), 0)
                     .unwrap_or_default()
                     .with_timezone(&Local),
                 change_type,
@@ -233,20 +316,7 @@ impl ChangelogGenerator {
         }
 
         // Then by category
-        let type_order = [
-            ChangeType::Feature,
-            ChangeType::Fix,
-            ChangeType::Perf,
-            ChangeType::Refactor,
-            ChangeType::Docs,
-            ChangeType::Test,
-            ChangeType::Build,
-            ChangeType::Ci,
-            ChangeType::Style,
-            ChangeType::Chore,
-        ];
-
-        for change_type in &type_order {
+        for change_type in &TYPE_ORDER {
             if let Some(entries) = grouped.get(change_type) {
                 if !entries.is_empty() {
                     output.push_str(&format!(
@@ -352,7 +422,7 @@ impl ChangelogGenerator {
         }
 
         let mut contributors: Vec<_> = contributors.into_iter().collect();
-        contributors.sort_by(|a, b| b.1.cmp(&a.1));
+        contributors.sort_by_key(|b| std::cmp::Reverse(b.1));
 
         output.push_str("## 👥 Contributors\n\n");
         output.push_str("Thank you to all contributors:\n\n");
@@ -363,3 +433,431 @@ impl ChangelogGenerator {
         output
     }
 }
+
+/// Keep a Changelog sections, in the order the format lists them.
+const KEEP_A_CHANGELOG_SECTIONS: [&str; 6] = [
+    "Added",
+    "Changed",
+    "Deprecated",
+    "Removed",
+    "Fixed",
+    "Security",
+];
+
+/// The Keep a Changelog section for `entry`, or `None` for changes users do not see (docs,
+/// tests, build, CI, style, and chores). Deprecations, removals, and security fixes are marked
+/// by their conventional-commit scope, e.g. `fix(security): ...`; the message is not searched,
+/// so "add security headers" stays under Added.
+fn keep_a_changelog_section(entry: &ChangelogEntry) -> Option<&'static str> {
+    let scope = entry.scope.as_deref().unwrap_or_default().to_lowercase();
+    match scope.as_str() {
+        "security" => return Some("Security"),
+        "deprecate" | "deprecated" | "deprecation" => return Some("Deprecated"),
+        "remove" | "removed" | "removal" => return Some("Removed"),
+        _ => {}
+    }
+    match entry.change_type {
+        ChangeType::Feature => Some("Added"),
+        ChangeType::Fix => Some("Fixed"),
+        ChangeType::Perf | ChangeType::Refactor | ChangeType::Revert => Some("Changed"),
+        _ if entry.breaking => Some("Changed"),
+        _ => None,
+    }
+}
+
+/// `entries` as a [Keep a Changelog](https://keepachangelog.com/en/1.1.0/) document with one
+/// release, headed `version` and `date`, or `Unreleased` without a version.
+pub fn format_keep_a_changelog(
+    entries: &[ChangelogEntry],
+    version: Option<&str>,
+    date: &str,
+) -> String {
+    let mut output = String::from(
+        "# Changelog\n\nAll notable changes to this project will be documented in this file.\n\nThe format is based on [Keep a Changelog](https://keepachangelog.com/en/1.1.0/),\nand this project adheres to [Semantic Versioning](https://semver.org/spec/v2.0.0.html).\n\n",
+    );
+    match version {
+        Some(version) => output.push_str(&format!(
+            "## [{}] - {}\n\n",
+            version.trim_start_matches('v'),
+            date
+        )),
+        None => output.push_str("## [Unreleased]\n\n"),
+    }
+
+    let mut sections: HashMap<&str, Vec<&ChangelogEntry>> = HashMap::new();
+    for entry in entries {
+        if let Some(section) = keep_a_changelog_section(entry) {
+            sections.entry(section).or_default().push(entry);
+        }
+    }
+    for section in KEEP_A_CHANGELOG_SECTIONS {
+        let Some(entries) = sections.get(section) else {
+            continue;
+        };
+        output.push_str(&format!("### {}\n\n", section));
+        for entry in entries {
+            output.push_str("- ");
+            if entry.breaking {
+                output.push_str("**BREAKING:** ");
+            }
+            if let Some(scope) = &entry.scope {
+                output.push_str(&format!("**{}:** ", scope));
+            }
+            output.push_str(&format!("{} ({})\n", entry.message, entry.commit_hash));
+        }
+        output.push('\n');
+    }
+    output
+}
+
+/// Entries sent to the model for `--ai-summary`; older ones past this are left out.
+const MAX_SUMMARY_ENTRIES: usize = 400;
+
+/// Area for entries without a conventional-commit scope.
+const GENERAL_AREA: &str = "general";
+
+/// Model-written prose for a release: an overview and a summary per area (commit scope).
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ReleaseSummary {
+    pub highlights: String,
+    pub areas: Vec<AreaSummary>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AreaSummary {
+    pub area: String,
+    pub summary: String,
+}
+
+impl ReleaseSummary {
+    /// Highlights and per-area sections, with headings at `level` (2 for `##`).
+    pub fn to_markdown(&self, level: usize) -> String {
+        let heading = "#".repeat(level);
+        let mut output = format!("{} Highlights\n\n{}\n\n", heading, self.highlights);
+        if !self.areas.is_empty() {
+            output.push_str(&format!("{} By Area\n\n", heading));
+            for area in &self.areas {
+                output.push_str(&format!("**{}:** {}\n\n", area.area, area.summary));
+            }
+        }
+        output
+    }
+}
+
+/// `document` with `summary` inserted before its first heading at `level`, or appended when
+/// there is none.
+pub fn insert_summary(document: &str, summary: &ReleaseSummary, level: usize) -> String {
+    let heading = format!("{} ", "#".repeat(level));
+    let position = document
+        .match_indices(&format!("\n{}", heading))
+        .next()
+        .map(|(index, _)| index + 1)
+        .unwrap_or(document.len());
+    let mut output = document[..position].to_string();
+    if !output.is_empty() && !output.ends_with("\n\n") {
+        output.push_str(if output.ends_with('\n') { "\n" } else { "\n\n" });
+    }
+    output.push_str(&summary.to_markdown(level));
+    output.push_str(&document[position..]);
+    output
+}
+
+/// System and user prompt asking the model to summarize `entries` as release prose.
+pub fn build_summary_prompt(entries: &[ChangelogEntry], version: Option<&str>) -> (String, String) {
+    let system_prompt = "You write release notes for software projects. You turn a list of commits into short, accurate prose for the people who use the project. You never mention a change that is not in the list.".to_string();
+
+    let mut areas: Vec<(&str, Vec<&ChangelogEntry>)> = Vec::new();
+    let start = entries.len().saturating_sub(MAX_SUMMARY_ENTRIES);
+    for entry in &entries[start..] {
+        let area = entry.scope.as_deref().unwrap_or(GENERAL_AREA);
+        match areas.iter_mut().find(|(name, _)| *name == area) {
+            Some((_, entries)) => entries.push(entry),
+            None => areas.push((area, vec![entry])),
+        }
+    }
+    let mut commits = String::new();
+    for (area, entries) in &areas {
+        commits.push_str(&format!("<area name=\"{}\">\n", area));
+        for entry in entries {
+            commits.push_str(&format!(
+                "- [{}{}] {} ({})\n",
+                entry.change_type.heading(),
+                if entry.breaking { ", BREAKING" } else { "" },
+                entry.message,
+                entry.commit_hash
+            ));
+        }
+        commits.push_str("</area>\n");
+    }
+
+    let user_prompt = format!(
+        r#"<task>
+Summarize the commits in {} for the release notes.
+</task>
+
+<commits>
+{}</commits>
+
+<requirements>
+- Highlights: one or two short paragraphs on the changes that matter most to users, breaking changes first
+- One or two sentences per area, using the area names given
+- Plain language; no commit hashes and no bullet lists
+- Only describe changes in the list
+</requirements>
+
+<format>
+<highlights>
+...
+</highlights>
+<area name="...">...</area>
+</format>"#,
+        version.map_or_else(
+            || "this release".to_string(),
+            |version| format!("version {}", version)
+        ),
+        commits
+    );
+    (system_prompt, user_prompt)
+}
+
+/// The summary in a model response, or `None` when it has no highlights.
+pub fn parse_summary(response: &str) -> Option<ReleaseSummary> {
+    static HIGHLIGHTS: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"(?s)<highlights>(.*?)</highlights>").unwrap());
+    static AREA: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r#"(?s)<area name="([^"]*)">(.*?)</area>"#).unwrap());
+
+    let highlights = HIGHLIGHTS.captures(response)?[1].trim().to_string();
+    if highlights.is_empty() {
+        return None;
+    }
+    let areas = AREA
+        .captures_iter(response)
+        .map(|captures| AreaSummary {
+            area: captures[1].trim().to_string(),
+            summary: captures[2].trim().to_string(),
+        })
+        .filter(|area| !area.summary.is_empty())
+        .collect();
+    Some(ReleaseSummary { highlights, areas })
+}
+
+/// Values available to a `changelog_template`.
+#[derive(Debug, Serialize)]
+pub struct ChangelogContext<'a> {
+    pub entries: &'a [ChangelogEntry],
+    /// Entries grouped by change type, in changelog order; empty groups are left out.
+    pub groups: Vec<ChangelogGroup<'a>>,
+    pub breaking: Vec<&'a ChangelogEntry>,
+    pub from: Option<&'a str>,
+    pub to: &'a str,
+    pub version: Option<&'a str>,
+    /// Today, as `YYYY-MM-DD`.
+    pub date: String,
+    /// With `--ai-summary`, the model's highlights and per-area summaries.
+    pub summary: Option<&'a ReleaseSummary>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChangelogGroup<'a> {
+    pub change_type: ChangeType,
+    pub heading: &'static str,
+    pub entries: Vec<&'a ChangelogEntry>,
+}
+
+impl<'a> ChangelogContext<'a> {
+    pub fn new(
+        entries: &'a [ChangelogEntry],
+        from: Option<&'a str>,
+        to: &'a str,
+        version: Option<&'a str>,
+    ) -> Self {
+        let groups = TYPE_ORDER
+            .iter()
+            .map(|change_type| ChangelogGroup {
+                change_type: change_type.clone(),
+                heading: change_type.heading(),
+                entries: entries
+                    .iter()
+                    .filter(|entry| entry.change_type == *change_type)
+                    .collect(),
+            })
+            .filter(|group| !group.entries.is_empty())
+            .collect();
+        Self {
+            entries,
+            groups,
+            breaking: entries.iter().filter(|entry| entry.breaking).collect(),
+            from,
+            to,
+            version,
+            date: Local::now().format("%Y-%m-%d").to_string(),
+            summary: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(
+        change_type: ChangeType,
+        scope: Option<&str>,
+        message: &str,
+        breaking: bool,
+    ) -> ChangelogEntry {
+        ChangelogEntry {
+            commit_hash: "abc1234".to_string(),
+            message: message.to_string(),
+            author: "Dev".to_string(),
+            date: DateTime::from_timestamp(1_700_000_000, 0)
+                .unwrap()
+                .with_timezone(&Local),
+            change_type,
+            scope: scope.map(str::to_string),
+            breaking,
+        }
+    }
+
+    #[test]
+    fn prompts_by_area_and_parses_the_summary() {
+        let entries = vec![
+            entry(ChangeType::Feature, Some("cli"), "add --json flag", false),
+            entry(ChangeType::Fix, None, "handle empty diffs", true),
+        ];
+        let (_, prompt) = build_summary_prompt(&entries, Some("1.2.0"));
+        assert!(prompt.contains("version 1.2.0"));
+        assert!(
+            prompt.contains("<area name=\"cli\">\n- [Features] add --json flag (abc1234)\n</area>")
+        );
+        assert!(
+            prompt.contains("<area name=\"general\">\n- [Bug Fixes, BREAKING] handle empty diffs")
+        );
+
+        let summary = parse_summary(
+            "Sure!\n<highlights>\nThe CLI can now emit JSON.\n</highlights>\n<area name=\"cli\">Adds a --json flag.</area>\n<area name=\"general\"></area>",
+        )
+        .unwrap();
+        assert_eq!(summary.highlights, "The CLI can now emit JSON.");
+        assert_eq!(summary.areas.len(), 1);
+        assert_eq!(
+            summary.to_markdown(2),
+            "## Highlights\n\nThe CLI can now emit JSON.\n\n## By Area\n\n**cli:** Adds a --json flag.\n\n"
+        );
+        assert!(parse_summary("no tags here").is_none());
+
+        let document = "# Changelog\n\n## [Start - HEAD]\n\n### Features\n\n* add --json flag\n";
+        assert!(insert_summary(document, &summary, 3).starts_with(
+            "# Changelog\n\n## [Start - HEAD]\n\n### Highlights\n\nThe CLI can now emit JSON.\n\n### By Area\n\n**cli:** Adds a --json flag.\n\n### Features\n"
+        ));
+    }
+
+    #[test]
+    fn filters_commits_by_path_and_scope() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let signature = git2::Signature::now("Dev", "dev@example.com").unwrap();
+        for (path, message) in [
+            ("packages/cart/lib.rs", "feat(cart): add totals"),
+            ("packages/auth/lib.rs", "fix(auth): expire tokens"),
+            ("packages/cart/README.md", "docs: describe totals"),
+        ] {
+            let file = dir.path().join(path);
+            std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+            std::fs::write(&file, message).unwrap();
+            let mut index = repo.index().unwrap();
+            index.add_path(std::path::Path::new(path)).unwrap();
+            let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+            let parent = repo.head().ok().map(|head| head.peel_to_commit().unwrap());
+            let parents: Vec<&git2::Commit> = parent.iter().collect();
+            repo.commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                message,
+                &tree,
+                &parents,
+            )
+            .unwrap();
+        }
+        let root = dir.path().to_str().unwrap();
+        let messages = |generator: ChangelogGenerator| -> Vec<String> {
+            generator
+                .collect_entries(None, "HEAD")
+                .unwrap()
+                .into_iter()
+                .map(|entry| entry.message)
+                .collect()
+        };
+
+        let cart = ChangelogGenerator::new(root)
+            .unwrap()
+            .with_paths(vec![PathBuf::from("packages/cart")]);
+        assert_eq!(messages(cart), vec!["add totals", "describe totals"]);
+        let auth = ChangelogGenerator::new(root)
+            .unwrap()
+            .with_scope(Some("auth".to_string()));
+        assert_eq!(messages(auth), vec!["expire tokens"]);
+        assert_eq!(
+            ChangelogGenerator::new(root)
+                .unwrap()
+                .scopes(None, "HEAD")
+                .unwrap(),
+            vec!["cart", "auth"]
+        );
+    }
+
+    #[test]
+    fn groups_entries_into_keep_a_changelog_sections() {
+        let entries = vec![
+            entry(ChangeType::Feature, Some("cli"), "add --json flag", false),
+            entry(ChangeType::Fix, None, "handle empty diffs", false),
+            entry(
+                ChangeType::Refactor,
+                Some("removal"),
+                "drop legacy config loader",
+                true,
+            ),
+            entry(
+                ChangeType::Feature,
+                None,
+                "remove security prompts from setup",
+                false,
+            ),
+            entry(ChangeType::Chore, None, "bump deps", false),
+            entry(
+                ChangeType::Fix,
+                Some("security"),
+                "escape branch names",
+                false,
+            ),
+        ];
+
+        let output = format_keep_a_changelog(&entries, Some("v1.2.0"), "2024-05-01");
+        assert!(output.starts_with("# Changelog\n"));
+        assert!(output.contains(
+            "## [1.2.0] - 2024-05-01\n\n### Added\n\n- **cli:** add --json flag (abc1234)\n- remove security prompts from setup (abc1234)\n\n### Removed\n\n- **BREAKING:** **removal:** drop legacy config loader (abc1234)\n\n### Fixed\n\n- handle empty diffs (abc1234)\n\n### Security\n\n- **security:** escape branch names (abc1234)\n"
+        ));
+        assert!(!output.contains("bump deps"));
+        assert!(format_keep_a_changelog(&[], None, "2024-05-01").contains("## [Unreleased]\n"));
+
+        let json = serde_json::to_value(&entries[0]).unwrap();
+        assert_eq!(json["change_type"], "feature");
+        assert!(json["date"].as_str().unwrap().starts_with("2023-11-1"));
+
+        let example = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("examples/templates/changelog.md.j2");
+        let output = crate::core::report_template::render(
+            &example,
+            &ChangelogContext::new(&entries, Some("v1.1.0"), "HEAD", Some("1.2.0")),
+        )
+        .unwrap();
+        assert!(output.contains("## 1.2.0 ("), "{}", output);
+        assert!(
+            output.contains("### Features\n\n- **cli:** add --json flag"),
+            "{}",
+            output
+        );
+    }
+}
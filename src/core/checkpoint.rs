@@ -0,0 +1,212 @@
+use crate::core::cache::stable_hash;
+use crate::core::comment::Comment;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::warn;
+
+const DIFF_FILE: &str = "diff.patch";
+const FILES_DIR: &str = "files";
+/// Runs left behind longer than this are removed when a new one starts.
+const MAX_RUN_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// The comments produced for one file before post-processing.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FileResult {
+    file_path: PathBuf,
+    comments: Vec<Comment>,
+}
+
+/// A review in progress, kept in `.diffscope/run-<id>/`: the diff under review and the
+/// comments of each file as it completes, so `review --resume <id>` can skip finished files
+/// after a crash or network error. The run is deleted when dropped unless [`Self::keep`] was
+/// called.
+pub struct ReviewCheckpoint {
+    id: String,
+    dir: PathBuf,
+    keep: bool,
+}
+
+impl ReviewCheckpoint {
+    /// Starts a new run for `diff_content` under `repo_root`, removing stale earlier runs.
+    pub fn create(repo_root: &Path, diff_content: &str) -> Result<Self> {
+        prune_runs(repo_root, MAX_RUN_AGE);
+        let id = format!(
+            "{}-{}",
+            chrono::Utc::now().format("%Y%m%d-%H%M%S"),
+            &stable_hash(&[diff_content])[..6]
+        );
+        let dir = run_dir(repo_root, &id);
+        std::fs::create_dir_all(dir.join(FILES_DIR))
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+        // Keep run state out of `git status` even when `.diffscope/` is not ignored.
+        std::fs::write(dir.join(".gitignore"), "*\n")?;
+        std::fs::write(dir.join(DIFF_FILE), diff_content)?;
+        Ok(Self {
+            id,
+            dir,
+            keep: false,
+        })
+    }
+
+    /// Reopens the run `id` started by an earlier `review`.
+    pub fn open(repo_root: &Path, id: &str) -> Result<Self> {
+        let id = id.trim_start_matches("run-");
+        let dir = run_dir(repo_root, id);
+        if !dir.join(DIFF_FILE).is_file() {
+            anyhow::bail!(
+                "No review run {} in {} (runs are removed once they finish)",
+                id,
+                dir.display()
+            );
+        }
+        Ok(Self {
+            id: id.to_string(),
+            dir,
+            keep: false,
+        })
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// The diff the run was started with.
+    pub fn diff(&self) -> Result<String> {
+        Ok(std::fs::read_to_string(self.dir.join(DIFF_FILE))?)
+    }
+
+    /// Comments of the files that finished before the run stopped.
+    pub fn completed(&self) -> Result<HashMap<PathBuf, Vec<Comment>>> {
+        let mut completed = HashMap::new();
+        for entry in std::fs::read_dir(self.dir.join(FILES_DIR))? {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            let result: FileResult = serde_json::from_str(&std::fs::read_to_string(&path)?)
+                .with_context(|| format!("Corrupt checkpoint {}", path.display()))?;
+            completed.insert(result.file_path, result.comments);
+        }
+        Ok(completed)
+    }
+
+    /// Records that `file_path` finished with `comments`. The file is written under a temporary
+    /// name and renamed, so a crash never leaves a half-written result behind.
+    pub fn record(&self, file_path: &Path, comments: &[Comment]) -> Result<()> {
+        let name = stable_hash(&[&file_path.to_string_lossy()]);
+        let result = FileResult {
+            file_path: file_path.to_path_buf(),
+            comments: comments.to_vec(),
+        };
+        let target = self.dir.join(FILES_DIR).join(format!("{}.json", name));
+        let partial = target.with_extension("json.tmp");
+        std::fs::write(&partial, serde_json::to_string_pretty(&result)?)?;
+        std::fs::rename(&partial, &target)?;
+        Ok(())
+    }
+
+    /// Leaves the run on disk after this handle is dropped, so it can be resumed.
+    pub fn keep(&mut self) {
+        self.keep = true;
+    }
+}
+
+impl Drop for ReviewCheckpoint {
+    fn drop(&mut self) {
+        if self.keep {
+            return;
+        }
+        if let Err(err) = std::fs::remove_dir_all(&self.dir) {
+            if err.kind() != std::io::ErrorKind::NotFound {
+                warn!(
+                    "Failed to remove review run {}: {}",
+                    self.dir.display(),
+                    err
+                );
+            }
+        }
+    }
+}
+
+fn runs_dir(repo_root: &Path) -> PathBuf {
+    repo_root.join(".diffscope")
+}
+
+fn run_dir(repo_root: &Path, id: &str) -> PathBuf {
+    runs_dir(repo_root).join(format!("run-{}", id))
+}
+
+/// Removes runs under `repo_root` that were last written more than `max_age` ago.
+fn prune_runs(repo_root: &Path, max_age: Duration) {
+    let Ok(entries) = std::fs::read_dir(runs_dir(repo_root)) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        if !entry.file_name().to_string_lossy().starts_with("run-") {
+            continue;
+        }
+        let age = entry
+            .metadata()
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok());
+        if age.is_some_and(|age| age > max_age) {
+            if let Err(err) = std::fs::remove_dir_all(entry.path()) {
+                warn!(
+                    "Failed to remove stale review run {}: {}",
+                    entry.path().display(),
+                    err
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::comment::test_comment;
+
+    #[test]
+    fn resumes_recorded_files_and_removes_finished_runs() {
+        let repo = tempfile::tempdir().unwrap();
+        let diff = "--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1 +1 @@\n-a\n+b\n";
+        let checkpoint = ReviewCheckpoint::create(repo.path(), diff).unwrap();
+        let comment = test_comment("src/lib.rs", 1, "Finding");
+        checkpoint
+            .record(Path::new("src/lib.rs"), &[comment])
+            .unwrap();
+        checkpoint.record(Path::new("src/main.rs"), &[]).unwrap();
+
+        let resumed =
+            ReviewCheckpoint::open(repo.path(), &format!("run-{}", checkpoint.id())).unwrap();
+        assert_eq!(resumed.diff().unwrap(), diff);
+        let completed = resumed.completed().unwrap();
+        assert_eq!(completed.len(), 2);
+        assert_eq!(completed[Path::new("src/lib.rs")][0].content, "Finding");
+        assert!(completed[Path::new("src/main.rs")].is_empty());
+
+        let id = resumed.id().to_string();
+        drop(resumed);
+        assert!(ReviewCheckpoint::open(repo.path(), &id).is_err());
+    }
+
+    #[test]
+    fn kept_runs_survive_and_stale_runs_are_pruned() {
+        let repo = tempfile::tempdir().unwrap();
+        let mut checkpoint = ReviewCheckpoint::create(repo.path(), "diff").unwrap();
+        checkpoint.keep();
+        let id = checkpoint.id().to_string();
+        drop(checkpoint);
+        let mut reopened = ReviewCheckpoint::open(repo.path(), &id).unwrap();
+        reopened.keep();
+        drop(reopened);
+
+        std::thread::sleep(Duration::from_millis(20));
+        prune_runs(repo.path(), Duration::ZERO);
+        assert!(ReviewCheckpoint::open(repo.path(), &id).is_err());
+    }
+}
@@ -0,0 +1,121 @@
+use crate::core::comment::Comment;
+use std::path::Path;
+
+/// Where GitHub and GitLab look for the file, in the order they look.
+const LOCATIONS: &[&str] = &[
+    ".github/CODEOWNERS",
+    "CODEOWNERS",
+    "docs/CODEOWNERS",
+    ".gitlab/CODEOWNERS",
+];
+
+/// Ownership rules from a repository's CODEOWNERS file.
+#[derive(Debug, Clone, Default)]
+pub struct CodeOwners {
+    /// Each rule's patterns and owners, in file order; the last matching rule wins.
+    rules: Vec<(Vec<glob::Pattern>, Vec<String>)>,
+}
+
+impl CodeOwners {
+    /// The first CODEOWNERS file in `repo_root`, if there is one.
+    pub fn load(repo_root: &Path) -> Option<Self> {
+        LOCATIONS.iter().find_map(|location| {
+            let content = std::fs::read_to_string(repo_root.join(location)).ok()?;
+            Some(Self::parse(&content))
+        })
+    }
+
+    pub fn parse(content: &str) -> Self {
+        let rules = content
+            .lines()
+            .map(|line| line.split('#').next().unwrap_or_default().trim())
+            // GitLab section headers: `[Section]`, `^[Optional section]`.
+            .filter(|line| !line.is_empty() && !line.starts_with('[') && !line.starts_with("^["))
+            .filter_map(|line| {
+                let mut fields = line.split_whitespace();
+                let patterns = patterns_for(fields.next()?);
+                let owners = fields.map(str::to_string).collect();
+                (!patterns.is_empty()).then_some((patterns, owners))
+            })
+            .collect();
+        Self { rules }
+    }
+
+    /// Owners of `path` (relative to the repository root). A matching rule with no owners
+    /// leaves the file unowned.
+    pub fn owners_of(&self, path: &Path) -> Vec<String> {
+        let options = glob::MatchOptions {
+            require_literal_separator: true,
+            ..Default::default()
+        };
+        self.rules
+            .iter()
+            .rev()
+            .find(|(patterns, _)| {
+                patterns
+                    .iter()
+                    .any(|pattern| pattern.matches_path_with(path, options))
+            })
+            .map(|(_, owners)| owners.clone())
+            .unwrap_or_default()
+    }
+
+    /// Sets `owners` on each comment from its file.
+    pub fn assign(&self, comments: &mut [Comment]) {
+        for comment in comments {
+            comment.owners = self.owners_of(&comment.file_path);
+        }
+    }
+}
+
+/// Globs matching what a CODEOWNERS pattern does under gitignore rules: a pattern with a
+/// leading or inner slash is anchored to the root, others match at any depth, and a
+/// pattern naming a directory covers everything below it.
+fn patterns_for(pattern: &str) -> Vec<glob::Pattern> {
+    let directory_only = pattern.ends_with('/');
+    let trimmed = pattern.trim_end_matches('/');
+    let body = trimmed.trim_start_matches('/');
+    if body.is_empty() {
+        return Vec::new();
+    }
+    let base = if trimmed.starts_with('/') || body.contains('/') {
+        body.to_string()
+    } else {
+        format!("**/{}", body)
+    };
+    // `docs/*` covers the files directly in `docs`, not nested directories.
+    let mut globs = Vec::new();
+    if !base.ends_with("/*") {
+        globs.push(format!("{}/**", base));
+    }
+    if !directory_only {
+        globs.push(base);
+    }
+    globs
+        .iter()
+        .filter_map(|glob| glob::Pattern::new(glob).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn last_matching_rule_owns_the_file() {
+        let owners = CodeOwners::parse(
+            "# Default owners\n*       @acme/core\n*.js    @acme/web  # front end\n/build/logs/ @ops\ndocs/*  docs@acme.com\napps/   @acme/apps\n/apps/github\n[Security]\n**/auth/** @acme/security\n",
+        );
+        let owners_of = |path: &str| owners.owners_of(Path::new(path));
+
+        assert_eq!(owners_of("src/main.rs"), vec!["@acme/core"]);
+        assert_eq!(owners_of("web/src/app.js"), vec!["@acme/web"]);
+        assert_eq!(owners_of("build/logs/2024/run.log"), vec!["@ops"]);
+        assert_eq!(owners_of("tools/build/logs/run.log"), vec!["@acme/core"]);
+        assert_eq!(owners_of("docs/intro.md"), vec!["docs@acme.com"]);
+        assert_eq!(owners_of("docs/guides/setup.md"), vec!["@acme/core"]);
+        assert_eq!(owners_of("services/apps/api.rs"), vec!["@acme/apps"]);
+        assert!(owners_of("apps/github/hook.rs").is_empty());
+        assert_eq!(owners_of("src/auth/token.rs"), vec!["@acme/security"]);
+    }
+}
@@ -17,6 +17,9 @@ pub struct Comment {
     pub code_suggestion: Option<CodeSuggestion>,
     pub tags: Vec<String>,
     pub fix_effort: FixEffort,
+    /// CODEOWNERS entries for `file_path`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub owners: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,6 +30,23 @@ pub struct CodeSuggestion {
     pub diff: String,
 }
 
+/// A file the model could not review, e.g. after a provider error. Its deterministic findings
+/// and any batches reviewed before the failure are still reported.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedFile {
+    pub file_path: PathBuf,
+    pub error: String,
+}
+
+impl SkippedFile {
+    pub fn new(file_path: &Path, error: &anyhow::Error) -> Self {
+        Self {
+            file_path: file_path.to_path_buf(),
+            error: format!("{:#}", error),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReviewSummary {
     pub total_comments: usize,
@@ -155,6 +175,7 @@ impl CommentSynthesizer {
             code_suggestion,
             tags,
             fix_effort,
+            owners: Vec::new(),
         }))
     }
 
@@ -431,6 +452,25 @@ pub fn compute_comment_id(file_path: &Path, content: &str, category: &Category)
     format!("cmt_{:016x}", hash)
 }
 
+/// A Warning-level Bug finding for tests; override fields with struct update syntax.
+#[cfg(test)]
+pub(crate) fn test_comment(file_path: &str, line_number: usize, content: &str) -> Comment {
+    Comment {
+        id: "cmt_test".to_string(),
+        file_path: PathBuf::from(file_path),
+        line_number,
+        content: content.to_string(),
+        severity: Severity::Warning,
+        category: Category::Bug,
+        suggestion: None,
+        confidence: 0.9,
+        code_suggestion: None,
+        tags: Vec::new(),
+        fix_effort: FixEffort::Low,
+        owners: Vec::new(),
+    }
+}
+
 fn fnv1a64(bytes: &[u8]) -> u64 {
     let mut hash: u64 = 0xcbf29ce484222325;
     for &byte in bytes {
@@ -1,3 +1,5 @@
+use crate::core::{DiffParser, UnifiedDiff};
+
 pub struct CommitPromptBuilder;
 
 impl CommitPromptBuilder {
@@ -134,4 +136,109 @@ Analyze the changes and provide a PR title in <title> tags.
 
         (system_prompt.to_string(), user_prompt)
     }
+
+    /// A commit message built from the staged file list alone, used when no model is
+    /// reachable so the commit template still starts with something useful.
+    pub fn fallback_commit_message(diff: &str) -> String {
+        let files = DiffParser::parse_unified_diff(diff).unwrap_or_default();
+        let verb = |file: &UnifiedDiff| {
+            if file.is_new {
+                "add"
+            } else if file.is_deleted {
+                "remove"
+            } else if file.old_path.is_some() {
+                "rename"
+            } else {
+                "update"
+            }
+        };
+        match files.as_slice() {
+            [] => "chore: update files".to_string(),
+            [file] => format!("chore: {} {}", verb(file), file.file_path.display()),
+            files => {
+                let names: Vec<String> = files
+                    .iter()
+                    .filter_map(|file| file.file_path.file_name())
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .collect();
+                let verb = if files.iter().all(|file| verb(file) == verb(&files[0])) {
+                    verb(&files[0])
+                } else {
+                    "update"
+                };
+                match names.split_last() {
+                    Some((last, rest)) if names.len() <= 3 => {
+                        format!("chore: {} {} and {}", verb, rest.join(", "), last)
+                    }
+                    _ => format!("chore: {} {} files", verb, files.len()),
+                }
+            }
+        }
+    }
+
+    /// The commit message file `existing` with `suggestion` filled in. An empty message gets
+    /// the suggestion as its first lines; a message that already has text (an amend or a
+    /// template) keeps it, with the suggestion added as comment lines git strips.
+    pub fn fill_message_file(existing: &str, suggestion: &str) -> String {
+        let has_message = existing
+            .lines()
+            .take_while(|line| !line.starts_with('#'))
+            .any(|line| !line.trim().is_empty());
+        if !has_message {
+            return format!(
+                "{}\n{}",
+                suggestion.trim_end(),
+                existing.trim_start_matches('\n')
+            );
+        }
+
+        let mut commented = String::from("# Suggested by diffscope:\n");
+        for line in suggestion.trim_end().lines() {
+            if line.trim().is_empty() {
+                commented.push_str("#\n");
+            } else {
+                commented.push_str(&format!("#   {}\n", line));
+            }
+        }
+        commented.push_str("#\n");
+        // Above git's own comment block, right after the message.
+        let offset = existing
+            .match_indices('\n')
+            .map(|(index, _)| index + 1)
+            .find(|&start| existing[start..].starts_with('#'))
+            .unwrap_or(existing.len());
+        let (message, rest) = existing.split_at(offset);
+        let separator = if message.ends_with('\n') || message.is_empty() {
+            ""
+        } else {
+            "\n"
+        };
+        format!("{}{}{}{}", message, separator, commented, rest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fills_empty_messages_and_comments_next_to_existing_ones() {
+        let template = "\n# Please enter the commit message for your changes.\n";
+        assert_eq!(
+            CommitPromptBuilder::fill_message_file(template, "fix: handle empty input\n"),
+            "fix: handle empty input\n# Please enter the commit message for your changes.\n"
+        );
+
+        let amend = "feat: add retries\n\n# Please enter the commit message for your changes.\n";
+        assert_eq!(
+            CommitPromptBuilder::fill_message_file(amend, "feat: add retries\n\nWith backoff."),
+            "feat: add retries\n\n# Suggested by diffscope:\n#   feat: add retries\n#\n#   With backoff.\n#\n# Please enter the commit message for your changes.\n"
+        );
+
+        let diff = "--- /dev/null\n+++ b/src/retry.rs\n@@ -0,0 +1,1 @@\n+pub fn retry() {}\n--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1,1 +1,2 @@\n mod net;\n+mod retry;\n";
+        assert_eq!(
+            CommitPromptBuilder::fallback_commit_message(diff),
+            "chore: update retry.rs and lib.rs"
+        );
+    }
 }
@@ -5,7 +5,9 @@ use std::collections::HashSet;
 use std::path::Path;
 use std::path::PathBuf;
 
+use crate::core::function_scope::{expand_to_enclosing_scope, is_definition};
 use crate::core::SymbolIndex;
+use ignore::WalkBuilder;
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LLMContextChunk {
     pub file_path: PathBuf,
@@ -24,11 +26,25 @@ pub enum ContextType {
 
 pub struct ContextFetcher {
     repo_path: PathBuf,
+    function_scope: bool,
 }
 
 impl ContextFetcher {
     pub fn new(repo_path: PathBuf) -> Self {
-        Self { repo_path }
+        Self {
+            repo_path,
+            function_scope: false,
+        }
+    }
+
+    /// Expand hunk ranges to their enclosing function/class so definitions are never cut mid-body.
+    pub fn repo_path(&self) -> &Path {
+        &self.repo_path
+    }
+
+    pub fn with_function_scope(mut self, enabled: bool) -> Self {
+        self.function_scope = enabled;
+        self
     }
 
     pub async fn fetch_context_for_file(
@@ -42,20 +58,27 @@ impl ContextFetcher {
         if full_path.exists() {
             let content = read_file_lossy(&full_path).await?;
             let file_lines: Vec<&str> = content.lines().collect();
-            let merged_ranges = merge_ranges(lines);
+            let merged_ranges = if self.function_scope {
+                let expanded: Vec<(usize, usize)> = lines
+                    .iter()
+                    .map(|range| expand_to_enclosing_scope(file_path, &file_lines, *range))
+                    .collect();
+                merge_ranges(&expanded)
+            } else {
+                merge_ranges(lines)
+            };
 
             for (start, end) in merged_ranges {
                 if file_lines.is_empty() {
                     break;
                 }
                 let start = start.max(1);
-                let end = end.max(start);
+                let end = end.max(start).min(file_lines.len());
                 let start_idx = start.saturating_sub(1);
-                let end_idx = end.min(file_lines.len());
 
                 if start_idx < file_lines.len() {
                     let chunk_content = truncate_with_notice(
-                        file_lines[start_idx..end_idx].join("\n"),
+                        file_lines[start_idx..end].join("\n"),
                         MAX_CONTEXT_CHARS,
                     );
                     chunks.push(LLMContextChunk {
@@ -176,6 +199,88 @@ impl ContextFetcher {
         Ok(chunks)
     }
 
+    /// Finds call sites of `symbols` across the repo (respecting .gitignore) so the review can
+    /// flag callers that were not updated alongside a signature change.
+    pub async fn fetch_symbol_references<F>(
+        &self,
+        file_path: &Path,
+        symbols: &[String],
+        max_files: usize,
+        max_references: usize,
+        should_exclude: F,
+    ) -> Result<Vec<LLMContextChunk>>
+    where
+        F: Fn(&Path) -> bool,
+    {
+        let mut chunks = Vec::new();
+        if symbols.is_empty() || max_references == 0 {
+            return Ok(chunks);
+        }
+
+        let alternation = symbols
+            .iter()
+            .map(|symbol| regex::escape(symbol))
+            .collect::<Vec<_>>()
+            .join("|");
+        let call_pattern = regex::Regex::new(&format!(r"\b(?:{})\s*\(", alternation))?;
+
+        let walker = WalkBuilder::new(&self.repo_path)
+            .hidden(true)
+            .git_ignore(true)
+            .git_exclude(true)
+            .build();
+        let mut files_seen = 0usize;
+
+        'files: for entry in walker.flatten() {
+            let path = entry.path();
+            if !path.is_file() {
+                continue;
+            }
+            if files_seen >= max_files {
+                break;
+            }
+            files_seen += 1;
+
+            let relative_path = path.strip_prefix(&self.repo_path).unwrap_or(path);
+            if should_exclude(relative_path) {
+                continue;
+            }
+            let Ok(content) = std::fs::read_to_string(path) else {
+                continue;
+            };
+            if content.len() > MAX_REFERENCE_FILE_BYTES {
+                continue;
+            }
+
+            let lines: Vec<&str> = content.lines().collect();
+            for (idx, line) in lines.iter().enumerate() {
+                if !call_pattern.is_match(line) || is_definition(line) {
+                    continue;
+                }
+                let start = idx.saturating_sub(REFERENCE_CONTEXT_LINES);
+                let end = (idx + REFERENCE_CONTEXT_LINES + 1).min(lines.len());
+                chunks.push(LLMContextChunk {
+                    file_path: relative_path.to_path_buf(),
+                    content: truncate_with_notice(lines[start..end].join("\n"), MAX_CONTEXT_CHARS),
+                    context_type: ContextType::Reference,
+                    line_range: Some((start + 1, end)),
+                });
+                if chunks.len() >= max_references {
+                    break 'files;
+                }
+            }
+        }
+
+        if !chunks.is_empty() {
+            tracing::debug!(
+                "Found {} call site(s) for changed signatures in {}",
+                chunks.len(),
+                file_path.display()
+            );
+        }
+        Ok(chunks)
+    }
+
     pub async fn fetch_related_definitions_with_index(
         &self,
         file_path: &PathBuf,
@@ -234,6 +339,8 @@ fn merge_ranges(lines: &[(usize, usize)]) -> Vec<(usize, usize)> {
 }
 
 const MAX_CONTEXT_CHARS: usize = 8000;
+const MAX_REFERENCE_FILE_BYTES: usize = 500_000;
+const REFERENCE_CONTEXT_LINES: usize = 2;
 
 fn truncate_with_notice(mut content: String, max_chars: usize) -> String {
     if max_chars == 0 || content.len() <= max_chars {
@@ -0,0 +1,193 @@
+use crate::core::tokenizer::normalize_model_name;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Output tokens assumed per review request (capped at `max_tokens`); most responses are a
+/// handful of findings.
+pub const EXPECTED_OUTPUT_TOKENS: usize = 1000;
+
+/// Price in USD per million tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ModelPrice {
+    pub input: f64,
+    pub output: f64,
+}
+
+/// Published list prices, matched by model-name prefix. More specific prefixes come first.
+const PRICES: &[(&str, f64, f64)] = &[
+    ("gpt-4o-mini", 0.15, 0.60),
+    ("gpt-4o", 2.50, 10.00),
+    ("gpt-4.1-nano", 0.10, 0.40),
+    ("gpt-4.1-mini", 0.40, 1.60),
+    ("gpt-4.1", 2.00, 8.00),
+    ("gpt-4-turbo", 10.00, 30.00),
+    ("gpt-4", 30.00, 60.00),
+    ("gpt-3.5-turbo", 0.50, 1.50),
+    ("gpt-5-nano", 0.05, 0.40),
+    ("gpt-5-mini", 0.25, 2.00),
+    ("gpt-5", 1.25, 10.00),
+    ("o1-mini", 1.10, 4.40),
+    ("o1", 15.00, 60.00),
+    ("o3-mini", 1.10, 4.40),
+    ("o3", 2.00, 8.00),
+    ("o4-mini", 1.10, 4.40),
+    ("claude-3-haiku", 0.25, 1.25),
+    ("claude-3-5-haiku", 0.80, 4.00),
+    ("claude-haiku-4", 1.00, 5.00),
+    ("claude-3-5-sonnet", 3.00, 15.00),
+    ("claude-3-7-sonnet", 3.00, 15.00),
+    ("claude-sonnet-4", 3.00, 15.00),
+    ("claude-3-opus", 15.00, 75.00),
+    ("claude-opus-4", 15.00, 75.00),
+    ("gemini-1.5-flash", 0.075, 0.30),
+    ("gemini-1.5-pro", 1.25, 5.00),
+    ("gemini-2.0-flash", 0.10, 0.40),
+    ("gemini-2.5-flash", 0.30, 2.50),
+    ("gemini-2.5-pro", 1.25, 10.00),
+    ("deepseek-chat", 0.27, 1.10),
+    ("deepseek-reasoner", 0.55, 2.19),
+    ("codestral", 0.30, 0.90),
+    ("mistral-large", 2.00, 6.00),
+    ("mistral-small", 0.10, 0.30),
+];
+
+/// The price of `model`: an entry in `overrides` (the `model_pricing` setting) for the exact
+/// name, free for local models, else the built-in list. `None` when the price is unknown.
+pub fn price_for(model: &str, overrides: &HashMap<String, ModelPrice>) -> Option<ModelPrice> {
+    if let Some(price) = overrides.get(model) {
+        return Some(*price);
+    }
+    let lower = model.trim().to_lowercase();
+    if lower.starts_with("ollama:") || lower.starts_with("local:") {
+        return Some(ModelPrice {
+            input: 0.0,
+            output: 0.0,
+        });
+    }
+    let name = normalize_model_name(model);
+    PRICES
+        .iter()
+        .find(|(prefix, _, _)| name.starts_with(prefix))
+        .map(|&(_, input, output)| ModelPrice { input, output })
+}
+
+/// The prompts one file would send.
+#[derive(Debug, Clone, Serialize)]
+pub struct FileEstimate {
+    pub file_path: PathBuf,
+    pub requests: usize,
+    pub input_tokens: usize,
+    /// Hunks answered from the review cache, which cost nothing.
+    pub cached_hunks: usize,
+    /// The shared request of a small file reviewed together with others (`batching`). Its
+    /// `requests` is then 0 and `input_tokens` its share of that request.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub batch: Option<usize>,
+}
+
+/// What a review would send, from `review --dry-run`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReviewEstimate {
+    pub files: Vec<FileEstimate>,
+    /// Files left out of the review.
+    pub excluded: Vec<ExcludedFile>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExcludedFile {
+    pub file_path: PathBuf,
+    /// e.g. "excluded", "deleted", or "binary".
+    pub reason: &'static str,
+}
+
+/// Projected spend for one model.
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelCost {
+    pub model: String,
+    pub input_tokens: usize,
+    pub output_tokens: usize,
+    /// USD; `None` when the model's price is unknown.
+    pub cost: Option<f64>,
+}
+
+impl ReviewEstimate {
+    pub fn requests(&self) -> usize {
+        let batches: std::collections::HashSet<usize> =
+            self.files.iter().filter_map(|file| file.batch).collect();
+        self.files.iter().map(|file| file.requests).sum::<usize>() + batches.len()
+    }
+
+    pub fn input_tokens(&self) -> usize {
+        self.files.iter().map(|file| file.input_tokens).sum()
+    }
+
+    /// The cost of the review on `model`, assuming `EXPECTED_OUTPUT_TOKENS` (at most
+    /// `max_tokens`) per request.
+    pub fn cost(
+        &self,
+        model: &str,
+        max_tokens: usize,
+        overrides: &HashMap<String, ModelPrice>,
+    ) -> ModelCost {
+        let input_tokens = self.input_tokens();
+        let output_tokens = self.requests() * EXPECTED_OUTPUT_TOKENS.min(max_tokens);
+        ModelCost {
+            model: model.to_string(),
+            input_tokens,
+            output_tokens,
+            cost: price_for(model, overrides).map(|price| {
+                (input_tokens as f64 * price.input + output_tokens as f64 * price.output)
+                    / 1_000_000.0
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prices_reviews_by_model() {
+        let estimate = ReviewEstimate {
+            files: vec![
+                FileEstimate {
+                    file_path: PathBuf::from("src/a.rs"),
+                    requests: 2,
+                    input_tokens: 300_000,
+                    cached_hunks: 0,
+                    batch: None,
+                },
+                FileEstimate {
+                    file_path: PathBuf::from("src/b.rs"),
+                    requests: 1,
+                    input_tokens: 100_000,
+                    cached_hunks: 1,
+                    batch: None,
+                },
+            ],
+            excluded: Vec::new(),
+        };
+        let overrides = HashMap::from([(
+            "acme-coder".to_string(),
+            ModelPrice {
+                input: 1.0,
+                output: 2.0,
+            },
+        )]);
+
+        let gpt = estimate.cost("openrouter:openai/gpt-4o-mini", 4000, &overrides);
+        assert_eq!(gpt.output_tokens, 3000);
+        assert!((gpt.cost.unwrap() - (0.4 * 0.15 + 0.003 * 0.60)).abs() < 1e-9);
+        let custom = estimate.cost("acme-coder", 500, &overrides);
+        assert_eq!(custom.output_tokens, 1500);
+        assert!((custom.cost.unwrap() - 0.403).abs() < 1e-9);
+        assert_eq!(
+            estimate.cost("ollama:codellama", 4000, &overrides).cost,
+            Some(0.0)
+        );
+        assert_eq!(estimate.cost("mystery-model", 4000, &overrides).cost, None);
+    }
+}
@@ -0,0 +1,264 @@
+use crate::core::diff_parser::{ChangeType, UnifiedDiff};
+use anyhow::{Context, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+static COBERTURA_CLASS: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"<class\b[^>]*\bfilename="([^"]+)""#).unwrap());
+static COBERTURA_LINE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"<line\b[^>]*\bnumber="(\d+)"[^>]*\bhits="(\d+)"|<line\b[^>]*\bhits="(\d+)"[^>]*\bnumber="(\d+)""#)
+        .unwrap()
+});
+
+/// Line hit counts per source file, read from an lcov or Cobertura report.
+#[derive(Debug, Default)]
+pub struct CoverageReport {
+    files: HashMap<PathBuf, BTreeMap<usize, u64>>,
+}
+
+/// How well the lines a diff adds to one file are covered.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatchCoverage {
+    pub file_path: PathBuf,
+    /// Added lines the report has data for; blank lines and comments are not instrumented.
+    pub instrumented: usize,
+    pub covered: usize,
+    /// Instrumented added lines with zero hits, in line order.
+    pub uncovered: Vec<usize>,
+}
+
+impl CoverageReport {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read coverage report {}", path.display()))?;
+        Ok(Self::parse(&content))
+    }
+
+    /// Parses Cobertura XML when the content looks like XML, lcov tracefile data otherwise.
+    pub fn parse(content: &str) -> Self {
+        if content.trim_start().starts_with('<') {
+            Self::parse_cobertura(content)
+        } else {
+            Self::parse_lcov(content)
+        }
+    }
+
+    fn parse_lcov(content: &str) -> Self {
+        let mut report = Self::default();
+        let mut current: Option<PathBuf> = None;
+        for line in content.lines() {
+            let line = line.trim();
+            if let Some(path) = line.strip_prefix("SF:") {
+                current = Some(PathBuf::from(path));
+            } else if let Some(data) = line.strip_prefix("DA:") {
+                let mut fields = data.split(',');
+                let (Some(number), Some(hits), Some(path)) =
+                    (fields.next(), fields.next(), &current)
+                else {
+                    continue;
+                };
+                if let (Ok(number), Ok(hits)) = (number.parse(), hits.parse::<f64>()) {
+                    report.record(path.clone(), number, hits as u64);
+                }
+            } else if line == "end_of_record" {
+                current = None;
+            }
+        }
+        report
+    }
+
+    fn parse_cobertura(content: &str) -> Self {
+        let mut report = Self::default();
+        let mut current: Option<PathBuf> = None;
+        for line in content.lines() {
+            if let Some(captures) = COBERTURA_CLASS.captures(line) {
+                current = Some(PathBuf::from(&captures[1]));
+            }
+            let Some(path) = &current else {
+                continue;
+            };
+            for captures in COBERTURA_LINE.captures_iter(line) {
+                let number = captures.get(1).or(captures.get(4));
+                let hits = captures.get(2).or(captures.get(3));
+                if let (Some(number), Some(hits)) = (number, hits) {
+                    if let (Ok(number), Ok(hits)) = (number.as_str().parse(), hits.as_str().parse())
+                    {
+                        report.record(path.clone(), number, hits);
+                    }
+                }
+            }
+        }
+        report
+    }
+
+    /// Hits from several records for the same line (e.g. per test target) are summed.
+    fn record(&mut self, path: PathBuf, line: usize, hits: u64) {
+        *self.files.entry(path).or_default().entry(line).or_insert(0) += hits;
+    }
+
+    /// Line hits for `path`. Reports often use absolute paths or paths relative to a source
+    /// root, so a report path ending with `path` (or vice versa) also matches.
+    pub fn file(&self, path: &Path) -> Option<&BTreeMap<usize, u64>> {
+        self.files.get(path).or_else(|| {
+            self.files
+                .iter()
+                .find(|(reported, _)| reported.ends_with(path) || path.ends_with(reported))
+                .map(|(_, lines)| lines)
+        })
+    }
+
+    /// Share of instrumented lines covered across the whole report.
+    pub fn line_rate(&self) -> Option<f64> {
+        let (covered, total) = self
+            .files
+            .values()
+            .flat_map(|lines| lines.values())
+            .fold((0usize, 0usize), |(covered, total), hits| {
+                (covered + usize::from(*hits > 0), total + 1)
+            });
+        (total > 0).then(|| covered as f64 / total as f64)
+    }
+
+    /// Coverage of the lines `diff` adds; `None` when the report has no data for the file.
+    pub fn patch_coverage(&self, diff: &UnifiedDiff) -> Option<PatchCoverage> {
+        let lines = self.file(&diff.file_path)?;
+        let mut coverage = PatchCoverage {
+            file_path: diff.file_path.clone(),
+            instrumented: 0,
+            covered: 0,
+            uncovered: Vec::new(),
+        };
+        let added = diff
+            .hunks
+            .iter()
+            .flat_map(|hunk| &hunk.changes)
+            .filter(|line| matches!(line.change_type, ChangeType::Added))
+            .filter_map(|line| line.new_line_no);
+        for line in added {
+            match lines.get(&line) {
+                Some(0) => {
+                    coverage.instrumented += 1;
+                    coverage.uncovered.push(line);
+                }
+                Some(_) => {
+                    coverage.instrumented += 1;
+                    coverage.covered += 1;
+                }
+                None => {}
+            }
+        }
+        Some(coverage)
+    }
+}
+
+impl PatchCoverage {
+    pub fn rate(&self) -> Option<f64> {
+        (self.instrumented > 0).then(|| self.covered as f64 / self.instrumented as f64)
+    }
+}
+
+/// Groups sorted line numbers into inclusive runs of consecutive lines.
+pub fn line_runs(lines: &[usize]) -> Vec<(usize, usize)> {
+    let mut runs: Vec<(usize, usize)> = Vec::new();
+    for &line in lines {
+        match runs.last_mut() {
+            Some((_, end)) if *end + 1 == line => *end = line,
+            _ => runs.push((line, line)),
+        }
+    }
+    runs
+}
+
+/// Markdown section comparing coverage of the added lines with the report's overall coverage.
+pub fn format_coverage_section(report: &CoverageReport, diffs: &[UnifiedDiff]) -> String {
+    let files: Vec<PatchCoverage> = diffs
+        .iter()
+        .filter_map(|diff| report.patch_coverage(diff))
+        .filter(|coverage| coverage.instrumented > 0)
+        .collect();
+    if files.is_empty() {
+        return String::new();
+    }
+
+    let percent = |rate: f64| format!("{:.1}%", rate * 100.0);
+    let mut output = String::new();
+    output.push_str("## 🧪 Coverage Delta\n\n");
+    output.push_str("| File | Added lines covered | Patch coverage |\n");
+    output.push_str("|------|---------------------|----------------|\n");
+    for coverage in &files {
+        output.push_str(&format!(
+            "| `{}` | {}/{} | {} |\n",
+            coverage.file_path.display(),
+            coverage.covered,
+            coverage.instrumented,
+            coverage.rate().map(percent).unwrap_or_default()
+        ));
+    }
+
+    let covered: usize = files.iter().map(|coverage| coverage.covered).sum();
+    let instrumented: usize = files.iter().map(|coverage| coverage.instrumented).sum();
+    let patch_rate = covered as f64 / instrumented as f64;
+    output.push_str(&format!(
+        "\n**Patch coverage:** {} ({}/{} added lines)",
+        percent(patch_rate),
+        covered,
+        instrumented
+    ));
+    if let Some(overall) = report.line_rate() {
+        output.push_str(&format!(
+            " vs. {} overall ({:+.1} points)",
+            percent(overall),
+            (patch_rate - overall) * 100.0
+        ));
+    }
+    output.push_str("\n\n");
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_lcov_and_cobertura_and_scores_added_lines() {
+        let lcov = CoverageReport::parse(
+            "TN:\nSF:/ci/build/src/lib.rs\nDA:1,4\nDA:2,0\nDA:3,0\nDA:5,1\nend_of_record\n",
+        );
+        let cobertura = CoverageReport::parse(
+            r#"<?xml version="1.0" ?>
+<coverage line-rate="0.5">
+  <sources><source>/ci/build</source></sources>
+  <packages><package name="src"><classes>
+    <class name="lib" filename="src/lib.rs" line-rate="0.5">
+      <lines>
+        <line number="1" hits="4"/>
+        <line number="2" hits="0"/>
+        <line hits="0" number="3" branch="false"/>
+        <line number="5" hits="1"/>
+      </lines>
+    </class>
+  </classes></package></packages>
+</coverage>"#,
+        );
+        let diff = crate::core::DiffParser::parse_unified_diff(
+            "--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1,2 +1,5 @@\n fn a() {}\n+fn b() {\n+    todo!()\n+\n+}\n",
+        )
+        .unwrap()
+        .remove(0);
+
+        for report in [&lcov, &cobertura] {
+            let coverage = report.patch_coverage(&diff).unwrap();
+            assert_eq!(coverage.instrumented, 3);
+            assert_eq!(coverage.covered, 1);
+            assert_eq!(coverage.uncovered, vec![2, 3]);
+            assert_eq!(report.line_rate(), Some(0.5));
+        }
+        assert_eq!(line_runs(&[2, 3, 7]), vec![(2, 3), (7, 7)]);
+
+        let section = format_coverage_section(&lcov, &[diff]);
+        assert!(section.contains("| `src/lib.rs` | 1/3 | 33.3% |"));
+        assert!(section.contains("vs. 50.0% overall (-16.7 points)"));
+    }
+}
@@ -0,0 +1,383 @@
+use ignore::WalkBuilder;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+pub const DEFAULT_MAX_GRAPH_FILES: usize = 2000;
+/// How many import hops away from a changed file still count as impacted.
+pub const IMPACT_MAX_DEPTH: usize = 3;
+const MAX_GRAPH_FILE_BYTES: u64 = 500_000;
+
+const JS_EXTENSIONS: &[&str] = &["ts", "tsx", "js", "jsx", "mjs", "cjs"];
+
+static JS_IMPORT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?:\bfrom\s+|\bimport\s+|\brequire\s*\(\s*|\bimport\s*\(\s*)["']([^"']+)["']"#)
+        .unwrap()
+});
+static PY_FROM_IMPORT: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\s*from\s+(\.*)([\w.]*)\s+import\s+([\w\s,*]+)").unwrap());
+static PY_IMPORT: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\s*import\s+([\w.]+(?:\s*,\s*[\w.]+)*)").unwrap());
+static RUST_MOD: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\s*(?:pub(?:\([^)]*\))?\s+)?mod\s+([A-Za-z_]\w*)\s*;").unwrap());
+static RUST_USE_CRATE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"crate::(\{[^}]*\}|[\w:]+)").unwrap());
+
+/// Lightweight module dependency graph built from import/require/use statements.
+/// Paths are relative to the repo root; only edges between files in the repo are kept.
+#[derive(Debug, Default)]
+pub struct DependencyGraph {
+    imports: HashMap<PathBuf, HashSet<PathBuf>>,
+    importers: HashMap<PathBuf, HashSet<PathBuf>>,
+}
+
+/// A module affected by a change, with its distance from the changed file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImpactedModule {
+    pub file_path: PathBuf,
+    pub depth: usize,
+}
+
+impl DependencyGraph {
+    /// Returns the graph for `repo_root`, building it on first use in this process.
+    pub fn shared(repo_root: &Path, max_files: usize) -> Arc<DependencyGraph> {
+        static GRAPHS: Lazy<Mutex<HashMap<PathBuf, Arc<DependencyGraph>>>> =
+            Lazy::new(|| Mutex::new(HashMap::new()));
+        let mut graphs = GRAPHS.lock().unwrap();
+        graphs
+            .entry(repo_root.to_path_buf())
+            .or_insert_with(|| Arc::new(DependencyGraph::build(repo_root, max_files)))
+            .clone()
+    }
+
+    pub fn build(repo_root: &Path, max_files: usize) -> Self {
+        let walker = WalkBuilder::new(repo_root)
+            .hidden(true)
+            .git_ignore(true)
+            .git_exclude(true)
+            .build();
+
+        let mut sources = Vec::new();
+        for entry in walker.flatten() {
+            let path = entry.path();
+            if !path.is_file() || language_of(path).is_none() {
+                continue;
+            }
+            if sources.len() >= max_files {
+                break;
+            }
+            if entry.metadata().map(|m| m.len()).unwrap_or(0) > MAX_GRAPH_FILE_BYTES {
+                continue;
+            }
+            if let Ok(relative) = path.strip_prefix(repo_root) {
+                sources.push(relative.to_path_buf());
+            }
+        }
+
+        let known: HashSet<PathBuf> = sources.iter().cloned().collect();
+        let mut graph = DependencyGraph::default();
+        for file in &sources {
+            let Ok(content) = std::fs::read_to_string(repo_root.join(file)) else {
+                continue;
+            };
+            for target in resolve_imports(file, &content, &known) {
+                if &target != file {
+                    graph.add_edge(file.clone(), target);
+                }
+            }
+        }
+        graph
+    }
+
+    fn add_edge(&mut self, from: PathBuf, to: PathBuf) {
+        self.importers
+            .entry(to.clone())
+            .or_default()
+            .insert(from.clone());
+        self.imports.entry(from).or_default().insert(to);
+    }
+
+    #[allow(dead_code)]
+    pub fn imports_of(&self, file: &Path) -> Vec<PathBuf> {
+        let mut imports: Vec<PathBuf> = self
+            .imports
+            .get(file)
+            .map(|set| set.iter().cloned().collect())
+            .unwrap_or_default();
+        imports.sort();
+        imports
+    }
+
+    /// Modules that import `changed` directly or transitively, nearest first.
+    pub fn impacted_by(&self, changed: &Path, max_depth: usize) -> Vec<ImpactedModule> {
+        let mut seen = HashSet::new();
+        seen.insert(changed.to_path_buf());
+        let mut queue = VecDeque::from([(changed.to_path_buf(), 0usize)]);
+        let mut impacted = Vec::new();
+
+        while let Some((file, depth)) = queue.pop_front() {
+            if depth >= max_depth {
+                continue;
+            }
+            let Some(importers) = self.importers.get(&file) else {
+                continue;
+            };
+            let mut importers: Vec<&PathBuf> = importers.iter().collect();
+            importers.sort();
+            for importer in importers {
+                if seen.insert(importer.clone()) {
+                    impacted.push(ImpactedModule {
+                        file_path: importer.clone(),
+                        depth: depth + 1,
+                    });
+                    queue.push_back((importer.clone(), depth + 1));
+                }
+            }
+        }
+
+        impacted
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Language {
+    JavaScript,
+    Python,
+    Rust,
+}
+
+fn language_of(path: &Path) -> Option<Language> {
+    let ext = path.extension()?.to_str()?;
+    if JS_EXTENSIONS.contains(&ext) {
+        Some(Language::JavaScript)
+    } else if ext == "py" {
+        Some(Language::Python)
+    } else if ext == "rs" {
+        Some(Language::Rust)
+    } else {
+        None
+    }
+}
+
+fn resolve_imports(file: &Path, content: &str, known: &HashSet<PathBuf>) -> Vec<PathBuf> {
+    let dir = file.parent().unwrap_or(Path::new(""));
+    let mut targets = Vec::new();
+    let mut push_first = |candidates: Vec<PathBuf>| {
+        if let Some(found) = candidates
+            .into_iter()
+            .map(|c| normalize(&c))
+            .find(|c| known.contains(c))
+        {
+            targets.push(found);
+        }
+    };
+
+    match language_of(file) {
+        Some(Language::JavaScript) => {
+            for caps in JS_IMPORT.captures_iter(content) {
+                let spec = &caps[1];
+                if !spec.starts_with('.') {
+                    continue; // package imports are outside the repo graph
+                }
+                let base = dir.join(spec);
+                let mut candidates = vec![base.clone()];
+                for ext in JS_EXTENSIONS {
+                    candidates.push(PathBuf::from(format!("{}.{}", base.display(), ext)));
+                }
+                for ext in JS_EXTENSIONS {
+                    candidates.push(base.join(format!("index.{}", ext)));
+                }
+                push_first(candidates);
+            }
+        }
+        Some(Language::Python) => {
+            for line in content.lines() {
+                if let Some(caps) = PY_FROM_IMPORT.captures(line) {
+                    let dots = caps[1].len();
+                    let module = &caps[2];
+                    let root = if dots > 0 {
+                        let mut root = dir.to_path_buf();
+                        for _ in 1..dots {
+                            root.pop();
+                        }
+                        root
+                    } else {
+                        PathBuf::new()
+                    };
+                    let module_path = root.join(module.replace('.', "/"));
+                    let mut resolved = false;
+                    // `from pkg import mod` may name submodules rather than attributes.
+                    for name in caps[3].split(',').map(str::trim) {
+                        let candidates = python_candidates(&module_path.join(name), dots == 0);
+                        if candidates.iter().any(|c| known.contains(&normalize(c))) {
+                            push_first(candidates);
+                            resolved = true;
+                        }
+                    }
+                    if !resolved && !module.is_empty() {
+                        push_first(python_candidates(&module_path, dots == 0));
+                    }
+                } else if let Some(caps) = PY_IMPORT.captures(line) {
+                    for module in caps[1].split(',').map(str::trim) {
+                        push_first(python_candidates(
+                            &PathBuf::from(module.replace('.', "/")),
+                            true,
+                        ));
+                    }
+                }
+            }
+        }
+        Some(Language::Rust) => {
+            let module_dir = rust_module_dir(file);
+            for line in content.lines() {
+                if let Some(caps) = RUST_MOD.captures(line) {
+                    let name = &caps[1];
+                    push_first(vec![
+                        module_dir.join(format!("{}.rs", name)),
+                        module_dir.join(name).join("mod.rs"),
+                    ]);
+                }
+            }
+            if let Some(src_root) = rust_src_root(file) {
+                for caps in RUST_USE_CRATE.captures_iter(content) {
+                    let paths: Vec<String> = match caps[1].strip_prefix('{') {
+                        Some(group) => group
+                            .trim_end_matches('}')
+                            .split(',')
+                            .map(|p| p.trim().to_string())
+                            .collect(),
+                        None => vec![caps[1].to_string()],
+                    };
+                    for path in paths {
+                        let segments: Vec<&str> = path
+                            .split("::")
+                            .filter(|s| !s.is_empty() && *s != "self" && *s != "*")
+                            .collect();
+                        // Longest module prefix that exists wins: `a::b::Item` -> a/b.rs.
+                        let mut candidates = Vec::new();
+                        for len in (1..=segments.len()).rev() {
+                            let module = segments[..len].join("/");
+                            candidates.push(src_root.join(format!("{}.rs", module)));
+                            candidates.push(src_root.join(&module).join("mod.rs"));
+                        }
+                        push_first(candidates);
+                    }
+                }
+            }
+        }
+        None => {}
+    }
+
+    targets.sort();
+    targets.dedup();
+    targets
+}
+
+fn python_candidates(module_path: &Path, absolute: bool) -> Vec<PathBuf> {
+    let mut candidates = vec![
+        PathBuf::from(format!("{}.py", module_path.display())),
+        module_path.join("__init__.py"),
+    ];
+    if absolute {
+        let src = Path::new("src").join(module_path);
+        candidates.push(PathBuf::from(format!("{}.py", src.display())));
+        candidates.push(src.join("__init__.py"));
+    }
+    candidates
+}
+
+/// Directory holding the child modules declared with `mod x;` in `file`.
+fn rust_module_dir(file: &Path) -> PathBuf {
+    let dir = file.parent().unwrap_or(Path::new("")).to_path_buf();
+    match file.file_stem().and_then(|s| s.to_str()) {
+        Some("mod") | Some("lib") | Some("main") => dir,
+        Some(stem) => dir.join(stem),
+        None => dir,
+    }
+}
+
+fn rust_src_root(file: &Path) -> Option<PathBuf> {
+    file.ancestors()
+        .find(|ancestor| ancestor.file_name().and_then(|n| n.to_str()) == Some("src"))
+        .map(Path::to_path_buf)
+}
+
+/// Lexically resolves `.` and `..` so `src/a/../b.ts` matches `src/b.ts`.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other.as_os_str()),
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(root: &Path, path: &str, content: &str) {
+        let full = root.join(path);
+        std::fs::create_dir_all(full.parent().unwrap()).unwrap();
+        std::fs::write(full, content).unwrap();
+    }
+
+    #[test]
+    fn resolves_imports_and_walks_importers_transitively() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(root, "web/util.ts", "export const add = (a, b) => a + b;\n");
+        write(root, "web/cart.ts", "import { add } from './util';\n");
+        write(root, "web/app.tsx", "import Cart from \"./cart\";\n");
+        write(root, "py/pkg/helpers.py", "def helper(): pass\n");
+        write(root, "py/pkg/service.py", "from .helpers import helper\n");
+        write(root, "src/main.rs", "mod core;\n");
+        write(root, "src/core/mod.rs", "pub mod parser;\n");
+        write(
+            root,
+            "src/core/parser.rs",
+            "use crate::core::{parser, other};\n",
+        );
+        write(root, "src/report.rs", "use crate::core::parser::Parser;\n");
+
+        let graph = DependencyGraph::build(root, 100);
+
+        let impacted = graph.impacted_by(Path::new("web/util.ts"), 3);
+        assert_eq!(
+            impacted,
+            vec![
+                ImpactedModule {
+                    file_path: PathBuf::from("web/cart.ts"),
+                    depth: 1
+                },
+                ImpactedModule {
+                    file_path: PathBuf::from("web/app.tsx"),
+                    depth: 2
+                },
+            ]
+        );
+        assert_eq!(
+            graph.imports_of(Path::new("py/pkg/service.py")),
+            vec![PathBuf::from("py/pkg/helpers.py")]
+        );
+        let rust_importers: Vec<PathBuf> = graph
+            .impacted_by(Path::new("src/core/parser.rs"), 1)
+            .into_iter()
+            .map(|m| m.file_path)
+            .collect();
+        assert_eq!(
+            rust_importers,
+            vec![
+                PathBuf::from("src/core/mod.rs"),
+                PathBuf::from("src/report.rs")
+            ]
+        );
+    }
+}
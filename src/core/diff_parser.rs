@@ -5,13 +5,19 @@ use std::path::PathBuf;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UnifiedDiff {
+    /// Path of the file after the change (the new path for renames).
     pub file_path: PathBuf,
+    /// Path before a rename or move; `None` when the file kept its path.
+    #[serde(default)]
+    pub old_path: Option<PathBuf>,
     pub old_content: Option<String>,
     pub new_content: Option<String>,
     pub hunks: Vec<DiffHunk>,
     pub is_binary: bool,
     pub is_deleted: bool,
     pub is_new: bool,
+    #[serde(default)]
+    pub is_renamed: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -32,7 +38,7 @@ pub struct DiffLine {
     pub content: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum ChangeType {
     Added,
     Removed,
@@ -177,23 +183,27 @@ impl DiffParser {
 
         Ok(UnifiedDiff {
             file_path,
+            old_path: None,
             old_content: Some(old_content.to_string()),
             new_content: Some(new_content.to_string()),
             hunks,
             is_binary: false,
             is_deleted: false,
             is_new: false,
+            is_renamed: false,
         })
     }
 
     fn parse_single_file_diff(lines: &[&str], i: &mut usize) -> Result<UnifiedDiff> {
         let file_line = lines[*i];
-        let file_path = Self::extract_file_path(file_line)?;
+        let mut file_path = Self::extract_file_path(file_line)?;
         *i += 1;
 
         let mut is_binary = false;
         let mut is_deleted = false;
         let mut is_new = false;
+        let mut rename_from = None;
+        let mut rename_to = None;
         while *i < lines.len()
             && !lines[*i].starts_with("@@")
             && !lines[*i].starts_with("diff --git")
@@ -208,6 +218,12 @@ impl DiffParser {
             if line.starts_with("new file mode") {
                 is_new = true;
             }
+            if let Some(path) = line.strip_prefix("rename from ") {
+                rename_from = Some(Self::unquote_path(path));
+            }
+            if let Some(path) = line.strip_prefix("rename to ") {
+                rename_to = Some(Self::unquote_path(path));
+            }
             if line.starts_with("--- ") {
                 if let Ok(path) = Self::extract_path_from_header(line, "--- ") {
                     if path == "/dev/null" {
@@ -232,14 +248,22 @@ impl DiffParser {
             hunks.push(hunk);
         }
 
+        // `rename to` is authoritative; the `diff --git` line is ambiguous when paths contain spaces.
+        if let Some(path) = &rename_to {
+            file_path = path.clone();
+        }
+        let is_renamed = rename_from.is_some() && rename_from != rename_to;
+
         Ok(UnifiedDiff {
             file_path: PathBuf::from(file_path),
+            old_path: rename_from.filter(|_| is_renamed).map(PathBuf::from),
             old_content: None,
             new_content: None,
             hunks,
             is_binary,
             is_deleted,
             is_new,
+            is_renamed,
         })
     }
 
@@ -252,6 +276,8 @@ impl DiffParser {
 
         let is_new = old_path == "/dev/null";
         let is_deleted = new_path == "/dev/null";
+        let is_renamed = !is_new && !is_deleted && old_path != new_path;
+        let renamed_from = is_renamed.then(|| PathBuf::from(&old_path));
         let file_path = if new_path != "/dev/null" {
             new_path
         } else {
@@ -282,12 +308,14 @@ impl DiffParser {
 
         Ok(UnifiedDiff {
             file_path: PathBuf::from(file_path),
+            old_path: renamed_from,
             old_content: None,
             new_content: None,
             hunks,
             is_binary,
             is_deleted,
             is_new,
+            is_renamed,
         })
     }
 
@@ -348,6 +376,15 @@ impl DiffParser {
             .to_string())
     }
 
+    /// Paths in `rename from`/`rename to` headers carry no a/ b/ prefix but may be quoted.
+    fn unquote_path(raw: &str) -> String {
+        let raw = raw.trim();
+        raw.strip_prefix('"')
+            .and_then(|s| s.strip_suffix('"'))
+            .unwrap_or(raw)
+            .to_string()
+    }
+
     fn parse_hunk(lines: &[&str], i: &mut usize) -> Result<DiffHunk> {
         let header = lines[*i];
         let (old_start, old_lines, new_start, new_lines) = Self::parse_hunk_header(header)?;
@@ -356,14 +393,23 @@ impl DiffParser {
         let mut changes = Vec::new();
         let mut old_line = old_start;
         let mut new_line = new_start;
+        let mut old_remaining = old_lines;
+        let mut new_remaining = new_lines;
 
         while *i < lines.len()
             && !lines[*i].starts_with("@@")
             && !lines[*i].starts_with("diff --git")
-            && !lines[*i].starts_with("--- ")
-            && !lines[*i].starts_with("+++ ")
         {
             let line = lines[*i];
+            // `--- `/`+++ ` start the next file unless the hunk still expects lines, e.g. a
+            // removed `-- comment` or an added `++ x` in a new file.
+            let expects_removed = line.starts_with("--- ") && old_remaining > 0;
+            let expects_added = line.starts_with("+++ ") && new_remaining > 0;
+            if (line.starts_with("--- ") && !expects_removed)
+                || (line.starts_with("+++ ") && !expects_added)
+            {
+                break;
+            }
             if line.starts_with("\\ No newline at end of file") {
                 *i += 1;
                 continue;
@@ -384,6 +430,7 @@ impl DiffParser {
                 ChangeType::Added => {
                     let line_no = new_line;
                     new_line += 1;
+                    new_remaining = new_remaining.saturating_sub(1);
                     DiffLine {
                         old_line_no: None,
                         new_line_no: Some(line_no),
@@ -394,6 +441,7 @@ impl DiffParser {
                 ChangeType::Removed => {
                     let line_no = old_line;
                     old_line += 1;
+                    old_remaining = old_remaining.saturating_sub(1);
                     DiffLine {
                         old_line_no: Some(line_no),
                         new_line_no: None,
@@ -406,6 +454,8 @@ impl DiffParser {
                     let new_no = new_line;
                     old_line += 1;
                     new_line += 1;
+                    old_remaining = old_remaining.saturating_sub(1);
+                    new_remaining = new_remaining.saturating_sub(1);
                     DiffLine {
                         old_line_no: Some(old_no),
                         new_line_no: Some(new_no),
@@ -540,4 +590,64 @@ index 0000000..f735c20\n\
         assert!(diffs[0].is_new);
         assert!(!diffs[0].is_deleted);
     }
+
+    #[test]
+    fn test_parse_new_file_with_marker_like_lines() {
+        let diff_text = "\
+diff --git a/query.sql b/query.sql\n\
+new file mode 100644\n\
+index 0000000..f735c20\n\
+--- /dev/null\n\
++++ b/query.sql\n\
+@@ -0,0 +1,2 @@\n\
++++ counter\n\
++SELECT 1;\n\
+diff --git a/old.sql b/old.sql\n\
+deleted file mode 100644\n\
+index 83db48f..0000000\n\
+--- a/old.sql\n\
++++ /dev/null\n\
+@@ -1,2 +0,0 @@\n\
+--- legacy\n\
+-SELECT 2;\n";
+
+        let diffs = DiffParser::parse_unified_diff(diff_text).unwrap();
+        assert_eq!(diffs.len(), 2);
+        assert!(diffs[0].is_new);
+        assert_eq!(diffs[0].hunks[0].changes.len(), 2);
+        assert_eq!(diffs[0].hunks[0].changes[0].content, "++ counter");
+        assert!(diffs[1].is_deleted);
+        assert_eq!(diffs[1].file_path, PathBuf::from("old.sql"));
+        assert_eq!(diffs[1].hunks[0].changes[0].content, "-- legacy");
+    }
+
+    #[test]
+    fn test_parse_renamed_file() {
+        let diff_text = "\
+diff --git a/src/old name.rs b/src/new name.rs\n\
+similarity index 92%\n\
+rename from src/old name.rs\n\
+rename to src/new name.rs\n\
+index 83db48f..f735c20 100644\n\
+--- a/src/old name.rs\n\
++++ b/src/new name.rs\n\
+@@ -1,1 +1,1 @@\n\
+-hello\n\
++world\n\
+diff --git a/docs/a.md b/guide/a.md\n\
+similarity index 100%\n\
+rename from docs/a.md\n\
+rename to guide/a.md\n";
+
+        let diffs = DiffParser::parse_unified_diff(diff_text).unwrap();
+        assert_eq!(diffs.len(), 2);
+        assert!(diffs[0].is_renamed);
+        assert!(!diffs[0].is_new);
+        assert_eq!(diffs[0].file_path, PathBuf::from("src/new name.rs"));
+        assert_eq!(diffs[0].old_path, Some(PathBuf::from("src/old name.rs")));
+        assert_eq!(diffs[0].hunks.len(), 1);
+        assert!(diffs[1].is_renamed);
+        assert!(diffs[1].hunks.is_empty());
+        assert_eq!(diffs[1].old_path, Some(PathBuf::from("docs/a.md")));
+    }
 }
@@ -0,0 +1,438 @@
+use crate::adapters::llm::{create_adapter, LLMRequest};
+use crate::config::Config;
+use crate::core::{GitIntegration, SymbolIndex};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// How long the model check waits for a reply.
+const MODEL_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Files sampled when deciding which analyzers matter for the repository.
+const MAX_SCANNED_FILES: usize = 5000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Ok,
+    Warn,
+    Fail,
+}
+
+/// One `diffscope doctor` finding, with the command or setting that fixes it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Check {
+    pub name: String,
+    pub status: Status,
+    pub detail: String,
+    pub fix: Option<String>,
+}
+
+impl Check {
+    fn new(name: &str, status: Status, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status,
+            detail: detail.into(),
+            fix: None,
+        }
+    }
+
+    fn fix(mut self, fix: impl Into<String>) -> Self {
+        self.fix = Some(fix.into());
+        self
+    }
+}
+
+/// One check per config file `Config::load` reads (see `check_config_file`), plus one for
+/// merging them.
+pub fn check_config_files(paths: &[PathBuf]) -> Vec<Check> {
+    if paths.is_empty() {
+        return vec![Check::new(
+            "config",
+            Status::Ok,
+            "no .diffscope.yml found; using defaults",
+        )
+        .fix("run `diffscope init` to generate one for this repository")];
+    }
+    let mut checks: Vec<Check> = paths.iter().map(|path| check_config_file(path)).collect();
+    if checks.iter().all(|check| check.status != Status::Fail) {
+        if let Err(err) = Config::load() {
+            checks.push(
+                Check::new("config", Status::Fail, format!("{:#}", err))
+                    .fix("point `extends` at an existing file that does not extend this one"),
+            );
+        }
+    }
+    checks
+}
+
+/// Parses the configuration file at `path` and reports settings that would be silently
+/// ignored because no such key exists.
+pub fn check_config_file(path: &Path) -> Check {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) => {
+            return Check::new(
+                "config",
+                Status::Fail,
+                format!("cannot read {}: {}", path.display(), err),
+            )
+        }
+    };
+    let issues = crate::config::validate(&content);
+    let listed = issues
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ");
+    if serde_yaml::from_str::<Config>(&content).is_err() {
+        // `Config::load` falls back to the defaults on this error, so reviews quietly ignore
+        // the whole file.
+        return Check::new(
+            "config",
+            Status::Fail,
+            format!("{} is invalid: {}", path.display(), listed),
+        )
+        .fix("fix the setting above; until then every command runs with the default config");
+    }
+    if issues.is_empty() {
+        return Check::new("config", Status::Ok, format!("{} is valid", path.display()));
+    }
+    Check::new(
+        "config",
+        Status::Warn,
+        format!(
+            "{} has settings that are ignored: {}",
+            path.display(),
+            listed
+        ),
+    )
+    .fix("correct the keys; `diffscope config validate` lists them all")
+}
+
+/// Builds the configured plugins, which validates their `plugins.settings` entries.
+pub async fn check_plugins(config: &Config) -> Check {
+    let mut manager = crate::plugins::plugin::PluginManager::new();
+    match manager.load_builtin_plugins(&config.plugins).await {
+        Ok(()) => Check::new(
+            "plugins",
+            Status::Ok,
+            format!("{} plugins loaded", manager.loaded_plugins().len()),
+        ),
+        Err(err) => Check::new("plugins", Status::Fail, format!("{:#}", err))
+            .fix("fix the plugin settings in .diffscope.yml (see `diffscope plugins list`)"),
+    }
+}
+
+/// Resolves the API key for the configured model and, unless `offline`, sends it a one-line
+/// request to confirm the key is accepted and the model is reachable.
+pub async fn check_model(config: &Config, offline: bool) -> Check {
+    let name = "model";
+    let adapter = match create_adapter(&config.model_config()) {
+        Ok(adapter) => adapter,
+        Err(err) => {
+            return Check::new(name, Status::Fail, format!("{}: {:#}", config.model, err)).fix(
+                "export the provider's API key, set `api_key` in .diffscope.yml, or pick another model with --model",
+            )
+        }
+    };
+    if let Some(dir) = &config.llm_replay_dir {
+        return Check::new(
+            name,
+            Status::Ok,
+            format!(
+                "{}: replaying recorded responses from {}",
+                config.model,
+                dir.display()
+            ),
+        );
+    }
+    if offline {
+        return Check::new(
+            name,
+            Status::Ok,
+            format!(
+                "{}: credentials found (request skipped with --offline)",
+                config.model
+            ),
+        );
+    }
+
+    let request = LLMRequest {
+        system_prompt: "Reply with the single word OK.".to_string(),
+        user_prompt: "ping".to_string(),
+        temperature: Some(0.0),
+        max_tokens: Some(5),
+        cached_context: None,
+    };
+    let started = Instant::now();
+    match tokio::time::timeout(MODEL_TIMEOUT, adapter.complete(request)).await {
+        Ok(Ok(_)) => Check::new(
+            name,
+            Status::Ok,
+            format!(
+                "{} responded in {} ms",
+                config.model,
+                started.elapsed().as_millis()
+            ),
+        ),
+        Ok(Err(err)) => {
+            // HTTP clients repeat each cause in the message above it; the ends are enough.
+            let detail = match err.chain().count() {
+                1 => err.to_string(),
+                _ => format!("{}: {}", err, err.root_cause()),
+            };
+            let fix = model_error_fix(&detail);
+            Check::new(name, Status::Fail, format!("{}: {}", config.model, detail)).fix(fix)
+        }
+        Err(_) => Check::new(
+            name,
+            Status::Fail,
+            format!(
+                "{} did not respond within {} s",
+                config.model,
+                MODEL_TIMEOUT.as_secs()
+            ),
+        )
+        .fix("check your network connection and `base_url`, or raise `timeout_secs`"),
+    }
+}
+
+/// The likely remedy for a failed model request, from its error text.
+fn model_error_fix(error: &str) -> &'static str {
+    let error = error.to_ascii_lowercase();
+    if error.contains("401") || error.contains("403") || error.contains("api key") {
+        "the API key was rejected; create a new one or check it belongs to this provider"
+    } else if error.contains("404") || error.contains("model") && error.contains("not") {
+        "the model name was not recognized; check `model` (for Ollama, `ollama pull` it first)"
+    } else if error.contains("429") {
+        "the provider is rate limiting or out of quota; check your plan or set `rate_limits`"
+    } else {
+        "check your network connection and `base_url`"
+    }
+}
+
+/// Whether `path` is inside a usable git repository with history deep enough to diff.
+pub fn check_git(path: &Path) -> Check {
+    let name = "git";
+    let git = match GitIntegration::new(path) {
+        Ok(git) => git,
+        Err(_) => {
+            return Check::new(name, Status::Warn, "not inside a git repository").fix(
+                "run from a repository to use `diffscope git` and `pr`; piped diffs still work",
+            )
+        }
+    };
+    let Some(workdir) = git.workdir() else {
+        return Check::new(
+            name,
+            Status::Warn,
+            "bare repository; there is no working tree to review",
+        )
+        .fix("run from a checkout");
+    };
+    if git.get_recent_commits(1).is_err() {
+        return Check::new(
+            name,
+            Status::Warn,
+            format!("{} has no commits yet", workdir.display()),
+        )
+        .fix("make a first commit; `diffscope git staged` works meanwhile");
+    }
+    if git.is_shallow() {
+        return Check::new(
+            name,
+            Status::Warn,
+            format!("{} is a shallow clone", workdir.display()),
+        )
+        .fix("run `git fetch --unshallow` (in CI, check out with fetch-depth: 0) so branch diffs find their merge base");
+    }
+    let branch = git
+        .get_current_branch()
+        .unwrap_or_else(|_| "HEAD".to_string());
+    Check::new(
+        name,
+        Status::Ok,
+        format!("{} on {}", workdir.display(), branch),
+    )
+}
+
+/// Plugin, the program it runs, file extensions it analyzes (empty: any), and what it falls
+/// back to without the program.
+type Analyzer = (
+    &'static str,
+    &'static str,
+    &'static [&'static str],
+    Option<&'static str>,
+);
+
+const ANALYZERS: &[Analyzer] = &[
+    (
+        "eslint",
+        "eslint",
+        &["js", "jsx", "ts", "tsx", "mjs", "cjs"],
+        None,
+    ),
+    ("ruff", "ruff", &["py", "pyi"], None),
+    ("golangci", "golangci-lint", &["go"], None),
+    ("semgrep", "semgrep", &[], None),
+    ("iac", "tflint", &["tf"], Some("built-in IaC rules")),
+    (
+        "secret_scan",
+        "gitleaks",
+        &[],
+        Some("built-in secret rules"),
+    ),
+];
+
+/// Availability of the LSP server and the external analyzers the enabled plugins run,
+/// skipping analyzers for languages the repository does not contain.
+pub fn check_tools(config: &Config, repo_root: &Path) -> Vec<Check> {
+    let mut checks = Vec::new();
+    if config.symbol_index && config.symbol_index_provider == "lsp" {
+        let command = config.symbol_index_lsp_command.clone().or_else(|| {
+            SymbolIndex::detect_lsp_command(
+                repo_root,
+                config.symbol_index_max_files,
+                &config.symbol_index_lsp_languages,
+                |path| config.should_exclude(path),
+            )
+        });
+        checks.push(match command {
+            Some(command) if SymbolIndex::lsp_command_available(&command) => {
+                Check::new("lsp", Status::Ok, format!("{} found", command))
+            }
+            Some(command) => Check::new("lsp", Status::Fail, format!("{} is not on PATH", command))
+                .fix("install it, or set `symbol_index_lsp_command`; `diffscope lsp-check` shows the details"),
+            None => Check::new("lsp", Status::Warn, "no LSP server matches the repository's languages")
+                .fix("set `symbol_index_lsp_command`, or `symbol_index_provider: regex`"),
+        });
+    }
+
+    let counts = SymbolIndex::scan_extension_counts(repo_root, MAX_SCANNED_FILES, |path| {
+        config.should_exclude(path)
+    });
+    let plugins = &config.plugins;
+    for (plugin, program, extensions, fallback) in ANALYZERS {
+        let enabled = match *plugin {
+            "eslint" => plugins.eslint,
+            "ruff" => plugins.ruff,
+            "golangci" => plugins.golangci,
+            "semgrep" => plugins.semgrep,
+            "iac" => plugins.iac,
+            "secret_scan" => plugins.secret_scan,
+            _ => false,
+        };
+        let relevant =
+            extensions.is_empty() || extensions.iter().any(|ext| counts.contains_key(*ext));
+        if !enabled || !relevant {
+            continue;
+        }
+        let name = format!("plugins.{}", plugin);
+        checks.push(if SymbolIndex::lsp_command_available(program) {
+            Check::new(&name, Status::Ok, format!("{} found", program))
+        } else {
+            let detail = match fallback {
+                Some(fallback) => format!("{} is not on PATH; using {}", program, fallback),
+                None => format!("{} is not on PATH; the plugin is skipped", program),
+            };
+            Check::new(&name, Status::Warn, detail).fix(format!(
+                "install {}, or set `plugins.{}: false` to silence this",
+                program, plugin
+            ))
+        });
+    }
+
+    let on_github = GitIntegration::new(repo_root)
+        .and_then(|git| git.get_remote_url())
+        .ok()
+        .flatten()
+        .is_some_and(|url| url.contains("github.com"));
+    if on_github && !SymbolIndex::lsp_command_available("gh") {
+        checks.push(
+            Check::new(
+                "gh",
+                Status::Warn,
+                "the GitHub CLI is not on PATH; `diffscope pr` needs it",
+            )
+            .fix("install it from https://cli.github.com and run `gh auth login`"),
+        );
+    }
+    checks
+}
+
+/// The checks as aligned `[ok]`/`[warn]`/`[fail]` lines, each failure followed by its fix.
+pub fn render(checks: &[Check]) -> String {
+    let width = checks
+        .iter()
+        .map(|check| check.name.len())
+        .max()
+        .unwrap_or(0);
+    let mut out = String::new();
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for check in checks {
+        let label = match check.status {
+            Status::Ok => "ok",
+            Status::Warn => "warn",
+            Status::Fail => "fail",
+        };
+        *counts.entry(label).or_default() += 1;
+        out.push_str(&format!(
+            "{:<6} {:<width$}  {}\n",
+            format!("[{}]", label),
+            check.name,
+            check.detail
+        ));
+        if let (Some(fix), false) = (&check.fix, check.status == Status::Ok) {
+            out.push_str(&format!("{:<6} {:<width$}  fix: {}\n", "", "", fix));
+        }
+    }
+    out.push_str(&format!(
+        "\n{} ok, {} warnings, {} failures\n",
+        counts.get("ok").unwrap_or(&0),
+        counts.get("warn").unwrap_or(&0),
+        counts.get("fail").unwrap_or(&0)
+    ));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_config_git_and_tool_problems_with_fixes() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join(".diffscope.yml");
+
+        std::fs::write(&config_path, "model: gpt-4o\nmax_token: 100\n").unwrap();
+        let check = check_config_file(&config_path);
+        assert_eq!(check.status, Status::Warn);
+        assert!(check
+            .detail
+            .ends_with("line 2: unknown key `max_token` (did you mean `max_tokens`?)"));
+
+        std::fs::write(&config_path, "temperature: hot\n").unwrap();
+        assert_eq!(check_config_file(&config_path).status, Status::Fail);
+        assert_eq!(check_config_files(&[])[0].status, Status::Ok);
+
+        assert_eq!(check_git(dir.path()).status, Status::Warn);
+
+        std::fs::write(dir.path().join("app.py"), "print('hi')\n").unwrap();
+        let mut config = Config::default();
+        config.plugins.ruff = true;
+        config.plugins.golangci = true;
+        let checks = check_tools(&config, dir.path());
+        assert!(checks.iter().any(|check| check.name == "plugins.ruff"));
+        assert!(!checks.iter().any(|check| check.name == "plugins.golangci"));
+
+        let rendered = render(&[
+            Check::new("git", Status::Ok, "repo on main").fix("unused"),
+            Check::new("model", Status::Fail, "gpt-4o: 401").fix("new key"),
+        ]);
+        assert_eq!(
+            rendered,
+            "[ok]   git    repo on main\n[fail] model  gpt-4o: 401\n              fix: new key\n\n1 ok, 0 warnings, 1 failures\n"
+        );
+    }
+}
@@ -0,0 +1,340 @@
+use crate::adapters::embeddings::Embedder;
+use crate::core::cache::stable_hash;
+use crate::core::diff_parser::ChangeType;
+use crate::core::{languages, ContextType, LLMContextChunk, UnifiedDiff};
+use anyhow::Result;
+use ignore::WalkBuilder;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+/// Texts sent per embeddings request.
+const EMBED_BATCH: usize = 64;
+
+/// Windows with fewer non-blank lines than this are not worth retrieving.
+const MIN_CHUNK_LINES: usize = 3;
+
+/// A window of a source file, as indexed for semantic search.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodeChunk {
+    pub file_path: PathBuf,
+    /// 1-based, inclusive.
+    pub line_range: (usize, usize),
+    pub content: String,
+}
+
+impl CodeChunk {
+    fn key(&self) -> String {
+        stable_hash(&[&self.file_path.to_string_lossy(), &self.content])
+    }
+
+    fn embedding_text(&self) -> String {
+        format!("{}\n{}", self.file_path.display(), self.content)
+    }
+}
+
+/// Splits the repository's source files (respecting .gitignore and `should_exclude`) into
+/// windows of `chunk_lines` lines, stopping at `max_chunks`.
+pub fn chunk_repository<F>(
+    repo_root: &Path,
+    chunk_lines: usize,
+    max_chunks: usize,
+    max_file_bytes: usize,
+    should_exclude: F,
+) -> Vec<CodeChunk>
+where
+    F: Fn(&Path) -> bool,
+{
+    let mut chunks = Vec::new();
+    let chunk_lines = chunk_lines.max(MIN_CHUNK_LINES);
+    let walker = WalkBuilder::new(repo_root)
+        .hidden(true)
+        .git_ignore(true)
+        .build();
+    for entry in walker.flatten() {
+        let path = entry.path();
+        if !path.is_file() {
+            continue;
+        }
+        let relative = path.strip_prefix(repo_root).unwrap_or(path);
+        if should_exclude(relative)
+            || languages::detect(relative).is_none()
+            || !languages::categories(relative).is_empty()
+        {
+            continue;
+        }
+        if std::fs::metadata(path).map_or(true, |meta| meta.len() as usize > max_file_bytes) {
+            continue;
+        }
+        let Ok(bytes) = std::fs::read(path) else {
+            continue;
+        };
+        if bytes.iter().take(2048).any(|byte| *byte == 0) {
+            continue;
+        }
+        let content = String::from_utf8_lossy(&bytes);
+        let lines: Vec<&str> = content.lines().collect();
+        for (window, start) in lines.chunks(chunk_lines).zip((0..).step_by(chunk_lines)) {
+            if window.iter().filter(|line| !line.trim().is_empty()).count() < MIN_CHUNK_LINES {
+                continue;
+            }
+            if chunks.len() >= max_chunks {
+                return chunks;
+            }
+            chunks.push(CodeChunk {
+                file_path: relative.to_path_buf(),
+                line_range: (start + 1, start + window.len()),
+                content: window.join("\n"),
+            });
+        }
+    }
+    chunks
+}
+
+fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a * norm_b)
+}
+
+/// Code chunks with their embeddings, for finding code similar to a change.
+pub struct EmbeddingIndex {
+    entries: Vec<(CodeChunk, Vec<f32>)>,
+}
+
+impl EmbeddingIndex {
+    /// Embeds `chunks`, reusing the vectors stored in `cache_file` for chunks whose path
+    /// and content are unchanged and writing the updated vectors back.
+    pub async fn build(
+        embedder: &dyn Embedder,
+        chunks: Vec<CodeChunk>,
+        cache_file: Option<&Path>,
+    ) -> Result<Self> {
+        let mut cached: HashMap<String, Vec<f32>> = cache_file
+            .and_then(|path| std::fs::read(path).ok())
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default();
+
+        let missing: Vec<&CodeChunk> = chunks
+            .iter()
+            .filter(|chunk| !cached.contains_key(&chunk.key()))
+            .collect();
+        if !missing.is_empty() {
+            info!(
+                "Embedding {} of {} code chunks with {}",
+                missing.len(),
+                chunks.len(),
+                embedder.model_name()
+            );
+        }
+        for batch in missing.chunks(EMBED_BATCH) {
+            let texts: Vec<String> = batch.iter().map(|chunk| chunk.embedding_text()).collect();
+            let vectors = embedder.embed(&texts).await?;
+            for (chunk, vector) in batch.iter().zip(vectors) {
+                cached.insert(chunk.key(), vector);
+            }
+        }
+
+        let entries: Vec<(CodeChunk, Vec<f32>)> = chunks
+            .into_iter()
+            .filter_map(|chunk| {
+                let vector = cached.get(&chunk.key())?.clone();
+                Some((chunk, vector))
+            })
+            .collect();
+        if let Some(path) = cache_file {
+            // Keep only live chunks so the cache does not grow without bound.
+            let live: HashMap<String, &Vec<f32>> = entries
+                .iter()
+                .map(|(chunk, vector)| (chunk.key(), vector))
+                .collect();
+            let written = path
+                .parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|_| std::fs::write(path, serde_json::to_vec(&live)?));
+            if let Err(err) = written {
+                warn!(
+                    "Failed to write embeddings cache {}: {}",
+                    path.display(),
+                    err
+                );
+            }
+        }
+        Ok(Self { entries })
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Up to `top_k` chunks per hunk of `diff` at least `min_similarity` alike, best first,
+    /// leaving out the lines the hunks themselves cover.
+    pub async fn related(
+        &self,
+        embedder: &dyn Embedder,
+        diff: &UnifiedDiff,
+        top_k: usize,
+        min_similarity: f32,
+    ) -> Result<Vec<LLMContextChunk>> {
+        if self.is_empty() || top_k == 0 || diff.hunks.is_empty() {
+            return Ok(Vec::new());
+        }
+        let queries: Vec<String> = diff
+            .hunks
+            .iter()
+            .map(|hunk| {
+                let code: Vec<&str> = hunk
+                    .changes
+                    .iter()
+                    .filter(|change| change.change_type != ChangeType::Removed)
+                    .map(|change| change.content.as_str())
+                    .collect();
+                format!("{}\n{}", diff.file_path.display(), code.join("\n"))
+            })
+            .collect();
+        let vectors = embedder.embed(&queries).await?;
+
+        let mut best: HashMap<usize, f32> = HashMap::new();
+        for (hunk, query) in diff.hunks.iter().zip(&vectors) {
+            let hunk_end = hunk.new_start + hunk.new_lines;
+            let mut scored: Vec<(usize, f32)> = self
+                .entries
+                .iter()
+                .enumerate()
+                .filter(|(_, (chunk, _))| {
+                    chunk.file_path != diff.file_path
+                        || chunk.line_range.1 < hunk.new_start
+                        || chunk.line_range.0 > hunk_end
+                })
+                .map(|(index, (_, vector))| (index, cosine(query, vector)))
+                .filter(|(_, score)| *score >= min_similarity)
+                .collect();
+            scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+            for (index, score) in scored.into_iter().take(top_k) {
+                let entry = best.entry(index).or_insert(score);
+                *entry = entry.max(score);
+            }
+        }
+
+        let mut ranked: Vec<(usize, f32)> = best.into_iter().collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        Ok(ranked
+            .into_iter()
+            .map(|(index, score)| {
+                let chunk = &self.entries[index].0;
+                LLMContextChunk {
+                    file_path: chunk.file_path.clone(),
+                    content: format!(
+                        "Similar code ({:.0}% alike):\n{}",
+                        score * 100.0,
+                        chunk.content
+                    ),
+                    context_type: ContextType::Reference,
+                    line_range: Some(chunk.line_range),
+                }
+            })
+            .collect())
+    }
+}
+
+/// An embedder with the index it built, set up for a review (`embeddings`).
+pub struct SemanticSearch {
+    pub embedder: Box<dyn Embedder>,
+    pub index: EmbeddingIndex,
+    pub top_k: usize,
+    pub min_similarity: f32,
+}
+
+impl SemanticSearch {
+    /// Code similar to the hunks of `diff`; nothing when the search fails.
+    pub async fn related(&self, diff: &UnifiedDiff) -> Vec<LLMContextChunk> {
+        self.index
+            .related(
+                self.embedder.as_ref(),
+                diff,
+                self.top_k,
+                self.min_similarity,
+            )
+            .await
+            .unwrap_or_else(|err| {
+                warn!(
+                    "Semantic search for {} failed: {:#}",
+                    diff.file_path.display(),
+                    err
+                );
+                Vec::new()
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+
+    /// Counts a few telltale words, so texts about the same thing point the same way.
+    struct Keywords;
+
+    #[async_trait]
+    impl Embedder for Keywords {
+        async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+            Ok(texts
+                .iter()
+                .map(|text| {
+                    ["retry", "backoff", "render", "html"]
+                        .iter()
+                        .map(|word| text.matches(word).count() as f32)
+                        .collect()
+                })
+                .collect())
+        }
+
+        fn model_name(&self) -> &str {
+            "keywords"
+        }
+    }
+
+    #[tokio::test]
+    async fn retrieves_similar_code_from_other_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(
+            root.join("src/http.rs"),
+            "fn fetch() {\n    retry(3);\n    backoff(2);\n    retry_again();\n}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("src/view.rs"),
+            "fn page() {\n    render(1);\n    html(2);\n    render_again();\n}\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("Cargo.lock"), "retry\nretry\nretry\nretry\n").unwrap();
+
+        let chunks = chunk_repository(root, 40, 100, 100_000, |_| false);
+        assert_eq!(chunks.len(), 2);
+        let cache = root.join("cache/embeddings.json");
+        let index = EmbeddingIndex::build(&Keywords, chunks, Some(&cache))
+            .await
+            .unwrap();
+        assert!(cache.is_file());
+
+        let diff = crate::core::DiffParser::parse_unified_diff(
+            "--- a/src/sync.rs\n+++ b/src/sync.rs\n@@ -1 +1,2 @@\n fn sync() {\n+    retry_with_backoff();\n",
+        )
+        .unwrap()
+        .remove(0);
+        let related = index.related(&Keywords, &diff, 3, 0.5).await.unwrap();
+        assert_eq!(related.len(), 1);
+        assert_eq!(related[0].file_path, PathBuf::from("src/http.rs"));
+        assert_eq!(related[0].line_range, Some((1, 5)));
+    }
+}
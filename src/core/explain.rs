@@ -0,0 +1,87 @@
+use crate::core::function_scope::expand_to_enclosing_scope;
+use std::path::{Path, PathBuf};
+
+/// Lines shown on each side of `path:line` when no enclosing function is found.
+const LINE_CONTEXT: usize = 15;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExplainTarget {
+    /// A file in the repository, optionally narrowed to the code around a 1-based line.
+    File { path: PathBuf, line: Option<usize> },
+    /// Anything that is not a file is treated as a revision.
+    Commit(String),
+}
+
+/// Reads `path`, `path:line`, or a commit-ish, checking paths against `repo_root`.
+pub fn parse_target(repo_root: &Path, target: &str) -> ExplainTarget {
+    if repo_root.join(target).is_file() {
+        return ExplainTarget::File {
+            path: PathBuf::from(target),
+            line: None,
+        };
+    }
+    if let Some((path, line)) = target.rsplit_once(':') {
+        if let Ok(line) = line.parse::<usize>() {
+            if line > 0 && repo_root.join(path).is_file() {
+                return ExplainTarget::File {
+                    path: PathBuf::from(path),
+                    line: Some(line),
+                };
+            }
+        }
+    }
+    ExplainTarget::Commit(target.to_string())
+}
+
+/// The 1-based line range to explain for `line`: its enclosing function or class, or a
+/// window around it when the language is unsupported or the line is outside any definition.
+pub fn range_around(path: &Path, lines: &[&str], line: usize) -> (usize, usize) {
+    let scope = expand_to_enclosing_scope(path, lines, (line, line));
+    if scope != (line, line) {
+        return scope;
+    }
+    (
+        line.saturating_sub(LINE_CONTEXT).max(1),
+        (line + LINE_CONTEXT).min(lines.len().max(1)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_files_lines_and_commits() {
+        let repo = tempfile::tempdir().unwrap();
+        std::fs::create_dir(repo.path().join("src")).unwrap();
+        std::fs::write(
+            repo.path().join("src/lib.rs"),
+            "use std::fmt;\n\npub fn total(items: &[u32]) -> u32 {\n    items.iter().sum()\n}\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            parse_target(repo.path(), "src/lib.rs"),
+            ExplainTarget::File {
+                path: "src/lib.rs".into(),
+                line: None
+            }
+        );
+        assert_eq!(
+            parse_target(repo.path(), "src/lib.rs:4"),
+            ExplainTarget::File {
+                path: "src/lib.rs".into(),
+                line: Some(4)
+            }
+        );
+        assert_eq!(
+            parse_target(repo.path(), "HEAD~2"),
+            ExplainTarget::Commit("HEAD~2".to_string())
+        );
+
+        let source = std::fs::read_to_string(repo.path().join("src/lib.rs")).unwrap();
+        let lines: Vec<&str> = source.lines().collect();
+        assert_eq!(range_around(Path::new("src/lib.rs"), &lines, 4), (3, 5));
+        assert_eq!(range_around(Path::new("notes.txt"), &lines, 4), (1, 5));
+    }
+}
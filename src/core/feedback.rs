@@ -0,0 +1,785 @@
+use crate::config::Config;
+use crate::core::{similarity, Comment};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Current accept/reject state per comment id. This is the whole JSON feedback file, and
+/// the view reviews use to suppress findings with either backend.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct FeedbackStore {
+    #[serde(default)]
+    pub suppress: HashSet<String>,
+    #[serde(default)]
+    pub accept: HashSet<String>,
+
+    /// Text of rejected findings by id, for suppressing reworded repeats.
+    #[serde(default)]
+    pub rejected: HashMap<String, RejectedFinding>,
+
+    /// Category and stated confidence of decided findings by id, for confidence calibration.
+    #[serde(default)]
+    pub decided: HashMap<String, DecidedFinding>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DecidedFinding {
+    pub category: String,
+    /// The confidence the finding was posted with; unknown for findings posted before it was
+    /// recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RejectedFinding {
+    pub file_path: String,
+    pub category: String,
+    pub content: String,
+
+    /// Where rewordings of this finding are suppressed.
+    #[serde(default)]
+    pub scope: FeedbackScope,
+
+    /// `owner/name` of the repository it was rejected in, for `repo` scope.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repo: Option<String>,
+}
+
+/// How far a rejection reaches beyond the exact comment.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum FeedbackScope {
+    /// Only the file the finding was rejected in.
+    #[default]
+    File,
+    /// The file's directory and everything below it.
+    Directory,
+    /// Any file in the same repository.
+    Repo,
+    /// Every repository sharing this feedback store.
+    Global,
+}
+
+impl FeedbackScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FeedbackScope::File => "file",
+            FeedbackScope::Directory => "directory",
+            FeedbackScope::Repo => "repo",
+            FeedbackScope::Global => "global",
+        }
+    }
+
+    #[cfg_attr(not(feature = "sqlite-feedback"), allow(dead_code))]
+    fn parse(value: &str) -> Self {
+        match value {
+            "directory" => FeedbackScope::Directory,
+            "repo" => FeedbackScope::Repo,
+            "global" => FeedbackScope::Global,
+            _ => FeedbackScope::File,
+        }
+    }
+}
+
+impl RejectedFinding {
+    fn covers(&self, file_path: &str, repo: Option<&str>) -> bool {
+        if self.scope == FeedbackScope::Global {
+            return true;
+        }
+        // A shared store holds several repositories' rejections; only `global` ones cross
+        // between them. Rejections recorded without a repository predate repository tagging
+        // and come from a store local to one repository.
+        let same_repo = match (self.repo.as_deref(), repo) {
+            (Some(rejected_in), Some(repo)) => rejected_in.eq_ignore_ascii_case(repo),
+            (Some(_), None) => false,
+            (None, _) => true,
+        };
+        same_repo
+            && match self.scope {
+                FeedbackScope::File => self.file_path == file_path,
+                FeedbackScope::Directory => {
+                    let dir = Path::new(&self.file_path)
+                        .parent()
+                        .unwrap_or_else(|| Path::new(""));
+                    Path::new(file_path).starts_with(dir)
+                }
+                FeedbackScope::Repo | FeedbackScope::Global => true,
+            }
+    }
+}
+
+impl FeedbackStore {
+    /// Records `id` as accepted; returns whether that changed anything.
+    pub fn accept_id(&mut self, id: &str) -> bool {
+        let changed = self.suppress.remove(id);
+        self.rejected.remove(id);
+        self.accept.insert(id.to_string()) || changed
+    }
+
+    /// Records `id` as rejected so it is suppressed in later reviews.
+    pub fn reject_id(&mut self, id: &str) -> bool {
+        let changed = self.accept.remove(id);
+        self.suppress.insert(id.to_string()) || changed
+    }
+
+    /// Applies one decision; returns whether it changed the comment's current state.
+    pub fn record(&mut self, event: &FeedbackEvent) -> bool {
+        if let Some(category) = &event.category {
+            self.decided.insert(
+                event.comment_id.clone(),
+                DecidedFinding {
+                    category: category.clone(),
+                    confidence: event.confidence,
+                },
+            );
+        }
+        match event.decision {
+            Decision::Accept => self.accept_id(&event.comment_id),
+            Decision::Reject => {
+                if let Some(rejected) = event.rejected_finding() {
+                    self.rejected.insert(event.comment_id.clone(), rejected);
+                }
+                self.reject_id(&event.comment_id)
+            }
+        }
+    }
+
+    /// Every current decision as an event, e.g. to replay an exported store into a log.
+    pub fn events(&self) -> Vec<FeedbackEvent> {
+        let accepted = self.accept.iter().map(|id| FeedbackEvent {
+            comment_id: id.clone(),
+            decision: Decision::Accept,
+            scope: FeedbackScope::File,
+            repo: None,
+            file_path: None,
+            content: None,
+            category: self.decided.get(id).map(|found| found.category.clone()),
+            severity: None,
+            confidence: self.decided.get(id).and_then(|found| found.confidence),
+        });
+        let rejected = self.suppress.iter().map(|id| {
+            let finding = self.rejected.get(id);
+            FeedbackEvent {
+                comment_id: id.clone(),
+                decision: Decision::Reject,
+                scope: finding.map(|finding| finding.scope).unwrap_or_default(),
+                repo: finding.and_then(|finding| finding.repo.clone()),
+                file_path: finding.map(|finding| finding.file_path.clone()),
+                content: finding.map(|finding| finding.content.clone()),
+                category: finding
+                    .map(|finding| finding.category.clone())
+                    .or_else(|| self.decided.get(id).map(|found| found.category.clone())),
+                severity: None,
+                confidence: self.decided.get(id).and_then(|found| found.confidence),
+            }
+        });
+        accepted.chain(rejected).collect()
+    }
+
+    /// Adds decisions from `other` (e.g. the team's shared store) without overriding any
+    /// decision already made here.
+    pub fn merge(&mut self, other: FeedbackStore) {
+        for (id, decided) in other.decided {
+            let known = self.accept.contains(&id) || self.suppress.contains(&id);
+            if !known {
+                self.decided.entry(id).or_insert(decided);
+            }
+        }
+        for id in other.suppress {
+            if self.accept.contains(&id) {
+                continue;
+            }
+            if let Some(rejected) = other.rejected.get(&id) {
+                self.rejected
+                    .entry(id.clone())
+                    .or_insert_with(|| rejected.clone());
+            }
+            self.suppress.insert(id);
+        }
+        for id in other.accept {
+            if !self.suppress.contains(&id) {
+                self.accept.insert(id);
+            }
+        }
+    }
+
+    /// Whether `comment` (reviewed in `repo`) was rejected before: by id, or by a rejected
+    /// finding of the same category within that rejection's scope whose wording is at least
+    /// `threshold` similar. A `threshold` of 0 only matches identical wording.
+    pub fn is_suppressed(&self, comment: &Comment, repo: Option<&str>, threshold: f32) -> bool {
+        if self.suppress.contains(&comment.id) {
+            return true;
+        }
+        if self.rejected.is_empty() {
+            return false;
+        }
+        let threshold = if threshold > 0.0 {
+            threshold as f64
+        } else {
+            1.0
+        };
+        let file_path = comment.file_path.display().to_string();
+        let category = format!("{:?}", comment.category);
+        self.rejected.iter().any(|(id, rejected)| {
+            self.suppress.contains(id)
+                && rejected.category == category
+                && rejected.covers(&file_path, repo)
+                && similarity::word_similarity(&rejected.content, &comment.content) >= threshold
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    Accept,
+    Reject,
+}
+
+impl Decision {
+    #[cfg_attr(not(feature = "sqlite-feedback"), allow(dead_code))]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Decision::Accept => "accept",
+            Decision::Reject => "reject",
+        }
+    }
+}
+
+/// One accept/reject decision on a finding. Everything but the id is kept for history; the
+/// JSON backend only keeps what fuzzy suppression needs.
+#[derive(Debug, Clone)]
+#[cfg_attr(not(feature = "sqlite-feedback"), allow(dead_code))]
+pub struct FeedbackEvent {
+    pub comment_id: String,
+    pub decision: Decision,
+    pub scope: FeedbackScope,
+    pub repo: Option<String>,
+    pub file_path: Option<String>,
+    pub content: Option<String>,
+    pub category: Option<String>,
+    pub severity: Option<String>,
+    pub confidence: Option<f32>,
+}
+
+impl FeedbackEvent {
+    fn rejected_finding(&self) -> Option<RejectedFinding> {
+        Some(RejectedFinding {
+            file_path: self.file_path.clone()?,
+            category: self.category.clone()?,
+            content: self.content.clone()?,
+            scope: self.scope,
+            repo: self.repo.clone(),
+        })
+    }
+
+    pub fn for_comment(
+        comment: &Comment,
+        decision: Decision,
+        scope: FeedbackScope,
+        repo: Option<String>,
+    ) -> Self {
+        Self {
+            comment_id: comment.id.clone(),
+            decision,
+            scope,
+            repo,
+            file_path: Some(comment.file_path.display().to_string()),
+            content: Some(comment.content.clone()),
+            category: Some(format!("{:?}", comment.category)),
+            severity: Some(format!("{:?}", comment.severity)),
+            confidence: Some(comment.confidence),
+        }
+    }
+}
+
+/// `owner/name` of the `origin` remote of the repository at `repo_path`, as `repo` scoped
+/// rejections record it.
+pub fn current_repo(repo_path: &Path) -> Option<String> {
+    let url = crate::core::GitIntegration::new(repo_path)
+        .ok()?
+        .get_remote_url()
+        .ok()??;
+    let url = url.trim_end_matches('/');
+    let url = url.strip_suffix(".git").unwrap_or(url);
+    let mut parts = url.rsplit(['/', ':']);
+    let name = parts.next().filter(|part| !part.is_empty())?;
+    let owner = parts.next().filter(|part| !part.is_empty())?;
+    Some(format!("{}/{}", owner, name))
+}
+
+/// What `feedback stats` groups acceptance by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum StatsGroup {
+    Category,
+    Severity,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum StatsPeriod {
+    Week,
+    Month,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatsRow {
+    pub period: String,
+    pub group: String,
+    pub accepted: usize,
+    pub total: usize,
+}
+
+/// Where feedback is kept: the JSON state file, or (with the `sqlite-feedback` feature and
+/// `feedback_db` set) a SQLite history of every decision.
+pub enum FeedbackLog {
+    Json {
+        path: PathBuf,
+        store: FeedbackStore,
+    },
+    #[cfg(feature = "sqlite-feedback")]
+    Sqlite {
+        path: PathBuf,
+        db: sqlite::FeedbackDb,
+    },
+}
+
+impl FeedbackLog {
+    /// Opens the configured backend; `path` overrides its file location.
+    pub fn open(config: &Config, path: Option<PathBuf>) -> Result<Self> {
+        match &config.feedback_db {
+            Some(db_path) => Self::open_sqlite(path.unwrap_or_else(|| db_path.clone())),
+            None => {
+                let path = path.unwrap_or_else(|| config.feedback_path.clone());
+                let store = match std::fs::read_to_string(&path) {
+                    Ok(content) => serde_json::from_str(&content).unwrap_or_default(),
+                    Err(_) => FeedbackStore::default(),
+                };
+                Ok(FeedbackLog::Json { path, store })
+            }
+        }
+    }
+
+    #[cfg(feature = "sqlite-feedback")]
+    fn open_sqlite(path: PathBuf) -> Result<Self> {
+        let db = sqlite::FeedbackDb::open(&path)?;
+        Ok(FeedbackLog::Sqlite { path, db })
+    }
+
+    #[cfg(not(feature = "sqlite-feedback"))]
+    fn open_sqlite(path: PathBuf) -> Result<Self> {
+        anyhow::bail!(
+            "feedback_db ({}) needs a build with `--features sqlite-feedback`",
+            path.display()
+        )
+    }
+
+    pub fn location(&self) -> &Path {
+        match self {
+            FeedbackLog::Json { path, .. } => path,
+            #[cfg(feature = "sqlite-feedback")]
+            FeedbackLog::Sqlite { path, .. } => path,
+        }
+    }
+
+    /// Records `event`; returns whether it changed the comment's current decision.
+    pub fn record(&mut self, event: &FeedbackEvent) -> Result<bool> {
+        match self {
+            FeedbackLog::Json { store, .. } => Ok(store.record(event)),
+            #[cfg(feature = "sqlite-feedback")]
+            FeedbackLog::Sqlite { db, .. } => db.record(event),
+        }
+    }
+
+    /// Writes pending changes; SQLite commits on every `record`.
+    pub fn save(&self) -> Result<()> {
+        match self {
+            FeedbackLog::Json { path, store } => {
+                std::fs::write(path, serde_json::to_string_pretty(store)?)?;
+                Ok(())
+            }
+            #[cfg(feature = "sqlite-feedback")]
+            FeedbackLog::Sqlite { .. } => Ok(()),
+        }
+    }
+
+    pub fn snapshot(self) -> Result<FeedbackStore> {
+        match self {
+            FeedbackLog::Json { store, .. } => Ok(store),
+            #[cfg(feature = "sqlite-feedback")]
+            FeedbackLog::Sqlite { db, .. } => db.snapshot(),
+        }
+    }
+
+    pub fn stats(&self, group: StatsGroup, period: StatsPeriod) -> Result<Vec<StatsRow>> {
+        match self {
+            FeedbackLog::Json { .. } => {
+                let _ = (group, period);
+                anyhow::bail!(
+                    "Feedback stats need decision history; set feedback_db to use the SQLite backend"
+                )
+            }
+            #[cfg(feature = "sqlite-feedback")]
+            FeedbackLog::Sqlite { db, .. } => db.stats(group, period),
+        }
+    }
+}
+
+#[cfg(feature = "sqlite-feedback")]
+mod sqlite {
+    use super::{
+        Decision, FeedbackEvent, FeedbackScope, FeedbackStore, StatsGroup, StatsPeriod, StatsRow,
+    };
+    use anyhow::{Context, Result};
+    use rusqlite::{params, Connection, OptionalExtension};
+    use std::path::Path;
+
+    const SCHEMA: &str = "CREATE TABLE IF NOT EXISTS feedback (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        comment_id TEXT NOT NULL,
+        decision TEXT NOT NULL,
+        repo TEXT,
+        file_path TEXT,
+        content TEXT,
+        category TEXT,
+        severity TEXT,
+        created_at TEXT NOT NULL,
+        scope TEXT,
+        confidence REAL
+    );
+    CREATE INDEX IF NOT EXISTS feedback_comment ON feedback (comment_id, id);";
+
+    pub struct FeedbackDb {
+        conn: Connection,
+    }
+
+    impl FeedbackDb {
+        pub fn open(path: &Path) -> Result<Self> {
+            let conn = Connection::open(path)
+                .with_context(|| format!("Failed to open feedback database {}", path.display()))?;
+            Self::init(conn)
+        }
+
+        pub(super) fn init(conn: Connection) -> Result<Self> {
+            conn.execute_batch(SCHEMA)?;
+            // Databases created before scoped rejections lack the column.
+            let has_scope = conn
+                .prepare("SELECT 1 FROM pragma_table_info('feedback') WHERE name = 'scope'")?
+                .exists([])?;
+            if !has_scope {
+                conn.execute_batch("ALTER TABLE feedback ADD COLUMN scope TEXT")?;
+            }
+            // ...and, before calibration, the finding's confidence.
+            let has_confidence = conn
+                .prepare("SELECT 1 FROM pragma_table_info('feedback') WHERE name = 'confidence'")?
+                .exists([])?;
+            if !has_confidence {
+                conn.execute_batch("ALTER TABLE feedback ADD COLUMN confidence REAL")?;
+            }
+            Ok(Self { conn })
+        }
+
+        fn latest(&self, comment_id: &str) -> Result<Option<String>> {
+            Ok(self
+                .conn
+                .query_row(
+                    "SELECT decision FROM feedback WHERE comment_id = ?1 ORDER BY id DESC LIMIT 1",
+                    params![comment_id],
+                    |row| row.get(0),
+                )
+                .optional()?)
+        }
+
+        /// Appends `event` unless it repeats the comment's latest decision, so re-running
+        /// `feedback sync` does not inflate the history.
+        pub fn record(&self, event: &FeedbackEvent) -> Result<bool> {
+            if self.latest(&event.comment_id)?.as_deref() == Some(event.decision.as_str()) {
+                return Ok(false);
+            }
+            self.record_at(event, &chrono::Utc::now().to_rfc3339())?;
+            Ok(true)
+        }
+
+        pub(super) fn record_at(&self, event: &FeedbackEvent, created_at: &str) -> Result<()> {
+            self.conn.execute(
+                "INSERT INTO feedback (comment_id, decision, repo, file_path, content, category, severity, created_at, scope, confidence)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                params![
+                    event.comment_id,
+                    event.decision.as_str(),
+                    event.repo,
+                    event.file_path,
+                    event.content,
+                    event.category,
+                    event.severity,
+                    created_at,
+                    event.scope.as_str(),
+                    event.confidence
+                ],
+            )?;
+            Ok(())
+        }
+
+        /// The latest decision per comment.
+        pub fn snapshot(&self) -> Result<FeedbackStore> {
+            let mut statement = self.conn.prepare(
+                "SELECT comment_id, decision, repo, file_path, content, category, severity, scope, confidence
+                 FROM feedback f
+                 WHERE id = (SELECT MAX(id) FROM feedback WHERE comment_id = f.comment_id)",
+            )?;
+            let mut store = FeedbackStore::default();
+            let rows = statement.query_map([], |row| {
+                let decision: String = row.get(1)?;
+                Ok(FeedbackEvent {
+                    comment_id: row.get(0)?,
+                    decision: if decision == Decision::Reject.as_str() {
+                        Decision::Reject
+                    } else {
+                        Decision::Accept
+                    },
+                    scope: FeedbackScope::parse(
+                        row.get::<_, Option<String>>(7)?
+                            .as_deref()
+                            .unwrap_or_default(),
+                    ),
+                    repo: row.get(2)?,
+                    file_path: row.get(3)?,
+                    content: row.get(4)?,
+                    category: row.get(5)?,
+                    severity: row.get(6)?,
+                    confidence: row.get(8)?,
+                })
+            })?;
+            for event in rows {
+                store.record(&event?);
+            }
+            Ok(store)
+        }
+
+        pub fn stats(&self, group: StatsGroup, period: StatsPeriod) -> Result<Vec<StatsRow>> {
+            let column = match group {
+                StatsGroup::Category => "category",
+                StatsGroup::Severity => "severity",
+            };
+            let format = match period {
+                StatsPeriod::Week => "%Y-W%W",
+                StatsPeriod::Month => "%Y-%m",
+            };
+            let sql = format!(
+                "SELECT strftime('{format}', created_at) AS period, COALESCE({column}, 'unknown') AS grp,
+                        SUM(decision = 'accept'), COUNT(*)
+                 FROM feedback GROUP BY period, grp ORDER BY period, grp"
+            );
+            let mut statement = self.conn.prepare(&sql)?;
+            let rows = statement.query_map([], |row| {
+                Ok(StatsRow {
+                    period: row.get(0)?,
+                    group: row.get(1)?,
+                    accepted: row.get::<_, i64>(2)? as usize,
+                    total: row.get::<_, i64>(3)? as usize,
+                })
+            })?;
+            Ok(rows.collect::<rusqlite::Result<_>>()?)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::comment::{Category, FixEffort, Severity};
+
+    fn event(comment_id: &str, decision: Decision, category: &str) -> FeedbackEvent {
+        FeedbackEvent {
+            comment_id: comment_id.to_string(),
+            decision,
+            scope: FeedbackScope::File,
+            repo: Some("acme/shop".to_string()),
+            file_path: Some("src/lib.rs".to_string()),
+            content: Some("Avoid unwrap.".to_string()),
+            category: Some(category.to_string()),
+            severity: Some("Warning".to_string()),
+            confidence: None,
+        }
+    }
+
+    #[test]
+    fn json_log_tracks_the_latest_decision() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config {
+            feedback_path: dir.path().join("feedback.json"),
+            ..Config::default()
+        };
+        let mut log = FeedbackLog::open(&config, None).unwrap();
+        assert!(log
+            .record(&event("cmt_a", Decision::Reject, "Bug"))
+            .unwrap());
+        assert!(!log
+            .record(&event("cmt_a", Decision::Reject, "Bug"))
+            .unwrap());
+        assert!(log
+            .record(&event("cmt_b", Decision::Accept, "Style"))
+            .unwrap());
+        log.save().unwrap();
+
+        let store = FeedbackLog::open(&config, None)
+            .unwrap()
+            .snapshot()
+            .unwrap();
+        assert!(store.suppress.contains("cmt_a"));
+        assert!(store.accept.contains("cmt_b"));
+    }
+
+    fn comment(path: &str, category: Category, content: &str) -> Comment {
+        Comment {
+            id: crate::core::comment::compute_comment_id(Path::new(path), content, &category),
+            file_path: path.into(),
+            line_number: 3,
+            content: content.to_string(),
+            severity: Severity::Warning,
+            category,
+            suggestion: None,
+            confidence: 0.8,
+            code_suggestion: None,
+            tags: Vec::new(),
+            fix_effort: FixEffort::Low,
+            owners: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn suppresses_reworded_repeats_of_rejected_findings() {
+        let rejected = comment(
+            "src/lib.rs",
+            Category::Bug,
+            "Calling unwrap() here will panic if parsing the config fails.",
+        );
+        let mut store = FeedbackStore::default();
+        store.record(&FeedbackEvent::for_comment(
+            &rejected,
+            Decision::Reject,
+            FeedbackScope::File,
+            None,
+        ));
+
+        let reworded = comment(
+            "src/lib.rs",
+            Category::Bug,
+            "Calling unwrap() here will panic when parsing the config fails.",
+        );
+        assert!(store.is_suppressed(&rejected, None, 0.0));
+        assert!(!store.is_suppressed(&reworded, None, 0.0));
+        assert!(store.is_suppressed(&reworded, None, 0.8));
+        assert!(!store.is_suppressed(
+            &comment("src/main.rs", Category::Bug, &reworded.content),
+            None,
+            0.8
+        ));
+        assert!(!store.is_suppressed(
+            &comment("src/lib.rs", Category::Style, &reworded.content),
+            None,
+            0.8
+        ));
+    }
+
+    #[test]
+    fn rejection_scope_limits_where_repeats_are_suppressed() {
+        let text = "Generated accessor duplicates the field name.";
+        let reject = |path: &str, scope: FeedbackScope| {
+            let mut store = FeedbackStore::default();
+            store.record(&FeedbackEvent::for_comment(
+                &comment(path, Category::Style, text),
+                Decision::Reject,
+                scope,
+                Some("acme/shop".to_string()),
+            ));
+            store
+        };
+        let elsewhere = |path: &str| comment(path, Category::Style, text);
+
+        let directory = reject("src/generated/api.rs", FeedbackScope::Directory);
+        let shop = Some("acme/shop");
+        assert!(directory.is_suppressed(&elsewhere("src/generated/v2/model.rs"), shop, 0.0));
+        assert!(!directory.is_suppressed(&elsewhere("src/api.rs"), shop, 0.0));
+        assert!(!directory.is_suppressed(
+            &elsewhere("src/generated/v2/model.rs"),
+            Some("acme/admin"),
+            0.0
+        ));
+        assert!(!directory.is_suppressed(&elsewhere("src/generated/v2/model.rs"), None, 0.0));
+
+        let repo = reject("src/generated/api.rs", FeedbackScope::Repo);
+        assert!(repo.is_suppressed(&elsewhere("src/api.rs"), Some("acme/shop"), 0.0));
+        assert!(!repo.is_suppressed(&elsewhere("src/api.rs"), Some("acme/admin"), 0.0));
+
+        let global = reject("src/generated/api.rs", FeedbackScope::Global);
+        assert!(global.is_suppressed(&elsewhere("lib/api.rs"), Some("acme/admin"), 0.0));
+    }
+
+    #[test]
+    fn merge_keeps_local_decisions() {
+        let mut local = FeedbackStore::default();
+        local.record(&event("cmt_a", Decision::Accept, "Bug"));
+        local.record(&event("cmt_b", Decision::Reject, "Bug"));
+        let mut team = FeedbackStore::default();
+        team.record(&event("cmt_a", Decision::Reject, "Bug"));
+        team.record(&event("cmt_b", Decision::Accept, "Bug"));
+        team.record(&event("cmt_c", Decision::Reject, "Style"));
+
+        local.merge(team);
+
+        assert!(local.accept.contains("cmt_a") && !local.suppress.contains("cmt_a"));
+        assert!(local.suppress.contains("cmt_b") && !local.accept.contains("cmt_b"));
+        assert!(local.suppress.contains("cmt_c"));
+        assert_eq!(local.rejected["cmt_c"].category, "Style");
+    }
+
+    #[cfg(feature = "sqlite-feedback")]
+    #[test]
+    fn sqlite_log_keeps_history_and_reports_acceptance() {
+        let db = sqlite::FeedbackDb::init(rusqlite::Connection::open_in_memory().unwrap()).unwrap();
+        db.record_at(
+            &event("cmt_a", Decision::Reject, "Bug"),
+            "2026-09-03T10:00:00Z",
+        )
+        .unwrap();
+        db.record_at(
+            &event("cmt_a", Decision::Accept, "Bug"),
+            "2026-10-01T10:00:00Z",
+        )
+        .unwrap();
+        db.record_at(
+            &event("cmt_b", Decision::Reject, "Style"),
+            "2026-10-02T10:00:00Z",
+        )
+        .unwrap();
+        assert!(!db
+            .record(&event("cmt_b", Decision::Reject, "Style"))
+            .unwrap());
+
+        let store = db.snapshot().unwrap();
+        assert!(store.accept.contains("cmt_a"));
+        assert!(store.suppress.contains("cmt_b"));
+
+        let stats = db.stats(StatsGroup::Category, StatsPeriod::Month).unwrap();
+        let summary: Vec<_> = stats
+            .iter()
+            .map(|row| {
+                (
+                    row.period.as_str(),
+                    row.group.as_str(),
+                    row.accepted,
+                    row.total,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("2026-09", "Bug", 0, 1),
+                ("2026-10", "Bug", 1, 1),
+                ("2026-10", "Style", 0, 1)
+            ]
+        );
+    }
+}
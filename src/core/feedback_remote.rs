@@ -0,0 +1,421 @@
+use crate::config::Config;
+use crate::core::feedback::{FeedbackEvent, FeedbackStore};
+use anyhow::{Context, Result};
+use reqwest::{Method, StatusCode, Url};
+use std::time::Duration;
+
+/// Attempts at a conditional write before giving up on concurrent updates.
+const MAX_PUSH_ATTEMPTS: usize = 5;
+
+/// A feedback store shared by a team: one JSON document (the `FeedbackStore` format) behind
+/// an HTTP URL or in S3, updated with ETag compare-and-swap so concurrent pushes never
+/// overwrite each other's decisions.
+pub struct RemoteStore {
+    client: reqwest::Client,
+    url: Url,
+    auth: RemoteAuth,
+}
+
+enum RemoteAuth {
+    Bearer(Option<String>),
+    S3(S3Credentials),
+}
+
+pub struct S3Credentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+    pub region: String,
+}
+
+impl S3Credentials {
+    fn from_env() -> Result<Self> {
+        Ok(Self {
+            access_key_id: std::env::var("AWS_ACCESS_KEY_ID")
+                .context("AWS_ACCESS_KEY_ID is required for an s3:// feedback_remote")?,
+            secret_access_key: std::env::var("AWS_SECRET_ACCESS_KEY")
+                .context("AWS_SECRET_ACCESS_KEY is required for an s3:// feedback_remote")?,
+            session_token: std::env::var("AWS_SESSION_TOKEN").ok(),
+            region: std::env::var("AWS_REGION")
+                .or_else(|_| std::env::var("AWS_DEFAULT_REGION"))
+                .unwrap_or_else(|_| "us-east-1".to_string()),
+        })
+    }
+}
+
+impl RemoteStore {
+    /// The configured remote, if any. `s3://bucket/key` uses AWS credentials from the
+    /// environment (and `AWS_ENDPOINT_URL` for S3-compatible stores); anything else is a
+    /// plain HTTP(S) URL.
+    pub fn from_config(config: &Config) -> Result<Option<Self>> {
+        let Some(remote) = config.feedback_remote.as_deref() else {
+            return Ok(None);
+        };
+        let store = match remote.strip_prefix("s3://") {
+            Some(location) => {
+                let (bucket, key) = location
+                    .split_once('/')
+                    .filter(|(bucket, key)| !bucket.is_empty() && !key.is_empty())
+                    .context("feedback_remote must look like s3://bucket/key")?;
+                let credentials = S3Credentials::from_env()?;
+                let endpoint = std::env::var("AWS_ENDPOINT_URL").ok();
+                Self::s3(bucket, key, endpoint.as_deref(), credentials)?
+            }
+            None => Self::http(
+                remote,
+                config
+                    .feedback_remote_token
+                    .clone()
+                    .or_else(|| std::env::var("DIFFSCOPE_FEEDBACK_TOKEN").ok()),
+            )?,
+        };
+        Ok(Some(store))
+    }
+
+    fn client() -> Result<reqwest::Client> {
+        Ok(reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()?)
+    }
+
+    pub fn http(url: &str, token: Option<String>) -> Result<Self> {
+        Ok(Self {
+            client: Self::client()?,
+            url: Url::parse(url).with_context(|| format!("Invalid feedback_remote {}", url))?,
+            auth: RemoteAuth::Bearer(token),
+        })
+    }
+
+    /// `endpoint` switches to path-style URLs on an S3-compatible server.
+    pub fn s3(
+        bucket: &str,
+        key: &str,
+        endpoint: Option<&str>,
+        credentials: S3Credentials,
+    ) -> Result<Self> {
+        let url = match endpoint {
+            Some(endpoint) => format!(
+                "{}/{}/{}",
+                endpoint.trim_end_matches('/'),
+                bucket,
+                uri_encode(key, false)
+            ),
+            None => format!(
+                "https://{}.s3.{}.amazonaws.com/{}",
+                bucket,
+                credentials.region,
+                uri_encode(key, false)
+            ),
+        };
+        Ok(Self {
+            client: Self::client()?,
+            url: Url::parse(&url)?,
+            auth: RemoteAuth::S3(credentials),
+        })
+    }
+
+    fn request(&self, method: Method, body: &[u8]) -> reqwest::RequestBuilder {
+        let builder = self.client.request(method.clone(), self.url.clone());
+        match &self.auth {
+            RemoteAuth::Bearer(Some(token)) => builder.bearer_auth(token),
+            RemoteAuth::Bearer(None) => builder,
+            RemoteAuth::S3(credentials) => {
+                let now = chrono::Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+                sign_s3(builder, &method, &self.url, body, credentials, &now)
+            }
+        }
+    }
+
+    /// The shared store and its ETag; an empty store when nothing has been pushed yet.
+    pub async fn fetch(&self) -> Result<(FeedbackStore, Option<String>)> {
+        let response = self
+            .request(Method::GET, b"")
+            .send()
+            .await
+            .context("Failed to fetch the shared feedback store")?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok((FeedbackStore::default(), None));
+        }
+        if !response.status().is_success() {
+            anyhow::bail!(
+                "Fetching the shared feedback store failed ({})",
+                response.status()
+            );
+        }
+        let etag = response
+            .headers()
+            .get(reqwest::header::ETAG)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let body = response.bytes().await?;
+        let store = serde_json::from_slice(&body).context("Invalid shared feedback store")?;
+        Ok((store, etag))
+    }
+
+    /// Writes `store` only if the remote still has `etag`; `false` when someone else wrote
+    /// first.
+    async fn put(&self, store: &FeedbackStore, etag: Option<&str>) -> Result<bool> {
+        let body = serde_json::to_vec_pretty(store)?;
+        let builder = self
+            .request(Method::PUT, &body)
+            .header(reqwest::header::CONTENT_TYPE, "application/json");
+        let builder = match etag {
+            Some(etag) => builder.header(reqwest::header::IF_MATCH, etag),
+            None => builder.header(reqwest::header::IF_NONE_MATCH, "*"),
+        };
+        let response = builder
+            .body(body)
+            .send()
+            .await
+            .context("Failed to update the shared feedback store")?;
+        match response.status() {
+            StatusCode::PRECONDITION_FAILED | StatusCode::CONFLICT => Ok(false),
+            status if status.is_success() => Ok(true),
+            status => anyhow::bail!("Updating the shared feedback store failed ({})", status),
+        }
+    }
+
+    /// Applies `events` on top of the latest shared store, retrying when another push wins.
+    pub async fn push(&self, events: &[FeedbackEvent]) -> Result<()> {
+        if events.is_empty() {
+            return Ok(());
+        }
+        for _ in 0..MAX_PUSH_ATTEMPTS {
+            let (mut store, etag) = self.fetch().await?;
+            let mut changed = false;
+            for event in events {
+                changed |= store.record(event);
+            }
+            if !changed || self.put(&store, etag.as_deref()).await? {
+                return Ok(());
+            }
+        }
+        anyhow::bail!(
+            "The shared feedback store kept changing; gave up after {} attempts",
+            MAX_PUSH_ATTEMPTS
+        )
+    }
+}
+
+/// Merges the configured shared store into `store`; local decisions win.
+pub async fn merge_shared(config: &Config, store: &mut FeedbackStore) -> Result<()> {
+    if let Some(remote) = RemoteStore::from_config(config)? {
+        store.merge(remote.fetch().await?.0);
+    }
+    Ok(())
+}
+
+/// Pushes `events` to the configured shared store; returns whether one is configured.
+pub async fn push_shared(config: &Config, events: &[FeedbackEvent]) -> Result<bool> {
+    match RemoteStore::from_config(config)? {
+        Some(remote) => {
+            remote.push(events).await?;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    hex(ring::digest::digest(&ring::digest::SHA256, data).as_ref())
+}
+
+fn hmac(key: &[u8], data: &str) -> Vec<u8> {
+    let key = ring::hmac::Key::new(ring::hmac::HMAC_SHA256, key);
+    ring::hmac::sign(&key, data.as_bytes()).as_ref().to_vec()
+}
+
+/// Percent-encodes everything but RFC 3986 unreserved characters (and `/` unless
+/// `encode_slash`), as SigV4 canonical URIs require.
+fn uri_encode(value: &str, encode_slash: bool) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            b'/' if !encode_slash => "/".to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+/// Adds AWS Signature Version 4 headers for an S3 object request. `amz_date` is
+/// `YYYYMMDDTHHMMSSZ`.
+fn sign_s3(
+    builder: reqwest::RequestBuilder,
+    method: &Method,
+    url: &Url,
+    body: &[u8],
+    credentials: &S3Credentials,
+    amz_date: &str,
+) -> reqwest::RequestBuilder {
+    let date = &amz_date[..8];
+    let host = match url.port() {
+        Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+        None => url.host_str().unwrap_or_default().to_string(),
+    };
+    let payload_hash = sha256_hex(body);
+
+    let mut headers = vec![
+        ("host", host),
+        ("x-amz-content-sha256", payload_hash.clone()),
+        ("x-amz-date", amz_date.to_string()),
+    ];
+    if let Some(token) = &credentials.session_token {
+        headers.push(("x-amz-security-token", token.clone()));
+    }
+    let signed_headers = headers
+        .iter()
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>()
+        .join(";");
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(name, value)| format!("{}:{}\n", name, value.trim()))
+        .collect();
+    let canonical_request = format!(
+        "{}\n{}\n{}\n{}\n{}\n{}",
+        method.as_str(),
+        url.path(),
+        url.query().unwrap_or_default(),
+        canonical_headers,
+        signed_headers,
+        payload_hash
+    );
+    let scope = format!("{}/{}/s3/aws4_request", date, credentials.region);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        sha256_hex(canonical_request.as_bytes())
+    );
+    let key = hmac(
+        format!("AWS4{}", credentials.secret_access_key).as_bytes(),
+        date,
+    );
+    let key = hmac(&key, &credentials.region);
+    let key = hmac(&key, "s3");
+    let key = hmac(&key, "aws4_request");
+    let signature = hex(&hmac(&key, &string_to_sign));
+
+    let mut builder = builder.header(
+        reqwest::header::AUTHORIZATION,
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            credentials.access_key_id, scope, signed_headers, signature
+        ),
+    );
+    for (name, value) in headers.into_iter().filter(|(name, _)| *name != "host") {
+        builder = builder.header(name, value);
+    }
+    builder
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::feedback::{Decision, FeedbackScope};
+
+    fn reject(id: &str) -> FeedbackEvent {
+        FeedbackEvent {
+            comment_id: id.to_string(),
+            decision: Decision::Reject,
+            scope: FeedbackScope::File,
+            repo: None,
+            file_path: Some("src/lib.rs".to_string()),
+            content: Some("Avoid unwrap.".to_string()),
+            category: Some("Bug".to_string()),
+            severity: None,
+            confidence: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn push_retries_when_the_etag_changed() {
+        let mut server = mockito::Server::new_async().await;
+        let path = "/team/feedback.json";
+        let first = server
+            .mock("GET", path)
+            .match_header("authorization", "Bearer team-token")
+            .with_header("etag", "\"v1\"")
+            .with_body(r#"{"suppress": ["cmt_old"], "accept": []}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let stale = server
+            .mock("PUT", path)
+            .match_header("if-match", "\"v1\"")
+            .with_status(412)
+            .expect(1)
+            .create_async()
+            .await;
+        let second = server
+            .mock("GET", path)
+            .with_header("etag", "\"v2\"")
+            .with_body(r#"{"suppress": ["cmt_old", "cmt_teammate"], "accept": []}"#)
+            .create_async()
+            .await;
+        let write = server
+            .mock("PUT", path)
+            .match_header("if-match", "\"v2\"")
+            .match_body(mockito::Matcher::Regex("cmt_teammate".to_string()))
+            .match_body(mockito::Matcher::Regex("cmt_new".to_string()))
+            .with_status(200)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let remote = RemoteStore::http(
+            &format!("{}{}", server.url(), path),
+            Some("team-token".to_string()),
+        )
+        .unwrap();
+        remote.push(&[reject("cmt_new")]).await.unwrap();
+
+        first.assert_async().await;
+        stale.assert_async().await;
+        second.assert_async().await;
+        write.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn s3_requests_are_signed_and_missing_objects_are_empty() {
+        let mut server = mockito::Server::new_async().await;
+        let get = server
+            .mock("GET", "/team-bucket/diffscope/feedback.json")
+            .match_header(
+                "authorization",
+                mockito::Matcher::Regex(
+                    r"^AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/\d{8}/eu-west-1/s3/aws4_request, SignedHeaders=host;x-amz-content-sha256;x-amz-date, Signature=[0-9a-f]{64}$"
+                        .to_string(),
+                ),
+            )
+            .with_status(404)
+            .create_async()
+            .await;
+
+        let remote = RemoteStore::s3(
+            "team-bucket",
+            "diffscope/feedback.json",
+            Some(&server.url()),
+            S3Credentials {
+                access_key_id: "AKIDEXAMPLE".to_string(),
+                secret_access_key: "secret".to_string(),
+                session_token: None,
+                region: "eu-west-1".to_string(),
+            },
+        )
+        .unwrap();
+        let (store, etag) = remote.fetch().await.unwrap();
+
+        get.assert_async().await;
+        assert!(store.suppress.is_empty());
+        assert_eq!(etag, None);
+        assert_eq!(uri_encode("a b/c+d", false), "a%20b/c%2Bd");
+    }
+}
@@ -0,0 +1,296 @@
+use crate::adapters::llm::{LLMAdapter, LLMRequest};
+use crate::core::prompt::{PromptBuilder, PromptConfig};
+use crate::core::{ContextFetcher, LLMContextChunk, PromptLimits, SymbolIndex, UnifiedDiff};
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::path::{Component, Path, PathBuf};
+use tracing::{debug, info, warn};
+
+const REQUEST_PROMPT: &str = "You reviewed a code change and may look at more of the repository before finalizing your review. Name the files (paths relative to the repository root) and symbols (functions, types, constants) whose code would confirm or rule out an issue, or reveal one you could not judge. Ask only for what matters. Reply with only JSON: {\"files\": [\"src/db.rs\"], \"symbols\": [\"open_pool\"]}, with empty lists when you need nothing more.";
+
+const REVISE_INSTRUCTIONS: &str = "You asked for the code above to check your previous review. Revise it: keep the issues the code confirms, drop the ones it rules out, and add any it reveals. Reply with the complete list of issues in the original format.";
+
+/// Requests per round; a model asking for more than this gets the first ones.
+const MAX_FILES_PER_ROUND: usize = 5;
+const MAX_SYMBOLS_PER_ROUND: usize = 10;
+
+#[derive(Debug, Default, Deserialize)]
+struct Needs {
+    #[serde(default)]
+    files: Vec<String>,
+    #[serde(default)]
+    symbols: Vec<String>,
+}
+
+fn parse_needs(content: &str) -> Option<Needs> {
+    let start = content.find('{')?;
+    let end = content.rfind('}')?;
+    serde_json::from_str(content.get(start..=end)?).ok()
+}
+
+/// A repository-relative path the model asked for, or `None` if it would leave the repository.
+fn repo_relative(path: &str) -> Option<String> {
+    let path = path.trim().trim_start_matches("./");
+    let parsed = Path::new(path);
+    let inside = !path.is_empty()
+        && parsed
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)));
+    inside.then(|| path.to_string())
+}
+
+/// The follow-up loop (`follow_up`): after a file's first review, the model names the files
+/// and symbols it wants to see, they are fetched, and the model revises its review with
+/// them. Rounds repeat until the model needs nothing new or a budget runs out.
+pub struct FollowUp<'a> {
+    pub fetcher: &'a ContextFetcher,
+    pub symbol_index: Option<&'a SymbolIndex>,
+    pub max_symbol_locations: usize,
+    pub max_rounds: usize,
+    /// Tokens of fetched code across all rounds for one request.
+    pub max_context_tokens: usize,
+    pub limits: PromptLimits,
+}
+
+impl FollowUp<'_> {
+    /// Runs the loop on `answer`, the model's reply to `request` for `diff`, whose prompt
+    /// already carried `context`. Returns the final review; a failed round keeps the review
+    /// from before it.
+    pub async fn revise(
+        &self,
+        adapter: &dyn LLMAdapter,
+        request: &LLMRequest,
+        diff: &UnifiedDiff,
+        context: &[LLMContextChunk],
+        mut answer: String,
+    ) -> String {
+        let mut seen_files: HashSet<PathBuf> = context
+            .iter()
+            .filter(|chunk| chunk.line_range.is_none())
+            .map(|chunk| chunk.file_path.clone())
+            .collect();
+        seen_files.insert(diff.file_path.clone());
+        let mut seen_symbols: HashSet<String> = HashSet::new();
+        let mut fetched: Vec<LLMContextChunk> = Vec::new();
+        let mut spent = 0;
+
+        for round in 1..=self.max_rounds {
+            let needs = match adapter
+                .complete(LLMRequest {
+                    system_prompt: REQUEST_PROMPT.to_string(),
+                    user_prompt: format!(
+                        "{}\n\n<your_review>\n{}\n</your_review>",
+                        request.user_prompt, answer
+                    ),
+                    temperature: Some(0.0),
+                    max_tokens: None,
+                    cached_context: None,
+                })
+                .await
+            {
+                Ok(response) => match parse_needs(&response.content) {
+                    Some(needs) => needs,
+                    None => {
+                        debug!("Unreadable follow-up request: {}", response.content);
+                        break;
+                    }
+                },
+                Err(err) => {
+                    warn!(
+                        "Follow-up for {} failed: {:#}",
+                        diff.file_path.display(),
+                        err
+                    );
+                    break;
+                }
+            };
+
+            let chunks = self
+                .fetch(diff, needs, &mut seen_files, &mut seen_symbols)
+                .await;
+            let mut added = 0;
+            for chunk in chunks {
+                let tokens = self.limits.tokenizer.count_tokens(&chunk.content);
+                if spent + tokens > self.max_context_tokens {
+                    continue;
+                }
+                spent += tokens;
+                fetched.push(chunk);
+                added += 1;
+            }
+            if added == 0 {
+                break;
+            }
+            info!(
+                "Follow-up round {} for {}: revising with {} more context chunk(s)",
+                round,
+                diff.file_path.display(),
+                added
+            );
+
+            let builder = PromptBuilder::new(PromptConfig {
+                limits: self.limits,
+                ..Default::default()
+            });
+            let additional = match builder.format_context(&fetched) {
+                Ok(additional) => additional,
+                Err(_) => break,
+            };
+            let revised = adapter
+                .complete(LLMRequest {
+                    user_prompt: format!(
+                        "{}\n\n<additional_context>\n{}\n</additional_context>\n\n<previous_review>\n{}\n</previous_review>\n\n<revision>\n{}\n</revision>",
+                        request.user_prompt, additional, answer, REVISE_INSTRUCTIONS
+                    ),
+                    ..request.clone()
+                })
+                .await;
+            match revised {
+                Ok(response) => answer = response.content,
+                Err(err) => {
+                    warn!(
+                        "Follow-up for {} failed, keeping the earlier review: {:#}",
+                        diff.file_path.display(),
+                        err
+                    );
+                    break;
+                }
+            }
+        }
+        answer
+    }
+
+    async fn fetch(
+        &self,
+        diff: &UnifiedDiff,
+        needs: Needs,
+        seen_files: &mut HashSet<PathBuf>,
+        seen_symbols: &mut HashSet<String>,
+    ) -> Vec<LLMContextChunk> {
+        let mut chunks = Vec::new();
+
+        let files: Vec<String> = needs
+            .files
+            .iter()
+            .filter_map(|path| repo_relative(path))
+            .filter(|path| seen_files.insert(PathBuf::from(path)))
+            .take(MAX_FILES_PER_ROUND)
+            .collect();
+        match self.fetcher.fetch_additional_context(&files).await {
+            Ok(found) => chunks.extend(found),
+            Err(err) => debug!("Could not fetch requested files: {:#}", err),
+        }
+
+        let symbols: Vec<String> = needs
+            .symbols
+            .into_iter()
+            .map(|symbol| symbol.trim().to_string())
+            .filter(|symbol| !symbol.is_empty() && seen_symbols.insert(symbol.clone()))
+            .take(MAX_SYMBOLS_PER_ROUND)
+            .collect();
+        let definitions = match self.symbol_index {
+            Some(index) => {
+                self.fetcher
+                    .fetch_related_definitions_with_index(
+                        &diff.file_path,
+                        &symbols,
+                        index,
+                        self.max_symbol_locations,
+                    )
+                    .await
+            }
+            None => {
+                self.fetcher
+                    .fetch_related_definitions(&diff.file_path, &symbols)
+                    .await
+            }
+        };
+        match definitions {
+            Ok(found) => chunks.extend(found),
+            Err(err) => debug!("Could not fetch requested symbols: {:#}", err),
+        }
+        chunks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::llm::LLMResponse;
+    use anyhow::Result;
+    use async_trait::async_trait;
+
+    /// Asks for `src/db.rs` and `../secrets.txt` once, then withdraws its finding when it sees
+    /// that `open_pool` already retries.
+    struct Reviewer;
+
+    #[async_trait]
+    impl LLMAdapter for Reviewer {
+        async fn complete(&self, request: LLMRequest) -> Result<LLMResponse> {
+            let content = if request.system_prompt == REQUEST_PROMPT {
+                if request.user_prompt.contains("No issues") {
+                    r#"{"files": [], "symbols": []}"#
+                } else {
+                    r#"{"files": ["src/db.rs", "../secrets.txt"], "symbols": []}"#
+                }
+            } else if request.user_prompt.contains("retries three times") {
+                "No issues found."
+            } else {
+                "Line 2: Bug - open_pool may fail without a retry. Flaky startup. Retry."
+            };
+            Ok(LLMResponse {
+                content: content.to_string(),
+                model: "reviewer".to_string(),
+                usage: None,
+            })
+        }
+
+        fn _model_name(&self) -> &str {
+            "reviewer"
+        }
+    }
+
+    #[tokio::test]
+    async fn revises_the_review_with_requested_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path().join("repo");
+        std::fs::create_dir_all(repo.join("src")).unwrap();
+        std::fs::write(
+            repo.join("src/db.rs"),
+            "// open_pool retries three times\nfn open_pool() {}\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("secrets.txt"), "hunter2\n").unwrap();
+        let diff = crate::core::DiffParser::parse_unified_diff(
+            "--- a/src/main.rs\n+++ b/src/main.rs\n@@ -1 +1,2 @@\n fn main() {\n+    open_pool();\n",
+        )
+        .unwrap()
+        .remove(0);
+        let fetcher = ContextFetcher::new(repo);
+        let follow_up = FollowUp {
+            fetcher: &fetcher,
+            symbol_index: None,
+            max_symbol_locations: 5,
+            max_rounds: 3,
+            max_context_tokens: 1000,
+            limits: PromptLimits::default(),
+        };
+        let request = LLMRequest {
+            system_prompt: "Review.".to_string(),
+            user_prompt: "<diff>...</diff>".to_string(),
+            temperature: None,
+            max_tokens: None,
+            cached_context: None,
+        };
+        let first = Reviewer.complete(request.clone()).await.unwrap().content;
+
+        let revised = follow_up
+            .revise(&Reviewer, &request, &diff, &[], first)
+            .await;
+        assert_eq!(revised, "No issues found.");
+
+        assert_eq!(repo_relative("./src/db.rs").as_deref(), Some("src/db.rs"));
+        assert_eq!(repo_relative("../secrets.txt"), None);
+        assert_eq!(repo_relative("/etc/passwd"), None);
+    }
+}
@@ -0,0 +1,341 @@
+use crate::core::diff_parser::{ChangeType, UnifiedDiff};
+use crate::core::syntax::{self, Definition};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Scopes longer than this fall back to the raw hunk range to keep context focused.
+const MAX_SCOPE_LINES: usize = 300;
+
+static DEFINITION_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"^\s*(?:(?:pub(?:\([^)]*\))?|export|default|async|static|public|private|protected|internal|abstract|final|override|open|unsafe|const|extern(?:\s+"[^"]*")?|inline|virtual)\s+)*(?:fn|function\*?|def|class|struct|enum|impl|trait|interface|func|fun|module|object|mod|record)\b"#,
+    )
+    .unwrap()
+});
+
+static ARROW_FUNCTION_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*(?:export\s+)?(?:const|let|var)\s+[A-Za-z_$][A-Za-z0-9_$]*\s*=\s*(?:async\s+)?(?:\([^)]*\)|[A-Za-z_$][A-Za-z0-9_$]*)\s*=>").unwrap()
+});
+
+// Java/C#/C++ style methods: `public int total(List<Item> items) {`.
+static METHOD_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*(?:[A-Za-z_][\w<>\[\],.*&:]*\s+)+[A-Za-z_]\w*\s*\([^;]*$").unwrap()
+});
+
+static CONTROL_KEYWORD: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*(?:\}\s*)?(?:if|else|for|while|switch|catch|return|new|do|try|throw|case|match|loop)\b")
+        .unwrap()
+});
+
+static FUNCTION_NAME_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?:const|let|var)\s+([A-Za-z_$][\w$]*)\s*=|([A-Za-z_$][\w$]*)\s*(?:<[^>]*>)?\s*\(")
+        .unwrap()
+});
+
+const NON_NAME_KEYWORDS: &[&str] = &[
+    "fn", "function", "def", "func", "fun", "if", "for", "while", "switch", "catch", "return",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ScopeStyle {
+    Braces,
+    Indentation,
+}
+
+fn scope_style(file_path: &Path) -> Option<ScopeStyle> {
+    let ext = file_path.extension()?.to_str()?.to_lowercase();
+    match ext.as_str() {
+        "rs" | "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "go" | "java" | "kt" | "kts"
+        | "cs" | "c" | "h" | "cc" | "cpp" | "cxx" | "hpp" | "swift" | "php" | "scala" | "dart" => {
+            Some(ScopeStyle::Braces)
+        }
+        "py" | "rb" => Some(ScopeStyle::Indentation),
+        _ => None,
+    }
+}
+
+/// Expands a 1-based inclusive line range to the boundaries of the functions/classes that
+/// enclose its first and last lines. Scopes come from the tree-sitter grammar when there is one
+/// for the file, and from definition-line and brace/indentation heuristics otherwise.
+/// Unsupported languages and oversized scopes are returned as-is.
+pub fn expand_to_enclosing_scope(
+    file_path: &Path,
+    lines: &[&str],
+    range: (usize, usize),
+) -> (usize, usize) {
+    if lines.is_empty() {
+        return range;
+    }
+    let last = lines.len() - 1;
+    let start_idx = range.0.max(1).saturating_sub(1).min(last);
+    let end_idx = range.1.max(range.0).saturating_sub(1).min(last);
+
+    let (start, end) = match syntax::definitions(file_path, &lines.join("\n")) {
+        Some(definitions) => (
+            innermost(&definitions, start_idx, false).map_or(start_idx, |def| def.line),
+            innermost(&definitions, end_idx, false).map_or(end_idx, |def| def.end_line),
+        ),
+        None => {
+            let Some(style) = scope_style(file_path) else {
+                return range;
+            };
+            (
+                enclosing_scope(lines, style, start_idx).map_or(start_idx, |(s, _)| s),
+                enclosing_scope(lines, style, end_idx).map_or(end_idx, |(_, e)| e),
+            )
+        }
+    };
+
+    if end.saturating_sub(start) + 1 > MAX_SCOPE_LINES {
+        return range;
+    }
+    (start.min(start_idx) + 1, end.max(end_idx) + 1)
+}
+
+/// The innermost of `definitions` spanning 0-based `line_idx`, only functions and methods when
+/// `callable`.
+fn innermost(definitions: &[Definition], line_idx: usize, callable: bool) -> Option<&Definition> {
+    definitions
+        .iter()
+        .filter(|def| def.line <= line_idx && line_idx <= def.end_line)
+        .filter(|def| def.callable || !callable)
+        .min_by_key(|def| def.end_line - def.line)
+}
+
+/// Innermost definition (0-based inclusive line indexes) whose body contains `line_idx`, found
+/// by scanning back for a definition line, for languages without a grammar.
+fn enclosing_scope(lines: &[&str], style: ScopeStyle, line_idx: usize) -> Option<(usize, usize)> {
+    let lowest = line_idx.saturating_sub(MAX_SCOPE_LINES);
+    for candidate in (lowest..=line_idx).rev() {
+        if !is_definition(lines[candidate]) {
+            continue;
+        }
+        let end = match style {
+            ScopeStyle::Braces => brace_scope_end(lines, candidate),
+            ScopeStyle::Indentation => indent_scope_end(lines, candidate),
+        };
+        if let Some(end) = end {
+            if end >= line_idx {
+                return Some((candidate, end));
+            }
+        }
+    }
+    None
+}
+
+pub fn is_definition(line: &str) -> bool {
+    if DEFINITION_PATTERN.is_match(line) || ARROW_FUNCTION_PATTERN.is_match(line) {
+        return true;
+    }
+    !CONTROL_KEYWORD.is_match(line) && METHOD_PATTERN.is_match(line)
+}
+
+/// Name of the function declared on `line`, if it is a function definition.
+fn defined_function_name(line: &str) -> Option<String> {
+    if !is_definition(line) {
+        return None;
+    }
+    FUNCTION_NAME_PATTERN.captures_iter(line).find_map(|caps| {
+        let name = caps.get(1).or_else(|| caps.get(2))?.as_str();
+        (!NON_NAME_KEYWORDS.contains(&name)).then(|| name.to_string())
+    })
+}
+
+/// Functions whose signature was modified or removed by `diff`, i.e. whose callers may need updating.
+pub fn changed_function_signatures(diff: &UnifiedDiff) -> Vec<String> {
+    let mut names = Vec::new();
+    for hunk in &diff.hunks {
+        let mut removed: HashMap<String, &str> = HashMap::new();
+        let mut added: HashMap<String, &str> = HashMap::new();
+        for line in &hunk.changes {
+            let target = match line.change_type {
+                ChangeType::Removed => &mut removed,
+                ChangeType::Added => &mut added,
+                ChangeType::Context => continue,
+            };
+            if let Some(name) = defined_function_name(&line.content) {
+                target.insert(name, line.content.trim());
+            }
+        }
+        for (name, old_signature) in removed {
+            let changed = added
+                .get(&name)
+                .is_none_or(|new_signature| *new_signature != old_signature);
+            if changed && !names.contains(&name) {
+                names.push(name);
+            }
+        }
+    }
+    names
+}
+
+/// Functions in the new version of the file (`lines`) whose bodies contain added lines, with
+/// their 1-based inclusive line range.
+pub fn changed_functions(diff: &UnifiedDiff, lines: &[&str]) -> Vec<(String, (usize, usize))> {
+    let definitions = syntax::definitions(&diff.file_path, &lines.join("\n"));
+    let style = scope_style(&diff.file_path);
+    if definitions.is_none() && style.is_none() {
+        return Vec::new();
+    }
+    let mut functions: Vec<(String, (usize, usize))> = Vec::new();
+    let added = diff
+        .hunks
+        .iter()
+        .flat_map(|hunk| &hunk.changes)
+        .filter(|line| line.change_type == ChangeType::Added)
+        .filter_map(|line| line.new_line_no);
+    for line_no in added {
+        let idx = line_no.saturating_sub(1);
+        if idx >= lines.len() {
+            continue;
+        }
+        let found = match (&definitions, style) {
+            (Some(definitions), _) => innermost(definitions, idx, true)
+                .map(|def| (def.name.clone(), def.line, def.end_line)),
+            (None, Some(style)) => enclosing_scope(lines, style, idx).and_then(|(start, end)| {
+                defined_function_name(lines[start]).map(|name| (name, start, end))
+            }),
+            (None, None) => None,
+        };
+        let Some((name, start, end)) = found else {
+            continue;
+        };
+        let range = (start + 1, end + 1);
+        if !functions.iter().any(|(_, existing)| *existing == range) {
+            functions.push((name, range));
+        }
+    }
+    functions
+}
+
+fn brace_scope_end(lines: &[&str], start: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut opened = false;
+    let limit = (start + MAX_SCOPE_LINES).min(lines.len());
+
+    for (idx, line) in lines.iter().enumerate().take(limit).skip(start) {
+        // Signatures that end without an opening brace (declarations) have no body.
+        if !opened && idx > start + 5 {
+            return None;
+        }
+        let mut in_string: Option<char> = None;
+        let mut prev = '\0';
+        for ch in line.chars() {
+            if let Some(quote) = in_string {
+                if ch == quote && prev != '\\' {
+                    in_string = None;
+                }
+            } else if ch == '/' && prev == '/' {
+                break;
+            } else if ch == '"' || ch == '`' {
+                // Single quotes are skipped: Rust lifetimes would otherwise open a "string".
+                in_string = Some(ch);
+            } else if ch == '{' {
+                depth += 1;
+                opened = true;
+            } else if ch == '}' && depth > 0 {
+                depth -= 1;
+                if opened && depth == 0 {
+                    return Some(idx);
+                }
+            } else if ch == ';' && !opened {
+                return None;
+            }
+            prev = if prev == '\\' && ch == '\\' { '\0' } else { ch };
+        }
+    }
+    None
+}
+
+fn indent_scope_end(lines: &[&str], start: usize) -> Option<usize> {
+    let base_indent = indentation(lines[start]);
+    let mut end = start;
+    for (idx, line) in lines.iter().enumerate().skip(start + 1) {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let indent = indentation(line);
+        if indent <= base_indent {
+            // Ruby closes blocks with a dedented `end`.
+            if indent == base_indent && line.trim() == "end" {
+                end = idx;
+            }
+            break;
+        }
+        end = idx;
+    }
+    (end > start).then_some(end)
+}
+
+fn indentation(line: &str) -> usize {
+    line.chars()
+        .take_while(|c| c.is_whitespace())
+        .map(|c| if c == '\t' { 4 } else { 1 })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expands_hunk_to_enclosing_rust_function() {
+        let source = "use std::fmt;\n\nstruct Cart {\n    items: Vec<u32>,\n}\n\nimpl Cart {\n    pub fn total(&self) -> u32 {\n        let mut sum = 0;\n        for item in &self.items {\n            sum += item;\n        }\n        sum\n    }\n\n    fn is_empty(&self) -> bool {\n        self.items.is_empty()\n    }\n}\n";
+        let lines: Vec<&str> = source.lines().collect();
+
+        // Hunk touching the loop body (line 11) expands to the whole `total` method.
+        assert_eq!(
+            expand_to_enclosing_scope(Path::new("src/cart.rs"), &lines, (11, 11)),
+            (8, 14)
+        );
+        // Unknown languages keep the raw range.
+        assert_eq!(
+            expand_to_enclosing_scope(Path::new("notes.txt"), &lines, (11, 11)),
+            (11, 11)
+        );
+    }
+
+    #[test]
+    fn detects_changed_and_removed_signatures() {
+        let diff = crate::core::DiffParser::parse_unified_diff(
+            "--- a/src/cart.rs\n+++ b/src/cart.rs\n@@ -1,7 +1,6 @@\n-pub fn total(items: &[u32]) -> u32 {\n+pub fn total(items: &[u32], discount: u32) -> u32 {\n     items.iter().sum()\n }\n-fn unused() {}\n fn helper(x: u32) -> u32 {\n-    x\n+    x + 1\n }\n",
+        )
+        .unwrap()
+        .remove(0);
+        let mut names = changed_function_signatures(&diff);
+        names.sort();
+        assert_eq!(names, vec!["total".to_string(), "unused".to_string()]);
+    }
+
+    #[test]
+    fn expands_hunk_to_enclosing_python_function() {
+        let source = "class Cart:\n    def total(self):\n        total = 0\n        for item in self.items:\n            total += item\n        return total\n\n    def empty(self):\n        return not self.items\n";
+        let lines: Vec<&str> = source.lines().collect();
+        assert_eq!(
+            expand_to_enclosing_scope(Path::new("cart.py"), &lines, (5, 5)),
+            (2, 6)
+        );
+    }
+
+    #[test]
+    fn uses_the_syntax_tree_where_brace_counting_fails() {
+        let source = "fn is_close(c: char) -> bool {\n    let open = '{';\n    c == '}' && c != open\n}\n\nstruct Tokens;\n";
+        let lines: Vec<&str> = source.lines().collect();
+        assert_eq!(
+            expand_to_enclosing_scope(Path::new("src/lex.rs"), &lines, (3, 3)),
+            (1, 4)
+        );
+
+        let diff = crate::core::DiffParser::parse_unified_diff(
+            "--- a/src/lex.rs\n+++ b/src/lex.rs\n@@ -1,6 +1,6 @@\n fn is_close(c: char) -> bool {\n     let open = '{';\n-    c == '}'\n+    c == '}' && c != open\n }\n \n-struct Token;\n+struct Tokens;\n",
+        )
+        .unwrap()
+        .remove(0);
+        assert_eq!(
+            changed_functions(&diff, &lines),
+            vec![("is_close".to_string(), (1, 4))]
+        );
+    }
+}
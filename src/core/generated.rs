@@ -0,0 +1,158 @@
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Bytes of each file read when looking for a generator's header.
+const HEADER_BYTES: usize = 1024;
+
+/// Lines of the header searched for `MARKERS`.
+const HEADER_LINES: usize = 5;
+
+/// Header comments generators leave, matched case-sensitively.
+const MARKERS: &[&str] = &[
+    "@generated",
+    "DO NOT EDIT",
+    "Code generated by",
+    "Autogenerated by",
+    "Auto-generated by",
+    "auto-generated by",
+    "This file was generated",
+    "This file is generated",
+];
+
+/// Files marked as generated in a repository: by `linguist-generated` in `.gitattributes`,
+/// a generator's header comment, or living in an OpenAPI Generator output directory.
+/// Path-only conventions (`*.pb.go`, ...) are `languages::categories`.
+#[derive(Debug, Clone, Default)]
+pub struct GeneratedFiles {
+    root: PathBuf,
+    /// `.gitattributes` patterns setting `linguist-generated`, in file order.
+    attributes: Vec<(glob::Pattern, bool)>,
+}
+
+impl GeneratedFiles {
+    pub fn load(repo_root: &Path) -> Self {
+        let attributes = std::fs::read_to_string(repo_root.join(".gitattributes"))
+            .map(|content| parse_attributes(&content))
+            .unwrap_or_default();
+        Self {
+            root: repo_root.to_path_buf(),
+            attributes,
+        }
+    }
+
+    /// Whether `path` (relative to the repository root) is generated. An explicit
+    /// `linguist-generated=false` wins over the header and directory checks.
+    pub fn is_generated(&self, path: &Path) -> bool {
+        if let Some(generated) = self.attribute(path) {
+            return generated;
+        }
+        self.in_openapi_output(path) || has_generated_header(&self.root.join(path))
+    }
+
+    /// The last `.gitattributes` setting that applies to `path`, as git resolves it.
+    fn attribute(&self, path: &Path) -> Option<bool> {
+        let options = glob::MatchOptions {
+            require_literal_separator: true,
+            ..Default::default()
+        };
+        let name = Path::new(path.file_name()?);
+        self.attributes
+            .iter()
+            .rev()
+            .find(|(pattern, _)| {
+                // Patterns without a slash match the file name at any depth.
+                let target = if pattern.as_str().contains('/') {
+                    path
+                } else {
+                    name
+                };
+                pattern.matches_path_with(target, options)
+            })
+            .map(|(_, generated)| *generated)
+    }
+
+    /// OpenAPI Generator writes a `.openapi-generator` directory at the root of each client.
+    fn in_openapi_output(&self, path: &Path) -> bool {
+        path.ancestors()
+            .skip(1)
+            .any(|dir| self.root.join(dir).join(".openapi-generator").is_dir())
+    }
+}
+
+fn parse_attributes(content: &str) -> Vec<(glob::Pattern, bool)> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let pattern = fields.next()?.trim_start_matches('/');
+            let generated = fields.find_map(|attribute| match attribute {
+                "linguist-generated" | "linguist-generated=true" => Some(true),
+                "-linguist-generated" | "!linguist-generated" | "linguist-generated=false" => {
+                    Some(false)
+                }
+                _ => None,
+            })?;
+            Some((glob::Pattern::new(pattern).ok()?, generated))
+        })
+        .collect()
+}
+
+fn has_generated_header(path: &Path) -> bool {
+    let mut header = Vec::with_capacity(HEADER_BYTES);
+    let Ok(file) = std::fs::File::open(path) else {
+        return false;
+    };
+    if file
+        .take(HEADER_BYTES as u64)
+        .read_to_end(&mut header)
+        .is_err()
+    {
+        return false;
+    }
+    String::from_utf8_lossy(&header)
+        .lines()
+        .take(HEADER_LINES)
+        .any(|line| MARKERS.iter().any(|marker| line.contains(marker)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_attributes_headers_and_openapi_output() {
+        let repo = tempfile::tempdir().unwrap();
+        for (path, content) in [
+            (
+                ".gitattributes",
+                "*.snap linguist-generated\n/api/schema/** linguist-generated=true\napi/schema/hand.rs -linguist-generated\n",
+            ),
+            ("ui/__tests__/app.snap", "exports[`app`] = `<div/>`;\n"),
+            ("api/schema/types.rs", "pub struct User;\n"),
+            ("api/schema/hand.rs", "// Code generated by hand. DO NOT EDIT.\n"),
+            ("proto/user.go", "// Code generated by protoc-gen-go. DO NOT EDIT.\npackage proto\n"),
+            ("clients/ts/.openapi-generator/VERSION", "7.0.0\n"),
+            ("clients/ts/src/api.ts", "export class Api {}\n"),
+            ("src/lib.rs", "//! Handwritten.\npub fn run() {}\n"),
+        ] {
+            let path = repo.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+
+        let generated = GeneratedFiles::load(repo.path());
+        for path in [
+            "ui/__tests__/app.snap",
+            "api/schema/types.rs",
+            "proto/user.go",
+            "clients/ts/src/api.ts",
+        ] {
+            assert!(generated.is_generated(Path::new(path)), "{}", path);
+        }
+        assert!(!generated.is_generated(Path::new("api/schema/hand.rs")));
+        assert!(!generated.is_generated(Path::new("src/lib.rs")));
+        assert!(!generated.is_generated(Path::new("src/missing.rs")));
+    }
+}
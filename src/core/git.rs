@@ -1,7 +1,20 @@
 use anyhow::{Context, Result};
 use git2::{BranchType, DiffFormat, DiffOptions, Repository};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
+/// The commit that last changed a line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineBlame {
+    pub line: usize,
+    /// Abbreviated commit id.
+    pub commit: String,
+    pub author: String,
+    /// Commit time in Unix seconds.
+    pub time: i64,
+    pub summary: String,
+}
+
 pub struct GitIntegration {
     repo: Repository,
 }
@@ -21,13 +34,25 @@ impl GitIntegration {
             .repo
             .diff_tree_to_workdir_with_index(Some(&head), Some(&mut diff_options))?;
 
-        let mut diff_text = Vec::new();
-        diff.print(DiffFormat::Patch, |_delta, _hunk, line| {
-            diff_text.extend_from_slice(line.content());
-            true
-        })?;
+        patch_text(&diff)
+    }
+
+    /// Uncommitted changes to the file at `path` (relative to the work tree), staged or not,
+    /// compared with HEAD. An untracked file shows up as entirely added.
+    pub fn get_path_diff(&self, path: &Path) -> Result<String> {
+        let mut diff_options = DiffOptions::new();
+        diff_options
+            .include_untracked(true)
+            .show_untracked_content(true)
+            .disable_pathspec_match(true)
+            .pathspec(path);
+
+        let head = self.repo.head()?.peel_to_tree()?;
+        let diff = self
+            .repo
+            .diff_tree_to_workdir_with_index(Some(&head), Some(&mut diff_options))?;
 
-        Ok(String::from_utf8_lossy(&diff_text).to_string())
+        patch_text(&diff)
     }
 
     pub fn get_staged_diff(&self) -> Result<String> {
@@ -40,13 +65,26 @@ impl GitIntegration {
             .repo
             .diff_tree_to_tree(Some(&head), Some(&index_tree), None)?;
 
-        let mut diff_text = Vec::new();
-        diff.print(DiffFormat::Patch, |_delta, _hunk, line| {
-            diff_text.extend_from_slice(line.content());
-            true
-        })?;
+        patch_text(&diff)
+    }
+
+    /// What `git commit --amend` would commit: the index compared with HEAD's parent (or
+    /// with nothing, when HEAD is the root commit).
+    pub fn get_amend_diff(&self) -> Result<String> {
+        let head = self.repo.head()?.peel_to_commit()?;
+        let parent = match head.parent(0) {
+            Ok(parent) => Some(parent.tree()?),
+            Err(_) => None,
+        };
+        let mut index = self.repo.index()?;
+        let oid = index.write_tree()?;
+        let index_tree = self.repo.find_tree(oid)?;
+
+        let diff = self
+            .repo
+            .diff_tree_to_tree(parent.as_ref(), Some(&index_tree), None)?;
 
-        Ok(String::from_utf8_lossy(&diff_text).to_string())
+        patch_text(&diff)
     }
 
     pub fn get_branch_diff(&self, base_branch: &str) -> Result<String> {
@@ -60,13 +98,57 @@ impl GitIntegration {
             .repo
             .diff_tree_to_tree(Some(&base_tree), Some(&head_tree), None)?;
 
-        let mut diff_text = Vec::new();
-        diff.print(DiffFormat::Patch, |_delta, _hunk, line| {
-            diff_text.extend_from_slice(line.content());
-            true
-        })?;
+        patch_text(&diff)
+    }
+
+    /// Diff between two commits' trees, e.g. the last reviewed and current PR heads.
+    pub fn get_commit_range_diff(&self, from: &str, to: &str) -> Result<String> {
+        let from_tree = self.repo.revparse_single(from)?.peel_to_tree()?;
+        let to_tree = self.repo.revparse_single(to)?.peel_to_tree()?;
+
+        let diff = self
+            .repo
+            .diff_tree_to_tree(Some(&from_tree), Some(&to_tree), None)?;
+
+        patch_text(&diff)
+    }
+
+    /// Diff of a `from..to` range (`to` defaults to HEAD), or of `from...to` against their
+    /// merge base, as `git diff` reads ranges.
+    pub fn get_range_diff(&self, range: &str) -> Result<String> {
+        let (from, to, merge_base) = match range.split_once("...") {
+            Some((from, to)) => (from, to, true),
+            None => match range.split_once("..") {
+                Some((from, to)) => (from, to, false),
+                None => anyhow::bail!("Expected a range like A..B or A...B, got {}", range),
+            },
+        };
+        let from = if from.is_empty() { "HEAD" } else { from };
+        let to = if to.is_empty() { "HEAD" } else { to };
+        if !merge_base {
+            return self.get_commit_range_diff(from, to);
+        }
+        let from_commit = self.repo.revparse_single(from)?.peel_to_commit()?;
+        let to_commit = self.repo.revparse_single(to)?.peel_to_commit()?;
+        let base = self.repo.merge_base(from_commit.id(), to_commit.id())?;
+        self.get_commit_range_diff(&base.to_string(), to)
+    }
+
+    /// Message and diff (against its first parent) of commit `rev`.
+    pub fn get_commit(&self, rev: &str) -> Result<(String, String)> {
+        let commit = self.repo.revparse_single(rev)?.peel_to_commit()?;
+        let parent_tree = match commit.parents().next() {
+            Some(parent) => Some(parent.tree()?),
+            None => None,
+        };
+        let diff =
+            self.repo
+                .diff_tree_to_tree(parent_tree.as_ref(), Some(&commit.tree()?), None)?;
 
-        Ok(String::from_utf8_lossy(&diff_text).to_string())
+        Ok((
+            commit.message().unwrap_or_default().to_string(),
+            patch_text(&diff)?,
+        ))
     }
 
     pub fn get_current_branch(&self) -> Result<String> {
@@ -102,6 +184,138 @@ impl GitIntegration {
         Ok(commits)
     }
 
+    /// How many non-merge commits since `since` (Unix seconds) touched each of `paths`,
+    /// looking at no more than `max_commits` commits back from HEAD.
+    pub fn churn(
+        &self,
+        paths: &[PathBuf],
+        since: i64,
+        max_commits: usize,
+    ) -> Result<HashMap<PathBuf, usize>> {
+        let mut counts = HashMap::new();
+        if paths.is_empty() {
+            return Ok(counts);
+        }
+        for (_, files) in self.file_history(Some(paths), since, max_commits)? {
+            for path in files {
+                *counts.entry(path).or_insert(0) += 1;
+            }
+        }
+        Ok(counts)
+    }
+
+    /// The summary and changed files of each non-merge commit since `since` (Unix seconds),
+    /// newest first, looking at no more than `max_commits` commits back from HEAD. With
+    /// `paths`, only changes to those files are listed.
+    pub fn file_history(
+        &self,
+        paths: Option<&[PathBuf]>,
+        since: i64,
+        max_commits: usize,
+    ) -> Result<Vec<(String, Vec<PathBuf>)>> {
+        let mut revwalk = self.repo.revwalk()?;
+        revwalk.push_head()?;
+        revwalk.set_sorting(git2::Sort::TIME)?;
+
+        let mut history = Vec::new();
+        for oid in revwalk.take(max_commits) {
+            let commit = self.repo.find_commit(oid?)?;
+            if commit.time().seconds() < since {
+                break;
+            }
+            if commit.parent_count() > 1 {
+                continue;
+            }
+            let mut options = DiffOptions::new();
+            options.disable_pathspec_match(true);
+            for path in paths.into_iter().flatten() {
+                options.pathspec(path);
+            }
+            let parent = match commit.parents().next() {
+                Some(parent) => Some(parent.tree()?),
+                None => None,
+            };
+            let diff = self.repo.diff_tree_to_tree(
+                parent.as_ref(),
+                Some(&commit.tree()?),
+                Some(&mut options),
+            )?;
+            let files: Vec<PathBuf> = diff
+                .deltas()
+                .filter_map(|delta| delta.new_file().path().or(delta.old_file().path()))
+                .map(Path::to_path_buf)
+                .collect();
+            if !files.is_empty() {
+                history.push((commit.summary().unwrap_or_default().to_string(), files));
+            }
+        }
+        Ok(history)
+    }
+
+    /// Blame for `lines` (1-based) of `path` with the given current `content`, which may
+    /// differ from HEAD. Lines that are not committed yet are left out.
+    pub fn blame_lines(
+        &self,
+        path: &Path,
+        content: &[u8],
+        lines: &[usize],
+    ) -> Result<Vec<LineBlame>> {
+        let blame = self
+            .repo
+            .blame_file(path, None)
+            .with_context(|| format!("Failed to blame {}", path.display()))?;
+        let blame = blame.blame_buffer(content)?;
+        let mut summaries: HashMap<git2::Oid, String> = HashMap::new();
+        let mut blamed = Vec::new();
+        for &line in lines {
+            let Some(hunk) = blame.get_line(line) else {
+                continue;
+            };
+            let oid = hunk.final_commit_id();
+            if oid.is_zero() {
+                continue;
+            }
+            let summary = summaries
+                .entry(oid)
+                .or_insert_with(|| {
+                    self.repo
+                        .find_commit(oid)
+                        .ok()
+                        .and_then(|commit| commit.summary().map(str::to_string))
+                        .unwrap_or_default()
+                })
+                .clone();
+            let signature = hunk.final_signature();
+            blamed.push(LineBlame {
+                line,
+                commit: oid.to_string()[..8].to_string(),
+                author: signature.name().unwrap_or("unknown").to_string(),
+                time: signature.when().seconds(),
+                summary,
+            });
+        }
+        Ok(blamed)
+    }
+
+    /// Where git runs hooks from: `core.hooksPath` if set, else `.git/hooks`.
+    pub fn hooks_dir(&self) -> PathBuf {
+        let configured = self
+            .repo
+            .config()
+            .ok()
+            .and_then(|config| config.get_path("core.hooksPath").ok());
+        match (configured, self.workdir()) {
+            (Some(path), Some(workdir)) if path.is_relative() => workdir.join(path),
+            (Some(path), _) => path,
+            (None, _) => self.repo.path().join("hooks"),
+        }
+    }
+
+    /// Whether this is a shallow clone, whose history may not reach a diff's merge base.
+    pub fn is_shallow(&self) -> bool {
+        self.repo.is_shallow()
+    }
+
     pub fn workdir(&self) -> Option<PathBuf> {
         self.repo.workdir().map(|path| path.to_path_buf())
     }
@@ -125,3 +339,88 @@ impl GitIntegration {
         Ok("main".to_string())
     }
 }
+
+/// `diff` as unified diff text, with the `+`/`-`/space prefix on every line.
+fn patch_text(diff: &git2::Diff) -> Result<String> {
+    let mut diff_text = Vec::new();
+    diff.print(DiffFormat::Patch, |_delta, _hunk, line| {
+        if matches!(line.origin(), '+' | '-' | ' ') {
+            diff_text.push(line.origin() as u8);
+        }
+        diff_text.extend_from_slice(line.content());
+        true
+    })?;
+
+    Ok(String::from_utf8_lossy(&diff_text).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit(repo: &Repository, path: &str, content: &str, message: &str) -> git2::Oid {
+        std::fs::write(repo.workdir().unwrap().join(path), content).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new(path)).unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::now("Dev", "dev@example.com").unwrap();
+        let parent = repo.head().ok().map(|head| head.peel_to_commit().unwrap());
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parents,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn diffs_single_commits_and_ranges() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let first = commit(&repo, "lib.rs", "fn a() {}\n", "Add a");
+        commit(&repo, "lib.rs", "fn a() {}\nfn b() {}\n", "Add b");
+        commit(
+            &repo,
+            "lib.rs",
+            "fn a() {}\nfn b() {}\nfn c() {}\n",
+            "Add c",
+        );
+        let git = GitIntegration::new(dir.path()).unwrap();
+
+        let (message, diff) = git.get_commit("HEAD~1").unwrap();
+        assert_eq!(message, "Add b");
+        assert!(diff.contains("\n fn a() {}\n+fn b() {}\n"));
+        assert!(!diff.contains("fn c"));
+
+        let (_, root) = git.get_commit(&first.to_string()).unwrap();
+        assert!(root.contains("+fn a() {}"));
+
+        let range = git.get_range_diff(&format!("{}..", first)).unwrap();
+        assert!(range.contains("+fn b() {}\n+fn c() {}\n"));
+        assert_eq!(
+            git.get_range_diff("HEAD~1...HEAD").unwrap(),
+            git.get_range_diff("HEAD~1..HEAD").unwrap()
+        );
+        assert!(git.get_range_diff("HEAD").is_err());
+    }
+
+    #[test]
+    fn counts_recent_commits_per_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        commit(&repo, "lib.rs", "fn a() {}\n", "Add a");
+        commit(&repo, "main.rs", "fn main() {}\n", "Add main");
+        commit(&repo, "lib.rs", "fn a() {}\nfn b() {}\n", "Add b");
+        let git = GitIntegration::new(dir.path()).unwrap();
+
+        let paths = [PathBuf::from("lib.rs"), PathBuf::from("README.md")];
+        let churn = git.churn(&paths, 0, 100).unwrap();
+        assert_eq!(churn.get(Path::new("lib.rs")), Some(&2));
+        assert_eq!(churn.len(), 1);
+        assert!(git.churn(&paths, i64::MAX, 100).unwrap().is_empty());
+    }
+}
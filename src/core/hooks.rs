@@ -0,0 +1,165 @@
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+/// First lines of every hook this module writes; hooks without it are never touched.
+const MARKER: &str = "# Installed by `diffscope hooks install`";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookKind {
+    /// Reviews the staged changes before each commit.
+    PreCommit,
+    /// Reviews the commits about to be pushed.
+    PrePush,
+    /// Fills the commit message template with `diffscope git suggest`.
+    PrepareCommitMsg,
+}
+
+impl HookKind {
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            HookKind::PreCommit => "pre-commit",
+            HookKind::PrePush => "pre-push",
+            HookKind::PrepareCommitMsg => "prepare-commit-msg",
+        }
+    }
+}
+
+/// Shell script for `kind`. Reviews fail the hook on error-level findings, while a failed
+/// commit message suggestion never blocks the commit; `model` (e.g. a fast local `ollama:`
+/// model) overrides the configured one. `DIFFSCOPE_SKIP=1` skips it.
+pub fn script(kind: HookKind, model: Option<&str>) -> String {
+    let model_flag = model
+        .map(|model| format!(" --model {}", shell_quote(model)))
+        .unwrap_or_default();
+    let flags = format!("--fail-on error --output-format markdown{}", model_flag);
+    let body = match kind {
+        HookKind::PreCommit => format!("exec diffscope git staged {}\n", flags),
+        HookKind::PrePush => format!(
+            r#"zero=0000000000000000000000000000000000000000
+while read -r local_ref local_sha remote_ref remote_sha; do
+    # Deleted branches have nothing to review.
+    [ "$local_sha" = "$zero" ] && continue
+    if [ "$remote_sha" = "$zero" ]; then
+        diffscope git branch {flags} || exit 1
+    else
+        diffscope git range "$remote_sha..$local_sha" {flags} || exit 1
+    fi
+done
+"#
+        ),
+        // $1 is the message file, $2 where the message came from, $3 the commit for `commit`.
+        HookKind::PrepareCommitMsg => format!(
+            r#"case "$2" in
+    # -m/-F messages, merges, and squashes already have their message.
+    message|merge|squash) exit 0 ;;
+    commit)
+        # --amend passes HEAD; -c/-C with another commit's message is left alone.
+        [ "$3" = HEAD ] || exit 0
+        diffscope git suggest --message-file "$1" --amend{model_flag} || true
+        ;;
+    *) diffscope git suggest --message-file "$1"{model_flag} || true ;;
+esac
+"#
+        ),
+    };
+    format!(
+        "#!/bin/sh\n{}\n# Skip once with DIFFSCOPE_SKIP=1 (pre-commit and pre-push also honor --no-verify).\n[ \"$DIFFSCOPE_SKIP\" = \"1\" ] && exit 0\ncommand -v diffscope >/dev/null 2>&1 || {{ echo \"diffscope not found; skipping {} hook\" >&2; exit 0; }}\n\n{}",
+        MARKER,
+        kind.file_name(),
+        body
+    )
+}
+
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// Writes the `kind` hook into `hooks_dir`. An existing hook DiffScope did not write is only
+/// replaced with `force`, after being backed up next to it as `<hook>.bak`.
+pub fn install(
+    hooks_dir: &Path,
+    kind: HookKind,
+    model: Option<&str>,
+    force: bool,
+) -> Result<PathBuf> {
+    std::fs::create_dir_all(hooks_dir)?;
+    let path = hooks_dir.join(kind.file_name());
+    if let Ok(existing) = std::fs::read_to_string(&path) {
+        if !existing.contains(MARKER) {
+            if !force {
+                anyhow::bail!(
+                    "{} already exists and was not installed by diffscope; rerun with --force to replace it (it is backed up to {}.bak)",
+                    path.display(),
+                    kind.file_name()
+                );
+            }
+            std::fs::copy(&path, path.with_extension("bak"))
+                .with_context(|| format!("Failed to back up {}", path.display()))?;
+        }
+    }
+    std::fs::write(&path, script(kind, model))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+    }
+    Ok(path)
+}
+
+/// Removes the `kind` hook if DiffScope wrote it, restoring a hook backed up by `install`.
+/// Returns whether anything was removed.
+pub fn uninstall(hooks_dir: &Path, kind: HookKind) -> Result<bool> {
+    let path = hooks_dir.join(kind.file_name());
+    match std::fs::read_to_string(&path) {
+        Ok(existing) if existing.contains(MARKER) => {
+            let backup = path.with_extension("bak");
+            if backup.exists() {
+                std::fs::rename(&backup, &path)?;
+            } else {
+                std::fs::remove_file(&path)?;
+            }
+            Ok(true)
+        }
+        _ => Ok(false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn installs_and_uninstalls_without_clobbering_other_hooks() {
+        let dir = tempfile::tempdir().unwrap();
+        let hooks = dir.path().join("hooks");
+
+        let path = install(&hooks, HookKind::PreCommit, Some("ollama:codellama"), false).unwrap();
+        let script = std::fs::read_to_string(&path).unwrap();
+        assert!(script.starts_with("#!/bin/sh\n"));
+        assert!(script.ends_with(
+            "exec diffscope git staged --fail-on error --output-format markdown --model 'ollama:codellama'\n"
+        ));
+        assert!(uninstall(&hooks, HookKind::PreCommit).unwrap());
+        assert!(!path.exists());
+
+        let pre_push = hooks.join("pre-push");
+        std::fs::write(&pre_push, "#!/bin/sh\nmake lint\n").unwrap();
+        assert!(install(&hooks, HookKind::PrePush, None, false).is_err());
+        install(&hooks, HookKind::PrePush, None, true).unwrap();
+        assert!(std::fs::read_to_string(&pre_push)
+            .unwrap()
+            .contains("diffscope git range \"$remote_sha..$local_sha\""));
+        assert!(uninstall(&hooks, HookKind::PrePush).unwrap());
+        assert_eq!(
+            std::fs::read_to_string(&pre_push).unwrap(),
+            "#!/bin/sh\nmake lint\n"
+        );
+        assert!(!uninstall(&hooks, HookKind::PrePush).unwrap());
+
+        let path = install(&hooks, HookKind::PrepareCommitMsg, None, false).unwrap();
+        let script = std::fs::read_to_string(&path).unwrap();
+        assert!(path.ends_with("prepare-commit-msg"));
+        assert!(script.contains("diffscope git suggest --message-file \"$1\" --amend || true"));
+        assert!(!script.contains("--fail-on"));
+    }
+}
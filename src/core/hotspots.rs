@@ -0,0 +1,106 @@
+use crate::core::GitIntegration;
+use anyhow::Result;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Commits walked when mining history.
+const MAX_COMMITS: usize = 5000;
+
+/// Commit summaries that describe a bug fix.
+static FIX_MESSAGE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)^(fix|hotfix|bugfix)(\(.*\))?!?:|\b(fix(es|ed)?|bug|hotfix|regression|crash|revert)\b")
+        .unwrap()
+});
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Hotspot {
+    pub path: PathBuf,
+    /// Commits touching the file in the window.
+    pub commits: usize,
+    /// Of those, commits whose summary describes a bug fix.
+    pub fixes: usize,
+}
+
+impl Hotspot {
+    /// Ranks by fixes first: a file fixed often is riskier than one merely edited often.
+    pub fn score(&self) -> usize {
+        self.fixes * 3 + self.commits
+    }
+}
+
+pub fn is_fix(summary: &str) -> bool {
+    FIX_MESSAGE.is_match(summary)
+}
+
+/// Files changed in the last `days` days (only `paths`, when given) with their commit and
+/// bug-fix counts, hottest first. Files that no longer exist are left out.
+pub fn analyze(
+    git: &GitIntegration,
+    repo_root: &Path,
+    paths: Option<&[PathBuf]>,
+    days: u32,
+) -> Result<Vec<Hotspot>> {
+    let since = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |now| now.as_secs() as i64)
+        - i64::from(days) * 24 * 60 * 60;
+    let mut counts: HashMap<PathBuf, (usize, usize)> = HashMap::new();
+    for (summary, files) in git.file_history(paths, since, MAX_COMMITS)? {
+        let fix = is_fix(&summary);
+        for path in files {
+            let entry = counts.entry(path).or_default();
+            entry.0 += 1;
+            entry.1 += usize::from(fix);
+        }
+    }
+
+    let mut hotspots: Vec<Hotspot> = counts
+        .into_iter()
+        .filter(|(path, _)| repo_root.join(path).is_file())
+        .map(|(path, (commits, fixes))| Hotspot {
+            path,
+            commits,
+            fixes,
+        })
+        .collect();
+    hotspots.sort_by(|a, b| b.score().cmp(&a.score()).then_with(|| a.path.cmp(&b.path)));
+    Ok(hotspots)
+}
+
+/// The note added to a hotspot's review context.
+pub fn context_note(hotspot: &Hotspot, days: u32) -> String {
+    format!(
+        "This file is a known hotspot: {} bug fixes among {} commits in the last {} days. Scrutinize the change for regressions and edge cases that earlier fixes addressed.",
+        hotspot.fixes,
+        hotspot.commits,
+        days
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_fix_commits() {
+        for summary in [
+            "fix: handle empty cart",
+            "fix(api)!: reject stale tokens",
+            "Fixes crash when config is missing",
+            "Revert \"Add retry loop\"",
+            "hotfix for checkout regression",
+        ] {
+            assert!(is_fix(summary), "{}", summary);
+        }
+        for summary in [
+            "Add prefix matcher",
+            "feat: bugle sounds",
+            "Refactor debug output",
+        ] {
+            assert!(!is_fix(summary), "{}", summary);
+        }
+    }
+}
@@ -0,0 +1,248 @@
+use crate::core::comment::{Comment, CommentSynthesizer};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+const SEVERITIES: [&str; 4] = ["Error", "Warning", "Info", "Suggestion"];
+
+/// Keywords shared by the languages DiffScope usually reviews.
+const KEYWORDS: &str =
+    "as async await break case catch class const continue def else elif enum except export \
+    extends false final finally fn for from func function go if impl import in interface \
+    let match mod mut new nil None null pub return self Self static struct switch this \
+    throw trait true True False try type use var while with yield";
+
+static TOKEN_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"(?P<comment>//[^\n]*|#\s[^\n]*)|(?P<string>"(?:[^"\\\n]|\\.)*"|'(?:[^'\\\n]|\\.)*')|(?P<number>\b\d+(?:\.\d+)?\b)|(?P<word>\b[A-Za-z_][A-Za-z0-9_]*\b)"#,
+    )
+    .unwrap()
+});
+
+/// Renders findings as a single self-contained HTML page (inline CSS and JS, no external
+/// assets) for publishing as a CI artifact.
+pub fn render(comments: &[Comment]) -> String {
+    let summary = CommentSynthesizer::generate_summary(comments);
+
+    let mut by_file: BTreeMap<&Path, Vec<&Comment>> = BTreeMap::new();
+    for comment in comments {
+        by_file
+            .entry(comment.file_path.as_path())
+            .or_default()
+            .push(comment);
+    }
+
+    let mut html = String::from(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>DiffScope Review</title>\n",
+    );
+    html.push_str(STYLE);
+    html.push_str("</head>\n<body>\n<h1>DiffScope Review</h1>\n<section class=\"stats\">\n");
+    for (label, value) in [
+        ("Score", format!("{:.1}/10", summary.overall_score)),
+        ("Issues", summary.total_comments.to_string()),
+        ("Critical", summary.critical_issues.to_string()),
+        ("Files", summary.files_reviewed.to_string()),
+    ] {
+        html.push_str(&format!(
+            "<div class=\"stat\"><span>{}</span><strong>{}</strong></div>\n",
+            label, value
+        ));
+    }
+    html.push_str("</section>\n");
+
+    if !summary.recommendations.is_empty() {
+        html.push_str("<ul class=\"recommendations\">\n");
+        for recommendation in &summary.recommendations {
+            html.push_str(&format!("<li>{}</li>\n", escape(recommendation)));
+        }
+        html.push_str("</ul>\n");
+    }
+
+    html.push_str("<nav class=\"tabs\">\n");
+    html.push_str(&format!(
+        "<button class=\"active\" data-filter=\"all\">All ({})</button>\n",
+        comments.len()
+    ));
+    for severity in SEVERITIES {
+        let count = summary.by_severity.get(severity).copied().unwrap_or(0);
+        html.push_str(&format!(
+            "<button data-filter=\"{0}\">{0} ({1})</button>\n",
+            severity, count
+        ));
+    }
+    html.push_str("</nav>\n");
+
+    if comments.is_empty() {
+        html.push_str("<p class=\"empty\">No issues found.</p>\n");
+    }
+    for (path, file_comments) in &by_file {
+        html.push_str(&format!(
+            "<details class=\"file\" open>\n<summary>{} <span class=\"count\">{}</span></summary>\n",
+            escape(&path.to_string_lossy()),
+            file_comments.len()
+        ));
+        for comment in file_comments {
+            render_finding(&mut html, comment);
+        }
+        html.push_str("</details>\n");
+    }
+
+    html.push_str(SCRIPT);
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+fn render_finding(html: &mut String, comment: &Comment) {
+    let severity = format!("{:?}", comment.severity);
+    html.push_str(&format!(
+        "<article class=\"finding\" data-severity=\"{0}\">\n<header><span class=\"badge {1}\">{0}</span> <span class=\"category\">{2:?}</span> <span class=\"line\">line {3}</span> <span class=\"confidence\">{4:.0}% confidence</span></header>\n<p>{5}</p>\n",
+        severity,
+        severity.to_lowercase(),
+        comment.category,
+        comment.line_number,
+        comment.confidence * 100.0,
+        escape(&comment.content)
+    ));
+    if let Some(suggestion) = &comment.suggestion {
+        html.push_str(&format!(
+            "<p class=\"suggestion\"><strong>Suggestion:</strong> {}</p>\n",
+            escape(suggestion)
+        ));
+    }
+    if let Some(code) = &comment.code_suggestion {
+        if !code.explanation.is_empty() {
+            html.push_str(&format!("<p>{}</p>\n", escape(&code.explanation)));
+        }
+        html.push_str("<pre class=\"code\"><code>");
+        for line in code.original_code.lines() {
+            html.push_str(&format!(
+                "<span class=\"del\">- {}</span>\n",
+                highlight(line)
+            ));
+        }
+        for line in code.suggested_code.lines() {
+            html.push_str(&format!(
+                "<span class=\"add\">+ {}</span>\n",
+                highlight(line)
+            ));
+        }
+        html.push_str("</code></pre>\n");
+    }
+    html.push_str("</article>\n");
+}
+
+/// Lightweight, language-agnostic highlighting of comments, strings, numbers, and keywords.
+fn highlight(code: &str) -> String {
+    let mut output = String::new();
+    let mut last = 0;
+    for captures in TOKEN_REGEX.captures_iter(code) {
+        let token = captures.get(0).unwrap();
+        output.push_str(&escape(&code[last..token.start()]));
+        last = token.end();
+        let class = if captures.name("comment").is_some() {
+            Some("tok-comment")
+        } else if captures.name("string").is_some() {
+            Some("tok-string")
+        } else if captures.name("number").is_some() {
+            Some("tok-number")
+        } else if KEYWORDS.split_whitespace().any(|k| k == token.as_str()) {
+            Some("tok-keyword")
+        } else {
+            None
+        };
+        match class {
+            Some(class) => output.push_str(&format!(
+                "<span class=\"{}\">{}</span>",
+                class,
+                escape(token.as_str())
+            )),
+            None => output.push_str(&escape(token.as_str())),
+        }
+    }
+    output.push_str(&escape(&code[last..]));
+    output
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+const STYLE: &str = r#"<style>
+body { font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", sans-serif; margin: 2rem auto; max-width: 960px; color: #1f2328; padding: 0 1rem; }
+.stats { display: flex; gap: 1rem; margin-bottom: 1rem; }
+.stat { border: 1px solid #d0d7de; border-radius: 6px; padding: 0.75rem 1rem; flex: 1; }
+.stat span { display: block; color: #656d76; font-size: 0.85rem; }
+.stat strong { font-size: 1.4rem; }
+.tabs { display: flex; gap: 0.5rem; margin: 1rem 0; }
+.tabs button { border: 1px solid #d0d7de; background: #f6f8fa; border-radius: 6px; padding: 0.4rem 0.8rem; cursor: pointer; }
+.tabs button.active { background: #0969da; border-color: #0969da; color: #fff; }
+.file { border: 1px solid #d0d7de; border-radius: 6px; margin-bottom: 1rem; padding: 0.5rem 1rem; }
+.file summary { font-family: monospace; font-weight: 600; cursor: pointer; }
+.count { background: #eaeef2; border-radius: 1rem; padding: 0 0.5rem; font-size: 0.8rem; }
+.finding { border-top: 1px solid #eaeef2; padding: 0.75rem 0; }
+.finding header { font-size: 0.85rem; color: #656d76; }
+.badge { border-radius: 4px; padding: 0.1rem 0.4rem; color: #fff; font-weight: 600; }
+.badge.error { background: #cf222e; }
+.badge.warning { background: #bf8700; }
+.badge.info { background: #0969da; }
+.badge.suggestion { background: #8250df; }
+.code { background: #f6f8fa; border-radius: 6px; padding: 0.75rem; overflow-x: auto; }
+.code .del { background: #ffebe9; display: block; }
+.code .add { background: #dafbe1; display: block; }
+.tok-keyword { color: #cf222e; }
+.tok-string { color: #0a3069; }
+.tok-number { color: #0550ae; }
+.tok-comment { color: #6e7781; font-style: italic; }
+.empty { color: #1a7f37; font-weight: 600; }
+</style>
+"#;
+
+const SCRIPT: &str = r#"<script>
+document.querySelectorAll('.tabs button').forEach(function (button) {
+  button.addEventListener('click', function () {
+    document.querySelectorAll('.tabs button').forEach(function (b) { b.classList.remove('active'); });
+    button.classList.add('active');
+    var filter = button.dataset.filter;
+    document.querySelectorAll('.finding').forEach(function (finding) {
+      finding.hidden = filter !== 'all' && finding.dataset.severity !== filter;
+    });
+    document.querySelectorAll('.file').forEach(function (file) {
+      file.hidden = !file.querySelector('.finding:not([hidden])');
+    });
+  });
+});
+</script>
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::comment::{test_comment, CodeSuggestion};
+
+    #[test]
+    fn renders_escaped_findings_with_highlighted_suggestions() {
+        let comment = Comment {
+            confidence: 0.8,
+            code_suggestion: Some(CodeSuggestion {
+                original_code: "let v = s.parse().unwrap();".to_string(),
+                suggested_code: "let v = s.parse()?; // \"checked\"".to_string(),
+                explanation: String::new(),
+                diff: String::new(),
+            }),
+            ..test_comment("src/<lib>.rs", 4, "Avoid `unwrap()` on <user> input")
+        };
+
+        let html = render(&[comment]);
+        assert!(html.contains("<summary>src/&lt;lib&gt;.rs"));
+        assert!(html.contains("on &lt;user&gt; input"));
+        assert!(html.contains("data-severity=\"Warning\""));
+        assert!(html.contains("<span class=\"tok-keyword\">let</span> v"));
+        assert!(html.contains("<span class=\"tok-comment\">// &quot;checked&quot;</span>"));
+        assert!(!html.contains("<script src"));
+    }
+}
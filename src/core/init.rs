@@ -0,0 +1,330 @@
+use crate::core::SymbolIndex;
+use std::path::Path;
+
+/// Files sampled when counting languages.
+const MAX_SCANNED_FILES: usize = 5000;
+
+/// Languages reported by `diffscope init`, by file extension.
+const LANGUAGES: &[(&str, &[&str])] = &[
+    ("Rust", &["rs"]),
+    ("Python", &["py", "pyi"]),
+    ("TypeScript", &["ts", "tsx"]),
+    ("JavaScript", &["js", "jsx", "mjs", "cjs"]),
+    ("Go", &["go"]),
+    ("Java", &["java"]),
+    ("Kotlin", &["kt"]),
+    ("Ruby", &["rb"]),
+    ("C/C++", &["c", "h", "cc", "cpp", "cxx", "hpp"]),
+    ("C#", &["cs"]),
+    ("PHP", &["php"]),
+    ("Swift", &["swift"]),
+    ("Terraform", &["tf"]),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CiSystem {
+    GitHubActions,
+    GitLab,
+    Bitbucket,
+    CircleCi,
+    Jenkins,
+}
+
+impl CiSystem {
+    pub fn name(&self) -> &'static str {
+        match self {
+            CiSystem::GitHubActions => "GitHub Actions",
+            CiSystem::GitLab => "GitLab CI",
+            CiSystem::Bitbucket => "Bitbucket Pipelines",
+            CiSystem::CircleCi => "CircleCI",
+            CiSystem::Jenkins => "Jenkins",
+        }
+    }
+}
+
+/// What `diffscope init` found in a repository.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RepoProfile {
+    /// Languages by number of files, most common first.
+    pub languages: Vec<(&'static str, usize)>,
+    pub ci: Option<CiSystem>,
+    /// Plugins backed by a linter the repository already configures (eslint, ruff, ...).
+    pub linters: Vec<&'static str>,
+    /// Build output and dependency directories to keep out of reviews.
+    pub exclude_patterns: Vec<String>,
+}
+
+/// The answers `diffscope init` asks for, with defaults suited to `RepoProfile`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InitAnswers {
+    pub model: String,
+    /// `balanced`, `chill`, or `assertive`.
+    pub review_profile: String,
+    /// Lowest severity that fails CI, if any.
+    pub fail_on: Option<String>,
+    pub github_workflow: bool,
+}
+
+impl InitAnswers {
+    pub fn defaults(profile: &RepoProfile) -> Self {
+        let model = if std::env::var("OPENAI_API_KEY").is_err()
+            && std::env::var("ANTHROPIC_API_KEY").is_ok()
+        {
+            "claude-3-5-sonnet-20241022"
+        } else {
+            "gpt-4o"
+        };
+        Self {
+            model: model.to_string(),
+            review_profile: "balanced".to_string(),
+            fail_on: None,
+            github_workflow: profile.ci == Some(CiSystem::GitHubActions),
+        }
+    }
+}
+
+fn exists(repo_root: &Path, path: &str) -> bool {
+    repo_root.join(path).exists()
+}
+
+fn contains(repo_root: &Path, path: &str, needle: &str) -> bool {
+    std::fs::read_to_string(repo_root.join(path)).is_ok_and(|content| content.contains(needle))
+}
+
+fn detect_ci(repo_root: &Path) -> Option<CiSystem> {
+    if exists(repo_root, ".github/workflows") {
+        Some(CiSystem::GitHubActions)
+    } else if exists(repo_root, ".gitlab-ci.yml") {
+        Some(CiSystem::GitLab)
+    } else if exists(repo_root, "bitbucket-pipelines.yml") {
+        Some(CiSystem::Bitbucket)
+    } else if exists(repo_root, ".circleci/config.yml") {
+        Some(CiSystem::CircleCi)
+    } else if exists(repo_root, "Jenkinsfile") {
+        Some(CiSystem::Jenkins)
+    } else {
+        None
+    }
+}
+
+fn detect_linters(repo_root: &Path) -> Vec<&'static str> {
+    let any = |paths: &[&str]| paths.iter().any(|path| exists(repo_root, path));
+    let mut linters = Vec::new();
+    if any(&[
+        ".eslintrc",
+        ".eslintrc.js",
+        ".eslintrc.cjs",
+        ".eslintrc.json",
+        ".eslintrc.yml",
+        ".eslintrc.yaml",
+        "eslint.config.js",
+        "eslint.config.mjs",
+        "eslint.config.cjs",
+    ]) || contains(repo_root, "package.json", "\"eslintConfig\"")
+    {
+        linters.push("eslint");
+    }
+    if any(&["ruff.toml", ".ruff.toml"]) || contains(repo_root, "pyproject.toml", "[tool.ruff") {
+        linters.push("ruff");
+    }
+    if any(&[
+        ".golangci.yml",
+        ".golangci.yaml",
+        ".golangci.toml",
+        ".golangci.json",
+    ]) {
+        linters.push("golangci");
+    }
+    if any(&[".semgrep.yml", ".semgrep.yaml", ".semgrep"]) {
+        linters.push("semgrep");
+    }
+    linters
+}
+
+/// Lockfiles, minified bundles, and vendored directories are skipped without a pattern
+/// (`languages.auto_skip`), so only build output needs listing.
+fn detect_excludes(repo_root: &Path) -> Vec<String> {
+    let mut patterns = Vec::new();
+    for (marker, pattern) in [
+        ("Cargo.toml", "**/target/**"),
+        ("dist", "**/dist/**"),
+        ("build", "**/build/**"),
+        ("pyproject.toml", "**/.venv/**"),
+    ] {
+        if exists(repo_root, marker) && !patterns.contains(&pattern.to_string()) {
+            patterns.push(pattern.to_string());
+        }
+    }
+    patterns
+}
+
+/// Languages, CI system, configured linters, and build directories of the repository.
+pub fn inspect(repo_root: &Path) -> RepoProfile {
+    let counts = SymbolIndex::scan_extension_counts(repo_root, MAX_SCANNED_FILES, |_| false);
+    let mut languages: Vec<(&'static str, usize)> = LANGUAGES
+        .iter()
+        .map(|(name, extensions)| {
+            let files = extensions
+                .iter()
+                .filter_map(|ext| counts.get(*ext))
+                .sum::<usize>();
+            (*name, files)
+        })
+        .filter(|(_, files)| *files > 0)
+        .collect();
+    languages.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+
+    RepoProfile {
+        languages,
+        ci: detect_ci(repo_root),
+        linters: detect_linters(repo_root),
+        exclude_patterns: detect_excludes(repo_root),
+    }
+}
+
+/// Plugins that only wrap an external linter; `diffscope init` turns each on only when the
+/// repository already configures it.
+const TOOL_PLUGINS: &[&str] = &["eslint", "ruff", "golangci", "semgrep"];
+
+/// A commented `.diffscope.yml` for `profile` and `answers`.
+pub fn render_config(profile: &RepoProfile, answers: &InitAnswers) -> String {
+    let mut out = String::from(
+        "# Generated by `diffscope init`; see .diffscope.yml.example for every option.\n",
+    );
+    if !profile.languages.is_empty() {
+        let languages: Vec<String> = profile
+            .languages
+            .iter()
+            .map(|(name, files)| format!("{} ({})", name, files))
+            .collect();
+        out.push_str(&format!("# Languages: {}\n", languages.join(", ")));
+    }
+    if let Some(ci) = profile.ci {
+        out.push_str(&format!("# CI: {}\n", ci.name()));
+    }
+    out.push_str(&format!(
+        "\nmodel: {}\nreview_profile: {}  # {}\n",
+        answers.model,
+        answers.review_profile,
+        crate::core::review_presets::profile_names().join(" | ")
+    ));
+    match &answers.fail_on {
+        Some(severity) => out.push_str(&format!(
+            "fail_on: {}  # Exit non-zero on findings at or above this severity\n",
+            severity
+        )),
+        None => out
+            .push_str("# fail_on: error  # Exit non-zero on findings at or above this severity\n"),
+    }
+
+    if !profile.exclude_patterns.is_empty() {
+        out.push_str("\nexclude_patterns:\n");
+        for pattern in &profile.exclude_patterns {
+            out.push_str(&format!("  - \"{}\"\n", pattern));
+        }
+    }
+
+    out.push_str("\n# Linter plugins run only when the repository already uses the tool; the other\n# built-in plugins (secrets, iac, complexity, missing_tests, ...) stay enabled.\nplugins:\n");
+    for plugin in TOOL_PLUGINS {
+        out.push_str(&format!(
+            "  {}: {}\n",
+            plugin,
+            profile.linters.contains(plugin)
+        ));
+    }
+    out
+}
+
+/// Environment variable holding the API key `model` needs, if any.
+pub fn api_key_env(model: &str) -> Option<&'static str> {
+    if model.starts_with("claude") {
+        Some("ANTHROPIC_API_KEY")
+    } else if model.starts_with("openrouter:") {
+        Some("OPENROUTER_API_KEY")
+    } else if model.starts_with("ollama:") || model.starts_with("local:") {
+        None
+    } else {
+        Some("OPENAI_API_KEY")
+    }
+}
+
+/// A GitHub Actions workflow that reviews each pull request and posts the findings.
+pub fn render_workflow(answers: &InitAnswers) -> String {
+    let key = api_key_env(&answers.model)
+        .map(|key| format!("          {key}: ${{{{ secrets.{key} }}}}\n"))
+        .unwrap_or_default();
+    format!(
+        r#"name: DiffScope Review
+on:
+  pull_request:
+    types: [opened, synchronize, reopened]
+
+permissions:
+  contents: read
+  pull-requests: write
+
+jobs:
+  review:
+    runs-on: ubuntu-latest
+    steps:
+      - uses: actions/checkout@v4
+        with:
+          fetch-depth: 0
+      - name: Install DiffScope
+        run: curl -sSL https://raw.githubusercontent.com/haasonsaas/diffscope/main/install.sh | sh
+      - name: Review pull request
+        env:
+{key}          GH_TOKEN: ${{{{ github.token }}}}
+        run: diffscope pr --number ${{{{ github.event.pull_request.number }}}} --post-comments
+"#
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inspects_the_repository_and_renders_a_loadable_config() {
+        let repo = tempfile::tempdir().unwrap();
+        for (path, content) in [
+            ("Cargo.toml", "[package]\nname = \"shop\"\n"),
+            ("Cargo.lock", ""),
+            ("src/main.rs", "fn main() {}\n"),
+            ("src/cart.rs", "pub fn total() {}\n"),
+            ("scripts/report.py", "print('hi')\n"),
+            ("pyproject.toml", "[tool.ruff]\nline-length = 100\n"),
+            (".github/workflows/ci.yml", "on: push\n"),
+        ] {
+            let path = repo.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+
+        let profile = inspect(repo.path());
+        assert_eq!(profile.languages, vec![("Rust", 2), ("Python", 1)]);
+        assert_eq!(profile.ci, Some(CiSystem::GitHubActions));
+        assert_eq!(profile.linters, vec!["ruff"]);
+        assert_eq!(
+            profile.exclude_patterns,
+            vec!["**/target/**", "**/.venv/**"]
+        );
+
+        let answers = InitAnswers {
+            model: "claude-3-5-sonnet-20241022".to_string(),
+            review_profile: "chill".to_string(),
+            fail_on: Some("error".to_string()),
+            github_workflow: true,
+        };
+        let config: crate::config::Config =
+            serde_yaml::from_str(&render_config(&profile, &answers)).unwrap();
+        assert_eq!(config.model, "claude-3-5-sonnet-20241022");
+        assert_eq!(config.review_profile.as_deref(), Some("chill"));
+        assert_eq!(config.fail_on, Some(crate::config::FailOn::Error));
+        assert!(config.plugins.ruff && !config.plugins.eslint && config.plugins.secrets);
+
+        let workflow = render_workflow(&answers);
+        assert!(workflow.contains("ANTHROPIC_API_KEY: ${{ secrets.ANTHROPIC_API_KEY }}\n"));
+        assert!(workflow.contains("--number ${{ github.event.pull_request.number }}"));
+    }
+}
@@ -3,6 +3,49 @@ use anyhow::Result;
 use regex::Regex;
 use std::collections::HashSet;
 
+/// Hidden tag on replies to `@diffscope` commands, naming the comment that was answered.
+const REPLY_MARKER: &str = "<!-- diffscope:reply-to=";
+
+pub fn reply_marker(comment_id: u64) -> String {
+    format!("{}{} -->", REPLY_MARKER, comment_id)
+}
+
+/// Commands in `(comment id, body)` pairs that no DiffScope reply answers yet. Comments
+/// written by DiffScope itself are never treated as commands.
+pub fn pending_commands<'a>(
+    comments: impl IntoIterator<Item = (u64, &'a str)>,
+) -> Vec<(u64, InteractiveCommand)> {
+    let comments: Vec<(u64, &str)> = comments.into_iter().collect();
+    let answered: HashSet<u64> = comments
+        .iter()
+        .flat_map(|(_, body)| {
+            body.split(REPLY_MARKER)
+                .skip(1)
+                .filter_map(|rest| rest.split_whitespace().next()?.parse().ok())
+        })
+        .collect();
+    comments
+        .into_iter()
+        .filter(|(id, body)| !answered.contains(id) && !body.contains("<!-- diffscope:"))
+        .filter_map(|(id, body)| Some((id, InteractiveCommand::parse(body)?)))
+        .collect()
+}
+
+const EXPLAIN_SYSTEM_PROMPT: &str = "You are a helpful code explainer. Provide clear, educational explanations of the code you are given. Answer in markdown with these sections: ### Summary (two or three sentences), ### How it works (the main steps or changes), ### Dependencies (the definitions and callers it relies on), and ### Caveats (edge cases, risks, or anything surprising). Refer to files, functions, and line numbers where they help.";
+
+/// A structured explanation of `subject` (e.g. a file, a function, or a commit) from
+/// `material`: its source or diff plus related definitions.
+pub async fn explain(adapter: &dyn LLMAdapter, subject: &str, material: &str) -> Result<String> {
+    let request = LLMRequest {
+        system_prompt: EXPLAIN_SYSTEM_PROMPT.to_string(),
+        user_prompt: format!("Explain {}.\n\n{}", subject, material),
+        temperature: Some(0.5),
+        max_tokens: Some(1500),
+        cached_context: None,
+    };
+    Ok(adapter.complete(request).await?.content)
+}
+
 #[allow(dead_code)]
 pub struct InteractiveCommand {
     pub command: CommandType,
@@ -65,7 +108,7 @@ impl InteractiveCommand {
             CommandType::Review => self.execute_review(adapter, diff_content).await,
             CommandType::Ignore => self.execute_ignore(),
             CommandType::Explain => self.execute_explain(adapter, diff_content).await,
-            CommandType::Generate => self.execute_generate(adapter).await,
+            CommandType::Generate => self.execute_generate(adapter, diff_content).await,
             CommandType::Help => Ok(Self::get_help_text()),
             CommandType::Config => Ok(Self::get_config_info()),
         }
@@ -93,6 +136,7 @@ impl InteractiveCommand {
                 user_prompt: prompt,
                 temperature: Some(0.3),
                 max_tokens: Some(1000),
+                cached_context: None,
             };
 
             let response = adapter.complete(request).await?;
@@ -119,31 +163,25 @@ impl InteractiveCommand {
         adapter: &dyn LLMAdapter,
         diff_content: Option<&str>,
     ) -> Result<String> {
-        let context = if self.args.is_empty() {
-            diff_content.unwrap_or("No specific context").to_string()
+        let subject = if self.args.is_empty() {
+            "the code changes".to_string()
         } else {
-            // Try to find specific line or section
-            let target = self.args.join(" ");
-            format!(
-                "Explain the following in the context of the code changes: {}",
-                target
-            )
-        };
-
-        let request = LLMRequest {
-            system_prompt:
-                "You are a helpful code explainer. Provide clear, educational explanations."
-                    .to_string(),
-            user_prompt: format!("Explain this code or change:\n\n{}", context),
-            temperature: Some(0.5),
-            max_tokens: Some(800),
+            format!("{} in the code changes", self.args.join(" "))
         };
-
-        let response = adapter.complete(request).await?;
-        Ok(format!("## 💡 Explanation\n\n{}", response.content))
+        let explanation = explain(
+            adapter,
+            &subject,
+            diff_content.unwrap_or("No specific context"),
+        )
+        .await?;
+        Ok(format!("## 💡 Explanation\n\n{}", explanation))
     }
 
-    async fn execute_generate(&self, adapter: &dyn LLMAdapter) -> Result<String> {
+    async fn execute_generate(
+        &self,
+        adapter: &dyn LLMAdapter,
+        diff_content: Option<&str>,
+    ) -> Result<String> {
         if self.args.is_empty() {
             return Ok(
                 "Please specify what to generate (e.g., @diffscope generate tests)".to_string(),
@@ -151,7 +189,14 @@ impl InteractiveCommand {
         }
 
         let target = self.args[0].as_str();
-        let context = self.args[1..].join(" ");
+        let mut context = self.args[1..].join(" ");
+        if let Some(diff) = diff_content {
+            context = format!(
+                "{}\n\nGround it in the functions changed by this diff:\n\n{}",
+                context.trim(),
+                diff
+            );
+        }
 
         let (system_prompt, user_prompt) = match target {
             "tests" => (
@@ -177,6 +222,7 @@ impl InteractiveCommand {
             user_prompt,
             temperature: Some(0.7),
             max_tokens: Some(1500),
+            cached_context: None,
         };
 
         let response = adapter.complete(request).await?;
@@ -245,6 +291,7 @@ Interactive commands respect these configurations."#
 }
 
 #[allow(dead_code)]
+#[derive(Default)]
 pub struct InteractiveProcessor {
     ignored_patterns: HashSet<String>,
 }
@@ -275,3 +322,25 @@ impl InteractiveProcessor {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pending_commands_skip_answered_and_own_comments() {
+        let reply = format!("## 💡 Explanation\n\n...\n\n{}", reply_marker(1));
+        let comments = vec![
+            (1, "@diffscope explain"),
+            (2, "looks good to me"),
+            (3, "@diffscope review security"),
+            (4, reply.as_str()),
+        ];
+
+        let pending = pending_commands(comments);
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].0, 3);
+        assert_eq!(pending[0].1.command, CommandType::Review);
+        assert_eq!(pending[0].1.args, vec!["security".to_string()]);
+    }
+}
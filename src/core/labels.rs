@@ -0,0 +1,108 @@
+use crate::config::LabelMap;
+use crate::core::comment::{Category, Comment, Severity};
+use crate::core::pr_summary::{ChangeType, PRSummary};
+use crate::plugins::builtin::BREAKING_CHANGE_TAG;
+
+/// Labels for a pull request from its summary's change type and the review findings, in
+/// map order without duplicates. Signals mapped to an empty name add nothing.
+pub fn labels_for(
+    map: &LabelMap,
+    summary: Option<&PRSummary>,
+    comments: &[Comment],
+) -> Vec<String> {
+    let change_type = summary.map(|summary| match summary.change_type {
+        ChangeType::Feature => &map.feature,
+        ChangeType::Fix => &map.fix,
+        ChangeType::Refactor => &map.refactor,
+        ChangeType::Docs => &map.docs,
+        ChangeType::Test => &map.test,
+        ChangeType::Chore => &map.chore,
+    });
+    let security = comments.iter().any(|comment| {
+        comment.category == Category::Security
+            && matches!(comment.severity, Severity::Error | Severity::Warning)
+    });
+    let needs_tests = comments
+        .iter()
+        .any(|comment| comment.category == Category::Testing);
+    let breaking = summary.is_some_and(|summary| {
+        summary
+            .breaking_changes
+            .as_deref()
+            .is_some_and(|text| !text.trim().is_empty())
+    }) || comments
+        .iter()
+        .any(|comment| comment.tags.iter().any(|tag| tag == BREAKING_CHANGE_TAG));
+
+    let mut labels: Vec<String> = Vec::new();
+    for label in [
+        change_type,
+        security.then_some(&map.security),
+        needs_tests.then_some(&map.needs_tests),
+        breaking.then_some(&map.breaking_change),
+    ]
+    .into_iter()
+    .flatten()
+    {
+        if !label.is_empty() && !labels.contains(label) {
+            labels.push(label.clone());
+        }
+    }
+    labels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::comment::test_comment;
+
+    fn comment(category: Category, severity: Severity, tags: &[&str]) -> Comment {
+        Comment {
+            severity,
+            category,
+            confidence: 1.0,
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            ..test_comment("src/lib.rs", 1, "Finding")
+        }
+    }
+
+    #[test]
+    fn maps_change_type_and_findings_to_labels() {
+        let summary = PRSummary {
+            title: "Add coupons".to_string(),
+            description: String::new(),
+            change_type: ChangeType::Feature,
+            key_changes: Vec::new(),
+            breaking_changes: None,
+            testing_notes: String::new(),
+            stats: Default::default(),
+            visual_diff: None,
+        };
+        let comments = vec![
+            comment(Category::Security, Severity::Info, &[]),
+            comment(Category::Testing, Severity::Info, &["missing-tests"]),
+            comment(
+                Category::Architecture,
+                Severity::Warning,
+                &[BREAKING_CHANGE_TAG],
+            ),
+        ];
+        let map = LabelMap {
+            needs_tests: String::new(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            labels_for(&map, Some(&summary), &comments),
+            vec!["feature", "breaking-change"]
+        );
+        assert_eq!(
+            labels_for(
+                &LabelMap::default(),
+                None,
+                &[comment(Category::Security, Severity::Error, &[])]
+            ),
+            vec!["security"]
+        );
+    }
+}
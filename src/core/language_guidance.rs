@@ -0,0 +1,94 @@
+use crate::core::languages;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Built-in review advice by language, as `languages::detect` names them.
+const BUILTIN: &[(&str, &str)] = &[
+    (
+        "rust",
+        "Check every `unsafe` block for a sound safety argument, including aliasing, alignment, and initialization. Flag `unwrap`/`expect` on fallible paths in library code, errors discarded with `let _ =` or `.ok()`, lifetimes or clones that work around the borrow checker instead of fixing ownership, locks or `RefCell` borrows held across `.await`, and blocking calls inside async code.",
+    ),
+    (
+        "python",
+        "Flag missing or wrong type hints on public functions, mutable default arguments, bare `except` or swallowed exceptions, late-binding closures in loops, blocking I/O or `time.sleep` inside `async` functions, coroutines that are never awaited, and string-built SQL or shell commands.",
+    ),
+    (
+        "sql",
+        "Flag queries built by string concatenation (SQL injection), migrations that take long table locks (adding non-null columns with defaults, building indexes without CONCURRENTLY, rewriting large tables), missing indexes for new filters or joins, data-destroying changes without a backfill or rollback path, and missing transaction boundaries.",
+    ),
+    (
+        "go",
+        "Flag ignored errors, errors wrapped without `%w`, goroutines that can leak or outlive their context, data races on shared maps or slices, loop variables captured by goroutines, `defer` inside loops, and missing `ctx` propagation or cancellation.",
+    ),
+    (
+        "typescript",
+        "Flag `any`, non-null assertions, and type casts that hide real errors, floating promises and missing `await`, unhandled rejections, `==` comparisons, and unvalidated external input trusted because of its static type.",
+    ),
+    (
+        "javascript",
+        "Flag floating promises and missing `await`, unhandled rejections, `==` comparisons, prototype pollution from merging untrusted objects, `innerHTML` or `eval` with untrusted data (XSS), and mutation of shared state across async boundaries.",
+    ),
+    (
+        "java",
+        "Flag swallowed exceptions, resources not closed with try-with-resources, `equals` without `hashCode`, unsynchronized shared mutable state, `Optional.get` without a check, and string-built SQL or JPQL.",
+    ),
+    (
+        "c",
+        "Flag buffer overflows and unchecked lengths, use-after-free and double free, unchecked `malloc` results, integer overflow in size calculations, format string bugs, and missing NUL termination.",
+    ),
+    (
+        "cpp",
+        "Flag raw owning pointers where RAII or smart pointers fit, use-after-move, dangling references and iterators invalidated by container changes, undefined behavior from signed overflow or out-of-bounds access, and missing `noexcept` on move operations.",
+    ),
+    (
+        "shell",
+        "Flag unquoted variable expansions, missing `set -euo pipefail` or unchecked exit codes, `eval` or command strings built from input, unsafe temporary files, and `cd` without a failure check.",
+    ),
+];
+
+/// The advice appended to the system prompt for `path`: the `overrides` entry for its
+/// language when there is one (an empty entry turns the advice off), else the built-in
+/// advice. Returns the language with its advice.
+pub fn for_path(
+    path: &Path,
+    overrides: &HashMap<String, String>,
+) -> Option<(&'static str, String)> {
+    let language = languages::detect(path)?;
+    let advice = match overrides
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(language))
+    {
+        Some((_, advice)) => advice.trim().to_string(),
+        None => BUILTIN
+            .iter()
+            .find(|(name, _)| *name == language)?
+            .1
+            .to_string(),
+    };
+    (!advice.is_empty()).then_some((language, advice))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_builtin_or_overridden_advice_by_language() {
+        let mut overrides = HashMap::new();
+        overrides.insert("Python".to_string(), "Use our typing rules.".to_string());
+        overrides.insert("sql".to_string(), String::new());
+
+        let (language, advice) = for_path(Path::new("src/lib.rs"), &overrides).unwrap();
+        assert_eq!(language, "rust");
+        assert!(advice.contains("unsafe"));
+        assert_eq!(
+            for_path(Path::new("app/models.py"), &overrides),
+            Some(("python", "Use our typing rules.".to_string()))
+        );
+        assert_eq!(for_path(Path::new("db/001_init.sql"), &overrides), None);
+        assert_eq!(for_path(Path::new("README.md"), &overrides), None);
+        assert!(BUILTIN
+            .iter()
+            .all(|(name, _)| languages::names().contains(name)));
+    }
+}
@@ -0,0 +1,265 @@
+use std::path::{Component, Path};
+
+/// Languages by file extension, named the way `languages.include`/`exclude` refer to them.
+const EXTENSIONS: &[(&str, &[&str])] = &[
+    ("rust", &["rs"]),
+    ("python", &["py", "pyi", "pyw"]),
+    ("typescript", &["ts", "tsx", "mts", "cts"]),
+    ("javascript", &["js", "jsx", "mjs", "cjs"]),
+    ("go", &["go"]),
+    ("java", &["java"]),
+    ("kotlin", &["kt", "kts"]),
+    ("scala", &["scala", "sc"]),
+    ("ruby", &["rb", "rake", "gemspec"]),
+    ("c", &["c", "h"]),
+    ("cpp", &["cc", "cpp", "cxx", "hh", "hpp", "hxx"]),
+    ("csharp", &["cs"]),
+    ("php", &["php"]),
+    ("swift", &["swift"]),
+    ("objective-c", &["m", "mm"]),
+    ("dart", &["dart"]),
+    ("elixir", &["ex", "exs"]),
+    ("haskell", &["hs"]),
+    ("lua", &["lua"]),
+    ("shell", &["sh", "bash", "zsh"]),
+    ("sql", &["sql"]),
+    ("terraform", &["tf", "tfvars", "hcl"]),
+    ("protobuf", &["proto"]),
+    ("html", &["html", "htm"]),
+    ("css", &["css", "scss", "sass", "less"]),
+    ("vue", &["vue"]),
+    ("svelte", &["svelte"]),
+    ("markdown", &["md", "mdx", "markdown"]),
+    ("yaml", &["yml", "yaml"]),
+    ("json", &["json"]),
+    ("toml", &["toml"]),
+];
+
+/// Languages of files recognized by name rather than extension.
+const FILENAMES: &[(&str, &str)] = &[
+    ("Dockerfile", "dockerfile"),
+    ("Containerfile", "dockerfile"),
+    ("Makefile", "makefile"),
+    ("GNUmakefile", "makefile"),
+    ("Rakefile", "ruby"),
+    ("Gemfile", "ruby"),
+    ("Jenkinsfile", "groovy"),
+    ("CMakeLists.txt", "cmake"),
+];
+
+const LOCKFILES: &[&str] = &[
+    "Cargo.lock",
+    "package-lock.json",
+    "npm-shrinkwrap.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "bun.lockb",
+    "poetry.lock",
+    "Pipfile.lock",
+    "uv.lock",
+    "Gemfile.lock",
+    "composer.lock",
+    "go.sum",
+    "mix.lock",
+    "pubspec.lock",
+    "Podfile.lock",
+    "flake.lock",
+    "packages.lock.json",
+];
+
+/// Directories holding third-party code, after linguist's `vendor.yml`.
+const VENDORED_DIRS: &[&str] = &[
+    "node_modules",
+    "vendor",
+    "vendored",
+    "third_party",
+    "third-party",
+    "bower_components",
+    "jspm_packages",
+    "Pods",
+    "Carthage",
+    "Godeps",
+    ".yarn",
+];
+
+/// File name suffixes of code emitted by common generators.
+const GENERATED_SUFFIXES: &[&str] = &[
+    ".pb.go",
+    ".pb.cc",
+    ".pb.h",
+    "_pb2.py",
+    "_pb2.pyi",
+    "_pb2_grpc.py",
+    ".pb.swift",
+    ".g.dart",
+    ".freezed.dart",
+    ".designer.cs",
+];
+
+/// Categories skipped from reviews unless `languages.include` names them or
+/// `languages.auto_skip` is off.
+pub const AUTO_SKIPPED: &[&str] = &["lockfile", "minified", "vendored", "generated"];
+
+/// Every language and category name `detect` and `categories` produce.
+pub fn names() -> Vec<&'static str> {
+    let mut names: Vec<&'static str> = EXTENSIONS
+        .iter()
+        .map(|(language, _)| *language)
+        .chain(FILENAMES.iter().map(|(_, language)| *language))
+        .chain(CATEGORIES.iter().copied())
+        .collect();
+    names.sort_unstable();
+    names.dedup();
+    names
+}
+
+/// Language of `path`, by file name and then extension.
+pub fn detect(path: &Path) -> Option<&'static str> {
+    let name = path.file_name()?.to_str()?;
+    if let Some((_, language)) = FILENAMES.iter().find(|(file, _)| *file == name) {
+        return Some(language);
+    }
+    let ext = path.extension()?.to_str()?.to_lowercase();
+    EXTENSIONS
+        .iter()
+        .find(|(_, extensions)| extensions.contains(&ext.as_str()))
+        .map(|(language, _)| *language)
+}
+
+/// Categories of `path` beyond its language: `lockfile`, `minified` (bundles and source
+/// maps), `vendored`, and `generated` (by file name; `generated::GeneratedFiles` also looks
+/// at `.gitattributes` and file headers).
+pub fn categories(path: &Path) -> Vec<&'static str> {
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default();
+    let mut categories = Vec::new();
+    if LOCKFILES.contains(&name) {
+        categories.push("lockfile");
+    }
+    if is_minified(name) {
+        categories.push("minified");
+    }
+    if path.components().any(|component| match component {
+        Component::Normal(dir) => dir.to_str().is_some_and(|dir| VENDORED_DIRS.contains(&dir)),
+        _ => false,
+    }) {
+        categories.push("vendored");
+    }
+    if GENERATED_SUFFIXES
+        .iter()
+        .any(|suffix| name.ends_with(suffix))
+        || name.contains(".generated.")
+        || name.contains("_generated.")
+        || name.starts_with("zz_generated.")
+        || path
+            .components()
+            .any(|component| component.as_os_str() == "__generated__")
+    {
+        categories.push("generated");
+    }
+    categories
+}
+
+fn is_minified(name: &str) -> bool {
+    let name = name.to_lowercase();
+    [".js", ".css", ".mjs"].iter().any(|ext| {
+        name.strip_suffix(ext).is_some_and(|stem| {
+            stem.ends_with(".min") || stem.ends_with("-min") || stem.ends_with(".bundle")
+        })
+    }) || name.ends_with(".js.map")
+        || name.ends_with(".css.map")
+}
+
+/// Whether `name` (a language or category) appears in `names`, ignoring case.
+fn listed(names: &[String], name: &str) -> bool {
+    names.iter().any(|listed| listed.eq_ignore_ascii_case(name))
+}
+
+/// Category names, as opposed to languages.
+const CATEGORIES: &[&str] = &["lockfile", "minified", "vendored", "generated"];
+
+/// Whether `languages:` keeps `path` out of reviews. A file matches a name when it is the
+/// file's language or one of its categories. Once `include` names a language, files
+/// matching none of it are skipped; categories in `include` only lift `auto_skip`.
+pub fn is_filtered(path: &Path, include: &[String], exclude: &[String], auto_skip: bool) -> bool {
+    let categories = categories(path);
+    let names: Vec<&str> = detect(path)
+        .into_iter()
+        .chain(categories.iter().copied())
+        .collect();
+    let restricts = include.iter().any(|name| {
+        !CATEGORIES
+            .iter()
+            .any(|category| name.eq_ignore_ascii_case(category))
+    });
+    if restricts && !names.iter().any(|name| listed(include, name)) {
+        return true;
+    }
+    if names.iter().any(|name| listed(exclude, name)) {
+        return true;
+    }
+    categories
+        .iter()
+        .any(|category| skips(category, include, exclude, auto_skip))
+}
+
+/// Whether files in `category` are skipped under these settings.
+pub fn skips(category: &str, include: &[String], exclude: &[String], auto_skip: bool) -> bool {
+    listed(exclude, category)
+        || (auto_skip && AUTO_SKIPPED.contains(&category) && !listed(include, category))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_languages_and_skips_lockfiles_bundles_and_vendored_code() {
+        assert_eq!(detect(Path::new("src/main.rs")), Some("rust"));
+        assert_eq!(detect(Path::new("web/App.TSX")), Some("typescript"));
+        assert_eq!(detect(Path::new("docker/Dockerfile")), Some("dockerfile"));
+        assert_eq!(detect(Path::new("LICENSE")), None);
+        assert_eq!(categories(Path::new("web/yarn.lock")), vec!["lockfile"]);
+        assert_eq!(
+            categories(Path::new("vendor/lib/app.min.js")),
+            vec!["minified", "vendored"]
+        );
+        assert_eq!(categories(Path::new("api/user.pb.go")), vec!["generated"]);
+        assert!(is_filtered(Path::new("api/user.pb.go"), &[], &[], true));
+
+        let none: &[String] = &[];
+        let names = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        assert!(!is_filtered(Path::new("src/lib.rs"), none, none, true));
+        assert!(is_filtered(Path::new("Cargo.lock"), none, none, true));
+        assert!(!is_filtered(Path::new("Cargo.lock"), none, none, false));
+        assert!(!is_filtered(
+            Path::new("Cargo.lock"),
+            &names(&["rust", "lockfile"]),
+            none,
+            true
+        ));
+        assert!(!is_filtered(
+            Path::new("src/lib.rs"),
+            &names(&["lockfile"]),
+            none,
+            true
+        ));
+
+        let include = names(&["Rust", "python"]);
+        assert!(!is_filtered(
+            Path::new("tools/gen.py"),
+            &include,
+            none,
+            true
+        ));
+        assert!(is_filtered(Path::new("web/app.ts"), &include, none, true));
+        assert!(is_filtered(
+            Path::new("api/user_generated.rs"),
+            &include,
+            &names(&["generated"]),
+            true
+        ));
+    }
+}
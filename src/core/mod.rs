@@ -1,22 +1,64 @@
+pub mod ask;
+pub mod cache;
+pub mod calibration;
 pub mod changelog;
+pub mod checkpoint;
+pub mod codeowners;
 pub mod comment;
 pub mod commit_prompt;
 pub mod context;
+pub mod cost;
+pub mod coverage;
+pub mod dependency_graph;
 pub mod diff_parser;
+pub mod doctor;
+pub mod embeddings;
+pub mod explain;
+pub mod feedback;
+pub mod feedback_remote;
+pub mod follow_up;
+pub mod function_scope;
+pub mod generated;
 pub mod git;
+pub mod hooks;
+pub mod hotspots;
+pub mod html_report;
+pub mod init;
 pub mod interactive;
+pub mod labels;
+pub mod language_guidance;
+pub mod languages;
+pub mod policy;
+pub mod position;
 pub mod pr_summary;
 pub mod prompt;
+pub mod remote_config;
+pub mod repo_map;
+pub mod report_template;
+pub mod review_presets;
+pub mod risk;
+pub mod rule_pack;
+pub mod similarity;
 pub mod smart_review_prompt;
+pub mod suggestion;
 pub mod symbol_index;
+pub mod syntax;
+pub mod test_gen;
+pub mod tokenizer;
+pub mod triage;
+pub mod verification;
 
+pub use cache::ReviewCache;
 pub use changelog::ChangelogGenerator;
 pub use comment::{Comment, CommentSynthesizer};
 pub use commit_prompt::CommitPromptBuilder;
 pub use context::{ContextFetcher, ContextType, LLMContextChunk};
+pub use coverage::CoverageReport;
+pub use dependency_graph::DependencyGraph;
 pub use diff_parser::{DiffParser, UnifiedDiff};
-pub use git::GitIntegration;
+pub use git::{GitIntegration, LineBlame};
 pub use pr_summary::{PRSummaryGenerator, SummaryOptions};
 pub use prompt::PromptBuilder;
 pub use smart_review_prompt::SmartReviewPromptBuilder;
-pub use symbol_index::SymbolIndex;
+pub use symbol_index::{LspIndexing, SymbolIndex};
+pub use tokenizer::PromptLimits;
@@ -0,0 +1,207 @@
+use crate::core::comment::{Comment, Severity};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+/// Tag added to findings that a `block` policy marks as merge-blocking.
+pub const BLOCKING_TAG: &str = "policy:block";
+
+/// One entry of the `policies` config section: findings matching every condition in `match`
+/// get `action` applied. Policies run in order after the other post-processing stages.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewPolicy {
+    #[serde(default)]
+    pub name: Option<String>,
+
+    #[serde(default, rename = "match")]
+    pub matcher: PolicyMatch,
+
+    pub action: PolicyAction,
+
+    /// Target severity for `action: set_severity`.
+    #[serde(default)]
+    pub severity: Option<String>,
+}
+
+/// Conditions of a policy. Empty lists match anything.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PolicyMatch {
+    /// Category names, case-insensitive (`security`, `best_practice`, ...).
+    #[serde(default)]
+    pub categories: Vec<String>,
+
+    #[serde(default)]
+    pub severities: Vec<String>,
+
+    /// Globs (or path prefixes) matched against the finding's file path.
+    #[serde(default)]
+    pub paths: Vec<String>,
+
+    /// Only findings with confidence strictly below this value.
+    #[serde(default)]
+    pub confidence_below: Option<f32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyAction {
+    /// Mark as merge-blocking: fails the run and requests changes on GitHub.
+    Block,
+    /// Drop the finding.
+    Silence,
+    /// Raise severity one level.
+    Escalate,
+    /// Lower severity one level.
+    Downgrade,
+    /// Set severity to `severity`.
+    SetSeverity,
+}
+
+pub fn parse_severity(value: &str) -> Option<Severity> {
+    match value.to_lowercase().as_str() {
+        "error" => Some(Severity::Error),
+        "warning" => Some(Severity::Warning),
+        "info" => Some(Severity::Info),
+        "suggestion" => Some(Severity::Suggestion),
+        _ => None,
+    }
+}
+
+impl PolicyMatch {
+    fn matches(&self, comment: &Comment) -> bool {
+        let category = format!("{:?}", comment.category).to_lowercase();
+        let category_matches = self.categories.is_empty()
+            || self
+                .categories
+                .iter()
+                .any(|c| c.replace('_', "").to_lowercase() == category);
+        let severity_matches = self.severities.is_empty()
+            || self
+                .severities
+                .iter()
+                .any(|s| parse_severity(s).as_ref() == Some(&comment.severity));
+        let path = comment.file_path.to_string_lossy();
+        let path_matches = self.paths.is_empty()
+            || self.paths.iter().any(|pattern| {
+                if pattern.contains('*') {
+                    glob::Pattern::new(pattern)
+                        .map(|glob| glob.matches(&path))
+                        .unwrap_or(false)
+                } else {
+                    path.starts_with(pattern.as_str())
+                }
+            });
+        let confidence_matches = self
+            .confidence_below
+            .is_none_or(|threshold| comment.confidence < threshold);
+        category_matches && severity_matches && path_matches && confidence_matches
+    }
+}
+
+/// Applies `policies` to every finding in order; a silenced finding skips later policies.
+pub fn apply_policies(comments: Vec<Comment>, policies: &[ReviewPolicy]) -> Vec<Comment> {
+    if policies.is_empty() {
+        return comments;
+    }
+    comments
+        .into_iter()
+        .filter_map(|mut comment| {
+            for policy in policies {
+                if !policy.matcher.matches(&comment) {
+                    continue;
+                }
+                debug!(
+                    "Policy {} applies {:?} to {}:{}",
+                    policy.name.as_deref().unwrap_or("(unnamed)"),
+                    policy.action,
+                    comment.file_path.display(),
+                    comment.line_number
+                );
+                match policy.action {
+                    PolicyAction::Silence => return None,
+                    PolicyAction::Block => {
+                        if !comment.tags.iter().any(|tag| tag == BLOCKING_TAG) {
+                            comment.tags.push(BLOCKING_TAG.to_string());
+                        }
+                    }
+                    PolicyAction::Escalate => {
+                        comment.severity = match comment.severity {
+                            Severity::Suggestion => Severity::Info,
+                            Severity::Info => Severity::Warning,
+                            Severity::Warning | Severity::Error => Severity::Error,
+                        }
+                    }
+                    PolicyAction::Downgrade => {
+                        comment.severity = match comment.severity {
+                            Severity::Error => Severity::Warning,
+                            Severity::Warning => Severity::Info,
+                            Severity::Info | Severity::Suggestion => Severity::Suggestion,
+                        }
+                    }
+                    PolicyAction::SetSeverity => {
+                        if let Some(severity) = policy.severity.as_deref().and_then(parse_severity)
+                        {
+                            comment.severity = severity;
+                        }
+                    }
+                }
+            }
+            Some(comment)
+        })
+        .collect()
+}
+
+pub fn is_blocking(comment: &Comment) -> bool {
+    comment.tags.iter().any(|tag| tag == BLOCKING_TAG)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::comment::{test_comment, Category};
+
+    #[test]
+    fn policies_route_findings_by_category_path_and_confidence() {
+        let comment = |path: &str, category, severity, confidence| Comment {
+            severity,
+            category,
+            confidence,
+            ..test_comment(path, 1, "finding")
+        };
+        let policies: Vec<ReviewPolicy> = serde_yaml::from_str(
+            r#"
+- name: block-security-errors
+  match: { categories: [security], severities: [error] }
+  action: block
+- match: { categories: [style] }
+  action: silence
+- match: { categories: [performance], paths: ["src/hot/**"] }
+  action: escalate
+- match: { confidence_below: 0.5 }
+  action: set_severity
+  severity: suggestion
+"#,
+        )
+        .unwrap();
+
+        let result = apply_policies(
+            vec![
+                comment("src/auth.rs", Category::Security, Severity::Error, 0.9),
+                comment("src/ui.rs", Category::Style, Severity::Warning, 0.9),
+                comment(
+                    "src/hot/loop.rs",
+                    Category::Performance,
+                    Severity::Warning,
+                    0.9,
+                ),
+                comment("src/cold.rs", Category::Performance, Severity::Warning, 0.3),
+            ],
+            &policies,
+        );
+
+        assert_eq!(result.len(), 3);
+        assert!(is_blocking(&result[0]));
+        assert_eq!(result[1].severity, Severity::Error);
+        assert!(!is_blocking(&result[1]));
+        assert_eq!(result[2].severity, Severity::Suggestion);
+    }
+}
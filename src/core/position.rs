@@ -0,0 +1,211 @@
+use crate::core::comment::Comment;
+use crate::core::diff_parser::{ChangeType, UnifiedDiff};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Which version of the file a review comment is anchored to, in GitHub's terms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum DiffSide {
+    /// The old file (removed lines).
+    Left,
+    /// The new file (added and context lines).
+    Right,
+}
+
+impl DiffSide {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DiffSide::Left => "LEFT",
+            DiffSide::Right => "RIGHT",
+        }
+    }
+}
+
+/// Where a comment lands in a pull request diff.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffPosition {
+    pub path: PathBuf,
+    /// Line number in the old file for `Left`, in the new file for `Right`.
+    pub line: usize,
+    pub side: DiffSide,
+    pub change_type: ChangeType,
+    /// Legacy GitHub `position`: lines below the file's first `@@` header, where later
+    /// hunk headers count as lines too.
+    pub position: usize,
+}
+
+/// A one-click-applyable replacement for lines `start_line..=end_line` of the new file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SuggestedChange {
+    pub start_line: usize,
+    pub end_line: usize,
+    pub body: String,
+}
+
+#[derive(Debug, Clone)]
+struct MappedLine {
+    old_line_no: Option<usize>,
+    new_line_no: Option<usize>,
+    change_type: ChangeType,
+    content: String,
+    position: usize,
+    hunk: usize,
+}
+
+/// Maps `Comment.line_number` values onto the lines of a unified diff so they can be posted
+/// as inline review comments.
+pub struct PositionMap {
+    files: HashMap<PathBuf, Vec<MappedLine>>,
+}
+
+impl PositionMap {
+    pub fn new(diffs: &[UnifiedDiff]) -> Self {
+        let mut files = HashMap::new();
+        for diff in diffs {
+            let mut lines = Vec::new();
+            let mut position = 0usize;
+            for (hunk_idx, hunk) in diff.hunks.iter().enumerate() {
+                if hunk_idx > 0 {
+                    position += 1; // the hunk header itself
+                }
+                for change in &hunk.changes {
+                    position += 1;
+                    lines.push(MappedLine {
+                        old_line_no: change.old_line_no,
+                        new_line_no: change.new_line_no,
+                        change_type: change.change_type.clone(),
+                        content: change.content.clone(),
+                        position,
+                        hunk: hunk_idx,
+                    });
+                }
+            }
+            files.insert(diff.file_path.clone(), lines);
+        }
+        Self { files }
+    }
+
+    /// Locates `line` in the diff for `path`, preferring the new file. Returns `None` for lines
+    /// outside every hunk, which GitHub rejects for inline comments.
+    pub fn locate(&self, path: &Path, line: usize) -> Option<DiffPosition> {
+        let lines = self.files.get(path)?;
+        let to_position = |mapped: &MappedLine, side: DiffSide| DiffPosition {
+            path: path.to_path_buf(),
+            line,
+            side,
+            change_type: mapped.change_type.clone(),
+            position: mapped.position,
+        };
+        lines
+            .iter()
+            .find(|mapped| mapped.new_line_no == Some(line))
+            .map(|mapped| to_position(mapped, DiffSide::Right))
+            .or_else(|| {
+                lines
+                    .iter()
+                    .find(|mapped| {
+                        mapped.change_type == ChangeType::Removed
+                            && mapped.old_line_no == Some(line)
+                    })
+                    .map(|mapped| to_position(mapped, DiffSide::Left))
+            })
+    }
+
+    /// Builds a GitHub suggestion for `comment` when its `CodeSuggestion.original_code` matches
+    /// the new-file lines around the commented line, so applying it replaces exactly that code.
+    pub fn suggestion_for(&self, comment: &Comment) -> Option<SuggestedChange> {
+        let code = comment.code_suggestion.as_ref()?;
+        let original: Vec<&str> = code
+            .original_code
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect();
+        if original.is_empty() {
+            return None;
+        }
+
+        let lines = self.files.get(&comment.file_path)?;
+        let anchor = lines
+            .iter()
+            .find(|mapped| mapped.new_line_no == Some(comment.line_number))?;
+        let hunk_lines: Vec<&MappedLine> = lines
+            .iter()
+            .filter(|mapped| mapped.hunk == anchor.hunk && mapped.new_line_no.is_some())
+            .collect();
+        let anchor_idx = hunk_lines
+            .iter()
+            .position(|mapped| mapped.new_line_no == Some(comment.line_number))?;
+
+        // The commented line may be anywhere inside the replaced block.
+        let first = anchor_idx.saturating_sub(original.len() - 1);
+        for start in first..=anchor_idx {
+            let Some(window) = hunk_lines.get(start..start + original.len()) else {
+                break;
+            };
+            let matches = window
+                .iter()
+                .zip(&original)
+                .all(|(mapped, expected)| mapped.content.trim() == *expected);
+            if matches {
+                return Some(SuggestedChange {
+                    start_line: window[0].new_line_no?,
+                    end_line: window[window.len() - 1].new_line_no?,
+                    body: suggestion_block(&code.suggested_code),
+                });
+            }
+        }
+        None
+    }
+}
+
+/// Fenced `suggestion` block that GitHub renders with a "Commit suggestion" button.
+pub fn suggestion_block(suggested_code: &str) -> String {
+    format!(
+        "```suggestion\n{}\n```",
+        suggested_code.trim_end_matches('\n')
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::comment::{test_comment, CodeSuggestion};
+    use crate::core::DiffParser;
+
+    #[test]
+    fn maps_lines_to_positions_and_matching_suggestions() {
+        let diffs = DiffParser::parse_unified_diff(
+            "diff --git a/src/lib.rs b/src/lib.rs\n--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1,3 +1,3 @@\n fn first() {\n-    let a = 1;\n+    let a = items[0];\n }\n@@ -20,2 +20,3 @@\n fn second() {\n+    let b = map.get(key).unwrap();\n }\n",
+        )
+        .unwrap();
+        let map = PositionMap::new(&diffs);
+        let path = Path::new("src/lib.rs");
+
+        let added = map.locate(path, 2).unwrap();
+        assert_eq!((added.side, added.position), (DiffSide::Right, 3));
+        // Second hunk: its header occupies position 5.
+        assert_eq!(map.locate(path, 21).unwrap().position, 7);
+        assert!(map.locate(path, 10).is_none());
+
+        let mut comment = Comment {
+            code_suggestion: Some(CodeSuggestion {
+                original_code: "let b = map.get(key).unwrap();".to_string(),
+                suggested_code: "    let b = map.get(key).copied().unwrap_or_default();"
+                    .to_string(),
+                explanation: String::new(),
+                diff: String::new(),
+            }),
+            ..test_comment("src/lib.rs", 21, "unwrap panics when the key is missing")
+        };
+        let suggestion = map.suggestion_for(&comment).unwrap();
+        assert_eq!((suggestion.start_line, suggestion.end_line), (21, 21));
+        assert!(suggestion.body.starts_with("```suggestion\n"));
+
+        // Placeholder or mismatched originals never become suggestions.
+        comment.code_suggestion.as_mut().unwrap().original_code = "// unrelated".to_string();
+        assert!(map.suggestion_for(&comment).is_none());
+    }
+}
@@ -1,9 +1,25 @@
 use crate::adapters::llm::{LLMAdapter, LLMRequest};
 use crate::core::{GitIntegration, UnifiedDiff};
 use anyhow::Result;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Files described in one walkthrough request; the walkthrough lists no more than this.
+pub const MAX_WALKTHROUGH_FILES: usize = 50;
+const MAX_FILE_DIFF_CHARS: usize = 3000;
+const MAX_WALKTHROUGH_DIFF_CHARS: usize = 60000;
 
 pub struct PRSummaryGenerator;
 
+/// Mermaid diagram type for `generate_change_diagram`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum DiagramKind {
+    /// How modules and components relate.
+    Flowchart,
+    /// The order of calls and messages between components.
+    Sequence,
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct SummaryOptions {
     pub include_diagram: bool,
@@ -27,18 +43,28 @@ impl PRSummaryGenerator {
     ) -> Result<PRSummary> {
         // Get commit messages for context
         let commits = git.get_recent_commits(10)?;
+        Self::generate_summary_for_commits(diffs, &commits, adapter, options).await
+    }
 
+    /// Like `generate_summary_with_options`, with the recent commit messages already read.
+    pub async fn generate_summary_for_commits(
+        diffs: &[UnifiedDiff],
+        commits: &[String],
+        adapter: &dyn LLMAdapter,
+        options: SummaryOptions,
+    ) -> Result<PRSummary> {
         // Analyze changes
         let stats = Self::calculate_stats(diffs);
 
         // Build prompt for AI summary
-        let prompt = Self::build_summary_prompt(diffs, &commits, &stats, &options);
+        let prompt = Self::build_summary_prompt(diffs, commits, &stats, &options);
 
         let request = LLMRequest {
             system_prompt: Self::get_system_prompt(),
             user_prompt: prompt,
             temperature: Some(0.3),
             max_tokens: Some(1000),
+            cached_context: None,
         };
 
         let response = adapter.complete(request).await?;
@@ -47,23 +73,87 @@ impl PRSummaryGenerator {
         Self::parse_summary_response(&response.content, stats)
     }
 
+    /// A Mermaid diagram of the change, of `kind` or whichever type the model finds clearer.
     pub async fn generate_change_diagram(
         diffs: &[UnifiedDiff],
         adapter: &dyn LLMAdapter,
+        kind: Option<DiagramKind>,
     ) -> Result<Option<String>> {
         let stats = Self::calculate_stats(diffs);
-        let prompt = Self::build_diagram_prompt(diffs, &stats);
+        let prompt = Self::build_diagram_prompt(diffs, &stats, kind);
         let request = LLMRequest {
             system_prompt: "You create concise Mermaid diagrams for code changes. Respond with a single mermaid diagram or 'none'.".to_string(),
             user_prompt: prompt,
             temperature: Some(0.2),
             max_tokens: Some(800),
+            cached_context: None,
         };
 
         let response = adapter.complete(request).await?;
         Ok(extract_mermaid_block(&response.content))
     }
 
+    /// One-line descriptions of what changed in each file, from a single request over all
+    /// text diffs (each cut to a few thousand characters). Files the model skips are absent.
+    pub async fn generate_file_summaries(
+        diffs: &[UnifiedDiff],
+        adapter: &dyn LLMAdapter,
+    ) -> Result<HashMap<PathBuf, String>> {
+        let files: Vec<&UnifiedDiff> = diffs
+            .iter()
+            .filter(|diff| !diff.is_binary && !diff.hunks.is_empty())
+            .take(MAX_WALKTHROUGH_FILES)
+            .collect();
+        if files.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let request = LLMRequest {
+            system_prompt: "You summarize code changes file by file. For each file, write one line: the file path, a colon, and a single sentence of at most 15 words saying what changed in it and why it matters. Do not add any other text.".to_string(),
+            user_prompt: Self::build_file_summary_prompt(&files),
+            temperature: Some(0.2),
+            max_tokens: Some(files.len() * 40 + 200),
+            cached_context: None,
+        };
+        let response = adapter.complete(request).await?;
+        Ok(parse_file_summaries(&response.content, &files))
+    }
+
+    fn build_file_summary_prompt(files: &[&UnifiedDiff]) -> String {
+        let mut prompt = String::from("Describe what changed in each of these files:\n");
+        let budget = (MAX_WALKTHROUGH_DIFF_CHARS / files.len()).min(MAX_FILE_DIFF_CHARS);
+        for diff in files {
+            prompt.push_str(&format!("\n### {}\n```diff\n", diff.file_path.display()));
+            let mut body = String::new();
+            for hunk in &diff.hunks {
+                body.push_str(&format!(
+                    "@@ -{},{} +{},{} @@\n",
+                    hunk.old_start, hunk.old_lines, hunk.new_start, hunk.new_lines
+                ));
+                for line in &hunk.changes {
+                    let prefix = match line.change_type {
+                        crate::core::diff_parser::ChangeType::Added => '+',
+                        crate::core::diff_parser::ChangeType::Removed => '-',
+                        crate::core::diff_parser::ChangeType::Context => ' ',
+                    };
+                    body.push(prefix);
+                    body.push_str(&line.content);
+                    body.push('\n');
+                }
+            }
+            if body.len() > budget {
+                let mut end = budget;
+                while !body.is_char_boundary(end) {
+                    end -= 1;
+                }
+                body.truncate(end);
+                body.push_str("\n... (truncated)\n");
+            }
+            prompt.push_str(&body);
+            prompt.push_str("```\n");
+        }
+        prompt
+    }
+
     pub fn build_diagram_only_summary(diffs: &[UnifiedDiff], diagram: String) -> PRSummary {
         let stats = Self::calculate_stats(diffs);
         PRSummary {
@@ -178,11 +268,17 @@ impl PRSummaryGenerator {
         prompt
     }
 
-    fn build_diagram_prompt(diffs: &[UnifiedDiff], stats: &ChangeStats) -> String {
+    fn build_diagram_prompt(
+        diffs: &[UnifiedDiff],
+        stats: &ChangeStats,
+        kind: Option<DiagramKind>,
+    ) -> String {
         let mut prompt = String::new();
-        prompt.push_str(
-            "Create a single Mermaid flowchart or sequence diagram that summarizes the change.\n",
-        );
+        prompt.push_str(match kind {
+            None => "Create a single Mermaid flowchart or sequence diagram that summarizes the change.\n",
+            Some(DiagramKind::Flowchart) => "Create a single Mermaid flowchart that summarizes how the changed modules and components relate.\n",
+            Some(DiagramKind::Sequence) => "Create a single Mermaid sequence diagram that summarizes the calls and messages the change adds or alters.\n",
+        });
         prompt.push_str(
             "Use only one mermaid code block. If a diagram isn't useful, reply with 'none'.\n\n",
         );
@@ -207,11 +303,13 @@ impl PRSummaryGenerator {
                 .filter(|c| matches!(c.change_type, crate::core::diff_parser::ChangeType::Removed))
                 .count();
             let status = if diff.is_deleted {
-                "deleted"
+                "deleted".to_string()
             } else if diff.is_new {
-                "new"
+                "new".to_string()
+            } else if let Some(old_path) = diff.old_path.as_ref().filter(|_| diff.is_renamed) {
+                format!("renamed from {}", old_path.display())
             } else {
-                "modified"
+                "modified".to_string()
             };
             prompt.push_str(&format!(
                 "- {} ({}; +{}, -{})\n",
@@ -463,6 +561,36 @@ fn extract_mermaid_diagram(content: &str) -> Option<String> {
     None
 }
 
+/// Reads `path: description` lines, matching paths against the requested files so list
+/// markers and backticks around them are tolerated.
+fn parse_file_summaries(content: &str, files: &[&UnifiedDiff]) -> HashMap<PathBuf, String> {
+    let mut summaries = HashMap::new();
+    for line in content.lines() {
+        let line = line
+            .trim()
+            .trim_start_matches(['-', '*'])
+            .trim_start()
+            .replace('`', "");
+        let Some((path, description)) = line.split_once(": ") else {
+            continue;
+        };
+        let path = path.trim().trim_matches('*');
+        let description = description.trim();
+        if description.is_empty() {
+            continue;
+        }
+        if let Some(diff) = files
+            .iter()
+            .find(|diff| diff.file_path.to_str() == Some(path))
+        {
+            summaries
+                .entry(diff.file_path.clone())
+                .or_insert_with(|| description.to_string());
+        }
+    }
+    summaries
+}
+
 fn extract_mermaid_block(content: &str) -> Option<String> {
     if content.to_lowercase().contains("none") {
         return None;
@@ -507,3 +635,31 @@ fn extract_mermaid_block(content: &str) -> Option<String> {
         Some(fallback)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::DiffParser;
+
+    #[test]
+    fn parses_one_line_file_summaries() {
+        let diffs = DiffParser::parse_unified_diff(
+            "--- a/src/auth.rs\n+++ b/src/auth.rs\n@@ -1 +1 @@\n-a\n+b\n--- a/src/db.rs\n+++ b/src/db.rs\n@@ -1 +1 @@\n-c\n+d\n",
+        )
+        .unwrap();
+        let files: Vec<&UnifiedDiff> = diffs.iter().collect();
+        let response = "Here you go:\n- `src/auth.rs`: Rejects expired tokens before the lookup.\nsrc/other.rs: Not part of the diff.\n**src/db.rs**: Pools connections: up to 10.";
+
+        let summaries = parse_file_summaries(response, &files);
+
+        assert_eq!(summaries.len(), 2);
+        assert_eq!(
+            summaries[&PathBuf::from("src/auth.rs")],
+            "Rejects expired tokens before the lookup."
+        );
+        assert_eq!(
+            summaries[&PathBuf::from("src/db.rs")],
+            "Pools connections: up to 10."
+        );
+    }
+}
@@ -1,6 +1,9 @@
-use crate::core::{LLMContextChunk, UnifiedDiff};
-use anyhow::Result;
+use crate::core::diff_parser::{ChangeType, DiffHunk};
+use crate::core::tokenizer::PromptBudget;
+use crate::core::{LLMContextChunk, PromptLimits, UnifiedDiff};
+use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::path::Path;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PromptConfig {
@@ -8,8 +11,41 @@ pub struct PromptConfig {
     pub user_prompt_template: String,
     pub max_tokens: usize,
     pub include_context: bool,
-    pub max_context_chars: usize,
-    pub max_diff_chars: usize,
+    pub limits: PromptLimits,
+    /// Jinja source of a `prompt_template`, rendered instead of `user_prompt_template`.
+    #[serde(default)]
+    pub custom_template: Option<String>,
+    /// The repository's review instructions, for `{{ conventions }}`.
+    #[serde(default)]
+    pub conventions: Option<String>,
+    /// The ticket the change implements, for `{{ ticket }}`.
+    #[serde(default)]
+    pub ticket: Option<String>,
+    /// Rounds and context tokens of the follow-up loop when it is enabled, since it changes
+    /// the review a prompt ends up with.
+    #[serde(default)]
+    pub follow_up: Option<(usize, usize)>,
+}
+
+impl PromptConfig {
+    /// What the prompt for a diff depends on besides the diff itself, for keying cached
+    /// reviews and grouping files into shared requests.
+    pub fn fingerprint(&self) -> String {
+        let mut fingerprint = match &self.custom_template {
+            Some(template) => format!(
+                "{}\n{}\n{}\n{}",
+                self.system_prompt,
+                template,
+                self.conventions.as_deref().unwrap_or_default(),
+                self.ticket.as_deref().unwrap_or_default()
+            ),
+            None => self.system_prompt.clone(),
+        };
+        if let Some((rounds, tokens)) = self.follow_up {
+            fingerprint.push_str(&format!("\nfollow_up:{}:{}", rounds, tokens));
+        }
+        fingerprint
+    }
 }
 
 impl Default for PromptConfig {
@@ -52,10 +88,144 @@ Line 28: Performance - O(n²) algorithm for large dataset. Will be slow with man
 </instructions>"#.to_string(),
             max_tokens: 2000,
             include_context: true,
-            max_context_chars: 20000,
-            max_diff_chars: 40000,
+            limits: PromptLimits {
+                max_context_chars: 20000,
+                max_diff_chars: 40000,
+                ..Default::default()
+            },
+            custom_template: None,
+            conventions: None,
+            ticket: None,
+            follow_up: None,
+        }
+    }
+}
+
+/// Reads the `prompt_template` at `path`, checking that it compiles.
+pub fn load_template(path: &Path) -> Result<String> {
+    let source = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read prompt template {}", path.display()))?;
+    template_env(&source).with_context(|| format!("Invalid prompt template {}", path.display()))?;
+    Ok(source)
+}
+
+fn template_env(source: &str) -> Result<minijinja::Environment<'_>> {
+    let mut env = minijinja::Environment::new();
+    env.set_trim_blocks(true);
+    env.set_lstrip_blocks(true);
+    env.add_template("prompt", source)?;
+    Ok(env)
+}
+
+/// Splits `diff` into several diffs whose hunks each fit the diff budget, so files with many
+/// hunks are reviewed across multiple requests instead of silently losing the later hunks.
+/// A single hunk larger than the budget gets a batch of its own (and is truncated there).
+pub fn split_diff_into_batches(diff: &UnifiedDiff, limits: &PromptLimits) -> Vec<UnifiedDiff> {
+    let empty_batch = || UnifiedDiff {
+        hunks: Vec::new(),
+        ..diff.clone()
+    };
+    let fresh_budget = || {
+        let mut budget = limits.diff_budget();
+        // Reserve the longest file header any prompt builder emits.
+        budget.try_add(&format!(
+            "Please review the following code changes in file: {}\n\n## Code Changes\n\n",
+            diff.file_path.display()
+        ));
+        budget
+    };
+
+    let mut batches = Vec::new();
+    let mut current = empty_batch();
+    let mut budget = fresh_budget();
+
+    for hunk in &diff.hunks {
+        let rendered = render_hunk_for_budget(hunk);
+        let mut fits = budget.try_add(&rendered);
+        if !fits && !current.hunks.is_empty() {
+            batches.push(std::mem::replace(&mut current, empty_batch()));
+            budget = fresh_budget();
+            fits = budget.try_add(&rendered);
+        }
+        current.hunks.push(hunk.clone());
+        if !fits {
+            batches.push(std::mem::replace(&mut current, empty_batch()));
+            budget = fresh_budget();
+        }
+    }
+    if !current.hunks.is_empty() || batches.is_empty() {
+        batches.push(current);
+    }
+
+    batches
+}
+
+/// Renders a hunk the way the most verbose prompt format does (numbered lines plus a
+/// section header), so batches fit whichever prompt builder consumes them.
+fn render_hunk_for_budget(hunk: &DiffHunk) -> String {
+    let mut rendered = format!(
+        "### Hunk: Lines {}-{} (was {}-{})\n\n```diff\n",
+        hunk.new_start,
+        hunk.new_start + hunk.new_lines,
+        hunk.old_start,
+        hunk.old_start + hunk.old_lines
+    );
+    let mut line_num = hunk.new_start;
+    for change in &hunk.changes {
+        let prefix = match change.change_type {
+            ChangeType::Added => "+",
+            ChangeType::Removed => "-",
+            ChangeType::Context => " ",
+        };
+        rendered.push_str(&format!("{}{:4} {}\n", prefix, line_num, change.content));
+        if change.change_type != ChangeType::Removed {
+            line_num += 1;
+        }
+    }
+    rendered.push_str("```\n\n");
+    rendered
+}
+
+/// Characters the hunks of `diff` take in a prompt, as counted against the diff budget.
+pub fn rendered_diff_len(diff: &UnifiedDiff) -> usize {
+    diff.hunks
+        .iter()
+        .map(|hunk| render_hunk_for_budget(hunk).len())
+        .sum()
+}
+
+/// Packs small files into shared requests. `files` pairs each file's system prompt with its
+/// diff; a file only joins files with the same system prompt, at most `max_files` per request
+/// and with their diffs together inside the diff budget. Returns indexes into `files`, with
+/// requests ordered by their first file.
+pub fn pack_small_files(
+    files: &[(&str, &UnifiedDiff)],
+    limits: &PromptLimits,
+    max_files: usize,
+) -> Vec<Vec<usize>> {
+    let mut open: Vec<(&str, PromptBudget, Vec<usize>)> = Vec::new();
+    let mut packed = Vec::new();
+    for (index, &(system_prompt, diff)) in files.iter().enumerate() {
+        let mut rendered = format!("File: {}\n", diff.file_path.display());
+        rendered.extend(diff.hunks.iter().map(render_hunk_for_budget));
+        if let Some(slot) = open
+            .iter()
+            .position(|(prompt, _, _)| *prompt == system_prompt)
+        {
+            let (_, budget, members) = &mut open[slot];
+            if members.len() < max_files && budget.try_add(&rendered) {
+                members.push(index);
+                continue;
+            }
+            packed.push(open.remove(slot).2);
         }
+        let mut budget = limits.diff_budget();
+        budget.try_add(&rendered);
+        open.push((system_prompt, budget, vec![index]));
     }
+    packed.extend(open.into_iter().map(|(_, _, members)| members));
+    packed.sort_by_key(|members| members[0]);
+    packed
 }
 
 pub struct PromptBuilder {
@@ -79,25 +249,117 @@ impl PromptBuilder {
             String::new()
         };
 
-        let user_prompt = self
-            .config
-            .user_prompt_template
-            .replace("{diff}", &diff_text)
-            .replace("{context}", &context_text);
+        let user_prompt = self.render_user_prompt(&diff_text, &context_text, &[diff])?;
+
+        Ok((self.config.system_prompt.clone(), user_prompt))
+    }
+
+    /// One prompt for several small files: each file's diff under its `File:` header, the
+    /// context of all of them, and a request to group findings under `File: <path>` lines so
+    /// `parse_multi_file_response` can hand them back to their files.
+    pub fn build_multi_file_prompt(
+        &self,
+        files: &[(&UnifiedDiff, &[LLMContextChunk])],
+    ) -> Result<(String, String)> {
+        let mut diff_text = String::new();
+        let mut context_chunks: Vec<LLMContextChunk> = Vec::new();
+        for (diff, chunks) in files {
+            if !diff_text.is_empty() {
+                diff_text.push('\n');
+            }
+            diff_text.push_str(&self.format_diff(diff)?);
+            for chunk in chunks.iter() {
+                let known = context_chunks.iter().any(|known| {
+                    known.file_path == chunk.file_path
+                        && known.line_range == chunk.line_range
+                        && known.content == chunk.content
+                });
+                if !known {
+                    context_chunks.push(chunk.clone());
+                }
+            }
+        }
+        let context_text = if self.config.include_context {
+            self.format_context(&context_chunks)?
+        } else {
+            String::new()
+        };
+
+        let paths: Vec<String> = files
+            .iter()
+            .map(|(diff, _)| diff.file_path.display().to_string())
+            .collect();
+        let diffs: Vec<&UnifiedDiff> = files.iter().map(|(diff, _)| *diff).collect();
+        let mut user_prompt = self.render_user_prompt(&diff_text, &context_text, &diffs)?;
+        user_prompt.push_str(&format!(
+            "\n\n<files>\nThe diff covers {} files: {}. Group your findings by file: write `File: <path>` on its own line before each file's issues, with line numbers from that file. Leave out files without issues.\n</files>",
+            paths.len(),
+            paths.join(", ")
+        ));
 
         Ok((self.config.system_prompt.clone(), user_prompt))
     }
 
+    /// Fills the `prompt_template`, or the built-in template when there is none. Several
+    /// `diffs` (a shared request) list their paths and languages comma-separated.
+    fn render_user_prompt(
+        &self,
+        diff_text: &str,
+        context_text: &str,
+        diffs: &[&UnifiedDiff],
+    ) -> Result<String> {
+        let Some(template) = &self.config.custom_template else {
+            return Ok(self
+                .config
+                .user_prompt_template
+                .replace("{diff}", diff_text)
+                .replace("{context}", context_text));
+        };
+        let mut languages: Vec<&str> = Vec::new();
+        for language in diffs
+            .iter()
+            .filter_map(|diff| crate::core::languages::detect(&diff.file_path))
+        {
+            if !languages.contains(&language) {
+                languages.push(language);
+            }
+        }
+        let file_path = diffs
+            .iter()
+            .map(|diff| diff.file_path.display().to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let env = template_env(template)?;
+        env.get_template("prompt")?
+            .render(minijinja::context! {
+                diff => diff_text,
+                context => context_text,
+                file_path => file_path,
+                file_language => languages.join(", "),
+                conventions => self.config.conventions.as_deref().unwrap_or_default(),
+                ticket => self.config.ticket.as_deref().unwrap_or_default(),
+            })
+            .context("Failed to render prompt template")
+    }
+
     fn format_diff(&self, diff: &UnifiedDiff) -> Result<String> {
         let mut output = String::new();
         let mut truncated = false;
-        output.push_str(&format!("File: {}\n", diff.file_path.display()));
+        let mut budget = self.config.limits.diff_budget();
+        let file_header = match diff.old_path.as_ref().filter(|_| diff.is_renamed) {
+            Some(old_path) => format!(
+                "File: {} (renamed from {})\n",
+                diff.file_path.display(),
+                old_path.display()
+            ),
+            None => format!("File: {}\n", diff.file_path.display()),
+        };
+        budget.try_add(&file_header);
+        output.push_str(&file_header);
 
         'hunks: for hunk in &diff.hunks {
             let header = format!("{}\n", hunk.context);
-            if self.config.max_diff_chars > 0
-                && output.len().saturating_add(header.len()) > self.config.max_diff_chars
-            {
+            if !budget.try_add(&header) {
                 truncated = true;
                 break;
             }
@@ -110,9 +372,7 @@ impl PromptBuilder {
                     crate::core::diff_parser::ChangeType::Context => " ",
                 };
                 let line = format!("{}{}\n", prefix, change.content);
-                if self.config.max_diff_chars > 0
-                    && output.len().saturating_add(line.len()) > self.config.max_diff_chars
-                {
+                if !budget.try_add(&line) {
                     truncated = true;
                     break 'hunks;
                 }
@@ -127,8 +387,9 @@ impl PromptBuilder {
         Ok(output)
     }
 
-    fn format_context(&self, chunks: &[LLMContextChunk]) -> Result<String> {
+    pub fn format_context(&self, chunks: &[LLMContextChunk]) -> Result<String> {
         let mut output = String::new();
+        let mut budget = self.config.limits.context_budget();
 
         for chunk in chunks {
             let block = format!(
@@ -141,9 +402,7 @@ impl PromptBuilder {
                     .unwrap_or_default(),
                 chunk.content
             );
-            if self.config.max_context_chars > 0
-                && output.len().saturating_add(block.len()) > self.config.max_context_chars
-            {
+            if !budget.try_add(&block) {
                 output.push_str("\n[Context truncated]\n");
                 break;
             }
@@ -153,3 +412,116 @@ impl PromptBuilder {
         Ok(output)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::DiffParser;
+
+    #[test]
+    fn splits_hunks_into_batches_that_fit_the_diff_budget() {
+        let mut diff_text = String::from("--- a/src/lib.rs\n+++ b/src/lib.rs\n");
+        for i in 0..6 {
+            let start = i * 100 + 1;
+            diff_text.push_str(&format!(
+                "@@ -{start},2 +{start},2 @@\n context line {i}\n-let value = old_call({i});\n+let value = new_call({i});\n"
+            ));
+        }
+        let diff = DiffParser::parse_unified_diff(&diff_text)
+            .unwrap()
+            .remove(0);
+
+        let unlimited = PromptLimits::default();
+        assert_eq!(split_diff_into_batches(&diff, &unlimited).len(), 1);
+
+        let limits = PromptLimits {
+            max_diff_chars: 400,
+            ..Default::default()
+        };
+        let batches = split_diff_into_batches(&diff, &limits);
+        assert!(batches.len() > 1);
+        assert_eq!(batches.iter().map(|b| b.hunks.len()).sum::<usize>(), 6);
+        assert!(batches.iter().all(|b| b.file_path == diff.file_path));
+    }
+
+    #[test]
+    fn packs_small_files_sharing_a_system_prompt() {
+        let diff = |path: &str| {
+            DiffParser::parse_unified_diff(&format!(
+                "--- a/{path}\n+++ b/{path}\n@@ -1 +1 @@\n-old\n+new\n"
+            ))
+            .unwrap()
+            .remove(0)
+        };
+        let diffs: Vec<UnifiedDiff> = ["a.rs", "b.rs", "c.rs", "d.rs", "e.rs"]
+            .into_iter()
+            .map(diff)
+            .collect();
+        let files = [
+            ("review", &diffs[0]),
+            ("migrations", &diffs[1]),
+            ("review", &diffs[2]),
+            ("review", &diffs[3]),
+            ("migrations", &diffs[4]),
+        ];
+
+        let limits = PromptLimits::default();
+        assert_eq!(
+            pack_small_files(&files, &limits, 8),
+            vec![vec![0, 2, 3], vec![1, 4]]
+        );
+        assert_eq!(
+            pack_small_files(&files, &limits, 2),
+            vec![vec![0, 2], vec![1, 4], vec![3]]
+        );
+        let tight = PromptLimits {
+            max_diff_chars: rendered_diff_len(&diffs[0]) + 20,
+            ..Default::default()
+        };
+        assert_eq!(pack_small_files(&files, &tight, 8).len(), 5);
+    }
+
+    #[test]
+    fn renders_prompt_templates_with_file_variables() {
+        let diff = DiffParser::parse_unified_diff(
+            "--- a/src/app.py\n+++ b/src/app.py\n@@ -1 +1 @@\n-x = 1\n+x = 2\n",
+        )
+        .unwrap()
+        .remove(0);
+        let template = "Review this {{ file_language }} change to {{ file_path }}.\n{% if ticket %}\nTicket: {{ ticket }}\n{% endif %}\nConventions: {{ conventions }}\n{{ diff }}";
+        let config = PromptConfig {
+            custom_template: Some(template.to_string()),
+            conventions: Some("Use type hints.".to_string()),
+            ticket: Some("SHOP-12: Fix totals".to_string()),
+            ..Default::default()
+        };
+
+        let (_, user_prompt) = PromptBuilder::new(config.clone())
+            .build_prompt(&diff, &[])
+            .unwrap();
+        assert_eq!(
+            user_prompt,
+            "Review this python change to src/app.py.\nTicket: SHOP-12: Fix totals\nConventions: Use type hints.\nFile: src/app.py\n@@ -1 +1 @@\n-x = 1\n+x = 2\n"
+        );
+        assert_ne!(config.fingerprint(), PromptConfig::default().fingerprint());
+        let follow_up = PromptConfig {
+            follow_up: Some((2, 8000)),
+            ..Default::default()
+        };
+        assert_ne!(
+            follow_up.fingerprint(),
+            PromptConfig::default().fingerprint()
+        );
+
+        // The shipped example must keep rendering.
+        let example =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("examples/templates/review-prompt.md.j2");
+        let config = PromptConfig {
+            custom_template: Some(load_template(&example).unwrap()),
+            ..config
+        };
+        let (_, user_prompt) = PromptBuilder::new(config).build_prompt(&diff, &[]).unwrap();
+        assert!(user_prompt.contains("<ticket>\nSHOP-12: Fix totals\n</ticket>"));
+        assert!(user_prompt.contains("python changes to src/app.py"));
+    }
+}
@@ -0,0 +1,228 @@
+use crate::core::rule_pack::{cache_path, is_fresh};
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::warn;
+
+/// How long an unpinned remote config is reused before it is fetched again.
+pub const CACHE_TTL: Duration = Duration::from_secs(3600);
+
+const GITHUB_RAW_URL: &str = "https://raw.githubusercontent.com";
+const GITHUB_RAW_HOST: &str = "raw.githubusercontent.com";
+
+pub fn default_cache_dir() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("diffscope")
+        .join("config")
+}
+
+/// The URL `source` names when it is remote: an http(s) URL, or GitHub shorthand
+/// `owner/repo@ref` (that repository's `.diffscope.yml`) or `owner/repo/path/file.yml@ref`.
+/// Local paths return `None`.
+pub fn remote_url(source: &str) -> Option<String> {
+    if source.starts_with("https://") || source.starts_with("http://") {
+        return Some(source.to_string());
+    }
+    if source.contains("://") || source.starts_with(['.', '/', '~']) {
+        return None;
+    }
+    let (spec, reference) = source.rsplit_once('@')?;
+    let mut parts = spec.splitn(3, '/');
+    let (owner, repo) = (parts.next()?, parts.next()?);
+    let path = parts.next().unwrap_or(".diffscope.yml");
+    if [owner, repo, path, reference]
+        .iter()
+        .any(|part| part.is_empty())
+    {
+        return None;
+    }
+    Some(format!(
+        "{}/{}/{}/{}/{}",
+        GITHUB_RAW_URL, owner, repo, reference, path
+    ))
+}
+
+/// `relative` resolved against the directory of remote config `base`.
+pub fn join_url(base: &str, relative: &str) -> String {
+    let dir = base.rfind('/').map_or(base, |end| &base[..=end]);
+    format!("{}{}", dir, relative.trim_start_matches("./"))
+}
+
+fn sha256_hex(content: &str) -> String {
+    ring::digest::digest(&ring::digest::SHA256, content.as_bytes())
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+fn verify(url: &str, content: &str, sha256: Option<&str>) -> Result<()> {
+    match sha256 {
+        Some(expected) if !sha256_hex(content).eq_ignore_ascii_case(expected.trim()) => {
+            anyhow::bail!(
+                "{} does not match its pinned sha256 {} (got {})",
+                url,
+                expected,
+                sha256_hex(content)
+            )
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Plain-http configs can be rewritten in transit, so they are only accepted with a pin.
+fn check_scheme(url: &str, sha256: Option<&str>) -> Result<()> {
+    let parsed = reqwest::Url::parse(url).with_context(|| format!("Invalid config URL {}", url))?;
+    match parsed.scheme() {
+        "https" => Ok(()),
+        "http" if sha256.is_some() => Ok(()),
+        "http" => anyhow::bail!("{} uses plain http; use https or pin it with sha256", url),
+        scheme => anyhow::bail!("{} uses unsupported scheme {}", url, scheme),
+    }
+}
+
+/// The GitHub token to send with a request for `url`: only for https requests to GitHub's
+/// raw content host itself.
+fn github_token_for(url: &str) -> Option<String> {
+    let parsed = reqwest::Url::parse(url).ok()?;
+    if parsed.scheme() != "https" || parsed.host_str() != Some(GITHUB_RAW_HOST) {
+        return None;
+    }
+    std::env::var("GITHUB_TOKEN")
+        .or_else(|_| std::env::var("GH_TOKEN"))
+        .ok()
+}
+
+/// The config at `url`, through the cache. A `sha256` pin must match the content, and a
+/// cached copy that matches it is used without a request. Unpinned configs are reused for
+/// `ttl`, and a stale copy stands in when the server cannot be reached.
+pub fn load(url: &str, sha256: Option<&str>, cache_dir: &Path, ttl: Duration) -> Result<String> {
+    check_scheme(url, sha256)?;
+    let cached = cache_path(cache_dir, url);
+    let cached_content = std::fs::read_to_string(&cached).ok();
+    if let Some(content) = &cached_content {
+        let reusable = match sha256 {
+            Some(_) => verify(url, content, sha256).is_ok(),
+            None => is_fresh(&cached, ttl),
+        };
+        if reusable {
+            return Ok(content.clone());
+        }
+    }
+
+    match fetch(url) {
+        Ok(content) => {
+            verify(url, &content, sha256)?;
+            std::fs::create_dir_all(cache_dir)?;
+            std::fs::write(&cached, &content)?;
+            Ok(content)
+        }
+        Err(err) => match cached_content {
+            Some(content) if sha256.is_none() => {
+                warn!("Using cached config {}: {:#}", url, err);
+                Ok(content)
+            }
+            _ => Err(err),
+        },
+    }
+}
+
+/// Fetches on a thread of its own: config loading is synchronous and may already be running
+/// inside the main runtime.
+fn fetch(url: &str) -> Result<String> {
+    let url = url.to_string();
+    let token = github_token_for(&url);
+    std::thread::spawn(move || -> Result<String> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        runtime.block_on(async {
+            let mut request = reqwest::Client::builder()
+                .timeout(Duration::from_secs(20))
+                .build()?
+                .get(&url);
+            if let Some(token) = token {
+                request = request.bearer_auth(token);
+            }
+            let response = request
+                .send()
+                .await
+                .with_context(|| format!("Failed to fetch config {}", url))?
+                .error_for_status()
+                .with_context(|| format!("Failed to fetch config {}", url))?;
+            Ok(response.text().await?)
+        })
+    })
+    .join()
+    .map_err(|_| anyhow::anyhow!("config fetch thread panicked"))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_sources_and_caches_pinned_configs() {
+        assert_eq!(
+            remote_url("acme/platform@v3").as_deref(),
+            Some("https://raw.githubusercontent.com/acme/platform/v3/.diffscope.yml")
+        );
+        assert_eq!(
+            remote_url("acme/platform/review/base.yml@main").as_deref(),
+            Some("https://raw.githubusercontent.com/acme/platform/main/review/base.yml")
+        );
+        assert_eq!(remote_url("configs/base.yml"), None);
+        assert_eq!(remote_url("./team@2024.yml"), None);
+        assert_eq!(
+            join_url("https://example.com/org/base.yml", "./security.yml"),
+            "https://example.com/org/security.yml"
+        );
+
+        let body = "model: gpt-4o\nmin_confidence: 0.4\n";
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("GET", "/org-diffscope.yml")
+            .with_body(body)
+            .expect(1)
+            .create();
+        let url = format!("{}/org-diffscope.yml", server.url());
+        let cache = tempfile::tempdir().unwrap();
+        let pin = sha256_hex(body);
+
+        assert_eq!(
+            load(&url, Some(&pin), cache.path(), CACHE_TTL).unwrap(),
+            body
+        );
+        // The pinned copy in the cache is reused even with no time-to-live.
+        assert_eq!(
+            load(&url, Some(&pin), cache.path(), Duration::ZERO).unwrap(),
+            body
+        );
+        mock.assert();
+
+        let err = load(&url, None, cache.path(), CACHE_TTL).unwrap_err();
+        assert!(err.to_string().contains("plain http"));
+
+        std::env::set_var("GITHUB_TOKEN", "ci-token");
+        assert_eq!(
+            github_token_for("https://raw.githubusercontent.com/acme/platform/v3/.diffscope.yml")
+                .as_deref(),
+            Some("ci-token")
+        );
+        assert_eq!(
+            github_token_for("https://raw.githubusercontent.com.attacker.net/acme/x.yml"),
+            None
+        );
+        assert_eq!(
+            github_token_for("http://raw.githubusercontent.com/acme/platform/v3/x.yml"),
+            None
+        );
+
+        let other_cache = tempfile::tempdir().unwrap();
+        let err = load(&url, Some("00ff"), other_cache.path(), CACHE_TTL).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("does not match its pinned sha256 00ff"));
+    }
+}
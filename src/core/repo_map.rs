@@ -0,0 +1,202 @@
+use crate::core::SymbolIndex;
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+
+/// Symbols listed per file; the rest are counted.
+const MAX_SYMBOLS_PER_FILE: usize = 8;
+
+/// A compact outline of the repository (directories, files, and the symbols each file
+/// defines) so the model sees the project's layout and naming even for an isolated change.
+#[derive(Debug, Default)]
+pub struct RepoMap {
+    /// Files in path order, each with its symbols in line order.
+    files: Vec<(PathBuf, Vec<String>)>,
+}
+
+impl RepoMap {
+    pub fn from_index(index: &SymbolIndex) -> Self {
+        let mut by_file: BTreeMap<PathBuf, Vec<(usize, String)>> = BTreeMap::new();
+        for (name, locations) in index.symbols() {
+            for location in locations {
+                by_file
+                    .entry(location.file_path.clone())
+                    .or_default()
+                    .push((location.line_range.0, name.to_string()));
+            }
+        }
+        let files = by_file
+            .into_iter()
+            .map(|(path, mut symbols)| {
+                symbols.sort();
+                // Methods are indexed both bare and as `Type::method`; list them once.
+                let qualified: Vec<(usize, String)> = symbols
+                    .iter()
+                    .filter(|(_, name)| name.contains("::"))
+                    .cloned()
+                    .collect();
+                let mut names: Vec<String> = Vec::new();
+                for (line, name) in symbols {
+                    let shadowed = qualified.iter().any(|(other_line, other)| {
+                        *other_line == line && other.ends_with(&format!("::{}", name))
+                    });
+                    if !shadowed && !names.contains(&name) {
+                        names.push(name);
+                    }
+                }
+                (path, names)
+            })
+            .collect();
+        Self { files }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// The map within about `max_chars`, keeping the files nearest the directories all of
+    /// `changed` share when not everything fits.
+    pub fn render(&self, changed: &[&Path], max_chars: usize) -> String {
+        let focus_dirs = changed
+            .iter()
+            .map(|path| directories(path))
+            .reduce(|shared, dirs| {
+                let common = shared.iter().zip(&dirs).take_while(|(a, b)| a == b).count();
+                dirs[..common].to_vec()
+            })
+            .unwrap_or_default();
+        let mut ranked: Vec<usize> = (0..self.files.len()).collect();
+        ranked.sort_by_key(|&index| {
+            let dirs = directories(&self.files[index].0);
+            let shared = dirs
+                .iter()
+                .zip(&focus_dirs)
+                .take_while(|(a, b)| a == b)
+                .count();
+            (std::cmp::Reverse(shared), dirs.len())
+        });
+
+        let mut chosen = Vec::new();
+        let mut used = 0;
+        for index in ranked {
+            let cost = file_line(&self.files[index]).len() + self.files[index].0.as_os_str().len();
+            if used + cost > max_chars {
+                continue;
+            }
+            used += cost;
+            chosen.push(index);
+        }
+        chosen.sort_unstable();
+
+        let mut output = String::new();
+        let mut open_dirs: Vec<String> = Vec::new();
+        for &index in &chosen {
+            let (path, _) = &self.files[index];
+            let dirs = directories(path);
+            let shared = open_dirs
+                .iter()
+                .zip(&dirs)
+                .take_while(|(a, b)| a == b)
+                .count();
+            for (depth, dir) in dirs.iter().enumerate().skip(shared) {
+                output.push_str(&format!("{}{}/\n", "  ".repeat(depth), dir));
+            }
+            output.push_str(&format!(
+                "{}{}\n",
+                "  ".repeat(dirs.len()),
+                file_line(&self.files[index])
+            ));
+            open_dirs = dirs;
+        }
+        let omitted = self.files.len() - chosen.len();
+        if omitted > 0 {
+            output.push_str(&format!("... {} more files\n", omitted));
+        }
+        output
+    }
+
+    /// The map as context shared by every request of a review of `changed`, rendered once so
+    /// providers with prompt caching can reuse it across files.
+    pub fn shared_context(&self, changed: &[&Path], max_chars: usize) -> Option<String> {
+        if self.is_empty() || max_chars == 0 {
+            return None;
+        }
+        Some(format!(
+            "## Repository Map\n\nFiles in this repository and the symbols they define:\n{}",
+            self.render(changed, max_chars)
+        ))
+    }
+}
+
+fn directories(path: &Path) -> Vec<String> {
+    let mut dirs: Vec<String> = path
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(part.to_string_lossy().into_owned()),
+            _ => None,
+        })
+        .collect();
+    dirs.pop();
+    dirs
+}
+
+fn file_line((path, symbols): &(PathBuf, Vec<String>)) -> String {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mut line = format!(
+        "{}: {}",
+        name,
+        symbols
+            .iter()
+            .take(MAX_SYMBOLS_PER_FILE)
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    if symbols.len() > MAX_SYMBOLS_PER_FILE {
+        line.push_str(&format!(
+            " (+{} more)",
+            symbols.len() - MAX_SYMBOLS_PER_FILE
+        ));
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_a_tree_nearest_the_focus_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for (path, content) in [
+            ("src/lib.rs", "pub struct Engine;\npub fn review() {}\n"),
+            (
+                "src/core/cache.rs",
+                "pub struct ReviewCache;\nimpl ReviewCache {\n    fn key() {}\n}\n",
+            ),
+            ("src/core/prompt.rs", "pub struct PromptBuilder;\n"),
+            ("tools/gen.py", "def generate():\n    pass\n"),
+        ] {
+            std::fs::create_dir_all(root.join(path).parent().unwrap()).unwrap();
+            std::fs::write(root.join(path), content).unwrap();
+        }
+        let index = SymbolIndex::build(root, 100, 100_000, 5, |_| false).unwrap();
+        let map = RepoMap::from_index(&index);
+
+        assert_eq!(
+            map.render(&[Path::new("src/core/new.rs")], 10_000),
+            "src/\n  core/\n    cache.rs: ReviewCache, ReviewCache::key\n    prompt.rs: PromptBuilder\n  lib.rs: Engine, review\ntools/\n  gen.py: generate\n"
+        );
+        assert_eq!(
+            map.render(&[Path::new("tools/other.py")], 50),
+            "tools/\n  gen.py: generate\n... 3 more files\n"
+        );
+        assert_eq!(
+            map.render(&[Path::new("src/core/new.rs"), Path::new("src/lib.rs")], 50),
+            "src/\n  lib.rs: Engine, review\n... 3 more files\n"
+        );
+    }
+}
@@ -0,0 +1,103 @@
+use crate::core::comment::{Comment, ReviewSummary, SkippedFile};
+use crate::core::risk::RiskReport;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Values available to a `report_template`.
+#[derive(Debug, Serialize)]
+pub struct ReportContext<'a> {
+    /// `"review"` or `"smart_review"`, for templates shared by both commands.
+    pub kind: &'static str,
+    pub comments: &'a [Comment],
+    pub summary: &'a ReviewSummary,
+    /// Findings grouped by file path, in path order.
+    pub files: Vec<FileFindings<'a>>,
+    /// Smart review only: rendered PR summary, change walkthrough, impact, and coverage sections.
+    pub pr_summary: Option<String>,
+    pub walkthrough: &'a str,
+    pub impact: &'a str,
+    pub coverage: &'a str,
+    /// Smart review only: per-file and overall risk scores.
+    pub risk: Option<&'a RiskReport>,
+    /// Files left unreviewed because the model request failed.
+    pub skipped: &'a [SkippedFile],
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileFindings<'a> {
+    pub path: String,
+    pub comments: Vec<&'a Comment>,
+}
+
+impl<'a> ReportContext<'a> {
+    pub fn new(kind: &'static str, comments: &'a [Comment], summary: &'a ReviewSummary) -> Self {
+        let mut by_file: BTreeMap<String, Vec<&Comment>> = BTreeMap::new();
+        for comment in comments {
+            by_file
+                .entry(comment.file_path.display().to_string())
+                .or_default()
+                .push(comment);
+        }
+        Self {
+            kind,
+            comments,
+            summary,
+            files: by_file
+                .into_iter()
+                .map(|(path, comments)| FileFindings { path, comments })
+                .collect(),
+            pr_summary: None,
+            walkthrough: "",
+            impact: "",
+            coverage: "",
+            risk: None,
+            skipped: &[],
+        }
+    }
+}
+
+/// Renders the Jinja template at `path` (minijinja syntax) with `context`.
+pub fn render<T: Serialize>(path: &Path, context: &T) -> Result<String> {
+    let source = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read template {}", path.display()))?;
+    let mut env = minijinja::Environment::new();
+    env.set_trim_blocks(true);
+    env.set_lstrip_blocks(true);
+    env.add_template("report", &source)
+        .with_context(|| format!("Invalid template {}", path.display()))?;
+    env.get_template("report")?
+        .render(context)
+        .with_context(|| format!("Failed to render template {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::comment::{test_comment, CommentSynthesizer};
+
+    #[test]
+    fn renders_user_template_with_grouped_findings() {
+        let comments = vec![test_comment("src/lib.rs", 9, "Missing error handling")];
+        let summary = CommentSynthesizer::generate_summary(&comments);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("report.md.j2");
+        std::fs::write(
+            &path,
+            "# ACME Review ({{ summary.total_comments }})\n{% for file in files %}\n## {{ file.path }}\n{% for c in file.comments %}\n- L{{ c.line_number }} [{{ c.severity }}] {{ c.content }}\n{% endfor %}\n{% endfor %}\n",
+        )
+        .unwrap();
+
+        let output = render(&path, &ReportContext::new("review", &comments, &summary)).unwrap();
+        assert_eq!(
+            output,
+            "# ACME Review (1)\n## src/lib.rs\n- L9 [Warning] Missing error handling\n"
+        );
+
+        // The shipped example must keep rendering.
+        let example = Path::new(env!("CARGO_MANIFEST_DIR")).join("examples/templates/report.md.j2");
+        let output = render(&example, &ReportContext::new("review", &comments, &summary)).unwrap();
+        assert!(output.contains("## src/lib.rs\n\n- Line 9 (Warning, Bug): Missing error handling"));
+    }
+}
@@ -0,0 +1,124 @@
+/// A `review_profile` that configures a whole review for one domain. Its settings are merged
+/// beneath the configuration files, so anything set there still wins.
+pub struct ReviewPreset {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// YAML settings: system prompt, category weights, analyzers, and smart-review sections.
+    pub settings: &'static str,
+}
+
+/// The tone-only profiles, which add a line of guidance to the default prompt.
+pub const TONE_PROFILES: &[&str] = &["balanced", "chill", "assertive"];
+
+pub const PRESETS: &[ReviewPreset] = &[
+    ReviewPreset {
+        name: "security-audit",
+        description: "Vulnerabilities, secrets, and unsafe input handling; style and docs muted",
+        settings: r#"
+system_prompt: |
+  You are a security auditor reviewing code changes. Look for injection (SQL, command, template, path traversal), broken authentication or authorization, secrets and credentials in code or logs, unsafe deserialization, SSRF, weak or misused cryptography, missing input validation, and data exposure. Explain how each issue could be exploited. Ignore style and formatting.
+category_weights:
+  style: 0.3
+  documentation: 0.3
+  maintainability: 0.6
+  bestpractice: 0.6
+plugins:
+  secrets: true
+  secret_scan: true
+  semgrep: true
+  dependency_audit: true
+  iac: true
+  spelling: false
+  doc_drift: false
+  complexity: false
+  missing_tests: false
+smart_review_sections: [risk, summary, breaking_changes, impact, actions, breakdown]
+"#,
+    },
+    ReviewPreset {
+        name: "performance",
+        description: "Algorithmic cost, allocations, queries, and blocking calls",
+        settings: r#"
+system_prompt: |
+  You are a performance engineer reviewing code changes. Look for algorithmic complexity regressions, work repeated inside loops, N+1 queries and missing indexes, unnecessary allocations and copies, blocking I/O on async or hot paths, lock contention, unbounded caches or buffers, and missing pagination or batching. Estimate the impact where you can. Ignore style and formatting.
+category_weights:
+  style: 0.3
+  documentation: 0.3
+  testing: 0.6
+plugins:
+  complexity: true
+  spelling: false
+  doc_drift: false
+  missing_tests: false
+smart_review_sections: [summary, impact, walkthrough, actions, breakdown]
+"#,
+    },
+    ReviewPreset {
+        name: "api-compat",
+        description: "Breaking changes to public APIs, wire formats, and defaults",
+        settings: r#"
+system_prompt: |
+  You are reviewing code changes for API compatibility. Look for removed or renamed public items, changed signatures, return types, or error types, changed defaults, serialization or wire-format changes, altered HTTP routes or status codes, and behavior changes existing callers would not expect. Say which callers break and whether a deprecation path exists.
+category_weights:
+  style: 0.3
+  performance: 0.7
+plugins:
+  breaking_changes: true
+  dependency_impact: true
+  doc_drift: true
+  spelling: false
+  complexity: false
+smart_review_sections: [breaking_changes, impact, summary, walkthrough, actions, breakdown]
+"#,
+    },
+    ReviewPreset {
+        name: "dependency-bump",
+        description: "Dependency upgrades: advisories, major versions, and adapted call sites",
+        settings: r#"
+system_prompt: |
+  You are reviewing a dependency upgrade. Look for known vulnerabilities, major version bumps and their breaking changes, new transitive dependencies, license changes, and code that was or still needs to be adapted to changed APIs. Treat lockfile noise as expected.
+category_weights:
+  style: 0.2
+  documentation: 0.3
+  maintainability: 0.6
+plugins:
+  dependency_audit: true
+  dependency_impact: true
+  breaking_changes: true
+  spelling: false
+  doc_drift: false
+  complexity: false
+  missing_tests: false
+smart_review_sections: [summary, impact, breaking_changes, walkthrough, actions]
+"#,
+    },
+];
+
+pub fn find(name: &str) -> Option<&'static ReviewPreset> {
+    PRESETS.iter().find(|preset| preset.name == name)
+}
+
+/// Every valid `review_profile`.
+pub fn profile_names() -> Vec<&'static str> {
+    TONE_PROFILES
+        .iter()
+        .copied()
+        .chain(PRESETS.iter().map(|preset| preset.name))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets_are_valid_settings() {
+        for preset in PRESETS {
+            let issues = crate::config::validate(preset.settings);
+            assert!(issues.is_empty(), "{}: {:?}", preset.name, issues);
+            let config: crate::config::Config = serde_yaml::from_str(preset.settings).unwrap();
+            assert!(config.system_prompt.is_some(), "{}", preset.name);
+            assert!(!config.category_weights.is_empty(), "{}", preset.name);
+        }
+    }
+}
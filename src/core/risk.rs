@@ -0,0 +1,277 @@
+use crate::core::comment::{Comment, Severity};
+use crate::core::diff_parser::ChangeType;
+use crate::core::{CoverageReport, GitIntegration, UnifiedDiff};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// History counted as recent churn.
+pub const CHURN_WINDOW_DAYS: i64 = 90;
+
+/// Commits walked when counting churn.
+const MAX_CHURN_COMMITS: usize = 2000;
+
+/// Each factor's weight in a file's score.
+const SIZE_WEIGHT: f64 = 0.25;
+const CHURN_WEIGHT: f64 = 0.2;
+const COMPLEXITY_WEIGHT: f64 = 0.2;
+const COVERAGE_WEIGHT: f64 = 0.15;
+const FINDINGS_WEIGHT: f64 = 0.2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RiskLevel {
+    Low,
+    Medium,
+    High,
+}
+
+impl RiskLevel {
+    fn of(score: u32) -> Self {
+        match score {
+            0..=29 => RiskLevel::Low,
+            30..=59 => RiskLevel::Medium,
+            _ => RiskLevel::High,
+        }
+    }
+}
+
+/// What a file's risk score is made of.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RiskFactors {
+    /// Added plus removed lines.
+    pub lines_changed: usize,
+    /// Commits touching the file in the last `CHURN_WINDOW_DAYS` days.
+    pub recent_commits: usize,
+    /// Change in summed cyclomatic complexity; `None` for languages without a grammar.
+    pub complexity_delta: Option<i64>,
+    /// Share of added lines covered by tests; `None` without coverage data.
+    pub patch_coverage: Option<f64>,
+    pub errors: usize,
+    pub warnings: usize,
+}
+
+impl RiskFactors {
+    /// 0-100. Each factor saturates, so one huge value cannot dominate, and unknown
+    /// complexity or coverage leaves its weight out rather than guessing.
+    pub fn score(&self) -> u32 {
+        // x / (x + half): 0 at 0, 0.5 at `half`, approaching 1.
+        let saturate = |value: f64, half: f64| value / (value + half);
+        let mut weighted = vec![
+            (SIZE_WEIGHT, saturate(self.lines_changed as f64, 150.0)),
+            (CHURN_WEIGHT, saturate(self.recent_commits as f64, 8.0)),
+            (
+                FINDINGS_WEIGHT,
+                saturate((3 * self.errors + self.warnings) as f64, 3.0),
+            ),
+        ];
+        if let Some(delta) = self.complexity_delta {
+            weighted.push((COMPLEXITY_WEIGHT, saturate(delta.max(0) as f64, 8.0)));
+        }
+        if let Some(coverage) = self.patch_coverage {
+            weighted.push((COVERAGE_WEIGHT, 1.0 - coverage.clamp(0.0, 1.0)));
+        }
+        let total: f64 = weighted.iter().map(|(weight, _)| weight).sum();
+        let score: f64 = weighted.iter().map(|(weight, value)| weight * value).sum();
+        (score / total * 100.0).round() as u32
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileRisk {
+    pub path: PathBuf,
+    pub score: u32,
+    pub level: RiskLevel,
+    pub factors: RiskFactors,
+}
+
+/// Risk of a whole change: `files` riskiest first, and a score blending the riskiest file
+/// with the average weighted by lines changed.
+#[derive(Debug, Clone, Serialize)]
+pub struct RiskReport {
+    pub score: u32,
+    pub level: RiskLevel,
+    pub files: Vec<FileRisk>,
+}
+
+impl RiskReport {
+    pub fn from_factors(factors: Vec<(PathBuf, RiskFactors)>) -> Self {
+        let mut files: Vec<FileRisk> = factors
+            .into_iter()
+            .map(|(path, factors)| {
+                let score = factors.score();
+                FileRisk {
+                    path,
+                    score,
+                    level: RiskLevel::of(score),
+                    factors,
+                }
+            })
+            .collect();
+        files.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.path.cmp(&b.path)));
+
+        let max = files.first().map_or(0, |file| file.score);
+        let lines: usize = files.iter().map(|file| file.factors.lines_changed).sum();
+        let average = if lines == 0 {
+            max as f64
+        } else {
+            files
+                .iter()
+                .map(|file| file.score as f64 * file.factors.lines_changed as f64)
+                .sum::<f64>()
+                / lines as f64
+        };
+        let score = ((max as f64 + average) / 2.0).round() as u32;
+        Self {
+            score,
+            level: RiskLevel::of(score),
+            files,
+        }
+    }
+}
+
+/// Scores each changed file in `diffs` from its size, recent churn in `repo_root`, complexity
+/// delta, coverage of the added lines, and the findings on it.
+pub fn assess(
+    diffs: &[UnifiedDiff],
+    comments: &[Comment],
+    repo_root: &Path,
+    coverage: Option<&CoverageReport>,
+) -> RiskReport {
+    let diffs: Vec<&UnifiedDiff> = diffs
+        .iter()
+        .filter(|diff| !diff.is_deleted && !diff.is_binary)
+        .collect();
+    let paths: Vec<PathBuf> = diffs.iter().map(|diff| diff.file_path.clone()).collect();
+    let since = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |now| now.as_secs() as i64)
+        - CHURN_WINDOW_DAYS * 24 * 60 * 60;
+    let churn = GitIntegration::new(repo_root)
+        .and_then(|git| git.churn(&paths, since, MAX_CHURN_COMMITS))
+        .unwrap_or_default();
+    let mut findings: HashMap<&Path, (usize, usize)> = HashMap::new();
+    for comment in comments {
+        let counts = findings.entry(comment.file_path.as_path()).or_default();
+        match comment.severity {
+            Severity::Error => counts.0 += 1,
+            Severity::Warning => counts.1 += 1,
+            _ => {}
+        }
+    }
+
+    let factors = diffs
+        .into_iter()
+        .map(|diff| {
+            let (errors, warnings) = findings
+                .get(diff.file_path.as_path())
+                .copied()
+                .unwrap_or_default();
+            let factors = RiskFactors {
+                lines_changed: diff
+                    .hunks
+                    .iter()
+                    .flat_map(|hunk| &hunk.changes)
+                    .filter(|line| !matches!(line.change_type, ChangeType::Context))
+                    .count(),
+                recent_commits: churn.get(&diff.file_path).copied().unwrap_or(0),
+                complexity_delta: std::fs::read_to_string(repo_root.join(&diff.file_path))
+                    .ok()
+                    .and_then(|source| crate::plugins::builtin::complexity_delta(diff, &source)),
+                patch_coverage: coverage
+                    .and_then(|report| report.patch_coverage(diff))
+                    .and_then(|coverage| coverage.rate()),
+                errors,
+                warnings,
+            };
+            (diff.file_path.clone(), factors)
+        })
+        .collect();
+    RiskReport::from_factors(factors)
+}
+
+/// Markdown table of the `limit` riskiest files, or nothing when no file is above low risk.
+pub fn format_risk_section(report: &RiskReport, limit: usize) -> String {
+    let risky: Vec<&FileRisk> = report
+        .files
+        .iter()
+        .filter(|file| file.level != RiskLevel::Low)
+        .take(limit)
+        .collect();
+    if risky.is_empty() {
+        return String::new();
+    }
+
+    let mut output = format!(
+        "## ⚠️ Highest-Risk Files\n\n**Change risk:** {}/100 ({:?})\n\n",
+        report.score, report.level
+    );
+    output.push_str(&format!(
+        "| File | Risk | Lines changed | Commits ({}d) | Complexity Δ | Patch coverage | Findings |\n",
+        CHURN_WINDOW_DAYS
+    ));
+    output.push_str("|------|------|---------------|---------------|--------------|----------------|----------|\n");
+    for file in risky {
+        let factors = &file.factors;
+        output.push_str(&format!(
+            "| `{}` | {} ({:?}) | {} | {} | {} | {} | {} |\n",
+            file.path.display(),
+            file.score,
+            file.level,
+            factors.lines_changed,
+            factors.recent_commits,
+            factors
+                .complexity_delta
+                .map(|delta| format!("{:+}", delta))
+                .unwrap_or_else(|| "–".to_string()),
+            factors
+                .patch_coverage
+                .map(|rate| format!("{:.0}%", rate * 100.0))
+                .unwrap_or_else(|| "–".to_string()),
+            factors.errors + factors.warnings
+        ));
+    }
+    output.push('\n');
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scores_saturate_and_rank_the_riskiest_files_first() {
+        let quiet = RiskFactors {
+            lines_changed: 4,
+            ..Default::default()
+        };
+        let risky = RiskFactors {
+            lines_changed: 600,
+            recent_commits: 25,
+            complexity_delta: Some(14),
+            patch_coverage: Some(0.1),
+            errors: 1,
+            warnings: 2,
+        };
+        assert!(quiet.score() < 10);
+        assert!(risky.score() >= 70 && risky.score() <= 100);
+        // Unknown coverage is left out, not counted as uncovered.
+        let unknown_coverage = RiskFactors {
+            patch_coverage: None,
+            ..risky.clone()
+        };
+        assert!(unknown_coverage.score() <= risky.score() + 5);
+
+        let report = RiskReport::from_factors(vec![
+            (PathBuf::from("README.md"), quiet),
+            (PathBuf::from("src/billing.rs"), risky),
+        ]);
+        assert_eq!(report.files[0].path, PathBuf::from("src/billing.rs"));
+        assert_eq!(report.files[0].level, RiskLevel::High);
+        assert_eq!(report.level, RiskLevel::High);
+
+        let section = format_risk_section(&report, 5);
+        assert!(section.contains("| `src/billing.rs` |"));
+        assert!(!section.contains("README.md"));
+    }
+}
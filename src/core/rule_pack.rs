@@ -0,0 +1,205 @@
+use crate::config::{Config, CustomRule};
+use crate::plugins::builtin::RulesAnalyzer;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tracing::warn;
+
+/// A shared set of review standards: prompt guidance plus deterministic rules.
+#[derive(Debug, Default, Deserialize)]
+pub struct RulePack {
+    #[serde(default)]
+    pub name: Option<String>,
+
+    /// Appended to `review_instructions`.
+    #[serde(default)]
+    pub instructions: Option<String>,
+
+    /// Appended to `plugins.rules`.
+    #[serde(default)]
+    pub rules: Vec<CustomRule>,
+}
+
+pub fn default_cache_dir() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("diffscope")
+        .join("rule-packs")
+}
+
+fn is_url(source: &str) -> bool {
+    source.starts_with("https://") || source.starts_with("http://")
+}
+
+pub(crate) fn cache_path(cache_dir: &Path, url: &str) -> PathBuf {
+    let digest = ring::digest::digest(&ring::digest::SHA256, url.as_bytes());
+    let name: String = digest
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    cache_dir.join(format!("{}.yml", name))
+}
+
+pub(crate) fn is_fresh(path: &Path, ttl: Duration) -> bool {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_some_and(|age| age < ttl)
+}
+
+/// Packs steer the review, so one fetched over plain http, which can be rewritten in transit,
+/// is only accepted from this machine.
+fn check_scheme(url: &str) -> Result<()> {
+    let parsed =
+        reqwest::Url::parse(url).with_context(|| format!("Invalid rule pack URL {}", url))?;
+    let loopback = parsed.host_str().is_some_and(|host| {
+        host == "localhost"
+            || host
+                .trim_matches(['[', ']'])
+                .parse::<std::net::IpAddr>()
+                .is_ok_and(|ip| ip.is_loopback())
+    });
+    match parsed.scheme() {
+        "https" => Ok(()),
+        "http" if loopback => Ok(()),
+        _ => anyhow::bail!("Rule pack {} must be fetched over https", url),
+    }
+}
+
+/// Reads a rule pack from a local path, or from an https URL through the cache. A stale
+/// cached copy is used when the server cannot be reached. A pack whose rules do not compile is
+/// an error, like one that does not parse.
+pub async fn load(source: &str, cache_dir: &Path, ttl: Duration) -> Result<RulePack> {
+    let content = if is_url(source) {
+        check_scheme(source)?;
+        let cached = cache_path(cache_dir, source);
+        if is_fresh(&cached, ttl) {
+            tokio::fs::read_to_string(&cached).await?
+        } else {
+            match fetch(source).await {
+                Ok(content) => {
+                    tokio::fs::create_dir_all(cache_dir).await?;
+                    tokio::fs::write(&cached, &content).await?;
+                    content
+                }
+                Err(err) if cached.exists() => {
+                    warn!("Using cached rule pack {}: {:#}", source, err);
+                    tokio::fs::read_to_string(&cached).await?
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    } else {
+        tokio::fs::read_to_string(source)
+            .await
+            .with_context(|| format!("Failed to read rule pack {}", source))?
+    };
+    let pack: RulePack =
+        serde_yaml::from_str(&content).with_context(|| format!("Invalid rule pack {}", source))?;
+    RulesAnalyzer::new(&pack.rules, &Default::default())
+        .with_context(|| format!("Invalid rule pack {}", source))?;
+    Ok(pack)
+}
+
+async fn fetch(url: &str) -> Result<String> {
+    let response = reqwest::Client::builder()
+        .timeout(Duration::from_secs(20))
+        .build()?
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()
+        .with_context(|| format!("Failed to fetch rule pack {}", url))?;
+    Ok(response.text().await?)
+}
+
+/// Merges `pack` into `config`: instructions go after the repo's own, rules after its own.
+pub fn merge(config: &mut Config, source: &str, pack: RulePack) {
+    if let Some(instructions) = pack
+        .instructions
+        .as_deref()
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+    {
+        let name = pack.name.as_deref().unwrap_or(source);
+        let section = format!("Rule pack {}:\n{}", name, instructions);
+        config.review_instructions = Some(match config.review_instructions.take() {
+            Some(existing) if !existing.trim().is_empty() => {
+                format!("{}\n\n{}", existing.trim_end(), section)
+            }
+            _ => section,
+        });
+    }
+    config.plugins.rules.extend(pack.rules);
+}
+
+/// Loads and merges every configured rule pack. Packs that cannot be loaded are skipped with
+/// a warning, so an unreachable server never blocks a review.
+pub async fn apply_rule_packs(config: &mut Config) {
+    let ttl = Duration::from_secs(config.rule_pack_ttl_secs);
+    let cache_dir = default_cache_dir();
+    for source in config.rule_packs.clone() {
+        match load(&source, &cache_dir, ttl).await {
+            Ok(pack) => merge(config, &source, pack),
+            Err(err) => warn!("Skipping rule pack {}: {:#}", source, err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn fetches_caches_and_merges_rule_packs() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/diffscope-rules.yml")
+            .with_body(
+                "name: platform\ninstructions: Flag blocking calls in async handlers.\nrules:\n  - id: no-unwrap\n    pattern: '\\.unwrap\\(\\)'\n    message: Avoid unwrap in services.\n",
+            )
+            .expect(1)
+            .create_async()
+            .await;
+        let cache = tempfile::tempdir().unwrap();
+        let url = format!("{}/diffscope-rules.yml", server.url());
+        let ttl = Duration::from_secs(3600);
+
+        let pack = load(&url, cache.path(), ttl).await.unwrap();
+        // Served from the cache the second time.
+        load(&url, cache.path(), ttl).await.unwrap();
+        mock.assert_async().await;
+
+        let mut config = Config {
+            review_instructions: Some("Prefer small functions.".to_string()),
+            ..Config::default()
+        };
+        merge(&mut config, &url, pack);
+        assert_eq!(
+            config.review_instructions.as_deref(),
+            Some("Prefer small functions.\n\nRule pack platform:\nFlag blocking calls in async handlers.")
+        );
+        assert_eq!(config.plugins.rules.len(), 1);
+        assert_eq!(config.plugins.rules[0].severity, "warning");
+    }
+
+    #[tokio::test]
+    async fn rejects_plain_http_and_packs_whose_rules_do_not_compile() {
+        let cache = tempfile::tempdir().unwrap();
+        let ttl = Duration::from_secs(3600);
+        let insecure = load("http://rules.example.com/pack.yml", cache.path(), ttl).await;
+        assert!(format!("{:#}", insecure.unwrap_err()).contains("https"));
+
+        let pack = cache.path().join("broken.yml");
+        std::fs::write(
+            &pack,
+            "rules:\n  - id: broken\n    pattern: '(unclosed'\n    message: Never compiles.\n",
+        )
+        .unwrap();
+        let broken = load(&pack.to_string_lossy(), cache.path(), ttl).await;
+        assert!(format!("{:#}", broken.unwrap_err()).contains("Invalid pattern in rule broken"));
+    }
+}
@@ -0,0 +1,51 @@
+use std::collections::HashSet;
+use std::hash::Hash;
+
+/// Lowercase words of `text` with numbers collapsed to `0`, so line numbers and counts do
+/// not make otherwise identical comments differ.
+pub fn words(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            if word.chars().all(|c| c.is_ascii_digit()) {
+                "0".to_string()
+            } else {
+                word.to_string()
+            }
+        })
+        .collect()
+}
+
+pub fn jaccard<T: Eq + Hash>(a: &HashSet<T>, b: &HashSet<T>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+/// Jaccard similarity of the word sets of two texts.
+pub fn word_similarity(a: &str, b: &str) -> f64 {
+    let a: HashSet<String> = words(a).into_iter().collect();
+    let b: HashSet<String> = words(b).into_iter().collect();
+    jaccard(&a, &b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compares_normalized_words() {
+        assert_eq!(
+            words("Line 42: `foo_bar()` PANICS"),
+            ["line", "0", "foo_bar", "panics"]
+        );
+        assert_eq!(
+            word_similarity("Avoid unwrap here.", "avoid UNWRAP here"),
+            1.0
+        );
+        assert!(word_similarity("Avoid unwrap here.", "Missing docs.") < 0.1);
+    }
+}
@@ -1,14 +1,15 @@
-use crate::core::{LLMContextChunk, UnifiedDiff};
+use crate::core::{LLMContextChunk, PromptLimits, UnifiedDiff};
 use anyhow::Result;
 
 pub struct SmartReviewPromptBuilder;
 
 impl SmartReviewPromptBuilder {
+    /// Returns `(system_prompt, user_prompt)`. The file's context goes ahead of its diff in the
+    /// user prompt: it differs per file, so only context shared across files is worth caching.
     pub fn build_enhanced_review_prompt(
         diff: &UnifiedDiff,
         context_chunks: &[LLMContextChunk],
-        max_context_chars: usize,
-        max_diff_chars: usize,
+        limits: &PromptLimits,
         system_prompt_suffix: Option<&str>,
     ) -> Result<(String, String)> {
         let mut system_prompt = Self::build_smart_review_system_prompt();
@@ -19,12 +20,8 @@ impl SmartReviewPromptBuilder {
                 system_prompt.push_str(trimmed);
             }
         }
-        let user_prompt = Self::build_smart_review_user_prompt(
-            diff,
-            context_chunks,
-            max_context_chars,
-            max_diff_chars,
-        )?;
+        let mut user_prompt = Self::build_smart_review_context(context_chunks, limits);
+        user_prompt.push_str(&Self::build_smart_review_user_prompt(diff, limits)?);
 
         Ok((system_prompt, user_prompt))
     }
@@ -84,23 +81,13 @@ TAGS: [comma-separated relevant tags]
 - Focus on issues that improve security, reliability, or maintainability"#.to_string()
     }
 
-    fn build_smart_review_user_prompt(
-        diff: &UnifiedDiff,
+    fn build_smart_review_context(
         context_chunks: &[LLMContextChunk],
-        max_context_chars: usize,
-        max_diff_chars: usize,
-    ) -> Result<String> {
+        limits: &PromptLimits,
+    ) -> String {
         let mut prompt = String::new();
-        let mut context_chars = 0usize;
-        let mut diff_chars = 0usize;
-        let mut diff_truncated = false;
+        let mut budget = limits.context_budget();
 
-        prompt.push_str(&format!(
-            "Please review the following code changes in file: {}\n\n",
-            diff.file_path.display()
-        ));
-
-        // Add context information
         if !context_chunks.is_empty() {
             prompt.push_str("## Context Information\n\n");
             for chunk in context_chunks {
@@ -119,17 +106,33 @@ TAGS: [comma-separated relevant tags]
                         .collect::<Vec<_>>()
                         .join("\n")
                 );
-                if max_context_chars > 0
-                    && context_chars.saturating_add(block.len()) > max_context_chars
-                {
+                if !budget.try_add(&block) {
                     prompt.push_str("[Context truncated]\n\n");
                     break;
                 }
                 prompt.push_str(&block);
-                context_chars = context_chars.saturating_add(block.len());
             }
         }
 
+        prompt
+    }
+
+    fn build_smart_review_user_prompt(diff: &UnifiedDiff, limits: &PromptLimits) -> Result<String> {
+        let mut prompt = String::new();
+        let mut budget = limits.diff_budget();
+        let mut diff_truncated = false;
+
+        prompt.push_str(&format!(
+            "Please review the following code changes in file: {}\n\n",
+            diff.file_path.display()
+        ));
+        if let Some(old_path) = diff.old_path.as_ref().filter(|_| diff.is_renamed) {
+            prompt.push_str(&format!(
+                "This file was renamed from {}; lines not shown are unchanged from the original.\n\n",
+                old_path.display()
+            ));
+        }
+
         prompt.push_str("## Code Changes\n\n");
 
         // Format the diff with line numbers and change indicators
@@ -141,19 +144,17 @@ TAGS: [comma-separated relevant tags]
                 hunk.old_start,
                 hunk.old_start + hunk.old_lines
             );
-            if max_diff_chars > 0 && diff_chars.saturating_add(hunk_header.len()) > max_diff_chars {
+            if !budget.try_add(&hunk_header) {
                 diff_truncated = true;
                 break;
             }
             prompt.push_str(&hunk_header);
-            diff_chars = diff_chars.saturating_add(hunk_header.len());
 
-            if max_diff_chars > 0 && diff_chars.saturating_add("```diff\n".len()) > max_diff_chars {
+            if !budget.try_add("```diff\n") {
                 diff_truncated = true;
                 break;
             }
             prompt.push_str("```diff\n");
-            diff_chars = diff_chars.saturating_add("```diff\n".len());
             let mut line_num = hunk.new_start;
 
             for line in &hunk.changes {
@@ -164,13 +165,11 @@ TAGS: [comma-separated relevant tags]
                 };
 
                 let rendered = format!("{}{:4} {}\n", prefix, line_num, line.content);
-                if max_diff_chars > 0 && diff_chars.saturating_add(rendered.len()) > max_diff_chars
-                {
+                if !budget.try_add(&rendered) {
                     diff_truncated = true;
                     break;
                 }
                 prompt.push_str(&rendered);
-                diff_chars = diff_chars.saturating_add(rendered.len());
 
                 if !matches!(
                     line.change_type,
@@ -181,7 +180,7 @@ TAGS: [comma-separated relevant tags]
             }
 
             prompt.push_str("```\n\n");
-            diff_chars = diff_chars.saturating_add("```\n\n".len());
+            budget.try_add("```\n\n");
 
             if diff_truncated {
                 break;
@@ -0,0 +1,237 @@
+use crate::core::comment::CodeSuggestion;
+use std::path::PathBuf;
+
+/// Where a code suggestion lands in a file: lines `start..end` (0-based) are replaced by
+/// `replacement`, already indented like the code it replaces.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Placement {
+    pub start: usize,
+    pub end: usize,
+    pub replacement: Vec<String>,
+}
+
+impl Placement {
+    /// `lines` with the replacement applied.
+    pub fn patch<'a>(&'a self, lines: &[&'a str]) -> Vec<&'a str> {
+        let mut patched: Vec<&str> = lines[..self.start].to_vec();
+        patched.extend(self.replacement.iter().map(String::as_str));
+        patched.extend(&lines[self.end..]);
+        patched
+    }
+}
+
+/// Finds the suggestion's original code in `lines` (ignoring indentation), preferring the
+/// match covering or nearest to 1-based `line`.
+pub fn place(
+    lines: &[&str],
+    line: usize,
+    code: &CodeSuggestion,
+) -> std::result::Result<Placement, &'static str> {
+    let original: Vec<&str> = code
+        .original_code
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect();
+    if original.is_empty() {
+        return Err("it has no original code");
+    }
+    let (start, end) = (0..lines.len())
+        .filter_map(|start| match_at(lines, start, &original).map(|end| (start, end)))
+        .min_by_key(|&(start, end)| {
+            let line = line.max(1) - 1;
+            if line < start {
+                start - line
+            } else {
+                line.saturating_sub(end - 1)
+            }
+        })
+        .ok_or("its original code is not in the file")?;
+
+    let indent: String = lines[start]
+        .chars()
+        .take_while(|c| c.is_whitespace())
+        .collect();
+    Ok(Placement {
+        start,
+        end,
+        replacement: reindent(&code.suggested_code, &indent),
+    })
+}
+
+/// The end (exclusive) of the block at `start` whose non-blank lines are `original`, ignoring
+/// indentation and blank lines between them.
+fn match_at(lines: &[&str], start: usize, original: &[&str]) -> Option<usize> {
+    let mut index = start;
+    for (position, expected) in original.iter().enumerate() {
+        if position > 0 {
+            while lines.get(index)?.trim().is_empty() {
+                index += 1;
+            }
+        }
+        if lines.get(index)?.trim() != *expected {
+            return None;
+        }
+        index += 1;
+    }
+    Some(index)
+}
+
+/// `code` with its common indentation replaced by `indent`.
+fn reindent(code: &str, indent: &str) -> Vec<String> {
+    let leading = |line: &str| line.chars().take_while(|c| c.is_whitespace()).count();
+    let common = code
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(leading)
+        .min()
+        .unwrap_or(0);
+    code.lines()
+        .map(|line| {
+            if line.trim().is_empty() {
+                String::new()
+            } else {
+                let body: String = line.chars().skip(common).collect();
+                format!("{}{}", indent, body)
+            }
+        })
+        .collect()
+}
+
+/// A one-hunk unified diff replacing `old` with `new` at 1-based line `start`.
+pub fn block_diff(start: usize, old: &str, new: &str) -> String {
+    let text = similar::TextDiff::from_lines(old, new);
+    let mut out = format!(
+        "@@ -{},{} +{},{} @@",
+        start,
+        old.lines().count(),
+        start,
+        new.lines().count()
+    );
+    for change in text.iter_all_changes() {
+        let sign = match change.tag() {
+            similar::ChangeTag::Delete => '-',
+            similar::ChangeTag::Insert => '+',
+            similar::ChangeTag::Equal => ' ',
+        };
+        out.push('\n');
+        out.push(sign);
+        out.push_str(change.value().trim_end_matches('\n'));
+    }
+    out
+}
+
+/// A suggestion that could not be applied, and why.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    pub file_path: PathBuf,
+    pub line: usize,
+    pub reason: &'static str,
+}
+
+/// Applies `suggestions` (1-based comment line and suggestion) to one file's `source`.
+/// Suggestions whose code is gone or that overlap an earlier one become conflicts; the rest
+/// are applied together. Returns the patched source and the conflicts.
+pub fn apply_all(
+    file_path: &std::path::Path,
+    source: &str,
+    suggestions: &[(usize, &CodeSuggestion)],
+) -> (String, Vec<Conflict>) {
+    let lines: Vec<&str> = source.lines().collect();
+    let mut placements: Vec<Placement> = Vec::new();
+    let mut conflicts = Vec::new();
+    for &(line, code) in suggestions {
+        let conflict = |reason| Conflict {
+            file_path: file_path.to_path_buf(),
+            line,
+            reason,
+        };
+        match place(&lines, line, code) {
+            Ok(placement)
+                if placements
+                    .iter()
+                    .any(|other| placement.start < other.end && other.start < placement.end) =>
+            {
+                conflicts.push(conflict("it overlaps another suggestion"))
+            }
+            Ok(placement) => placements.push(placement),
+            Err(reason) => conflicts.push(conflict(reason)),
+        }
+    }
+
+    // Bottom-up, so earlier line numbers stay valid.
+    placements.sort_by_key(|placement| std::cmp::Reverse(placement.start));
+    let mut patched: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+    for placement in &placements {
+        patched.splice(
+            placement.start..placement.end,
+            placement.replacement.iter().cloned(),
+        );
+    }
+    let mut patched = patched.join("\n");
+    if source.ends_with('\n') {
+        patched.push('\n');
+    }
+    (patched, conflicts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn suggestion(original: &str, suggested: &str) -> CodeSuggestion {
+        CodeSuggestion {
+            original_code: original.to_string(),
+            suggested_code: suggested.to_string(),
+            explanation: String::new(),
+            diff: String::new(),
+        }
+    }
+
+    #[test]
+    fn applies_suggestions_and_reports_conflicts() {
+        let source = "fn load() -> Result<u8> {\n    let raw = read().unwrap();\n    let n = parse(raw).unwrap();\n    Ok(n)\n}\n";
+        let raw = suggestion("let raw = read().unwrap();", "let raw = read()?;");
+        let parse = suggestion(
+            "let n = parse(raw).unwrap();",
+            "let n = parse(raw)\n    .context(\"bad number\")?;",
+        );
+        let overlapping = suggestion("let raw = read().unwrap();", "let raw = read().ok()?;");
+        let stale = suggestion("let raw = fetch().unwrap();", "let raw = fetch()?;");
+
+        let (patched, conflicts) = apply_all(
+            Path::new("src/lib.rs"),
+            source,
+            &[(2, &raw), (3, &parse), (2, &overlapping), (2, &stale)],
+        );
+
+        assert_eq!(
+            patched,
+            "fn load() -> Result<u8> {\n    let raw = read()?;\n    let n = parse(raw)\n        .context(\"bad number\")?;\n    Ok(n)\n}\n"
+        );
+        let reasons: Vec<_> = conflicts.iter().map(|c| c.reason).collect();
+        assert_eq!(
+            reasons,
+            vec![
+                "it overlaps another suggestion",
+                "its original code is not in the file"
+            ]
+        );
+    }
+
+    #[test]
+    fn matches_across_blank_lines_and_reindents_unicode_whitespace() {
+        let source = "fn main() {\n    let a = 1;\n\n    let b = 2;\n}\n";
+        let spaced = suggestion("let a = 1;\nlet b = 2;", "let (a, b) = (1, 2);");
+        let (patched, conflicts) = apply_all(Path::new("src/main.rs"), source, &[(2, &spaced)]);
+        assert!(conflicts.is_empty());
+        assert_eq!(patched, "fn main() {\n    let (a, b) = (1, 2);\n}\n");
+
+        let lines = ["x = 1", "y = 2"];
+        let padded = suggestion("x = 1\n\ny = 2", "\u{3000}x = 3\n\u{3000}\u{3000}y = 4");
+        let placement = place(&lines, 1, &padded).unwrap();
+        assert_eq!((placement.start, placement.end), (0, 2));
+        assert_eq!(placement.replacement, vec!["x = 3", "\u{3000}y = 4"]);
+    }
+}
@@ -1,14 +1,20 @@
+use crate::core::syntax;
 use anyhow::Result;
 use ignore::WalkBuilder;
 use once_cell::sync::Lazy;
 use regex::Regex;
 use serde_json::{json, Value};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::env;
 use std::fs;
-use std::io::{BufRead, BufReader, Read, Write};
+use std::io::{BufRead, BufReader, Write};
 use std::path::{Path, PathBuf};
-use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::warn;
 
 #[derive(Debug, Clone)]
 pub struct SymbolLocation {
@@ -84,7 +90,69 @@ const LSP_SERVER_OPTIONS: &[LspServerOption] = &[
     },
 ];
 
+/// How `build_with_lsp` runs language servers.
+pub struct LspIndexing<'a> {
+    /// The server command for each file extension it indexes. One server is started per
+    /// distinct command; extensions without one are parsed instead.
+    pub commands: HashMap<String, String>,
+    /// The LSP language id for each file extension.
+    pub languages: &'a HashMap<String, String>,
+    /// Servers and parsing jobs run at once.
+    pub workers: usize,
+    /// How long a server may take to answer a request before it is stopped and its
+    /// remaining files are parsed instead.
+    pub timeout: Duration,
+}
+
+/// The files indexed so far across concurrent jobs, against `max_files`.
+struct FileBudget {
+    seen: AtomicUsize,
+    max: usize,
+}
+
+impl FileBudget {
+    fn exhausted(&self) -> bool {
+        self.seen.load(Ordering::Relaxed) >= self.max
+    }
+
+    fn record(&self) {
+        self.seen.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+enum IndexJob<'a> {
+    Server {
+        command: &'a str,
+        files: Vec<(PathBuf, String)>,
+    },
+    Parse(&'a [PathBuf]),
+}
+
 impl SymbolIndex {
+    /// The server to start for each extension in `lsp_languages` found in the repository:
+    /// the first installed server known to handle it.
+    pub fn detect_lsp_commands<F>(
+        repo_root: &Path,
+        max_files: usize,
+        lsp_languages: &HashMap<String, String>,
+        should_exclude: F,
+    ) -> HashMap<String, String>
+    where
+        F: Fn(&PathBuf) -> bool,
+    {
+        if max_files == 0 {
+            return HashMap::new();
+        }
+        let enabled_extensions = normalized_extension_set(lsp_languages);
+        let extension_counts = collect_extension_counts(
+            repo_root,
+            max_files.min(LSP_DETECT_MAX_FILES),
+            &enabled_extensions,
+            should_exclude,
+        );
+        choose_lsp_commands(&extension_counts, &enabled_extensions)
+    }
+
     pub fn detect_lsp_command<F>(
         repo_root: &Path,
         max_files: usize,
@@ -177,14 +245,9 @@ impl SymbolIndex {
                 continue;
             }
 
-            let extension = match path.extension().and_then(|ext| ext.to_str()) {
-                Some(ext) => ext,
-                None => continue,
-            };
-            let patterns = match patterns_for_extension(extension) {
-                Some(patterns) => patterns,
-                None => continue,
-            };
+            if !is_indexable(path) {
+                continue;
+            }
 
             let metadata = match fs::metadata(path) {
                 Ok(metadata) => metadata,
@@ -203,9 +266,7 @@ impl SymbolIndex {
             }
 
             let content = String::from_utf8_lossy(&bytes);
-            let lines: Vec<&str> = content.lines().collect();
-            let file_added =
-                add_symbols_from_lines(&mut index, &relative, &lines, patterns, max_locations);
+            let file_added = add_symbols(&mut index, &relative, &content, max_locations);
 
             if file_added {
                 files_seen += 1;
@@ -216,21 +277,22 @@ impl SymbolIndex {
         Ok(index)
     }
 
+    /// Indexes files with language servers, one per command in `lsp.commands`, running up
+    /// to `lsp.workers` servers and parsing jobs at once. Files no server handles, and the
+    /// files of a server that fails to start or stops answering, are parsed instead.
     pub fn build_with_lsp<F>(
         repo_root: &Path,
         max_files: usize,
         max_bytes: usize,
         max_locations: usize,
-        lsp_command: &str,
-        lsp_languages: &HashMap<String, String>,
+        lsp: &LspIndexing,
         should_exclude: F,
     ) -> Result<Self>
     where
         F: Fn(&PathBuf) -> bool,
     {
-        let mut index = SymbolIndex::default();
         if max_files == 0 {
-            return Ok(index);
+            return Ok(SymbolIndex::default());
         }
 
         let walker = WalkBuilder::new(repo_root)
@@ -241,7 +303,7 @@ impl SymbolIndex {
             .git_global(true)
             .build();
 
-        let mut lsp_files: Vec<(PathBuf, String)> = Vec::new();
+        let mut server_files: BTreeMap<&str, Vec<(PathBuf, String)>> = BTreeMap::new();
         let mut other_files = Vec::new();
 
         for entry in walker.flatten() {
@@ -261,103 +323,103 @@ impl SymbolIndex {
                 Some(ext) => ext,
                 None => continue,
             };
-            if let Some(language_id) = lsp_languages.get(extension) {
-                lsp_files.push((relative, language_id.clone()));
-            } else if patterns_for_extension(extension).is_some() {
-                other_files.push(relative);
+            match (lsp.languages.get(extension), lsp.commands.get(extension)) {
+                (Some(language_id), Some(command)) => server_files
+                    .entry(command.as_str())
+                    .or_default()
+                    .push((relative, language_id.clone())),
+                _ if is_indexable(path) => other_files.push(relative),
+                _ => {}
             }
         }
 
-        let mut files_seen = 0usize;
-        let mut fallback_lsp = false;
-
-        if !lsp_files.is_empty() {
-            match LspClient::spawn(lsp_command, repo_root) {
-                Ok(mut client) => {
-                    for (relative, language_id) in &lsp_files {
-                        if files_seen >= max_files {
-                            break;
-                        }
-                        if let Ok(full_path) = repo_root.join(relative).canonicalize() {
-                            if let Ok(metadata) = fs::metadata(&full_path) {
-                                if metadata.len() as usize > max_bytes {
-                                    continue;
+        let workers = lsp.workers.max(1);
+        let mut jobs: Vec<IndexJob> = server_files
+            .into_iter()
+            .map(|(command, files)| IndexJob::Server { command, files })
+            .collect();
+        let per_job = other_files.len().div_ceil(workers).max(1);
+        jobs.extend(other_files.chunks(per_job).map(IndexJob::Parse));
+
+        let budget = FileBudget {
+            seen: AtomicUsize::new(0),
+            max: max_files,
+        };
+        let next_job = AtomicUsize::new(0);
+        let finished: Mutex<Vec<(usize, SymbolIndex)>> = Mutex::new(Vec::new());
+        std::thread::scope(|scope| {
+            for _ in 0..workers.min(jobs.len()) {
+                scope.spawn(|| loop {
+                    let position = next_job.fetch_add(1, Ordering::Relaxed);
+                    let Some(job) = jobs.get(position) else {
+                        break;
+                    };
+                    let partial = match job {
+                        IndexJob::Server { command, files } => index_with_server(
+                            command,
+                            files,
+                            repo_root,
+                            max_bytes,
+                            max_locations,
+                            lsp.timeout,
+                            &budget,
+                        ),
+                        IndexJob::Parse(files) => {
+                            let mut partial = SymbolIndex::default();
+                            for relative in files.iter() {
+                                if budget.exhausted() {
+                                    break;
                                 }
-                            }
-                            let content = match fs::read_to_string(&full_path) {
-                                Ok(content) => content,
-                                Err(_) => continue,
-                            };
-                            if let Ok(file_added) = client.index_file(
-                                &mut index,
-                                relative,
-                                &full_path,
-                                &content,
-                                language_id,
-                                max_locations,
-                            ) {
-                                if file_added {
-                                    files_seen += 1;
+                                let Some(content) =
+                                    read_source(&repo_root.join(relative), max_bytes)
+                                else {
+                                    continue;
+                                };
+                                if add_symbols(&mut partial, relative, &content, max_locations) {
+                                    budget.record();
+                                    partial.files_indexed += 1;
                                 }
                             }
+                            partial
                         }
+                    };
+                    if let Ok(mut finished) = finished.lock() {
+                        finished.push((position, partial));
                     }
-                    let _ = client.shutdown();
-                }
-                Err(_) => {
-                    fallback_lsp = true;
-                }
+                });
             }
-        }
+        });
 
-        for relative in other_files.into_iter().chain(
-            lsp_files
-                .into_iter()
-                .filter(|_| fallback_lsp)
-                .map(|(path, _)| path),
-        ) {
-            if files_seen >= max_files {
-                break;
-            }
-            let full_path = repo_root.join(&relative);
-            let extension = match full_path.extension().and_then(|ext| ext.to_str()) {
-                Some(ext) => ext,
-                None => continue,
-            };
-            let patterns = match patterns_for_extension(extension) {
-                Some(patterns) => patterns,
-                None => continue,
-            };
-            let metadata = match fs::metadata(&full_path) {
-                Ok(metadata) => metadata,
-                Err(_) => continue,
-            };
-            if metadata.len() as usize > max_bytes {
-                continue;
-            }
-            let bytes = match fs::read(&full_path) {
-                Ok(bytes) => bytes,
-                Err(_) => continue,
-            };
-            if bytes.iter().take(2048).any(|b| *b == 0) {
-                continue;
-            }
-            let content = String::from_utf8_lossy(&bytes);
-            let lines: Vec<&str> = content.lines().collect();
-            let file_added =
-                add_symbols_from_lines(&mut index, &relative, &lines, patterns, max_locations);
-            if file_added {
-                files_seen += 1;
-            }
+        // Merge in job order so the kept locations do not depend on which job finished first.
+        let mut finished = finished.into_inner().unwrap_or_default();
+        finished.sort_by_key(|(position, _)| *position);
+        let mut index = SymbolIndex::default();
+        for (_, partial) in finished {
+            index.merge(partial, max_locations);
         }
-
         Ok(index)
     }
 
+    fn merge(&mut self, other: SymbolIndex, max_locations: usize) {
+        for (name, locations) in other.symbols {
+            let entry = self.symbols.entry(name).or_default();
+            let room = max_locations.saturating_sub(entry.len());
+            entry.extend(locations.into_iter().take(room));
+        }
+        self.files_indexed += other.files_indexed;
+    }
+
     pub fn lookup(&self, symbol: &str) -> Option<&Vec<SymbolLocation>> {
         self.symbols.get(symbol)
     }
 
+    /// Every indexed symbol with the places it is defined.
+    pub fn symbols(&self) -> impl Iterator<Item = (&str, &[SymbolLocation])> {
+        self.symbols
+            .iter()
+            .map(|(name, locations)| (name.as_str(), locations.as_slice()))
+    }
+
     pub fn files_indexed(&self) -> usize {
         self.files_indexed
     }
@@ -459,6 +521,112 @@ fn choose_lsp_command(
     best_command.map(|command| command.to_string())
 }
 
+fn choose_lsp_commands(
+    extension_counts: &HashMap<String, usize>,
+    enabled_extensions: &HashSet<String>,
+) -> HashMap<String, String> {
+    let mut commands = HashMap::new();
+    for option in LSP_SERVER_OPTIONS {
+        let extensions: Vec<&str> = option
+            .extensions
+            .iter()
+            .copied()
+            .filter(|ext| enabled_extensions.is_empty() || enabled_extensions.contains(*ext))
+            .filter(|ext| extension_counts.contains_key(*ext) && !commands.contains_key(*ext))
+            .collect();
+        if extensions.is_empty() || !is_program_available(option.program) {
+            continue;
+        }
+        for ext in extensions {
+            commands.insert(ext.to_string(), option.command.to_string());
+        }
+    }
+    commands
+}
+
+/// Indexes `files` with one language server, parsing them instead once the server fails.
+fn index_with_server(
+    command: &str,
+    files: &[(PathBuf, String)],
+    repo_root: &Path,
+    max_bytes: usize,
+    max_locations: usize,
+    timeout: Duration,
+    budget: &FileBudget,
+) -> SymbolIndex {
+    let mut index = SymbolIndex::default();
+    let mut client = match LspClient::spawn(command, repo_root, timeout) {
+        Ok(client) => Some(client),
+        Err(err) => {
+            warn!(
+                "LSP server `{}` did not start, parsing its files instead: {:#}",
+                command, err
+            );
+            None
+        }
+    };
+
+    for (relative, language_id) in files {
+        if budget.exhausted() {
+            break;
+        }
+        let full_path = repo_root.join(relative);
+        let Some(content) = read_source(&full_path, max_bytes) else {
+            continue;
+        };
+        let served = match client.as_mut() {
+            Some(active) => {
+                match active.index_file(
+                    &mut index,
+                    relative,
+                    &full_path,
+                    &content,
+                    language_id,
+                    max_locations,
+                ) {
+                    Ok(file_added) => Some(file_added),
+                    Err(err) => {
+                        if active.failed {
+                            warn!(
+                                "LSP server `{}` stopped at {}, parsing the remaining files instead: {:#}",
+                                command,
+                                relative.display(),
+                                err
+                            );
+                            client = None;
+                        }
+                        None
+                    }
+                }
+            }
+            None => None,
+        };
+        let file_added =
+            served.unwrap_or_else(|| add_symbols(&mut index, relative, &content, max_locations));
+        if file_added {
+            budget.record();
+            index.files_indexed += 1;
+        }
+    }
+
+    if let Some(mut client) = client {
+        let _ = client.shutdown();
+    }
+    index
+}
+
+/// The text of a source file, unless it is missing, larger than `max_bytes`, or binary.
+fn read_source(path: &Path, max_bytes: usize) -> Option<String> {
+    if fs::metadata(path).ok()?.len() as usize > max_bytes {
+        return None;
+    }
+    let bytes = fs::read(path).ok()?;
+    if bytes.iter().take(2048).any(|b| *b == 0) {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&bytes).into_owned())
+}
+
 fn split_command(command: &str) -> Result<Vec<String>> {
     shell_words::split(command).map_err(|err| anyhow::anyhow!(err.to_string()))
 }
@@ -632,6 +800,57 @@ fn patterns_for_extension(ext: &str) -> Option<&'static Vec<Regex>> {
     SYMBOL_PATTERNS.get(ext)
 }
 
+/// Whether the regex patterns or a tree-sitter grammar cover `path`.
+fn is_indexable(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| patterns_for_extension(ext).is_some())
+        || syntax::language_for(path).is_some()
+}
+
+/// Indexes the definitions in `content`, parsed with tree-sitter when there is a grammar for
+/// the file (so methods are also indexed as `Type::method`), else matched by regex.
+fn add_symbols(
+    index: &mut SymbolIndex,
+    relative: &Path,
+    content: &str,
+    max_locations: usize,
+) -> bool {
+    let lines: Vec<&str> = content.lines().collect();
+    if let Some(definitions) = syntax::definitions(relative, content) {
+        let mut file_added = false;
+        for definition in &definitions {
+            file_added |= add_location(
+                index,
+                &definition.name,
+                relative,
+                &lines,
+                definition.line,
+                max_locations,
+            );
+            if !definition.containers.is_empty() {
+                file_added |= add_location(
+                    index,
+                    &definition.qualified_name(),
+                    relative,
+                    &lines,
+                    definition.line,
+                    max_locations,
+                );
+            }
+        }
+        return file_added;
+    }
+    let extension = relative
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default();
+    match patterns_for_extension(extension) {
+        Some(patterns) => add_symbols_from_lines(index, relative, &lines, patterns, max_locations),
+        None => false,
+    }
+}
+
 fn add_symbols_from_lines(
     index: &mut SymbolIndex,
     relative: &Path,
@@ -644,24 +863,8 @@ fn add_symbols_from_lines(
         for pattern in patterns {
             if let Some(caps) = pattern.captures(line) {
                 if let Some(name) = caps.get(1) {
-                    let symbol = name.as_str().to_string();
-                    if symbol.len() < 2 {
-                        continue;
-                    }
-                    let entry = index.symbols.entry(symbol).or_default();
-                    if entry.len() >= max_locations {
-                        continue;
-                    }
-
-                    let start = idx.saturating_sub(2);
-                    let end = (idx + 3).min(lines.len().saturating_sub(1));
-                    let snippet = lines[start..=end].join("\n");
-                    entry.push(SymbolLocation {
-                        file_path: relative.to_path_buf(),
-                        line_range: (start + 1, end + 1),
-                        snippet,
-                    });
-                    file_added = true;
+                    file_added |=
+                        add_location(index, name.as_str(), relative, lines, idx, max_locations);
                 }
             }
         }
@@ -670,16 +873,48 @@ fn add_symbols_from_lines(
     file_added
 }
 
+/// Records `symbol` as defined at line `idx` (0-based), with a few lines around it.
+fn add_location(
+    index: &mut SymbolIndex,
+    symbol: &str,
+    relative: &Path,
+    lines: &[&str],
+    idx: usize,
+    max_locations: usize,
+) -> bool {
+    if symbol.len() < 2 || idx >= lines.len() {
+        return false;
+    }
+    let entry = index.symbols.entry(symbol.to_string()).or_default();
+    if entry.len() >= max_locations {
+        return false;
+    }
+
+    let start = idx.saturating_sub(2);
+    let end = (idx + 3).min(lines.len().saturating_sub(1));
+    let snippet = lines[start..=end].join("\n");
+    entry.push(SymbolLocation {
+        file_path: relative.to_path_buf(),
+        line_range: (start + 1, end + 1),
+        snippet,
+    });
+    true
+}
+
 struct LspClient {
     child: Child,
     stdin: ChildStdin,
-    stdout: BufReader<ChildStdout>,
+    /// Messages from the server, read on a separate thread so a silent server can time out.
+    messages: Receiver<Result<Value>>,
+    timeout: Duration,
+    /// Set once the server timed out or closed the connection.
+    failed: bool,
     next_id: u64,
     root_uri: String,
 }
 
 impl LspClient {
-    fn spawn(command: &str, root: &Path) -> Result<Self> {
+    fn spawn(command: &str, root: &Path, timeout: Duration) -> Result<Self> {
         let parts = split_command(command)?;
         let (program, args) = parts
             .split_first()
@@ -700,10 +935,23 @@ impl LspClient {
             .stdout
             .take()
             .ok_or_else(|| anyhow::anyhow!("Missing LSP stdout"))?;
+        let (sender, messages) = mpsc::channel();
+        std::thread::spawn(move || {
+            let mut stdout = BufReader::new(stdout);
+            loop {
+                let message = read_message(&mut stdout);
+                let closed = message.is_err();
+                if sender.send(message).is_err() || closed {
+                    break;
+                }
+            }
+        });
         let mut client = LspClient {
             child,
             stdin,
-            stdout: BufReader::new(stdout),
+            messages,
+            timeout,
+            failed: false,
             next_id: 1,
             root_uri: path_to_uri(root)?,
         };
@@ -761,11 +1009,6 @@ impl LspClient {
         let mut file_added = false;
 
         for symbol in symbols {
-            let entry = index.symbols.entry(symbol.name.clone()).or_default();
-            if entry.len() >= max_locations {
-                continue;
-            }
-
             let start = symbol.range.0.max(1);
             let end = symbol.range.1.max(start);
             let start_idx = start.saturating_sub(3);
@@ -776,12 +1019,18 @@ impl LspClient {
                 String::new()
             };
 
-            entry.push(SymbolLocation {
-                file_path: relative.to_path_buf(),
-                line_range: (start, end),
-                snippet,
-            });
-            file_added = true;
+            for name in std::iter::once(symbol.name).chain(symbol.qualified) {
+                let entry = index.symbols.entry(name).or_default();
+                if entry.len() >= max_locations {
+                    continue;
+                }
+                entry.push(SymbolLocation {
+                    file_path: relative.to_path_buf(),
+                    line_range: (start, end),
+                    snippet: snippet.clone(),
+                });
+                file_added = true;
+            }
         }
 
         Ok(file_added)
@@ -798,8 +1047,21 @@ impl LspClient {
         });
         self.send_message(&message)?;
 
+        let deadline = Instant::now() + self.timeout;
         loop {
-            let response = self.read_message()?;
+            let response = self.read_message(deadline)?;
+            if response.get("method").is_some() {
+                // A request from the server (configuration, progress); answer so it does not
+                // wait on us.
+                if let Some(server_id) = response.get("id") {
+                    self.send_message(&json!({
+                        "jsonrpc": "2.0",
+                        "id": server_id,
+                        "result": Value::Null,
+                    }))?;
+                }
+                continue;
+            }
             if response.get("id").and_then(|v| v.as_u64()) == Some(id) {
                 if let Some(error) = response.get("error") {
                     return Err(anyhow::anyhow!("LSP error: {}", error));
@@ -827,41 +1089,73 @@ impl LspClient {
         Ok(())
     }
 
-    fn read_message(&mut self) -> Result<Value> {
-        let mut content_length = None;
-        loop {
-            let mut header = String::new();
-            let bytes = self.stdout.read_line(&mut header)?;
-            if bytes == 0 {
-                return Err(anyhow::anyhow!("LSP closed connection"));
+    fn read_message(&mut self, deadline: Instant) -> Result<Value> {
+        let wait = deadline.saturating_duration_since(Instant::now());
+        match self.messages.recv_timeout(wait) {
+            Ok(Ok(message)) => Ok(message),
+            Ok(Err(err)) => {
+                self.failed = true;
+                Err(err)
             }
-            let header_trimmed = header.trim();
-            if header_trimmed.is_empty() {
-                break;
+            Err(RecvTimeoutError::Timeout) => {
+                self.failed = true;
+                Err(anyhow::anyhow!(
+                    "LSP server did not respond within {}s",
+                    self.timeout.as_secs()
+                ))
             }
-            if let Some(rest) = header_trimmed.strip_prefix("Content-Length:") {
-                content_length = rest.trim().parse::<usize>().ok();
+            Err(RecvTimeoutError::Disconnected) => {
+                self.failed = true;
+                Err(anyhow::anyhow!("LSP closed connection"))
             }
         }
-
-        let length = content_length.ok_or_else(|| anyhow::anyhow!("Missing Content-Length"))?;
-        let mut buffer = vec![0u8; length];
-        self.stdout.read_exact(&mut buffer)?;
-        let value: Value = serde_json::from_slice(&buffer)?;
-        Ok(value)
     }
 
     fn shutdown(&mut self) -> Result<()> {
-        let _ = self.send_request("shutdown", json!({}));
-        let _ = self.send_notification("exit", json!({}));
-        let _ = self.child.kill();
+        if !self.failed {
+            let _ = self.send_request("shutdown", json!({}));
+            let _ = self.send_notification("exit", json!({}));
+        }
         Ok(())
     }
 }
 
+impl Drop for LspClient {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn read_message(stdout: &mut impl BufRead) -> Result<Value> {
+    let mut content_length = None;
+    loop {
+        let mut header = String::new();
+        let bytes = stdout.read_line(&mut header)?;
+        if bytes == 0 {
+            return Err(anyhow::anyhow!("LSP closed connection"));
+        }
+        let header_trimmed = header.trim();
+        if header_trimmed.is_empty() {
+            break;
+        }
+        if let Some(rest) = header_trimmed.strip_prefix("Content-Length:") {
+            content_length = rest.trim().parse::<usize>().ok();
+        }
+    }
+
+    let length = content_length.ok_or_else(|| anyhow::anyhow!("Missing Content-Length"))?;
+    let mut buffer = vec![0u8; length];
+    stdout.read_exact(&mut buffer)?;
+    let value: Value = serde_json::from_slice(&buffer)?;
+    Ok(value)
+}
+
 #[derive(Debug)]
 struct LspSymbol {
     name: String,
+    /// `Container::name` for symbols nested in a type or module.
+    qualified: Option<String>,
     range: (usize, usize),
 }
 
@@ -869,31 +1163,43 @@ fn extract_lsp_symbols(result: &Value) -> Vec<LspSymbol> {
     let mut symbols = Vec::new();
     if let Some(array) = result.as_array() {
         for entry in array {
-            collect_lsp_symbol(entry, &mut symbols);
+            collect_lsp_symbol(entry, None, &mut symbols);
         }
     }
     symbols
 }
 
-fn collect_lsp_symbol(value: &Value, symbols: &mut Vec<LspSymbol>) {
+fn collect_lsp_symbol(value: &Value, container: Option<&str>, symbols: &mut Vec<LspSymbol>) {
     if let Some(obj) = value.as_object() {
+        let name = obj.get("name").and_then(|v| v.as_str());
+        // Flat `SymbolInformation` results name their container instead of nesting.
+        let container = container.map(str::to_string).or_else(|| {
+            obj.get("containerName")
+                .and_then(|v| v.as_str())
+                .map(lsp_container_name)
+                .filter(|name| !name.is_empty())
+        });
+        let qualified = match (&container, name) {
+            (Some(container), Some(name)) => Some(format!("{}::{}", container, name)),
+            _ => None,
+        };
+
         if let (Some(name), Some(range)) = (
-            obj.get("name").and_then(|v| v.as_str()),
+            name,
             extract_range(obj.get("selectionRange").or_else(|| obj.get("range"))),
         ) {
             symbols.push(LspSymbol {
                 name: name.to_string(),
+                qualified: qualified.clone(),
                 range,
             });
         }
 
         if let Some(location) = obj.get("location") {
-            if let (Some(name), Some(range)) = (
-                obj.get("name").and_then(|v| v.as_str()),
-                extract_range(location.get("range")),
-            ) {
+            if let (Some(name), Some(range)) = (name, extract_range(location.get("range"))) {
                 symbols.push(LspSymbol {
                     name: name.to_string(),
+                    qualified: qualified.clone(),
                     range,
                 });
             }
@@ -901,14 +1207,27 @@ fn collect_lsp_symbol(value: &Value, symbols: &mut Vec<LspSymbol>) {
 
         if let Some(children) = obj.get("children") {
             if let Some(child_array) = children.as_array() {
+                let child_container = name.map(|name| match &container {
+                    Some(container) => format!("{}::{}", container, lsp_container_name(name)),
+                    None => lsp_container_name(name),
+                });
                 for child in child_array {
-                    collect_lsp_symbol(child, symbols);
+                    collect_lsp_symbol(child, child_container.as_deref(), symbols);
                 }
             }
         }
     }
 }
 
+/// The type an LSP container symbol stands for: rust-analyzer names impl blocks
+/// `impl Display for Foo<T>`, which contain `Foo`'s methods.
+fn lsp_container_name(name: &str) -> String {
+    let name = name.strip_prefix("impl ").unwrap_or(name);
+    let name = name.rsplit(" for ").next().unwrap_or(name);
+    let name = name.split('<').next().unwrap_or(name);
+    name.trim().to_string()
+}
+
 fn extract_range(value: Option<&Value>) -> Option<(usize, usize)> {
     let range = value?.as_object()?;
     let start = range.get("start")?.as_object()?;
@@ -940,3 +1259,48 @@ fn url_encode(segment: &str) -> String {
     }
     out
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn parses_the_files_of_servers_that_fail_or_hang() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(
+            root.join("lib.rs"),
+            "struct Foo;\nimpl Foo {\n    fn bar() {}\n}\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("app.py"), "def handler():\n    pass\n").unwrap();
+        std::fs::write(root.join("util.go"), "package util\nfunc Helper() {}\n").unwrap();
+
+        let languages: HashMap<String, String> = [("rs", "rust"), ("py", "python")]
+            .into_iter()
+            .map(|(ext, id)| (ext.to_string(), id.to_string()))
+            .collect();
+        let commands: HashMap<String, String> = [
+            ("rs", "sleep 30"),
+            ("py", "diffscope-missing-language-server"),
+        ]
+        .into_iter()
+        .map(|(ext, command)| (ext.to_string(), command.to_string()))
+        .collect();
+        let lsp = LspIndexing {
+            commands,
+            languages: &languages,
+            workers: 4,
+            timeout: Duration::from_millis(500),
+        };
+
+        let started = Instant::now();
+        let index = SymbolIndex::build_with_lsp(root, 100, 100_000, 5, &lsp, |_| false).unwrap();
+        assert!(started.elapsed() < Duration::from_secs(10));
+        assert_eq!(index.files_indexed(), 3);
+        assert!(index.lookup("Foo::bar").is_some());
+        assert!(index.lookup("handler").is_some());
+        assert!(index.lookup("Helper").is_some());
+    }
+}
@@ -0,0 +1,282 @@
+use std::path::Path;
+use tree_sitter::{Node, Parser};
+
+/// Tree-sitter grammar for `path`, chosen by extension.
+pub fn language_for(path: &Path) -> Option<tree_sitter::Language> {
+    let language = match path.extension()?.to_str()?.to_lowercase().as_str() {
+        "rs" => tree_sitter_rust::LANGUAGE.into(),
+        "py" => tree_sitter_python::LANGUAGE.into(),
+        "go" => tree_sitter_go::LANGUAGE.into(),
+        "js" | "jsx" | "mjs" | "cjs" => tree_sitter_javascript::LANGUAGE.into(),
+        "ts" | "mts" | "cts" => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+        "tsx" => tree_sitter_typescript::LANGUAGE_TSX.into(),
+        _ => return None,
+    };
+    Some(language)
+}
+
+/// Whether `source` fails to parse as the language of `path`, or `None` when there is no
+/// grammar for it.
+pub fn has_errors(path: &Path, source: &str) -> Option<bool> {
+    let mut parser = Parser::new();
+    parser.set_language(&language_for(path)?).ok()?;
+    let tree = parser.parse(source, None)?;
+    Some(tree.root_node().has_error())
+}
+
+/// A named definition found in a source file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Definition {
+    pub name: String,
+    /// Enclosing types, modules, and functions, outermost first.
+    pub containers: Vec<String>,
+    /// 0-based line of the definition.
+    pub line: usize,
+    /// 0-based line the definition ends on, inclusive.
+    pub end_line: usize,
+    /// Whether it is a function or method rather than a type or module.
+    pub callable: bool,
+}
+
+impl Definition {
+    /// The name with its containers, e.g. `Foo::bar`.
+    pub fn qualified_name(&self) -> String {
+        let mut parts = self.containers.clone();
+        parts.push(self.name.clone());
+        parts.join("::")
+    }
+}
+
+/// The functions, methods, types, and modules defined in `source`, including nested ones,
+/// or `None` when there is no grammar for `path`.
+pub fn definitions(path: &Path, source: &str) -> Option<Vec<Definition>> {
+    let mut parser = Parser::new();
+    parser.set_language(&language_for(path)?).ok()?;
+    let tree = parser.parse(source, None)?;
+    let mut definitions = Vec::new();
+    collect_definitions(
+        tree.root_node(),
+        source.as_bytes(),
+        &mut Vec::new(),
+        &mut definitions,
+    );
+    Some(definitions)
+}
+
+fn collect_definitions(
+    node: Node,
+    source: &[u8],
+    containers: &mut Vec<String>,
+    definitions: &mut Vec<Definition>,
+) {
+    let text = |node: Node| node.utf8_text(source).ok().map(str::to_string);
+    let field_text = |field: &str| node.child_by_field_name(field).and_then(text);
+
+    // The definition's name, and the container it opens for its children.
+    let (name, container) = match node.kind() {
+        "function_item"
+        | "function_signature_item"
+        | "struct_item"
+        | "enum_item"
+        | "union_item"
+        | "trait_item"
+        | "type_item"
+        | "mod_item"
+        | "function_definition"
+        | "class_definition"
+        | "function_declaration"
+        | "generator_function_declaration"
+        | "class_declaration"
+        | "abstract_class_declaration"
+        | "interface_declaration"
+        | "type_alias_declaration"
+        | "enum_declaration"
+        | "method_definition"
+        | "method_signature"
+        | "abstract_method_signature"
+        | "method_elem"
+        | "type_spec" => {
+            let name = field_text("name");
+            (name.clone(), name)
+        }
+        "impl_item" => {
+            let name = node
+                .child_by_field_name("type")
+                .and_then(|ty| type_name(ty, source));
+            (name.clone(), name)
+        }
+        "method_declaration" => {
+            // Go methods belong to their receiver's type.
+            let receiver = node
+                .child_by_field_name("receiver")
+                .and_then(|receiver| first_of_kind(receiver, "type_identifier"))
+                .and_then(text);
+            if let (Some(name), Some(receiver)) = (field_text("name"), receiver) {
+                containers.push(receiver);
+                definitions.push(Definition {
+                    name: name.clone(),
+                    containers: containers.clone(),
+                    line: node.start_position().row,
+                    end_line: node.end_position().row,
+                    callable: true,
+                });
+                containers.push(name);
+                recurse(node, source, containers, definitions);
+                containers.truncate(containers.len() - 2);
+                return;
+            }
+            let name = field_text("name");
+            (name.clone(), name)
+        }
+        "variable_declarator" | "pair" | "field_definition" | "public_field_definition" => {
+            let value = node.child_by_field_name("value");
+            let name = ["name", "key", "property"]
+                .iter()
+                .find_map(|field| field_text(field))
+                .map(|name| name.trim_matches(|c| c == '"' || c == '\'').to_string());
+            match value.map(|value| value.kind()) {
+                Some(
+                    "arrow_function"
+                    | "function"
+                    | "function_expression"
+                    | "generator_function"
+                    | "class",
+                ) => (name.clone(), name),
+                // Object literals hold methods without being definitions themselves.
+                Some("object") => (None, name),
+                _ => (None, None),
+            }
+        }
+        _ => (None, None),
+    };
+
+    if let Some(name) = name {
+        definitions.push(Definition {
+            name,
+            containers: containers.clone(),
+            line: node.start_position().row,
+            end_line: node.end_position().row,
+            callable: is_callable(node),
+        });
+    }
+    match container {
+        Some(container) => {
+            containers.push(container);
+            recurse(node, source, containers, definitions);
+            containers.pop();
+        }
+        None => recurse(node, source, containers, definitions),
+    }
+}
+
+/// Whether `node`, a definition, is a function or method (including a variable or property
+/// holding a function).
+fn is_callable(node: Node) -> bool {
+    match node.kind() {
+        "function_item"
+        | "function_signature_item"
+        | "function_definition"
+        | "function_declaration"
+        | "generator_function_declaration"
+        | "method_definition"
+        | "method_signature"
+        | "abstract_method_signature"
+        | "method_elem"
+        | "method_declaration" => true,
+        "variable_declarator" | "pair" | "field_definition" | "public_field_definition" => node
+            .child_by_field_name("value")
+            .is_some_and(|value| value.kind() != "class"),
+        _ => false,
+    }
+}
+
+fn recurse(
+    node: Node,
+    source: &[u8],
+    containers: &mut Vec<String>,
+    definitions: &mut Vec<Definition>,
+) {
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        collect_definitions(child, source, containers, definitions);
+    }
+}
+
+/// The bare name of a Rust type such as `Foo<T>`, `crate::Foo`, or `&Foo`.
+fn type_name(node: Node, source: &[u8]) -> Option<String> {
+    match node.kind() {
+        "type_identifier" | "identifier" | "primitive_type" => {
+            node.utf8_text(source).ok().map(str::to_string)
+        }
+        "generic_type" | "reference_type" | "pointer_type" => {
+            type_name(node.child_by_field_name("type")?, source)
+        }
+        "scoped_type_identifier" => type_name(node.child_by_field_name("name")?, source),
+        _ => None,
+    }
+}
+
+fn first_of_kind<'tree>(node: Node<'tree>, kind: &str) -> Option<Node<'tree>> {
+    if node.kind() == kind {
+        return Some(node);
+    }
+    let mut cursor = node.walk();
+    let children: Vec<Node<'tree>> = node.named_children(&mut cursor).collect();
+    children
+        .into_iter()
+        .find_map(|child| first_of_kind(child, kind))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_parse_errors_for_known_languages() {
+        assert_eq!(has_errors(Path::new("a.rs"), "fn main() {}\n"), Some(false));
+        assert_eq!(has_errors(Path::new("a.rs"), "fn main() {\n"), Some(true));
+        assert_eq!(has_errors(Path::new("a.py"), "def f(:\n"), Some(true));
+        assert_eq!(has_errors(Path::new("a.rb"), "def f; end\n"), None);
+    }
+
+    fn qualified(path: &str, source: &str) -> Vec<String> {
+        definitions(Path::new(path), source)
+            .unwrap()
+            .iter()
+            .map(Definition::qualified_name)
+            .collect()
+    }
+
+    #[test]
+    fn finds_methods_with_their_containers() {
+        assert_eq!(
+            qualified(
+                "a.rs",
+                "struct Foo;\nimpl<T> Display for Foo<T> {\n    fn fmt(&self) {\n        fn helper() {}\n    }\n}\ntrait Store {\n    fn get(&self);\n}\n",
+            ),
+            ["Foo", "Foo", "Foo::fmt", "Foo::fmt::helper", "Store", "Store::get"]
+        );
+        assert_eq!(
+            qualified(
+                "a.py",
+                "class Repo:\n    @cached\n    def load(self):\n        pass\n",
+            ),
+            ["Repo", "Repo::load"]
+        );
+        assert_eq!(
+            qualified(
+                "a.go",
+                "type Store interface {\n\tGet(id int) error\n}\nfunc (s *DB) Get(id int) error { return nil }\n",
+            ),
+            ["Store", "Store::Get", "DB::Get"]
+        );
+        assert_eq!(
+            qualified(
+                "a.js",
+                "const api = {\n  fetchUser() {},\n  save: async () => {},\n};\nclass View { render() {} }\n",
+            ),
+            ["api::fetchUser", "api::save", "View", "View::render"]
+        );
+        assert_eq!(definitions(Path::new("a.rb"), "def f; end\n"), None);
+    }
+}
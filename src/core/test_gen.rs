@@ -0,0 +1,241 @@
+use crate::core::{LLMContextChunk, PromptBuilder, PromptLimits};
+use anyhow::Result;
+use ignore::WalkBuilder;
+use std::path::{Path, PathBuf};
+
+/// Files examined when looking for an existing test to imitate.
+const MAX_SCANNED_FILES: usize = 5000;
+
+/// Characters of the example test included in the prompt.
+const MAX_EXAMPLE_CHARS: usize = 6000;
+
+/// How a language's tests are written and where a generated test file for a source file goes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestConvention {
+    pub framework: String,
+    pub test_path: PathBuf,
+}
+
+fn extension(path: &Path) -> Option<String> {
+    Some(path.extension()?.to_str()?.to_lowercase())
+}
+
+/// Whether `path` looks like a test file for `ext`.
+fn is_test_file(path: &Path, ext: &str) -> bool {
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default();
+    let in_tests_dir = path.components().any(|part| {
+        matches!(
+            part.as_os_str().to_str(),
+            Some("tests" | "test" | "__tests__")
+        )
+    });
+    match ext {
+        "rs" => in_tests_dir,
+        "py" => name.starts_with("test_") || name.ends_with("_test.py"),
+        "go" => name.ends_with("_test.go"),
+        "js" | "jsx" | "ts" | "tsx" => {
+            name.contains(".test.") || name.contains(".spec.") || in_tests_dir
+        }
+        _ => false,
+    }
+}
+
+/// Whether `path` is itself a test file, which needs no generated tests.
+pub fn is_test_path(path: &Path) -> bool {
+    extension(path).is_some_and(|ext| is_test_file(path, &ext))
+}
+
+/// An existing test file in the language of `file_path`, preferring one in the same
+/// directory tree, so generated tests can follow the project's style.
+pub fn find_example_test(repo_root: &Path, file_path: &Path) -> Option<(PathBuf, String)> {
+    let ext = extension(file_path)?;
+    let walker = WalkBuilder::new(repo_root)
+        .hidden(true)
+        .git_ignore(true)
+        .git_exclude(true)
+        .build();
+    let mut best: Option<(usize, PathBuf)> = None;
+    for entry in walker.flatten().take(MAX_SCANNED_FILES) {
+        let path = entry.path();
+        let relative = path.strip_prefix(repo_root).unwrap_or(path);
+        if !path.is_file() || extension(relative).as_deref() != Some(ext.as_str()) {
+            continue;
+        }
+        if !is_test_file(relative, &ext) {
+            continue;
+        }
+        let shared = relative
+            .components()
+            .zip(file_path.components())
+            .take_while(|(a, b)| a == b)
+            .count();
+        if best.as_ref().is_none_or(|(score, _)| shared > *score) {
+            best = Some((shared, relative.to_path_buf()));
+        }
+    }
+    let (_, path) = best?;
+    let content = std::fs::read_to_string(repo_root.join(&path)).ok()?;
+    let content = match content.char_indices().nth(MAX_EXAMPLE_CHARS) {
+        Some((end, _)) => format!("{}\n[truncated]", &content[..end]),
+        None => content,
+    };
+    Some((path, content))
+}
+
+fn js_framework(repo_root: &Path) -> &'static str {
+    let package = std::fs::read_to_string(repo_root.join("package.json")).unwrap_or_default();
+    if package.contains("\"vitest\"") {
+        "Vitest"
+    } else if package.contains("\"mocha\"") {
+        "Mocha"
+    } else {
+        "Jest"
+    }
+}
+
+/// The test framework and generated test path for `file_path`, or `None` for languages
+/// without a known convention.
+pub fn convention_for(
+    repo_root: &Path,
+    file_path: &Path,
+    example: Option<&(PathBuf, String)>,
+) -> Option<TestConvention> {
+    let ext = extension(file_path)?;
+    let stem = file_path.file_stem()?.to_str()?;
+    let dir = file_path.parent().unwrap_or_else(|| Path::new(""));
+    let (framework, test_path) = match ext.as_str() {
+        "rs" => (
+            "Rust's built-in test harness (#[test], or #[tokio::test] for async code)".to_string(),
+            PathBuf::from("tests").join(format!("{}_generated.rs", stem)),
+        ),
+        "py" => {
+            let framework = match example {
+                Some((_, content)) if content.contains("unittest.TestCase") => "unittest",
+                _ => "pytest",
+            };
+            // Next to the existing tests, which pytest and unittest discovery already find.
+            let tests_dir = example
+                .and_then(|(path, _)| path.parent())
+                .map_or_else(|| PathBuf::from("tests"), Path::to_path_buf);
+            (
+                framework.to_string(),
+                tests_dir.join(format!("test_{}.py", stem)),
+            )
+        }
+        "go" => (
+            "Go's testing package".to_string(),
+            dir.join(format!("{}_test.go", stem)),
+        ),
+        "js" | "jsx" | "ts" | "tsx" => (
+            js_framework(repo_root).to_string(),
+            dir.join(format!("{}.test.{}", stem, ext)),
+        ),
+        _ => return None,
+    };
+    Some(TestConvention {
+        framework,
+        test_path,
+    })
+}
+
+const SYSTEM_PROMPT: &str = "You are a test generation expert. Write focused unit tests for the changed functions you are given, covering their normal behaviour, edge cases, and error paths. Follow the conventions of the example test when one is given (imports, helpers, naming, assertion style). Only call code that the definitions show exists. Reply with the complete test file in a single fenced code block and nothing else.";
+
+/// Prompt asking for a test file covering `functions` (name and source) of `file_path`.
+pub fn build_prompt(
+    file_path: &Path,
+    functions: &[(String, String)],
+    convention: &TestConvention,
+    example: Option<&(PathBuf, String)>,
+    context: &[LLMContextChunk],
+    limits: &PromptLimits,
+) -> Result<(String, String)> {
+    let mut prompt = format!(
+        "Write {} tests for these functions changed in `{}`. The tests will be saved as `{}`.\n",
+        convention.framework,
+        file_path.display(),
+        convention.test_path.display()
+    );
+    for (name, source) in functions {
+        prompt.push_str(&format!(
+            "\n<function name=\"{}\">\n{}\n</function>\n",
+            name, source
+        ));
+    }
+    if let Some((path, content)) = example {
+        prompt.push_str(&format!(
+            "\n<example_test path=\"{}\">\n{}\n</example_test>\n",
+            path.display(),
+            content
+        ));
+    }
+    let builder = PromptBuilder::new(crate::core::prompt::PromptConfig {
+        limits: *limits,
+        ..Default::default()
+    });
+    let context = builder.format_context(context)?;
+    if !context.trim().is_empty() {
+        prompt.push_str(&format!("\n<context>{}</context>\n", context));
+    }
+    Ok((SYSTEM_PROMPT.to_string(), prompt))
+}
+
+/// The code inside the first fenced block of `response`, or the whole response.
+pub fn extract_code(response: &str) -> String {
+    let Some(start) = response.find("```") else {
+        return response.trim().to_string();
+    };
+    let body = &response[start + 3..];
+    let body = body.split_once('\n').map_or("", |(_, rest)| rest);
+    let body = body.find("```").map_or(body, |end| &body[..end]);
+    format!("{}\n", body.trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::DiffParser;
+
+    #[test]
+    fn finds_changed_functions_and_test_conventions() {
+        let repo = tempfile::tempdir().unwrap();
+        for (path, content) in [
+            ("src/cart.py", "class Cart:\n    def total(self):\n        return sum(self.items) - self.discount\n\n    def empty(self):\n        return not self.items\n"),
+            ("tests/unit/test_orders.py", "import unittest\n\nclass OrdersTest(unittest.TestCase):\n    pass\n"),
+            ("web/app.test.js", "test('x', () => {});\n"),
+        ] {
+            let path = repo.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+
+        let diff = DiffParser::parse_unified_diff(
+            "--- a/src/cart.py\n+++ b/src/cart.py\n@@ -1,3 +1,3 @@\n class Cart:\n     def total(self):\n-        return sum(self.items)\n+        return sum(self.items) - self.discount\n",
+        )
+        .unwrap()
+        .remove(0);
+        let source = std::fs::read_to_string(repo.path().join("src/cart.py")).unwrap();
+        let lines: Vec<&str> = source.lines().collect();
+        assert_eq!(
+            crate::core::function_scope::changed_functions(&diff, &lines),
+            vec![("total".to_string(), (2, 3))]
+        );
+
+        let example = find_example_test(repo.path(), Path::new("src/cart.py")).unwrap();
+        assert_eq!(example.0, PathBuf::from("tests/unit/test_orders.py"));
+        let convention =
+            convention_for(repo.path(), Path::new("src/cart.py"), Some(&example)).unwrap();
+        assert_eq!(convention.framework, "unittest");
+        assert_eq!(
+            convention.test_path,
+            PathBuf::from("tests/unit/test_cart.py")
+        );
+
+        assert_eq!(
+            extract_code("Here you go:\n```python\nimport unittest\n```\nDone."),
+            "import unittest\n"
+        );
+    }
+}
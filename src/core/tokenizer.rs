@@ -0,0 +1,231 @@
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tiktoken_rs::CoreBPE;
+
+/// Tokens reserved for the system prompt, instructions and prompt scaffolding.
+const PROMPT_OVERHEAD_TOKENS: usize = 2000;
+const DEFAULT_CONTEXT_WINDOW: usize = 8192;
+
+static CL100K: Lazy<Option<CoreBPE>> = Lazy::new(|| tiktoken_rs::cl100k_base().ok());
+static O200K: Lazy<Option<CoreBPE>> = Lazy::new(|| tiktoken_rs::o200k_base().ok());
+
+/// Known context windows, matched by model-name prefix. More specific prefixes come first.
+const CONTEXT_WINDOWS: &[(&str, usize)] = &[
+    ("gpt-4.1", 1_047_576),
+    ("gpt-4o", 128_000),
+    ("gpt-4-turbo", 128_000),
+    ("gpt-4-32k", 32_768),
+    ("gpt-4", 8_192),
+    ("gpt-3.5-turbo", 16_385),
+    ("gpt-5", 400_000),
+    ("o1-mini", 128_000),
+    ("o1", 200_000),
+    ("o3", 200_000),
+    ("o4", 200_000),
+    ("claude", 200_000),
+    ("gemini", 1_000_000),
+    ("codestral", 256_000),
+    ("mistral", 128_000),
+    ("open-mistral", 128_000),
+    ("open-mixtral", 64_000),
+    ("ministral", 128_000),
+    ("pixtral", 128_000),
+    ("deepseek", 64_000),
+    ("llama-3.1", 128_000),
+    ("llama-3.3", 128_000),
+    ("llama3.1", 128_000),
+    ("llama3.2", 128_000),
+    ("llama3.3", 128_000),
+    ("llama3", 8_192),
+    ("qwen2.5-coder", 32_768),
+    ("codellama", 16_384),
+    ("mixtral", 32_768),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Tokenizer {
+    Cl100k,
+    O200k,
+    /// Character-based estimate for models without a public tokenizer.
+    #[default]
+    Heuristic,
+}
+
+impl Tokenizer {
+    pub fn for_model(model: &str) -> Self {
+        let name = normalize_model_name(model);
+        if ["gpt-4o", "gpt-4.1", "gpt-5", "o1", "o3", "o4"]
+            .iter()
+            .any(|prefix| name.starts_with(prefix))
+        {
+            Self::O200k
+        } else if name.starts_with("gpt-4") || name.starts_with("gpt-3.5") {
+            Self::Cl100k
+        } else {
+            Self::Heuristic
+        }
+    }
+
+    pub fn count_tokens(&self, text: &str) -> usize {
+        let bpe = match self {
+            Self::Cl100k => CL100K.as_ref(),
+            Self::O200k => O200K.as_ref(),
+            Self::Heuristic => None,
+        };
+        match bpe {
+            Some(bpe) => bpe.encode_ordinary(text).len(),
+            None => estimate_tokens(text),
+        }
+    }
+}
+
+/// Roughly 3.5 characters per token for code, rounded up so truncation errs on the safe side.
+fn estimate_tokens(text: &str) -> usize {
+    (text.chars().count() * 2).div_ceil(7)
+}
+
+pub(crate) fn normalize_model_name(model: &str) -> String {
+    let lower = model.trim().to_lowercase();
+    let without_route = [
+        "openrouter:",
+        "ollama:",
+        "groq:",
+        "mistral:",
+        "deepseek:",
+        "local:",
+    ]
+    .iter()
+    .find_map(|prefix| lower.strip_prefix(prefix))
+    .unwrap_or(&lower);
+    without_route
+        .rsplit('/')
+        .next()
+        .unwrap_or(without_route)
+        .to_string()
+}
+
+pub fn context_window(model: &str) -> usize {
+    let name = normalize_model_name(model);
+    CONTEXT_WINDOWS
+        .iter()
+        .find(|(prefix, _)| name.starts_with(prefix))
+        .map(|(_, window)| *window)
+        .unwrap_or(DEFAULT_CONTEXT_WINDOW)
+}
+
+/// Character and token caps for the diff and context sections of a prompt. Zero disables a cap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct PromptLimits {
+    pub tokenizer: Tokenizer,
+    pub max_context_chars: usize,
+    pub max_diff_chars: usize,
+    pub max_context_tokens: usize,
+    pub max_diff_tokens: usize,
+}
+
+impl PromptLimits {
+    /// Splits the model's usable input window two-thirds diff, one-third context.
+    pub fn for_model(model: &str, context_window_tokens: usize, max_output_tokens: usize) -> Self {
+        let available = context_window_tokens
+            .saturating_sub(max_output_tokens)
+            .saturating_sub(PROMPT_OVERHEAD_TOKENS);
+        let max_diff_tokens = (available * 2 / 3).max(1);
+        Self {
+            tokenizer: Tokenizer::for_model(model),
+            max_context_chars: 0,
+            max_diff_chars: 0,
+            max_context_tokens: available.saturating_sub(max_diff_tokens).max(1),
+            max_diff_tokens,
+        }
+    }
+
+    pub fn diff_budget(&self) -> PromptBudget {
+        PromptBudget::new(self.tokenizer, self.max_diff_chars, self.max_diff_tokens)
+    }
+
+    pub fn context_budget(&self) -> PromptBudget {
+        PromptBudget::new(
+            self.tokenizer,
+            self.max_context_chars,
+            self.max_context_tokens,
+        )
+    }
+}
+
+/// Tracks how much of a prompt section has been filled against its char and token caps.
+#[derive(Debug, Clone)]
+pub struct PromptBudget {
+    tokenizer: Tokenizer,
+    max_chars: usize,
+    max_tokens: usize,
+    used_chars: usize,
+    used_tokens: usize,
+}
+
+impl PromptBudget {
+    pub fn new(tokenizer: Tokenizer, max_chars: usize, max_tokens: usize) -> Self {
+        Self {
+            tokenizer,
+            max_chars,
+            max_tokens,
+            used_chars: 0,
+            used_tokens: 0,
+        }
+    }
+
+    /// Reserves room for `text`, returning false (and reserving nothing) if it would overflow.
+    pub fn try_add(&mut self, text: &str) -> bool {
+        let chars = self.used_chars.saturating_add(text.len());
+        if self.max_chars > 0 && chars > self.max_chars {
+            return false;
+        }
+        let tokens = if self.max_tokens > 0 {
+            let tokens = self
+                .used_tokens
+                .saturating_add(self.tokenizer.count_tokens(text));
+            if tokens > self.max_tokens {
+                return false;
+            }
+            tokens
+        } else {
+            self.used_tokens
+        };
+        self.used_chars = chars;
+        self.used_tokens = tokens;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_tokenizer_and_window_from_model_name() {
+        assert_eq!(Tokenizer::for_model("gpt-4o-mini"), Tokenizer::O200k);
+        assert_eq!(Tokenizer::for_model("gpt-4-turbo"), Tokenizer::Cl100k);
+        assert_eq!(
+            Tokenizer::for_model("claude-3-5-sonnet-20241022"),
+            Tokenizer::Heuristic
+        );
+        assert_eq!(context_window("openrouter:openai/gpt-4o"), 128_000);
+        assert_eq!(context_window("gpt-4"), 8_192);
+        assert_eq!(context_window("ollama:llama3.1:8b"), 128_000);
+        assert_eq!(context_window("some-unknown-model"), DEFAULT_CONTEXT_WINDOW);
+    }
+
+    #[test]
+    fn budget_stops_at_token_limit() {
+        let tokenizer = Tokenizer::Cl100k;
+        let line = "let value = compute(input);\n";
+        let per_line = tokenizer.count_tokens(line);
+        assert!(per_line > 0 && per_line < line.len());
+
+        let mut budget = PromptBudget::new(tokenizer, 0, per_line * 3);
+        assert!(budget.try_add(line));
+        assert!(budget.try_add(line));
+        assert!(budget.try_add(line));
+        assert!(!budget.try_add(line));
+    }
+}
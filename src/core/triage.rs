@@ -0,0 +1,169 @@
+use crate::adapters::llm::{LLMAdapter, LLMRequest};
+use crate::core::diff_parser::ChangeType;
+use crate::core::UnifiedDiff;
+use serde::Deserialize;
+use std::collections::HashMap;
+use tracing::{debug, info, warn};
+
+const SYSTEM_PROMPT: &str = "You triage code changes before an in-depth review. Rate each numbered hunk for the risk that it introduces a bug, security hole, or performance problem: 0.0 for trivial changes (formatting, comments, docs, renames, log messages, version bumps), 1.0 for risky ones (logic, error handling, concurrency, input handling, security-sensitive code). Reply with only JSON: {\"hunks\": [{\"hunk\": 1, \"risk\": 0.0}]}.";
+
+/// Changed lines shown per hunk; the rest is summarized, since the model only needs the gist.
+const MAX_LINES_PER_HUNK: usize = 80;
+
+#[derive(Debug, Deserialize)]
+struct Scores {
+    hunks: Vec<HunkScore>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HunkScore {
+    hunk: usize,
+    risk: f32,
+}
+
+fn render_hunks(diff: &UnifiedDiff) -> String {
+    let mut output = format!("File: {}\n", diff.file_path.display());
+    for (index, hunk) in diff.hunks.iter().enumerate() {
+        output.push_str(&format!(
+            "\n### Hunk {} (lines {}-{})\n",
+            index + 1,
+            hunk.new_start,
+            hunk.new_start + hunk.new_lines
+        ));
+        for change in hunk.changes.iter().take(MAX_LINES_PER_HUNK) {
+            let sign = match change.change_type {
+                ChangeType::Added => '+',
+                ChangeType::Removed => '-',
+                ChangeType::Context => ' ',
+            };
+            output.push_str(&format!("{}{}\n", sign, change.content));
+        }
+        if hunk.changes.len() > MAX_LINES_PER_HUNK {
+            output.push_str(&format!(
+                "... {} more lines\n",
+                hunk.changes.len() - MAX_LINES_PER_HUNK
+            ));
+        }
+    }
+    output
+}
+
+fn parse_scores(content: &str) -> Option<HashMap<usize, f32>> {
+    let start = content.find('{')?;
+    let end = content.rfind('}')?;
+    let scores: Scores = serde_json::from_str(content.get(start..=end)?).ok()?;
+    Some(
+        scores
+            .hunks
+            .into_iter()
+            .map(|score| (score.hunk, score.risk))
+            .collect(),
+    )
+}
+
+/// Asks the screening model to rate each hunk of `diff` and keeps only the hunks rated at or
+/// above `threshold`, so trivial changes never reach the review model. Hunks the model does
+/// not rate are kept, as is the whole diff when the request fails or its answer cannot be read.
+pub async fn triage_hunks(
+    adapter: &dyn LLMAdapter,
+    mut diff: UnifiedDiff,
+    threshold: f32,
+) -> UnifiedDiff {
+    if diff.hunks.is_empty() {
+        return diff;
+    }
+    let request = LLMRequest {
+        system_prompt: SYSTEM_PROMPT.to_string(),
+        user_prompt: render_hunks(&diff),
+        temperature: Some(0.0),
+        max_tokens: None,
+        cached_context: None,
+    };
+    let scores = match adapter.complete(request).await {
+        Ok(response) => match parse_scores(&response.content) {
+            Some(scores) => scores,
+            None => {
+                debug!("Unreadable triage answer: {}", response.content);
+                return diff;
+            }
+        },
+        Err(err) => {
+            warn!(
+                "Triage of {} failed, reviewing every hunk: {}",
+                diff.file_path.display(),
+                err
+            );
+            return diff;
+        }
+    };
+
+    let total = diff.hunks.len();
+    let mut number = 0;
+    diff.hunks.retain(|_| {
+        number += 1;
+        scores
+            .get(&number)
+            .is_none_or(|risk| !risk.is_finite() || *risk >= threshold)
+    });
+    if diff.hunks.len() < total {
+        info!(
+            "Triage: {} of {} hunk(s) in {} look trivial; not sending them for review",
+            total - diff.hunks.len(),
+            total,
+            diff.file_path.display()
+        );
+    }
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::llm::LLMResponse;
+    use anyhow::Result;
+    use async_trait::async_trait;
+
+    /// Rates hunks that touch `unsafe` as risky and everything else as trivial, leaving hunk 4
+    /// unrated.
+    struct Screener;
+
+    #[async_trait]
+    impl LLMAdapter for Screener {
+        async fn complete(&self, request: LLMRequest) -> Result<LLMResponse> {
+            let scores: Vec<String> = request
+                .user_prompt
+                .split("### Hunk ")
+                .skip(1)
+                .filter(|hunk| !hunk.starts_with('4'))
+                .map(|hunk| {
+                    let number = hunk.split(' ').next().unwrap();
+                    let risk = if hunk.contains("unsafe") { 0.9 } else { 0.1 };
+                    format!("{{\"hunk\": {}, \"risk\": {}}}", number, risk)
+                })
+                .collect();
+            Ok(LLMResponse {
+                content: format!("```json\n{{\"hunks\": [{}]}}\n```", scores.join(", ")),
+                model: "screener".to_string(),
+                usage: None,
+            })
+        }
+
+        fn _model_name(&self) -> &str {
+            "screener"
+        }
+    }
+
+    #[tokio::test]
+    async fn keeps_only_risky_and_unrated_hunks() {
+        let diff = crate::core::DiffParser::parse_unified_diff(
+            "--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1 +1 @@\n-// Adds numbers\n+// Adds two numbers\n@@ -10 +10 @@\n-let p = ptr;\n+let p = unsafe { &*ptr };\n@@ -20 +20 @@\n-const NAME: &str = \"a\";\n+const NAME: &str = \"b\";\n@@ -30 +30 @@\n-let x = 1;\n+let x = 2;\n",
+        )
+        .unwrap()
+        .remove(0);
+
+        let triaged = triage_hunks(&Screener, diff, 0.5).await;
+
+        let kept: Vec<usize> = triaged.hunks.iter().map(|hunk| hunk.new_start).collect();
+        assert_eq!(kept, vec![10, 30]);
+    }
+}
@@ -0,0 +1,186 @@
+use crate::adapters::llm::{LLMAdapter, LLMRequest};
+use crate::core::comment::{Comment, Severity};
+use crate::core::diff_parser::ChangeType;
+use crate::core::UnifiedDiff;
+use serde::Deserialize;
+use tracing::{debug, warn};
+
+const SYSTEM_PROMPT: &str = "You double-check code review findings. Given one finding and the code it refers to, decide whether it describes a real problem in this exact code, not a hypothetical one or one the code already handles. Reply with only JSON: {\"valid\": true or false, \"reason\": \"one sentence\"}.";
+
+#[derive(Debug, Deserialize)]
+struct Verdict {
+    valid: bool,
+    #[serde(default)]
+    reason: String,
+}
+
+/// The hunk of `diff` containing `line`, one numbered line per change.
+fn code_around(diff: &UnifiedDiff, line: usize) -> Option<String> {
+    let hunk = diff.hunks.iter().find(|hunk| {
+        hunk.changes
+            .iter()
+            .any(|change| change.new_line_no == Some(line) || change.old_line_no == Some(line))
+    })?;
+    Some(
+        hunk.changes
+            .iter()
+            .map(|change| {
+                let (sign, number) = match change.change_type {
+                    ChangeType::Added => ('+', change.new_line_no),
+                    ChangeType::Removed => ('-', change.old_line_no),
+                    ChangeType::Context => (' ', change.new_line_no),
+                };
+                let number = number.map(|n| n.to_string()).unwrap_or_default();
+                format!("{:>5} {}{}", number, sign, change.content)
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+    )
+}
+
+fn parse_verdict(content: &str) -> Option<Verdict> {
+    let start = content.find('{')?;
+    let end = content.rfind('}')?;
+    serde_json::from_str(content.get(start..=end)?).ok()
+}
+
+/// Asks the model to confirm each comment whose severity is in `severities`, against the hunk
+/// it points at, and drops the ones it retracts. Comments are kept when the model fails or its
+/// answer cannot be read.
+pub async fn verify_findings(
+    adapter: &dyn LLMAdapter,
+    diff: &UnifiedDiff,
+    comments: Vec<Comment>,
+    severities: &[Severity],
+) -> Vec<Comment> {
+    let mut verified = Vec::with_capacity(comments.len());
+    for comment in comments {
+        if !severities.contains(&comment.severity) {
+            verified.push(comment);
+            continue;
+        }
+        let Some(code) = code_around(diff, comment.line_number) else {
+            verified.push(comment);
+            continue;
+        };
+        let request = LLMRequest {
+            system_prompt: SYSTEM_PROMPT.to_string(),
+            user_prompt: format!(
+                "File: {}\nLine: {}\nFinding ({:?}, {:?}): {}\n\nCode:\n```\n{}\n```",
+                comment.file_path.display(),
+                comment.line_number,
+                comment.severity,
+                comment.category,
+                comment.content,
+                code
+            ),
+            temperature: Some(0.0),
+            max_tokens: Some(200),
+            cached_context: None,
+        };
+        match adapter.complete(request).await {
+            Ok(response) => match parse_verdict(&response.content) {
+                Some(verdict) if !verdict.valid => {
+                    debug!(
+                        "Dropping finding at {}:{} after verification: {}",
+                        comment.file_path.display(),
+                        comment.line_number,
+                        verdict.reason
+                    );
+                    continue;
+                }
+                Some(_) => {}
+                None => debug!("Unreadable verification answer: {}", response.content),
+            },
+            Err(err) => warn!("Finding verification failed, keeping finding: {}", err),
+        }
+        verified.push(comment);
+    }
+    verified
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::llm::LLMResponse;
+    use crate::core::comment::{Category, FixEffort};
+    use anyhow::Result;
+    use async_trait::async_trait;
+    use std::sync::Mutex;
+
+    /// Retracts findings that mention "overflow".
+    struct Reviewer {
+        prompts: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl LLMAdapter for Reviewer {
+        async fn complete(&self, request: LLMRequest) -> Result<LLMResponse> {
+            let valid = !request.user_prompt.contains("overflow");
+            self.prompts.lock().unwrap().push(request.user_prompt);
+            Ok(LLMResponse {
+                content: format!(
+                    "```json\n{{\"valid\": {}, \"reason\": \"checked\"}}\n```",
+                    valid
+                ),
+                model: "reviewer".to_string(),
+                usage: None,
+            })
+        }
+
+        fn _model_name(&self) -> &str {
+            "reviewer"
+        }
+    }
+
+    fn comment(content: &str, severity: Severity) -> Comment {
+        Comment {
+            id: content.to_string(),
+            file_path: "src/lib.rs".into(),
+            line_number: 2,
+            content: content.to_string(),
+            severity,
+            category: Category::Bug,
+            suggestion: None,
+            confidence: 0.8,
+            code_suggestion: None,
+            tags: Vec::new(),
+            fix_effort: FixEffort::Low,
+            owners: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn drops_findings_the_model_retracts() {
+        let diff = crate::core::DiffParser::parse_unified_diff(
+            "--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1,2 +1,2 @@\n fn add(a: u8, b: u8) -> u8 {\n-    a + b\n+    a.wrapping_add(b)\n",
+        )
+        .unwrap()
+        .remove(0);
+        let reviewer = Reviewer {
+            prompts: Mutex::new(Vec::new()),
+        };
+        let comments = vec![
+            comment("Possible overflow in add.", Severity::Error),
+            comment("Wrapping hides a logic bug.", Severity::Warning),
+            comment("Minor overflow nit.", Severity::Info),
+        ];
+
+        let kept = verify_findings(
+            &reviewer,
+            &diff,
+            comments,
+            &[Severity::Error, Severity::Warning],
+        )
+        .await;
+
+        let kept: Vec<_> = kept.iter().map(|c| c.content.as_str()).collect();
+        assert_eq!(
+            kept,
+            vec!["Wrapping hides a logic bug.", "Minor overflow nit."]
+        );
+        let prompts = reviewer.prompts.lock().unwrap();
+        assert_eq!(prompts.len(), 2);
+        assert!(prompts[0].contains("    2 +    a.wrapping_add(b)"));
+    }
+}
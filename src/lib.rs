@@ -0,0 +1,13 @@
+//! DiffScope's review engine as a library. [`ReviewEngine`] runs the same pipeline as
+//! `diffscope review`: context gathering, plugins, the LLM review, and filtering.
+
+pub mod adapters;
+pub mod config;
+pub mod core;
+pub mod plugins;
+pub mod providers;
+pub mod review;
+
+pub use config::Config;
+pub use core::Comment;
+pub use review::ReviewEngine;
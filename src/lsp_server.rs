@@ -0,0 +1,352 @@
+use crate::core;
+use crate::core::comment::Severity;
+use crate::ReviewEngine;
+use anyhow::Result;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWriteExt, BufReader};
+use tracing::{info, warn};
+
+struct LspState {
+    engine: ReviewEngine,
+    /// Per document URI: the latest save, and the findings published for it.
+    documents: Mutex<HashMap<String, (u64, Vec<core::Comment>)>>,
+    stdout: tokio::sync::Mutex<tokio::io::Stdout>,
+}
+
+/// Runs a language server on stdin/stdout that reviews each saved file's changes against HEAD
+/// and publishes the findings as diagnostics, with code suggestions offered as quick fixes.
+pub async fn serve_stdio(engine: ReviewEngine) -> Result<()> {
+    let state = Arc::new(LspState {
+        engine,
+        documents: Mutex::new(HashMap::new()),
+        stdout: tokio::sync::Mutex::new(tokio::io::stdout()),
+    });
+    let indexing = state.clone();
+    tokio::task::spawn_blocking(move || indexing.engine.symbol_index().is_some()).await?;
+
+    info!("Language server ready on stdio");
+    let mut reader = BufReader::new(tokio::io::stdin());
+    while let Some(message) = read_message(&mut reader).await? {
+        let id = message.get("id").cloned();
+        let params = &message["params"];
+        let uri = params["textDocument"]["uri"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        match message["method"].as_str().unwrap_or_default() {
+            "initialize" => {
+                let capabilities = json!({
+                    "capabilities": {
+                        "textDocumentSync": { "openClose": true, "save": { "includeText": false } },
+                        "codeActionProvider": { "codeActionKinds": ["quickfix"] },
+                    },
+                    "serverInfo": { "name": "diffscope", "version": env!("CARGO_PKG_VERSION") },
+                });
+                respond(&state, id, capabilities).await?;
+            }
+            "textDocument/didSave" => {
+                tokio::spawn(review_document(state.clone(), uri));
+            }
+            "textDocument/didClose" => {
+                state.documents.lock().unwrap().remove(&uri);
+                publish(&state, &uri, Vec::new()).await?;
+            }
+            "textDocument/codeAction" => {
+                let findings = state
+                    .documents
+                    .lock()
+                    .unwrap()
+                    .get(&uri)
+                    .map(|(_, findings)| findings.clone())
+                    .unwrap_or_default();
+                let source = match uri_to_path(&uri) {
+                    Some(path) => tokio::fs::read_to_string(path).await.unwrap_or_default(),
+                    None => String::new(),
+                };
+                let first = params["range"]["start"]["line"].as_u64().unwrap_or(0) as usize;
+                let last = params["range"]["end"]["line"].as_u64().unwrap_or(0) as usize;
+                let actions = code_actions(&uri, &source, &findings, (first, last));
+                respond(&state, id, Value::Array(actions)).await?;
+            }
+            "shutdown" => respond(&state, id, Value::Null).await?,
+            "exit" => break,
+            method if id.is_some() => {
+                let error =
+                    json!({ "code": -32601, "message": format!("method not found: {}", method) });
+                send(
+                    &state,
+                    json!({ "jsonrpc": "2.0", "id": id, "error": error }),
+                )
+                .await?;
+            }
+            // Other notifications (didOpen, didChange, ...) need no answer.
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+async fn review_document(state: Arc<LspState>, uri: String) {
+    let generation = {
+        let mut documents = state.documents.lock().unwrap();
+        let entry = documents.entry(uri.clone()).or_default();
+        entry.0 += 1;
+        entry.0
+    };
+    let findings = match review_file(&state.engine, &uri).await {
+        Ok(findings) => findings,
+        Err(err) => {
+            warn!("Review of {} failed: {:#}", uri, err);
+            let message = format!("diffscope: review failed: {:#}", err);
+            let notification = json!({
+                "jsonrpc": "2.0",
+                "method": "window/logMessage",
+                "params": { "type": 1, "message": message },
+            });
+            let _ = send(&state, notification).await;
+            return;
+        }
+    };
+    {
+        let mut documents = state.documents.lock().unwrap();
+        match documents.get_mut(&uri) {
+            // A later save is being reviewed; its findings will replace these.
+            Some(entry) if entry.0 == generation => entry.1 = findings.clone(),
+            _ => return,
+        }
+    }
+    if let Err(err) = publish(&state, &uri, findings).await {
+        warn!("Failed to publish diagnostics for {}: {:#}", uri, err);
+    }
+}
+
+/// Findings on the file at `uri` for its uncommitted changes.
+async fn review_file(engine: &ReviewEngine, uri: &str) -> Result<Vec<core::Comment>> {
+    let path = uri_to_path(uri).ok_or_else(|| anyhow::anyhow!("not a file URI: {}", uri))?;
+    let root = engine
+        .repo_root()
+        .canonicalize()
+        .unwrap_or_else(|_| engine.repo_root().to_path_buf());
+    let path = path.canonicalize().unwrap_or(path);
+    let Ok(relative) = path.strip_prefix(&root) else {
+        return Ok(Vec::new());
+    };
+    let diff = core::GitIntegration::new(&root)?.get_path_diff(relative)?;
+    if diff.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut findings = engine.review_diff(&diff).await?;
+    findings.retain(|comment| comment.file_path == relative);
+    Ok(findings)
+}
+
+async fn publish(state: &LspState, uri: &str, findings: Vec<core::Comment>) -> Result<()> {
+    let source = match uri_to_path(uri) {
+        Some(path) => tokio::fs::read_to_string(path).await.unwrap_or_default(),
+        None => String::new(),
+    };
+    let lines: Vec<&str> = source.lines().collect();
+    let diagnostics: Vec<Value> = findings
+        .iter()
+        .map(|comment| diagnostic(comment, &lines))
+        .collect();
+    send(
+        state,
+        json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/publishDiagnostics",
+            "params": { "uri": uri, "diagnostics": diagnostics },
+        }),
+    )
+    .await
+}
+
+/// The LSP diagnostic for `comment`, spanning its whole line of `lines`.
+fn diagnostic(comment: &core::Comment, lines: &[&str]) -> Value {
+    let line = comment.line_number.saturating_sub(1);
+    let width = lines
+        .get(line)
+        .map_or(0, |text| text.encode_utf16().count());
+    let severity = match comment.severity {
+        Severity::Error => 1,
+        Severity::Warning => 2,
+        Severity::Info => 3,
+        Severity::Suggestion => 4,
+    };
+    let mut message = comment.content.clone();
+    if let Some(suggestion) = &comment.suggestion {
+        message.push_str(&format!("\n\nSuggestion: {}", suggestion));
+    }
+    json!({
+        "range": {
+            "start": { "line": line, "character": 0 },
+            "end": { "line": line, "character": width },
+        },
+        "severity": severity,
+        "source": "diffscope",
+        "code": format!("{:?}", comment.category).to_lowercase(),
+        "message": message,
+    })
+}
+
+/// Quick fixes for the findings on lines `first..=last` (0-based) that carry a code
+/// suggestion still matching `source`.
+fn code_actions(
+    uri: &str,
+    source: &str,
+    findings: &[core::Comment],
+    (first, last): (usize, usize),
+) -> Vec<Value> {
+    let lines: Vec<&str> = source.lines().collect();
+    findings
+        .iter()
+        .filter(|comment| (first..=last).contains(&comment.line_number.saturating_sub(1)))
+        .filter_map(|comment| {
+            let code = comment.code_suggestion.as_ref()?;
+            let placement = core::suggestion::place(&lines, comment.line_number, code).ok()?;
+            let mut new_text = placement.replacement.join("\n");
+            new_text.push('\n');
+            let edit = json!({
+                "range": {
+                    "start": { "line": placement.start, "character": 0 },
+                    "end": { "line": placement.end, "character": 0 },
+                },
+                "newText": new_text,
+            });
+            let summary = if code.explanation.trim().is_empty() {
+                comment.content.lines().next().unwrap_or_default()
+            } else {
+                code.explanation.trim()
+            };
+            Some(json!({
+                "title": format!("diffscope: {}", summary),
+                "kind": "quickfix",
+                "diagnostics": [diagnostic(comment, &lines)],
+                "isPreferred": true,
+                "edit": { "changes": { uri: [edit] } },
+            }))
+        })
+        .collect()
+}
+
+fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix("file://")?;
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| path.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    Some(Path::new(&String::from_utf8(decoded).ok()?).to_path_buf())
+}
+
+/// Reads one `Content-Length`-framed message, or `None` once the client closes the stream.
+async fn read_message<R: AsyncRead + Unpin>(reader: &mut BufReader<R>) -> Result<Option<Value>> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).await? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = Some(value.trim().parse::<usize>()?);
+            }
+        }
+    }
+    let length = length.ok_or_else(|| anyhow::anyhow!("message without Content-Length"))?;
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).await?;
+    Ok(Some(serde_json::from_slice(&body)?))
+}
+
+async fn respond(state: &LspState, id: Option<Value>, result: Value) -> Result<()> {
+    send(
+        state,
+        json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+    )
+    .await
+}
+
+async fn send(state: &LspState, message: Value) -> Result<()> {
+    let body = message.to_string();
+    let mut stdout = state.stdout.lock().await;
+    stdout
+        .write_all(format!("Content-Length: {}\r\n\r\n{}", body.len(), body).as_bytes())
+        .await?;
+    stdout.flush().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::comment::{Category, CodeSuggestion};
+
+    #[tokio::test]
+    async fn frames_messages_and_turns_findings_into_diagnostics_and_fixes() {
+        let stream = b"Content-Length: 17\r\nContent-Type: application/vscode-jsonrpc\r\n\r\n{\"method\":\"exit\"}";
+        let mut reader = BufReader::new(&stream[..]);
+        let message = read_message(&mut reader).await.unwrap().unwrap();
+        assert_eq!(message["method"], "exit");
+        assert!(read_message(&mut reader).await.unwrap().is_none());
+
+        assert_eq!(
+            uri_to_path("file:///home/dev/my%20app/src/lib.rs"),
+            Some(PathBuf::from("/home/dev/my app/src/lib.rs"))
+        );
+
+        let source = "fn load() -> u8 {\n    let n = parse().unwrap();\n    n\n}\n";
+        let comment = core::Comment {
+            category: Category::BestPractice,
+            code_suggestion: Some(CodeSuggestion {
+                original_code: "let n = parse().unwrap();".to_string(),
+                suggested_code: "let n = parse()?;".to_string(),
+                explanation: "Propagate the error".to_string(),
+                diff: String::new(),
+            }),
+            ..crate::tests::finding("src/lib.rs", 2, "unwrap panics on bad input")
+        };
+
+        let lines: Vec<&str> = source.lines().collect();
+        let diagnostic = diagnostic(&comment, &lines);
+        assert_eq!(diagnostic["range"]["start"]["line"], 1);
+        assert_eq!(diagnostic["range"]["end"]["character"], 29);
+        assert_eq!(diagnostic["severity"], 2);
+
+        let uri = "file:///repo/src/lib.rs";
+        assert!(code_actions(uri, source, std::slice::from_ref(&comment), (0, 0)).is_empty());
+        let actions = code_actions(uri, source, &[comment], (1, 1));
+        assert_eq!(actions[0]["title"], "diffscope: Propagate the error");
+        assert_eq!(
+            actions[0]["edit"]["changes"][uri][0],
+            json!({
+                "range": {
+                    "start": { "line": 1, "character": 0 },
+                    "end": { "line": 2, "character": 0 },
+                },
+                "newText": "    let n = parse()?;\n",
+            })
+        );
+    }
+}
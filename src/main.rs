@@ -1,13 +1,21 @@
-mod adapters;
-mod config;
-mod core;
-mod plugins;
-
-use anyhow::Result;
+mod lsp_server;
+mod mcp;
+mod server;
+
+use diffscope::review::ReviewOutcome;
+use diffscope::review::{
+    apply_category_weights, apply_code_owners, apply_confidence_calibration,
+    apply_confidence_threshold, apply_severity_overrides, base_prompt_config, build_follow_up,
+    build_repo_map, build_review_cache, build_review_guidance, build_semantic_search,
+    build_symbol_index, build_triage_adapter, context_line_ranges, extract_symbols,
+    extract_symbols_from_diff, fetch_caller_context, file_prompt_config, filter_comments_for_diff,
+    is_batchable, load_feedback, load_hotspot_notes, pack_batched_files, resolve_lsp_commands,
+    skipped_lockfile_findings, BatchedFile, ContextSources, FileContext,
+};
+use diffscope::{adapters, config, core, plugins, providers, ReviewEngine};
+
+use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
-use once_cell::sync::Lazy;
-use regex::Regex;
-use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::io::IsTerminal;
 use std::path::{Path, PathBuf};
@@ -22,12 +30,41 @@ struct Cli {
     #[command(subcommand)]
     command: Commands,
 
-    #[arg(long, global = true, default_value = "gpt-4o")]
-    model: String,
+    #[arg(
+        long,
+        global = true,
+        help = "Model to use [default: `model` from the config, else gpt-4o]"
+    )]
+    model: Option<String>,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "NAME",
+        help = "Apply a named profile from the config's `profiles` section (or set DIFFSCOPE_PROFILE)"
+    )]
+    profile: Option<String>,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "PROFILE",
+        value_parser = clap::builder::PossibleValuesParser::new(core::review_presets::profile_names()),
+        help = "Review tone (balanced, chill, assertive) or domain preset for this run"
+    )]
+    review_profile: Option<String>,
 
     #[arg(long, global = true)]
     prompt: Option<String>,
 
+    #[arg(
+        long,
+        global = true,
+        value_name = "TEXT",
+        help = "Ticket or issue the change implements, for `prompt_template`"
+    )]
+    ticket: Option<String>,
+
     #[arg(long, global = true)]
     temperature: Option<f32>,
 
@@ -45,6 +82,13 @@ struct Cli {
     #[arg(long, global = true, default_value = "json")]
     output_format: OutputFormat,
 
+    #[arg(
+        long,
+        global = true,
+        help = "Write JSON output as {comments, skipped} to list files the model could not review"
+    )]
+    json_include_skipped: bool,
+
     #[arg(short, long, global = true)]
     verbose: bool,
 
@@ -54,6 +98,74 @@ struct Cli {
         help = "Force an LSP command for symbol indexing (enables LSP provider)"
     )]
     lsp_command: Option<String>,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "DIR",
+        help = "Record every LLM request/response pair as JSON in DIR"
+    )]
+    llm_log: Option<PathBuf>,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "DIR",
+        help = "Serve LLM responses from recordings in DIR instead of calling a provider"
+    )]
+    llm_replay: Option<PathBuf>,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Ignore and do not write the per-hunk review cache"
+    )]
+    no_cache: bool,
+
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        help = "Exit non-zero when a finding is at or above this severity"
+    )]
+    fail_on: Option<config::FailOn>,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "N",
+        help = "Exit non-zero when a review has more than N findings"
+    )]
+    max_issues: Option<usize>,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "PATH",
+        help = "lcov or Cobertura report; flags added lines that no test covers"
+    )]
+    coverage_report: Option<PathBuf>,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Re-check high-severity findings with the model and drop the ones it retracts"
+    )]
+    verify_findings: bool,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Screen hunks with the cheap triage model and review only the risky ones"
+    )]
+    triage: bool,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Let the model ask for more files and symbols, then revise its review"
+    )]
+    follow_up: bool,
 }
 
 #[derive(Subcommand)]
@@ -67,6 +179,20 @@ enum Commands {
 
         #[arg(short, long)]
         output: Option<PathBuf>,
+
+        #[arg(
+            long,
+            value_name = "ID",
+            help = "Continue an interrupted review from its last completed file (needs review_checkpoints)"
+        )]
+        resume: Option<String>,
+
+        #[arg(
+            long,
+            conflicts_with = "resume",
+            help = "Build the prompts and report token, request, and cost estimates without calling the model"
+        )]
+        dry_run: bool,
     },
     Check {
         #[arg(default_value = ".")]
@@ -88,6 +214,24 @@ enum Commands {
 
         #[arg(long)]
         summary: bool,
+
+        #[arg(
+            long,
+            help = "Only review commits pushed since the last DiffScope review of this PR"
+        )]
+        incremental: bool,
+
+        #[arg(
+            long,
+            help = "Answer @diffscope commands left in PR comments instead of reviewing"
+        )]
+        respond: bool,
+
+        #[arg(
+            long,
+            help = "Label the PR from its change type and findings (see `labels` in the config)"
+        )]
+        apply_labels: bool,
     },
     Compare {
         #[arg(long)]
@@ -107,6 +251,30 @@ enum Commands {
             help = "Output file path (prints to stdout if not provided)"
         )]
         output: Option<PathBuf>,
+
+        #[arg(
+            long,
+            help = "Write findings, summary, and risk scores as JSON instead of markdown"
+        )]
+        json: bool,
+    },
+    #[command(about = "Draw a Mermaid diagram of a change")]
+    Diagram {
+        #[arg(
+            long,
+            help = "Path to diff file (reads stdin, or uncommitted changes, if not provided)"
+        )]
+        diff: Option<PathBuf>,
+
+        #[arg(long, value_enum, help = "Diagram type [default: the model picks]")]
+        kind: Option<core::pr_summary::DiagramKind>,
+
+        #[arg(
+            short,
+            long,
+            help = "Output file: .svg, .png, or .pdf renders with mmdc; anything else gets the Mermaid source (prints to stdout if not provided)"
+        )]
+        output: Option<PathBuf>,
     },
     #[command(about = "Generate changelog and release notes from git history")]
     Changelog {
@@ -119,6 +287,39 @@ enum Commands {
         #[arg(long, help = "Generate release notes for a specific version")]
         release: Option<String>,
 
+        #[arg(
+            long,
+            value_enum,
+            conflicts_with = "template",
+            help = "Output format; overrides `changelog_template` [default: markdown]"
+        )]
+        format: Option<ChangelogFormat>,
+
+        #[arg(
+            long,
+            help = "Add model-written highlights and per-area summaries to the notes"
+        )]
+        ai_summary: bool,
+
+        #[arg(
+            long,
+            help = "Jinja template to render the changelog with [default: `changelog_template`]"
+        )]
+        template: Option<PathBuf>,
+
+        #[arg(
+            long = "path",
+            help = "Only include commits touching this path, e.g. a monorepo package (repeatable)"
+        )]
+        paths: Vec<PathBuf>,
+
+        #[arg(
+            long,
+            requires = "output",
+            help = "Write one changelog per conventional-commit scope into the --output directory"
+        )]
+        split_by_scope: bool,
+
         #[arg(
             short,
             long,
@@ -126,12 +327,127 @@ enum Commands {
         )]
         output: Option<PathBuf>,
     },
+    #[command(
+        about = "Run a language server that reviews files on save and publishes findings as diagnostics"
+    )]
+    Lsp,
     #[command(about = "Preflight LSP setup and configuration")]
     LspCheck {
         #[arg(default_value = ".")]
         path: PathBuf,
     },
+    #[command(about = "List models available on the local Ollama server")]
+    Models {
+        #[arg(
+            long,
+            help = "Ollama server URL (defaults to base_url or http://localhost:11434)"
+        )]
+        base_url: Option<String>,
+    },
+    #[command(about = "Validate or print the configuration")]
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+    #[command(about = "Inspect analyzer plugins")]
+    Plugins {
+        #[command(subcommand)]
+        command: PluginCommands,
+    },
+    #[command(about = "Run a webhook server that reviews GitHub PRs and GitLab MRs automatically")]
+    Serve {
+        #[arg(long, default_value_t = 8080)]
+        port: u16,
+
+        #[arg(long, help = "Address to bind (0.0.0.0, or 127.0.0.1 with --local)")]
+        host: Option<String>,
+
+        #[arg(
+            long,
+            help = "Serve a JSON API for editors and local tools (POST /review, POST /summary, GET /usage) instead of webhooks"
+        )]
+        local: bool,
+
+        #[arg(
+            long,
+            help = "Accept unsigned webhook deliveries when no webhook secret is configured"
+        )]
+        insecure: bool,
+    },
+    #[command(
+        about = "Run a Model Context Protocol server on stdio exposing review, summary, explain, and commit message tools"
+    )]
+    Mcp,
+    #[command(about = "Ask a free-form question about a diff")]
+    Ask {
+        question: String,
+
+        #[arg(
+            long,
+            help = "Path to diff file (reads stdin, or uncommitted changes, if not provided)"
+        )]
+        diff: Option<PathBuf>,
+    },
+    #[command(
+        about = "Check API keys, model access, git, LSP servers, analyzers, and config, with fixes"
+    )]
+    Doctor {
+        #[arg(long, help = "Skip the request that checks the API key and model")]
+        offline: bool,
+    },
+    #[command(about = "List files changed and bug-fixed most often in recent history")]
+    Hotspots {
+        #[arg(
+            long,
+            help = "Days of history to mine [default: `hotspots.days`, else 180]"
+        )]
+        days: Option<u32>,
+
+        #[arg(long, default_value_t = 20, help = "Number of files to list")]
+        limit: usize,
+
+        #[arg(long, help = "Print the hotspots as JSON")]
+        json: bool,
+    },
+    #[command(
+        about = "Inspect the repository and write a tailored .diffscope.yml (and optional GitHub Actions workflow)"
+    )]
+    Init {
+        #[arg(long, help = "Accept the detected defaults without asking")]
+        yes: bool,
+
+        #[arg(long, help = "Overwrite existing .diffscope.yml and workflow files")]
+        force: bool,
+    },
+    #[command(
+        about = "Install or remove git hooks that review changes before they leave your machine"
+    )]
+    Hooks {
+        #[command(subcommand)]
+        command: HookCommands,
+    },
+    #[command(about = "Generate code grounded in a diff")]
+    Generate {
+        #[command(subcommand)]
+        command: GenerateCommands,
+    },
+    #[command(about = "Explain a file, the code around path:line, or a commit")]
+    Explain {
+        #[arg(value_name = "PATH[:LINE]|COMMIT")]
+        target: String,
+    },
+    #[command(about = "Apply the code suggestions of a review JSON file to the working tree")]
+    Apply {
+        #[arg(value_name = "REVIEW_JSON")]
+        review: PathBuf,
+
+        #[arg(long, help = "Show the resulting diff without changing any file")]
+        dry_run: bool,
+    },
     Feedback {
+        #[command(subcommand)]
+        command: Option<FeedbackCommands>,
+
         #[arg(
             long,
             value_name = "FILE",
@@ -146,11 +462,121 @@ enum Commands {
         )]
         reject: Option<PathBuf>,
 
+        #[arg(
+            long,
+            value_enum,
+            requires = "reject",
+            help = "Where rewordings of rejected comments are also suppressed [default: file]"
+        )]
+        scope: Option<core::feedback::FeedbackScope>,
+
         #[arg(long, help = "Override feedback file path")]
         feedback_path: Option<PathBuf>,
     },
 }
 
+#[derive(Subcommand)]
+enum HookCommands {
+    #[command(about = "Install hooks (pre-commit by default) that fail on error-level findings")]
+    Install {
+        #[arg(long, help = "Review staged changes before each commit")]
+        pre_commit: bool,
+
+        #[arg(long, help = "Review the pushed commits before each push")]
+        pre_push: bool,
+
+        #[arg(
+            long,
+            value_name = "MODEL",
+            help = "Model the hooks use instead of the configured one, e.g. ollama:codellama"
+        )]
+        local_model: Option<String>,
+
+        #[arg(
+            long,
+            help = "Replace existing hooks (they are backed up as <hook>.bak)"
+        )]
+        force: bool,
+    },
+    #[command(about = "Remove hooks installed by diffscope (all of them by default)")]
+    Uninstall {
+        #[arg(long)]
+        pre_commit: bool,
+
+        #[arg(long)]
+        pre_push: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum GenerateCommands {
+    #[command(about = "Write tests for the functions a diff changes, in the project's framework")]
+    Tests {
+        #[arg(
+            long,
+            help = "Path to diff file (reads stdin, or uncommitted changes, if not provided)"
+        )]
+        diff: Option<PathBuf>,
+
+        #[arg(long, help = "Write the test files instead of printing them")]
+        write: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum FeedbackCommands {
+    #[command(about = "Record 👍/👎 reactions and resolved threads on posted PR comments")]
+    Sync {
+        #[arg(long, value_name = "OWNER/NAME")]
+        repo: String,
+    },
+    #[command(about = "Write the current accept/reject decisions as a JSON feedback store")]
+    Export {
+        #[arg(
+            long,
+            short,
+            value_name = "FILE",
+            help = "Write to FILE instead of stdout"
+        )]
+        output: Option<PathBuf>,
+    },
+    #[command(about = "Record the decisions of an exported feedback store")]
+    Import {
+        #[arg(value_name = "FILE")]
+        input: PathBuf,
+    },
+    #[command(about = "Show acceptance rates over time (needs the SQLite feedback_db)")]
+    Stats {
+        #[arg(long, value_enum, default_value = "category")]
+        by: core::feedback::StatsGroup,
+
+        #[arg(long, value_enum, default_value = "month")]
+        period: core::feedback::StatsPeriod,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommands {
+    #[command(
+        about = "Check a config file strictly: type errors and unknown keys, with line numbers"
+    )]
+    Validate {
+        #[arg(help = "Config file (defaults to the one diffscope loads)")]
+        path: Option<PathBuf>,
+    },
+    #[command(about = "Print the loaded configuration as YAML, with secrets masked")]
+    Show {
+        #[arg(long, help = "Include CLI overrides, normalization, and rule packs")]
+        effective: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum PluginCommands {
+    #[command(about = "List loaded plugins and their effective settings")]
+    List,
+}
+
 #[derive(Subcommand)]
 enum GitCommands {
     Uncommitted,
@@ -159,15 +585,82 @@ enum GitCommands {
         #[arg(help = "Base branch/ref (defaults to repo default)")]
         base: Option<String>,
     },
-    Suggest,
+    #[command(about = "Review the changes made by a single commit")]
+    Commit {
+        #[arg(help = "Commit SHA or ref")]
+        sha: String,
+    },
+    #[command(about = "Review a commit range (A..B, or A...B from their merge base)")]
+    Range {
+        #[arg(help = "Range such as v1.2.0..HEAD or main...feature")]
+        range: String,
+    },
+    #[command(about = "Suggest a commit message for the staged changes")]
+    Suggest {
+        #[arg(
+            long,
+            help = "Install a prepare-commit-msg hook that pre-fills commit messages"
+        )]
+        install_hook: bool,
+
+        #[arg(long, help = "Remove the prepare-commit-msg hook")]
+        uninstall_hook: bool,
+
+        #[arg(
+            long,
+            value_name = "MODEL",
+            requires = "install_hook",
+            help = "Model the hook uses instead of the configured one, e.g. ollama:codellama"
+        )]
+        local_model: Option<String>,
+
+        #[arg(
+            long,
+            requires = "install_hook",
+            help = "Replace an existing hook (it is backed up as prepare-commit-msg.bak)"
+        )]
+        force: bool,
+
+        /// Commit message file to fill in instead of printing (used by the hook).
+        #[arg(long, value_name = "FILE", hide = true)]
+        message_file: Option<PathBuf>,
+
+        /// Describe the amended commit instead of only the staged changes (used by the hook).
+        #[arg(long, hide = true)]
+        amend: bool,
+    },
     PrTitle,
 }
 
+#[derive(Clone, Copy, Debug, clap::ValueEnum)]
+enum ChangelogFormat {
+    /// Grouped by commit type; with `--release`, release notes.
+    Markdown,
+    /// Keep a Changelog sections (Added, Changed, Fixed, ...).
+    KeepAChangelog,
+    /// The parsed commit entries.
+    Json,
+    /// The parsed commit entries.
+    Yaml,
+}
+
 #[derive(Clone, Copy, Debug, clap::ValueEnum)]
 enum OutputFormat {
     Json,
     Patch,
     Markdown,
+    /// Reviewdog Diagnostic Format, one result document.
+    Rdjson,
+    /// Reviewdog Diagnostic Format, one diagnostic per line.
+    Rdjsonl,
+    /// JUnit XML, one failed test case per finding.
+    Junit,
+    /// Checkstyle XML.
+    Checkstyle,
+    /// Code Climate issues, as read by GitLab's Code Quality report.
+    Codeclimate,
+    /// Self-contained HTML report.
+    Html,
 }
 
 #[tokio::main]
@@ -180,11 +673,36 @@ async fn main() -> Result<()> {
         EnvFilter::new("info")
     };
 
-    tracing_subscriber::fmt().with_env_filter(filter).init();
+    if matches!(cli.command, Commands::Mcp | Commands::Lsp) {
+        // stdout carries the protocol.
+        tracing_subscriber::fmt()
+            .with_env_filter(filter)
+            .with_writer(std::io::stderr)
+            .init();
+    } else {
+        tracing_subscriber::fmt().with_env_filter(filter).init();
+    }
 
     // Load configuration from file and merge with CLI options
-    let mut config = config::Config::load().unwrap_or_default();
-    config.merge_with_cli(Some(cli.model.clone()), cli.prompt.clone());
+    if !matches!(cli.command, Commands::Config { .. }) {
+        warn_on_config_issues();
+    }
+    let profile = cli
+        .profile
+        .clone()
+        .or_else(|| std::env::var("DIFFSCOPE_PROFILE").ok())
+        .filter(|profile| !profile.is_empty());
+    let review_profile = cli.review_profile.as_deref();
+    let mut config = match config::Config::load_with_profile(profile.as_deref(), review_profile) {
+        Ok(config) => config,
+        // Falling back to the defaults would quietly drop the profile that was asked for.
+        Err(err) if profile.is_some() => return Err(err),
+        Err(_) => config::Config::load_layers(&[], None, review_profile).unwrap_or_default(),
+    };
+    if let Some(profile) = &profile {
+        info!("Using config profile {}", profile);
+    }
+    config.merge_with_cli(cli.model.clone(), cli.prompt.clone());
 
     // Override with CLI temperature and max_tokens if provided
     if let Some(temp) = cli.temperature {
@@ -193,6 +711,9 @@ async fn main() -> Result<()> {
     if let Some(tokens) = cli.max_tokens {
         config.max_tokens = tokens;
     }
+    if cli.ticket.is_some() {
+        config.ticket = cli.ticket;
+    }
     if let Some(flag) = cli.openai_responses {
         config.openai_use_responses = Some(flag);
     }
@@ -201,15 +722,55 @@ async fn main() -> Result<()> {
         config.symbol_index_provider = "lsp".to_string();
         config.symbol_index_lsp_command = Some(command);
     }
-    config.normalize();
-
-    match cli.command {
+    if let Some(dir) = cli.llm_log {
+        config.llm_log_dir = Some(dir);
+    }
+    if let Some(dir) = cli.llm_replay {
+        config.llm_replay_dir = Some(dir);
+    }
+    if cli.no_cache {
+        config.review_cache = false;
+    }
+    if cli.json_include_skipped {
+        config.json_include_skipped = true;
+    }
+    if cli.fail_on.is_some() {
+        config.fail_on = cli.fail_on;
+    }
+    if cli.max_issues.is_some() {
+        config.max_issues = cli.max_issues;
+    }
+    if cli.coverage_report.is_some() {
+        config.plugins.coverage_report = cli.coverage_report;
+    }
+    if cli.verify_findings {
+        config.verify_findings = true;
+    }
+    if cli.triage {
+        config.triage.enabled = true;
+    }
+    if cli.follow_up {
+        config.follow_up.enabled = true;
+    }
+    config.normalize();
+    if reviews_code(&cli.command) {
+        core::rule_pack::apply_rule_packs(&mut config).await;
+    }
+
+    match cli.command {
         Commands::Review {
             diff,
             patch,
             output,
+            resume,
+            dry_run,
         } => {
-            review_command(config, diff, patch, output, cli.output_format).await?;
+            let options = ReviewOptions {
+                patch,
+                resume,
+                dry_run,
+            };
+            review_command(config, diff, output, options, cli.output_format).await?;
         }
         Commands::Check { path } => {
             check_command(path, config, cli.output_format).await?;
@@ -222,247 +783,508 @@ async fn main() -> Result<()> {
             repo,
             post_comments,
             summary,
+            incremental,
+            respond,
+            apply_labels,
         } => {
-            pr_command(
-                number,
-                repo,
+            let options = PrOptions {
                 post_comments,
                 summary,
-                config,
-                cli.output_format,
-            )
-            .await?;
+                incremental,
+                respond,
+                apply_labels,
+            };
+            pr_command(number, repo, options, config, cli.output_format).await?;
         }
         Commands::Compare { old_file, new_file } => {
             compare_command(old_file, new_file, config, cli.output_format).await?;
         }
-        Commands::SmartReview { diff, output } => {
-            smart_review_command(config, diff, output).await?;
+        Commands::SmartReview { diff, output, json } => {
+            smart_review_command(config, diff, output, json).await?;
+        }
+        Commands::Diagram { diff, kind, output } => {
+            diagram_command(config, diff, kind, output).await?;
         }
         Commands::Changelog {
             from,
             to,
             release,
+            format,
+            ai_summary,
+            template,
+            paths,
+            split_by_scope,
             output,
         } => {
-            changelog_command(from, to, release, output).await?;
+            // An explicit --format wins over the configured template.
+            let template = match format {
+                Some(_) => None,
+                None => template.or_else(|| config.changelog_template.clone()),
+            };
+            let options = ChangelogOptions {
+                format: format.unwrap_or(ChangelogFormat::Markdown),
+                ai_summary,
+                template,
+                paths,
+                split_by_scope,
+            };
+            changelog_command(config, from, to, release, options, output).await?;
+        }
+        Commands::Lsp => {
+            let repo_root = core::GitIntegration::new(".")
+                .ok()
+                .and_then(|git| git.workdir())
+                .unwrap_or_else(|| PathBuf::from("."));
+            lsp_server::serve_stdio(ReviewEngine::new(config).with_repo_root(repo_root)).await?;
         }
         Commands::LspCheck { path } => {
             lsp_check_command(path, config).await?;
         }
+        Commands::Models { base_url } => {
+            models_command(config, base_url).await?;
+        }
+        Commands::Config { command } => config_command(command, config)?,
+        Commands::Plugins { command } => match command {
+            PluginCommands::List => plugins_list_command(config).await?,
+        },
+        Commands::Serve {
+            port,
+            host,
+            local,
+            insecure,
+        } => {
+            let default_host = if local { "127.0.0.1" } else { "0.0.0.0" };
+            let host = host.as_deref().unwrap_or(default_host);
+            let addr: std::net::SocketAddr = format!("{}:{}", host, port).parse()?;
+            let repo_root = core::GitIntegration::new(".")
+                .ok()
+                .and_then(|git| git.workdir())
+                .unwrap_or_else(|| PathBuf::from("."));
+            if local {
+                server::serve_local(config, addr, repo_root).await?;
+            } else {
+                server::serve(config, addr, repo_root, insecure).await?;
+            }
+        }
+        Commands::Mcp => {
+            let repo_root = core::GitIntegration::new(".")
+                .ok()
+                .and_then(|git| git.workdir())
+                .unwrap_or_else(|| PathBuf::from("."));
+            mcp::serve_stdio(config, repo_root).await?;
+        }
+        Commands::Ask { question, diff } => ask_command(config, &question, diff).await?,
+        Commands::Doctor { offline } => doctor_command(config, offline).await?,
+        Commands::Hotspots { days, limit, json } => hotspots_command(config, days, limit, json)?,
+        Commands::Init { yes, force } => init_command(yes, force)?,
+        Commands::Hooks { command } => hooks_command(command)?,
+        Commands::Generate {
+            command: GenerateCommands::Tests { diff, write },
+        } => generate_tests_command(config, diff, write).await?,
+        Commands::Explain { target } => explain_command(config, &target).await?,
+        Commands::Apply { review, dry_run } => apply_command(&review, dry_run).await?,
         Commands::Feedback {
+            command,
             accept,
             reject,
+            scope,
             feedback_path,
-        } => {
-            feedback_command(config, accept, reject, feedback_path).await?;
-        }
+        } => match command {
+            Some(FeedbackCommands::Sync { repo }) => {
+                feedback_sync_command(config, &repo, feedback_path).await?
+            }
+            Some(FeedbackCommands::Export { output }) => {
+                feedback_export_command(config, feedback_path, output)?
+            }
+            Some(FeedbackCommands::Import { input }) => {
+                feedback_import_command(config, feedback_path, input).await?
+            }
+            Some(FeedbackCommands::Stats { by, period }) => {
+                feedback_stats_command(config, feedback_path, by, period)?
+            }
+            None => feedback_command(config, accept, reject, scope, feedback_path).await?,
+        },
     }
 
     Ok(())
 }
 
+/// Whether `command` reviews code, and so needs the configured rule packs.
+fn reviews_code(command: &Commands) -> bool {
+    matches!(
+        command,
+        Commands::Review { .. }
+            | Commands::Check { .. }
+            | Commands::Git { .. }
+            | Commands::Pr { .. }
+            | Commands::Compare { .. }
+            | Commands::SmartReview { .. }
+            | Commands::Lsp
+            | Commands::Serve { .. }
+            | Commands::Mcp
+    )
+}
+
 async fn review_command(
     config: config::Config,
     diff_path: Option<PathBuf>,
-    patch: bool,
     output_path: Option<PathBuf>,
+    options: ReviewOptions,
     format: OutputFormat,
 ) -> Result<()> {
+    let ReviewOptions {
+        patch,
+        resume,
+        dry_run,
+    } = options;
     info!("Starting diff review with model: {}", config.model);
 
     let repo_root = core::GitIntegration::new(".")
         .ok()
         .and_then(|git| git.workdir())
         .unwrap_or_else(|| PathBuf::from("."));
-    let repo_path_str = repo_root.to_string_lossy().to_string();
-    let context_fetcher = core::ContextFetcher::new(repo_root.clone());
-
-    let mut plugin_manager = plugins::plugin::PluginManager::new();
-    plugin_manager.load_builtin_plugins(&config.plugins).await?;
-    let feedback = load_feedback_store(&config);
 
-    let diff_content = if let Some(path) = diff_path {
-        tokio::fs::read_to_string(path).await?
-    } else if std::io::stdin().is_terminal() {
-        if let Ok(git) = core::GitIntegration::new(".") {
-            let diff = git.get_uncommitted_diff()?;
-            if diff.is_empty() {
-                println!("No changes found");
+    let (diff_content, resumed) = if let Some(id) = resume {
+        let checkpoint = core::checkpoint::ReviewCheckpoint::open(&repo_root, &id)?;
+        if diff_path.is_some() {
+            warn!("Ignoring --diff: --resume reviews the diff the run started with");
+        }
+        (checkpoint.diff()?, Some(checkpoint))
+    } else {
+        let diff_content = if let Some(path) = diff_path {
+            tokio::fs::read_to_string(path).await?
+        } else if std::io::stdin().is_terminal() {
+            if let Ok(git) = core::GitIntegration::new(".") {
+                let diff = git.get_uncommitted_diff()?;
+                if diff.is_empty() {
+                    println!("No changes found");
+                    return Ok(());
+                }
+                diff
+            } else {
+                println!("No diff provided and not in a git repository.");
                 return Ok(());
             }
-            diff
         } else {
-            println!("No diff provided and not in a git repository.");
-            return Ok(());
-        }
-    } else {
-        use std::io::Read;
-        let mut buffer = String::new();
-        std::io::stdin().read_to_string(&mut buffer)?;
-        buffer
+            use std::io::Read;
+            let mut buffer = String::new();
+            std::io::stdin().read_to_string(&mut buffer)?;
+            buffer
+        };
+        (diff_content, None)
     };
 
-    let diffs = core::DiffParser::parse_unified_diff(&diff_content)?;
-    info!("Parsed {} file diffs", diffs.len());
-    let symbol_index = build_symbol_index(&config, &repo_root);
-    let model_config = adapters::llm::ModelConfig {
-        model_name: config.model.clone(),
-        api_key: config.api_key.clone(),
-        base_url: config.base_url.clone(),
-        temperature: config.temperature,
-        max_tokens: config.max_tokens,
-        openai_use_responses: config.openai_use_responses,
+    if dry_run {
+        let diffs = core::DiffParser::parse_unified_diff(&diff_content)?;
+        info!("Parsed {} file diffs", diffs.len());
+        let context_fetcher = core::ContextFetcher::new(repo_root.clone())
+            .with_function_scope(config.function_scope_context);
+        let mut plugin_manager = plugins::plugin::PluginManager::new();
+        plugin_manager.load_builtin_plugins(&config.plugins).await?;
+        plugin_manager.begin_review(&diffs).await;
+        let estimate = estimate_review(
+            &config,
+            &context_fetcher,
+            &plugin_manager,
+            &repo_root,
+            &diffs,
+        )
+        .await?;
+        return print_review_estimate(&estimate, &config, output_path, format).await;
+    }
+    let mut checkpoint = match resumed {
+        Some(checkpoint) => Some(checkpoint),
+        None if config.review_checkpoints => {
+            core::checkpoint::ReviewCheckpoint::create(&repo_root, &diff_content)
+                .map_err(|err| warn!("Not checkpointing this review: {:#}", err))
+                .ok()
+        }
+        None => None,
     };
 
-    let adapter = adapters::llm::create_adapter(&model_config)?;
-    let base_prompt_config = core::prompt::PromptConfig {
-        max_context_chars: config.max_context_chars,
-        max_diff_chars: config.max_diff_chars,
-        ..Default::default()
+    let engine = ReviewEngine::new(config.clone()).with_repo_root(&repo_root);
+    let reviewed = engine
+        .review_with_checkpoint(&diff_content, checkpoint.as_ref())
+        .await;
+    let ReviewOutcome { comments, skipped } = match &mut checkpoint {
+        Some(checkpoint) => {
+            if reviewed
+                .as_ref()
+                .map_or(true, |outcome| !outcome.skipped.is_empty())
+            {
+                checkpoint.keep();
+            }
+            reviewed.with_context(|| {
+                format!(
+                    "Review stopped; continue it with `diffscope review --resume {}`",
+                    checkpoint.id()
+                )
+            })?
+        }
+        None => reviewed?,
     };
-    let mut all_comments = Vec::new();
 
-    for diff in &diffs {
-        // Check if file should be excluded
-        if config.should_exclude(&diff.file_path) {
-            info!("Skipping excluded file: {}", diff.file_path.display());
-            continue;
-        }
-        if diff.is_deleted {
-            info!("Skipping deleted file: {}", diff.file_path.display());
+    let effective_format = if patch { OutputFormat::Patch } else { format };
+    output_comments(&comments, &skipped, output_path, effective_format, &config).await?;
+    if let Some(checkpoint) = checkpoint.as_ref().filter(|_| !skipped.is_empty()) {
+        eprintln!(
+            "Retry the skipped files with `diffscope review --resume {}`",
+            checkpoint.id()
+        );
+    }
+
+    finish_review(&comments, &skipped, &config)
+}
+
+/// Builds the prompts `review` would send for `diffs` and counts their tokens, without calling
+/// the review model. Context is gathered as `review` gathers it, except for similar code from
+/// the embeddings index, which would call the embeddings API.
+async fn estimate_review(
+    config: &config::Config,
+    context_fetcher: &core::ContextFetcher,
+    plugin_manager: &plugins::plugin::PluginManager,
+    repo_root: &Path,
+    diffs: &[core::UnifiedDiff],
+) -> Result<core::cost::ReviewEstimate> {
+    use core::cost::{ExcludedFile, FileEstimate};
+
+    let symbol_index = build_symbol_index(config, repo_root);
+    let repo_map = build_repo_map(config, symbol_index.as_ref());
+    let limits = config.prompt_limits();
+    let base_prompt_config = base_prompt_config(config)?;
+    let review_cache = build_review_cache(config);
+    let excluded = config.exclusion_filter(repo_root);
+    let hotspot_notes = load_hotspot_notes(config, repo_root, diffs);
+    let repo_path_str = repo_root.to_string_lossy().to_string();
+    let sources = ContextSources {
+        config,
+        repo_path: &repo_path_str,
+        context_fetcher,
+        plugin_manager,
+        symbol_index: symbol_index.as_ref(),
+        repo_map: repo_map.as_ref(),
+        semantic_search: None,
+        hotspot_notes: &hotspot_notes,
+    };
+    let shared_tokens = sources
+        .shared_context(diffs)
+        .map_or(0, |context| limits.tokenizer.count_tokens(&context));
+    let mut estimate = core::cost::ReviewEstimate::default();
+    let mut small_files = Vec::new();
+    for diff in diffs {
+        let reason = if excluded(&diff.file_path) {
+            Some("excluded")
+        } else if diff.is_deleted {
+            Some("deleted")
+        } else if diff.is_binary || diff.hunks.is_empty() {
+            Some("no text changes")
+        } else {
+            None
+        };
+        if let Some(reason) = reason {
+            estimate.excluded.push(ExcludedFile {
+                file_path: diff.file_path.clone(),
+                reason,
+            });
             continue;
         }
-        if diff.is_binary || diff.hunks.is_empty() {
-            info!("Skipping non-text diff: {}", diff.file_path.display());
+
+        let context_chunks = sources.for_file(diff).await?.chunks;
+        let path_config = config.get_path_config(&diff.file_path);
+        let local_prompt_config =
+            file_prompt_config(config, &base_prompt_config, &diff.file_path, path_config);
+        let (_, pending_diff) = match &review_cache {
+            Some(cache) => cache.partition(diff, &local_prompt_config.fingerprint()),
+            None => (Vec::new(), diff.clone()),
+        };
+        let mut file = FileEstimate {
+            file_path: diff.file_path.clone(),
+            requests: 0,
+            input_tokens: 0,
+            cached_hunks: diff.hunks.len() - pending_diff.hunks.len(),
+            batch: None,
+        };
+        let batches = core::prompt::split_diff_into_batches(&pending_diff, &limits);
+        if !pending_diff.hunks.is_empty() && is_batchable(config, &batches) {
+            estimate.files.push(file);
+            small_files.push(BatchedFile {
+                diff: pending_diff,
+                context_chunks,
+                prompt_config: local_prompt_config,
+                carried: Vec::new(),
+            });
             continue;
         }
-
-        let mut context_chunks = context_fetcher
-            .fetch_context_for_file(
-                &diff.file_path,
-                &diff
-                    .hunks
-                    .iter()
-                    .map(|h| (h.new_start, h.new_start + h.new_lines.saturating_sub(1)))
-                    .collect::<Vec<_>>(),
-            )
-            .await?;
-
-        // Run pre-analyzers to get additional context
-        let analyzer_chunks = plugin_manager
-            .run_pre_analyzers(diff, &repo_path_str)
-            .await?;
-        context_chunks.extend(analyzer_chunks);
-
-        // Extract symbols from diff and fetch their definitions
-        let symbols = extract_symbols_from_diff(diff);
-        if !symbols.is_empty() {
-            let definition_chunks = context_fetcher
-                .fetch_related_definitions(&diff.file_path, &symbols)
-                .await?;
-            context_chunks.extend(definition_chunks);
-            if let Some(index) = &symbol_index {
-                let index_chunks = context_fetcher
-                    .fetch_related_definitions_with_index(
-                        &diff.file_path,
-                        &symbols,
-                        index,
-                        config.symbol_index_max_locations,
-                    )
-                    .await?;
-                context_chunks.extend(index_chunks);
+        if !pending_diff.hunks.is_empty() {
+            let prompt_builder = core::PromptBuilder::new(local_prompt_config);
+            for batch in &batches {
+                let (system_prompt, user_prompt) =
+                    prompt_builder.build_prompt(batch, &context_chunks)?;
+                file.requests += 1;
+                file.input_tokens += limits.tokenizer.count_tokens(&system_prompt)
+                    + shared_tokens
+                    + limits.tokenizer.count_tokens(&user_prompt);
             }
         }
+        estimate.files.push(file);
+    }
 
-        // Get path-specific configuration
-        let path_config = config.get_path_config(&diff.file_path);
-
-        // Apply path-specific system prompt if available
-        let mut local_prompt_config = base_prompt_config.clone();
-        if let Some(custom_prompt) = &config.system_prompt {
-            local_prompt_config.system_prompt = custom_prompt.clone();
+    let mut shared_requests = 0;
+    for group in pack_batched_files(config, small_files) {
+        if group.len() > 1 {
+            shared_requests += 1;
         }
-        if let Some(pc) = path_config {
-            if let Some(ref prompt) = pc.system_prompt {
-                local_prompt_config.system_prompt = prompt.clone();
+        let prompt_builder = core::PromptBuilder::new(group[0].prompt_config.clone());
+        let (system_prompt, user_prompt) = match group.as_slice() {
+            [file] => prompt_builder.build_prompt(&file.diff, &file.context_chunks)?,
+            _ => {
+                let prompt_files: Vec<_> = group
+                    .iter()
+                    .map(|file| (&file.diff, file.context_chunks.as_slice()))
+                    .collect();
+                prompt_builder.build_multi_file_prompt(&prompt_files)?
             }
-
-            // Add focus areas to context
-            if !pc.focus.is_empty() {
-                let focus_chunk = core::LLMContextChunk {
-                    content: format!("Focus areas for this file: {}", pc.focus.join(", ")),
-                    context_type: core::ContextType::Documentation,
-                    file_path: diff.file_path.clone(),
-                    line_range: None,
-                };
-                context_chunks.push(focus_chunk);
+        };
+        let tokens = limits.tokenizer.count_tokens(&system_prompt)
+            + shared_tokens
+            + limits.tokenizer.count_tokens(&user_prompt);
+        for (position, member) in group.iter().enumerate() {
+            let Some(file) = estimate
+                .files
+                .iter_mut()
+                .find(|file| file.file_path == member.diff.file_path)
+            else {
+                continue;
+            };
+            if group.len() == 1 {
+                file.requests = 1;
+                file.input_tokens = tokens;
+            } else {
+                file.batch = Some(shared_requests);
+                file.input_tokens = tokens / group.len()
+                    + if position == 0 {
+                        tokens % group.len()
+                    } else {
+                        0
+                    };
             }
+        }
+    }
+    Ok(estimate)
+}
 
-            if !pc.extra_context.is_empty() {
-                let extra_chunks = context_fetcher
-                    .fetch_additional_context(&pc.extra_context)
-                    .await?;
-                context_chunks.extend(extra_chunks);
+/// Prints a dry-run estimate: per-file requests and tokens, then the projected cost on the
+/// configured model and on every model named in a profile.
+async fn print_review_estimate(
+    estimate: &core::cost::ReviewEstimate,
+    config: &config::Config,
+    output_path: Option<PathBuf>,
+    format: OutputFormat,
+) -> Result<()> {
+    let mut models = vec![config.model.clone()];
+    let mut profiles: Vec<_> = config.profiles.iter().collect();
+    profiles.sort_by(|a, b| a.0.cmp(b.0));
+    for (_, profile) in profiles {
+        if let Some(model) = profile.get("model").and_then(|model| model.as_str()) {
+            if !models.iter().any(|known| known == model) {
+                models.push(model.to_string());
             }
         }
+    }
+    let costs: Vec<core::cost::ModelCost> = models
+        .iter()
+        .map(|model| estimate.cost(model, config.max_tokens, &config.model_pricing))
+        .collect();
 
-        if let Some(guidance) = build_review_guidance(&config, path_config) {
-            local_prompt_config.system_prompt.push_str("\n\n");
-            local_prompt_config.system_prompt.push_str(&guidance);
+    let output = if matches!(format, OutputFormat::Json) {
+        let mut output = serde_json::to_string_pretty(&serde_json::json!({
+            "files": estimate.files,
+            "excluded": estimate.excluded,
+            "requests": estimate.requests(),
+            "input_tokens": estimate.input_tokens(),
+            "models": costs,
+        }))?;
+        output.push('\n');
+        output
+    } else {
+        let mut output = String::from("Dry run: no model requests were sent.\n\n");
+        output.push_str(&format!(
+            "{:<50} {:>8} {:>13} {:>7}\n",
+            "FILE", "REQUESTS", "INPUT TOKENS", "CACHED"
+        ));
+        for file in &estimate.files {
+            let requests = match file.batch {
+                Some(batch) => format!("batch {}", batch),
+                None => file.requests.to_string(),
+            };
+            output.push_str(&format!(
+                "{:<50} {:>8} {:>13} {:>7}\n",
+                file.file_path.display(),
+                requests,
+                file.input_tokens,
+                file.cached_hunks
+            ));
         }
-
-        let local_prompt_builder = core::PromptBuilder::new(local_prompt_config);
-        let (system_prompt, user_prompt) =
-            local_prompt_builder.build_prompt(diff, &context_chunks)?;
-
-        let request = adapters::llm::LLMRequest {
-            system_prompt,
-            user_prompt,
-            temperature: None,
-            max_tokens: None,
-        };
-
-        let response = adapter.complete(request).await?;
-
-        if let Ok(raw_comments) = parse_llm_response(&response.content, &diff.file_path) {
-            let mut comments = core::CommentSynthesizer::synthesize(raw_comments)?;
-
-            // Apply severity overrides if configured
-            if let Some(pc) = path_config {
-                for comment in &mut comments {
-                    for (category, severity) in &pc.severity_overrides {
-                        if format!("{:?}", comment.category).to_lowercase()
-                            == category.to_lowercase()
-                        {
-                            comment.severity = match severity.to_lowercase().as_str() {
-                                "error" => core::comment::Severity::Error,
-                                "warning" => core::comment::Severity::Warning,
-                                "info" => core::comment::Severity::Info,
-                                "suggestion" => core::comment::Severity::Suggestion,
-                                _ => comment.severity.clone(),
-                            };
-                        }
-                    }
-                }
+        output.push_str(&format!(
+            "{:<50} {:>8} {:>13}\n",
+            "Total",
+            estimate.requests(),
+            estimate.input_tokens()
+        ));
+        if !estimate.excluded.is_empty() {
+            output.push_str(&format!("\nNot reviewed ({}):\n", estimate.excluded.len()));
+            for file in &estimate.excluded {
+                output.push_str(&format!(
+                    "  {} ({})\n",
+                    file.file_path.display(),
+                    file.reason
+                ));
             }
-
-            let comments = filter_comments_for_diff(diff, comments);
-            all_comments.extend(comments);
         }
-    }
-
-    let processed_comments = plugin_manager
-        .run_post_processors(all_comments, &repo_path_str)
-        .await?;
-    let processed_comments = apply_confidence_threshold(processed_comments, config.min_confidence);
-    let processed_comments = apply_feedback_suppression(processed_comments, &feedback);
-    let processed_comments = apply_feedback_suppression(processed_comments, &feedback);
-    let processed_comments = apply_feedback_suppression(processed_comments, &feedback);
-
-    let effective_format = if patch { OutputFormat::Patch } else { format };
-    output_comments(&processed_comments, output_path, effective_format).await?;
+        output.push_str(&format!(
+            "\n{:<40} {:>13} {:>13} {:>10}\n",
+            "MODEL", "INPUT TOKENS", "OUTPUT TOKENS", "COST"
+        ));
+        for cost in &costs {
+            let price = match cost.cost {
+                Some(cost) => format!("${:.2}", cost),
+                None => "unknown".to_string(),
+            };
+            output.push_str(&format!(
+                "{:<40} {:>13} {:>13} {:>10}\n",
+                cost.model, cost.input_tokens, cost.output_tokens, price
+            ));
+        }
+        output.push_str(&format!(
+            "\nOutput assumes {} tokens per request. Analyzer context and --verify-findings requests are not included.\n",
+            core::cost::EXPECTED_OUTPUT_TOKENS.min(config.max_tokens)
+        ));
+        if config.triage.enabled {
+            output.push_str(
+                "Triage is not run in a dry run, so every hunk is counted as going to the review model.\n",
+            );
+        }
+        if config.embeddings.enabled {
+            output.push_str("Similar code from the embeddings index is not included.\n");
+        }
+        if config.follow_up.enabled {
+            output.push_str(
+                "Follow-up rounds are not included; each adds up to two requests per reviewed request.\n",
+            );
+        }
+        if costs.iter().any(|cost| cost.cost.is_none()) {
+            output.push_str(
+                "Set prices for unlisted models under `model_pricing` in .diffscope.yml.\n",
+            );
+        }
+        output
+    };
 
+    if let Some(path) = output_path {
+        tokio::fs::write(path, output).await?;
+    } else {
+        print!("{}", output);
+    }
     Ok(())
 }
 
@@ -481,6 +1303,180 @@ async fn check_command(path: PathBuf, config: config::Config, format: OutputForm
     review_diff_content_with_repo(&diff_content, config, format, &repo_root).await
 }
 
+async fn models_command(config: config::Config, base_url: Option<String>) -> Result<()> {
+    let mut model_config = config.model_config();
+    let configured_ollama_url = config
+        .base_url
+        .clone()
+        .filter(|url| config.model.starts_with("ollama:") || url.contains("11434"));
+    model_config.base_url = base_url.or(configured_ollama_url);
+
+    let adapter = adapters::OllamaAdapter::new(model_config)?;
+    let mut models = adapter.list_models().await?;
+    if models.is_empty() {
+        println!("No local Ollama models found. Pull one with `ollama pull <model>`.");
+        return Ok(());
+    }
+
+    models.sort_by(|a, b| a.name.cmp(&b.name));
+    println!("{:<40} {:>10}  MODIFIED", "NAME", "SIZE");
+    for model in &models {
+        println!(
+            "{:<40} {:>10}  {}",
+            format!("ollama:{}", model.name),
+            format_model_size(model.size),
+            model.modified_at.as_deref().unwrap_or("-")
+        );
+    }
+    Ok(())
+}
+
+/// Points at `diffscope config validate` when a config file has problems that would
+/// otherwise go unnoticed: a type error discards the configuration, and unknown keys are ignored.
+fn warn_on_config_issues() {
+    let mut warned = false;
+    for path in config::Config::source_files() {
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        let issues = config::validate(&content);
+        match issues.first() {
+            Some(issue) if serde_yaml::from_str::<config::Config>(&content).is_err() => warn!(
+                "Ignoring the configuration, using default settings ({}: {}); run `diffscope config validate`",
+                path.display(),
+                issue
+            ),
+            Some(issue) => warn!(
+                "{} has {} ignored setting(s) ({}); run `diffscope config validate`",
+                path.display(),
+                issues.len(),
+                issue
+            ),
+            None => continue,
+        }
+        warned = true;
+    }
+    if let (false, Err(err)) = (warned, config::Config::load()) {
+        warn!(
+            "Ignoring the configuration, using default settings: {:#}",
+            err
+        );
+    }
+}
+
+fn config_command(command: ConfigCommands, effective_config: config::Config) -> Result<()> {
+    match command {
+        ConfigCommands::Validate { path } => {
+            let explicit = path.is_some();
+            let paths = match path {
+                Some(path) => vec![path],
+                None => config::Config::source_files(),
+            };
+            if paths.is_empty() {
+                println!("No config file found; the defaults are in use.");
+                return Ok(());
+            }
+            let mut problems = 0;
+            for path in &paths {
+                let content = std::fs::read_to_string(path)
+                    .with_context(|| format!("Failed to read {}", path.display()))?;
+                let issues = config::validate(&content);
+                if issues.is_empty() {
+                    println!("{} is valid", path.display());
+                }
+                for issue in &issues {
+                    match issue.line {
+                        Some(line) => println!("{}:{}: {}", path.display(), line, issue.message),
+                        None => println!("{}: {}", path.display(), issue.message),
+                    }
+                }
+                problems += issues.len();
+            }
+            // Each file can be valid while `extends` points nowhere or in a circle.
+            if !explicit && problems == 0 {
+                if let Err(err) = config::Config::load() {
+                    println!("{:#}", err);
+                    problems += 1;
+                }
+            }
+            if problems > 0 {
+                anyhow::bail!("found {} configuration problem(s)", problems);
+            }
+        }
+        ConfigCommands::Show { effective } => {
+            let config = if effective {
+                effective_config
+            } else {
+                config::Config::load()?
+            };
+            let files = config::Config::source_files();
+            if files.is_empty() {
+                println!("# No config file found; defaults");
+            }
+            for path in files {
+                println!("# Loaded from {}", path.display());
+            }
+            print!("{}", config::to_redacted_yaml(&config)?);
+        }
+    }
+    Ok(())
+}
+
+async fn plugins_list_command(config: config::Config) -> Result<()> {
+    let mut plugin_manager = plugins::plugin::PluginManager::new();
+    plugin_manager.load_builtin_plugins(&config.plugins).await?;
+
+    println!("{:<24} {:<16} SETTINGS", "ID", "STAGE");
+    for plugin in plugin_manager.loaded_plugins() {
+        let settings = match plugin.settings {
+            serde_json::Value::Null => "-".to_string(),
+            settings => settings.to_string(),
+        };
+        println!("{:<24} {:<16} {}", plugin.id, plugin.stage, settings);
+    }
+
+    let disabled: Vec<&str> = [
+        ("eslint", config.plugins.eslint),
+        ("semgrep", config.plugins.semgrep),
+        ("ruff", config.plugins.ruff),
+        ("golangci", config.plugins.golangci),
+        ("iac", config.plugins.iac),
+        ("complexity", config.plugins.complexity),
+        ("missing_tests", config.plugins.missing_tests),
+        ("doc_drift", config.plugins.doc_drift),
+        ("spelling", config.plugins.spelling),
+        ("secret_scan", config.plugins.secret_scan),
+        ("secrets", config.plugins.secrets),
+        ("dependency_impact", config.plugins.dependency_impact),
+        ("dependency_audit", config.plugins.dependency_audit),
+        ("breaking_changes", config.plugins.breaking_changes),
+        ("blame", config.plugins.blame),
+        ("duplicate_filter", config.plugins.duplicate_filter),
+        ("comment_clusters", config.plugins.comment_clusters),
+        ("calibration", config.plugins.calibration),
+        ("suggestion_check", config.plugins.suggestion_check),
+    ]
+    .into_iter()
+    .filter(|(_, enabled)| !enabled)
+    .map(|(id, _)| id)
+    .collect();
+    if !disabled.is_empty() {
+        println!("\nDisabled built-in plugins: {}", disabled.join(", "));
+    }
+    Ok(())
+}
+
+fn format_model_size(bytes: u64) -> String {
+    const GB: f64 = 1024.0 * 1024.0 * 1024.0;
+    const MB: f64 = 1024.0 * 1024.0;
+    let bytes = bytes as f64;
+    if bytes >= GB {
+        format!("{:.1} GB", bytes / GB)
+    } else {
+        format!("{:.0} MB", bytes / MB)
+    }
+}
+
 async fn lsp_check_command(path: PathBuf, config: config::Config) -> Result<()> {
     let repo_root = core::GitIntegration::new(&path)
         .ok()
@@ -537,6 +1533,23 @@ async fn lsp_check_command(path: PathBuf, config: config::Config) -> Result<()>
         println!("command available: no");
     }
 
+    let mut servers: Vec<(String, String)> = resolve_lsp_commands(&config, &repo_root)
+        .into_iter()
+        .collect();
+    servers.sort();
+    for (ext, command) in &servers {
+        println!(
+            "LSP server for .{}: {} (available: {})",
+            ext,
+            command,
+            if core::SymbolIndex::lsp_command_available(command) {
+                "yes"
+            } else {
+                "no"
+            }
+        );
+    }
+
     let mut normalized_languages = HashMap::new();
     let mut invalid_mappings = Vec::new();
     for (ext, language) in &config.symbol_index_lsp_languages {
@@ -631,8 +1644,36 @@ async fn git_command(
             info!("Analyzing changes from branch: {}", base_branch);
             git.get_branch_diff(&base_branch)?
         }
-        GitCommands::Suggest => {
-            return suggest_commit_message(config).await;
+        GitCommands::Commit { sha } => {
+            info!("Analyzing commit: {}", sha);
+            git.get_commit(&sha)?.1
+        }
+        GitCommands::Range { range } => {
+            info!("Analyzing commit range: {}", range);
+            git.get_range_diff(&range)?
+        }
+        GitCommands::Suggest {
+            install_hook,
+            uninstall_hook,
+            local_model,
+            force,
+            message_file,
+            amend,
+        } => {
+            let kind = core::hooks::HookKind::PrepareCommitMsg;
+            if install_hook {
+                let path =
+                    core::hooks::install(&git.hooks_dir(), kind, local_model.as_deref(), force)?;
+                println!("Installed {}", path.display());
+                return Ok(());
+            }
+            if uninstall_hook {
+                if core::hooks::uninstall(&git.hooks_dir(), kind)? {
+                    println!("Removed the diffscope {} hook", kind.file_name());
+                }
+                return Ok(());
+            }
+            return suggest_commit_message(config, message_file, amend).await;
         }
         GitCommands::PrTitle => {
             return suggest_pr_title(config).await;
@@ -648,16 +1689,54 @@ async fn git_command(
     review_diff_content_with_repo(&diff_content, config, format, &repo_root).await
 }
 
-async fn pr_command(
-    number: Option<u32>,
-    repo: Option<String>,
+/// Mode flags of the `review` command.
+#[derive(Debug, Clone, Default)]
+struct ReviewOptions {
+    patch: bool,
+    resume: Option<String>,
+    dry_run: bool,
+}
+
+/// Mode flags of the `pr` command.
+#[derive(Debug, Clone, Copy, Default)]
+struct PrOptions {
     post_comments: bool,
     summary: bool,
+    incremental: bool,
+    respond: bool,
+    apply_labels: bool,
+}
+
+async fn pr_command(
+    number: Option<u32>,
+    repo: Option<String>,
+    options: PrOptions,
     config: config::Config,
     format: OutputFormat,
 ) -> Result<()> {
     use std::process::Command;
 
+    let PrOptions {
+        post_comments,
+        summary,
+        incremental,
+        respond,
+        apply_labels,
+    } = options;
+
+    if config.provider.kind == config::ProviderKind::Bitbucket {
+        if respond {
+            anyhow::bail!("--respond is only supported for GitHub pull requests");
+        }
+        if incremental {
+            warn!("--incremental is only supported for GitHub; reviewing the full pull request");
+        }
+        if apply_labels {
+            warn!("--apply-labels is only supported for GitHub; Bitbucket has no PR labels");
+        }
+        return bitbucket_pr_command(number, repo, post_comments, summary, config, format).await;
+    }
+
     let pr_number = if let Some(num) = number {
         num.to_string()
     } else {
@@ -719,102 +1798,690 @@ async fn pr_command(
         return Ok(());
     }
 
+    if respond {
+        return respond_to_pr_commands(&pr_number, repo.as_deref(), &diff_content, &config).await;
+    }
+
     // Generate PR summary if requested
     if summary {
-        let diffs = core::DiffParser::parse_unified_diff(&diff_content)?;
-        let git = core::GitIntegration::new(".")?;
-
-        let model_config = adapters::llm::ModelConfig {
-            model_name: config.model.clone(),
-            api_key: config.api_key.clone(),
-            base_url: config.base_url.clone(),
-            temperature: config.temperature,
-            max_tokens: config.max_tokens,
-            openai_use_responses: config.openai_use_responses,
+        return print_pr_summary(&diff_content, &config).await;
+    }
+
+    let diffs = core::DiffParser::parse_unified_diff(&diff_content)?;
+    let mut review_content = diff_content.clone();
+    let mut head_sha = None;
+    if incremental {
+        let head = gh_pr_head_sha(&pr_number, repo.as_deref())?;
+        match gh_last_reviewed_sha(&pr_number, repo.as_deref())? {
+            Some(last) if last == head => {
+                println!("No new commits since the last review of PR #{}", pr_number);
+                return Ok(());
+            }
+            Some(last) => {
+                info!("Reviewing changes from {} to {}", last, head);
+                let range_diff = incremental_pr_diff(&git, repo.as_deref(), &last, &head)?;
+                review_content = restrict_diff_to_files(&range_diff, &diffs);
+                if review_content.trim().is_empty() {
+                    println!("No new changes to PR files since the last review");
+                    return Ok(());
+                }
+            }
+            None => info!("No previous DiffScope review found; reviewing the full PR"),
+        }
+        head_sha = Some(head);
+    }
+
+    let outcome = review_diff_content_raw(&review_content, config.clone(), &repo_root).await?;
+    let (comments, skipped) = (outcome.comments, outcome.skipped);
+
+    if apply_labels {
+        let pr_summary = match ReviewEngine::new(config.clone())
+            .summarize(&diff_content)
+            .await
+        {
+            Ok(pr_summary) => Some(pr_summary),
+            Err(err) => {
+                warn!("PR summary failed; labeling from findings only: {}", err);
+                None
+            }
         };
+        let labels = core::labels::labels_for(&config.labels, pr_summary.as_ref(), &comments);
+        if !labels.is_empty() {
+            // Labels are a convenience; a missing permission should not cost the review.
+            match gh_add_labels(repo.as_deref(), &pr_number, &labels) {
+                Ok(()) => println!("Applied labels to PR #{}: {}", pr_number, labels.join(", ")),
+                Err(err) => warn!("Failed to label PR #{}: {:#}", pr_number, err),
+            }
+        }
+    }
+
+    if post_comments {
+        let reviewed = core::DiffParser::parse_unified_diff(&review_content)?;
+        // Skipped files were not re-checked, so their earlier comments stay open.
+        let reviewed: Vec<&core::UnifiedDiff> = reviewed
+            .iter()
+            .filter(|diff| !skipped.iter().any(|file| file.file_path == diff.file_path))
+            .collect();
+        let posted: Vec<providers::github::PostedComment> =
+            gh_api_list(repo.as_deref(), &format!("pulls/{}/comments", pr_number))?;
+        let stale = providers::github::stale_review_comments(&posted, &comments, &reviewed);
+        for comment in &stale {
+            if let Err(err) = gh_mark_addressed(repo.as_deref(), comment) {
+                warn!("Failed to resolve comment {}: {}", comment.id, err);
+            }
+        }
+        if !stale.is_empty() {
+            println!("Marked {} earlier comments as addressed", stale.len());
+        }
+    }
 
-        let adapter = adapters::llm::create_adapter(&model_config)?;
-        let options = core::SummaryOptions {
-            include_diagram: config.smart_review_diagram,
+    // A review is submitted even without findings: its marker records the reviewed head, so
+    // the next `--incremental` run does not review the same commits again.
+    if post_comments {
+        info!("Posting {} comments to PR", comments.len());
+
+        // Anchor against the full PR diff: GitHub only accepts lines that appear in it.
+        let positions = core::position::PositionMap::new(&diffs);
+        let head_sha = match head_sha {
+            Some(sha) => sha,
+            None => gh_pr_head_sha(&pr_number, repo.as_deref())?,
         };
-        let pr_summary = core::PRSummaryGenerator::generate_summary_with_options(
-            &diffs,
-            &git,
-            adapter.as_ref(),
-            options,
-        )
+        let review = build_github_review(
+            &comments,
+            &positions,
+            &head_sha,
+            config.codeowners.mention_on_error,
+        );
+        let endpoint = format!(
+            "repos/{}/pulls/{}/reviews",
+            repo.as_deref().unwrap_or("{owner}/{repo}"),
+            pr_number
+        );
+
+        let mut child = Command::new("gh")
+            .args(["api", "--method", "POST", &endpoint, "--input", "-"])
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            use std::io::Write;
+            stdin.write_all(serde_json::to_string(&review)?.as_bytes())?;
+        }
+        let review_output = child.wait_with_output()?;
+        if !review_output.status.success() {
+            let stderr = String::from_utf8_lossy(&review_output.stderr);
+            anyhow::bail!("Failed to submit PR review: {}", stderr.trim());
+        }
+
+        println!(
+            "Submitted review with {} comments to PR #{}",
+            comments.len(),
+            pr_number
+        );
+    } else {
+        output_comments(&comments, &skipped, None, format, &config).await?;
+    }
+
+    finish_review(&comments, &skipped, &config)
+}
+
+async fn print_pr_summary(diff_content: &str, config: &config::Config) -> Result<()> {
+    let pr_summary = ReviewEngine::new(config.clone())
+        .summarize(diff_content)
         .await?;
+    println!("{}", pr_summary.to_markdown());
+    Ok(())
+}
+
+async fn bitbucket_pr_command(
+    number: Option<u32>,
+    repo: Option<String>,
+    post_comments: bool,
+    summary: bool,
+    config: config::Config,
+    format: OutputFormat,
+) -> Result<()> {
+    let pr_number = number
+        .ok_or_else(|| anyhow::anyhow!("--number is required for Bitbucket pull requests"))?;
+    let bitbucket = &config.provider.bitbucket;
+    let client = providers::bitbucket::BitbucketClient::new(bitbucket, repo.as_deref())?;
 
-        println!("{}", pr_summary.to_markdown());
+    info!("Reviewing Bitbucket PR #{}", pr_number);
+    let diff_content = client.fetch_diff(pr_number).await?;
+    if diff_content.is_empty() {
+        println!("No changes in PR");
         return Ok(());
     }
 
-    let comments = review_diff_content_raw(&diff_content, config.clone(), &repo_root).await?;
+    if summary {
+        return print_pr_summary(&diff_content, &config).await;
+    }
 
-    if post_comments && !comments.is_empty() {
-        info!("Posting {} comments to PR", comments.len());
+    let head_commit = if post_comments && bitbucket.build_status {
+        let commit = client.head_commit(pr_number).await?;
+        client
+            .set_build_status(
+                &commit,
+                providers::bitbucket::BuildState::InProgress,
+                "Review in progress",
+            )
+            .await?;
+        Some(commit)
+    } else {
+        None
+    };
 
-        for comment in &comments {
-            let body = format!("**{:?}**: {}", comment.severity, comment.content);
-
-            let mut comment_args = vec![
-                "pr".to_string(),
-                "comment".to_string(),
-                pr_number.clone(),
-                "--body".to_string(),
-                body,
-            ];
-            if let Some(repo) = repo.as_ref() {
-                comment_args.push("--repo".to_string());
-                comment_args.push(repo.clone());
-            }
-            let comment_output = Command::new("gh").args(&comment_args).output()?;
-            if !comment_output.status.success() {
-                let stderr = String::from_utf8_lossy(&comment_output.stderr);
-                anyhow::bail!("gh pr comment failed: {}", stderr.trim());
+    let repo_root = core::GitIntegration::new(".")
+        .ok()
+        .and_then(|git| git.workdir())
+        .unwrap_or_else(|| PathBuf::from("."));
+    let (comments, skipped) =
+        match review_diff_content_raw(&diff_content, config.clone(), &repo_root).await {
+            Ok(outcome) => (outcome.comments, outcome.skipped),
+            Err(err) => {
+                if let Some(commit) = &head_commit {
+                    let _ = client
+                        .set_build_status(
+                            commit,
+                            providers::bitbucket::BuildState::Failed,
+                            "Review could not be completed",
+                        )
+                        .await;
+                }
+                return Err(err);
             }
+        };
+
+    if !post_comments {
+        output_comments(&comments, &skipped, None, format, &config).await?;
+        return finish_review(&comments, &skipped, &config);
+    }
+
+    let diffs = core::DiffParser::parse_unified_diff(&diff_content)?;
+    let positions = core::position::PositionMap::new(&diffs);
+    for comment in &comments {
+        let position = positions.locate(&comment.file_path, comment.line_number);
+        let body = format!("**{:?}**: {}", comment.severity, comment.content);
+        let body = match &position {
+            Some(_) => body,
+            None => format!(
+                "`{}:{}` {}",
+                comment.file_path.display(),
+                comment.line_number,
+                body
+            ),
+        };
+        client
+            .post_comment(pr_number, &body, position.as_ref())
+            .await?;
+    }
+    println!(
+        "Posted {} comments to Bitbucket PR #{}",
+        comments.len(),
+        pr_number
+    );
+
+    if let Some(commit) = &head_commit {
+        let summary = core::CommentSynthesizer::generate_summary(&comments);
+        let policy_configured = config.fail_on.is_some() || config.max_issues.is_some();
+        let failed = review_policy_violation(&comments, &config).is_some()
+            || (!policy_configured && summary.critical_issues > 0);
+        let state = if failed {
+            providers::bitbucket::BuildState::Failed
+        } else {
+            providers::bitbucket::BuildState::Successful
+        };
+        let mut description = format!(
+            "{} issue(s), {} critical",
+            summary.total_comments, summary.critical_issues
+        );
+        if !skipped.is_empty() {
+            description.push_str(&format!(", {} file(s) not reviewed", skipped.len()));
         }
+        client.set_build_status(commit, state, &description).await?;
+    }
 
-        println!("Posted {} comments to PR #{}", comments.len(), pr_number);
-    } else {
-        output_comments(&comments, None, format).await?;
+    finish_review(&comments, &skipped, &config)
+}
+
+fn gh_pr_head_sha(pr_number: &str, repo: Option<&str>) -> Result<String> {
+    let mut args = vec![
+        "pr".to_string(),
+        "view".to_string(),
+        pr_number.to_string(),
+        "--json".to_string(),
+        "headRefOid".to_string(),
+        "-q".to_string(),
+        ".headRefOid".to_string(),
+    ];
+    if let Some(repo) = repo {
+        args.push("--repo".to_string());
+        args.push(repo.to_string());
+    }
+    let output = std::process::Command::new("gh").args(&args).output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("gh pr view failed: {}", stderr.trim());
+    }
+    let sha = String::from_utf8(output.stdout)?.trim().to_string();
+    if sha.is_empty() {
+        anyhow::bail!("Unable to determine head commit for PR #{}", pr_number);
+    }
+    Ok(sha)
+}
+
+const REVIEWED_SHA_MARKER: &str = "diffscope:reviewed-sha=";
+
+/// Head commit recorded by the most recent DiffScope review on the PR, if any.
+fn gh_last_reviewed_sha(pr_number: &str, repo: Option<&str>) -> Result<Option<String>> {
+    let endpoint = format!(
+        "repos/{}/pulls/{}/reviews",
+        repo.unwrap_or("{owner}/{repo}"),
+        pr_number
+    );
+    let jq = format!(
+        ".[].body | capture(\"{}(?<sha>[0-9a-f]+)\") | .sha",
+        REVIEWED_SHA_MARKER
+    );
+    let output = std::process::Command::new("gh")
+        .args(["api", "--paginate", &endpoint, "--jq", &jq])
+        .output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Failed to list PR reviews: {}", stderr.trim());
+    }
+    // Reviews are listed oldest first.
+    Ok(String::from_utf8(output.stdout)?
+        .lines()
+        .map(str::trim)
+        .rfind(|line| !line.is_empty())
+        .map(str::to_string))
+}
+
+/// Diff from the last reviewed head to the current one. Uses the local clone when it has both
+/// commits, which also covers force-pushes, and falls back to GitHub's compare API otherwise.
+fn incremental_pr_diff(
+    git: &core::GitIntegration,
+    repo: Option<&str>,
+    from: &str,
+    to: &str,
+) -> Result<String> {
+    if let Ok(diff) = git.get_commit_range_diff(from, to) {
+        return Ok(diff);
+    }
+    let endpoint = format!(
+        "repos/{}/compare/{}...{}",
+        repo.unwrap_or("{owner}/{repo}"),
+        from,
+        to
+    );
+    let output = std::process::Command::new("gh")
+        .args([
+            "api",
+            &endpoint,
+            "-H",
+            "Accept: application/vnd.github.v3.diff",
+        ])
+        .output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Failed to diff {}...{}: {}", from, to, stderr.trim());
+    }
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+/// Drops file sections of `diff_content` for files outside `pr_diffs`, such as upstream changes
+/// pulled in when a PR branch is rebased between reviews.
+fn restrict_diff_to_files(diff_content: &str, pr_diffs: &[core::UnifiedDiff]) -> String {
+    let files: HashSet<&Path> = pr_diffs.iter().map(|d| d.file_path.as_path()).collect();
+    let mut sections: Vec<String> = Vec::new();
+    for line in diff_content.split_inclusive('\n') {
+        match sections.last_mut() {
+            Some(section) if !line.starts_with("diff --git ") => section.push_str(line),
+            _ => sections.push(line.to_string()),
+        }
+    }
+    sections
+        .into_iter()
+        .filter(|section| {
+            core::DiffParser::parse_unified_diff(section)
+                .map(|diffs| {
+                    diffs
+                        .iter()
+                        .any(|diff| files.contains(diff.file_path.as_path()))
+                })
+                .unwrap_or(false)
+        })
+        .collect()
+}
+
+/// All items of a paginated GitHub list endpoint (`path` is relative to `repos/{owner}/{repo}`).
+fn gh_api_list<T: serde::de::DeserializeOwned>(repo: Option<&str>, path: &str) -> Result<Vec<T>> {
+    let endpoint = format!(
+        "repos/{}/{}?per_page=100",
+        repo.unwrap_or("{owner}/{repo}"),
+        path
+    );
+    let output = std::process::Command::new("gh")
+        .args(["api", "--paginate", &endpoint])
+        .output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Failed to list {}: {}", path, stderr.trim());
     }
+    // `--paginate` prints one JSON array per page back to back.
+    let mut items = Vec::new();
+    for page in serde_json::Deserializer::from_slice(&output.stdout).into_iter::<Vec<T>>() {
+        items.extend(page?);
+    }
+    Ok(items)
+}
 
+/// Posts `body` to a `gh api` endpoint relative to `repos/{owner}/{repo}`.
+fn gh_api_post(repo: Option<&str>, path: &str, body: &str) -> Result<()> {
+    let endpoint = format!("repos/{}/{}", repo.unwrap_or("{owner}/{repo}"), path);
+    let field = format!("body={}", body);
+    let output = std::process::Command::new("gh")
+        .args(["api", "--method", "POST", &endpoint, "-f", &field])
+        .output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Failed to post to {}: {}", path, stderr.trim());
+    }
     Ok(())
 }
 
-async fn suggest_commit_message(config: config::Config) -> Result<()> {
-    let git = core::GitIntegration::new(".")?;
-    let diff_content = git.get_staged_diff()?;
+fn gh_add_labels(repo: Option<&str>, pr_number: &str, labels: &[String]) -> Result<()> {
+    let endpoint = format!(
+        "repos/{}/issues/{}/labels",
+        repo.unwrap_or("{owner}/{repo}"),
+        pr_number
+    );
+    let mut args = vec![
+        "api".to_string(),
+        "--method".to_string(),
+        "POST".to_string(),
+        endpoint,
+    ];
+    for label in labels {
+        args.push("-f".to_string());
+        args.push(format!("labels[]={}", label));
+    }
+    let output = std::process::Command::new("gh").args(&args).output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Failed to add labels: {}", stderr.trim());
+    }
+    Ok(())
+}
 
-    if diff_content.is_empty() {
-        println!("No staged changes found. Stage your changes with 'git add' first.");
+fn gh_mark_addressed(repo: Option<&str>, comment: &providers::github::PostedComment) -> Result<()> {
+    use std::io::Write;
+    for call in providers::github::mark_addressed_calls(repo.unwrap_or("{owner}/{repo}"), comment) {
+        let mut child = std::process::Command::new("gh")
+            .args(["api", "--method", call.method, &call.path, "--input", "-"])
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::piped())
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(call.body.to_string().as_bytes())?;
+        }
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("Failed to mark review comment addressed: {}", stderr.trim());
+        }
+    }
+    Ok(())
+}
+
+/// Runs an `@diffscope` command from a PR comment against the PR diff and returns the reply,
+/// tagged so the command is not answered twice.
+async fn answer_interactive_command(
+    command: &core::interactive::InteractiveCommand,
+    comment_id: u64,
+    diff_content: &str,
+    config: &config::Config,
+) -> Result<String> {
+    let adapter = adapters::llm::create_adapter(&config.model_config())?;
+    let answer = command
+        .execute(adapter.as_ref(), Some(diff_content))
+        .await?;
+    Ok(format!(
+        "{}\n\n{}",
+        answer,
+        core::interactive::reply_marker(comment_id)
+    ))
+}
+
+/// Answers `@diffscope` commands in PR conversation and review comments that have no reply yet.
+/// Unanswered `@diffscope` commands in a PR's comments from commenters whose
+/// `author_association` `config` answers, and how many other commands were ignored. Every
+/// comment counts when looking for DiffScope's replies.
+fn pr_commands(
+    issue_comments: &[providers::github::IssueComment],
+    review_comments: &[providers::github::PostedComment],
+    config: &config::Config,
+) -> (Vec<(u64, core::interactive::InteractiveCommand)>, usize) {
+    let comments: Vec<(u64, &str, &str)> = issue_comments
+        .iter()
+        .map(|c| (c.id, c.body.as_str(), c.author_association.as_str()))
+        .chain(
+            review_comments
+                .iter()
+                .map(|c| (c.id, c.body.as_str(), c.author_association.as_str())),
+        )
+        .collect();
+    let (pending, ignored): (Vec<_>, Vec<_>) =
+        core::interactive::pending_commands(comments.iter().map(|(id, body, _)| (*id, *body)))
+            .into_iter()
+            .partition(|(comment_id, _)| {
+                comments.iter().any(|(id, _, association)| {
+                    id == comment_id && config.answers_commands_from(association)
+                })
+            });
+    (pending, ignored.len())
+}
+
+async fn respond_to_pr_commands(
+    pr_number: &str,
+    repo: Option<&str>,
+    diff_content: &str,
+    config: &config::Config,
+) -> Result<()> {
+    let issue_comments: Vec<providers::github::IssueComment> =
+        gh_api_list(repo, &format!("issues/{}/comments", pr_number))?;
+    let review_comments: Vec<providers::github::PostedComment> =
+        gh_api_list(repo, &format!("pulls/{}/comments", pr_number))?;
+    let review_ids: HashSet<u64> = review_comments.iter().map(|c| c.id).collect();
+
+    let (pending, ignored) = pr_commands(&issue_comments, &review_comments, config);
+    if ignored > 0 {
+        warn!(
+            "Ignoring {} @diffscope command(s) from commenters outside comment_command_associations",
+            ignored
+        );
+    }
+    if pending.is_empty() {
+        println!("No new @diffscope commands on PR #{}", pr_number);
         return Ok(());
     }
 
-    let model_config = adapters::llm::ModelConfig {
-        model_name: config.model.clone(),
-        api_key: config.api_key.clone(),
-        base_url: config.base_url.clone(),
-        temperature: config.temperature,
-        max_tokens: config.max_tokens,
-        openai_use_responses: config.openai_use_responses,
+    for (comment_id, command) in &pending {
+        info!(
+            "Answering {:?} command in comment {}",
+            command.command, comment_id
+        );
+        let reply = answer_interactive_command(command, *comment_id, diff_content, config).await?;
+        if review_ids.contains(comment_id) {
+            gh_api_post(
+                repo,
+                &format!("pulls/{}/comments/{}/replies", pr_number, comment_id),
+                &reply,
+            )?;
+        } else {
+            gh_api_post(repo, &format!("issues/{}/comments", pr_number), &reply)?;
+        }
+    }
+    println!(
+        "Answered {} @diffscope commands on PR #{}",
+        pending.len(),
+        pr_number
+    );
+    Ok(())
+}
+
+/// Builds one review submission for the Reviews API: findings inside the diff become inline
+/// comments, while the summary and any findings that can't be anchored go into the review body.
+/// With `mention_owners`, the review body mentions the CODEOWNERS of files with Error
+/// findings.
+fn build_github_review(
+    comments: &[core::Comment],
+    positions: &core::position::PositionMap,
+    head_sha: &str,
+    mention_owners: bool,
+) -> serde_json::Value {
+    let summary = core::CommentSynthesizer::generate_summary(comments);
+    let mut inline = Vec::new();
+    let mut unanchored = Vec::new();
+
+    for comment in comments {
+        let Some(position) = positions.locate(&comment.file_path, comment.line_number) else {
+            unanchored.push(comment);
+            continue;
+        };
+        let suggestion = positions.suggestion_for(comment);
+        let mut review_comment = serde_json::json!({
+            "path": position.path.to_string_lossy(),
+            "line": position.line,
+            "side": position.side.as_str(),
+            "body": format!(
+                "{}\n\n{}",
+                format_github_comment_body(comment, suggestion.as_ref()),
+                providers::github::finding_marker(comment)
+            ),
+        });
+        if let Some(change) = suggestion.filter(|c| c.start_line < c.end_line) {
+            review_comment["start_line"] = change.start_line.into();
+            review_comment["start_side"] = "RIGHT".into();
+            review_comment["line"] = change.end_line.into();
+        }
+        inline.push(review_comment);
+    }
+
+    let mut body = String::from("## DiffScope Review\n\n");
+    body.push_str(&format!(
+        "**Code Quality Score:** {:.1}/10 · **Issues:** {} · **Critical:** {} · **Files:** {}\n",
+        summary.overall_score,
+        summary.total_comments,
+        summary.critical_issues,
+        summary.files_reviewed
+    ));
+    if !summary.recommendations.is_empty() {
+        body.push('\n');
+        for recommendation in &summary.recommendations {
+            body.push_str(&format!("- {}\n", recommendation));
+        }
+    }
+    if mention_owners {
+        // Email owners cannot be mentioned.
+        let mut owners: Vec<&str> = comments
+            .iter()
+            .filter(|comment| comment.severity == core::comment::Severity::Error)
+            .flat_map(|comment| comment.owners.iter().map(String::as_str))
+            .filter(|owner| owner.starts_with('@'))
+            .collect();
+        owners.sort_unstable();
+        owners.dedup();
+        if !owners.is_empty() {
+            body.push_str(&format!(
+                "\ncc {}: errors were found in files you own.\n",
+                owners.join(" ")
+            ));
+        }
+    }
+    if !unanchored.is_empty() {
+        body.push_str("\n### Findings outside the diff\n\n");
+        for comment in unanchored {
+            body.push_str(&format!(
+                "- `{}:{}` {}\n",
+                comment.file_path.display(),
+                comment.line_number,
+                format_github_comment_body(comment, None).replace('\n', " ")
+            ));
+        }
+    }
+
+    // Hidden marker read back by `pr --incremental` to find the last reviewed head.
+    body.push_str(&format!("\n<!-- {}{} -->\n", REVIEWED_SHA_MARKER, head_sha));
+
+    // Findings a `block` policy marked stop the PR from merging under branch protection.
+    let event = if comments.iter().any(core::policy::is_blocking) {
+        "REQUEST_CHANGES"
+    } else {
+        "COMMENT"
     };
 
-    let adapter = adapters::llm::create_adapter(&model_config)?;
+    serde_json::json!({
+        "commit_id": head_sha,
+        "event": event,
+        "body": body,
+        "comments": inline,
+    })
+}
 
-    let (system_prompt, user_prompt) =
-        core::CommitPromptBuilder::build_commit_prompt(&diff_content);
+fn format_github_comment_body(
+    comment: &core::Comment,
+    suggestion: Option<&core::position::SuggestedChange>,
+) -> String {
+    let mut body = format!("**{:?}**: {}", comment.severity, comment.content);
+    if let Some(change) = suggestion {
+        body.push_str("\n\n");
+        body.push_str(&change.body);
+    } else if let Some(text) = &comment.suggestion {
+        body.push_str(&format!("\n\n💡 {}", text));
+    }
+    body
+}
 
-    let request = adapters::llm::LLMRequest {
-        system_prompt,
-        user_prompt,
-        temperature: Some(0.3),
-        max_tokens: Some(500),
+/// Suggests a commit message for the staged changes (with `amend`, for the amended commit).
+/// With `message_file`, as run by the prepare-commit-msg hook, the suggestion goes into that
+/// file instead, falling back to one built from the file list when the model is unreachable.
+async fn suggest_commit_message(
+    config: config::Config,
+    message_file: Option<PathBuf>,
+    amend: bool,
+) -> Result<()> {
+    let git = core::GitIntegration::new(".")?;
+    let diff_content = if amend {
+        git.get_amend_diff()?
+    } else {
+        git.get_staged_diff()?
     };
 
-    let response = adapter.complete(request).await?;
-    let commit_message = core::CommitPromptBuilder::extract_commit_message(&response.content);
+    if diff_content.is_empty() {
+        if message_file.is_none() {
+            println!("No staged changes found. Stage your changes with 'git add' first.");
+        }
+        return Ok(());
+    }
+
+    let suggested = request_commit_message(&config, &diff_content).await;
+    if let Some(message_file) = message_file {
+        let commit_message = suggested.unwrap_or_else(|err| {
+            eprintln!("diffscope: using an offline commit message ({})", err);
+            core::CommitPromptBuilder::fallback_commit_message(&diff_content)
+        });
+        let existing = std::fs::read_to_string(&message_file).unwrap_or_default();
+        std::fs::write(
+            &message_file,
+            core::CommitPromptBuilder::fill_message_file(&existing, &commit_message),
+        )?;
+        return Ok(());
+    }
+    let commit_message = suggested?;
 
     println!("\nSuggested commit message:");
     println!("{}", commit_message);
@@ -829,6 +2496,27 @@ async fn suggest_commit_message(config: config::Config) -> Result<()> {
     Ok(())
 }
 
+async fn request_commit_message(config: &config::Config, diff_content: &str) -> Result<String> {
+    let adapter = adapters::llm::create_adapter(&config.model_config())?;
+
+    let (system_prompt, user_prompt) = core::CommitPromptBuilder::build_commit_prompt(diff_content);
+
+    let request = adapters::llm::LLMRequest {
+        system_prompt,
+        user_prompt,
+        temperature: Some(0.3),
+        max_tokens: Some(500),
+        cached_context: None,
+    };
+
+    let response = adapter.complete(request).await?;
+    let commit_message = core::CommitPromptBuilder::extract_commit_message(&response.content);
+    if commit_message.is_empty() {
+        anyhow::bail!("the model returned no commit message");
+    }
+    Ok(commit_message)
+}
+
 async fn suggest_pr_title(config: config::Config) -> Result<()> {
     let git = core::GitIntegration::new(".")?;
     let base_branch = git
@@ -841,14 +2529,7 @@ async fn suggest_pr_title(config: config::Config) -> Result<()> {
         return Ok(());
     }
 
-    let model_config = adapters::llm::ModelConfig {
-        model_name: config.model.clone(),
-        api_key: config.api_key.clone(),
-        base_url: config.base_url.clone(),
-        temperature: config.temperature,
-        max_tokens: config.max_tokens,
-        openai_use_responses: config.openai_use_responses,
-    };
+    let model_config = config.model_config();
 
     let adapter = adapters::llm::create_adapter(&model_config)?;
 
@@ -860,6 +2541,7 @@ async fn suggest_pr_title(config: config::Config) -> Result<()> {
         user_prompt,
         temperature: Some(0.3),
         max_tokens: Some(200),
+        cached_context: None,
     };
 
     let response = adapter.complete(request).await?;
@@ -960,253 +2642,126 @@ async fn review_diff_content_with_repo(
     format: OutputFormat,
     repo_path: &Path,
 ) -> Result<()> {
-    let comments = review_diff_content_raw(diff_content, config, repo_path).await?;
-    output_comments(&comments, None, format).await
+    let outcome = review_diff_content_raw(diff_content, config.clone(), repo_path).await?;
+    output_comments(&outcome.comments, &outcome.skipped, None, format, &config).await?;
+    finish_review(&outcome.comments, &outcome.skipped, &config)
 }
 
 async fn review_diff_content_raw(
     diff_content: &str,
     config: config::Config,
     repo_path: &Path,
-) -> Result<Vec<core::Comment>> {
-    let diffs = core::DiffParser::parse_unified_diff(diff_content)?;
-    info!("Parsed {} file diffs", diffs.len());
-    let symbol_index = build_symbol_index(&config, repo_path);
-
-    // Initialize plugin manager and load builtin plugins
-    let mut plugin_manager = plugins::plugin::PluginManager::new();
-    plugin_manager.load_builtin_plugins(&config.plugins).await?;
-
-    let model_config = adapters::llm::ModelConfig {
-        model_name: config.model.clone(),
-        api_key: config.api_key.clone(),
-        base_url: config.base_url.clone(),
-        temperature: config.temperature,
-        max_tokens: config.max_tokens,
-        openai_use_responses: config.openai_use_responses,
-    };
-
-    let adapter = adapters::llm::create_adapter(&model_config)?;
-    let base_prompt_config = core::prompt::PromptConfig {
-        max_context_chars: config.max_context_chars,
-        max_diff_chars: config.max_diff_chars,
-        ..Default::default()
-    };
-    let mut all_comments = Vec::new();
-
-    let repo_path_str = repo_path.to_string_lossy().to_string();
-    let context_fetcher = core::ContextFetcher::new(repo_path.to_path_buf());
+) -> Result<ReviewOutcome> {
+    ReviewEngine::new(config)
+        .with_repo_root(repo_path)
+        .review(diff_content)
+        .await
+}
 
-    for diff in &diffs {
-        // Check if file should be excluded
-        if config.should_exclude(&diff.file_path) {
-            info!("Skipping excluded file: {}", diff.file_path.display());
-            continue;
-        }
-        if diff.is_deleted {
-            info!("Skipping deleted file: {}", diff.file_path.display());
-            continue;
-        }
-        if diff.is_binary || diff.hunks.is_empty() {
-            info!("Skipping non-text diff: {}", diff.file_path.display());
-            continue;
-        }
-
-        let mut context_chunks = context_fetcher
-            .fetch_context_for_file(
-                &diff.file_path,
-                &diff
-                    .hunks
-                    .iter()
-                    .map(|h| (h.new_start, h.new_start + h.new_lines.saturating_sub(1)))
-                    .collect::<Vec<_>>(),
-            )
-            .await?;
-
-        // Run pre-analyzers to get additional context
-        let analyzer_chunks = plugin_manager
-            .run_pre_analyzers(diff, &repo_path_str)
-            .await?;
-        context_chunks.extend(analyzer_chunks);
-
-        // Extract symbols from diff and fetch their definitions
-        let symbols = extract_symbols_from_diff(diff);
-        if !symbols.is_empty() {
-            let definition_chunks = context_fetcher
-                .fetch_related_definitions(&diff.file_path, &symbols)
-                .await?;
-            context_chunks.extend(definition_chunks);
-            if let Some(index) = &symbol_index {
-                let index_chunks = context_fetcher
-                    .fetch_related_definitions_with_index(
-                        &diff.file_path,
-                        &symbols,
-                        index,
-                        config.symbol_index_max_locations,
-                    )
-                    .await?;
-                context_chunks.extend(index_chunks);
-            }
-        }
-
-        // Get path-specific configuration
-        let path_config = config.get_path_config(&diff.file_path);
-
-        // Add focus areas and extra context if configured
-        if let Some(pc) = path_config {
-            if !pc.focus.is_empty() {
-                let focus_chunk = core::LLMContextChunk {
-                    content: format!("Focus areas for this file: {}", pc.focus.join(", ")),
-                    context_type: core::ContextType::Documentation,
-                    file_path: diff.file_path.clone(),
-                    line_range: None,
-                };
-                context_chunks.push(focus_chunk);
-            }
-            if !pc.extra_context.is_empty() {
-                let extra_chunks = context_fetcher
-                    .fetch_additional_context(&pc.extra_context)
-                    .await?;
-                context_chunks.extend(extra_chunks);
-            }
-        }
-
-        // Create prompt builder with config
-        let mut local_prompt_config = base_prompt_config.clone();
-        if let Some(custom_prompt) = &config.system_prompt {
-            local_prompt_config.system_prompt = custom_prompt.clone();
-        }
-        if let Some(pc) = path_config {
-            if let Some(ref prompt) = pc.system_prompt {
-                local_prompt_config.system_prompt = prompt.clone();
-            }
-        }
-        if let Some(guidance) = build_review_guidance(&config, path_config) {
-            local_prompt_config.system_prompt.push_str("\n\n");
-            local_prompt_config.system_prompt.push_str(&guidance);
+/// Describes how `comments` break the `fail_on` / `max_issues` policy or include findings a
+/// `block` policy marked, if they do.
+fn review_policy_violation(comments: &[core::Comment], config: &config::Config) -> Option<String> {
+    let blocking = comments
+        .iter()
+        .filter(|comment| core::policy::is_blocking(comment))
+        .count();
+    if blocking > 0 {
+        return Some(format!("{} merge-blocking finding(s)", blocking));
+    }
+    if let Some(fail_on) = config.fail_on {
+        let failing = comments
+            .iter()
+            .filter(|comment| fail_on.is_triggered_by(&comment.severity))
+            .count();
+        if failing > 0 {
+            return Some(format!(
+                "{} finding(s) at or above {} severity",
+                failing,
+                format!("{:?}", fail_on).to_lowercase()
+            ));
         }
-        let local_prompt_builder = core::PromptBuilder::new(local_prompt_config);
-        let (system_prompt, user_prompt) =
-            local_prompt_builder.build_prompt(diff, &context_chunks)?;
-
-        let request = adapters::llm::LLMRequest {
-            system_prompt,
-            user_prompt,
-            temperature: None,
-            max_tokens: None,
-        };
-
-        let response = adapter.complete(request).await?;
-
-        if let Ok(raw_comments) = parse_llm_response(&response.content, &diff.file_path) {
-            let mut comments = core::CommentSynthesizer::synthesize(raw_comments)?;
-
-            // Apply severity overrides if configured
-            if let Some(pc) = path_config {
-                for comment in &mut comments {
-                    for (category, severity) in &pc.severity_overrides {
-                        if format!("{:?}", comment.category).to_lowercase()
-                            == category.to_lowercase()
-                        {
-                            comment.severity = match severity.to_lowercase().as_str() {
-                                "error" => core::comment::Severity::Error,
-                                "warning" => core::comment::Severity::Warning,
-                                "info" => core::comment::Severity::Info,
-                                "suggestion" => core::comment::Severity::Suggestion,
-                                _ => comment.severity.clone(),
-                            };
-                        }
-                    }
-                }
-            }
-
-            let comments = filter_comments_for_diff(diff, comments);
-            all_comments.extend(comments);
+    }
+    if let Some(max_issues) = config.max_issues {
+        if comments.len() > max_issues {
+            return Some(format!(
+                "{} findings exceed max_issues ({})",
+                comments.len(),
+                max_issues
+            ));
         }
     }
-
-    // Run post-processors to filter and refine comments
-    let processed_comments = plugin_manager
-        .run_post_processors(all_comments, &repo_path_str)
-        .await?;
-    let processed_comments = apply_confidence_threshold(processed_comments, config.min_confidence);
-
-    Ok(processed_comments)
+    None
 }
 
-fn parse_llm_response(content: &str, file_path: &Path) -> Result<Vec<core::comment::RawComment>> {
-    let mut comments = Vec::new();
-    static LINE_PATTERN: Lazy<Regex> =
-        Lazy::new(|| Regex::new(r"(?i)line\s+(\d+):\s*(.+)").unwrap());
-
-    for line in content.lines() {
-        let trimmed = line.trim();
-
-        // Skip empty lines and common non-issue lines
-        if trimmed.is_empty()
-            || trimmed.starts_with("```")
-            || trimmed.starts_with('#')
-            || trimmed.starts_with('<')
-            || trimmed.contains("Here are")
-            || trimmed.contains("Here is")
-            || trimmed.contains("review of")
-        {
-            continue;
-        }
-
-        if let Some(caps) = LINE_PATTERN.captures(line) {
-            let line_number: usize = caps.get(1).unwrap().as_str().parse()?;
-            let comment_text = caps.get(2).unwrap().as_str().trim();
+/// Fails the command (non-zero exit) when the review breaks the configured policy. Called after
+/// the output is written so CI logs still show the findings.
+fn enforce_review_policy(comments: &[core::Comment], config: &config::Config) -> Result<()> {
+    match review_policy_violation(comments, config) {
+        Some(violation) => anyhow::bail!("Review failed: {}", violation),
+        None => Ok(()),
+    }
+}
 
-            // Extract suggestion if present
-            let (content, suggestion) = if let Some(sugg_idx) = comment_text.rfind(". Consider ") {
-                (
-                    comment_text[..sugg_idx + 1].to_string(),
-                    Some(
-                        comment_text[sugg_idx + 11..]
-                            .trim_end_matches('.')
-                            .to_string(),
-                    ),
-                )
-            } else if let Some(sugg_idx) = comment_text.rfind(". Use ") {
-                (
-                    comment_text[..sugg_idx + 1].to_string(),
-                    Some(
-                        comment_text[sugg_idx + 6..]
-                            .trim_end_matches('.')
-                            .to_string(),
-                    ),
-                )
-            } else {
-                (comment_text.to_string(), None)
-            };
+/// Exit status of a review that passed its policy but could not review every file.
+const PARTIAL_REVIEW_EXIT_CODE: i32 = 3;
 
-            comments.push(core::comment::RawComment {
-                file_path: file_path.to_path_buf(),
-                line_number,
-                content,
-                suggestion,
-                severity: None,
-                category: None,
-                confidence: None,
-                fix_effort: None,
-                tags: Vec::new(),
-            });
-        }
+/// Enforces the review policy, then exits with `PARTIAL_REVIEW_EXIT_CODE` when files were
+/// skipped, so CI can tell an incomplete review from a failing one.
+fn finish_review(
+    comments: &[core::Comment],
+    skipped: &[core::comment::SkippedFile],
+    config: &config::Config,
+) -> Result<()> {
+    enforce_review_policy(comments, config)?;
+    if !skipped.is_empty() {
+        use std::io::Write;
+        eprintln!(
+            "{} file(s) skipped due to errors: {}",
+            skipped.len(),
+            skipped
+                .iter()
+                .map(|file| file.file_path.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        std::io::stdout().flush()?;
+        std::process::exit(PARTIAL_REVIEW_EXIT_CODE);
     }
-
-    Ok(comments)
+    Ok(())
 }
 
 async fn output_comments(
     comments: &[core::Comment],
+    skipped: &[core::comment::SkippedFile],
     output_path: Option<PathBuf>,
     format: OutputFormat,
+    config: &config::Config,
 ) -> Result<()> {
     let output = match format {
+        OutputFormat::Json if config.json_include_skipped => {
+            serde_json::to_string_pretty(&serde_json::json!({
+                "comments": comments,
+                "skipped": skipped,
+            }))?
+        }
         OutputFormat::Json => serde_json::to_string_pretty(comments)?,
         OutputFormat::Patch => format_as_patch(comments),
-        OutputFormat::Markdown => format_as_markdown(comments),
+        OutputFormat::Markdown => match config.report_template.as_deref() {
+            Some(template) => {
+                let summary = core::CommentSynthesizer::generate_summary(comments);
+                let mut context =
+                    core::report_template::ReportContext::new("review", comments, &summary);
+                context.skipped = skipped;
+                core::report_template::render(template, &context)?
+            }
+            None => format_as_markdown(comments) + &format_skipped_section(skipped),
+        },
+        OutputFormat::Rdjson => format_as_rdjson(comments, skipped)?,
+        OutputFormat::Rdjsonl => format_as_rdjsonl(comments, skipped)?,
+        OutputFormat::Junit => format_as_junit(comments, skipped),
+        OutputFormat::Checkstyle => format_as_checkstyle(comments, skipped),
+        OutputFormat::Codeclimate => format_as_codeclimate(comments, skipped)?,
+        OutputFormat::Html => core::html_report::render(comments),
     };
 
     if let Some(path) = output_path {
@@ -1218,6 +2773,25 @@ async fn output_comments(
     Ok(())
 }
 
+/// "Files Skipped Due to Errors" for markdown reports; empty when nothing was skipped.
+fn format_skipped_section(skipped: &[core::comment::SkippedFile]) -> String {
+    if skipped.is_empty() {
+        return String::new();
+    }
+    let mut output = String::from("\n## ⚠️ Files Skipped Due to Errors\n\n");
+    output.push_str(
+        "The model could not review these files; findings from analyzers are still included.\n\n",
+    );
+    for file in skipped {
+        output.push_str(&format!(
+            "- `{}`: {}\n",
+            file.file_path.display(),
+            file.error
+        ));
+    }
+    output
+}
+
 fn format_as_patch(comments: &[core::Comment]) -> String {
     let mut output = String::new();
     for comment in comments {
@@ -1235,6 +2809,290 @@ fn format_as_patch(comments: &[core::Comment]) -> String {
     output
 }
 
+/// How machine-readable formats describe a file the model could not review.
+fn skipped_message(file: &core::comment::SkippedFile) -> String {
+    format!("Not reviewed: {}", file.error)
+}
+
+const RDJSON_SOURCE: &str = "diffscope";
+
+/// One finding as a reviewdog `Diagnostic`.
+fn rdjson_diagnostic(comment: &core::Comment) -> serde_json::Value {
+    let severity = match comment.severity {
+        core::comment::Severity::Error => "ERROR",
+        core::comment::Severity::Warning => "WARNING",
+        core::comment::Severity::Info | core::comment::Severity::Suggestion => "INFO",
+    };
+    let mut message = comment.content.clone();
+    if let Some(suggestion) = &comment.suggestion {
+        message.push_str(&format!("\n\nSuggestion: {}", suggestion));
+    }
+    serde_json::json!({
+        "message": message,
+        "location": {
+            "path": comment.file_path.to_string_lossy(),
+            "range": { "start": { "line": comment.line_number } },
+        },
+        "severity": severity,
+        "source": { "name": RDJSON_SOURCE },
+        "code": { "value": format!("{:?}", comment.category) },
+    })
+}
+
+/// A file the model could not review, as a file-level warning.
+fn rdjson_skipped_diagnostic(file: &core::comment::SkippedFile) -> serde_json::Value {
+    serde_json::json!({
+        "message": skipped_message(file),
+        "location": { "path": file.file_path.to_string_lossy() },
+        "severity": "WARNING",
+        "source": { "name": RDJSON_SOURCE },
+        "code": { "value": "Skipped" },
+    })
+}
+
+fn format_as_rdjson(
+    comments: &[core::Comment],
+    skipped: &[core::comment::SkippedFile],
+) -> Result<String> {
+    let diagnostics: Vec<_> = comments
+        .iter()
+        .map(rdjson_diagnostic)
+        .chain(skipped.iter().map(rdjson_skipped_diagnostic))
+        .collect();
+    Ok(serde_json::to_string_pretty(&serde_json::json!({
+        "source": {
+            "name": RDJSON_SOURCE,
+            "url": "https://github.com/haasonsaas/diffscope",
+        },
+        "diagnostics": diagnostics,
+    }))?)
+}
+
+fn format_as_rdjsonl(
+    comments: &[core::Comment],
+    skipped: &[core::comment::SkippedFile],
+) -> Result<String> {
+    let lines = comments
+        .iter()
+        .map(rdjson_diagnostic)
+        .chain(skipped.iter().map(rdjson_skipped_diagnostic))
+        .map(|diagnostic| serde_json::to_string(&diagnostic))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(lines.join("\n"))
+}
+
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Control characters other than tab/newline are not allowed in XML 1.0.
+            c if c.is_control() && c != '\t' && c != '\n' && c != '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Findings as failed JUnit test cases, grouped into one suite, so CI test reports list them.
+/// Files the model could not review are skipped test cases.
+fn format_as_junit(comments: &[core::Comment], skipped: &[core::comment::SkippedFile]) -> String {
+    let mut output = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    output.push_str(&format!(
+        "<testsuites name=\"diffscope\" tests=\"{0}\" failures=\"{1}\" skipped=\"{2}\">\n  <testsuite name=\"diffscope\" tests=\"{0}\" failures=\"{1}\" skipped=\"{2}\">\n",
+        (comments.len() + skipped.len()).max(1),
+        comments.len(),
+        skipped.len()
+    ));
+    if comments.is_empty() && skipped.is_empty() {
+        // Keep the report non-empty so CI shows a passing review instead of no tests.
+        output.push_str("    <testcase classname=\"diffscope\" name=\"review\"/>\n");
+    }
+    for comment in comments {
+        let mut details = format!(
+            "{}:{}\n{}",
+            comment.file_path.display(),
+            comment.line_number,
+            comment.content
+        );
+        if let Some(suggestion) = &comment.suggestion {
+            details.push_str(&format!("\nSuggestion: {}", suggestion));
+        }
+        output.push_str(&format!(
+            "    <testcase classname=\"{}\" name=\"line {}: {:?}\" file=\"{}\" line=\"{}\">\n      <failure message=\"{}\" type=\"{:?}\">{}</failure>\n    </testcase>\n",
+            xml_escape(&comment.file_path.to_string_lossy()),
+            comment.line_number,
+            comment.category,
+            xml_escape(&comment.file_path.to_string_lossy()),
+            comment.line_number,
+            xml_escape(&comment.content),
+            comment.severity,
+            xml_escape(&details)
+        ));
+    }
+    for file in skipped {
+        let path = xml_escape(&file.file_path.to_string_lossy());
+        output.push_str(&format!(
+            "    <testcase classname=\"{}\" name=\"review\" file=\"{}\">\n      <skipped message=\"{}\"/>\n    </testcase>\n",
+            path,
+            path,
+            xml_escape(&skipped_message(file))
+        ));
+    }
+    output.push_str("  </testsuite>\n</testsuites>\n");
+    output
+}
+
+fn format_as_checkstyle(
+    comments: &[core::Comment],
+    skipped: &[core::comment::SkippedFile],
+) -> String {
+    type FileEntries<'a> = (Vec<&'a core::Comment>, Vec<&'a core::comment::SkippedFile>);
+    let mut by_file: std::collections::BTreeMap<&Path, FileEntries> =
+        std::collections::BTreeMap::new();
+    for comment in comments {
+        by_file
+            .entry(comment.file_path.as_path())
+            .or_default()
+            .0
+            .push(comment);
+    }
+    for file in skipped {
+        by_file
+            .entry(file.file_path.as_path())
+            .or_default()
+            .1
+            .push(file);
+    }
+
+    let mut output =
+        String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<checkstyle version=\"4.3\">\n");
+    for (path, (file_comments, file_skipped)) in by_file {
+        output.push_str(&format!(
+            "  <file name=\"{}\">\n",
+            xml_escape(&path.to_string_lossy())
+        ));
+        for comment in file_comments {
+            let severity = match comment.severity {
+                core::comment::Severity::Error => "error",
+                core::comment::Severity::Warning => "warning",
+                core::comment::Severity::Info | core::comment::Severity::Suggestion => "info",
+            };
+            output.push_str(&format!(
+                "    <error line=\"{}\" severity=\"{}\" message=\"{}\" source=\"diffscope.{:?}\"/>\n",
+                comment.line_number,
+                severity,
+                xml_escape(&comment.content),
+                comment.category
+            ));
+        }
+        // Line 0 marks a file-level message.
+        for file in file_skipped {
+            output.push_str(&format!(
+                "    <error line=\"0\" severity=\"warning\" message=\"{}\" source=\"diffscope.Skipped\"/>\n",
+                xml_escape(&skipped_message(file))
+            ));
+        }
+        output.push_str("  </file>\n");
+    }
+    output.push_str("</checkstyle>\n");
+    output
+}
+
+/// Code Climate issue list. Fingerprints leave out line numbers so a finding keeps its identity
+/// when surrounding code moves, which lets GitLab tell new findings from existing ones.
+fn format_as_codeclimate(
+    comments: &[core::Comment],
+    skipped: &[core::comment::SkippedFile],
+) -> Result<String> {
+    use core::comment::{Category, Severity};
+
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    let mut issues: Vec<serde_json::Value> = comments
+        .iter()
+        .map(|comment| {
+            let path = comment.file_path.to_string_lossy();
+            let key = format!(
+                "{}\0{:?}\0{}",
+                path,
+                comment.category,
+                comment
+                    .content
+                    .split_whitespace()
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            );
+            // Identical findings in one file still need distinct fingerprints.
+            let occurrence = seen.entry(key.clone()).or_insert(0);
+            *occurrence += 1;
+            let digest = ring::digest::digest(
+                &ring::digest::SHA256,
+                format!("{}\0{}", key, occurrence).as_bytes(),
+            );
+            let fingerprint: String = digest.as_ref()[..16]
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect();
+
+            let severity = match comment.severity {
+                Severity::Error => "critical",
+                Severity::Warning => "major",
+                Severity::Info => "minor",
+                Severity::Suggestion => "info",
+            };
+            let category = match comment.category {
+                Category::Bug | Category::Testing => "Bug Risk",
+                Category::Security => "Security",
+                Category::Performance => "Performance",
+                Category::Style | Category::BestPractice => "Style",
+                Category::Documentation => "Clarity",
+                Category::Maintainability | Category::Architecture => "Complexity",
+            };
+            serde_json::json!({
+                "type": "issue",
+                "check_name": format!("diffscope/{:?}", comment.category),
+                "description": comment.content,
+                "content": { "body": comment.suggestion.clone().unwrap_or_default() },
+                "categories": [category],
+                "severity": severity,
+                "fingerprint": fingerprint,
+                "location": {
+                    "path": path,
+                    "lines": { "begin": comment.line_number },
+                },
+            })
+        })
+        .collect();
+    for file in skipped {
+        let path = file.file_path.to_string_lossy();
+        let digest = ring::digest::digest(
+            &ring::digest::SHA256,
+            format!("{}\0Skipped", path).as_bytes(),
+        );
+        let fingerprint: String = digest.as_ref()[..16]
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        issues.push(serde_json::json!({
+            "type": "issue",
+            "check_name": "diffscope/Skipped",
+            "description": skipped_message(file),
+            "categories": ["Bug Risk"],
+            "severity": "major",
+            "fingerprint": fingerprint,
+            "location": {
+                "path": path,
+                "lines": { "begin": 1 },
+            },
+        }));
+    }
+    Ok(serde_json::to_string_pretty(&issues)?)
+}
+
 fn format_as_markdown(comments: &[core::Comment]) -> String {
     let mut output = String::new();
 
@@ -1312,6 +3170,8 @@ fn format_as_markdown(comments: &[core::Comment]) -> String {
     }
     output.push('\n');
 
+    output.push_str(&plugins::builtin::format_breaking_changes_section(comments));
+
     // Recommendations
     if !summary.recommendations.is_empty() {
         output.push_str("### Recommendations\n\n");
@@ -1323,6 +3183,31 @@ fn format_as_markdown(comments: &[core::Comment]) -> String {
 
     output.push_str("---\n\n## Detailed Issues\n\n");
 
+    // With CODEOWNERS, findings are grouped by owning team, then by file.
+    if comments.iter().any(|comment| !comment.owners.is_empty()) {
+        let mut comments_by_owner = std::collections::BTreeMap::new();
+        for comment in comments {
+            comments_by_owner
+                .entry(comment.owners.join(" "))
+                .or_insert_with(Vec::new)
+                .push(comment);
+        }
+        for (owners, owned_comments) in comments_by_owner {
+            if owners.is_empty() {
+                output.push_str("## No Owner\n\n");
+            } else {
+                output.push_str(&format!("## Owned by {}\n\n", owners));
+            }
+            push_markdown_file_sections(&mut output, &owned_comments);
+        }
+    } else {
+        push_markdown_file_sections(&mut output, &comments.iter().collect::<Vec<_>>());
+    }
+
+    output
+}
+
+fn push_markdown_file_sections(output: &mut String, comments: &[&core::Comment]) {
     // Group comments by file
     let mut comments_by_file = std::collections::HashMap::new();
     for comment in comments {
@@ -1386,14 +3271,13 @@ fn format_as_markdown(comments: &[core::Comment]) -> String {
             output.push_str("---\n\n");
         }
     }
-
-    output
 }
 
 async fn smart_review_command(
     config: config::Config,
     diff_path: Option<PathBuf>,
     output_path: Option<PathBuf>,
+    json: bool,
 ) -> Result<()> {
     info!(
         "Starting smart review analysis with model: {}",
@@ -1405,7 +3289,8 @@ async fn smart_review_command(
         .and_then(|git| git.workdir())
         .unwrap_or_else(|| PathBuf::from("."));
     let repo_path_str = repo_root.to_string_lossy().to_string();
-    let context_fetcher = core::ContextFetcher::new(repo_root.clone());
+    let context_fetcher = core::ContextFetcher::new(repo_root.clone())
+        .with_function_scope(config.function_scope_context);
 
     let mut plugin_manager = plugins::plugin::PluginManager::new();
     plugin_manager.load_builtin_plugins(&config.plugins).await?;
@@ -1433,20 +3318,38 @@ async fn smart_review_command(
 
     let diffs = core::DiffParser::parse_unified_diff(&diff_content)?;
     info!("Parsed {} file diffs", diffs.len());
-    let walkthrough = build_change_walkthrough(&diffs);
+    plugin_manager.begin_review(&diffs).await;
+    let impact = if config.plugins.dependency_impact {
+        let graph = core::DependencyGraph::shared(
+            &repo_root,
+            core::dependency_graph::DEFAULT_MAX_GRAPH_FILES,
+        );
+        build_impact_section(&diffs, &graph)
+    } else {
+        String::new()
+    };
+    let coverage_report = config
+        .plugins
+        .coverage_report
+        .as_deref()
+        .map(core::CoverageReport::load)
+        .transpose()?;
+    let coverage = match &coverage_report {
+        Some(report) => core::coverage::format_coverage_section(report, &diffs),
+        None => String::new(),
+    };
     let symbol_index = build_symbol_index(&config, &repo_root);
+    let repo_map = build_repo_map(&config, symbol_index.as_ref());
+    let semantic_search = build_semantic_search(&config, &repo_root).await;
+    let follow_up = build_follow_up(&config, &context_fetcher, symbol_index.as_ref());
 
-    let model_config = adapters::llm::ModelConfig {
-        model_name: config.model.clone(),
-        api_key: config.api_key.clone(),
-        base_url: config.base_url.clone(),
-        temperature: config.temperature,
-        max_tokens: config.max_tokens,
-        openai_use_responses: config.openai_use_responses,
-    };
+    let model_config = config.model_config();
 
     let adapter = adapters::llm::create_adapter(&model_config)?;
+    let triage_adapter = build_triage_adapter(&config)?;
+    let verified_severities = config.verified_severities();
     let mut all_comments = Vec::new();
+    let mut skipped = Vec::new();
     let mut pr_summary = if config.smart_review_summary {
         match core::GitIntegration::new(&repo_root) {
             Ok(git) => {
@@ -1477,8 +3380,29 @@ async fn smart_review_command(
         None
     };
 
+    let excluded = config.exclusion_filter(&repo_root);
+    let file_summaries = if config.smart_review_walkthrough {
+        // Excluded files (lockfiles, generated code, `exclude_patterns`) never reach the model.
+        let summarized: Vec<core::UnifiedDiff> = diffs
+            .iter()
+            .filter(|diff| !excluded(&diff.file_path))
+            .cloned()
+            .collect();
+        core::PRSummaryGenerator::generate_file_summaries(&summarized, adapter.as_ref())
+            .await
+            .unwrap_or_else(|err| {
+                warn!("Walkthrough summaries failed: {}", err);
+                HashMap::new()
+            })
+    } else {
+        HashMap::new()
+    };
+    let walkthrough = build_change_walkthrough(&diffs, &file_summaries);
+
     if config.smart_review_diagram {
-        match core::PRSummaryGenerator::generate_change_diagram(&diffs, adapter.as_ref()).await {
+        match core::PRSummaryGenerator::generate_change_diagram(&diffs, adapter.as_ref(), None)
+            .await
+        {
             Ok(Some(diagram)) => {
                 if let Some(summary) = &mut pr_summary {
                     summary.visual_diff = Some(diagram);
@@ -1493,10 +3417,25 @@ async fn smart_review_command(
         }
     }
 
+    let hotspot_notes = load_hotspot_notes(&config, &repo_root, &diffs);
+    let sources = ContextSources {
+        config: &config,
+        repo_path: &repo_path_str,
+        context_fetcher: &context_fetcher,
+        plugin_manager: &plugin_manager,
+        symbol_index: symbol_index.as_ref(),
+        repo_map: repo_map.as_ref(),
+        semantic_search: semantic_search.as_ref(),
+        hotspot_notes: &hotspot_notes,
+    };
+    let shared_context = sources.shared_context(&diffs);
     for diff in &diffs {
         // Check if file should be excluded
-        if config.should_exclude(&diff.file_path) {
+        if excluded(&diff.file_path) {
             info!("Skipping excluded file: {}", diff.file_path.display());
+            all_comments.extend(
+                skipped_lockfile_findings(&config, &plugin_manager, diff, &repo_path_str).await,
+            );
             continue;
         }
         if diff.is_deleted {
@@ -1508,125 +3447,147 @@ async fn smart_review_command(
             continue;
         }
 
-        let mut context_chunks = context_fetcher
-            .fetch_context_for_file(
-                &diff.file_path,
-                &diff
-                    .hunks
-                    .iter()
-                    .map(|h| (h.new_start, h.new_start + h.new_lines.saturating_sub(1)))
-                    .collect::<Vec<_>>(),
-            )
-            .await?;
-
-        // Run pre-analyzers to get additional context
-        let analyzer_chunks = plugin_manager
-            .run_pre_analyzers(diff, &repo_path_str)
-            .await?;
-        context_chunks.extend(analyzer_chunks);
-
-        // Get path-specific configuration
+        let FileContext {
+            chunks: context_chunks,
+            findings,
+        } = sources.for_file(diff).await?;
         let path_config = config.get_path_config(&diff.file_path);
-
-        // Add focus areas to context if configured
-        if let Some(pc) = path_config {
-            if !pc.focus.is_empty() {
-                let focus_chunk = core::LLMContextChunk {
-                    content: format!("Focus areas for this file: {}", pc.focus.join(", ")),
-                    context_type: core::ContextType::Documentation,
-                    file_path: diff.file_path.clone(),
-                    line_range: None,
-                };
-                context_chunks.push(focus_chunk);
-            }
-            if !pc.extra_context.is_empty() {
-                let extra_chunks = context_fetcher
-                    .fetch_additional_context(&pc.extra_context)
-                    .await?;
-                context_chunks.extend(extra_chunks);
+        let guidance = build_review_guidance(&config, &diff.file_path, path_config);
+        let prompt_limits = config.prompt_limits();
+        let triaged = match &triage_adapter {
+            Some(screener) => {
+                core::triage::triage_hunks(screener.as_ref(), diff.clone(), config.triage.threshold)
+                    .await
             }
+            None => diff.clone(),
+        };
+        let batches = if triaged.hunks.is_empty() {
+            Vec::new()
+        } else {
+            core::prompt::split_diff_into_batches(&triaged, &prompt_limits)
+        };
+        if batches.len() > 1 {
+            info!(
+                "Reviewing {} in {} batches to stay within the diff budget",
+                diff.file_path.display(),
+                batches.len()
+            );
         }
 
-        // Extract symbols and get definitions
-        let symbols = extract_symbols_from_diff(diff);
-        if !symbols.is_empty() {
-            let definition_chunks = context_fetcher
-                .fetch_related_definitions(&diff.file_path, &symbols)
-                .await?;
-            context_chunks.extend(definition_chunks);
-            if let Some(index) = &symbol_index {
-                let index_chunks = context_fetcher
-                    .fetch_related_definitions_with_index(
-                        &diff.file_path,
-                        &symbols,
-                        index,
-                        config.symbol_index_max_locations,
-                    )
-                    .await?;
-                context_chunks.extend(index_chunks);
-            }
-        }
+        for batch in &batches {
+            let (system_prompt, user_prompt) =
+                core::SmartReviewPromptBuilder::build_enhanced_review_prompt(
+                    batch,
+                    &context_chunks,
+                    &prompt_limits,
+                    guidance.as_deref(),
+                )?;
+
+            let request = adapters::llm::LLMRequest {
+                system_prompt,
+                user_prompt,
+                temperature: Some(0.2), // Lower temperature for more consistent analysis
+                max_tokens: Some(4000),
+                cached_context: shared_context.clone(),
+            };
 
-        let guidance = build_review_guidance(&config, path_config);
-        let (system_prompt, user_prompt) =
-            core::SmartReviewPromptBuilder::build_enhanced_review_prompt(
-                diff,
-                &context_chunks,
-                config.max_context_chars,
-                config.max_diff_chars,
-                guidance.as_deref(),
-            )?;
+            let response = match adapter.complete(request.clone()).await {
+                Ok(response) => response,
+                Err(err) if adapters::llm::is_fatal(&err) => return Err(err),
+                Err(err) => {
+                    warn!("Skipping {}: {:#}", diff.file_path.display(), err);
+                    skipped.push(core::comment::SkippedFile::new(&diff.file_path, &err));
+                    break;
+                }
+            };
+            let content = match &follow_up {
+                Some(follow_up) => {
+                    follow_up
+                        .revise(
+                            adapter.as_ref(),
+                            &request,
+                            batch,
+                            &context_chunks,
+                            response.content,
+                        )
+                        .await
+                }
+                None => response.content,
+            };
 
-        let request = adapters::llm::LLMRequest {
-            system_prompt,
-            user_prompt,
-            temperature: Some(0.2), // Lower temperature for more consistent analysis
-            max_tokens: Some(4000),
-        };
+            if let Ok(raw_comments) = parse_smart_review_response(&content, &diff.file_path) {
+                let mut comments = core::CommentSynthesizer::synthesize(raw_comments)?;
 
-        let response = adapter.complete(request).await?;
-
-        if let Ok(raw_comments) = parse_smart_review_response(&response.content, &diff.file_path) {
-            let mut comments = core::CommentSynthesizer::synthesize(raw_comments)?;
-
-            // Apply severity overrides if configured
-            if let Some(pc) = path_config {
-                for comment in &mut comments {
-                    for (category, severity) in &pc.severity_overrides {
-                        if format!("{:?}", comment.category).to_lowercase()
-                            == category.to_lowercase()
-                        {
-                            comment.severity = match severity.to_lowercase().as_str() {
-                                "error" => core::comment::Severity::Error,
-                                "warning" => core::comment::Severity::Warning,
-                                "info" => core::comment::Severity::Info,
-                                "suggestion" => core::comment::Severity::Suggestion,
-                                _ => comment.severity.clone(),
-                            };
-                        }
-                    }
-                }
-            }
+                apply_severity_overrides(path_config, &mut comments);
 
-            let comments = filter_comments_for_diff(diff, comments);
-            all_comments.extend(comments);
+                let comments = filter_comments_for_diff(batch, comments);
+                let comments = core::verification::verify_findings(
+                    adapter.as_ref(),
+                    batch,
+                    comments,
+                    &verified_severities,
+                )
+                .await;
+                all_comments.extend(comments);
+            }
         }
+        all_comments.extend(findings);
     }
 
     // Run post-processors to filter and refine comments
-    let processed_comments = plugin_manager
+    let mut processed_comments = plugin_manager
         .run_post_processors(all_comments, &repo_path_str)
         .await?;
+    if config.confidence_calibration {
+        apply_confidence_calibration(&config, &load_feedback(&config), &mut processed_comments);
+    }
+    apply_category_weights(&config, &mut processed_comments);
     let processed_comments = apply_confidence_threshold(processed_comments, config.min_confidence);
+    let mut processed_comments = core::policy::apply_policies(processed_comments, &config.policies);
+    apply_code_owners(&config, &repo_root, &mut processed_comments);
 
     // Generate summary and output results
     let summary = core::CommentSynthesizer::generate_summary(&processed_comments);
-    let output = format_smart_review_output(
+    let risk = core::risk::assess(
+        &diffs,
         &processed_comments,
-        &summary,
-        pr_summary.as_ref(),
-        &walkthrough,
+        &repo_root,
+        coverage_report.as_ref(),
     );
+    let output = match &config.report_template {
+        _ if json => serde_json::to_string_pretty(&serde_json::json!({
+            "comments": processed_comments,
+            "summary": summary,
+            "risk": risk,
+            "skipped": skipped,
+        }))?,
+        Some(template) => {
+            let mut context = core::report_template::ReportContext::new(
+                "smart_review",
+                &processed_comments,
+                &summary,
+            );
+            context.pr_summary = pr_summary.as_ref().map(|summary| summary.to_markdown());
+            context.walkthrough = &walkthrough;
+            context.impact = &impact;
+            context.coverage = &coverage;
+            context.risk = Some(&risk);
+            context.skipped = &skipped;
+            core::report_template::render(template, &context)?
+        }
+        None => {
+            format_smart_review_output(
+                &processed_comments,
+                &summary,
+                pr_summary.as_ref(),
+                &walkthrough,
+                &impact,
+                &coverage,
+                &core::risk::format_risk_section(&risk, 5),
+                &config.smart_review_sections,
+            ) + &format_skipped_section(&skipped)
+        }
+    };
 
     if let Some(path) = output_path {
         tokio::fs::write(path, output).await?;
@@ -1634,7 +3595,7 @@ async fn smart_review_command(
         println!("{}", output);
     }
 
-    Ok(())
+    finish_review(&processed_comments, &skipped, &config)
 }
 
 fn parse_smart_review_response(
@@ -1818,53 +3779,8 @@ fn parse_smart_tags(value: &str) -> Vec<String> {
         .collect()
 }
 
-fn format_smart_review_output(
-    comments: &[core::Comment],
-    summary: &core::comment::ReviewSummary,
-    pr_summary: Option<&core::pr_summary::PRSummary>,
-    walkthrough: &str,
-) -> String {
+fn format_issue_breakdown(summary: &core::comment::ReviewSummary) -> String {
     let mut output = String::new();
-
-    output.push_str("# 🤖 Smart Review Analysis Results\n\n");
-
-    // Executive Summary
-    output.push_str("## 📊 Executive Summary\n\n");
-    let score_emoji = if summary.overall_score >= 8.0 {
-        "🟢"
-    } else if summary.overall_score >= 6.0 {
-        "🟡"
-    } else {
-        "🔴"
-    };
-    output.push_str(&format!(
-        "{} **Code Quality Score:** {:.1}/10\n",
-        score_emoji, summary.overall_score
-    ));
-    output.push_str(&format!(
-        "📝 **Total Issues Found:** {}\n",
-        summary.total_comments
-    ));
-    output.push_str(&format!(
-        "🚨 **Critical Issues:** {}\n",
-        summary.critical_issues
-    ));
-    output.push_str(&format!(
-        "📁 **Files Analyzed:** {}\n\n",
-        summary.files_reviewed
-    ));
-
-    if let Some(pr_summary) = pr_summary {
-        output.push_str(&format_pr_summary_section(pr_summary));
-        output.push('\n');
-    }
-
-    if !walkthrough.trim().is_empty() {
-        output.push_str(walkthrough);
-        output.push('\n');
-    }
-
-    // Quick Stats
     output.push_str("### 📈 Issue Breakdown\n\n");
 
     output.push_str("#### By Severity\n\n");
@@ -1897,13 +3813,81 @@ fn format_smart_review_output(
     }
     output.push('\n');
 
-    // Actionable Recommendations
-    if !summary.recommendations.is_empty() {
-        output.push_str("### 🎯 Priority Actions\n\n");
-        for (i, rec) in summary.recommendations.iter().enumerate() {
-            output.push_str(&format!("{}. {}\n", i + 1, rec));
+    output
+}
+
+#[allow(clippy::too_many_arguments)]
+fn format_smart_review_output(
+    comments: &[core::Comment],
+    summary: &core::comment::ReviewSummary,
+    pr_summary: Option<&core::pr_summary::PRSummary>,
+    walkthrough: &str,
+    impact: &str,
+    coverage: &str,
+    risk: &str,
+    sections: &[String],
+) -> String {
+    let mut output = String::new();
+
+    output.push_str("# 🤖 Smart Review Analysis Results\n\n");
+
+    // Executive Summary
+    output.push_str("## 📊 Executive Summary\n\n");
+    let score_emoji = if summary.overall_score >= 8.0 {
+        "🟢"
+    } else if summary.overall_score >= 6.0 {
+        "🟡"
+    } else {
+        "🔴"
+    };
+    output.push_str(&format!(
+        "{} **Code Quality Score:** {:.1}/10\n",
+        score_emoji, summary.overall_score
+    ));
+    output.push_str(&format!(
+        "📝 **Total Issues Found:** {}\n",
+        summary.total_comments
+    ));
+    output.push_str(&format!(
+        "🚨 **Critical Issues:** {}\n",
+        summary.critical_issues
+    ));
+    output.push_str(&format!(
+        "📁 **Files Analyzed:** {}\n\n",
+        summary.files_reviewed
+    ));
+
+    for section in sections {
+        match section.as_str() {
+            "summary" => {
+                if let Some(pr_summary) = pr_summary {
+                    output.push_str(&format_pr_summary_section(pr_summary));
+                    output.push('\n');
+                }
+            }
+            "breaking_changes" => {
+                output.push_str(&plugins::builtin::format_breaking_changes_section(comments))
+            }
+            "walkthrough" if !walkthrough.trim().is_empty() => {
+                output.push_str(walkthrough);
+                output.push('\n');
+            }
+            "impact" if !impact.trim().is_empty() => {
+                output.push_str(impact);
+                output.push('\n');
+            }
+            "coverage" => output.push_str(coverage),
+            "risk" => output.push_str(risk),
+            "breakdown" => output.push_str(&format_issue_breakdown(summary)),
+            "actions" if !summary.recommendations.is_empty() => {
+                output.push_str("### 🎯 Priority Actions\n\n");
+                for (i, rec) in summary.recommendations.iter().enumerate() {
+                    output.push_str(&format!("{}. {}\n", i + 1, rec));
+                }
+                output.push('\n');
+            }
+            _ => {}
         }
-        output.push('\n');
     }
 
     if comments.is_empty() {
@@ -2009,6 +3993,10 @@ fn format_detailed_comment(comment: &core::Comment) -> String {
         output.push_str("\n\n");
     }
 
+    if !comment.owners.is_empty() {
+        output.push_str(&format!("**Owners:** {}\n\n", comment.owners.join(" ")));
+    }
+
     output.push_str(&format!("{}\n\n", comment.content));
 
     if let Some(suggestion) = &comment.suggestion {
@@ -2025,27 +4013,79 @@ fn format_detailed_comment(comment: &core::Comment) -> String {
     output
 }
 
+/// Layout and filtering flags of the `changelog` command.
+struct ChangelogOptions {
+    format: ChangelogFormat,
+    ai_summary: bool,
+    template: Option<PathBuf>,
+    paths: Vec<PathBuf>,
+    split_by_scope: bool,
+}
+
 async fn changelog_command(
+    config: config::Config,
     from: Option<String>,
     to: Option<String>,
     release: Option<String>,
+    options: ChangelogOptions,
     output_path: Option<PathBuf>,
 ) -> Result<()> {
     info!("Generating changelog/release notes");
 
-    let generator = core::ChangelogGenerator::new(".")?;
+    let to_ref = to.as_deref().unwrap_or("HEAD");
+    let generator = core::ChangelogGenerator::new(".")?.with_paths(options.paths.clone());
 
-    let output = if let Some(version) = release {
-        // Generate release notes
-        info!("Generating release notes for version {}", version);
-        generator.generate_release_notes(&version, from.as_deref())?
-    } else {
-        // Generate changelog
-        let to_ref = to.as_deref().unwrap_or("HEAD");
-        info!("Generating changelog from {:?} to {}", from, to_ref);
-        generator.generate_changelog(from.as_deref(), to_ref)?
-    };
+    if options.split_by_scope {
+        let dir = output_path.context("--split-by-scope needs an --output directory")?;
+        let scopes = generator.scopes(from.as_deref(), to_ref)?;
+        if scopes.is_empty() {
+            anyhow::bail!("No commits in the range have a conventional-commit scope");
+        }
+        tokio::fs::create_dir_all(&dir).await?;
+        let extension = match (&options.template, options.format) {
+            (None, ChangelogFormat::Json) => "json",
+            (None, ChangelogFormat::Yaml) => "yaml",
+            _ => "md",
+        };
+        for scope in scopes {
+            let generator = core::ChangelogGenerator::new(".")?
+                .with_paths(options.paths.clone())
+                .with_scope(Some(scope.clone()));
+            let output = render_changelog(
+                &config,
+                &generator,
+                from.as_deref(),
+                to_ref,
+                release.as_deref(),
+                &options,
+            )
+            .await?;
+            let file_name: String = scope
+                .chars()
+                .map(|c| {
+                    if c.is_ascii_alphanumeric() || "._-".contains(c) {
+                        c
+                    } else {
+                        '-'
+                    }
+                })
+                .collect();
+            let path = dir.join(format!("{}.{}", file_name, extension));
+            tokio::fs::write(&path, output).await?;
+            println!("Wrote {}", path.display());
+        }
+        return Ok(());
+    }
 
+    let output = render_changelog(
+        &config,
+        &generator,
+        from.as_deref(),
+        to_ref,
+        release.as_deref(),
+        &options,
+    )
+    .await?;
     if let Some(path) = output_path {
         tokio::fs::write(path, output).await?;
         info!("Changelog written to file");
@@ -2056,217 +4096,1109 @@ async fn changelog_command(
     Ok(())
 }
 
-async fn feedback_command(
-    config: config::Config,
-    accept: Option<PathBuf>,
-    reject: Option<PathBuf>,
-    feedback_path: Option<PathBuf>,
-) -> Result<()> {
-    let (action, input_path) = match (accept, reject) {
-        (Some(path), None) => ("accept", path),
-        (None, Some(path)) => ("reject", path),
-        _ => {
-            anyhow::bail!("Specify exactly one of --accept or --reject");
+/// The changelog, release notes, or entry list for the commits `generator` selects.
+async fn render_changelog(
+    config: &config::Config,
+    generator: &core::ChangelogGenerator,
+    from: Option<&str>,
+    to_ref: &str,
+    release: Option<&str>,
+    options: &ChangelogOptions,
+) -> Result<String> {
+    let format = options.format;
+    let template = options.template.clone();
+    if options.ai_summary
+        && template.is_none()
+        && matches!(format, ChangelogFormat::Json | ChangelogFormat::Yaml)
+    {
+        anyhow::bail!("--ai-summary needs a markdown format or a template");
+    }
+    // A release's notes cover everything up to HEAD.
+    let summary_to = if release.is_some() && template.is_none() {
+        "HEAD"
+    } else {
+        to_ref
+    };
+    let summary = if options.ai_summary {
+        let entries = generator.collect_entries(from, summary_to)?;
+        summarize_release(config, &entries, release).await?
+    } else {
+        None
+    };
+
+    if let Some(template) = template {
+        let entries = generator.collect_entries(from, to_ref)?;
+        let mut context = core::changelog::ChangelogContext::new(&entries, from, to_ref, release);
+        context.summary = summary.as_ref();
+        return core::report_template::render(&template, &context);
+    }
+    let document = match format {
+        ChangelogFormat::Markdown => {
+            if let Some(version) = release {
+                // Generate release notes
+                info!("Generating release notes for version {}", version);
+                generator.generate_release_notes(version, from)?
+            } else {
+                // Generate changelog
+                info!("Generating changelog from {:?} to {}", from, to_ref);
+                generator.generate_changelog(from, to_ref)?
+            }
+        }
+        ChangelogFormat::KeepAChangelog => core::changelog::format_keep_a_changelog(
+            &generator.collect_entries(from, to_ref)?,
+            release,
+            &chrono::Local::now().format("%Y-%m-%d").to_string(),
+        ),
+        ChangelogFormat::Json => {
+            serde_json::to_string_pretty(&generator.collect_entries(from, to_ref)?)?
         }
+        ChangelogFormat::Yaml => serde_yaml::to_string(&generator.collect_entries(from, to_ref)?)?,
     };
+    Ok(match &summary {
+        // Release notes put sections at `##`; changelogs nest them under the release.
+        Some(summary) if release.is_some() && matches!(format, ChangelogFormat::Markdown) => {
+            core::changelog::insert_summary(&document, summary, 2)
+        }
+        Some(summary) => core::changelog::insert_summary(&document, summary, 3),
+        None => document,
+    })
+}
 
-    let feedback_path = feedback_path.unwrap_or_else(|| config.feedback_path.clone());
-    let content = tokio::fs::read_to_string(&input_path).await?;
-    let mut comments: Vec<core::Comment> = serde_json::from_str(&content)?;
+/// Model-written highlights and per-area summaries of `entries`, or `None` when there are no
+/// entries or the response has no summary.
+async fn summarize_release(
+    config: &config::Config,
+    entries: &[core::changelog::ChangelogEntry],
+    version: Option<&str>,
+) -> Result<Option<core::changelog::ReleaseSummary>> {
+    if entries.is_empty() {
+        return Ok(None);
+    }
+    let adapter = adapters::llm::create_adapter(&config.model_config())?;
+    let (system_prompt, user_prompt) = core::changelog::build_summary_prompt(entries, version);
+    let response = adapter
+        .complete(adapters::llm::LLMRequest {
+            system_prompt,
+            user_prompt,
+            temperature: Some(0.3),
+            max_tokens: Some(1500),
+            cached_context: None,
+        })
+        .await?;
+    let summary = core::changelog::parse_summary(&response.content);
+    if summary.is_none() {
+        warn!("The model returned no release summary; writing the notes without it");
+    }
+    Ok(summary)
+}
 
-    for comment in &mut comments {
-        if comment.id.trim().is_empty() {
-            comment.id = core::comment::compute_comment_id(
-                &comment.file_path,
-                &comment.content,
-                &comment.category,
-            );
-        }
+async fn diagram_command(
+    config: config::Config,
+    diff_path: Option<PathBuf>,
+    kind: Option<core::pr_summary::DiagramKind>,
+    output_path: Option<PathBuf>,
+) -> Result<()> {
+    let diffs = core::DiffParser::parse_unified_diff(&read_diff_input(diff_path).await?)?;
+    if diffs.is_empty() {
+        anyhow::bail!("No changes to diagram");
     }
 
-    let mut store = load_feedback_store_from_path(&feedback_path);
-    let mut updated = 0usize;
+    let adapter = adapters::llm::create_adapter(&config.model_config())?;
+    let diagram = core::PRSummaryGenerator::generate_change_diagram(&diffs, adapter.as_ref(), kind)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("The model did not produce a diagram for this change"))?;
 
-    if action == "accept" {
-        for comment in &comments {
-            if store.accept.insert(comment.id.clone()) {
-                updated += 1;
-            }
-            store.suppress.remove(&comment.id);
+    let Some(path) = output_path else {
+        println!("{}", diagram);
+        return Ok(());
+    };
+    let rendered = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| matches!(ext, "svg" | "png" | "pdf"));
+    if !rendered {
+        tokio::fs::write(&path, format!("{}\n", diagram)).await?;
+        println!("Wrote {}", path.display());
+        return Ok(());
+    }
+
+    let source = tempfile::Builder::new()
+        .prefix("diffscope-diagram-")
+        .suffix(".mmd")
+        .tempfile()?;
+    tokio::fs::write(source.path(), &diagram).await?;
+    let result = tokio::process::Command::new("mmdc")
+        .arg("-i")
+        .arg(source.path())
+        .arg("-o")
+        .arg(&path)
+        .output()
+        .await;
+    match result {
+        Ok(output) if output.status.success() => {
+            println!("Wrote {}", path.display());
+            Ok(())
         }
-    } else {
-        for comment in &comments {
-            if store.suppress.insert(comment.id.clone()) {
-                updated += 1;
-            }
-            store.accept.remove(&comment.id);
+        Ok(output) => anyhow::bail!(
+            "mmdc failed to render {}: {}",
+            path.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            let fallback = path.with_extension("mmd");
+            tokio::fs::write(&fallback, format!("{}\n", diagram)).await?;
+            warn!("mmdc not found (npm install -g @mermaid-js/mermaid-cli); wrote the Mermaid source instead");
+            println!("Wrote {}", fallback.display());
+            Ok(())
         }
+        Err(err) => Err(err.into()),
     }
+}
 
-    save_feedback_store(&feedback_path, &store)?;
-    println!(
-        "Updated feedback store at {} ({} {} comment(s))",
-        feedback_path.display(),
-        updated,
-        action
-    );
-
-    Ok(())
+/// The diff in `diff_path`, piped on stdin, or else the uncommitted changes.
+async fn read_diff_input(diff_path: Option<PathBuf>) -> Result<String> {
+    if let Some(path) = diff_path {
+        Ok(tokio::fs::read_to_string(path).await?)
+    } else if std::io::stdin().is_terminal() {
+        core::GitIntegration::new(".")?.get_uncommitted_diff()
+    } else {
+        use std::io::Read;
+        let mut buffer = String::new();
+        std::io::stdin().read_to_string(&mut buffer)?;
+        Ok(buffer)
+    }
 }
 
-fn extract_symbols_from_diff(diff: &core::UnifiedDiff) -> Vec<String> {
-    let mut symbols = Vec::new();
-    static SYMBOL_REGEX: Lazy<Regex> =
-        Lazy::new(|| Regex::new(r"\b([A-Z][a-zA-Z0-9_]*|[a-z][a-zA-Z0-9_]*)\s*\(").unwrap());
-    static CLASS_REGEX: Lazy<Regex> = Lazy::new(|| {
-        Regex::new(r"\b(class|struct|interface|enum)\s+([A-Z][a-zA-Z0-9_]*)").unwrap()
-    });
+/// Answers `question` about a diff with the same file, definition, and caller context a
+/// review would get.
+async fn ask_command(
+    config: config::Config,
+    question: &str,
+    diff_path: Option<PathBuf>,
+) -> Result<()> {
+    let diffs = core::DiffParser::parse_unified_diff(&read_diff_input(diff_path).await?)?;
+    if diffs.is_empty() {
+        anyhow::bail!("No changes to ask about");
+    }
 
-    for hunk in &diff.hunks {
-        for line in &hunk.changes {
-            if matches!(
-                line.change_type,
-                core::diff_parser::ChangeType::Added | core::diff_parser::ChangeType::Removed
-            ) {
-                // Extract function calls and references
-                for capture in SYMBOL_REGEX.captures_iter(&line.content) {
-                    if let Some(symbol) = capture.get(1) {
-                        let symbol_str = symbol.as_str().to_string();
-                        if symbol_str.len() > 2 && !symbols.contains(&symbol_str) {
-                            symbols.push(symbol_str);
-                        }
-                    }
-                }
+    let repo_root = core::GitIntegration::new(".")
+        .ok()
+        .and_then(|git| git.workdir())
+        .unwrap_or_else(|| PathBuf::from("."));
+    let context_fetcher = core::ContextFetcher::new(repo_root.clone())
+        .with_function_scope(config.function_scope_context);
+    let symbol_index = build_symbol_index(&config, &repo_root);
 
-                // Also look for class/struct references
-                for capture in CLASS_REGEX.captures_iter(&line.content) {
-                    if let Some(class_name) = capture.get(2) {
-                        let class_str = class_name.as_str().to_string();
-                        if !symbols.contains(&class_str) {
-                            symbols.push(class_str);
-                        }
-                    }
-                }
-            }
-        }
-    }
+    let files =
+        fetch_diffs_context(&config, &context_fetcher, symbol_index.as_ref(), diffs).await?;
 
-    symbols
+    let (system_prompt, user_prompt) =
+        core::ask::build_prompt(question, &files, &config.prompt_limits())?;
+    let adapter = adapters::llm::create_adapter(&config.model_config())?;
+    let response = adapter
+        .complete(adapters::llm::LLMRequest {
+            system_prompt,
+            user_prompt,
+            temperature: Some(0.2),
+            max_tokens: None,
+            cached_context: None,
+        })
+        .await?;
+    println!("{}", response.content.trim());
+    Ok(())
 }
 
-fn filter_comments_for_diff(
-    diff: &core::UnifiedDiff,
-    comments: Vec<core::Comment>,
-) -> Vec<core::Comment> {
-    let mut filtered = Vec::new();
-    let total = comments.len();
-    for comment in comments {
-        if is_line_in_diff(diff, comment.line_number) {
-            filtered.push(comment);
+/// Pairs each reviewable diff with its file, definition, symbol index, and caller context.
+async fn fetch_diffs_context(
+    config: &config::Config,
+    context_fetcher: &core::ContextFetcher,
+    symbol_index: Option<&core::SymbolIndex>,
+    diffs: Vec<core::UnifiedDiff>,
+) -> Result<Vec<(core::UnifiedDiff, Vec<core::LLMContextChunk>)>> {
+    let excluded = config.exclusion_filter(context_fetcher.repo_path());
+    let mut files = Vec::new();
+    for diff in diffs {
+        if excluded(&diff.file_path) || diff.is_binary || diff.hunks.is_empty() {
+            continue;
         }
+        let mut context_chunks = if diff.is_deleted {
+            Vec::new()
+        } else {
+            context_fetcher
+                .fetch_context_for_file(&diff.file_path, &context_line_ranges(&diff))
+                .await?
+        };
+        let symbols = extract_symbols_from_diff(&diff);
+        context_chunks.extend(
+            fetch_definitions(
+                config,
+                context_fetcher,
+                symbol_index,
+                &diff.file_path,
+                &symbols,
+            )
+            .await?,
+        );
+        context_chunks.extend(fetch_caller_context(config, context_fetcher, &diff).await?);
+        files.push((diff, context_chunks));
     }
+    Ok(files)
+}
 
-    if filtered.len() != total {
-        let dropped = total.saturating_sub(filtered.len());
-        info!(
-            "Dropped {} comment(s) for {} due to unmatched line numbers",
-            dropped,
-            diff.file_path.display()
+/// Definitions of `symbols`, from `file_path` itself and from the symbol index if enabled.
+async fn fetch_definitions(
+    config: &config::Config,
+    context_fetcher: &core::ContextFetcher,
+    symbol_index: Option<&core::SymbolIndex>,
+    file_path: &PathBuf,
+    symbols: &[String],
+) -> Result<Vec<core::LLMContextChunk>> {
+    if symbols.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut chunks = context_fetcher
+        .fetch_related_definitions(file_path, symbols)
+        .await?;
+    if let Some(index) = symbol_index {
+        chunks.extend(
+            context_fetcher
+                .fetch_related_definitions_with_index(
+                    file_path,
+                    symbols,
+                    index,
+                    config.symbol_index_max_locations,
+                )
+                .await?,
         );
     }
+    Ok(chunks)
+}
 
-    filtered
+/// Runs every environment check, printing each problem with its fix; fails if any check did.
+async fn doctor_command(config: config::Config, offline: bool) -> Result<()> {
+    use core::doctor;
+
+    let repo_root = core::GitIntegration::new(".")
+        .ok()
+        .and_then(|git| git.workdir())
+        .unwrap_or_else(|| PathBuf::from("."));
+    let mut checks = doctor::check_config_files(&config::Config::source_files());
+    checks.extend([
+        doctor::check_plugins(&config).await,
+        doctor::check_model(&config, offline).await,
+        doctor::check_git(Path::new(".")),
+    ]);
+    checks.extend(doctor::check_tools(&config, &repo_root));
+
+    print!("{}", doctor::render(&checks));
+    let failures = checks
+        .iter()
+        .filter(|check| check.status == doctor::Status::Fail)
+        .count();
+    if failures > 0 {
+        anyhow::bail!("diffscope doctor found {} problem(s)", failures);
+    }
+    Ok(())
 }
 
-fn build_review_guidance(
-    config: &config::Config,
-    path_config: Option<&config::PathConfig>,
-) -> Option<String> {
-    let mut sections = Vec::new();
-
-    if let Some(profile) = config.review_profile.as_deref() {
-        let guidance = match profile {
-            "chill" => Some(
-                "Be conservative and only surface high-confidence, high-impact issues. Avoid nitpicks and redundant comments.",
-            ),
-            "assertive" => Some(
-                "Be thorough and proactive. Surface edge cases, latent risks, and maintainability concerns even if they are subtle.",
-            ),
-            _ => None,
+fn hotspots_command(
+    config: config::Config,
+    days: Option<u32>,
+    limit: usize,
+    json: bool,
+) -> Result<()> {
+    let git = core::GitIntegration::new(".")?;
+    let repo_root = git.workdir().unwrap_or_else(|| PathBuf::from("."));
+    let days = days.unwrap_or(config.hotspots.days);
+    let mut hotspots = core::hotspots::analyze(&git, &repo_root, None, days)?;
+    hotspots.retain(|hotspot| !config.should_exclude(&hotspot.path));
+    hotspots.truncate(limit);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&hotspots)?);
+        return Ok(());
+    }
+    if hotspots.is_empty() {
+        println!("No commits in the last {} days", days);
+        return Ok(());
+    }
+    if git.is_shallow() {
+        println!("note: this is a shallow clone, so older history is missing\n");
+    }
+    println!("Hotspots in the last {} days:\n", days);
+    println!("{:>5} {:>7}  File", "Fixes", "Commits");
+    for hotspot in &hotspots {
+        let marker = if hotspot.fixes >= config.hotspots.min_fixes {
+            "  (hotspot)"
+        } else {
+            ""
         };
-        if let Some(text) = guidance {
-            sections.push(format!("Review profile ({}): {}", profile, text));
-        }
+        println!(
+            "{:>5} {:>7}  {}{}",
+            hotspot.fixes,
+            hotspot.commits,
+            hotspot.path.display(),
+            marker
+        );
     }
+    Ok(())
+}
 
-    if let Some(instructions) = config.review_instructions.as_deref() {
-        let trimmed = instructions.trim();
-        if !trimmed.is_empty() {
-            sections.push(format!("Global review instructions:\n{}", trimmed));
-        }
-    }
+/// Writes `.diffscope.yml`, and a GitHub Actions workflow when asked for, from what the
+/// repository already uses plus a few questions (skipped with `--yes` or without a terminal).
+fn init_command(yes: bool, force: bool) -> Result<()> {
+    use core::init::CiSystem;
 
-    if let Some(pc) = path_config {
-        if let Some(instructions) = pc.review_instructions.as_deref() {
-            let trimmed = instructions.trim();
-            if !trimmed.is_empty() {
-                sections.push(format!("Path-specific instructions:\n{}", trimmed));
-            }
-        }
+    let repo_root = core::GitIntegration::new(".")
+        .ok()
+        .and_then(|git| git.workdir())
+        .unwrap_or_else(|| PathBuf::from("."));
+    let profile = core::init::inspect(&repo_root);
+    let config_path = repo_root.join(".diffscope.yml");
+    let workflow_path = repo_root.join(".github/workflows/diffscope.yml");
+    if config_path.exists() && !force {
+        anyhow::bail!(
+            "{} already exists; rerun with --force to replace it",
+            config_path.display()
+        );
     }
 
-    if sections.is_empty() {
-        None
+    if profile.languages.is_empty() {
+        println!("Languages: none detected");
     } else {
-        Some(format!(
-            "Additional review guidance:\n{}",
-            sections.join("\n\n")
-        ))
+        let languages: Vec<&str> = profile.languages.iter().map(|(name, _)| *name).collect();
+        println!("Languages: {}", languages.join(", "));
+    }
+    println!("CI: {}", profile.ci.map_or("none detected", |ci| ci.name()));
+    if !profile.linters.is_empty() {
+        println!("Linter plugins: {}", profile.linters.join(", "));
     }
-}
 
-fn build_change_walkthrough(diffs: &[core::UnifiedDiff]) -> String {
-    let mut entries = Vec::new();
-    let mut truncated = false;
-    let max_entries = 50usize;
+    let mut answers = core::init::InitAnswers::defaults(&profile);
+    if !yes && std::io::stdin().is_terminal() {
+        println!();
+        answers.model = prompt_answer("Model", &answers.model)?;
+        answers.review_profile = prompt_answer(
+            &format!(
+                "Review profile ({})",
+                core::review_presets::profile_names().join(", ")
+            ),
+            "balanced",
+        )?;
+        let fail_on = prompt_answer(
+            "Fail CI on findings at or above (error, warning, none)",
+            "none",
+        )?;
+        answers.fail_on = (fail_on != "none").then_some(fail_on);
+        if matches!(profile.ci, None | Some(CiSystem::GitHubActions)) {
+            let default = if answers.github_workflow { "y" } else { "n" };
+            answers.github_workflow = prompt_answer(
+                "Add a GitHub Actions workflow that reviews pull requests? (y/n)",
+                default,
+            )?
+            .starts_with(['y', 'Y']);
+        }
+    }
 
-    for diff in diffs {
-        if diff.is_binary {
-            continue;
+    // Catch a typo now instead of on the first review.
+    let rendered = core::init::render_config(&profile, &answers);
+    serde_yaml::from_str::<config::Config>(&rendered).map_err(|err| {
+        anyhow::anyhow!(
+            "Generated configuration does not parse ({}); check the answers",
+            err
+        )
+    })?;
+    std::fs::write(&config_path, rendered)?;
+    println!("\nWrote {}", config_path.display());
+
+    if answers.github_workflow {
+        if workflow_path.exists() && !force {
+            println!(
+                "Kept the existing {}; rerun with --force to replace it",
+                workflow_path.display()
+            );
+        } else {
+            std::fs::create_dir_all(workflow_path.parent().unwrap())?;
+            std::fs::write(&workflow_path, core::init::render_workflow(&answers))?;
+            println!("Wrote {}", workflow_path.display());
+            if let Some(key) = core::init::api_key_env(&answers.model) {
+                println!("Add the {} repository secret for the workflow to use.", key);
+            }
         }
+    }
+    Ok(())
+}
 
-        let mut added = 0usize;
-        let mut removed = 0usize;
-        for hunk in &diff.hunks {
-            for change in &hunk.changes {
-                match change.change_type {
-                    core::diff_parser::ChangeType::Added => added += 1,
-                    core::diff_parser::ChangeType::Removed => removed += 1,
-                    _ => {}
+/// Reads one answer from stdin, returning `default` for an empty line.
+fn prompt_answer(question: &str, default: &str) -> Result<String> {
+    use std::io::Write;
+
+    print!("{} [{}]: ", question, default);
+    std::io::stdout().flush()?;
+    let mut line = String::new();
+    std::io::stdin().read_line(&mut line)?;
+    let answer = line.trim();
+    Ok(if answer.is_empty() { default } else { answer }.to_string())
+}
+
+fn hooks_command(command: HookCommands) -> Result<()> {
+    use core::hooks::HookKind;
+
+    let hooks_dir = core::GitIntegration::new(".")?.hooks_dir();
+    let selected = |pre_commit: bool, pre_push: bool, default: &[HookKind]| {
+        let mut kinds = Vec::new();
+        if pre_commit {
+            kinds.push(HookKind::PreCommit);
+        }
+        if pre_push {
+            kinds.push(HookKind::PrePush);
+        }
+        if kinds.is_empty() {
+            kinds.extend_from_slice(default);
+        }
+        kinds
+    };
+    match command {
+        HookCommands::Install {
+            pre_commit,
+            pre_push,
+            local_model,
+            force,
+        } => {
+            for kind in selected(pre_commit, pre_push, &[HookKind::PreCommit]) {
+                let path = core::hooks::install(&hooks_dir, kind, local_model.as_deref(), force)?;
+                println!("Installed {}", path.display());
+            }
+        }
+        HookCommands::Uninstall {
+            pre_commit,
+            pre_push,
+        } => {
+            for kind in selected(
+                pre_commit,
+                pre_push,
+                &[
+                    HookKind::PreCommit,
+                    HookKind::PrePush,
+                    HookKind::PrepareCommitMsg,
+                ],
+            ) {
+                if core::hooks::uninstall(&hooks_dir, kind)? {
+                    println!("Removed the diffscope {} hook", kind.file_name());
                 }
             }
         }
+    }
+    Ok(())
+}
 
-        let status = if diff.is_deleted {
-            "deleted"
-        } else if diff.is_new {
-            "new"
+/// Generates a test file per changed source file, covering the functions whose bodies the
+/// diff touched, modeled on the nearest existing test in the repository.
+async fn generate_tests_command(
+    config: config::Config,
+    diff_path: Option<PathBuf>,
+    write: bool,
+) -> Result<()> {
+    let diffs = core::DiffParser::parse_unified_diff(&read_diff_input(diff_path).await?)?;
+    let repo_root = core::GitIntegration::new(".")
+        .ok()
+        .and_then(|git| git.workdir())
+        .unwrap_or_else(|| PathBuf::from("."));
+    let context_fetcher = core::ContextFetcher::new(repo_root.clone());
+    let symbol_index = build_symbol_index(&config, &repo_root);
+    let adapter = adapters::llm::create_adapter(&config.model_config())?;
+
+    let excluded = config.exclusion_filter(&repo_root);
+    let mut generated = 0usize;
+    for diff in &diffs {
+        if excluded(&diff.file_path)
+            || diff.is_deleted
+            || diff.is_binary
+            || core::test_gen::is_test_path(&diff.file_path)
+        {
+            continue;
+        }
+        let Ok(source) = tokio::fs::read_to_string(repo_root.join(&diff.file_path)).await else {
+            continue;
+        };
+        let lines: Vec<&str> = source.lines().collect();
+        let functions: Vec<(String, String)> =
+            core::function_scope::changed_functions(diff, &lines)
+                .into_iter()
+                .map(|(name, (start, end))| (name, lines[start - 1..end].join("\n")))
+                .collect();
+        if functions.is_empty() {
+            continue;
+        }
+        let example = core::test_gen::find_example_test(&repo_root, &diff.file_path);
+        let Some(convention) =
+            core::test_gen::convention_for(&repo_root, &diff.file_path, example.as_ref())
+        else {
+            info!("No test convention for {}", diff.file_path.display());
+            continue;
+        };
+
+        let symbols = extract_symbols(functions.iter().flat_map(|(_, body)| body.lines()));
+        let context = fetch_definitions(
+            &config,
+            &context_fetcher,
+            symbol_index.as_ref(),
+            &diff.file_path,
+            &symbols,
+        )
+        .await?;
+        let (system_prompt, user_prompt) = core::test_gen::build_prompt(
+            &diff.file_path,
+            &functions,
+            &convention,
+            example.as_ref(),
+            &context,
+            &config.prompt_limits(),
+        )?;
+        let response = adapter
+            .complete(adapters::llm::LLMRequest {
+                system_prompt,
+                user_prompt,
+                temperature: Some(0.2),
+                max_tokens: None,
+                cached_context: None,
+            })
+            .await?;
+        let code = core::test_gen::extract_code(&response.content);
+        let names: Vec<&str> = functions.iter().map(|(name, _)| name.as_str()).collect();
+        generated += 1;
+
+        if !write {
+            println!(
+                "// {} ({}: {})\n{}",
+                convention.test_path.display(),
+                convention.framework,
+                names.join(", "),
+                code
+            );
+            continue;
+        }
+        let path = repo_root.join(&convention.test_path);
+        if path.exists() {
+            warn!(
+                "Not overwriting existing {}; run without --write to see the tests",
+                convention.test_path.display()
+            );
+            continue;
+        }
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(&path, code).await?;
+        println!(
+            "Wrote {} (tests for {})",
+            convention.test_path.display(),
+            names.join(", ")
+        );
+    }
+
+    if generated == 0 {
+        println!("No changed functions to generate tests for");
+    }
+    Ok(())
+}
+
+/// Explains a file, the function around `path:line`, or a commit, from its source (or diff)
+/// and related definitions.
+async fn explain_command(config: config::Config, target: &str) -> Result<()> {
+    let git = core::GitIntegration::new(".").ok();
+    let repo_root = git
+        .as_ref()
+        .and_then(|git| git.workdir())
+        .unwrap_or_else(|| PathBuf::from("."));
+    let context_fetcher = core::ContextFetcher::new(repo_root.clone())
+        .with_function_scope(config.function_scope_context);
+    let symbol_index = build_symbol_index(&config, &repo_root);
+    let limits = config.prompt_limits();
+
+    let (subject, material) = match core::explain::parse_target(&repo_root, target) {
+        core::explain::ExplainTarget::File { path, line } => {
+            let source = tokio::fs::read_to_string(repo_root.join(&path)).await?;
+            let lines: Vec<&str> = source.lines().collect();
+            let (range, subject) = match line {
+                Some(line) => (
+                    core::explain::range_around(&path, &lines, line),
+                    format!("the code around line {} of `{}`", line, path.display()),
+                ),
+                None => ((1, usize::MAX), format!("the file `{}`", path.display())),
+            };
+            let mut chunks = context_fetcher
+                .fetch_context_for_file(&path, &[range])
+                .await?;
+            let target_lines = lines
+                .iter()
+                .take(range.1)
+                .skip(range.0.saturating_sub(1))
+                .copied();
+            let symbols = extract_symbols(target_lines);
+            chunks.extend(
+                fetch_definitions(
+                    &config,
+                    &context_fetcher,
+                    symbol_index.as_ref(),
+                    &path,
+                    &symbols,
+                )
+                .await?,
+            );
+            let builder = core::PromptBuilder::new(core::prompt::PromptConfig {
+                limits,
+                ..Default::default()
+            });
+            (subject, builder.format_context(&chunks)?)
+        }
+        core::explain::ExplainTarget::Commit(rev) => {
+            let git = git.ok_or_else(|| {
+                anyhow::anyhow!(
+                    "{} is neither a file nor a commit (not in a git repository)",
+                    rev
+                )
+            })?;
+            let (message, diff) = git.get_commit(&rev).map_err(|err| {
+                anyhow::anyhow!("{} is neither a file nor a commit: {}", rev, err)
+            })?;
+            let diffs = core::DiffParser::parse_unified_diff(&diff)?;
+            let files =
+                fetch_diffs_context(&config, &context_fetcher, symbol_index.as_ref(), diffs)
+                    .await?;
+            (
+                format!("commit {}", rev),
+                format!(
+                    "Commit message:\n{}\n\n{}",
+                    message.trim(),
+                    core::ask::format_files(&files, &limits)?
+                ),
+            )
+        }
+    };
+
+    let adapter = adapters::llm::create_adapter(&config.model_config())?;
+    let explanation = core::interactive::explain(adapter.as_ref(), &subject, &material).await?;
+    println!("{}", explanation.trim());
+    Ok(())
+}
+
+/// Patches the working tree with every code suggestion in `review`, file by file. Suggestions
+/// whose code has changed since the review or that overlap another are reported, not applied.
+async fn apply_command(review: &Path, dry_run: bool) -> Result<()> {
+    let content = tokio::fs::read_to_string(review).await?;
+    let comments: Vec<core::Comment> = serde_json::from_str(&content)?;
+    let repo_root = core::GitIntegration::new(".")
+        .ok()
+        .and_then(|git| git.workdir())
+        .unwrap_or_else(|| PathBuf::from("."));
+
+    let mut by_file: std::collections::BTreeMap<
+        &Path,
+        Vec<(usize, &core::comment::CodeSuggestion)>,
+    > = std::collections::BTreeMap::new();
+    for comment in &comments {
+        if let Some(code) = &comment.code_suggestion {
+            by_file
+                .entry(comment.file_path.as_path())
+                .or_default()
+                .push((comment.line_number, code));
+        }
+    }
+    if by_file.is_empty() {
+        println!("No code suggestions in {}", review.display());
+        return Ok(());
+    }
+
+    let (mut applied, mut conflicts) = (0usize, Vec::new());
+    for (file_path, suggestions) in &by_file {
+        let conflict = |reason| {
+            suggestions
+                .iter()
+                .map(move |(line, _)| core::suggestion::Conflict {
+                    file_path: file_path.to_path_buf(),
+                    line: *line,
+                    reason,
+                })
+        };
+        let Some(path) = path_in_repo(&repo_root, file_path) else {
+            conflicts.extend(conflict("its path is outside the repository"));
+            continue;
+        };
+        let source = match tokio::fs::read_to_string(&path).await {
+            Ok(source) => source,
+            Err(_) => {
+                conflicts.extend(conflict("the file could not be read"));
+                continue;
+            }
+        };
+        let (patched, file_conflicts) =
+            core::suggestion::apply_all(file_path, &source, suggestions);
+        applied += suggestions.len() - file_conflicts.len();
+        conflicts.extend(file_conflicts);
+        if patched == source {
+            continue;
+        }
+        if dry_run {
+            let name = file_path.display().to_string();
+            print!(
+                "{}",
+                similar::TextDiff::from_lines(&source, &patched)
+                    .unified_diff()
+                    .header(&format!("a/{}", name), &format!("b/{}", name))
+            );
         } else {
-            "modified"
+            tokio::fs::write(&path, patched).await?;
+        }
+    }
+
+    for conflict in &conflicts {
+        eprintln!(
+            "Skipped {}:{}: {}",
+            conflict.file_path.display(),
+            conflict.line,
+            conflict.reason
+        );
+    }
+    eprintln!(
+        "{} {} suggestion(s), {} conflict(s)",
+        if dry_run { "Would apply" } else { "Applied" },
+        applied,
+        conflicts.len()
+    );
+    if !conflicts.is_empty() {
+        anyhow::bail!("{} suggestion(s) could not be applied", conflicts.len());
+    }
+    Ok(())
+}
+
+/// `file_path` under `repo_root`, or `None` when it is absolute, climbs out with `..`, or
+/// resolves (through symlinks) to somewhere outside the repository. Review files are input
+/// like any other, so a path in one must not choose where `apply` writes.
+fn path_in_repo(repo_root: &Path, file_path: &Path) -> Option<PathBuf> {
+    use std::path::Component;
+    if !file_path
+        .components()
+        .all(|part| matches!(part, Component::Normal(_) | Component::CurDir))
+    {
+        return None;
+    }
+    let path = repo_root.join(file_path);
+    let root = repo_root.canonicalize().ok()?;
+    match path.canonicalize() {
+        Ok(resolved) if !resolved.starts_with(&root) => None,
+        _ => Some(path),
+    }
+}
+
+async fn feedback_command(
+    config: config::Config,
+    accept: Option<PathBuf>,
+    reject: Option<PathBuf>,
+    scope: Option<core::feedback::FeedbackScope>,
+    feedback_path: Option<PathBuf>,
+) -> Result<()> {
+    let (action, input_path) = match (accept, reject) {
+        (Some(path), None) => ("accept", path),
+        (None, Some(path)) => ("reject", path),
+        _ => {
+            anyhow::bail!("Specify exactly one of --accept or --reject");
+        }
+    };
+
+    let content = tokio::fs::read_to_string(&input_path).await?;
+    let mut comments: Vec<core::Comment> = serde_json::from_str(&content)?;
+
+    for comment in &mut comments {
+        if comment.id.trim().is_empty() {
+            comment.id = core::comment::compute_comment_id(
+                &comment.file_path,
+                &comment.content,
+                &comment.category,
+            );
+        }
+    }
+
+    let decision = if action == "accept" {
+        core::feedback::Decision::Accept
+    } else {
+        core::feedback::Decision::Reject
+    };
+    let scope = scope.unwrap_or_default();
+    let repo = core::feedback::current_repo(Path::new("."));
+    if scope == core::feedback::FeedbackScope::Repo && repo.is_none() {
+        warn!("No origin remote found; repo-scoped rejections will apply in every repository");
+    }
+    let mut log = core::feedback::FeedbackLog::open(&config, feedback_path)?;
+    let events: Vec<_> = comments
+        .iter()
+        .map(|comment| {
+            core::feedback::FeedbackEvent::for_comment(comment, decision, scope, repo.clone())
+        })
+        .collect();
+    let mut updated = 0usize;
+    for event in &events {
+        if log.record(event)? {
+            updated += 1;
+        }
+    }
+
+    log.save()?;
+    println!(
+        "Updated feedback store at {} ({} {} comment(s))",
+        log.location().display(),
+        updated,
+        action
+    );
+    if decision == core::feedback::Decision::Reject {
+        let reach = match scope {
+            core::feedback::FeedbackScope::File => "in the same file".to_string(),
+            core::feedback::FeedbackScope::Directory => {
+                "in the same directory and below".to_string()
+            }
+            core::feedback::FeedbackScope::Repo => match &repo {
+                Some(repo) => format!("anywhere in {}", repo),
+                None => "anywhere in this repository".to_string(),
+            },
+            core::feedback::FeedbackScope::Global => {
+                "in every repository sharing this store".to_string()
+            }
+        };
+        println!(
+            "Scope: {} (rewordings are suppressed {}; use --scope to change)",
+            scope.as_str(),
+            reach
+        );
+    }
+    share_feedback(&config, &events).await
+}
+
+/// Pushes recorded decisions to the team's `feedback_remote`, if one is configured.
+async fn share_feedback(
+    config: &config::Config,
+    events: &[core::feedback::FeedbackEvent],
+) -> Result<()> {
+    if core::feedback_remote::push_shared(config, events).await? {
+        println!(
+            "Shared {} decision(s) with the team feedback store",
+            events.len()
+        );
+    }
+    Ok(())
+}
+
+fn feedback_export_command(
+    config: config::Config,
+    feedback_path: Option<PathBuf>,
+    output: Option<PathBuf>,
+) -> Result<()> {
+    let store = core::feedback::FeedbackLog::open(&config, feedback_path)?.snapshot()?;
+    let json = serde_json::to_string_pretty(&store)?;
+    match output {
+        Some(path) => {
+            std::fs::write(&path, json)?;
+            eprintln!(
+                "Exported {} rejected and {} accepted finding(s) to {}",
+                store.suppress.len(),
+                store.accept.len(),
+                path.display()
+            );
+        }
+        None => println!("{}", json),
+    }
+    Ok(())
+}
+
+/// Records every decision of an exported feedback store, e.g. a teammate's.
+async fn feedback_import_command(
+    config: config::Config,
+    feedback_path: Option<PathBuf>,
+    input: PathBuf,
+) -> Result<()> {
+    let content = tokio::fs::read_to_string(&input).await?;
+    let imported: core::feedback::FeedbackStore = serde_json::from_str(&content)
+        .map_err(|err| anyhow::anyhow!("{} is not a feedback store: {}", input.display(), err))?;
+    let events = imported.events();
+
+    let mut log = core::feedback::FeedbackLog::open(&config, feedback_path)?;
+    let mut updated = 0usize;
+    for event in &events {
+        if log.record(event)? {
+            updated += 1;
+        }
+    }
+    log.save()?;
+    println!(
+        "Imported {} decision(s) from {} into {} ({} changed)",
+        events.len(),
+        input.display(),
+        log.location().display(),
+        updated
+    );
+    share_feedback(&config, &events).await
+}
+
+/// Reads reactions and resolved state of DiffScope comments on `repo`'s pull requests (via
+/// `gh`) and records them as accepted or rejected findings.
+async fn feedback_sync_command(
+    config: config::Config,
+    repo: &str,
+    feedback_path: Option<PathBuf>,
+) -> Result<()> {
+    use core::feedback::{Decision, FeedbackEvent, FeedbackScope};
+    use providers::github::{FeedbackVerdict, PostedComment};
+
+    let posted: Vec<PostedComment> = gh_api_list(Some(repo), "pulls/comments")?
+        .into_iter()
+        .filter(|comment: &PostedComment| {
+            providers::github::marker_finding_id(&comment.body).is_some()
+        })
+        .collect();
+
+    let pulls: std::collections::BTreeSet<u64> = posted
+        .iter()
+        .filter_map(PostedComment::pull_number)
+        .collect();
+    let mut resolved = HashSet::new();
+    for number in pulls {
+        resolved.extend(gh_resolved_comment_ids(repo, number)?);
+    }
+
+    let mut log = core::feedback::FeedbackLog::open(&config, feedback_path)?;
+    let mut events = Vec::new();
+    let (mut accepted, mut rejected) = (0usize, 0usize);
+    for comment in &posted {
+        let Some(id) = providers::github::marker_finding_id(&comment.body) else {
+            continue;
+        };
+        let decision =
+            match providers::github::feedback_verdict(comment, resolved.contains(&comment.id)) {
+                Some(FeedbackVerdict::Accept) => Decision::Accept,
+                Some(FeedbackVerdict::Reject) => Decision::Reject,
+                None => continue,
+            };
+        let (severity, content) = providers::github::finding_text(&comment.body);
+        let event = FeedbackEvent {
+            comment_id: id.to_string(),
+            decision,
+            scope: FeedbackScope::File,
+            repo: Some(repo.to_string()),
+            file_path: Some(comment.path.display().to_string()),
+            content: Some(content.to_string()),
+            category: providers::github::marker_field(&comment.body, "category")
+                .map(str::to_string),
+            severity: severity.map(str::to_string),
+            confidence: providers::github::marker_field(&comment.body, "confidence")
+                .and_then(|confidence| confidence.parse().ok()),
         };
+        if log.record(&event)? {
+            match decision {
+                Decision::Accept => accepted += 1,
+                Decision::Reject => rejected += 1,
+            }
+        }
+        events.push(event);
+    }
+
+    log.save()?;
+    println!(
+        "Synced {} DiffScope comment(s) from {}: {} newly accepted, {} newly rejected ({})",
+        posted.len(),
+        repo,
+        accepted,
+        rejected,
+        log.location().display()
+    );
+    share_feedback(&config, &events).await
+}
+
+fn feedback_stats_command(
+    config: config::Config,
+    feedback_path: Option<PathBuf>,
+    by: core::feedback::StatsGroup,
+    period: core::feedback::StatsPeriod,
+) -> Result<()> {
+    let log = core::feedback::FeedbackLog::open(&config, feedback_path)?;
+    let rows = log.stats(by, period)?;
+    if rows.is_empty() {
+        println!("No feedback recorded in {}", log.location().display());
+        return Ok(());
+    }
+    let label = format!("{:?}", by);
+    println!(
+        "{:<10} {:<16} {:>8} {:>8} {:>11}",
+        "Period", label, "Accepted", "Rejected", "Acceptance"
+    );
+    for row in rows {
+        println!(
+            "{:<10} {:<16} {:>8} {:>8} {:>10.0}%",
+            row.period,
+            row.group,
+            row.accepted,
+            row.total - row.accepted,
+            row.accepted as f64 * 100.0 / row.total as f64
+        );
+    }
+    Ok(())
+}
+
+/// Ids of review comments that start a resolved thread on pull request `number`.
+fn gh_resolved_comment_ids(repo: &str, number: u64) -> Result<HashSet<u64>> {
+    let (owner, name) = repo
+        .split_once('/')
+        .ok_or_else(|| anyhow::anyhow!("--repo must be OWNER/NAME"))?;
+    let output = std::process::Command::new("gh")
+        .args([
+            "api",
+            "graphql",
+            "-f",
+            &format!("query={}", providers::github::REVIEW_THREADS_QUERY),
+            "-f",
+            &format!("owner={}", owner),
+            "-f",
+            &format!("name={}", name),
+            "-F",
+            &format!("number={}", number),
+        ])
+        .output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Failed to list review threads: {}", stderr.trim());
+    }
+    let response: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    Ok(providers::github::resolved_comment_ids(&response))
+}
+
+fn build_impact_section(diffs: &[core::UnifiedDiff], graph: &core::DependencyGraph) -> String {
+    let max_listed = 10usize;
+    let mut entries = Vec::new();
+
+    for diff in diffs {
+        let impacted = graph.impacted_by(&diff.file_path, core::dependency_graph::IMPACT_MAX_DEPTH);
+        if impacted.is_empty() {
+            continue;
+        }
+
+        let mut listed: Vec<String> = impacted
+            .iter()
+            .take(max_listed)
+            .map(|module| {
+                if module.depth == 1 {
+                    format!("`{}`", module.file_path.display())
+                } else {
+                    format!("`{}` (transitive)", module.file_path.display())
+                }
+            })
+            .collect();
+        if impacted.len() > max_listed {
+            listed.push(format!("+{} more", impacted.len() - max_listed));
+        }
 
         entries.push(format!(
-            "- `{}` ({}; +{}, -{})",
+            "- `{}` → {}",
             diff.file_path.display(),
-            status,
-            added,
-            removed
+            listed.join(", ")
         ));
-
-        if entries.len() >= max_entries {
-            truncated = true;
-            break;
-        }
     }
 
     if entries.is_empty() {
@@ -2274,100 +5206,81 @@ fn build_change_walkthrough(diffs: &[core::UnifiedDiff]) -> String {
     }
 
     let mut output = String::new();
-    output.push_str("## 🧭 Change Walkthrough\n\n");
+    output.push_str("## 🔗 Impact\n\n");
+    output.push_str("Modules that import the changed files and may need a second look:\n\n");
     output.push_str(&entries.join("\n"));
     output.push('\n');
-    if truncated {
-        output.push_str("\n...truncated (too many files)\n");
-    }
-
     output
 }
 
-fn build_symbol_index(config: &config::Config, repo_root: &Path) -> Option<core::SymbolIndex> {
-    if !config.symbol_index {
-        return None;
+/// A table of the changed files with their status, line counts, and (when `summaries` has
+/// one) a one-line description of the change.
+fn build_change_walkthrough(
+    diffs: &[core::UnifiedDiff],
+    summaries: &HashMap<PathBuf, String>,
+) -> String {
+    let max_entries = core::pr_summary::MAX_WALKTHROUGH_FILES;
+    let files: Vec<&core::UnifiedDiff> = diffs.iter().filter(|diff| !diff.is_binary).collect();
+    if files.is_empty() {
+        return String::new();
     }
 
-    let provider = config.symbol_index_provider.as_str();
-    let result = if provider == "lsp" {
-        let detected_command = if config.symbol_index_lsp_command.is_none() {
-            core::SymbolIndex::detect_lsp_command(
-                repo_root,
-                config.symbol_index_max_files,
-                &config.symbol_index_lsp_languages,
-                |path| config.should_exclude(path),
-            )
-        } else {
-            None
-        };
-
-        let command = config
-            .symbol_index_lsp_command
-            .as_deref()
-            .map(str::to_string)
-            .or(detected_command);
-
-        if let Some(command) = command {
-            if config.symbol_index_lsp_command.is_none() {
-                info!("Auto-detected LSP command: {}", command);
-            }
-
-            match core::SymbolIndex::build_with_lsp(
-                repo_root,
-                config.symbol_index_max_files,
-                config.symbol_index_max_bytes,
-                config.symbol_index_max_locations,
-                &command,
-                &config.symbol_index_lsp_languages,
-                |path| config.should_exclude(path),
-            ) {
-                Ok(index) => Ok(index),
-                Err(err) => {
-                    warn!("LSP indexer failed (falling back to regex): {}", err);
-                    core::SymbolIndex::build(
-                        repo_root,
-                        config.symbol_index_max_files,
-                        config.symbol_index_max_bytes,
-                        config.symbol_index_max_locations,
-                        |path| config.should_exclude(path),
-                    )
+    let mut output = String::new();
+    output.push_str("## 🧭 Change Walkthrough\n\n");
+    if summaries.is_empty() {
+        output.push_str("| File | Status | Changes |\n|------|--------|---------|\n");
+    } else {
+        output.push_str(
+            "| File | Status | Changes | Summary |\n|------|--------|---------|---------|\n",
+        );
+    }
+    for diff in files.iter().take(max_entries) {
+        let mut added = 0usize;
+        let mut removed = 0usize;
+        for hunk in &diff.hunks {
+            for change in &hunk.changes {
+                match change.change_type {
+                    core::diff_parser::ChangeType::Added => added += 1,
+                    core::diff_parser::ChangeType::Removed => removed += 1,
+                    _ => {}
                 }
             }
-        } else {
-            warn!("No LSP command configured or detected; falling back to regex indexer.");
-            core::SymbolIndex::build(
-                repo_root,
-                config.symbol_index_max_files,
-                config.symbol_index_max_bytes,
-                config.symbol_index_max_locations,
-                |path| config.should_exclude(path),
-            )
         }
-    } else {
-        core::SymbolIndex::build(
-            repo_root,
-            config.symbol_index_max_files,
-            config.symbol_index_max_bytes,
-            config.symbol_index_max_locations,
-            |path| config.should_exclude(path),
-        )
-    };
 
-    match result {
-        Ok(index) => {
-            info!(
-                "Indexed {} symbols across {} files",
-                index.symbols_indexed(),
-                index.files_indexed()
-            );
-            Some(index)
-        }
-        Err(err) => {
-            warn!("Symbol index build failed: {}", err);
-            None
+        let status = if diff.is_deleted {
+            "deleted".to_string()
+        } else if diff.is_new {
+            "new".to_string()
+        } else if let Some(old_path) = diff.old_path.as_ref().filter(|_| diff.is_renamed) {
+            format!("renamed from `{}`", old_path.display())
+        } else {
+            "modified".to_string()
+        };
+
+        output.push_str(&format!(
+            "| `{}` | {} | +{}, -{} |",
+            diff.file_path.display(),
+            status,
+            added,
+            removed
+        ));
+        if !summaries.is_empty() {
+            let summary = summaries
+                .get(&diff.file_path)
+                .map(|summary| summary.replace('|', "\\|"))
+                .unwrap_or_default();
+            output.push_str(&format!(" {} |", summary));
         }
+        output.push('\n');
     }
+    if files.len() > max_entries {
+        output.push_str(&format!(
+            "\n...and {} more files\n",
+            files.len() - max_entries
+        ));
+    }
+
+    output
 }
 
 fn format_pr_summary_section(summary: &core::pr_summary::PRSummary) -> String {
@@ -2412,103 +5325,28 @@ fn format_pr_summary_section(summary: &core::pr_summary::PRSummary) -> String {
     output
 }
 
-#[derive(Debug, Default, Serialize, Deserialize)]
-struct FeedbackStore {
-    #[serde(default)]
-    suppress: HashSet<String>,
-    #[serde(default)]
-    accept: HashSet<String>,
-}
-
-fn load_feedback_store_from_path(path: &Path) -> FeedbackStore {
-    match std::fs::read_to_string(path) {
-        Ok(content) => serde_json::from_str(&content).unwrap_or_default(),
-        Err(_) => FeedbackStore::default(),
-    }
-}
-
-fn load_feedback_store(config: &config::Config) -> FeedbackStore {
-    load_feedback_store_from_path(&config.feedback_path)
-}
-
-fn save_feedback_store(path: &Path, store: &FeedbackStore) -> Result<()> {
-    let content = serde_json::to_string_pretty(store)?;
-    std::fs::write(path, content)?;
-    Ok(())
-}
-
-fn apply_feedback_suppression(
-    comments: Vec<core::Comment>,
-    feedback: &FeedbackStore,
-) -> Vec<core::Comment> {
-    if feedback.suppress.is_empty() {
-        return comments;
-    }
-
-    let total = comments.len();
-    let mut kept = Vec::with_capacity(total);
-
-    for comment in comments {
-        if feedback.suppress.contains(&comment.id) {
-            continue;
-        }
-        kept.push(comment);
-    }
-
-    if kept.len() != total {
-        let dropped = total.saturating_sub(kept.len());
-        info!(
-            "Dropped {} comment(s) due to feedback suppression rules",
-            dropped
-        );
-    }
-
-    kept
-}
-
-fn apply_confidence_threshold(
-    comments: Vec<core::Comment>,
-    min_confidence: f32,
-) -> Vec<core::Comment> {
-    if min_confidence <= 0.0 {
-        return comments;
-    }
-
-    let total = comments.len();
-    let mut kept = Vec::with_capacity(total);
-
-    for comment in comments {
-        if comment.confidence >= min_confidence {
-            kept.push(comment);
-        }
-    }
-
-    if kept.len() != total {
-        let dropped = total.saturating_sub(kept.len());
-        info!(
-            "Dropped {} comment(s) below confidence threshold {}",
-            dropped, min_confidence
-        );
-    }
-
-    kept
-}
-
-fn is_line_in_diff(diff: &core::UnifiedDiff, line_number: usize) -> bool {
-    if line_number == 0 {
-        return false;
-    }
-    diff.hunks.iter().any(|hunk| {
-        hunk.changes
-            .iter()
-            .any(|line| line.new_line_no == Some(line_number))
-    })
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// A Warning-level Bug finding; override fields with struct update syntax.
+    pub(crate) fn finding(file_path: &str, line_number: usize, content: &str) -> core::Comment {
+        core::Comment {
+            id: "cmt_test".to_string(),
+            file_path: PathBuf::from(file_path),
+            line_number,
+            content: content.to_string(),
+            severity: core::comment::Severity::Warning,
+            category: core::comment::Category::Bug,
+            suggestion: None,
+            confidence: 0.9,
+            code_suggestion: None,
+            tags: Vec::new(),
+            fix_effort: core::comment::FixEffort::Low,
+            owners: Vec::new(),
+        }
+    }
+
     #[test]
     fn parse_smart_review_response_parses_fields() {
         let input = r#"
@@ -2547,4 +5385,278 @@ TAGS: auth, security
         assert!((confidence - 0.85).abs() < 0.0001);
         assert_eq!(comment.fix_effort, Some(core::comment::FixEffort::High));
     }
+
+    #[test]
+    fn github_review_anchors_comments_inside_the_diff() {
+        let diffs = core::DiffParser::parse_unified_diff(
+            "--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1,2 +1,3 @@\n fn run() {\n+    let first = items[0];\n }\n",
+        )
+        .unwrap();
+        let positions = core::position::PositionMap::new(&diffs);
+        let comment =
+            |line_number| finding("src/lib.rs", line_number, "Indexing panics on empty input");
+
+        let review = build_github_review(&[comment(2), comment(40)], &positions, "abc123", false);
+        assert_eq!(review["commit_id"], "abc123");
+        assert_eq!(review["event"], "COMMENT");
+        let inline = review["comments"].as_array().unwrap();
+        assert_eq!(inline.len(), 1);
+        assert_eq!(inline[0]["line"], 2);
+        assert_eq!(inline[0]["side"], "RIGHT");
+        assert!(review["body"].as_str().unwrap().contains("`src/lib.rs:40`"));
+        assert!(review["body"]
+            .as_str()
+            .unwrap()
+            .contains("<!-- diffscope:reviewed-sha=abc123 -->"));
+    }
+
+    #[test]
+    fn clean_github_review_still_records_the_reviewed_head() {
+        let positions = core::position::PositionMap::new(&[]);
+
+        let review = build_github_review(&[], &positions, "def456", false);
+        assert_eq!(review["event"], "COMMENT");
+        assert!(review["comments"].as_array().unwrap().is_empty());
+        assert!(review["body"]
+            .as_str()
+            .unwrap()
+            .contains("<!-- diffscope:reviewed-sha=def456 -->"));
+    }
+
+    #[test]
+    fn pr_commands_come_only_from_authorized_commenters() {
+        let comment = |id, association: &str| providers::github::IssueComment {
+            id,
+            body: "@diffscope explain".to_string(),
+            author_association: association.to_string(),
+        };
+        let comments = [
+            comment(1, "NONE"),
+            comment(2, "CONTRIBUTOR"),
+            comment(3, "OWNER"),
+        ];
+
+        let (pending, ignored) = pr_commands(&comments, &[], &config::Config::default());
+        let ids: Vec<u64> = pending.iter().map(|(id, _)| *id).collect();
+        assert_eq!(ids, [3]);
+        assert_eq!(ignored, 2);
+    }
+
+    #[test]
+    fn owners_are_mentioned_for_errors_and_group_the_markdown_report() {
+        let positions = core::position::PositionMap::new(&[]);
+        let comment = |path: &str, severity, owners: &[&str]| core::Comment {
+            severity,
+            category: core::comment::Category::Security,
+            owners: owners.iter().map(|owner| owner.to_string()).collect(),
+            ..finding(path, 3, "Token is logged")
+        };
+        let comments = [
+            comment(
+                "web/auth.ts",
+                core::comment::Severity::Error,
+                &["@acme/web", "sec@acme.com"],
+            ),
+            comment(
+                "api/db.rs",
+                core::comment::Severity::Warning,
+                &["@acme/api"],
+            ),
+            comment("README.md", core::comment::Severity::Info, &[]),
+        ];
+
+        let review = build_github_review(&comments, &positions, "abc123", true);
+        let body = review["body"].as_str().unwrap();
+        assert!(body.contains("cc @acme/web: errors were found in files you own."));
+        assert!(!body.contains("@acme/api"));
+
+        let report = format_as_markdown(&comments);
+        let sections: Vec<&str> = report
+            .lines()
+            .filter(|line| line.starts_with("## Owned by") || line.starts_with("## No Owner"))
+            .collect();
+        assert_eq!(
+            sections,
+            [
+                "## No Owner",
+                "## Owned by @acme/api",
+                "## Owned by @acme/web sec@acme.com"
+            ]
+        );
+    }
+
+    #[test]
+    fn rdjson_output_maps_findings_to_diagnostics() {
+        let comment = core::Comment {
+            severity: core::comment::Severity::Error,
+            category: core::comment::Category::Security,
+            suggestion: Some("Use a parameterized query".to_string()),
+            ..finding("src/lib.rs", 7, "SQL built from user input")
+        };
+
+        let result: serde_json::Value =
+            serde_json::from_str(&format_as_rdjson(std::slice::from_ref(&comment), &[]).unwrap())
+                .unwrap();
+        let diagnostic = &result["diagnostics"][0];
+        assert_eq!(diagnostic["location"]["path"], "src/lib.rs");
+        assert_eq!(diagnostic["location"]["range"]["start"]["line"], 7);
+        assert_eq!(diagnostic["severity"], "ERROR");
+        assert_eq!(diagnostic["code"]["value"], "Security");
+
+        let lines = format_as_rdjsonl(&[comment.clone(), comment], &[]).unwrap();
+        assert_eq!(lines.lines().count(), 2);
+    }
+
+    #[test]
+    fn codeclimate_fingerprints_ignore_line_numbers() {
+        let comment = |line_number| core::Comment {
+            severity: core::comment::Severity::Error,
+            ..finding("src/lib.rs", line_number, "Unchecked index")
+        };
+        let parse = |comments: &[core::Comment]| -> serde_json::Value {
+            serde_json::from_str(&format_as_codeclimate(comments, &[]).unwrap()).unwrap()
+        };
+
+        let before = parse(&[comment(10)]);
+        let after = parse(&[comment(14), comment(30)]);
+        assert_eq!(before[0]["fingerprint"], after[0]["fingerprint"]);
+        assert_ne!(after[0]["fingerprint"], after[1]["fingerprint"]);
+        assert_eq!(after[0]["severity"], "critical");
+        assert_eq!(after[0]["categories"][0], "Bug Risk");
+        assert_eq!(after[1]["location"]["lines"]["begin"], 30);
+    }
+
+    #[test]
+    fn xml_outputs_escape_finding_text() {
+        let comment = finding("src/lib.rs", 3, "Compare with `a < b && \"c\"`");
+
+        let junit = format_as_junit(std::slice::from_ref(&comment), &[]);
+        assert!(junit.contains("tests=\"1\" failures=\"1\" skipped=\"0\""));
+        assert!(junit.contains("message=\"Compare with `a &lt; b &amp;&amp; &quot;c&quot;`\""));
+
+        let checkstyle = format_as_checkstyle(&[comment], &[]);
+        assert!(checkstyle.contains("<file name=\"src/lib.rs\">"));
+        assert!(checkstyle.contains("line=\"3\" severity=\"warning\""));
+        assert!(checkstyle.contains("source=\"diffscope.Bug\""));
+    }
+
+    #[test]
+    fn machine_formats_report_skipped_files_as_warnings() {
+        let skipped = [core::comment::SkippedFile {
+            file_path: PathBuf::from("src/big.rs"),
+            error: "request timed out".to_string(),
+        }];
+
+        let rdjson: serde_json::Value =
+            serde_json::from_str(&format_as_rdjson(&[], &skipped).unwrap()).unwrap();
+        let diagnostic = &rdjson["diagnostics"][0];
+        assert_eq!(diagnostic["location"]["path"], "src/big.rs");
+        assert_eq!(diagnostic["severity"], "WARNING");
+        assert_eq!(diagnostic["message"], "Not reviewed: request timed out");
+        assert_eq!(format_as_rdjsonl(&[], &skipped).unwrap().lines().count(), 1);
+
+        let junit = format_as_junit(&[], &skipped);
+        assert!(junit.contains("tests=\"1\" failures=\"0\" skipped=\"1\""));
+        assert!(junit.contains("<skipped message=\"Not reviewed: request timed out\"/>"));
+
+        let checkstyle = format_as_checkstyle(&[], &skipped);
+        assert!(checkstyle.contains("<file name=\"src/big.rs\">"));
+        assert!(checkstyle.contains("severity=\"warning\" message=\"Not reviewed: request timed out\" source=\"diffscope.Skipped\""));
+
+        let codeclimate: serde_json::Value =
+            serde_json::from_str(&format_as_codeclimate(&[], &skipped).unwrap()).unwrap();
+        assert_eq!(codeclimate[0]["check_name"], "diffscope/Skipped");
+        assert_eq!(codeclimate[0]["location"]["path"], "src/big.rs");
+    }
+
+    #[tokio::test]
+    async fn json_output_lists_skipped_files_when_asked() {
+        let skipped = [core::comment::SkippedFile {
+            file_path: PathBuf::from("src/big.rs"),
+            error: "request timed out".to_string(),
+        }];
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("review.json");
+        let mut config = config::Config::default();
+        let mut reports = Vec::new();
+        for include_skipped in [false, true] {
+            config.json_include_skipped = include_skipped;
+            output_comments(
+                &[],
+                &skipped,
+                Some(path.clone()),
+                OutputFormat::Json,
+                &config,
+            )
+            .await
+            .unwrap();
+            let report: serde_json::Value =
+                serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+            reports.push(report);
+        }
+
+        assert_eq!(reports[0], serde_json::json!([]));
+        assert_eq!(reports[1]["comments"], serde_json::json!([]));
+        assert_eq!(reports[1]["skipped"][0]["file_path"], "src/big.rs");
+        assert_eq!(reports[1]["skipped"][0]["error"], "request timed out");
+    }
+
+    #[test]
+    fn review_policy_fails_on_severity_and_issue_count() {
+        let comment = |severity| core::Comment {
+            severity,
+            ..finding("src/lib.rs", 1, "finding")
+        };
+        let comments = vec![
+            comment(core::comment::Severity::Info),
+            comment(core::comment::Severity::Suggestion),
+        ];
+        let mut config = config::Config::default();
+        assert!(review_policy_violation(&comments, &config).is_none());
+
+        config.fail_on = Some(config::FailOn::Warning);
+        assert!(review_policy_violation(&comments, &config).is_none());
+        config.fail_on = Some(config::FailOn::Info);
+        assert_eq!(
+            review_policy_violation(&comments, &config).as_deref(),
+            Some("1 finding(s) at or above info severity")
+        );
+
+        config.fail_on = None;
+        config.max_issues = Some(1);
+        assert!(enforce_review_policy(&comments, &config).is_err());
+    }
+
+    #[test]
+    fn incremental_diff_keeps_only_pr_files() {
+        let pr_diffs = core::DiffParser::parse_unified_diff(
+            "diff --git a/src/lib.rs b/src/lib.rs\n--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1 +1 @@\n-a\n+b\n",
+        )
+        .unwrap();
+        let range_diff = "diff --git a/src/lib.rs b/src/lib.rs\n--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1 +1 @@\n-b\n+c\ndiff --git a/upstream.rs b/upstream.rs\n--- a/upstream.rs\n+++ b/upstream.rs\n@@ -1 +1 @@\n-x\n+y\n";
+
+        let restricted = restrict_diff_to_files(range_diff, &pr_diffs);
+        assert!(restricted.contains("+c"));
+        assert!(!restricted.contains("upstream.rs"));
+    }
+
+    #[test]
+    fn apply_only_writes_inside_the_repository() {
+        let repo = tempfile::tempdir().unwrap();
+        std::fs::create_dir(repo.path().join("src")).unwrap();
+        std::fs::write(repo.path().join("src/lib.rs"), "").unwrap();
+
+        assert!(path_in_repo(repo.path(), Path::new("src/lib.rs")).is_some());
+        assert!(path_in_repo(repo.path(), Path::new("src/new.rs")).is_some());
+        assert!(path_in_repo(repo.path(), Path::new("../outside.rs")).is_none());
+        assert!(path_in_repo(repo.path(), Path::new("src/../../outside.rs")).is_none());
+        assert!(path_in_repo(repo.path(), Path::new("/etc/passwd")).is_none());
+        #[cfg(unix)]
+        {
+            let outside = tempfile::tempdir().unwrap();
+            std::os::unix::fs::symlink(outside.path(), repo.path().join("link")).unwrap();
+            std::fs::write(outside.path().join("x.rs"), "").unwrap();
+            assert!(path_in_repo(repo.path(), Path::new("link/x.rs")).is_none());
+        }
+    }
 }
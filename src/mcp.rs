@@ -0,0 +1,281 @@
+use crate::config::Config;
+use crate::core;
+use anyhow::Result;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tracing::info;
+
+/// Protocol revision answered when the client does not ask for one.
+const PROTOCOL_VERSION: &str = "2024-11-05";
+
+struct McpState {
+    engine: crate::ReviewEngine,
+}
+
+/// Serves the Model Context Protocol over stdin/stdout (one JSON-RPC message per line) until
+/// the client closes stdin. The symbol index is built once and shared by every tool call.
+pub async fn serve_stdio(config: Config, repo_root: PathBuf) -> Result<()> {
+    let state = Arc::new(McpState {
+        engine: crate::ReviewEngine::new(config).with_repo_root(repo_root),
+    });
+    let indexing = state.clone();
+    tokio::task::spawn_blocking(move || indexing.engine.symbol_index().is_some()).await?;
+
+    info!("MCP server ready on stdio");
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut stdout = tokio::io::stdout();
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = handle_message(&state, &line).await {
+            stdout
+                .write_all(format!("{}\n", response).as_bytes())
+                .await?;
+            stdout.flush().await?;
+        }
+    }
+    Ok(())
+}
+
+/// The response to one JSON-RPC message, or `None` for notifications.
+async fn handle_message(state: &McpState, line: &str) -> Option<Value> {
+    let message: Value = match serde_json::from_str(line) {
+        Ok(message) => message,
+        Err(err) => return Some(error_response(Value::Null, -32700, &err.to_string())),
+    };
+    let id = message.get("id")?.clone();
+    let params = message.get("params").cloned().unwrap_or(Value::Null);
+    let result = match message["method"].as_str().unwrap_or_default() {
+        "initialize" => json!({
+            "protocolVersion": params["protocolVersion"].as_str().unwrap_or(PROTOCOL_VERSION),
+            "capabilities": { "tools": {} },
+            "serverInfo": { "name": "diffscope", "version": env!("CARGO_PKG_VERSION") },
+        }),
+        "ping" => json!({}),
+        "tools/list" => json!({ "tools": tool_definitions() }),
+        "tools/call" => {
+            let name = params["name"].as_str().unwrap_or_default();
+            match call_tool(state, name, &params["arguments"]).await {
+                Ok(text) => json!({ "content": [{ "type": "text", "text": text }] }),
+                Err(err) => json!({
+                    "content": [{ "type": "text", "text": format!("{:#}", err) }],
+                    "isError": true,
+                }),
+            }
+        }
+        method => {
+            return Some(error_response(
+                id,
+                -32601,
+                &format!("method not found: {}", method),
+            ))
+        }
+    };
+    Some(json!({ "jsonrpc": "2.0", "id": id, "result": result }))
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+fn tool_definitions() -> Value {
+    let diff_input = |description: &str| {
+        json!({
+            "type": "object",
+            "properties": { "diff": { "type": "string", "description": description } },
+            "required": ["diff"],
+        })
+    };
+    json!([
+        {
+            "name": "review_diff",
+            "description": "Review a unified diff against the repository and return the findings as JSON comments (file, line, severity, category, content, suggestion).",
+            "inputSchema": diff_input("Unified diff to review, e.g. the output of `git diff`"),
+        },
+        {
+            "name": "summarize_pr",
+            "description": "Write a pull request summary (title, description, key changes, testing notes) for a unified diff, as Markdown.",
+            "inputSchema": diff_input("Unified diff of the pull request"),
+        },
+        {
+            "name": "explain_symbol",
+            "description": "Explain what a function, type, or other symbol in the repository does, using its definition and related code.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "symbol": { "type": "string", "description": "Name of the symbol, e.g. parse_config" },
+                    "file": { "type": "string", "description": "Repository-relative file that defines or uses the symbol; needed when the symbol index is disabled" },
+                },
+                "required": ["symbol"],
+            },
+        },
+        {
+            "name": "suggest_commit",
+            "description": "Suggest a Conventional Commits message for a diff, or for the repository's staged changes when no diff is given.",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "diff": { "type": "string", "description": "Unified diff; defaults to the staged changes" } },
+            },
+        },
+    ])
+}
+
+fn string_argument<'a>(arguments: &'a Value, name: &str) -> Result<&'a str> {
+    arguments[name]
+        .as_str()
+        .filter(|value| !value.trim().is_empty())
+        .ok_or_else(|| anyhow::anyhow!("missing required argument `{}`", name))
+}
+
+async fn call_tool(state: &McpState, name: &str, arguments: &Value) -> Result<String> {
+    match name {
+        "review_diff" => {
+            let comments = state
+                .engine
+                .review_diff(string_argument(arguments, "diff")?)
+                .await?;
+            Ok(serde_json::to_string_pretty(&comments)?)
+        }
+        "summarize_pr" => {
+            let diff = string_argument(arguments, "diff")?;
+            let summary = state.engine.summarize(diff).await?;
+            Ok(summary.to_markdown())
+        }
+        "explain_symbol" => {
+            let symbol = string_argument(arguments, "symbol")?;
+            let file = arguments["file"].as_str().map(Path::new);
+            explain_symbol(state, symbol, file).await
+        }
+        "suggest_commit" => {
+            let diff = match arguments["diff"].as_str() {
+                Some(diff) => diff.to_string(),
+                None => core::GitIntegration::new(state.engine.repo_root())?.get_staged_diff()?,
+            };
+            if diff.trim().is_empty() {
+                anyhow::bail!("there are no staged changes to describe");
+            }
+            crate::request_commit_message(state.engine.config(), &diff).await
+        }
+        _ => anyhow::bail!("unknown tool: {}", name),
+    }
+}
+
+async fn explain_symbol(state: &McpState, symbol: &str, file: Option<&Path>) -> Result<String> {
+    let (config, symbol_index) = (state.engine.config(), state.engine.symbol_index());
+    let context_fetcher = core::ContextFetcher::new(state.engine.repo_root().to_path_buf())
+        .with_function_scope(config.function_scope_context);
+    let symbols = [symbol.to_string()];
+    let mut chunks = match file {
+        Some(file) => {
+            crate::fetch_definitions(
+                config,
+                &context_fetcher,
+                symbol_index,
+                &file.to_path_buf(),
+                &symbols,
+            )
+            .await?
+        }
+        None => Vec::new(),
+    };
+    if chunks.is_empty() {
+        if let Some(locations) = symbol_index.and_then(|index| index.lookup(symbol)) {
+            chunks.extend(
+                locations
+                    .iter()
+                    .take(config.symbol_index_max_locations)
+                    .map(|location| core::LLMContextChunk {
+                        content: location.snippet.clone(),
+                        context_type: core::ContextType::Definition,
+                        file_path: location.file_path.clone(),
+                        line_range: Some(location.line_range),
+                    }),
+            );
+        }
+    }
+    if chunks.is_empty() {
+        anyhow::bail!(
+            "no definition of `{}` found; pass the `file` that defines it, or enable symbol_index",
+            symbol
+        );
+    }
+
+    let builder = core::PromptBuilder::new(core::prompt::PromptConfig {
+        limits: config.prompt_limits(),
+        ..Default::default()
+    });
+    let material = builder.format_context(&chunks)?;
+    let adapter = crate::adapters::llm::create_adapter(&config.model_config())?;
+    let subject = format!("the symbol `{}`", symbol);
+    core::interactive::explain(adapter.as_ref(), &subject, &material).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn answers_the_handshake_and_reports_tool_errors() {
+        let repo = tempfile::tempdir().unwrap();
+        let state = McpState {
+            engine: crate::ReviewEngine::new(Config::default()).with_repo_root(repo.path()),
+        };
+
+        let initialize = handle_message(
+            &state,
+            r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2025-03-26"}}"#,
+        )
+        .await
+        .unwrap();
+        assert_eq!(initialize["result"]["protocolVersion"], "2025-03-26");
+        assert_eq!(initialize["result"]["serverInfo"]["name"], "diffscope");
+        assert!(handle_message(
+            &state,
+            r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#
+        )
+        .await
+        .is_none());
+
+        let tools = handle_message(&state, r#"{"jsonrpc":"2.0","id":2,"method":"tools/list"}"#)
+            .await
+            .unwrap();
+        let names: Vec<_> = tools["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|tool| tool["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "review_diff",
+                "summarize_pr",
+                "explain_symbol",
+                "suggest_commit"
+            ]
+        );
+
+        let call = handle_message(
+            &state,
+            r#"{"jsonrpc":"2.0","id":3,"method":"tools/call","params":{"name":"explain_symbol","arguments":{"symbol":"parse"}}}"#,
+        )
+        .await
+        .unwrap();
+        assert_eq!(call["result"]["isError"], true);
+        assert!(call["result"]["content"][0]["text"]
+            .as_str()
+            .unwrap()
+            .starts_with("no definition of `parse` found"));
+
+        let unknown = handle_message(
+            &state,
+            r#"{"jsonrpc":"2.0","id":4,"method":"resources/list"}"#,
+        )
+        .await
+        .unwrap();
+        assert_eq!(unknown["error"]["code"], -32601);
+    }
+}
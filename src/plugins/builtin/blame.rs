@@ -0,0 +1,300 @@
+use crate::config::PluginSettings;
+use crate::core::comment::{compute_comment_id, Category, FixEffort, Severity};
+use crate::core::diff_parser::{ChangeType, DiffHunk};
+use crate::core::{Comment, ContextType, GitIntegration, LLMContextChunk, LineBlame, UnifiedDiff};
+use crate::plugins::{PreAnalysis, PreAnalyzer};
+use anyhow::Result;
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+static REVERT: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)\brevert").unwrap());
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BlameSettings {
+    /// Age in days of the newest change around a hunk above which the hunk gets an old-code
+    /// hint.
+    #[serde(default = "default_stale_after_days")]
+    stale_after_days: i64,
+}
+
+fn default_stale_after_days() -> i64 {
+    730
+}
+
+/// Adds `git blame` for the context lines around each hunk (commit, author, age, and
+/// summary) to the review context, and notes hunks that change long-untouched code or code
+/// last touched by a revert.
+pub struct BlameAnalyzer {
+    settings: BlameSettings,
+}
+
+impl BlameAnalyzer {
+    pub fn new(settings: &PluginSettings) -> Result<Self> {
+        Ok(Self {
+            settings: super::parse_settings("blame", settings)?,
+        })
+    }
+
+    fn analysis(&self, diff: &UnifiedDiff, blame: &[LineBlame], now: i64) -> PreAnalysis {
+        let by_line: HashMap<usize, &LineBlame> =
+            blame.iter().map(|blamed| (blamed.line, blamed)).collect();
+        let mut analysis = PreAnalysis::default();
+        for hunk in &diff.hunks {
+            let around: Vec<&LineBlame> = hunk
+                .changes
+                .iter()
+                .filter(|line| matches!(line.change_type, ChangeType::Context))
+                .filter_map(|line| by_line.get(&line.new_line_no?).copied())
+                .collect();
+            let Some(latest) = around.iter().max_by_key(|blamed| blamed.time) else {
+                continue;
+            };
+
+            let end = hunk.new_start + hunk.new_lines.saturating_sub(1);
+            let mut content = format!(
+                "Git blame for the lines around {} lines {}-{}:\n",
+                diff.file_path.display(),
+                hunk.new_start,
+                end
+            );
+            for run in runs(&around) {
+                let (first, last) = (run[0], run[run.len() - 1]);
+                let lines = if first.line == last.line {
+                    format!("line {}", first.line)
+                } else {
+                    format!("lines {}-{}", first.line, last.line)
+                };
+                content.push_str(&format!(
+                    "- {}: {} by {}, {} ago: \"{}\"\n",
+                    lines,
+                    first.commit,
+                    first.author,
+                    age((now - first.time) / SECONDS_PER_DAY).1,
+                    first.summary
+                ));
+            }
+            analysis.chunks.push(LLMContextChunk {
+                content,
+                context_type: ContextType::Documentation,
+                file_path: diff.file_path.clone(),
+                line_range: Some((hunk.new_start, end)),
+            });
+            analysis.findings.extend(self.hint(diff, hunk, latest, now));
+        }
+        analysis
+    }
+
+    /// A note when the newest change around `hunk` is old or a revert.
+    fn hint(
+        &self,
+        diff: &UnifiedDiff,
+        hunk: &DiffHunk,
+        latest: &LineBlame,
+        now: i64,
+    ) -> Option<Comment> {
+        let days = (now - latest.time) / SECONDS_PER_DAY;
+        let stale = days >= self.settings.stale_after_days;
+        let reverted = REVERT.is_match(&latest.summary);
+        let (old, ago) = age(days);
+        let (content, suggestion, tag) = match (stale, reverted) {
+            (false, false) => return None,
+            (true, true) => (
+                format!(
+                    "Modifying {} code last touched in a revert: {} \"{}\".",
+                    old, latest.commit, latest.summary
+                ),
+                "Check why the earlier change was reverted so this one does not repeat it.",
+                "revert",
+            ),
+            (true, false) => (
+                format!(
+                    "Modifying {} code, last touched in {} \"{}\" by {}.",
+                    old, latest.commit, latest.summary, latest.author
+                ),
+                "Check what has come to rely on this long-stable code before changing it.",
+                "stale-code",
+            ),
+            (false, true) => (
+                format!(
+                    "Modifying code last touched in a revert {} ago: {} \"{}\".",
+                    ago, latest.commit, latest.summary
+                ),
+                "Check why the earlier change was reverted so this one does not repeat it.",
+                "revert",
+            ),
+        };
+        let line_number = hunk
+            .changes
+            .iter()
+            .filter(|line| !matches!(line.change_type, ChangeType::Context))
+            .find_map(|line| line.new_line_no.or(line.old_line_no))
+            .unwrap_or(hunk.new_start);
+        Some(Comment {
+            id: compute_comment_id(&diff.file_path, &content, &Category::Maintainability),
+            file_path: diff.file_path.clone(),
+            line_number,
+            content,
+            severity: Severity::Info,
+            category: Category::Maintainability,
+            suggestion: Some(suggestion.to_string()),
+            confidence: 1.0,
+            code_suggestion: None,
+            tags: vec!["blame".to_string(), tag.to_string()],
+            fix_effort: FixEffort::Low,
+            owners: Vec::new(),
+        })
+    }
+}
+
+/// Consecutive lines last changed by the same commit.
+fn runs<'a>(blame: &[&'a LineBlame]) -> Vec<Vec<&'a LineBlame>> {
+    let mut runs: Vec<Vec<&LineBlame>> = Vec::new();
+    for &blamed in blame {
+        match runs.last_mut() {
+            Some(run)
+                if run[run.len() - 1].commit == blamed.commit
+                    && run[run.len() - 1].line + 1 == blamed.line =>
+            {
+                run.push(blamed)
+            }
+            _ => runs.push(vec![blamed]),
+        }
+    }
+    runs
+}
+
+/// `days` as an adjective and as a duration: ("4-year-old", "4 years").
+fn age(days: i64) -> (String, String) {
+    let (count, unit) = match days.max(0) {
+        days if days >= 365 => (days / 365, "year"),
+        days if days >= 30 => (days / 30, "month"),
+        days => (days, "day"),
+    };
+    let plural = if count == 1 { "" } else { "s" };
+    (
+        format!("{}-{}-old", count, unit),
+        format!("{} {}{}", count, unit, plural),
+    )
+}
+
+#[async_trait]
+impl PreAnalyzer for BlameAnalyzer {
+    fn id(&self) -> &str {
+        "blame"
+    }
+
+    fn settings(&self) -> serde_json::Value {
+        serde_json::to_value(&self.settings).unwrap_or_default()
+    }
+
+    async fn run(&self, diff: &UnifiedDiff, repo_path: &str) -> Result<Vec<LLMContextChunk>> {
+        Ok(self.analyze(diff, repo_path).await?.chunks)
+    }
+
+    async fn analyze(&self, diff: &UnifiedDiff, repo_path: &str) -> Result<PreAnalysis> {
+        if diff.is_new || diff.is_deleted || diff.is_binary {
+            return Ok(PreAnalysis::default());
+        }
+        let lines: Vec<usize> = diff
+            .hunks
+            .iter()
+            .flat_map(|hunk| &hunk.changes)
+            .filter(|line| matches!(line.change_type, ChangeType::Context))
+            .filter_map(|line| line.new_line_no)
+            .collect();
+        if lines.is_empty() {
+            return Ok(PreAnalysis::default());
+        }
+
+        let repo_root = Path::new(repo_path).to_path_buf();
+        let path = diff.file_path.clone();
+        let content = diff.new_content.clone();
+        let blame = tokio::task::spawn_blocking(move || {
+            let content = match content {
+                Some(content) => content.into_bytes(),
+                None => std::fs::read(repo_root.join(&path))?,
+            };
+            GitIntegration::new(&repo_root)?.blame_lines(&path, &content, &lines)
+        })
+        .await?;
+        // Files outside the repository or not yet committed have no history to show.
+        let Ok(blame) = blame else {
+            return Ok(PreAnalysis::default());
+        };
+
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |now| now.as_secs() as i64);
+        Ok(self.analysis(diff, &blame, now))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn blames_context_lines_and_flags_old_reverted_code() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = git2::Repository::init(dir.path()).unwrap();
+        std::fs::write(
+            dir.path().join("retry.py"),
+            "import time\n\ndef retry():\n    pass\n",
+        )
+        .unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("retry.py")).unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        // 2019-01-01.
+        let signature =
+            git2::Signature::new("Dev", "dev@example.com", &git2::Time::new(1_546_300_800, 0))
+                .unwrap();
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            "Revert \"Add retry loop\"",
+            &tree,
+            &[],
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("retry.py"),
+            "import time\n\ndef retry():\n    time.sleep(1)\n",
+        )
+        .unwrap();
+        let diff = crate::core::DiffParser::parse_unified_diff(
+            "--- a/retry.py\n+++ b/retry.py\n@@ -1,4 +1,4 @@\n import time\n \n def retry():\n-    pass\n+    time.sleep(1)\n",
+        )
+        .unwrap()
+        .remove(0);
+
+        let analysis = BlameAnalyzer::new(&PluginSettings::new())
+            .unwrap()
+            .analyze(&diff, dir.path().to_str().unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(analysis.chunks.len(), 1);
+        let context = &analysis.chunks[0].content;
+        assert!(context.contains("- lines 1-3: "), "{}", context);
+        assert!(context.contains(" by Dev, "), "{}", context);
+        assert!(
+            context.contains("\"Revert \"Add retry loop\"\""),
+            "{}",
+            context
+        );
+        assert_eq!(analysis.findings.len(), 1);
+        assert_eq!(analysis.findings[0].line_number, 4);
+        assert!(analysis.findings[0]
+            .content
+            .contains("-year-old code last touched in a revert"));
+    }
+}
@@ -0,0 +1,778 @@
+use crate::config::PluginSettings;
+use crate::core::comment::{compute_comment_id, Category, FixEffort, Severity};
+use crate::core::{Comment, ContextType, LLMContextChunk, UnifiedDiff};
+use crate::plugins::{PreAnalysis, PreAnalyzer};
+use anyhow::Result;
+use async_trait::async_trait;
+use std::path::Path;
+use tree_sitter::{Node, Parser};
+
+/// Tag on every finding this plugin produces; the Breaking Changes report section lists them.
+pub const BREAKING_CHANGE_TAG: &str = "breaking-change";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Language {
+    Rust,
+    Python,
+    Go,
+    JavaScript,
+}
+
+impl Language {
+    fn of(path: &Path) -> Option<Self> {
+        let language = match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "rs" => Language::Rust,
+            "py" => Language::Python,
+            "go" => Language::Go,
+            "js" | "jsx" | "mjs" | "cjs" | "ts" | "mts" | "cts" | "tsx" => Language::JavaScript,
+            _ => return None,
+        };
+        Some(language)
+    }
+
+    fn separator(self) -> &'static str {
+        match self {
+            Language::Rust => "::",
+            _ => ".",
+        }
+    }
+}
+
+/// A public function or method's signature.
+#[derive(Debug, Clone, PartialEq)]
+struct Signature {
+    /// Qualified with the type or class for methods, e.g. `Cart::add` or `Cart.add`.
+    name: String,
+    owner: Option<String>,
+    params: Vec<Param>,
+    returns: Option<String>,
+    /// 1-based line of the declaration.
+    line: usize,
+}
+
+impl Signature {
+    fn kind(&self) -> &'static str {
+        if self.owner.is_some() {
+            "method"
+        } else {
+            "function"
+        }
+    }
+
+    fn params_text(&self) -> String {
+        let params: Vec<&str> = self
+            .params
+            .iter()
+            .map(|param| param.text.as_str())
+            .collect();
+        format!("({})", params.join(", "))
+    }
+
+    /// Whether callers of `self` still compile against `new`: each parameter keeps its
+    /// `key` and does not become required, and new ones were only appended and are optional.
+    fn params_compatible(&self, new: &Signature) -> bool {
+        new.params.len() >= self.params.len()
+            && self
+                .params
+                .iter()
+                .zip(&new.params)
+                .all(|(old, new)| old.key == new.key && (new.is_optional() || !old.is_optional()))
+            && new.params[self.params.len()..]
+                .iter()
+                .all(Param::is_optional)
+    }
+
+    /// Whether `new` takes the same parameters and returns the same type.
+    fn same_shape(&self, new: &Signature) -> bool {
+        self.returns == new.returns
+            && self.params.len() == new.params.len()
+            && self
+                .params
+                .iter()
+                .zip(&new.params)
+                .all(|(old, new)| old.key == new.key)
+    }
+}
+
+/// A parameter as callers see it.
+#[derive(Debug, Clone, PartialEq)]
+struct Param {
+    /// Source text, whitespace collapsed.
+    text: String,
+    /// What calls depend on: the type in Rust, Go, and TypeScript, whose calls are
+    /// positional, and the whole text in Python, whose callers may pass it by keyword.
+    key: String,
+}
+
+impl Param {
+    fn is_optional(&self) -> bool {
+        is_optional(&self.text)
+    }
+}
+
+/// Defaulted (`x=1`, `x?: T`), variadic (`*args`, `...rest`, `opts ...Option`), and
+/// keyword-only markers can be added without breaking calls.
+fn is_optional(param: &str) -> bool {
+    param.contains('=')
+        || param.contains("?:")
+        || param.starts_with('*')
+        || param.starts_with("...")
+        || param.contains(" ...")
+}
+
+#[derive(Debug, PartialEq)]
+enum BreakingChange {
+    Removed(Signature),
+    Renamed(Signature, Signature),
+    SignatureChanged(Signature, Signature),
+}
+
+fn text(node: Node, source: &[u8]) -> String {
+    node.utf8_text(source)
+        .unwrap_or_default()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn field_text(node: Node, field: &str, source: &[u8]) -> Option<String> {
+    node.child_by_field_name(field)
+        .map(|child| text(child, source))
+}
+
+/// Public functions and methods declared in `source`, or nothing when there is no grammar for
+/// `path`.
+fn signatures(path: &Path, source: &str) -> Vec<Signature> {
+    let (Some(language), Some(grammar)) =
+        (Language::of(path), crate::core::syntax::language_for(path))
+    else {
+        return Vec::new();
+    };
+    let mut parser = Parser::new();
+    if parser.set_language(&grammar).is_err() {
+        return Vec::new();
+    }
+    let Some(tree) = parser.parse(source, None) else {
+        return Vec::new();
+    };
+    let mut out = Vec::new();
+    collect(
+        language,
+        tree.root_node(),
+        source.as_bytes(),
+        None,
+        &mut out,
+    );
+    out
+}
+
+fn collect(
+    language: Language,
+    node: Node,
+    source: &[u8],
+    owner: Option<&str>,
+    out: &mut Vec<Signature>,
+) {
+    let name = field_text(node, "name", source).unwrap_or_default();
+    let descend = |owner: Option<&str>, out: &mut Vec<Signature>| {
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            collect(language, child, source, owner, out);
+        }
+    };
+    match (language, node.kind()) {
+        (Language::Rust, "source_file" | "declaration_list") => descend(owner, out),
+        // Methods of trait implementations belong to the trait's API, not the type's.
+        (Language::Rust, "impl_item") if node.child_by_field_name("trait").is_none() => {
+            let ty = field_text(node, "type", source).unwrap_or_default();
+            let ty = ty.split('<').next().unwrap_or_default();
+            if let Some(body) = node.child_by_field_name("body") {
+                collect(language, body, source, Some(ty), out);
+            }
+        }
+        (Language::Rust, "function_item") if rust_public(node, source) => out.push(signature(
+            language,
+            node,
+            source,
+            owner,
+            name,
+            "return_type",
+        )),
+        (Language::Python, "module" | "block") => descend(owner, out),
+        (Language::Python, "decorated_definition") => {
+            if let Some(definition) = node.child_by_field_name("definition") {
+                collect(language, definition, source, owner, out);
+            }
+        }
+        (Language::Python, "class_definition") if !name.starts_with('_') => {
+            if let Some(body) = node.child_by_field_name("body") {
+                collect(language, body, source, Some(&name), out);
+            }
+        }
+        (Language::Python, "function_definition")
+            if !name.starts_with('_') || name == "__init__" =>
+        {
+            out.push(signature(
+                language,
+                node,
+                source,
+                owner,
+                name,
+                "return_type",
+            ))
+        }
+        (Language::Go, "source_file") => descend(None, out),
+        (Language::Go, "function_declaration") if go_exported(&name) => {
+            out.push(signature(language, node, source, None, name, "result"))
+        }
+        (Language::Go, "method_declaration") if go_exported(&name) => {
+            let receiver = node
+                .child_by_field_name("receiver")
+                .and_then(|receiver| first_descendant(receiver, "type_identifier"))
+                .map(|ty| text(ty, source))
+                .unwrap_or_default();
+            if go_exported(&receiver) {
+                out.push(signature(
+                    language,
+                    node,
+                    source,
+                    Some(&receiver),
+                    name,
+                    "result",
+                ))
+            }
+        }
+        (Language::JavaScript, "program") => descend(None, out),
+        (Language::JavaScript, "export_statement") => {
+            if let Some(declaration) = node.child_by_field_name("declaration") {
+                collect_exported(declaration, source, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Exported JavaScript/TypeScript functions, arrow functions bound to `const`, and the public
+/// methods of exported classes.
+fn collect_exported(declaration: Node, source: &[u8], out: &mut Vec<Signature>) {
+    let language = Language::JavaScript;
+    let name = field_text(declaration, "name", source).unwrap_or_default();
+    match declaration.kind() {
+        // `function_signature` is a TypeScript overload declaration.
+        "function_declaration" | "generator_function_declaration" | "function_signature" => out
+            .push(signature(
+                language,
+                declaration,
+                source,
+                None,
+                name,
+                "return_type",
+            )),
+        "class_declaration" | "abstract_class_declaration" => {
+            let Some(body) = declaration.child_by_field_name("body") else {
+                return;
+            };
+            let mut cursor = body.walk();
+            for method in body.named_children(&mut cursor) {
+                if method.kind() != "method_definition" {
+                    continue;
+                }
+                let method_name = field_text(method, "name", source).unwrap_or_default();
+                let mut inner = method.walk();
+                let hidden = method.children(&mut inner).any(|child| {
+                    child.kind() == "accessibility_modifier" && text(child, source) != "public"
+                });
+                if hidden || method_name.starts_with('#') || method_name == "constructor" {
+                    continue;
+                }
+                out.push(signature(
+                    language,
+                    method,
+                    source,
+                    Some(&name),
+                    method_name,
+                    "return_type",
+                ));
+            }
+        }
+        "lexical_declaration" => {
+            let mut cursor = declaration.walk();
+            for declarator in declaration.named_children(&mut cursor) {
+                let value = declarator.child_by_field_name("value");
+                if let Some(function) = value.filter(|value| value.kind() == "arrow_function") {
+                    let name = field_text(declarator, "name", source).unwrap_or_default();
+                    let mut signature =
+                        signature(language, function, source, None, name, "return_type");
+                    signature.line = declarator.start_position().row + 1;
+                    out.push(signature);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn signature(
+    language: Language,
+    node: Node,
+    source: &[u8],
+    owner: Option<&str>,
+    name: String,
+    returns_field: &str,
+) -> Signature {
+    let params = match node.child_by_field_name("parameters") {
+        Some(params) => {
+            let mut cursor = params.walk();
+            let params = params
+                .named_children(&mut cursor)
+                .filter(|param| param.kind() != "comment")
+                .flat_map(|param| params_of(language, param, source))
+                .collect();
+            params
+        }
+        // A single unparenthesized arrow-function parameter.
+        None => field_text(node, "parameter", source)
+            .map(|text| Param {
+                text,
+                key: String::new(),
+            })
+            .into_iter()
+            .collect(),
+    };
+    let returns = field_text(node, returns_field, source)
+        .map(|returns| returns.trim_start_matches(':').trim().to_string());
+    Signature {
+        name: match owner {
+            Some(owner) => format!("{}{}{}", owner, language.separator(), name),
+            None => name,
+        },
+        owner: owner.map(str::to_string),
+        params,
+        returns,
+        line: node.start_position().row + 1,
+    }
+}
+
+/// The parameters declared by `param`; one Go declaration can name several (`a, b int`).
+fn params_of(language: Language, param: Node, source: &[u8]) -> Vec<Param> {
+    let param_text = text(param, source);
+    let ty = || {
+        field_text(param, "type", source)
+            .map(|ty| ty.trim_start_matches(':').trim().to_string())
+            .unwrap_or_default()
+    };
+    match (language, param.kind()) {
+        (Language::Rust, "parameter") => vec![Param {
+            key: ty(),
+            text: param_text,
+        }],
+        // `mut self` only changes the body; `&self` vs `&mut self` changes what callers hold.
+        (Language::Rust, "self_parameter") => vec![Param {
+            key: param_text.trim_start_matches("mut ").to_string(),
+            text: param_text,
+        }],
+        (Language::Go, "parameter_declaration" | "variadic_parameter_declaration") => {
+            let ty = match param.kind() {
+                "variadic_parameter_declaration" => format!("...{}", ty()),
+                _ => ty(),
+            };
+            let mut cursor = param.walk();
+            let names: Vec<String> = param
+                .children_by_field_name("name", &mut cursor)
+                .map(|name| text(name, source))
+                .collect();
+            if names.is_empty() {
+                return vec![Param {
+                    key: ty,
+                    text: param_text,
+                }];
+            }
+            names
+                .into_iter()
+                .map(|name| Param {
+                    text: format!("{} {}", name, ty),
+                    key: ty.clone(),
+                })
+                .collect()
+        }
+        (Language::JavaScript, _) => {
+            // Untyped JavaScript parameters only count by position.
+            let rest = param
+                .child_by_field_name("pattern")
+                .is_some_and(|pattern| pattern.kind() == "rest_pattern")
+                || param_text.starts_with("...");
+            let key = format!("{}{}", if rest { "..." } else { "" }, ty());
+            vec![Param {
+                key,
+                text: param_text,
+            }]
+        }
+        _ => vec![Param {
+            key: param_text.clone(),
+            text: param_text,
+        }],
+    }
+}
+
+fn rust_public(node: Node, source: &[u8]) -> bool {
+    let mut cursor = node.walk();
+    let public = node
+        .children(&mut cursor)
+        .any(|child| child.kind() == "visibility_modifier" && text(child, source) == "pub");
+    public
+}
+
+fn go_exported(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_uppercase())
+}
+
+fn first_descendant<'a>(node: Node<'a>, kind: &str) -> Option<Node<'a>> {
+    if node.kind() == kind {
+        return Some(node);
+    }
+    let mut cursor = node.walk();
+    let children: Vec<Node<'a>> = node.named_children(&mut cursor).collect();
+    children
+        .into_iter()
+        .find_map(|child| first_descendant(child, kind))
+}
+
+/// Public functions removed, renamed (a removed function and an added one with the same owner
+/// and signature), or whose parameters or return type changed between `old` and `new`.
+///
+/// A name can be declared more than once (a Python property's getter and setter, TypeScript
+/// overloads, methods in several `impl` blocks), so each old declaration is paired with its
+/// own new declaration of the same name.
+fn breaking_changes(path: &Path, old: &str, new: &str) -> Vec<BreakingChange> {
+    let before = signatures(path, old);
+    let after = signatures(path, new);
+    let mut added: Vec<&Signature> = after
+        .iter()
+        .filter(|signature| !before.iter().any(|old| old.name == signature.name))
+        .collect();
+
+    // Pair unchanged declarations first, then ones with as many parameters, then the rest in
+    // file order, so one changed overload is not blamed on its siblings.
+    let mut pairs: Vec<Option<usize>> = vec![None; before.len()];
+    let mut paired = vec![false; after.len()];
+    let passes: [fn(&Signature, &Signature) -> bool; 3] = [
+        |old, new| old.params_compatible(new) && old.returns == new.returns,
+        |old, new| old.params.len() == new.params.len(),
+        |_, _| true,
+    ];
+    for matches in passes {
+        for (old, pair) in before.iter().zip(pairs.iter_mut()) {
+            if pair.is_some() {
+                continue;
+            }
+            *pair = after.iter().enumerate().position(|(index, new)| {
+                !paired[index] && new.name == old.name && matches(old, new)
+            });
+            if let Some(index) = *pair {
+                paired[index] = true;
+            }
+        }
+    }
+
+    let mut changes = Vec::new();
+    for (old, pair) in before.iter().zip(pairs) {
+        let redeclared = after.iter().any(|new| new.name == old.name);
+        match pair {
+            Some(index) => {
+                let new = &after[index];
+                if !old.params_compatible(new) || old.returns != new.returns {
+                    changes.push(BreakingChange::SignatureChanged(old.clone(), new.clone()));
+                }
+            }
+            // One of several declarations of a name went away, e.g. a dropped overload.
+            None if redeclared => changes.push(BreakingChange::Removed(old.clone())),
+            None => {
+                let candidates: Vec<usize> = added
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, new)| new.owner == old.owner && old.same_shape(new))
+                    .map(|(index, _)| index)
+                    .collect();
+                match candidates[..] {
+                    [index] => {
+                        let new = added.remove(index);
+                        changes.push(BreakingChange::Renamed(old.clone(), new.clone()));
+                    }
+                    _ => changes.push(BreakingChange::Removed(old.clone())),
+                }
+            }
+        }
+    }
+    changes
+}
+
+fn describe(change: &BreakingChange) -> String {
+    let returns = |signature: &Signature| {
+        signature
+            .returns
+            .clone()
+            .unwrap_or_else(|| "nothing".to_string())
+    };
+    match change {
+        BreakingChange::Removed(old) => format!(
+            "Public {} `{}` was removed; callers outside this change will break.",
+            old.kind(),
+            old.name
+        ),
+        BreakingChange::Renamed(old, new) => format!(
+            "Public {} `{}` was renamed to `{}`; existing callers of `{}` will break.",
+            old.kind(),
+            old.name,
+            new.name,
+            old.name
+        ),
+        BreakingChange::SignatureChanged(old, new) if old.returns == new.returns => format!(
+            "Parameters of public {} `{}` changed from `{}` to `{}`; existing callers will break.",
+            old.kind(),
+            old.name,
+            old.params_text(),
+            new.params_text()
+        ),
+        BreakingChange::SignatureChanged(old, new) if old.params_compatible(new) => format!(
+            "Return type of public {} `{}` changed from `{}` to `{}`; callers that use the result may break.",
+            old.kind(),
+            old.name,
+            returns(old),
+            returns(new)
+        ),
+        BreakingChange::SignatureChanged(old, new) => format!(
+            "Signature of public {} `{}` changed from `{} -> {}` to `{} -> {}`; existing callers will break.",
+            old.kind(),
+            old.name,
+            old.params_text(),
+            returns(old),
+            new.params_text(),
+            returns(new)
+        ),
+    }
+}
+
+/// New-file line to anchor `change` on; a removed function is anchored on the hunk that
+/// removed it.
+fn anchor(diff: &UnifiedDiff, change: &BreakingChange) -> usize {
+    match change {
+        BreakingChange::Renamed(_, new) | BreakingChange::SignatureChanged(_, new) => new.line,
+        BreakingChange::Removed(old) => diff
+            .hunks
+            .iter()
+            .find(|hunk| old.line >= hunk.old_start && old.line < hunk.old_start + hunk.old_lines)
+            .map_or(1, |hunk| hunk.new_start.max(1)),
+    }
+}
+
+fn to_comment(diff: &UnifiedDiff, change: &BreakingChange) -> Comment {
+    let content = describe(change);
+    Comment {
+        id: compute_comment_id(&diff.file_path, &content, &Category::Architecture),
+        file_path: diff.file_path.clone(),
+        line_number: anchor(diff, change),
+        content,
+        severity: Severity::Warning,
+        category: Category::Architecture,
+        suggestion: Some(
+            "Keep the old signature as a deprecated wrapper, or call out the breaking change in the changelog and release notes."
+                .to_string(),
+        ),
+        confidence: 1.0,
+        code_suggestion: None,
+        tags: vec![BREAKING_CHANGE_TAG.to_string(), "api".to_string()],
+        fix_effort: FixEffort::Medium,
+        owners: Vec::new(),
+    }
+}
+
+/// Markdown section listing the breaking-change findings in `comments`, or nothing when there
+/// are none.
+pub fn format_breaking_changes_section(comments: &[Comment]) -> String {
+    let breaking: Vec<&Comment> = comments
+        .iter()
+        .filter(|comment| comment.tags.iter().any(|tag| tag == BREAKING_CHANGE_TAG))
+        .collect();
+    if breaking.is_empty() {
+        return String::new();
+    }
+    let mut output = String::from("## 💥 Breaking Changes\n\n");
+    for comment in breaking {
+        output.push_str(&format!(
+            "- `{}:{}` {}\n",
+            comment.file_path.display(),
+            comment.line_number,
+            comment.content
+        ));
+    }
+    output.push('\n');
+    output
+}
+
+/// Compares the public functions and methods of each changed Rust, Python, Go, and
+/// JavaScript/TypeScript file before and after the diff, and reports removals, renames, and
+/// incompatible parameter or return type changes.
+pub struct BreakingChangeAnalyzer;
+
+impl BreakingChangeAnalyzer {
+    pub fn new(settings: &PluginSettings) -> Result<Self> {
+        if !settings.is_empty() {
+            anyhow::bail!("Plugin breaking_changes does not take settings");
+        }
+        Ok(Self)
+    }
+}
+
+#[async_trait]
+impl PreAnalyzer for BreakingChangeAnalyzer {
+    fn id(&self) -> &str {
+        "breaking_changes"
+    }
+
+    async fn run(&self, diff: &UnifiedDiff, repo_path: &str) -> Result<Vec<LLMContextChunk>> {
+        Ok(self.analyze(diff, repo_path).await?.chunks)
+    }
+
+    async fn analyze(&self, diff: &UnifiedDiff, repo_path: &str) -> Result<PreAnalysis> {
+        if diff.is_new
+            || diff.is_deleted
+            || diff.is_binary
+            || Language::of(&diff.file_path).is_none()
+        {
+            return Ok(PreAnalysis::default());
+        }
+        let new_source = match &diff.new_content {
+            Some(content) => content.clone(),
+            None => {
+                match tokio::fs::read_to_string(Path::new(repo_path).join(&diff.file_path)).await {
+                    Ok(content) => content,
+                    Err(_) => return Ok(PreAnalysis::default()),
+                }
+            }
+        };
+        let old_source = match &diff.old_content {
+            Some(content) => content.clone(),
+            None => super::complexity::reconstruct_old(diff, &new_source),
+        };
+
+        let changes = breaking_changes(&diff.file_path, &old_source, &new_source);
+        if changes.is_empty() {
+            return Ok(PreAnalysis::default());
+        }
+        let findings: Vec<Comment> = changes
+            .iter()
+            .map(|change| to_comment(diff, change))
+            .collect();
+        let mut content = format!(
+            "Breaking changes to the public API of {} (found by comparing signatures):\n",
+            diff.file_path.display()
+        );
+        for finding in &findings {
+            content.push_str(&format!("- {}\n", finding.content));
+        }
+        Ok(PreAnalysis {
+            chunks: vec![LLMContextChunk {
+                content,
+                context_type: ContextType::Documentation,
+                file_path: diff.file_path.clone(),
+                line_range: None,
+            }],
+            findings,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn describe_all(path: &str, old: &str, new: &str) -> Vec<String> {
+        breaking_changes(Path::new(path), old, new)
+            .iter()
+            .map(describe)
+            .collect()
+    }
+
+    #[test]
+    fn reports_removed_renamed_and_changed_public_functions() {
+        let old = "pub fn total(items: &[Item]) -> u64 { 0 }\npub fn apply(code: &str) {}\npub fn clear() {}\nfn private(a: u8) {}\npub struct Cart;\nimpl Cart {\n    pub fn add(&mut self, item: Item) {}\n    fn helper(&self) {}\n}\n";
+        let new = "pub fn total(items: &[Item]) -> Result<u64> { Ok(0) }\npub fn apply_coupon(code: &str) {}\nfn private(a: u8, b: u8) {}\npub struct Cart;\nimpl Cart {\n    pub fn add(&mut self, item: Item, quantity: u32) {}\n    fn helper(&self, x: u8) {}\n}\n";
+        assert_eq!(
+            describe_all("src/cart.rs", old, new),
+            vec![
+                "Return type of public function `total` changed from `u64` to `Result<u64>`; callers that use the result may break.",
+                "Public function `apply` was renamed to `apply_coupon`; existing callers of `apply` will break.",
+                "Public function `clear` was removed; callers outside this change will break.",
+                "Parameters of public method `Cart::add` changed from `(&mut self, item: Item)` to `(&mut self, item: Item, quantity: u32)`; existing callers will break.",
+            ]
+        );
+    }
+
+    #[test]
+    fn optional_parameters_are_compatible_across_languages() {
+        assert!(describe_all(
+            "app.py",
+            "def fetch(url):\n    pass\n",
+            "def fetch(url, timeout=30, *, retries=3):\n    pass\n"
+        )
+        .is_empty());
+        assert_eq!(
+            describe_all(
+                "app.py",
+                "class Client:\n    def get(self, key):\n        pass\n",
+                "class Client:\n    def get(self, key, default):\n        pass\n"
+            ),
+            vec!["Parameters of public method `Client.get` changed from `(self, key)` to `(self, key, default)`; existing callers will break."]
+        );
+        assert_eq!(
+            describe_all(
+                "api.ts",
+                "export function load(id: string): Item { }\nexport const save = (item: Item): void => {};\n",
+                "export function load(id: string, cache?: boolean): Promise<Item> { }\n"
+            ),
+            vec![
+                "Return type of public function `load` changed from `Item` to `Promise<Item>`; callers that use the result may break.",
+                "Public function `save` was removed; callers outside this change will break.",
+            ]
+        );
+        assert_eq!(
+            describe_all(
+                "server.go",
+                "package api\n\nfunc (s *Server) Start(port int) error { return nil }\nfunc helper() {}\n",
+                "package api\n\nfunc (s *Server) Start(ctx context.Context, port int) error { return nil }\n"
+            ),
+            vec!["Parameters of public method `Server.Start` changed from `(port int)` to `(ctx context.Context, port int)`; existing callers will break."]
+        );
+    }
+
+    #[test]
+    fn pairs_repeated_names_and_ignores_parameter_names() {
+        // Renamed and `mut` parameters, and a type split across `impl` blocks.
+        assert!(describe_all(
+            "src/cart.rs",
+            "pub struct Cart;\nimpl Cart {\n    pub fn new(items: Vec<Item>) -> Self { Cart }\n}\nimpl Cart {\n    pub fn add(&mut self, item: Item) {}\n}\n",
+            "pub struct Cart;\nimpl Cart {\n    pub fn new(mut list: Vec<Item>) -> Self { Cart }\n}\nimpl Cart {\n    pub fn add(&mut self, entry: Item) {}\n}\n"
+        )
+        .is_empty());
+        assert!(describe_all(
+            "server.go",
+            "package api\n\nfunc Copy(src string, dst string) error { return nil }\n",
+            "package api\n\nfunc Copy(from, to string) error { return nil }\n"
+        )
+        .is_empty());
+        assert!(describe_all(
+            "model.py",
+            "class User:\n    @property\n    def name(self):\n        pass\n    @name.setter\n    def name(self, value):\n        pass\n",
+            "class User:\n    @name.setter\n    def name(self, value):\n        pass\n    @property\n    def name(self):\n        pass\n"
+        )
+        .is_empty());
+        assert_eq!(
+            describe_all(
+                "parse.ts",
+                "export function parse(input: string): Doc;\nexport function parse(input: Buffer): Doc;\nexport function parse(input: any): Doc { }\n",
+                "export function parse(text: string): Doc;\nexport function parse(input: any): Doc { }\n"
+            ),
+            vec!["Public function `parse` was removed; callers outside this change will break."]
+        );
+    }
+}
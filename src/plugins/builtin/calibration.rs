@@ -0,0 +1,216 @@
+use crate::config::PluginSettings;
+use crate::core::comment::Severity;
+use crate::core::Comment;
+use crate::plugins::PostProcessor;
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CalibrationSettings {
+    /// Comments kept per file, highest severity and confidence first.
+    #[serde(default = "default_max_per_file")]
+    max_per_file: usize,
+
+    /// Comments kept per category across the whole review.
+    #[serde(default = "default_max_per_category")]
+    max_per_category: usize,
+
+    /// Per-category overrides of `max_per_category`, e.g. `style: 5`.
+    #[serde(default)]
+    category_caps: HashMap<String, usize>,
+
+    /// Categories whose comments beyond the first `demote_after` in a file become suggestions.
+    #[serde(default = "default_demote_categories")]
+    demote_categories: Vec<String>,
+
+    #[serde(default = "default_demote_after")]
+    demote_after: usize,
+
+    /// Categories that are never capped or demoted.
+    #[serde(default = "default_protected_categories")]
+    protected_categories: Vec<String>,
+}
+
+fn default_max_per_file() -> usize {
+    15
+}
+
+fn default_max_per_category() -> usize {
+    30
+}
+
+fn default_demote_categories() -> Vec<String> {
+    vec!["style".to_string(), "best_practice".to_string()]
+}
+
+fn default_demote_after() -> usize {
+    3
+}
+
+fn default_protected_categories() -> Vec<String> {
+    vec!["security".to_string(), "bug".to_string()]
+}
+
+/// `BestPractice` -> `bestpractice`, `best_practice` -> `bestpractice`.
+fn category_key(value: &str) -> String {
+    value.replace(['_', ' '], "").to_lowercase()
+}
+
+fn severity_rank(severity: &Severity) -> u8 {
+    match severity {
+        Severity::Error => 3,
+        Severity::Warning => 2,
+        Severity::Info => 1,
+        Severity::Suggestion => 0,
+    }
+}
+
+/// Keeps large reviews readable: caps comments per file and per category and demotes
+/// repeated style-level findings, while Security and Bug comments always pass through.
+pub struct CommentCalibrator {
+    settings: CalibrationSettings,
+}
+
+impl CommentCalibrator {
+    pub fn new(settings: &PluginSettings) -> Result<Self> {
+        Ok(Self {
+            settings: super::parse_settings("calibration", settings)?,
+        })
+    }
+
+    fn matches(categories: &[String], category: &str) -> bool {
+        categories
+            .iter()
+            .any(|candidate| category_key(candidate) == category)
+    }
+
+    fn category_cap(&self, category: &str) -> usize {
+        self.settings
+            .category_caps
+            .iter()
+            .find(|(name, _)| category_key(name) == category)
+            .map(|(_, cap)| *cap)
+            .unwrap_or(self.settings.max_per_category)
+    }
+}
+
+#[async_trait]
+impl PostProcessor for CommentCalibrator {
+    fn id(&self) -> &str {
+        "calibration"
+    }
+
+    fn settings(&self) -> serde_json::Value {
+        serde_json::to_value(&self.settings).unwrap_or_default()
+    }
+
+    async fn run(&self, mut comments: Vec<Comment>, _repo_path: &str) -> Result<Vec<Comment>> {
+        let categories: Vec<String> = comments
+            .iter()
+            .map(|comment| category_key(&format!("{:?}", comment.category)))
+            .collect();
+
+        // Demote repeats in their original order, so the first few keep their severity.
+        let mut repeats: HashMap<(PathBuf, &str), usize> = HashMap::new();
+        for (comment, category) in comments.iter_mut().zip(&categories) {
+            if !Self::matches(&self.settings.demote_categories, category)
+                || Self::matches(&self.settings.protected_categories, category)
+            {
+                continue;
+            }
+            let seen = repeats
+                .entry((comment.file_path.clone(), category.as_str()))
+                .or_default();
+            *seen += 1;
+            if *seen > self.settings.demote_after {
+                comment.severity = Severity::Suggestion;
+            }
+        }
+
+        // Caps keep the most important comments, then the original order is restored.
+        let mut ranked: Vec<usize> = (0..comments.len()).collect();
+        ranked.sort_by(|&a, &b| {
+            severity_rank(&comments[b].severity)
+                .cmp(&severity_rank(&comments[a].severity))
+                .then(comments[b].confidence.total_cmp(&comments[a].confidence))
+        });
+        let mut per_file: HashMap<PathBuf, usize> = HashMap::new();
+        let mut per_category: HashMap<&str, usize> = HashMap::new();
+        let mut keep = vec![false; comments.len()];
+        for index in ranked {
+            let category = categories[index].as_str();
+            if Self::matches(&self.settings.protected_categories, category) {
+                keep[index] = true;
+                continue;
+            }
+            let file_count = per_file
+                .entry(comments[index].file_path.clone())
+                .or_default();
+            let category_count = per_category.entry(category).or_default();
+            if *file_count < self.settings.max_per_file
+                && *category_count < self.category_cap(category)
+            {
+                *file_count += 1;
+                *category_count += 1;
+                keep[index] = true;
+            }
+        }
+
+        let mut keep = keep.into_iter();
+        comments.retain(|_| keep.next().unwrap_or(false));
+        Ok(comments)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::comment::{Category, FixEffort};
+
+    fn comment(line: usize, category: Category, severity: Severity) -> Comment {
+        Comment {
+            id: format!("cmt_{}", line),
+            file_path: "src/lib.rs".into(),
+            line_number: line,
+            content: format!("Finding {}", line),
+            severity,
+            category,
+            suggestion: None,
+            confidence: 0.8,
+            code_suggestion: None,
+            tags: Vec::new(),
+            fix_effort: FixEffort::Low,
+            owners: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn caps_and_demotes_but_keeps_security_and_bugs() {
+        let mut comments: Vec<Comment> = (1..=5)
+            .map(|line| comment(line, Category::Style, Severity::Warning))
+            .collect();
+        comments.push(comment(6, Category::Performance, Severity::Error));
+        comments.push(comment(7, Category::Security, Severity::Error));
+        comments.push(comment(8, Category::Bug, Severity::Warning));
+        let settings: PluginSettings = serde_json::from_str(
+            r#"{"max_per_file": 3, "category_caps": {"style": 2}, "demote_after": 1}"#,
+        )
+        .unwrap();
+
+        let kept = CommentCalibrator::new(&settings)
+            .unwrap()
+            .run(comments, ".")
+            .await
+            .unwrap();
+
+        let lines: Vec<usize> = kept.iter().map(|c| c.line_number).collect();
+        // The first style comment keeps its warning and outranks the demoted ones.
+        assert_eq!(lines, vec![1, 2, 6, 7, 8]);
+        assert_eq!(kept[0].severity, Severity::Warning);
+        assert_eq!(kept[1].severity, Severity::Suggestion);
+    }
+}
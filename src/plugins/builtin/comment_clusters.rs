@@ -0,0 +1,200 @@
+use crate::config::PluginSettings;
+use crate::core::comment::Severity;
+use crate::core::similarity::{jaccard, words};
+use crate::core::Comment;
+use crate::plugins::PostProcessor;
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CommentClustersSettings {
+    /// Jaccard similarity of word shingles at which two comments are the same finding.
+    #[serde(default = "default_threshold")]
+    threshold: f64,
+
+    /// Words per shingle.
+    #[serde(default = "default_shingle_size")]
+    shingle_size: usize,
+}
+
+fn default_threshold() -> f64 {
+    0.7
+}
+
+fn default_shingle_size() -> usize {
+    3
+}
+
+/// Merges near-identical comments (same category, similar wording) into one comment on the
+/// first location that lists every other affected location.
+pub struct CommentClusterer {
+    settings: CommentClustersSettings,
+}
+
+impl CommentClusterer {
+    pub fn new(settings: &PluginSettings) -> Result<Self> {
+        let settings: CommentClustersSettings =
+            super::parse_settings("comment_clusters", settings)?;
+        if !(0.0..=1.0).contains(&settings.threshold) || settings.shingle_size == 0 {
+            anyhow::bail!(
+                "comment_clusters needs a threshold between 0 and 1 and a positive shingle_size"
+            );
+        }
+        Ok(Self { settings })
+    }
+
+    fn shingles(&self, text: &str) -> HashSet<String> {
+        let words = words(text);
+        if words.len() <= self.settings.shingle_size {
+            return HashSet::from([words.join(" ")]);
+        }
+        words
+            .windows(self.settings.shingle_size)
+            .map(|window| window.join(" "))
+            .collect()
+    }
+}
+
+fn severity_rank(severity: &Severity) -> u8 {
+    match severity {
+        Severity::Error => 3,
+        Severity::Warning => 2,
+        Severity::Info => 1,
+        Severity::Suggestion => 0,
+    }
+}
+
+struct Cluster {
+    shingles: HashSet<String>,
+    members: Vec<Comment>,
+}
+
+#[async_trait]
+impl PostProcessor for CommentClusterer {
+    fn id(&self) -> &str {
+        "comment_clusters"
+    }
+
+    fn settings(&self) -> serde_json::Value {
+        serde_json::to_value(&self.settings).unwrap_or_default()
+    }
+
+    async fn run(&self, comments: Vec<Comment>, _repo_path: &str) -> Result<Vec<Comment>> {
+        let mut clusters: Vec<Cluster> = Vec::new();
+        for comment in comments {
+            let shingles = self.shingles(&comment.content);
+            let existing = clusters.iter_mut().find(|cluster| {
+                cluster.members[0].category == comment.category
+                    && jaccard(&cluster.shingles, &shingles) >= self.settings.threshold
+            });
+            match existing {
+                Some(cluster) => cluster.members.push(comment),
+                None => clusters.push(Cluster {
+                    shingles,
+                    members: vec![comment],
+                }),
+            }
+        }
+
+        Ok(clusters
+            .into_iter()
+            .map(|cluster| {
+                let mut members = cluster.members.into_iter();
+                let mut representative = members.next().expect("clusters are never empty");
+                let mut locations =
+                    vec![(representative.file_path.clone(), representative.line_number)];
+                for member in members {
+                    let location = (member.file_path.clone(), member.line_number);
+                    if !locations.contains(&location) {
+                        locations.push(location);
+                    }
+                    if severity_rank(&member.severity) > severity_rank(&representative.severity) {
+                        representative.severity = member.severity;
+                    }
+                    representative.confidence = representative.confidence.max(member.confidence);
+                }
+                if locations.len() > 1 {
+                    let others = locations[1..]
+                        .iter()
+                        .map(|(path, line)| format!("`{}:{}`", path.display(), line))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    representative.content = format!(
+                        "{}\n\nAlso applies to: {}",
+                        representative.content.trim_end(),
+                        others
+                    );
+                    representative
+                        .tags
+                        .push(format!("cluster:{}", locations.len()));
+                }
+                representative
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::comment::{Category, FixEffort};
+
+    fn comment(path: &str, line: usize, content: &str, severity: Severity) -> Comment {
+        Comment {
+            id: format!("{}:{}", path, line),
+            file_path: path.into(),
+            line_number: line,
+            content: content.to_string(),
+            severity,
+            category: Category::Bug,
+            suggestion: None,
+            confidence: 0.7,
+            code_suggestion: None,
+            tags: Vec::new(),
+            fix_effort: FixEffort::Low,
+            owners: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn merges_near_duplicates_across_files() {
+        let comments = vec![
+            comment(
+                "src/a.rs",
+                10,
+                "Calling unwrap() on the result of parse will panic on invalid input.",
+                Severity::Warning,
+            ),
+            comment(
+                "src/b.rs",
+                42,
+                "Calling unwrap() on the result of parse will panic on invalid input here.",
+                Severity::Error,
+            ),
+            comment(
+                "src/c.rs",
+                7,
+                "This loop allocates a new String on every iteration.",
+                Severity::Info,
+            ),
+        ];
+
+        let clustered = CommentClusterer::new(&PluginSettings::new())
+            .unwrap()
+            .run(comments, ".")
+            .await
+            .unwrap();
+
+        assert_eq!(clustered.len(), 2);
+        assert_eq!(clustered[0].file_path.to_str(), Some("src/a.rs"));
+        assert!(clustered[0]
+            .content
+            .ends_with("\n\nAlso applies to: `src/b.rs:42`"));
+        assert_eq!(clustered[0].severity, Severity::Error);
+        assert_eq!(clustered[0].tags, vec!["cluster:2"]);
+        assert!(clustered[1].tags.is_empty());
+    }
+}
@@ -0,0 +1,436 @@
+use crate::config::PluginSettings;
+use crate::core::comment::{compute_comment_id, Category, FixEffort, Severity};
+use crate::core::diff_parser::ChangeType;
+use crate::core::{Comment, LLMContextChunk, UnifiedDiff};
+use crate::plugins::{PreAnalysis, PreAnalyzer};
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use tree_sitter::{Node, Parser};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ComplexitySettings {
+    /// Cyclomatic complexity above which a touched function is reported.
+    #[serde(default = "default_max_complexity")]
+    max_complexity: usize,
+
+    /// Function length (in lines) above which a touched function is reported.
+    #[serde(default = "default_max_lines")]
+    max_lines: usize,
+}
+
+fn default_max_complexity() -> usize {
+    10
+}
+
+fn default_max_lines() -> usize {
+    60
+}
+
+/// Node kinds for one tree-sitter grammar.
+struct Grammar {
+    language: tree_sitter::Language,
+    functions: &'static [&'static str],
+    /// Nodes that add a path through the function.
+    decisions: &'static [&'static str],
+    /// Binary expressions count when their operator short-circuits.
+    binary: &'static str,
+}
+
+fn grammar(path: &Path) -> Option<Grammar> {
+    let language = crate::core::syntax::language_for(path)?;
+    let ext = path.extension()?.to_str()?.to_lowercase();
+    let js_functions: &[&str] = &[
+        "function_declaration",
+        "function_expression",
+        "generator_function_declaration",
+        "method_definition",
+        "arrow_function",
+    ];
+    let js_decisions: &[&str] = &[
+        "if_statement",
+        "for_statement",
+        "for_in_statement",
+        "while_statement",
+        "do_statement",
+        "switch_case",
+        "catch_clause",
+        "ternary_expression",
+    ];
+    let grammar = match ext.as_str() {
+        "rs" => Grammar {
+            language,
+            functions: &["function_item"],
+            decisions: &[
+                "if_expression",
+                "while_expression",
+                "for_expression",
+                "match_arm",
+            ],
+            binary: "binary_expression",
+        },
+        "py" => Grammar {
+            language,
+            functions: &["function_definition"],
+            decisions: &[
+                "if_statement",
+                "elif_clause",
+                "for_statement",
+                "while_statement",
+                "except_clause",
+                "conditional_expression",
+                "case_clause",
+                "boolean_operator",
+            ],
+            binary: "",
+        },
+        "go" => Grammar {
+            language,
+            functions: &["function_declaration", "method_declaration", "func_literal"],
+            decisions: &[
+                "if_statement",
+                "for_statement",
+                "expression_case",
+                "type_case",
+                "communication_case",
+            ],
+            binary: "binary_expression",
+        },
+        "js" | "jsx" | "mjs" | "cjs" => Grammar {
+            language,
+            functions: js_functions,
+            decisions: js_decisions,
+            binary: "binary_expression",
+        },
+        "ts" | "mts" | "cts" => Grammar {
+            language,
+            functions: js_functions,
+            decisions: js_decisions,
+            binary: "binary_expression",
+        },
+        "tsx" => Grammar {
+            language,
+            functions: js_functions,
+            decisions: js_decisions,
+            binary: "binary_expression",
+        },
+        _ => return None,
+    };
+    Some(grammar)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct FunctionMetrics {
+    name: String,
+    /// 1-based inclusive line range.
+    start_line: usize,
+    end_line: usize,
+    complexity: usize,
+}
+
+impl FunctionMetrics {
+    fn lines(&self) -> usize {
+        self.end_line - self.start_line + 1
+    }
+}
+
+fn measure(grammar: &Grammar, source: &str) -> Vec<FunctionMetrics> {
+    let mut parser = Parser::new();
+    if parser.set_language(&grammar.language).is_err() {
+        return Vec::new();
+    }
+    let Some(tree) = parser.parse(source, None) else {
+        return Vec::new();
+    };
+
+    let mut functions = Vec::new();
+    collect_functions(grammar, tree.root_node(), source.as_bytes(), &mut functions);
+    functions
+}
+
+fn collect_functions(grammar: &Grammar, node: Node, source: &[u8], out: &mut Vec<FunctionMetrics>) {
+    if grammar.functions.contains(&node.kind()) {
+        out.push(FunctionMetrics {
+            name: function_name(node, source),
+            start_line: node.start_position().row + 1,
+            end_line: node.end_position().row + 1,
+            complexity: 1 + count_decisions(grammar, node, source),
+        });
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_functions(grammar, child, source, out);
+    }
+}
+
+/// Decision points in `function`, excluding nested functions (they are measured on their own).
+fn count_decisions(grammar: &Grammar, function: Node, source: &[u8]) -> usize {
+    let mut count = 0;
+    let mut cursor = function.walk();
+    for child in function.children(&mut cursor) {
+        if grammar.functions.contains(&child.kind()) {
+            continue;
+        }
+        if grammar.decisions.contains(&child.kind()) {
+            count += 1;
+        } else if child.kind() == grammar.binary {
+            let operator = child
+                .child_by_field_name("operator")
+                .and_then(|op| op.utf8_text(source).ok());
+            if matches!(operator, Some("&&" | "||" | "??")) {
+                count += 1;
+            }
+        }
+        count += count_decisions(grammar, child, source);
+    }
+    count
+}
+
+/// The function's own name, or the name it is assigned to (`const f = () => ...`).
+fn function_name(node: Node, source: &[u8]) -> String {
+    node.child_by_field_name("name")
+        .or_else(|| node.parent()?.child_by_field_name("name"))
+        .and_then(|name| name.utf8_text(source).ok())
+        .unwrap_or("<anonymous>")
+        .to_string()
+}
+
+/// Rebuilds the old file by reverse-applying the diff's hunks to the new file.
+pub(super) fn reconstruct_old(diff: &UnifiedDiff, new_source: &str) -> String {
+    let new_lines: Vec<&str> = new_source.lines().collect();
+    let mut old_lines: Vec<&str> = Vec::new();
+    let mut next = 0;
+    for hunk in &diff.hunks {
+        let hunk_start = hunk.new_start.saturating_sub(1).min(new_lines.len());
+        if hunk_start > next {
+            old_lines.extend(&new_lines[next..hunk_start]);
+            next = hunk_start;
+        }
+        for line in &hunk.changes {
+            match line.change_type {
+                ChangeType::Context => {
+                    old_lines.push(new_lines.get(next).copied().unwrap_or(&line.content));
+                    next += 1;
+                }
+                ChangeType::Added => next += 1,
+                ChangeType::Removed => old_lines.push(&line.content),
+            }
+        }
+    }
+    if next < new_lines.len() {
+        old_lines.extend(&new_lines[next..]);
+    }
+    old_lines.join("\n")
+}
+
+/// Change in the summed cyclomatic complexity of the file's functions, or `None` when there
+/// is no grammar for the file.
+pub fn complexity_delta(diff: &UnifiedDiff, new_source: &str) -> Option<i64> {
+    let grammar = grammar(&diff.file_path)?;
+    let total = |source: &str| -> i64 {
+        measure(&grammar, source)
+            .iter()
+            .map(|function| function.complexity as i64)
+            .sum()
+    };
+    Some(total(new_source) - total(&reconstruct_old(diff, new_source)))
+}
+
+/// Reports functions touched by the diff whose cyclomatic complexity or length crosses the
+/// configured thresholds, comparing against the same function before the change.
+pub struct ComplexityAnalyzer {
+    settings: ComplexitySettings,
+}
+
+impl ComplexityAnalyzer {
+    pub fn new(settings: &PluginSettings) -> Result<Self> {
+        Ok(Self {
+            settings: super::parse_settings("complexity", settings)?,
+        })
+    }
+
+    fn findings(&self, diff: &UnifiedDiff, grammar: &Grammar, new_source: &str) -> Vec<Comment> {
+        let added = super::added_lines(diff);
+        if added.is_empty() {
+            return Vec::new();
+        }
+
+        let old_source = match (&diff.old_content, diff.is_new) {
+            (_, true) => String::new(),
+            (Some(old), _) => old.clone(),
+            (None, _) => reconstruct_old(diff, new_source),
+        };
+        let mut before: HashMap<String, Vec<FunctionMetrics>> = HashMap::new();
+        for function in measure(grammar, &old_source).into_iter().rev() {
+            before
+                .entry(function.name.clone())
+                .or_default()
+                .push(function);
+        }
+
+        let mut comments = Vec::new();
+        for function in measure(grammar, new_source) {
+            let previous = before.get_mut(&function.name).and_then(|list| list.pop());
+            let Some(line) =
+                (function.start_line..=function.end_line).find(|line| added.contains(line))
+            else {
+                continue;
+            };
+            if let Some(comment) = self.to_comment(diff, &function, previous.as_ref(), line) {
+                comments.push(comment);
+            }
+        }
+        comments
+    }
+
+    fn to_comment(
+        &self,
+        diff: &UnifiedDiff,
+        function: &FunctionMetrics,
+        previous: Option<&FunctionMetrics>,
+        line: usize,
+    ) -> Option<Comment> {
+        let old_complexity = previous.map_or(0, |old| old.complexity);
+        let old_lines = previous.map_or(0, |old| old.lines());
+        let crossed = |new: usize, old: usize, max: usize| new > max && old <= max;
+
+        let mut problems = Vec::new();
+        if crossed(
+            function.complexity,
+            old_complexity,
+            self.settings.max_complexity,
+        ) {
+            problems.push(format!(
+                "cyclomatic complexity is now {} (limit {})",
+                function.complexity, self.settings.max_complexity
+            ));
+        }
+        if crossed(function.lines(), old_lines, self.settings.max_lines) {
+            problems.push(format!(
+                "it is now {} lines long (limit {})",
+                function.lines(),
+                self.settings.max_lines
+            ));
+        }
+        if problems.is_empty() {
+            return None;
+        }
+
+        let content = format!(
+            "This change pushes `{}` past the maintainability thresholds: {}.",
+            function.name,
+            problems.join("; ")
+        );
+        let delta = |new: usize, old: usize| new as i64 - old as i64;
+        Some(Comment {
+            id: compute_comment_id(&diff.file_path, &content, &Category::Maintainability),
+            file_path: diff.file_path.clone(),
+            line_number: line,
+            content,
+            severity: Severity::Info,
+            category: Category::Maintainability,
+            suggestion: Some(
+                "Extract helpers or split the function so each part handles one concern."
+                    .to_string(),
+            ),
+            confidence: 1.0,
+            code_suggestion: None,
+            tags: vec![
+                "complexity".to_string(),
+                format!("cyclomatic:{}", function.complexity),
+                format!(
+                    "cyclomatic_delta:{:+}",
+                    delta(function.complexity, old_complexity)
+                ),
+                format!("lines:{}", function.lines()),
+                format!("lines_delta:{:+}", delta(function.lines(), old_lines)),
+            ],
+            fix_effort: FixEffort::Medium,
+            owners: Vec::new(),
+        })
+    }
+}
+
+#[async_trait]
+impl PreAnalyzer for ComplexityAnalyzer {
+    fn id(&self) -> &str {
+        "complexity"
+    }
+
+    fn settings(&self) -> serde_json::Value {
+        serde_json::to_value(&self.settings).unwrap_or_default()
+    }
+
+    async fn run(&self, diff: &UnifiedDiff, repo_path: &str) -> Result<Vec<LLMContextChunk>> {
+        Ok(self.analyze(diff, repo_path).await?.chunks)
+    }
+
+    async fn analyze(&self, diff: &UnifiedDiff, repo_path: &str) -> Result<PreAnalysis> {
+        if diff.is_deleted || diff.is_binary {
+            return Ok(PreAnalysis::default());
+        }
+        let Some(grammar) = grammar(&diff.file_path) else {
+            return Ok(PreAnalysis::default());
+        };
+        let new_source = match &diff.new_content {
+            Some(content) => content.clone(),
+            None => {
+                match tokio::fs::read_to_string(Path::new(repo_path).join(&diff.file_path)).await {
+                    Ok(content) => content,
+                    Err(_) => return Ok(PreAnalysis::default()),
+                }
+            }
+        };
+
+        Ok(PreAnalysis {
+            chunks: Vec::new(),
+            findings: self.findings(diff, &grammar, &new_source),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_touched_functions_that_cross_thresholds() {
+        let new_source = "fn check(x: i32) -> bool {\n    if x > 0 {\n        return true;\n    }\n    if x < -10 && x != -20 {\n        return true;\n    }\n    false\n}\n";
+        let diff = crate::core::DiffParser::parse_unified_diff(
+            "--- a/src/check.rs\n+++ b/src/check.rs\n@@ -1,6 +1,9 @@\n fn check(x: i32) -> bool {\n     if x > 0 {\n         return true;\n     }\n+    if x < -10 && x != -20 {\n+        return true;\n+    }\n     false\n }\n",
+        )
+        .unwrap()
+        .remove(0);
+        assert_eq!(
+            reconstruct_old(&diff, new_source),
+            "fn check(x: i32) -> bool {\n    if x > 0 {\n        return true;\n    }\n    false\n}"
+        );
+
+        let settings: PluginSettings = serde_json::from_str(r#"{"max_complexity": 3}"#).unwrap();
+        let analyzer = ComplexityAnalyzer::new(&settings).unwrap();
+        let grammar = grammar(&diff.file_path).unwrap();
+        let findings = analyzer.findings(&diff, &grammar, new_source);
+
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].line_number, 5);
+        assert_eq!(findings[0].category, Category::Maintainability);
+        assert_eq!(
+            findings[0].tags,
+            vec![
+                "complexity",
+                "cyclomatic:4",
+                "cyclomatic_delta:+2",
+                "lines:9",
+                "lines_delta:+3"
+            ]
+        );
+
+        // Already past the limit before the change: nothing new to report.
+        let settings: PluginSettings = serde_json::from_str(r#"{"max_complexity": 1}"#).unwrap();
+        let analyzer = ComplexityAnalyzer::new(&settings).unwrap();
+        assert!(analyzer.findings(&diff, &grammar, new_source).is_empty());
+    }
+}
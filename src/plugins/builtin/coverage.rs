@@ -0,0 +1,103 @@
+use crate::config::PluginSettings;
+use crate::core::comment::{compute_comment_id, Category, FixEffort, Severity};
+use crate::core::coverage::{line_runs, CoverageReport};
+use crate::core::{Comment, LLMContextChunk, UnifiedDiff};
+use crate::plugins::{PreAnalysis, PreAnalyzer};
+use anyhow::Result;
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// Marks added lines that the supplied coverage report shows no test executing.
+pub struct CoverageAnalyzer {
+    report: Arc<CoverageReport>,
+}
+
+impl CoverageAnalyzer {
+    pub fn new(report: Arc<CoverageReport>, settings: &PluginSettings) -> Result<Self> {
+        if !settings.is_empty() {
+            anyhow::bail!("Plugin coverage does not take settings");
+        }
+        Ok(Self { report })
+    }
+}
+
+fn to_comment(diff: &UnifiedDiff, (start, end): (usize, usize)) -> Comment {
+    let content = if start == end {
+        format!("Added line {} is not covered by any test.", start)
+    } else {
+        format!("Added lines {}-{} are not covered by any test.", start, end)
+    };
+    Comment {
+        id: compute_comment_id(&diff.file_path, &content, &Category::Testing),
+        file_path: diff.file_path.clone(),
+        line_number: start,
+        content,
+        severity: Severity::Info,
+        category: Category::Testing,
+        suggestion: Some("Add a test that exercises this code path.".to_string()),
+        confidence: 1.0,
+        code_suggestion: None,
+        tags: vec!["coverage".to_string(), "uncovered".to_string()],
+        fix_effort: FixEffort::Medium,
+        owners: Vec::new(),
+    }
+}
+
+#[async_trait]
+impl PreAnalyzer for CoverageAnalyzer {
+    fn id(&self) -> &str {
+        "coverage"
+    }
+
+    async fn run(&self, diff: &UnifiedDiff, repo_path: &str) -> Result<Vec<LLMContextChunk>> {
+        Ok(self.analyze(diff, repo_path).await?.chunks)
+    }
+
+    async fn analyze(&self, diff: &UnifiedDiff, _repo_path: &str) -> Result<PreAnalysis> {
+        let Some(coverage) = self.report.patch_coverage(diff) else {
+            return Ok(PreAnalysis::default());
+        };
+        Ok(PreAnalysis {
+            chunks: Vec::new(),
+            findings: line_runs(&coverage.uncovered)
+                .into_iter()
+                .map(|run| to_comment(diff, run))
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn reports_runs_of_uncovered_added_lines() {
+        let report = CoverageReport::parse("SF:src/app.py\nDA:2,0\nDA:3,0\nDA:4,2\nDA:6,0\n");
+        let diff = crate::core::DiffParser::parse_unified_diff(
+            "--- a/src/app.py\n+++ b/src/app.py\n@@ -1,1 +1,6 @@\n import os\n+def f(x):\n+    if x:\n+        return 1\n+\n+    return 0\n",
+        )
+        .unwrap()
+        .remove(0);
+
+        let analysis = CoverageAnalyzer::new(Arc::new(report), &PluginSettings::new())
+            .unwrap()
+            .analyze(&diff, ".")
+            .await
+            .unwrap();
+
+        let contents: Vec<&str> = analysis
+            .findings
+            .iter()
+            .map(|comment| comment.content.as_str())
+            .collect();
+        assert_eq!(
+            contents,
+            vec![
+                "Added lines 2-3 are not covered by any test.",
+                "Added line 6 is not covered by any test."
+            ]
+        );
+        assert_eq!(analysis.findings[0].category, Category::Testing);
+    }
+}
@@ -0,0 +1,373 @@
+use crate::config::PluginSettings;
+use crate::core::comment::{compute_comment_id, Category, FixEffort, Severity};
+use crate::core::diff_parser::ChangeType;
+use crate::core::{Comment, LLMContextChunk, UnifiedDiff};
+use crate::plugins::{PreAnalysis, PreAnalyzer};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashSet;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DependencyAuditSettings {
+    /// OSV API base URL; point it at a mirror for air-gapped setups.
+    #[serde(default = "default_osv_url")]
+    osv_url: String,
+
+    #[serde(default = "default_timeout_secs")]
+    timeout_secs: u64,
+}
+
+fn default_osv_url() -> String {
+    "https://api.osv.dev".to_string()
+}
+
+fn default_timeout_secs() -> u64 {
+    20
+}
+
+static TOML_NAME: Lazy<Regex> = Lazy::new(|| Regex::new(r#"^name = "([^"]+)""#).unwrap());
+static TOML_VERSION: Lazy<Regex> = Lazy::new(|| Regex::new(r#"^version = "([^"]+)""#).unwrap());
+static NPM_PACKAGE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"^\s*"(?:.*/)?node_modules/([^"]+)": \{"#).unwrap());
+static NPM_VERSION: Lazy<Regex> = Lazy::new(|| Regex::new(r#"^\s*"version": "([^"]+)""#).unwrap());
+
+/// A dependency version introduced by the diff, at the line that introduces it.
+#[derive(Debug, Clone, PartialEq)]
+struct AddedDependency {
+    ecosystem: &'static str,
+    name: String,
+    version: String,
+    line: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct OsvBatchResponse {
+    results: Vec<OsvBatchResult>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct OsvBatchResult {
+    #[serde(default)]
+    vulns: Vec<OsvVulnId>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OsvVulnId {
+    id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct OsvVuln {
+    id: String,
+    #[serde(default)]
+    summary: Option<String>,
+    #[serde(default)]
+    aliases: Vec<String>,
+    #[serde(default)]
+    database_specific: Option<serde_json::Value>,
+}
+
+impl OsvVuln {
+    /// CVE alias when there is one, since that is the identifier most people search for.
+    fn display_id(&self) -> &str {
+        self.aliases
+            .iter()
+            .find(|alias| alias.starts_with("CVE-"))
+            .unwrap_or(&self.id)
+    }
+
+    fn is_severe(&self) -> bool {
+        self.database_specific
+            .as_ref()
+            .and_then(|specific| specific.get("severity"))
+            .and_then(|severity| severity.as_str())
+            .is_some_and(|severity| matches!(severity, "CRITICAL" | "HIGH"))
+    }
+}
+
+/// Looks up dependency versions added to lockfiles in the OSV database and reports known
+/// vulnerabilities as Security findings.
+pub struct DependencyAuditAnalyzer {
+    settings: DependencyAuditSettings,
+    client: Client,
+}
+
+impl DependencyAuditAnalyzer {
+    pub fn new(settings: &PluginSettings) -> Result<Self> {
+        let settings: DependencyAuditSettings =
+            super::parse_settings("dependency_audit", settings)?;
+        let client = Client::builder()
+            .timeout(Duration::from_secs(settings.timeout_secs))
+            .build()?;
+        Ok(Self { settings, client })
+    }
+
+    async fn lookup(&self, dependencies: &[AddedDependency]) -> Result<Vec<Vec<OsvVuln>>> {
+        let queries: Vec<_> = dependencies
+            .iter()
+            .map(|dep| {
+                json!({
+                    "package": { "name": dep.name, "ecosystem": dep.ecosystem },
+                    "version": dep.version,
+                })
+            })
+            .collect();
+        let batch: OsvBatchResponse = self
+            .client
+            .post(format!("{}/v1/querybatch", self.settings.osv_url))
+            .json(&json!({ "queries": queries }))
+            .send()
+            .await?
+            .error_for_status()
+            .context("OSV query failed")?
+            .json()
+            .await?;
+
+        let mut results = Vec::new();
+        for result in batch.results {
+            let mut vulns = Vec::new();
+            for vuln in result.vulns {
+                vulns.push(
+                    self.client
+                        .get(format!("{}/v1/vulns/{}", self.settings.osv_url, vuln.id))
+                        .send()
+                        .await?
+                        .error_for_status()?
+                        .json::<OsvVuln>()
+                        .await?,
+                );
+            }
+            results.push(vulns);
+        }
+        Ok(results)
+    }
+}
+
+/// Extracts dependency versions from added lines of a lockfile diff. Names may come from
+/// context lines, since a version bump only changes the `version` line.
+fn added_dependencies(diff: &UnifiedDiff) -> Vec<AddedDependency> {
+    let file_name = diff
+        .file_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let (ecosystem, name_regex, version_regex): (_, &Regex, &Regex) = match file_name.as_str() {
+        "Cargo.lock" => ("crates.io", &TOML_NAME, &TOML_VERSION),
+        "poetry.lock" => ("PyPI", &TOML_NAME, &TOML_VERSION),
+        "package-lock.json" => ("npm", &NPM_PACKAGE, &NPM_VERSION),
+        "go.sum" => return added_go_modules(diff),
+        _ => return Vec::new(),
+    };
+
+    let mut dependencies = Vec::new();
+    for hunk in &diff.hunks {
+        let mut current_name: Option<String> = None;
+        for line in &hunk.changes {
+            if matches!(line.change_type, ChangeType::Removed) {
+                continue;
+            }
+            if let Some(captures) = name_regex.captures(&line.content) {
+                current_name = Some(captures[1].to_string());
+                continue;
+            }
+            let (Some(name), Some(captures), Some(line_no)) = (
+                current_name.as_ref(),
+                version_regex.captures(&line.content),
+                line.new_line_no,
+            ) else {
+                continue;
+            };
+            if matches!(line.change_type, ChangeType::Added) && !name.is_empty() {
+                dependencies.push(AddedDependency {
+                    ecosystem,
+                    name: name.clone(),
+                    version: captures[1].to_string(),
+                    line: line_no,
+                });
+            }
+        }
+    }
+    dependencies
+}
+
+fn added_go_modules(diff: &UnifiedDiff) -> Vec<AddedDependency> {
+    let mut seen = HashSet::new();
+    let mut modules = Vec::new();
+    for line in diff.hunks.iter().flat_map(|hunk| &hunk.changes) {
+        if !matches!(line.change_type, ChangeType::Added) {
+            continue;
+        }
+        let mut parts = line.content.split_whitespace();
+        let (Some(module), Some(version), Some(line_no)) =
+            (parts.next(), parts.next(), line.new_line_no)
+        else {
+            continue;
+        };
+        let version = version.trim_end_matches("/go.mod");
+        if seen.insert((module.to_string(), version.to_string())) {
+            modules.push(AddedDependency {
+                ecosystem: "Go",
+                name: module.to_string(),
+                version: version.to_string(),
+                line: line_no,
+            });
+        }
+    }
+    modules
+}
+
+fn to_comment(diff: &UnifiedDiff, dependency: &AddedDependency, vulns: &[OsvVuln]) -> Comment {
+    let ids: Vec<&str> = vulns.iter().map(OsvVuln::display_id).collect();
+    let mut content = format!(
+        "{} {} has known vulnerabilities: {}.",
+        dependency.name,
+        dependency.version,
+        ids.join(", ")
+    );
+    if let Some(summary) = vulns.iter().find_map(|vuln| vuln.summary.as_deref()) {
+        content.push_str(&format!(" {}", summary));
+    }
+    let severity = if vulns.iter().any(OsvVuln::is_severe) {
+        Severity::Error
+    } else {
+        Severity::Warning
+    };
+
+    let mut tags = vec!["dependency-audit".to_string()];
+    tags.extend(ids.iter().map(|id| id.to_string()));
+    Comment {
+        id: compute_comment_id(&diff.file_path, &content, &Category::Security),
+        file_path: diff.file_path.clone(),
+        line_number: dependency.line,
+        content,
+        severity,
+        category: Category::Security,
+        suggestion: Some(format!(
+            "Upgrade {} to a version with the fixes, or document why it is not affected.",
+            dependency.name
+        )),
+        confidence: 1.0,
+        code_suggestion: None,
+        tags,
+        fix_effort: FixEffort::Low,
+        owners: Vec::new(),
+    }
+}
+
+#[async_trait]
+impl PreAnalyzer for DependencyAuditAnalyzer {
+    fn id(&self) -> &str {
+        "dependency_audit"
+    }
+
+    fn settings(&self) -> serde_json::Value {
+        serde_json::to_value(&self.settings).unwrap_or_default()
+    }
+
+    fn audits_lockfiles(&self) -> bool {
+        true
+    }
+
+    async fn run(&self, diff: &UnifiedDiff, repo_path: &str) -> Result<Vec<LLMContextChunk>> {
+        Ok(self.analyze(diff, repo_path).await?.chunks)
+    }
+
+    async fn analyze(&self, diff: &UnifiedDiff, _repo_path: &str) -> Result<PreAnalysis> {
+        let dependencies = added_dependencies(diff);
+        if dependencies.is_empty() {
+            return Ok(PreAnalysis::default());
+        }
+
+        let results = self.lookup(&dependencies).await?;
+        Ok(PreAnalysis {
+            chunks: Vec::new(),
+            findings: dependencies
+                .iter()
+                .zip(&results)
+                .filter(|(_, vulns)| !vulns.is_empty())
+                .map(|(dependency, vulns)| to_comment(diff, dependency, vulns))
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(diff: &str) -> UnifiedDiff {
+        crate::core::DiffParser::parse_unified_diff(diff)
+            .unwrap()
+            .remove(0)
+    }
+
+    #[test]
+    fn extracts_added_versions_from_lockfiles() {
+        let cargo = parse(
+            "--- a/Cargo.lock\n+++ b/Cargo.lock\n@@ -10,3 +10,3 @@\n [[package]]\n name = \"time\"\n-version = \"0.1.43\"\n+version = \"0.1.44\"\n",
+        );
+        let npm = parse(
+            "--- a/package-lock.json\n+++ b/package-lock.json\n@@ -5,2 +5,4 @@\n     },\n+    \"node_modules/lodash\": {\n+      \"version\": \"4.17.20\",\n     \"node_modules/zod\": {\n",
+        );
+        let go = parse(
+            "--- a/go.sum\n+++ b/go.sum\n@@ -1 +1,3 @@\n example.com/a v1.0.0 h1:abc=\n+golang.org/x/net v0.7.0 h1:def=\n+golang.org/x/net v0.7.0/go.mod h1:ghi=\n",
+        );
+
+        let summary = |diff: &UnifiedDiff| -> Vec<String> {
+            added_dependencies(diff)
+                .into_iter()
+                .map(|dep| {
+                    format!(
+                        "{}:{}@{}:{}",
+                        dep.ecosystem, dep.name, dep.version, dep.line
+                    )
+                })
+                .collect()
+        };
+        assert_eq!(summary(&cargo), vec!["crates.io:time@0.1.44:12"]);
+        assert_eq!(summary(&npm), vec!["npm:lodash@4.17.20:7"]);
+        assert_eq!(summary(&go), vec!["Go:golang.org/x/net@v0.7.0:2"]);
+    }
+
+    #[tokio::test]
+    async fn reports_osv_vulnerabilities_for_added_versions() {
+        let mut server = mockito::Server::new_async().await;
+        let batch = server
+            .mock("POST", "/v1/querybatch")
+            .match_body(mockito::Matcher::PartialJson(json!({
+                "queries": [{ "package": { "name": "lodash", "ecosystem": "npm" }, "version": "4.17.20" }]
+            })))
+            .with_body(r#"{"results": [{"vulns": [{"id": "GHSA-35jh-r3h4-6jhm", "modified": "2024-01-01T00:00:00Z"}]}]}"#)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/v1/vulns/GHSA-35jh-r3h4-6jhm")
+            .with_body(r#"{"id": "GHSA-35jh-r3h4-6jhm", "summary": "Command Injection in lodash", "aliases": ["CVE-2021-23337"], "database_specific": {"severity": "HIGH"}}"#)
+            .create_async()
+            .await;
+
+        let settings: PluginSettings =
+            serde_json::from_value(json!({ "osv_url": server.url() })).unwrap();
+        let analyzer = DependencyAuditAnalyzer::new(&settings).unwrap();
+        let diff = parse(
+            "--- a/package-lock.json\n+++ b/package-lock.json\n@@ -1,1 +1,3 @@\n {\n+  \"node_modules/lodash\": {\n+    \"version\": \"4.17.20\",\n",
+        );
+
+        let analysis = analyzer.analyze(&diff, ".").await.unwrap();
+        batch.assert_async().await;
+        let finding = &analysis.findings[0];
+        assert_eq!(finding.line_number, 3);
+        assert_eq!(finding.severity, Severity::Error);
+        assert_eq!(
+            finding.content,
+            "lodash 4.17.20 has known vulnerabilities: CVE-2021-23337. Command Injection in lodash"
+        );
+    }
+}
@@ -0,0 +1,93 @@
+use crate::config::PluginSettings;
+use crate::core::dependency_graph::{DEFAULT_MAX_GRAPH_FILES, IMPACT_MAX_DEPTH};
+use crate::core::{ContextType, DependencyGraph, LLMContextChunk, UnifiedDiff};
+use crate::plugins::PreAnalyzer;
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DependencyImpactSettings {
+    /// Impacted modules listed before the rest are summarized as a count.
+    #[serde(default = "default_max_modules")]
+    max_modules: usize,
+
+    #[serde(default = "default_max_depth")]
+    max_depth: usize,
+}
+
+fn default_max_modules() -> usize {
+    20
+}
+
+fn default_max_depth() -> usize {
+    IMPACT_MAX_DEPTH
+}
+
+pub struct DependencyImpactAnalyzer {
+    settings: DependencyImpactSettings,
+}
+
+impl DependencyImpactAnalyzer {
+    pub fn new(settings: &PluginSettings) -> Result<Self> {
+        Ok(Self {
+            settings: super::parse_settings("dependency_impact", settings)?,
+        })
+    }
+}
+
+#[async_trait]
+impl PreAnalyzer for DependencyImpactAnalyzer {
+    fn id(&self) -> &str {
+        "dependency_impact"
+    }
+
+    fn settings(&self) -> serde_json::Value {
+        serde_json::to_value(&self.settings).unwrap_or_default()
+    }
+
+    async fn run(&self, diff: &UnifiedDiff, repo_path: &str) -> Result<Vec<LLMContextChunk>> {
+        let repo_root = Path::new(repo_path).to_path_buf();
+        let graph = tokio::task::spawn_blocking(move || {
+            DependencyGraph::shared(&repo_root, DEFAULT_MAX_GRAPH_FILES)
+        })
+        .await?;
+
+        let impacted = graph.impacted_by(&diff.file_path, self.settings.max_depth);
+        if impacted.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut content = format!(
+            "Modules that depend on {} and may be affected by this change:\n",
+            diff.file_path.display()
+        );
+        for module in impacted.iter().take(self.settings.max_modules) {
+            let relation = if module.depth == 1 {
+                "imports it directly".to_string()
+            } else {
+                format!("transitive, {} hops away", module.depth)
+            };
+            content.push_str(&format!(
+                "- {} ({})\n",
+                module.file_path.display(),
+                relation
+            ));
+        }
+        if impacted.len() > self.settings.max_modules {
+            content.push_str(&format!(
+                "- ... and {} more\n",
+                impacted.len() - self.settings.max_modules
+            ));
+        }
+
+        Ok(vec![LLMContextChunk {
+            file_path: diff.file_path.clone(),
+            content,
+            context_type: ContextType::Reference,
+            line_range: None,
+        }])
+    }
+}
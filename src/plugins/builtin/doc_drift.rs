@@ -0,0 +1,356 @@
+use crate::config::PluginSettings;
+use crate::core::comment::{compute_comment_id, Category, FixEffort, Severity};
+use crate::core::diff_parser::{ChangeType, DiffLine};
+use crate::core::{Comment, ContextType, LLMContextChunk, UnifiedDiff};
+use crate::plugins::{PreAnalysis, PreAnalyzer};
+use anyhow::Result;
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use tokio::sync::OnceCell;
+
+static RUST_PUBLIC: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*pub\s+(?:const\s+)?(?:async\s+)?(?:unsafe\s+)?(?:fn|struct|enum|trait|type)\s+([A-Za-z_]\w*)")
+        .unwrap()
+});
+static JS_EXPORT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*export\s+(?:default\s+)?(?:async\s+)?(?:function\*?|class|interface|type|const|let|enum)\s+([A-Za-z_$][\w$]*)")
+        .unwrap()
+});
+static PYTHON_PUBLIC: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(?:async\s+)?(?:def|class)\s+([A-Za-z]\w*)").unwrap());
+static GO_EXPORTED: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^func\s+(?:\([^)]*\)\s*)?([A-Z]\w*)|^type\s+([A-Z]\w*)").unwrap());
+static JAVA_PUBLIC: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\s*public\s+(?:[\w<>\[\],?]+\s+)*([A-Za-z_]\w*)\s*[({]").unwrap());
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DocDriftSettings {
+    /// Documentation files, relative to the repository root, searched for mentions of changed APIs.
+    #[serde(default = "default_docs")]
+    docs: Vec<String>,
+}
+
+fn default_docs() -> Vec<String> {
+    vec!["README*".to_string(), "docs/**/*.md".to_string()]
+}
+
+/// A public symbol whose declaration line the diff changes.
+#[derive(Debug, PartialEq)]
+struct ApiChange {
+    name: String,
+    /// New-file line of the declaration; `None` when the declaration was removed.
+    line: Option<usize>,
+    /// First new-file line of the hunk, used to anchor findings for removed symbols.
+    hunk_line: usize,
+}
+
+fn public_symbol(path: &Path, line: &str) -> Option<String> {
+    let regex: &Regex = match path.extension()?.to_str()? {
+        "rs" => &RUST_PUBLIC,
+        "ts" | "tsx" | "js" | "jsx" | "mjs" => &JS_EXPORT,
+        "py" => &PYTHON_PUBLIC,
+        "go" => &GO_EXPORTED,
+        "java" | "kt" | "cs" => &JAVA_PUBLIC,
+        _ => return None,
+    };
+    let captures = regex.captures(line)?;
+    Some(captures.get(1).or(captures.get(2))?.as_str().to_string())
+}
+
+/// Public declarations removed or rewritten by the diff. A symbol only added is new API, not drift.
+fn api_changes(diff: &UnifiedDiff) -> Vec<ApiChange> {
+    let mut changes: Vec<ApiChange> = Vec::new();
+    for hunk in &diff.hunks {
+        let removed: Vec<String> = hunk
+            .changes
+            .iter()
+            .filter(|line| matches!(line.change_type, ChangeType::Removed))
+            .filter_map(|line| public_symbol(&diff.file_path, &line.content))
+            .collect();
+        for name in removed {
+            if changes.iter().any(|change| change.name == name) {
+                continue;
+            }
+            let line = hunk
+                .changes
+                .iter()
+                .filter(|line| matches!(line.change_type, ChangeType::Added))
+                .find(|line| {
+                    public_symbol(&diff.file_path, &line.content).as_deref() == Some(&name)
+                })
+                .and_then(|line| line.new_line_no);
+            changes.push(ApiChange {
+                name,
+                line,
+                hunk_line: hunk.new_start.max(1),
+            });
+        }
+    }
+    changes
+}
+
+fn is_doc_line(path: &Path, line: &str) -> bool {
+    let line = line.trim_start();
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("py") => line.starts_with('#'),
+        _ => {
+            line.starts_with("//")
+                || line.starts_with("/*")
+                || line.starts_with('*')
+                || line.starts_with("#[doc")
+        }
+    }
+}
+
+fn is_attribute(line: &str) -> bool {
+    let line = line.trim_start();
+    line.starts_with("#[") || line.starts_with('@')
+}
+
+/// Whether the doc comment attached to the declaration at `line` (1-based) was edited.
+/// Returns `None` when the declaration has no doc comment.
+fn doc_comment_changed(
+    path: &Path,
+    lines: &[&str],
+    line: usize,
+    changes: &[&DiffLine],
+) -> Option<bool> {
+    let edited = |(start, end): (usize, usize)| {
+        changes.iter().any(|change| {
+            matches!(change.change_type, ChangeType::Added)
+                && change
+                    .new_line_no
+                    .is_some_and(|line| line >= start && line <= end)
+        })
+    };
+
+    if path.extension().and_then(|ext| ext.to_str()) == Some("py") {
+        let first = lines.get(line)?.trim_start();
+        if !(first.starts_with("\"\"\"") || first.starts_with("'''")) {
+            return None;
+        }
+        let quote = &first[..3];
+        let end = if first.len() > 3 && first[3..].contains(quote) {
+            line + 1
+        } else {
+            (line + 1..lines.len())
+                .find(|&index| lines[index].contains(quote))
+                .map_or(lines.len(), |index| index + 1)
+        };
+        return Some(edited((line + 1, end)));
+    }
+
+    let mut index = line.checked_sub(1)?;
+    while index > 0 && is_attribute(lines[index - 1]) {
+        index -= 1;
+    }
+    let end = index;
+    while index > 0 && is_doc_line(path, lines[index - 1]) {
+        index -= 1;
+    }
+    (index < end).then(|| edited((index + 1, end)))
+}
+
+/// Flags public API changes whose doc comment or README/docs mentions were left untouched.
+pub struct DocDriftAnalyzer {
+    settings: DocDriftSettings,
+    /// Files changed in the current review, set by `begin_review`.
+    changed: RwLock<Vec<PathBuf>>,
+    /// Documentation files (relative path, content), read once per run.
+    docs: OnceCell<Vec<(PathBuf, String)>>,
+}
+
+impl DocDriftAnalyzer {
+    pub fn new(settings: &PluginSettings) -> Result<Self> {
+        Ok(Self {
+            settings: super::parse_settings("doc_drift", settings)?,
+            changed: RwLock::new(Vec::new()),
+            docs: OnceCell::new(),
+        })
+    }
+
+    async fn docs(&self, repo_path: &str) -> &[(PathBuf, String)] {
+        self.docs
+            .get_or_init(|| async {
+                let root = Path::new(repo_path);
+                let mut docs = BTreeMap::new();
+                for pattern in &self.settings.docs {
+                    let Ok(paths) = glob::glob(&root.join(pattern).to_string_lossy()) else {
+                        continue;
+                    };
+                    for path in paths.flatten().filter(|path| path.is_file()) {
+                        let relative = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+                        if let Ok(content) = tokio::fs::read_to_string(&path).await {
+                            docs.insert(relative, content);
+                        }
+                    }
+                }
+                docs.into_iter().collect()
+            })
+            .await
+    }
+
+    /// Unchanged documentation files that mention `name`, with the first mentioning line.
+    fn stale_mentions(&self, docs: &[(PathBuf, String)], name: &str) -> Vec<(PathBuf, usize)> {
+        let Ok(mention) = Regex::new(&format!(
+            r"`[^`]*\b{0}\b[^`]*`|\b{0}\(",
+            regex::escape(name)
+        )) else {
+            return Vec::new();
+        };
+        let changed = self.changed.read().map(|c| c.clone()).unwrap_or_default();
+        docs.iter()
+            .filter(|(path, _)| !changed.contains(path))
+            .filter_map(|(path, content)| {
+                let line = content.lines().position(|line| mention.is_match(line))?;
+                Some((path.clone(), line + 1))
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl PreAnalyzer for DocDriftAnalyzer {
+    fn id(&self) -> &str {
+        "doc_drift"
+    }
+
+    fn settings(&self) -> serde_json::Value {
+        serde_json::to_value(&self.settings).unwrap_or_default()
+    }
+
+    async fn begin_review(&self, diffs: &[UnifiedDiff]) {
+        if let Ok(mut changed) = self.changed.write() {
+            *changed = diffs.iter().map(|diff| diff.file_path.clone()).collect();
+        }
+    }
+
+    async fn run(&self, diff: &UnifiedDiff, repo_path: &str) -> Result<Vec<LLMContextChunk>> {
+        Ok(self.analyze(diff, repo_path).await?.chunks)
+    }
+
+    async fn analyze(&self, diff: &UnifiedDiff, repo_path: &str) -> Result<PreAnalysis> {
+        if diff.is_deleted || diff.is_binary {
+            return Ok(PreAnalysis::default());
+        }
+        let api_changes = api_changes(diff);
+        if api_changes.is_empty() {
+            return Ok(PreAnalysis::default());
+        }
+
+        let source = match &diff.new_content {
+            Some(content) => Some(content.clone()),
+            None => tokio::fs::read_to_string(Path::new(repo_path).join(&diff.file_path))
+                .await
+                .ok(),
+        };
+        let lines: Vec<&str> = source.as_deref().unwrap_or_default().lines().collect();
+        let changes: Vec<&DiffLine> = diff.hunks.iter().flat_map(|hunk| &hunk.changes).collect();
+        let docs = self.docs(repo_path).await;
+
+        let mut analysis = PreAnalysis::default();
+        for change in api_changes {
+            let mut stale = Vec::new();
+            if let Some(line) = change.line {
+                if doc_comment_changed(&diff.file_path, &lines, line, &changes) == Some(false) {
+                    stale.push("its doc comment".to_string());
+                }
+            }
+            for (path, line) in self.stale_mentions(docs, &change.name) {
+                stale.push(format!("`{}:{}`", path.display(), line));
+            }
+            if stale.is_empty() {
+                continue;
+            }
+
+            let verb = if change.line.is_some() {
+                "changed"
+            } else {
+                "was removed or renamed"
+            };
+            let stale = match stale.split_last() {
+                Some((last, rest)) if !rest.is_empty() => {
+                    format!("{} and {}", rest.join(", "), last)
+                }
+                _ => stale.join(""),
+            };
+            let content = format!(
+                "Public API `{}` {}, but {} still describe the old version.",
+                change.name, verb, stale
+            );
+            analysis.chunks.push(LLMContextChunk {
+                file_path: diff.file_path.clone(),
+                content: format!("Possible documentation drift: {}", content),
+                context_type: ContextType::Documentation,
+                line_range: None,
+            });
+            analysis.findings.push(Comment {
+                id: compute_comment_id(&diff.file_path, &content, &Category::Documentation),
+                file_path: diff.file_path.clone(),
+                line_number: change.line.unwrap_or(change.hunk_line),
+                content,
+                severity: Severity::Info,
+                category: Category::Documentation,
+                suggestion: Some(
+                    "Update the documentation to match the new signature.".to_string(),
+                ),
+                confidence: 0.7,
+                code_suggestion: None,
+                tags: vec!["doc-drift".to_string(), change.name],
+                fix_effort: FixEffort::Low,
+                owners: Vec::new(),
+            });
+        }
+        Ok(analysis)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn flags_signature_changes_with_stale_docs() {
+        let repo = tempfile::tempdir().unwrap();
+        std::fs::write(
+            repo.path().join("README.md"),
+            "# Usage\n\nCall `parse_config(path)` to load settings.\n",
+        )
+        .unwrap();
+        std::fs::create_dir(repo.path().join("src")).unwrap();
+        std::fs::write(
+            repo.path().join("src/config.rs"),
+            "/// Loads settings from `path`.\npub fn parse_config(path: &Path, strict: bool) -> Config {\n    todo!()\n}\n\n/// Old docs.\npub fn reload(force: bool) {}\n",
+        )
+        .unwrap();
+        let diff = crate::core::DiffParser::parse_unified_diff(
+            "--- a/src/config.rs\n+++ b/src/config.rs\n@@ -1,7 +1,7 @@\n /// Loads settings from `path`.\n-pub fn parse_config(path: &Path) -> Config {\n+pub fn parse_config(path: &Path, strict: bool) -> Config {\n     todo!()\n }\n \n-/// Reloads settings.\n-pub fn reload() {}\n+/// Old docs.\n+pub fn reload(force: bool) {}\n",
+        )
+        .unwrap()
+        .remove(0);
+
+        let analyzer = DocDriftAnalyzer::new(&PluginSettings::new()).unwrap();
+        analyzer.begin_review(std::slice::from_ref(&diff)).await;
+        let analysis = analyzer
+            .analyze(&diff, &repo.path().to_string_lossy())
+            .await
+            .unwrap();
+
+        // `reload` had its doc comment rewritten and no README mention, so only
+        // `parse_config` is reported.
+        assert_eq!(analysis.findings.len(), 1);
+        let finding = &analysis.findings[0];
+        assert_eq!(finding.line_number, 2);
+        assert_eq!(finding.category, Category::Documentation);
+        assert_eq!(
+            finding.content,
+            "Public API `parse_config` changed, but its doc comment and `README.md:3` still describe the old version."
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use crate::config::PluginSettings;
 use crate::core::Comment;
 use crate::plugins::PostProcessor;
 use anyhow::Result;
@@ -7,8 +8,11 @@ use std::collections::HashSet;
 pub struct DuplicateFilter;
 
 impl DuplicateFilter {
-    pub fn new() -> Self {
-        Self
+    pub fn new(settings: &PluginSettings) -> Result<Self> {
+        if !settings.is_empty() {
+            anyhow::bail!("Plugin duplicate_filter does not take settings");
+        }
+        Ok(Self)
     }
 }
 
@@ -1,15 +1,36 @@
+use crate::config::PluginSettings;
 use crate::core::{ContextType, LLMContextChunk, UnifiedDiff};
 use crate::plugins::PreAnalyzer;
 use anyhow::Result;
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::process::Command;
 
-pub struct EslintAnalyzer;
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EslintSettings {
+    /// ESLint config file; without one ESLint runs with `--no-eslintrc`.
+    #[serde(default)]
+    config: Option<PathBuf>,
+
+    #[serde(default = "default_extensions")]
+    extensions: Vec<String>,
+}
+
+fn default_extensions() -> Vec<String> {
+    ["js", "ts", "jsx", "tsx"].map(String::from).to_vec()
+}
+
+pub struct EslintAnalyzer {
+    settings: EslintSettings,
+}
 
 impl EslintAnalyzer {
-    pub fn new() -> Self {
-        Self
+    pub fn new(settings: &PluginSettings) -> Result<Self> {
+        Ok(Self {
+            settings: super::parse_settings("eslint", settings)?,
+        })
     }
 }
 
@@ -19,22 +40,31 @@ impl PreAnalyzer for EslintAnalyzer {
         "eslint"
     }
 
+    fn settings(&self) -> serde_json::Value {
+        serde_json::to_value(&self.settings).unwrap_or_default()
+    }
+
     async fn run(&self, diff: &UnifiedDiff, repo_path: &str) -> Result<Vec<LLMContextChunk>> {
-        if !diff.file_path.to_string_lossy().ends_with(".js")
-            && !diff.file_path.to_string_lossy().ends_with(".ts")
-            && !diff.file_path.to_string_lossy().ends_with(".jsx")
-            && !diff.file_path.to_string_lossy().ends_with(".tsx")
-        {
+        let extension = diff
+            .file_path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_string())
+            .unwrap_or_default();
+        if !self.settings.extensions.contains(&extension) {
             return Ok(Vec::new());
         }
 
         let file_path = PathBuf::from(repo_path).join(&diff.file_path);
 
-        let output = Command::new("eslint")
-            .arg("--format=json")
-            .arg("--no-eslintrc")
-            .arg(file_path.to_string_lossy().as_ref())
-            .output();
+        let mut command = Command::new("eslint");
+        command.arg("--format=json");
+        match &self.settings.config {
+            Some(config) => command
+                .arg("--config")
+                .arg(PathBuf::from(repo_path).join(config)),
+            None => command.arg("--no-eslintrc"),
+        };
+        let output = command.arg(file_path.to_string_lossy().as_ref()).output();
 
         match output {
             Ok(output) => {
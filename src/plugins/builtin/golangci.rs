@@ -0,0 +1,233 @@
+use crate::config::PluginSettings;
+use crate::core::comment::{compute_comment_id, Category, FixEffort, Severity};
+use crate::core::{Comment, ContextType, LLMContextChunk, UnifiedDiff};
+use crate::plugins::{PreAnalysis, PreAnalyzer};
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GolangciSettings {
+    /// golangci-lint config file; by default `.golangci.yml` is discovered as usual.
+    #[serde(default)]
+    config: Option<PathBuf>,
+
+    /// Post issues on changed lines as review comments, not just as prompt context.
+    #[serde(default = "default_true")]
+    post_findings: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct GolangciOutput {
+    #[serde(rename = "Issues", default)]
+    issues: Option<Vec<GolangciIssue>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct GolangciIssue {
+    #[serde(rename = "FromLinter")]
+    from_linter: String,
+    #[serde(rename = "Text")]
+    text: String,
+    #[serde(rename = "Severity", default)]
+    severity: String,
+    #[serde(rename = "Pos")]
+    pos: GolangciPosition,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct GolangciPosition {
+    #[serde(rename = "Filename")]
+    filename: PathBuf,
+    #[serde(rename = "Line")]
+    line: usize,
+}
+
+pub struct GolangciAnalyzer {
+    settings: GolangciSettings,
+    /// Issues per package directory, so a package with several changed files is linted once.
+    packages: Mutex<HashMap<PathBuf, Arc<Vec<GolangciIssue>>>>,
+}
+
+impl GolangciAnalyzer {
+    pub fn new(settings: &PluginSettings) -> Result<Self> {
+        Ok(Self {
+            settings: super::parse_settings("golangci", settings)?,
+            packages: Mutex::new(HashMap::new()),
+        })
+    }
+
+    async fn lint_package(&self, package: &Path, repo_path: &str) -> Arc<Vec<GolangciIssue>> {
+        let mut packages = self.packages.lock().await;
+        if let Some(issues) = packages.get(package) {
+            return issues.clone();
+        }
+
+        let mut command = tokio::process::Command::new("golangci-lint");
+        command.current_dir(repo_path).args([
+            "run",
+            "--out-format",
+            "json",
+            "--issues-exit-code",
+            "0",
+        ]);
+        if let Some(config) = &self.settings.config {
+            command.arg("--config").arg(config);
+        }
+        let target = if package.as_os_str().is_empty() {
+            "./".to_string()
+        } else {
+            format!("./{}", package.display())
+        };
+        let issues = match command.arg(target).output().await {
+            Ok(output) => serde_json::from_slice::<GolangciOutput>(&output.stdout)
+                .unwrap_or_default()
+                .issues
+                .unwrap_or_default(),
+            Err(_) => Vec::new(),
+        };
+
+        let issues = Arc::new(issues);
+        packages.insert(package.to_path_buf(), issues.clone());
+        issues
+    }
+
+    /// Keeps issues reported on lines this diff adds.
+    fn analysis_from_issues(&self, diff: &UnifiedDiff, issues: &[GolangciIssue]) -> PreAnalysis {
+        let added = super::added_lines(diff);
+        let issues: Vec<&GolangciIssue> = issues
+            .iter()
+            .filter(|issue| issue.pos.filename == diff.file_path && added.contains(&issue.pos.line))
+            .collect();
+        if issues.is_empty() {
+            return PreAnalysis::default();
+        }
+
+        let mut content = String::from("golangci-lint issues on changed lines:\n");
+        for issue in &issues {
+            content.push_str(&format!(
+                "- Line {} ({}): {}\n",
+                issue.pos.line, issue.from_linter, issue.text
+            ));
+        }
+        let chunks = vec![LLMContextChunk {
+            file_path: diff.file_path.clone(),
+            content,
+            context_type: ContextType::Documentation,
+            line_range: None,
+        }];
+
+        let findings = if self.settings.post_findings {
+            issues.iter().map(|issue| to_comment(diff, issue)).collect()
+        } else {
+            Vec::new()
+        };
+        PreAnalysis { chunks, findings }
+    }
+}
+
+fn to_comment(diff: &UnifiedDiff, issue: &GolangciIssue) -> Comment {
+    let category = match issue.from_linter.as_str() {
+        "gosec" => Category::Security,
+        "errcheck" | "govet" | "staticcheck" | "ineffassign" | "typecheck" | "bodyclose"
+        | "nilerr" | "sqlclosecheck" => Category::Bug,
+        "prealloc" | "perfsprint" => Category::Performance,
+        "unused" | "gocyclo" | "gocognit" | "dupl" | "funlen" => Category::Maintainability,
+        "gofmt" | "goimports" | "gocritic" | "revive" | "stylecheck" | "misspell" | "lll" => {
+            Category::Style
+        }
+        "godot" => Category::Documentation,
+        _ => Category::BestPractice,
+    };
+    let severity = match issue.severity.to_lowercase().as_str() {
+        "error" => Severity::Error,
+        "warning" => Severity::Warning,
+        _ if issue.from_linter == "typecheck" => Severity::Error,
+        _ if matches!(category, Category::Bug | Category::Security) => Severity::Warning,
+        _ => Severity::Info,
+    };
+    let content = format!("{}: {}", issue.from_linter, issue.text);
+
+    Comment {
+        id: compute_comment_id(&diff.file_path, &content, &category),
+        file_path: diff.file_path.clone(),
+        line_number: issue.pos.line,
+        content,
+        severity,
+        category,
+        suggestion: None,
+        confidence: 1.0,
+        code_suggestion: None,
+        tags: vec!["golangci-lint".to_string(), issue.from_linter.clone()],
+        fix_effort: FixEffort::Low,
+        owners: Vec::new(),
+    }
+}
+
+#[async_trait]
+impl PreAnalyzer for GolangciAnalyzer {
+    fn id(&self) -> &str {
+        "golangci"
+    }
+
+    fn settings(&self) -> serde_json::Value {
+        serde_json::to_value(&self.settings).unwrap_or_default()
+    }
+
+    async fn run(&self, diff: &UnifiedDiff, repo_path: &str) -> Result<Vec<LLMContextChunk>> {
+        Ok(self.analyze(diff, repo_path).await?.chunks)
+    }
+
+    async fn analyze(&self, diff: &UnifiedDiff, repo_path: &str) -> Result<PreAnalysis> {
+        if diff.file_path.extension().and_then(|ext| ext.to_str()) != Some("go") {
+            return Ok(PreAnalysis::default());
+        }
+        let package = diff.file_path.parent().unwrap_or_else(|| Path::new(""));
+        let issues = self.lint_package(package, repo_path).await;
+        Ok(self.analysis_from_issues(diff, &issues))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_issues_in_changed_file_and_lines() {
+        let diff = crate::core::DiffParser::parse_unified_diff(
+            "--- a/pkg/db/query.go\n+++ b/pkg/db/query.go\n@@ -10,2 +10,3 @@\n func run() {\n+\trows.Close()\n+\tq := \"SELECT \" + id\n",
+        )
+        .unwrap()
+        .remove(0);
+        let output: GolangciOutput = serde_json::from_str(
+            r#"{"Issues": [
+                {"FromLinter": "errcheck", "Text": "Error return value of `rows.Close` is not checked", "Severity": "", "Pos": {"Filename": "pkg/db/query.go", "Line": 11, "Column": 12}},
+                {"FromLinter": "gosec", "Text": "G202: SQL string concatenation", "Severity": "", "Pos": {"Filename": "pkg/db/query.go", "Line": 12, "Column": 7}},
+                {"FromLinter": "unused", "Text": "func `old` is unused", "Severity": "", "Pos": {"Filename": "pkg/db/query.go", "Line": 10, "Column": 6}},
+                {"FromLinter": "errcheck", "Text": "unchecked", "Severity": "", "Pos": {"Filename": "pkg/db/conn.go", "Line": 11, "Column": 1}}
+            ]}"#,
+        )
+        .unwrap();
+
+        let analyzer = GolangciAnalyzer::new(&PluginSettings::new()).unwrap();
+        let analysis = analyzer.analysis_from_issues(&diff, &output.issues.unwrap());
+
+        assert_eq!(analysis.findings.len(), 2);
+        assert_eq!(analysis.findings[0].category, Category::Bug);
+        assert_eq!(analysis.findings[0].severity, Severity::Warning);
+        assert_eq!(analysis.findings[1].category, Category::Security);
+        assert_eq!(analysis.findings[1].line_number, 12);
+        assert!(analysis.chunks[0]
+            .content
+            .contains("- Line 12 (gosec): G202: SQL string concatenation"));
+    }
+}
@@ -0,0 +1,487 @@
+use crate::config::PluginSettings;
+use crate::core::comment::{compute_comment_id, Category, FixEffort, Severity};
+use crate::core::diff_parser::ChangeType;
+use crate::core::{Comment, LLMContextChunk, UnifiedDiff};
+use crate::plugins::{PreAnalysis, PreAnalyzer};
+use anyhow::Result;
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct IacSettings {
+    #[serde(default = "default_true")]
+    tflint: bool,
+
+    #[serde(default = "default_true")]
+    checkov: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IacKind {
+    Terraform,
+    CloudFormation,
+    Kubernetes,
+}
+
+static PUBLIC_BUCKET: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"(?i)(?:\bacl\s*=\s*"public-read(?:-write)?"|AccessControl:\s*["']?PublicRead(?:Write)?|block_public_(?:acls|policy)\s*=\s*false|BlockPublic(?:Acls|Policy):\s*false)"#,
+    )
+    .unwrap()
+});
+static OPEN_CIDR: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"["'\s\[:=]0\.0\.0\.0/0\b|::/0"#).unwrap());
+static INGRESS: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)ingress").unwrap());
+static EGRESS: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)egress").unwrap());
+static SECRET_VALUE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"(?i)^\s*-?\s*"?(\w*(?:password|passwd|secret|token|api_?key)\w*)"?\s*[:=]\s*["']?([^"'\s#]{4,})"#,
+    )
+    .unwrap()
+});
+static ENV_SECRET_NAME: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?i)^\s*-?\s*name:\s*["']?\w*(?:password|passwd|secret|token|api_?key)\w*"#)
+        .unwrap()
+});
+static ENV_VALUE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"^\s*value:\s*["']?([^"'\s#]{4,})"#).unwrap());
+
+/// Key suffixes that name or point at a secret rather than hold one (`secretName`, `token_ttl`).
+const REFERENCE_KEY_SUFFIXES: [&str; 9] = [
+    "name", "ref", "arn", "path", "file", "id", "version", "ttl", "length",
+];
+
+/// Values that reference a secret instead of containing one.
+const REFERENCE_PREFIXES: [&str; 8] = [
+    "var.", "local.", "data.", "module.", "${", "{{", "!Ref", "arn:",
+];
+
+#[derive(Debug, Deserialize)]
+struct TflintOutput {
+    #[serde(default)]
+    issues: Vec<TflintIssue>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TflintIssue {
+    rule: TflintRule,
+    message: String,
+    range: TflintRange,
+}
+
+#[derive(Debug, Deserialize)]
+struct TflintRule {
+    name: String,
+    #[serde(default)]
+    severity: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TflintRange {
+    filename: PathBuf,
+    start: TflintPosition,
+    end: TflintPosition,
+}
+
+#[derive(Debug, Deserialize)]
+struct TflintPosition {
+    line: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct CheckovReport {
+    results: CheckovResults,
+}
+
+#[derive(Debug, Deserialize)]
+struct CheckovResults {
+    #[serde(default)]
+    failed_checks: Vec<CheckovCheck>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CheckovCheck {
+    check_id: String,
+    check_name: String,
+    file_line_range: [usize; 2],
+    #[serde(default)]
+    severity: Option<String>,
+}
+
+/// An issue found on an added line, by a tool or a built-in rule.
+struct IacFinding {
+    line: usize,
+    /// Last line of the flagged block (inclusive); tools report whole resources.
+    end_line: usize,
+    source: &'static str,
+    rule: String,
+    message: String,
+    severity: Severity,
+}
+
+/// Reviews Terraform, CloudFormation, and Kubernetes manifests with tflint and checkov when
+/// installed, and with a small built-in rule set otherwise.
+pub struct IacAnalyzer {
+    settings: IacSettings,
+}
+
+impl IacAnalyzer {
+    pub fn new(settings: &PluginSettings) -> Result<Self> {
+        Ok(Self {
+            settings: super::parse_settings("iac", settings)?,
+        })
+    }
+
+    /// `None` when tflint is unavailable or its output is unusable.
+    async fn run_tflint(&self, diff: &UnifiedDiff, repo_path: &str) -> Option<Vec<IacFinding>> {
+        let dir = diff.file_path.parent().unwrap_or_else(|| Path::new(""));
+        let output = Command::new("tflint")
+            .current_dir(Path::new(repo_path).join(dir))
+            .args(["--format", "json", "--force"])
+            .output()
+            .await
+            .ok()?;
+        let parsed: TflintOutput = serde_json::from_slice(&output.stdout).ok()?;
+        let file_name = diff.file_path.file_name()?;
+        Some(
+            parsed
+                .issues
+                .into_iter()
+                .filter(|issue| issue.range.filename.file_name() == Some(file_name))
+                .map(|issue| IacFinding {
+                    line: issue.range.start.line,
+                    end_line: issue.range.end.line,
+                    source: "tflint",
+                    severity: match issue.rule.severity.as_str() {
+                        "error" => Severity::Error,
+                        "warning" => Severity::Warning,
+                        _ => Severity::Info,
+                    },
+                    rule: issue.rule.name,
+                    message: issue.message,
+                })
+                .collect(),
+        )
+    }
+
+    /// `None` when checkov is unavailable or its output is unusable.
+    async fn run_checkov(&self, diff: &UnifiedDiff, repo_path: &str) -> Option<Vec<IacFinding>> {
+        let output = Command::new("checkov")
+            .current_dir(repo_path)
+            .args(["--quiet", "--compact", "-o", "json", "-f"])
+            .arg(&diff.file_path)
+            .output()
+            .await
+            .ok()?;
+        // A single framework yields one report object; several yield a list of them.
+        let reports: Vec<CheckovReport> = serde_json::from_slice(&output.stdout)
+            .or_else(|_| serde_json::from_slice(&output.stdout).map(|report| vec![report]))
+            .ok()?;
+        Some(
+            reports
+                .into_iter()
+                .flat_map(|report| report.results.failed_checks)
+                .map(|check| IacFinding {
+                    line: check.file_line_range[0],
+                    end_line: check.file_line_range[1],
+                    source: "checkov",
+                    severity: match check.severity.as_deref() {
+                        Some("CRITICAL") | Some("HIGH") => Severity::Error,
+                        Some("LOW") => Severity::Info,
+                        _ => Severity::Warning,
+                    },
+                    rule: check.check_id,
+                    message: check.check_name,
+                })
+                .collect(),
+        )
+    }
+}
+
+fn detect_kind(path: &Path, content: &str) -> Option<IacKind> {
+    let extension = path.extension()?.to_str()?.to_lowercase();
+    match extension.as_str() {
+        "tf" | "tfvars" => Some(IacKind::Terraform),
+        "yml" | "yaml" | "json" | "template" => {
+            if content.contains("AWSTemplateFormatVersion") || content.contains("AWS::") {
+                Some(IacKind::CloudFormation)
+            } else if content.contains("apiVersion") && content.contains("kind") {
+                Some(IacKind::Kubernetes)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+fn is_reference_key(key: &str) -> bool {
+    let key = key.to_lowercase();
+    REFERENCE_KEY_SUFFIXES
+        .iter()
+        .any(|suffix| key.ends_with(suffix))
+}
+
+fn is_reference(value: &str) -> bool {
+    REFERENCE_PREFIXES
+        .iter()
+        .any(|prefix| value.starts_with(prefix))
+}
+
+/// Built-in rules over the diff's new-side lines; only added lines are reported.
+fn builtin_findings(diff: &UnifiedDiff) -> Vec<IacFinding> {
+    let mut findings = Vec::new();
+    for hunk in &diff.hunks {
+        // Last direction keyword seen, so 0.0.0.0/0 egress rules are not reported.
+        let mut in_ingress = false;
+        let mut previous_names_secret = false;
+        for line in &hunk.changes {
+            if matches!(line.change_type, ChangeType::Removed) {
+                continue;
+            }
+            let content = line.content.as_str();
+            if INGRESS.is_match(content) {
+                in_ingress = true;
+            } else if EGRESS.is_match(content) {
+                in_ingress = false;
+            }
+            let names_secret = std::mem::replace(
+                &mut previous_names_secret,
+                ENV_SECRET_NAME.is_match(content),
+            );
+            let (true, Some(line_no)) = (
+                matches!(line.change_type, ChangeType::Added),
+                line.new_line_no,
+            ) else {
+                continue;
+            };
+
+            if PUBLIC_BUCKET.is_match(content) {
+                findings.push(IacFinding {
+                    line: line_no,
+                    end_line: line_no,
+                    source: "builtin",
+                    rule: "public-bucket".to_string(),
+                    message: "Storage bucket allows public access.".to_string(),
+                    severity: Severity::Error,
+                });
+            }
+            if in_ingress && OPEN_CIDR.is_match(content) {
+                findings.push(IacFinding {
+                    line: line_no,
+                    end_line: line_no,
+                    source: "builtin",
+                    rule: "open-ingress".to_string(),
+                    message: "Ingress is open to the whole internet (0.0.0.0/0).".to_string(),
+                    severity: Severity::Warning,
+                });
+            }
+            let secret = SECRET_VALUE
+                .captures(content)
+                .filter(|captures| !is_reference_key(&captures[1]))
+                .and_then(|captures| captures.get(2))
+                .or_else(|| {
+                    names_secret
+                        .then(|| ENV_VALUE.captures(content))
+                        .flatten()
+                        .and_then(|captures| captures.get(1))
+                })
+                .filter(|value| !is_reference(value.as_str()));
+            if secret.is_some() {
+                findings.push(IacFinding {
+                    line: line_no,
+                    end_line: line_no,
+                    source: "builtin",
+                    rule: "plaintext-secret".to_string(),
+                    message:
+                        "Secret is set as a plaintext value; reference a secret store instead."
+                            .to_string(),
+                    severity: Severity::Error,
+                });
+            }
+        }
+    }
+    findings
+}
+
+/// Tool findings whose flagged block includes an added line, anchored on the first such line so
+/// the comment lands inside the diff. Tools report a whole resource, whose first line is often
+/// unchanged context.
+fn on_added_lines(diff: &UnifiedDiff, found: Vec<IacFinding>) -> Vec<IacFinding> {
+    let added = super::added_lines(diff);
+    found
+        .into_iter()
+        .filter_map(|finding| {
+            let line = (finding.line..=finding.end_line.max(finding.line))
+                .find(|line| added.contains(line))?;
+            Some(IacFinding { line, ..finding })
+        })
+        .collect()
+}
+
+fn to_comment(diff: &UnifiedDiff, finding: &IacFinding) -> Comment {
+    let content = if finding.source == "builtin" {
+        finding.message.clone()
+    } else {
+        format!("{} {}: {}", finding.source, finding.rule, finding.message)
+    };
+    Comment {
+        id: compute_comment_id(&diff.file_path, &content, &Category::Security),
+        file_path: diff.file_path.clone(),
+        line_number: finding.line,
+        content,
+        severity: finding.severity.clone(),
+        category: Category::Security,
+        suggestion: None,
+        confidence: if finding.source == "builtin" {
+            0.9
+        } else {
+            1.0
+        },
+        code_suggestion: None,
+        tags: vec![
+            "iac".to_string(),
+            finding.source.to_string(),
+            finding.rule.clone(),
+        ],
+        fix_effort: FixEffort::Low,
+        owners: Vec::new(),
+    }
+}
+
+#[async_trait]
+impl PreAnalyzer for IacAnalyzer {
+    fn id(&self) -> &str {
+        "iac"
+    }
+
+    fn settings(&self) -> serde_json::Value {
+        serde_json::to_value(&self.settings).unwrap_or_default()
+    }
+
+    async fn run(&self, diff: &UnifiedDiff, repo_path: &str) -> Result<Vec<LLMContextChunk>> {
+        Ok(self.analyze(diff, repo_path).await?.chunks)
+    }
+
+    async fn analyze(&self, diff: &UnifiedDiff, repo_path: &str) -> Result<PreAnalysis> {
+        let content = match &diff.new_content {
+            Some(content) => content.clone(),
+            None => std::fs::read_to_string(Path::new(repo_path).join(&diff.file_path))
+                .unwrap_or_else(|_| {
+                    diff.hunks
+                        .iter()
+                        .flat_map(|hunk| &hunk.changes)
+                        .map(|line| line.content.as_str())
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                }),
+        };
+        let Some(kind) = detect_kind(&diff.file_path, &content) else {
+            return Ok(PreAnalysis::default());
+        };
+
+        let mut tool_findings: Option<Vec<IacFinding>> = None;
+        if kind == IacKind::Terraform && self.settings.tflint {
+            tool_findings = self.run_tflint(diff, repo_path).await;
+        }
+        if self.settings.checkov {
+            if let Some(found) = self.run_checkov(diff, repo_path).await {
+                tool_findings.get_or_insert_with(Vec::new).extend(found);
+            }
+        }
+        let findings = match tool_findings {
+            Some(found) => on_added_lines(diff, found),
+            None => builtin_findings(diff),
+        };
+
+        let mut seen = HashSet::new();
+        Ok(PreAnalysis {
+            chunks: Vec::new(),
+            findings: findings
+                .iter()
+                .filter(|finding| seen.insert((finding.line, finding.rule.clone())))
+                .map(|finding| to_comment(diff, finding))
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_rules_flag_public_buckets_open_ingress_and_plaintext_secrets() {
+        let diff = crate::core::DiffParser::parse_unified_diff(
+            r#"--- a/main.tf
++++ b/main.tf
+@@ -1,3 +1,16 @@
+ resource "aws_s3_bucket_acl" "logs" {
++  acl = "public-read"
+ }
++resource "aws_security_group" "web" {
++  ingress {
++    cidr_blocks = ["0.0.0.0/0"]
++  }
++  egress {
++    cidr_blocks = ["0.0.0.0/0"]
++  }
++}
++resource "aws_db_instance" "db" {
++  password = "hunter2hunter2"
++  master_password = var.db_password
++  secret_name = "db-credentials"
+ }
+"#,
+        )
+        .unwrap()
+        .remove(0);
+
+        let findings: Vec<String> = builtin_findings(&diff)
+            .into_iter()
+            .map(|finding| format!("{}:{}", finding.line, finding.rule))
+            .collect();
+        assert_eq!(
+            findings,
+            vec!["2:public-bucket", "6:open-ingress", "13:plaintext-secret"]
+        );
+
+        let k8s = "apiVersion: v1\nkind: Pod\n";
+        assert_eq!(
+            detect_kind(Path::new("deploy/pod.yaml"), k8s),
+            Some(IacKind::Kubernetes)
+        );
+        assert_eq!(detect_kind(Path::new("src/app.rs"), k8s), None);
+    }
+
+    #[test]
+    fn tool_findings_on_changed_blocks_anchor_on_the_added_line() {
+        let diff = crate::core::DiffParser::parse_unified_diff(
+            "--- a/main.tf\n+++ b/main.tf\n@@ -1,4 +1,4 @@\n resource \"aws_s3_bucket\" \"logs\" {\n   bucket = \"logs\"\n-  acl    = \"private\"\n+  acl    = \"public-read\"\n }\n",
+        )
+        .unwrap()
+        .remove(0);
+        let finding = |line, end_line| IacFinding {
+            line,
+            end_line,
+            source: "checkov",
+            rule: "CKV_AWS_20".to_string(),
+            message: "S3 bucket is public".to_string(),
+            severity: Severity::Error,
+        };
+
+        let kept = on_added_lines(&diff, vec![finding(1, 4), finding(1, 2), finding(3, 3)]);
+        let lines: Vec<usize> = kept.iter().map(|finding| finding.line).collect();
+        assert_eq!(lines, vec![3, 3]);
+    }
+}
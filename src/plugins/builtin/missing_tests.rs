@@ -0,0 +1,293 @@
+use crate::config::PluginSettings;
+use crate::core::comment::{compute_comment_id, Category, FixEffort, Severity};
+use crate::core::diff_parser::ChangeType;
+use crate::core::{Comment, ContextType, LLMContextChunk, UnifiedDiff};
+use crate::plugins::{PreAnalysis, PreAnalyzer};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+/// Added lines that are test code themselves, e.g. a Rust `#[test]` next to the change.
+static INLINE_TEST: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"#\[(?:tokio::)?test\]|#\[cfg\(test\)\]|^\s*def test_|^\s*func Test|^\s*(?:describe|it|test)\(",
+    )
+    .unwrap()
+});
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MissingTestsSettings {
+    /// Source globs and the test files expected to change with them. The first mapping whose
+    /// `source` matches a file applies.
+    #[serde(default = "default_mappings")]
+    mappings: Vec<TestMapping>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TestMapping {
+    source: String,
+    /// Globs for the matching tests; `{dir}`, `{stem}`, and `{ext}` refer to the source file.
+    tests: Vec<String>,
+}
+
+fn mapping(source: &str, tests: &[&str]) -> TestMapping {
+    TestMapping {
+        source: source.to_string(),
+        tests: tests.iter().map(|test| test.to_string()).collect(),
+    }
+}
+
+fn default_mappings() -> Vec<TestMapping> {
+    let mut mappings = vec![
+        mapping(
+            "**/*.rs",
+            &[
+                "tests/**/{stem}*.rs",
+                "{dir}/tests.rs",
+                "{dir}/{stem}/tests.rs",
+            ],
+        ),
+        mapping("**/*.py", &["**/test_{stem}.py", "**/{stem}_test.py"]),
+        mapping("**/*.go", &["{dir}/*_test.go"]),
+        mapping("src/main/**/*.java", &["src/test/**/{stem}Test*.java"]),
+        mapping(
+            "**/*.rb",
+            &["spec/**/{stem}_spec.rb", "test/**/{stem}_test.rb"],
+        ),
+    ];
+    for ext in ["ts", "tsx", "js", "jsx", "mjs"] {
+        mappings.push(mapping(
+            &format!("**/*.{}", ext),
+            &[
+                "**/{stem}.spec.*",
+                "**/{stem}.test.*",
+                "**/__tests__/{stem}.*",
+            ],
+        ));
+    }
+    mappings
+}
+
+/// Notes source files that change without any matching test file changing in the same review.
+pub struct MissingTestsAnalyzer {
+    settings: MissingTestsSettings,
+    sources: Vec<glob::Pattern>,
+    /// Every file in the current review, set by `begin_review`.
+    changed: RwLock<Vec<PathBuf>>,
+}
+
+impl MissingTestsAnalyzer {
+    pub fn new(settings: &PluginSettings) -> Result<Self> {
+        let settings: MissingTestsSettings = super::parse_settings("missing_tests", settings)?;
+        let sources = settings
+            .mappings
+            .iter()
+            .map(|mapping| {
+                glob::Pattern::new(&mapping.source)
+                    .with_context(|| format!("Invalid missing_tests source {}", mapping.source))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            settings,
+            sources,
+            changed: RwLock::new(Vec::new()),
+        })
+    }
+
+    /// Test globs for `path`, or `None` when no mapping covers it.
+    fn expected_tests(&self, path: &Path) -> Option<Vec<String>> {
+        let index = self
+            .sources
+            .iter()
+            .position(|source| source.matches_path(path))?;
+        let dir = path.parent().map(|dir| dir.to_string_lossy().to_string());
+        let stem = path.file_stem()?.to_string_lossy();
+        let ext = path
+            .extension()
+            .map(|ext| ext.to_string_lossy())
+            .unwrap_or_default();
+        Some(
+            self.settings.mappings[index]
+                .tests
+                .iter()
+                .map(|test| {
+                    let test = match dir.as_deref() {
+                        Some(dir) if !dir.is_empty() => test.replace("{dir}", dir),
+                        _ => test.replace("{dir}/", ""),
+                    };
+                    test.replace("{stem}", &stem).replace("{ext}", &ext)
+                })
+                .collect(),
+        )
+    }
+}
+
+fn is_test_file(path: &Path) -> bool {
+    let in_test_dir = path.components().any(|component| {
+        matches!(
+            component.as_os_str().to_str(),
+            Some("test" | "tests" | "__tests__" | "spec" | "testdata")
+        )
+    });
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    in_test_dir
+        || stem.starts_with("test_")
+        || stem.ends_with("_test")
+        || stem.ends_with("_spec")
+        || stem.ends_with("Test")
+        || stem.ends_with(".test")
+        || stem.ends_with(".spec")
+}
+
+#[async_trait]
+impl PreAnalyzer for MissingTestsAnalyzer {
+    fn id(&self) -> &str {
+        "missing_tests"
+    }
+
+    fn settings(&self) -> serde_json::Value {
+        serde_json::to_value(&self.settings).unwrap_or_default()
+    }
+
+    async fn begin_review(&self, diffs: &[UnifiedDiff]) {
+        if let Ok(mut changed) = self.changed.write() {
+            *changed = diffs.iter().map(|diff| diff.file_path.clone()).collect();
+        }
+    }
+
+    async fn run(&self, diff: &UnifiedDiff, repo_path: &str) -> Result<Vec<LLMContextChunk>> {
+        Ok(self.analyze(diff, repo_path).await?.chunks)
+    }
+
+    async fn analyze(&self, diff: &UnifiedDiff, _repo_path: &str) -> Result<PreAnalysis> {
+        if diff.is_deleted || is_test_file(&diff.file_path) {
+            return Ok(PreAnalysis::default());
+        }
+        let Some(expected) = self.expected_tests(&diff.file_path) else {
+            return Ok(PreAnalysis::default());
+        };
+        let added: Vec<_> = diff
+            .hunks
+            .iter()
+            .flat_map(|hunk| &hunk.changes)
+            .filter(|line| matches!(line.change_type, ChangeType::Added))
+            .collect();
+        let Some(first_line) = added.iter().find_map(|line| line.new_line_no) else {
+            return Ok(PreAnalysis::default());
+        };
+        if added.iter().any(|line| INLINE_TEST.is_match(&line.content)) {
+            return Ok(PreAnalysis::default());
+        }
+
+        let patterns: Vec<glob::Pattern> = expected
+            .iter()
+            .filter_map(|test| glob::Pattern::new(test).ok())
+            .collect();
+        let tested = self
+            .changed
+            .read()
+            .map(|changed| {
+                changed
+                    .iter()
+                    .any(|path| patterns.iter().any(|pattern| pattern.matches_path(path)))
+            })
+            .unwrap_or(true);
+        if tested {
+            return Ok(PreAnalysis::default());
+        }
+
+        let expected = expected
+            .iter()
+            .map(|test| format!("`{}`", test))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let content = format!(
+            "`{}` changed without a matching test change (looked for {}).",
+            diff.file_path.display(),
+            expected
+        );
+        Ok(PreAnalysis {
+            chunks: vec![LLMContextChunk {
+                file_path: diff.file_path.clone(),
+                content: format!(
+                    "No test files changed alongside this file (expected one of {}). Point out new or changed behaviour that is left untested.",
+                    expected
+                ),
+                context_type: ContextType::Documentation,
+                line_range: None,
+            }],
+            findings: vec![Comment {
+                id: compute_comment_id(&diff.file_path, &content, &Category::Testing),
+                file_path: diff.file_path.clone(),
+                line_number: first_line,
+                content,
+                severity: Severity::Info,
+                category: Category::Testing,
+                suggestion: Some(
+                    "Add or update tests that cover the changed behaviour.".to_string(),
+                ),
+                confidence: 0.8,
+                code_suggestion: None,
+                tags: vec!["missing-tests".to_string()],
+                fix_effort: FixEffort::Medium,
+                owners: Vec::new(),
+            }],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diff(path: &str, added: &str) -> UnifiedDiff {
+        crate::core::DiffParser::parse_unified_diff(&format!(
+            "--- a/{path}\n+++ b/{path}\n@@ -1,1 +1,2 @@\n first\n+{added}\n"
+        ))
+        .unwrap()
+        .remove(0)
+    }
+
+    #[tokio::test]
+    async fn flags_sources_changed_without_matching_tests() {
+        let analyzer = MissingTestsAnalyzer::new(&PluginSettings::new()).unwrap();
+        let diffs = vec![
+            diff("web/src/cart.ts", "export const total = 1;"),
+            diff("web/src/cart.spec.ts", "it('totals', () => {});"),
+            diff("web/src/price.ts", "export const tax = 2;"),
+            diff("svc/handler.go", "return nil"),
+            diff("src/lib.rs", "#[test]"),
+        ];
+        analyzer.begin_review(&diffs).await;
+
+        let mut flagged = Vec::new();
+        for diff in &diffs {
+            let analysis = analyzer.analyze(diff, ".").await.unwrap();
+            for finding in analysis.findings {
+                assert_eq!(finding.category, Category::Testing);
+                flagged.push(finding.file_path.display().to_string());
+            }
+        }
+        assert_eq!(flagged, vec!["web/src/price.ts", "svc/handler.go"]);
+
+        let settings: PluginSettings = serde_json::from_str(
+            r#"{"mappings": [{"source": "src/**/*.rs", "tests": ["tests/{stem}_*.rs"]}]}"#,
+        )
+        .unwrap();
+        let analyzer = MissingTestsAnalyzer::new(&settings).unwrap();
+        assert_eq!(
+            analyzer.expected_tests(Path::new("src/core/parser.rs")),
+            Some(vec!["tests/parser_*.rs".to_string()])
+        );
+        assert_eq!(analyzer.expected_tests(Path::new("lib/parser.py")), None);
+    }
+}
@@ -1,7 +1,72 @@
+mod blame;
+mod breaking_changes;
+mod calibration;
+mod comment_clusters;
+mod complexity;
+mod coverage;
+mod dependency_audit;
+mod dependency_impact;
+mod doc_drift;
 mod duplicate_filter;
 mod eslint;
+mod golangci;
+mod iac;
+mod missing_tests;
+mod ruff;
+mod rules;
+mod secret_scan;
+mod secrets;
 mod semgrep;
+mod spelling;
+mod suggestion_check;
 
+pub use blame::BlameAnalyzer;
+pub use breaking_changes::{
+    format_breaking_changes_section, BreakingChangeAnalyzer, BREAKING_CHANGE_TAG,
+};
+pub use calibration::CommentCalibrator;
+pub use comment_clusters::CommentClusterer;
+pub use complexity::{complexity_delta, ComplexityAnalyzer};
+pub use coverage::CoverageAnalyzer;
+pub use dependency_audit::DependencyAuditAnalyzer;
+pub use dependency_impact::DependencyImpactAnalyzer;
+pub use doc_drift::DocDriftAnalyzer;
 pub use duplicate_filter::DuplicateFilter;
 pub use eslint::EslintAnalyzer;
+pub use golangci::GolangciAnalyzer;
+pub use iac::IacAnalyzer;
+pub use missing_tests::MissingTestsAnalyzer;
+pub use ruff::RuffAnalyzer;
+pub use rules::RulesAnalyzer;
+pub use secret_scan::SecretScanAnalyzer;
+pub use secrets::SecretsDetector;
 pub use semgrep::SemgrepAnalyzer;
+pub use spelling::SpellingAnalyzer;
+pub use suggestion_check::SuggestionValidator;
+
+use crate::config::PluginSettings;
+use crate::core::diff_parser::ChangeType;
+use crate::core::UnifiedDiff;
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use std::collections::HashSet;
+
+/// Deserializes a plugin's `plugins.settings.<id>` map into its typed settings.
+fn parse_settings<T: DeserializeOwned>(id: &str, settings: &PluginSettings) -> Result<T> {
+    let map = settings
+        .iter()
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+    serde_json::from_value(serde_json::Value::Object(map))
+        .with_context(|| format!("Invalid settings for plugin {}", id))
+}
+
+/// New-file line numbers added by `diff`; linters only report findings on these lines.
+fn added_lines(diff: &UnifiedDiff) -> HashSet<usize> {
+    diff.hunks
+        .iter()
+        .flat_map(|hunk| &hunk.changes)
+        .filter(|line| matches!(line.change_type, ChangeType::Added))
+        .filter_map(|line| line.new_line_no)
+        .collect()
+}
@@ -0,0 +1,217 @@
+use crate::config::PluginSettings;
+use crate::core::comment::{compute_comment_id, Category, FixEffort, Severity};
+use crate::core::{Comment, ContextType, LLMContextChunk, UnifiedDiff};
+use crate::plugins::{PreAnalysis, PreAnalyzer};
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::process::Command;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuffSettings {
+    /// Ruff config file; by default ruff discovers `pyproject.toml` / `ruff.toml` itself.
+    #[serde(default)]
+    config: Option<PathBuf>,
+
+    /// Rule selectors passed as `--select`, e.g. `[E, F, S]`.
+    #[serde(default)]
+    select: Vec<String>,
+
+    /// Post diagnostics on changed lines as review comments, not just as prompt context.
+    #[serde(default = "default_true")]
+    post_findings: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize)]
+struct RuffDiagnostic {
+    code: Option<String>,
+    message: String,
+    location: RuffLocation,
+    #[serde(default)]
+    fix: Option<RuffFix>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RuffLocation {
+    row: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct RuffFix {
+    #[serde(default)]
+    message: Option<String>,
+}
+
+pub struct RuffAnalyzer {
+    settings: RuffSettings,
+}
+
+impl RuffAnalyzer {
+    pub fn new(settings: &PluginSettings) -> Result<Self> {
+        Ok(Self {
+            settings: super::parse_settings("ruff", settings)?,
+        })
+    }
+
+    /// Keeps diagnostics on lines added by the diff and converts them into chunks and findings.
+    fn analysis_from_output(&self, diff: &UnifiedDiff, stdout: &str) -> PreAnalysis {
+        let Ok(diagnostics) = serde_json::from_str::<Vec<RuffDiagnostic>>(stdout) else {
+            return PreAnalysis::default();
+        };
+        let added = super::added_lines(diff);
+        let diagnostics: Vec<RuffDiagnostic> = diagnostics
+            .into_iter()
+            .filter(|diagnostic| added.contains(&diagnostic.location.row))
+            .collect();
+        if diagnostics.is_empty() {
+            return PreAnalysis::default();
+        }
+
+        let mut content = String::from("Ruff diagnostics on changed lines:\n");
+        for diagnostic in &diagnostics {
+            content.push_str(&format!(
+                "- Line {} {}: {}\n",
+                diagnostic.location.row,
+                diagnostic.code.as_deref().unwrap_or("ruff"),
+                diagnostic.message
+            ));
+        }
+        let chunks = vec![LLMContextChunk {
+            file_path: diff.file_path.clone(),
+            content,
+            context_type: ContextType::Documentation,
+            line_range: None,
+        }];
+
+        let findings = if self.settings.post_findings {
+            diagnostics
+                .iter()
+                .map(|diagnostic| to_comment(diff, diagnostic))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        PreAnalysis { chunks, findings }
+    }
+}
+
+fn to_comment(diff: &UnifiedDiff, diagnostic: &RuffDiagnostic) -> Comment {
+    let code = diagnostic.code.clone().unwrap_or_default();
+    let prefix: String = code
+        .chars()
+        .take_while(|c| c.is_ascii_alphabetic())
+        .collect();
+    let category = match prefix.as_str() {
+        "F" | "B" | "PLE" => Category::Bug,
+        "S" => Category::Security,
+        "PERF" => Category::Performance,
+        "D" => Category::Documentation,
+        "E" | "W" | "I" | "N" | "Q" | "COM" => Category::Style,
+        "C" | "PLR" => Category::Maintainability,
+        _ => Category::BestPractice,
+    };
+    // Syntax errors and pyflakes/bugbear/bandit diagnostics are rarely false positives.
+    let severity = if code.starts_with("E9") || matches!(prefix.as_str(), "F" | "B" | "S" | "PLE") {
+        Severity::Warning
+    } else {
+        Severity::Info
+    };
+    let content = format!("Ruff {}: {}", code, diagnostic.message);
+
+    Comment {
+        id: compute_comment_id(&diff.file_path, &content, &category),
+        file_path: diff.file_path.clone(),
+        line_number: diagnostic.location.row,
+        content,
+        severity,
+        category,
+        suggestion: diagnostic.fix.as_ref().and_then(|fix| fix.message.clone()),
+        confidence: 1.0,
+        code_suggestion: None,
+        tags: vec!["ruff".to_string(), code],
+        fix_effort: FixEffort::Low,
+        owners: Vec::new(),
+    }
+}
+
+#[async_trait]
+impl PreAnalyzer for RuffAnalyzer {
+    fn id(&self) -> &str {
+        "ruff"
+    }
+
+    fn settings(&self) -> serde_json::Value {
+        serde_json::to_value(&self.settings).unwrap_or_default()
+    }
+
+    async fn run(&self, diff: &UnifiedDiff, repo_path: &str) -> Result<Vec<LLMContextChunk>> {
+        Ok(self.analyze(diff, repo_path).await?.chunks)
+    }
+
+    async fn analyze(&self, diff: &UnifiedDiff, repo_path: &str) -> Result<PreAnalysis> {
+        if diff.file_path.extension().and_then(|ext| ext.to_str()) != Some("py") {
+            return Ok(PreAnalysis::default());
+        }
+
+        let mut command = Command::new("ruff");
+        command.current_dir(repo_path).args([
+            "check",
+            "--output-format=json",
+            "--exit-zero",
+            "--quiet",
+        ]);
+        if let Some(config) = &self.settings.config {
+            command.arg("--config").arg(config);
+        }
+        if !self.settings.select.is_empty() {
+            command.arg("--select").arg(self.settings.select.join(","));
+        }
+
+        match command.arg(&diff.file_path).output() {
+            Ok(output) => {
+                Ok(self.analysis_from_output(diff, &String::from_utf8_lossy(&output.stdout)))
+            }
+            Err(_) => Ok(PreAnalysis::default()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_diagnostics_on_added_lines_to_findings() {
+        let diff = crate::core::DiffParser::parse_unified_diff(
+            "--- a/app.py\n+++ b/app.py\n@@ -1,2 +1,3 @@\n import os\n-x = 1\n+import sys\n+eval(data)\n",
+        )
+        .unwrap()
+        .remove(0);
+        let stdout = r#"[
+            {"code": "F401", "message": "`os` imported but unused", "location": {"row": 1, "column": 8}, "fix": {"message": "Remove unused import: `os`"}},
+            {"code": "F401", "message": "`sys` imported but unused", "location": {"row": 2, "column": 8}, "fix": {"message": "Remove unused import: `sys`"}},
+            {"code": "S307", "message": "Use of possibly insecure function", "location": {"row": 3, "column": 1}, "fix": null}
+        ]"#;
+
+        let analyzer = RuffAnalyzer::new(&PluginSettings::new()).unwrap();
+        let analysis = analyzer.analysis_from_output(&diff, stdout);
+
+        // Line 1 is unchanged context, so its diagnostic is left out.
+        assert_eq!(analysis.findings.len(), 2);
+        assert_eq!(analysis.findings[0].line_number, 2);
+        assert_eq!(analysis.findings[0].category, Category::Bug);
+        assert_eq!(
+            analysis.findings[0].suggestion.as_deref(),
+            Some("Remove unused import: `sys`")
+        );
+        assert_eq!(analysis.findings[1].category, Category::Security);
+        assert!(analysis.chunks[0].content.contains("- Line 3 S307:"));
+        assert!(!analysis.chunks[0].content.contains("`os`"));
+    }
+}
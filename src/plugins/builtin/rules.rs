@@ -0,0 +1,259 @@
+use crate::config::{CustomRule, PluginSettings};
+use crate::core::comment::{compute_comment_id, Category, FixEffort, Severity};
+use crate::core::diff_parser::ChangeType;
+use crate::core::policy::parse_severity;
+use crate::core::{Comment, LLMContextChunk, UnifiedDiff};
+use crate::plugins::{PreAnalysis, PreAnalyzer};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use regex::Regex;
+use serde::Deserialize;
+use std::path::Path;
+
+#[derive(Debug, Deserialize)]
+struct AstGrepMatch {
+    range: AstGrepRange,
+}
+
+#[derive(Debug, Deserialize)]
+struct AstGrepRange {
+    start: AstGrepPosition,
+}
+
+#[derive(Debug, Deserialize)]
+struct AstGrepPosition {
+    /// 0-based.
+    line: usize,
+}
+
+enum Matcher {
+    Regex(Regex),
+    AstGrep(String),
+}
+
+struct CompiledRule {
+    rule: CustomRule,
+    matcher: Matcher,
+    paths: Vec<glob::Pattern>,
+    severity: Severity,
+    category: Category,
+}
+
+/// Reports `plugins.rules` (including rules merged from rule packs) on added lines.
+pub struct RulesAnalyzer {
+    rules: Vec<CompiledRule>,
+}
+
+fn parse_category(value: &str) -> Option<Category> {
+    match value.to_lowercase().replace(['_', ' '], "").as_str() {
+        "bug" => Some(Category::Bug),
+        "security" => Some(Category::Security),
+        "performance" => Some(Category::Performance),
+        "style" => Some(Category::Style),
+        "documentation" => Some(Category::Documentation),
+        "bestpractice" => Some(Category::BestPractice),
+        "maintainability" => Some(Category::Maintainability),
+        "testing" => Some(Category::Testing),
+        "architecture" => Some(Category::Architecture),
+        _ => None,
+    }
+}
+
+impl RulesAnalyzer {
+    pub fn new(rules: &[CustomRule], settings: &PluginSettings) -> Result<Self> {
+        if !settings.is_empty() {
+            anyhow::bail!("Plugin rules does not take settings");
+        }
+        let rules = rules
+            .iter()
+            .map(|rule| {
+                let matcher = match (&rule.pattern, &rule.ast_grep) {
+                    (Some(pattern), None) => Matcher::Regex(
+                        Regex::new(pattern)
+                            .with_context(|| format!("Invalid pattern in rule {}", rule.id))?,
+                    ),
+                    (None, Some(pattern)) => Matcher::AstGrep(pattern.clone()),
+                    _ => anyhow::bail!("Rule {} needs exactly one of pattern or ast_grep", rule.id),
+                };
+                let paths = rule
+                    .paths
+                    .iter()
+                    .map(|path| {
+                        glob::Pattern::new(path)
+                            .with_context(|| format!("Invalid path glob in rule {}", rule.id))
+                    })
+                    .collect::<Result<_>>()?;
+                Ok(CompiledRule {
+                    severity: parse_severity(&rule.severity).with_context(|| {
+                        format!("Invalid severity {} in rule {}", rule.severity, rule.id)
+                    })?,
+                    category: parse_category(&rule.category).with_context(|| {
+                        format!("Invalid category {} in rule {}", rule.category, rule.id)
+                    })?,
+                    rule: rule.clone(),
+                    matcher,
+                    paths,
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { rules })
+    }
+}
+
+/// New-file lines `pattern` matches in `path`, per the `ast-grep` CLI. Empty when ast-grep
+/// is not installed.
+async fn ast_grep_lines(
+    pattern: &str,
+    language: Option<&str>,
+    path: &Path,
+    repo_path: &str,
+) -> Vec<usize> {
+    let mut command = tokio::process::Command::new("ast-grep");
+    command
+        .current_dir(repo_path)
+        .args(["run", "--json=compact", "--pattern", pattern]);
+    if let Some(language) = language {
+        command.args(["--lang", language]);
+    }
+    match command.arg(path).output().await {
+        Ok(output) => serde_json::from_slice::<Vec<AstGrepMatch>>(&output.stdout)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|found| found.range.start.line + 1)
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+fn to_comment(diff: &UnifiedDiff, compiled: &CompiledRule, line: usize) -> Comment {
+    let content = compiled.rule.message.clone();
+    Comment {
+        id: compute_comment_id(&diff.file_path, &content, &compiled.category),
+        file_path: diff.file_path.clone(),
+        line_number: line,
+        content,
+        severity: compiled.severity.clone(),
+        category: compiled.category.clone(),
+        suggestion: compiled.rule.suggestion.clone(),
+        confidence: 1.0,
+        code_suggestion: None,
+        tags: vec!["rule".to_string(), compiled.rule.id.clone()],
+        fix_effort: FixEffort::Low,
+        owners: Vec::new(),
+    }
+}
+
+#[async_trait]
+impl PreAnalyzer for RulesAnalyzer {
+    fn id(&self) -> &str {
+        "rules"
+    }
+
+    fn settings(&self) -> serde_json::Value {
+        serde_json::json!({
+            "rules": self.rules.iter().map(|compiled| &compiled.rule.id).collect::<Vec<_>>()
+        })
+    }
+
+    async fn run(&self, diff: &UnifiedDiff, repo_path: &str) -> Result<Vec<LLMContextChunk>> {
+        Ok(self.analyze(diff, repo_path).await?.chunks)
+    }
+
+    async fn analyze(&self, diff: &UnifiedDiff, repo_path: &str) -> Result<PreAnalysis> {
+        if diff.is_deleted || diff.is_binary {
+            return Ok(PreAnalysis::default());
+        }
+        let added: Vec<(usize, &str)> = diff
+            .hunks
+            .iter()
+            .flat_map(|hunk| &hunk.changes)
+            .filter(|line| matches!(line.change_type, ChangeType::Added))
+            .filter_map(|line| Some((line.new_line_no?, line.content.as_str())))
+            .collect();
+        if added.is_empty() {
+            return Ok(PreAnalysis::default());
+        }
+
+        let mut findings = Vec::new();
+        for compiled in &self.rules {
+            if !compiled.paths.is_empty()
+                && !compiled
+                    .paths
+                    .iter()
+                    .any(|pattern| pattern.matches_path(&diff.file_path))
+            {
+                continue;
+            }
+            match &compiled.matcher {
+                Matcher::Regex(regex) => findings.extend(
+                    added
+                        .iter()
+                        .filter(|(_, content)| regex.is_match(content))
+                        .map(|(line, _)| to_comment(diff, compiled, *line)),
+                ),
+                Matcher::AstGrep(pattern) => {
+                    let lines = ast_grep_lines(
+                        pattern,
+                        compiled.rule.language.as_deref(),
+                        &diff.file_path,
+                        repo_path,
+                    )
+                    .await;
+                    findings.extend(
+                        lines
+                            .into_iter()
+                            .filter(|line| added.iter().any(|(added, _)| added == line))
+                            .map(|line| to_comment(diff, compiled, line)),
+                    );
+                }
+            }
+        }
+
+        Ok(PreAnalysis {
+            chunks: Vec::new(),
+            findings,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn reports_regex_rules_on_added_lines_in_matching_paths() {
+        let rules: Vec<CustomRule> = serde_yaml::from_str(
+            r#"
+- id: no-unwrap
+  pattern: '\.unwrap\(\)'
+  paths: ["src/**/*.rs"]
+  message: Avoid unwrap in services.
+  severity: error
+  category: bug
+- id: no-todo
+  pattern: TODO
+  paths: ["docs/**"]
+  message: Resolve TODOs.
+"#,
+        )
+        .unwrap();
+        let diff = crate::core::DiffParser::parse_unified_diff(
+            "--- a/src/api/handler.rs\n+++ b/src/api/handler.rs\n@@ -1,2 +1,3 @@\n let a = b.unwrap();\n+let c = d.unwrap(); // TODO\n let e = f?;\n",
+        )
+        .unwrap()
+        .remove(0);
+
+        let analysis = RulesAnalyzer::new(&rules, &PluginSettings::new())
+            .unwrap()
+            .analyze(&diff, ".")
+            .await
+            .unwrap();
+
+        assert_eq!(analysis.findings.len(), 1);
+        let finding = &analysis.findings[0];
+        assert_eq!(finding.line_number, 2);
+        assert_eq!(finding.severity, Severity::Error);
+        assert_eq!(finding.category, Category::Bug);
+        assert_eq!(finding.tags, vec!["rule", "no-unwrap"]);
+    }
+}
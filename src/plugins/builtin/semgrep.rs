@@ -1,15 +1,80 @@
+use crate::config::PluginSettings;
 use crate::core::{ContextType, LLMContextChunk, UnifiedDiff};
 use crate::plugins::PreAnalyzer;
 use anyhow::Result;
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::process::Command;
 
-pub struct SemgrepAnalyzer;
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SemgrepSettings {
+    /// Value for `--config`: a registry ruleset (`p/owasp-top-ten`), rules file, or `auto`.
+    #[serde(default = "default_ruleset")]
+    config: String,
+
+    /// Maps semgrep severities (`ERROR`, `WARNING`, `INFO`) to the labels shown to the model.
+    #[serde(default)]
+    severity_mapping: HashMap<String, String>,
+}
+
+fn default_ruleset() -> String {
+    "auto".to_string()
+}
+
+#[derive(Deserialize)]
+struct SemgrepOutput {
+    results: Vec<SemgrepResult>,
+}
+
+#[derive(Deserialize)]
+struct SemgrepResult {
+    check_id: String,
+    start: SemgrepPosition,
+    extra: SemgrepExtra,
+}
+
+#[derive(Deserialize)]
+struct SemgrepPosition {
+    line: usize,
+}
+
+#[derive(Deserialize)]
+struct SemgrepExtra {
+    #[serde(default)]
+    message: String,
+    #[serde(default)]
+    severity: String,
+}
+
+pub struct SemgrepAnalyzer {
+    settings: SemgrepSettings,
+}
 
 impl SemgrepAnalyzer {
-    pub fn new() -> Self {
-        Self
+    pub fn new(settings: &PluginSettings) -> Result<Self> {
+        Ok(Self {
+            settings: super::parse_settings("semgrep", settings)?,
+        })
+    }
+
+    fn summarize(&self, output: &SemgrepOutput) -> String {
+        let mut content = String::from("Semgrep analysis:\n");
+        for result in &output.results {
+            let severity = self
+                .settings
+                .severity_mapping
+                .get(&result.extra.severity)
+                .cloned()
+                .unwrap_or_else(|| result.extra.severity.to_lowercase());
+            content.push_str(&format!(
+                "- Line {} [{}] {}: {}\n",
+                result.start.line, severity, result.check_id, result.extra.message
+            ));
+        }
+        content
     }
 }
 
@@ -19,11 +84,15 @@ impl PreAnalyzer for SemgrepAnalyzer {
         "semgrep"
     }
 
+    fn settings(&self) -> serde_json::Value {
+        serde_json::to_value(&self.settings).unwrap_or_default()
+    }
+
     async fn run(&self, diff: &UnifiedDiff, repo_path: &str) -> Result<Vec<LLMContextChunk>> {
         let file_path = PathBuf::from(repo_path).join(&diff.file_path);
 
         let output = Command::new("semgrep")
-            .arg("--config=auto")
+            .arg(format!("--config={}", self.settings.config))
             .arg("--json")
             .arg("--quiet")
             .arg(file_path.to_string_lossy().as_ref())
@@ -32,18 +101,51 @@ impl PreAnalyzer for SemgrepAnalyzer {
         match output {
             Ok(output) => {
                 let stdout = String::from_utf8_lossy(&output.stdout);
-                if !stdout.trim().is_empty() {
-                    Ok(vec![LLMContextChunk {
-                        file_path: diff.file_path.clone(),
-                        content: format!("Semgrep analysis:\n{}", stdout),
-                        context_type: ContextType::Documentation,
-                        line_range: None,
-                    }])
-                } else {
-                    Ok(Vec::new())
-                }
+                let content = match serde_json::from_str::<SemgrepOutput>(&stdout) {
+                    Ok(parsed) if parsed.results.is_empty() => return Ok(Vec::new()),
+                    Ok(parsed) => self.summarize(&parsed),
+                    Err(_) if !stdout.trim().is_empty() => {
+                        format!("Semgrep analysis:\n{}", stdout)
+                    }
+                    Err(_) => return Ok(Vec::new()),
+                };
+                Ok(vec![LLMContextChunk {
+                    file_path: diff.file_path.clone(),
+                    content,
+                    context_type: ContextType::Documentation,
+                    line_range: None,
+                }])
             }
             Err(_) => Ok(Vec::new()),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summarizes_results_with_mapped_severities() {
+        let settings: PluginSettings = serde_json::from_str(
+            r#"{"config": "p/owasp-top-ten", "severity_mapping": {"ERROR": "critical"}}"#,
+        )
+        .unwrap();
+        let analyzer = SemgrepAnalyzer::new(&settings).unwrap();
+        let output: SemgrepOutput = serde_json::from_str(
+            r#"{"results": [
+                {"check_id": "sqli", "start": {"line": 4}, "extra": {"message": "SQL injection", "severity": "ERROR"}},
+                {"check_id": "weak-hash", "start": {"line": 9}, "extra": {"message": "MD5 is weak", "severity": "WARNING"}}
+            ]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            analyzer.summarize(&output),
+            "Semgrep analysis:\n- Line 4 [critical] sqli: SQL injection\n- Line 9 [warning] weak-hash: MD5 is weak\n"
+        );
+
+        let typo: PluginSettings = serde_json::from_str(r#"{"rulset": "auto"}"#).unwrap();
+        assert!(SemgrepAnalyzer::new(&typo).is_err());
+    }
+}
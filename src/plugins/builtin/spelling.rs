@@ -0,0 +1,398 @@
+use crate::config::PluginSettings;
+use crate::core::comment::{compute_comment_id, Category, FixEffort, Severity};
+use crate::core::diff_parser::ChangeType;
+use crate::core::{Comment, LLMContextChunk, UnifiedDiff};
+use crate::plugins::{PreAnalysis, PreAnalyzer};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::time::Duration;
+
+static MISSPELLINGS: Lazy<HashMap<&'static str, &'static str>> = Lazy::new(|| {
+    include_str!("misspellings.txt")
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| line.split_once("->"))
+        .collect()
+});
+
+static INLINE_CODE: Lazy<Regex> = Lazy::new(|| Regex::new(r"`[^`]*`").unwrap());
+static URL: Lazy<Regex> = Lazy::new(|| Regex::new(r"\b\w+://\S+").unwrap());
+static MARKDOWN_LINK: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"\[[^\]]*\]\(\s*<?([^)\s>]+)>?(?:\s+"[^"]*")?\s*\)"#).unwrap());
+
+const DOC_EXTENSIONS: [&str; 5] = ["md", "markdown", "mdx", "rst", "txt"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SpellingSettings {
+    /// Words never reported, e.g. project names that collide with a known misspelling.
+    #[serde(default)]
+    ignore_words: Vec<String>,
+
+    /// Check relative links in added markdown lines point at files that exist.
+    #[serde(default = "default_true")]
+    check_links: bool,
+
+    /// LanguageTool server (e.g. `http://localhost:8081`) for grammar and spelling checks
+    /// beyond the bundled misspelling list.
+    #[serde(default)]
+    languagetool_url: Option<String>,
+
+    #[serde(default = "default_language")]
+    language: String,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_language() -> String {
+    "en-US".to_string()
+}
+
+#[derive(Debug, Deserialize)]
+struct LanguageToolResponse {
+    #[serde(default)]
+    matches: Vec<LanguageToolMatch>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LanguageToolMatch {
+    message: String,
+    offset: usize,
+    length: usize,
+    #[serde(default)]
+    replacements: Vec<LanguageToolReplacement>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LanguageToolReplacement {
+    value: String,
+}
+
+/// Prose on an added line: markdown text, or the text of a code comment.
+struct ProseLine {
+    line: usize,
+    text: String,
+}
+
+/// Flags typos in documentation and code comments on added lines, plus broken relative
+/// markdown links, as Suggestion-level Documentation comments.
+pub struct SpellingAnalyzer {
+    settings: SpellingSettings,
+    ignored: HashSet<String>,
+    client: Client,
+}
+
+impl SpellingAnalyzer {
+    pub fn new(settings: &PluginSettings) -> Result<Self> {
+        let settings: SpellingSettings = super::parse_settings("spelling", settings)?;
+        let ignored = settings
+            .ignore_words
+            .iter()
+            .map(|word| word.to_lowercase())
+            .collect();
+        let client = Client::builder().timeout(Duration::from_secs(20)).build()?;
+        Ok(Self {
+            settings,
+            ignored,
+            client,
+        })
+    }
+
+    fn typos(&self, prose: &[ProseLine]) -> Vec<(usize, String)> {
+        let mut found = Vec::new();
+        for line in prose {
+            let mut typos = Vec::new();
+            for word in line.text.split(|c: char| !c.is_ascii_alphabetic()) {
+                let lower = word.to_lowercase();
+                if self.ignored.contains(&lower) {
+                    continue;
+                }
+                if let Some(correction) = MISSPELLINGS.get(lower.as_str()) {
+                    typos.push(format!("`{}` → `{}`", word, match_case(word, correction)));
+                }
+            }
+            if !typos.is_empty() {
+                found.push((line.line, format!("Possible typo: {}.", typos.join(", "))));
+            }
+        }
+        found
+    }
+
+    async fn languagetool(&self, url: &str, prose: &[ProseLine]) -> Result<Vec<(usize, String)>> {
+        let text = prose
+            .iter()
+            .map(|line| line.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let response: LanguageToolResponse = self
+            .client
+            .post(format!("{}/v2/check", url.trim_end_matches('/')))
+            .form(&[
+                ("text", text.as_str()),
+                ("language", &self.settings.language),
+            ])
+            .send()
+            .await?
+            .error_for_status()
+            .context("LanguageTool check failed")?
+            .json()
+            .await?;
+
+        // Map character offsets in the joined text back to diff lines.
+        let mut starts = Vec::with_capacity(prose.len());
+        let mut offset = 0;
+        for line in prose {
+            starts.push(offset);
+            offset += line.text.chars().count() + 1;
+        }
+        Ok(response
+            .matches
+            .into_iter()
+            .filter_map(|found| {
+                let index = starts.partition_point(|&start| start <= found.offset) - 1;
+                let line = prose.get(index)?;
+                let word: String = line
+                    .text
+                    .chars()
+                    .skip(found.offset - starts[index])
+                    .take(found.length)
+                    .collect();
+                if self.ignored.contains(&word.to_lowercase()) {
+                    return None;
+                }
+                let message = match found.replacements.first() {
+                    Some(replacement) => {
+                        format!("{} (`{}` → `{}`)", found.message, word, replacement.value)
+                    }
+                    None => found.message,
+                };
+                Some((line.line, message))
+            })
+            .collect())
+    }
+}
+
+fn match_case(word: &str, correction: &str) -> String {
+    if word.chars().all(|c| c.is_ascii_uppercase()) && word.len() > 1 {
+        correction.to_uppercase()
+    } else if word.starts_with(|c: char| c.is_ascii_uppercase()) {
+        let mut chars = correction.chars();
+        chars
+            .next()
+            .map(|first| first.to_uppercase().chain(chars).collect())
+            .unwrap_or_default()
+    } else {
+        correction.to_string()
+    }
+}
+
+fn is_doc_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| DOC_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+fn comment_markers(path: &Path) -> &'static [&'static str] {
+    match path.extension().and_then(|ext| ext.to_str()).unwrap_or("") {
+        "py" | "rb" | "sh" | "bash" | "yml" | "yaml" | "toml" | "r" | "pl" => &["#"],
+        "sql" | "lua" | "hs" => &["--"],
+        "rs" | "go" | "js" | "jsx" | "ts" | "tsx" | "mjs" | "java" | "kt" | "swift" | "c" | "h"
+        | "cc" | "cpp" | "hpp" | "cs" | "php" | "scala" | "dart" => {
+            &["///", "//!", "//", "/**", "/*", "* "]
+        }
+        _ => &[],
+    }
+}
+
+/// Removes spans that are not prose: inline code and URLs.
+fn strip_code(text: &str) -> String {
+    URL.replace_all(&INLINE_CODE.replace_all(text, " "), " ")
+        .into_owned()
+}
+
+/// Added prose lines: markdown outside fenced code blocks, or comment text in source files.
+fn prose_lines(diff: &UnifiedDiff) -> Vec<ProseLine> {
+    let doc = is_doc_file(&diff.file_path);
+    let markers = comment_markers(&diff.file_path);
+    let mut prose = Vec::new();
+    for hunk in &diff.hunks {
+        let mut in_fence = false;
+        for line in &hunk.changes {
+            if matches!(line.change_type, ChangeType::Removed) {
+                continue;
+            }
+            let trimmed = line.content.trim_start();
+            if doc && (trimmed.starts_with("```") || trimmed.starts_with("~~~")) {
+                in_fence = !in_fence;
+                continue;
+            }
+            let (ChangeType::Added, Some(line_no)) = (&line.change_type, line.new_line_no) else {
+                continue;
+            };
+            let text = if doc {
+                (!in_fence).then_some(trimmed)
+            } else {
+                markers
+                    .iter()
+                    .find_map(|marker| trimmed.strip_prefix(marker))
+            };
+            if let Some(text) = text {
+                prose.push(ProseLine {
+                    line: line_no,
+                    text: strip_code(text),
+                });
+            }
+        }
+    }
+    prose
+}
+
+/// Relative link targets on added markdown lines that do not exist in the repository.
+fn broken_links(diff: &UnifiedDiff, repo_path: &str) -> Vec<(usize, String)> {
+    let base = diff.file_path.parent().unwrap_or_else(|| Path::new(""));
+    let mut broken = Vec::new();
+    for hunk in &diff.hunks {
+        for line in &hunk.changes {
+            let (ChangeType::Added, Some(line_no)) = (&line.change_type, line.new_line_no) else {
+                continue;
+            };
+            for captures in MARKDOWN_LINK.captures_iter(&line.content) {
+                let target = &captures[1];
+                if target.starts_with('#') || target.starts_with("//") || target.contains(':') {
+                    continue;
+                }
+                let path = target.split(['#', '?']).next().unwrap_or(target);
+                let resolved = match path.strip_prefix('/') {
+                    Some(rooted) => Path::new(repo_path).join(rooted),
+                    None => Path::new(repo_path).join(base).join(path),
+                };
+                if !resolved.exists() {
+                    broken.push((
+                        line_no,
+                        format!("Broken link: `{}` does not exist.", target),
+                    ));
+                }
+            }
+        }
+    }
+    broken
+}
+
+fn to_comment(diff: &UnifiedDiff, line: usize, content: String, tag: &str) -> Comment {
+    Comment {
+        id: compute_comment_id(&diff.file_path, &content, &Category::Documentation),
+        file_path: diff.file_path.clone(),
+        line_number: line,
+        content,
+        severity: Severity::Suggestion,
+        category: Category::Documentation,
+        suggestion: None,
+        confidence: 0.9,
+        code_suggestion: None,
+        tags: vec!["spelling".to_string(), tag.to_string()],
+        fix_effort: FixEffort::Low,
+        owners: Vec::new(),
+    }
+}
+
+#[async_trait]
+impl PreAnalyzer for SpellingAnalyzer {
+    fn id(&self) -> &str {
+        "spelling"
+    }
+
+    fn settings(&self) -> serde_json::Value {
+        serde_json::to_value(&self.settings).unwrap_or_default()
+    }
+
+    async fn run(&self, diff: &UnifiedDiff, repo_path: &str) -> Result<Vec<LLMContextChunk>> {
+        Ok(self.analyze(diff, repo_path).await?.chunks)
+    }
+
+    async fn analyze(&self, diff: &UnifiedDiff, repo_path: &str) -> Result<PreAnalysis> {
+        if diff.is_deleted || diff.is_binary {
+            return Ok(PreAnalysis::default());
+        }
+        let prose = prose_lines(diff);
+        let mut findings: Vec<Comment> = self
+            .typos(&prose)
+            .into_iter()
+            .map(|(line, content)| to_comment(diff, line, content, "typo"))
+            .collect();
+
+        if let (Some(url), false) = (&self.settings.languagetool_url, prose.is_empty()) {
+            match self.languagetool(url, &prose).await {
+                Ok(matches) => findings.extend(
+                    matches
+                        .into_iter()
+                        .map(|(line, content)| to_comment(diff, line, content, "languagetool")),
+                ),
+                Err(err) => tracing::warn!("LanguageTool check skipped: {:#}", err),
+            }
+        }
+        if self.settings.check_links && is_doc_file(&diff.file_path) {
+            findings.extend(
+                broken_links(diff, repo_path)
+                    .into_iter()
+                    .map(|(line, content)| to_comment(diff, line, content, "broken-link")),
+            );
+        }
+
+        Ok(PreAnalysis {
+            chunks: Vec::new(),
+            findings,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn flags_typos_in_prose_and_broken_links() {
+        let repo = tempfile::tempdir().unwrap();
+        std::fs::create_dir(repo.path().join("docs")).unwrap();
+        std::fs::write(repo.path().join("docs/setup.md"), "# Setup\n").unwrap();
+        let readme = crate::core::DiffParser::parse_unified_diff(
+            "--- a/README.md\n+++ b/README.md\n@@ -1,1 +1,6 @@\n # Project\n+Teh config is seperate from `recieve`.\n+```\n+let wich = 1;\n+```\n+See [setup](docs/setup.md#install) and [usage](docs/usage.md).\n",
+        )
+        .unwrap()
+        .remove(0);
+        let source = crate::core::DiffParser::parse_unified_diff(
+            "--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1,1 +1,3 @@\n fn a() {}\n+// Retreive the value untill ready.\n+let recieve = 1;\n",
+        )
+        .unwrap()
+        .remove(0);
+
+        let analyzer = SpellingAnalyzer::new(&PluginSettings::new()).unwrap();
+        let repo_path = repo.path().to_string_lossy();
+        let contents = |analysis: PreAnalysis| -> Vec<String> {
+            analysis
+                .findings
+                .into_iter()
+                .map(|finding| format!("{}: {}", finding.line_number, finding.content))
+                .collect()
+        };
+
+        assert_eq!(
+            contents(analyzer.analyze(&readme, &repo_path).await.unwrap()),
+            vec![
+                "2: Possible typo: `Teh` → `The`, `seperate` → `separate`.",
+                "6: Broken link: `docs/usage.md` does not exist.",
+            ]
+        );
+        assert_eq!(
+            contents(analyzer.analyze(&source, &repo_path).await.unwrap()),
+            vec!["2: Possible typo: `Retreive` → `Retrieve`, `untill` → `until`."]
+        );
+    }
+}
@@ -0,0 +1,185 @@
+use crate::config::PluginSettings;
+use crate::core::comment::CodeSuggestion;
+use crate::core::Comment;
+use crate::core::{suggestion, syntax};
+use crate::plugins::PostProcessor;
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum OnFailure {
+    /// Remove the code suggestion and keep the comment.
+    Drop,
+    /// Keep the suggestion, tagged `unverified-suggestion`.
+    Downgrade,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SuggestionCheckSettings {
+    /// Reject suggestions that turn a file tree-sitter parses cleanly into one it cannot.
+    #[serde(default = "default_syntax_check")]
+    syntax_check: bool,
+
+    #[serde(default = "default_on_failure")]
+    on_failure: OnFailure,
+}
+
+fn default_syntax_check() -> bool {
+    true
+}
+
+fn default_on_failure() -> OnFailure {
+    OnFailure::Drop
+}
+
+/// Applies each code suggestion to a copy of the reviewed file, so only suggestions that
+/// replace real code (and still parse) are posted. Verified suggestions get an accurate diff.
+pub struct SuggestionValidator {
+    settings: SuggestionCheckSettings,
+}
+
+impl SuggestionValidator {
+    pub fn new(settings: &PluginSettings) -> Result<Self> {
+        Ok(Self {
+            settings: super::parse_settings("suggestion_check", settings)?,
+        })
+    }
+
+    /// The suggestion rewritten against the file's actual lines, or why it does not apply.
+    fn verify(
+        &self,
+        path: &Path,
+        source: &str,
+        line: usize,
+        code: &CodeSuggestion,
+    ) -> std::result::Result<CodeSuggestion, &'static str> {
+        let lines: Vec<&str> = source.lines().collect();
+        let placement = suggestion::place(&lines, line, code)?;
+        let mut patched = placement.patch(&lines).join("\n");
+        if source.ends_with('\n') {
+            patched.push('\n');
+        }
+
+        if self.settings.syntax_check
+            && syntax::has_errors(path, source) == Some(false)
+            && syntax::has_errors(path, &patched) == Some(true)
+        {
+            return Err("the patched file no longer parses");
+        }
+
+        let old_block = lines[placement.start..placement.end].join("\n");
+        let new_block = placement.replacement.join("\n");
+        Ok(CodeSuggestion {
+            diff: suggestion::block_diff(placement.start + 1, &old_block, &new_block),
+            original_code: old_block,
+            suggested_code: new_block,
+            explanation: code.explanation.clone(),
+        })
+    }
+}
+
+#[async_trait]
+impl PostProcessor for SuggestionValidator {
+    fn id(&self) -> &str {
+        "suggestion_check"
+    }
+
+    fn settings(&self) -> serde_json::Value {
+        serde_json::to_value(&self.settings).unwrap_or_default()
+    }
+
+    async fn run(&self, mut comments: Vec<Comment>, repo_path: &str) -> Result<Vec<Comment>> {
+        for comment in &mut comments {
+            let Some(code) = &comment.code_suggestion else {
+                continue;
+            };
+            let source = tokio::fs::read_to_string(Path::new(repo_path).join(&comment.file_path))
+                .await
+                .map_err(|_| "the file could not be read");
+            let verified = source.and_then(|source| {
+                self.verify(&comment.file_path, &source, comment.line_number, code)
+            });
+            match (verified, self.settings.on_failure) {
+                (Ok(code), _) => comment.code_suggestion = Some(code),
+                (Err(_), OnFailure::Drop) => comment.code_suggestion = None,
+                (Err(reason), OnFailure::Downgrade) => {
+                    if let Some(code) = &mut comment.code_suggestion {
+                        code.explanation =
+                            format!("{} (Unverified: {}.)", code.explanation.trim_end(), reason);
+                    }
+                    comment.tags.push("unverified-suggestion".to_string());
+                }
+            }
+        }
+        Ok(comments)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::comment::{Category, FixEffort, Severity};
+
+    fn comment(line: usize, original: &str, suggested: &str) -> Comment {
+        Comment {
+            id: "cmt_1".to_string(),
+            file_path: "src/lib.rs".into(),
+            line_number: line,
+            content: "Avoid unwrap.".to_string(),
+            severity: Severity::Warning,
+            category: Category::Bug,
+            suggestion: None,
+            confidence: 0.9,
+            code_suggestion: Some(CodeSuggestion {
+                original_code: original.to_string(),
+                suggested_code: suggested.to_string(),
+                explanation: "Propagate the error.".to_string(),
+                diff: format!("- original\n+ {}", suggested),
+            }),
+            tags: Vec::new(),
+            fix_effort: FixEffort::Low,
+            owners: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn keeps_only_suggestions_that_apply_and_parse() {
+        let repo = tempfile::tempdir().unwrap();
+        std::fs::create_dir(repo.path().join("src")).unwrap();
+        std::fs::write(
+            repo.path().join("src/lib.rs"),
+            "fn load() -> Result<u8> {\n    let raw = read().unwrap();\n    Ok(raw)\n}\n",
+        )
+        .unwrap();
+        let repo_path = repo.path().to_str().unwrap();
+        let comments = vec![
+            comment(2, "let raw = read().unwrap();", "let raw = read()?;"),
+            comment(2, "let raw = fetch().unwrap();", "let raw = fetch()?;"),
+            comment(2, "let raw = read().unwrap();", "let raw = read(?;"),
+        ];
+
+        let validator = SuggestionValidator::new(&PluginSettings::new()).unwrap();
+        let checked = validator.run(comments.clone(), repo_path).await.unwrap();
+        let code = checked[0].code_suggestion.as_ref().unwrap();
+        assert_eq!(code.suggested_code, "    let raw = read()?;");
+        assert_eq!(
+            code.diff,
+            "@@ -2,1 +2,1 @@\n-    let raw = read().unwrap();\n+    let raw = read()?;"
+        );
+        assert!(checked[1].code_suggestion.is_none());
+        assert!(checked[2].code_suggestion.is_none());
+
+        let settings: PluginSettings =
+            serde_json::from_str(r#"{"on_failure": "downgrade", "syntax_check": false}"#).unwrap();
+        let validator = SuggestionValidator::new(&settings).unwrap();
+        let checked = validator.run(comments, repo_path).await.unwrap();
+        assert!(checked[1]
+            .tags
+            .contains(&"unverified-suggestion".to_string()));
+        assert!(checked[2].tags.is_empty());
+    }
+}
@@ -0,0 +1,191 @@
+use crate::config::{CommandPluginConfig, CommandPluginStage};
+use crate::core::{Comment, LLMContextChunk, UnifiedDiff};
+use crate::plugins::{PostProcessor, PreAnalyzer};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Serialize};
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+/// Runs an external program as a plugin: the input is written to its stdin as JSON and its
+/// stdout is parsed as JSON (context chunks for pre-analyzers, comments for post-processors).
+pub struct CommandPlugin {
+    config: CommandPluginConfig,
+    argv: Vec<String>,
+    patterns: Vec<glob::Pattern>,
+}
+
+impl CommandPlugin {
+    pub fn new(config: CommandPluginConfig) -> Result<Self> {
+        let argv = shell_words::split(&config.command)
+            .map_err(|err| anyhow::anyhow!("Invalid command for plugin {}: {}", config.id, err))?;
+        if argv.is_empty() {
+            anyhow::bail!("Plugin {} has an empty command", config.id);
+        }
+        let patterns = config
+            .applies_to
+            .iter()
+            .map(|pattern| glob::Pattern::new(pattern))
+            .collect::<Result<Vec<_>, _>>()
+            .with_context(|| format!("Invalid applies_to glob for plugin {}", config.id))?;
+        Ok(Self {
+            config,
+            argv,
+            patterns,
+        })
+    }
+
+    pub fn stage(&self) -> CommandPluginStage {
+        self.config.stage
+    }
+
+    fn applies_to(&self, path: &Path) -> bool {
+        self.patterns.is_empty()
+            || self
+                .patterns
+                .iter()
+                .any(|pattern| pattern.matches_path(path))
+    }
+
+    async fn exchange<I: Serialize, O: DeserializeOwned + Default>(
+        &self,
+        input: &I,
+        repo_path: &str,
+    ) -> Result<O> {
+        let input = serde_json::to_vec(input)?;
+        let mut child = Command::new(&self.argv[0])
+            .args(&self.argv[1..])
+            .current_dir(repo_path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("Failed to start `{}`", self.config.command))?;
+
+        // Feed stdin concurrently so a plugin that streams output early cannot deadlock.
+        let mut stdin = child.stdin.take().context("plugin stdin unavailable")?;
+        let writer = tokio::spawn(async move { stdin.write_all(&input).await });
+
+        let output = tokio::time::timeout(
+            Duration::from_secs(self.config.timeout_secs),
+            child.wait_with_output(),
+        )
+        .await
+        .with_context(|| format!("timed out after {}s", self.config.timeout_secs))??;
+        // A plugin may exit without reading its input; that is not an error.
+        let _ = writer.await;
+
+        if !output.status.success() {
+            anyhow::bail!(
+                "exited with {}: {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        if stdout.trim().is_empty() {
+            return Ok(O::default());
+        }
+        serde_json::from_str(&stdout).context("plugin printed invalid JSON")
+    }
+}
+
+#[async_trait]
+impl PreAnalyzer for CommandPlugin {
+    fn id(&self) -> &str {
+        &self.config.id
+    }
+
+    fn settings(&self) -> serde_json::Value {
+        serde_json::to_value(&self.config).unwrap_or_default()
+    }
+
+    async fn run(&self, diff: &UnifiedDiff, repo_path: &str) -> Result<Vec<LLMContextChunk>> {
+        if !self.applies_to(&diff.file_path) {
+            return Ok(Vec::new());
+        }
+        self.exchange(diff, repo_path).await
+    }
+}
+
+#[async_trait]
+impl PostProcessor for CommandPlugin {
+    fn id(&self) -> &str {
+        &self.config.id
+    }
+
+    fn settings(&self) -> serde_json::Value {
+        serde_json::to_value(&self.config).unwrap_or_default()
+    }
+
+    /// Only comments on matching files go through the plugin; the rest are kept as-is.
+    async fn run(&self, comments: Vec<Comment>, repo_path: &str) -> Result<Vec<Comment>> {
+        let (selected, mut untouched): (Vec<_>, Vec<_>) = comments
+            .into_iter()
+            .partition(|comment| self.applies_to(&comment.file_path));
+        if selected.is_empty() {
+            return Ok(untouched);
+        }
+        let processed: Vec<Comment> = self.exchange(&selected, repo_path).await?;
+        untouched.extend(processed);
+        Ok(untouched)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::core::comment::{test_comment, Category, Severity};
+    use std::path::PathBuf;
+
+    fn plugin(yaml: &str) -> CommandPlugin {
+        CommandPlugin::new(serde_yaml::from_str(yaml).unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn pipes_json_through_external_commands() {
+        let analyzer = plugin(
+            r#"
+id: notes
+command: sh -c 'cat > /dev/null; echo "[{\"file_path\":\"a.py\",\"content\":\"from plugin\",\"context_type\":\"Documentation\",\"line_range\":null}]"'
+applies_to: ["*.py"]
+"#,
+        );
+        let diff = crate::core::DiffParser::parse_unified_diff(
+            "--- a/a.py\n+++ b/a.py\n@@ -1 +1 @@\n-a\n+b\n",
+        )
+        .unwrap()
+        .remove(0);
+        let chunks = PreAnalyzer::run(&analyzer, &diff, ".").await.unwrap();
+        assert_eq!(chunks[0].content, "from plugin");
+
+        let mut other = diff.clone();
+        other.file_path = PathBuf::from("a.rs");
+        assert!(PreAnalyzer::run(&analyzer, &other, ".")
+            .await
+            .unwrap()
+            .is_empty());
+
+        let comment = |path: &str| Comment {
+            severity: Severity::Info,
+            category: Category::Style,
+            confidence: 0.5,
+            ..test_comment(path, 1, "draft")
+        };
+        let rewriter = plugin(
+            "id: rewrite\ncommand: sed s/draft/final/\napplies_to: [\"*.py\"]\nstage: post_processor\n",
+        );
+        let comments = PostProcessor::run(&rewriter, vec![comment("a.rs"), comment("a.py")], ".")
+            .await
+            .unwrap();
+        assert_eq!(comments[0].content, "draft");
+        assert_eq!(comments[1].content, "final");
+
+        let slow = plugin("id: slow\ncommand: sleep 5\ntimeout_secs: 0\n");
+        assert!(PreAnalyzer::run(&slow, &diff, ".").await.is_err());
+    }
+}
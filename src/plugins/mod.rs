@@ -1,7 +1,10 @@
 pub mod builtin;
+pub mod command;
 pub mod plugin;
 pub mod post_processor;
 pub mod pre_analyzer;
+#[cfg(feature = "wasm-plugins")]
+pub mod wasm;
 
 pub use post_processor::PostProcessor;
-pub use pre_analyzer::PreAnalyzer;
+pub use pre_analyzer::{PreAnalysis, PreAnalyzer};
@@ -1,9 +1,11 @@
-use crate::config::PluginConfig;
-use crate::core::{Comment, LLMContextChunk, UnifiedDiff};
-use crate::plugins::{PostProcessor, PreAnalyzer};
+use crate::config::{CommandPluginStage, PluginConfig, PluginSettings};
+use crate::core::{Comment, UnifiedDiff};
+use crate::plugins::{PostProcessor, PreAnalysis, PreAnalyzer};
 use anyhow::Result;
 use async_trait::async_trait;
+use serde::Serialize;
 use std::collections::HashMap;
+use std::path::Path;
 use std::sync::Arc;
 
 #[async_trait]
@@ -17,6 +19,14 @@ pub trait Plugin: Send + Sync {
     async fn as_post_processor(&self) -> Option<Box<dyn PostProcessor>>;
 }
 
+#[derive(Debug, Serialize)]
+pub struct LoadedPlugin {
+    pub id: String,
+    pub stage: &'static str,
+    pub settings: serde_json::Value,
+}
+
+#[derive(Default)]
 pub struct PluginManager {
     _plugins: HashMap<String, Arc<dyn Plugin>>,
     pre_analyzers: Vec<Arc<dyn PreAnalyzer>>,
@@ -33,19 +43,170 @@ impl PluginManager {
     }
 
     pub async fn load_builtin_plugins(&mut self, config: &PluginConfig) -> Result<()> {
+        use crate::plugins::builtin::{
+            BlameAnalyzer, BreakingChangeAnalyzer, CommentCalibrator, CommentClusterer,
+            ComplexityAnalyzer, CoverageAnalyzer, DependencyAuditAnalyzer,
+            DependencyImpactAnalyzer, DocDriftAnalyzer, DuplicateFilter, EslintAnalyzer,
+            GolangciAnalyzer, IacAnalyzer, MissingTestsAnalyzer, RuffAnalyzer, RulesAnalyzer,
+            SecretScanAnalyzer, SecretsDetector, SemgrepAnalyzer, SpellingAnalyzer,
+            SuggestionValidator,
+        };
+
+        let empty = PluginSettings::new();
+        let settings = |id: &str| config.settings.get(id).unwrap_or(&empty);
         if config.eslint {
-            self.register_pre_analyzer(Arc::new(crate::plugins::builtin::EslintAnalyzer::new()));
+            self.register_pre_analyzer(Arc::new(EslintAnalyzer::new(settings("eslint"))?));
         }
         if config.semgrep {
-            self.register_pre_analyzer(Arc::new(crate::plugins::builtin::SemgrepAnalyzer::new()));
+            self.register_pre_analyzer(Arc::new(SemgrepAnalyzer::new(settings("semgrep"))?));
+        }
+        if config.ruff {
+            self.register_pre_analyzer(Arc::new(RuffAnalyzer::new(settings("ruff"))?));
+        }
+        if config.secrets {
+            self.register_pre_analyzer(Arc::new(SecretsDetector::new(settings("secrets"))?));
+        }
+        if config.secret_scan {
+            let analyzer = SecretScanAnalyzer::new(settings("secret_scan"))?;
+            self.register_pre_analyzer(Arc::new(if config.secrets {
+                analyzer.without_fallback()
+            } else {
+                analyzer
+            }));
+        }
+        if config.iac {
+            self.register_pre_analyzer(Arc::new(IacAnalyzer::new(settings("iac"))?));
+        }
+        if config.complexity {
+            self.register_pre_analyzer(Arc::new(ComplexityAnalyzer::new(settings("complexity"))?));
+        }
+        if config.missing_tests {
+            self.register_pre_analyzer(Arc::new(MissingTestsAnalyzer::new(settings(
+                "missing_tests",
+            ))?));
+        }
+        if config.doc_drift {
+            self.register_pre_analyzer(Arc::new(DocDriftAnalyzer::new(settings("doc_drift"))?));
+        }
+        if config.spelling {
+            self.register_pre_analyzer(Arc::new(SpellingAnalyzer::new(settings("spelling"))?));
+        }
+        if config.golangci {
+            self.register_pre_analyzer(Arc::new(GolangciAnalyzer::new(settings("golangci"))?));
+        }
+        if config.dependency_impact {
+            self.register_pre_analyzer(Arc::new(DependencyImpactAnalyzer::new(settings(
+                "dependency_impact",
+            ))?));
+        }
+        if config.dependency_audit {
+            self.register_pre_analyzer(Arc::new(DependencyAuditAnalyzer::new(settings(
+                "dependency_audit",
+            ))?));
+        }
+        if config.breaking_changes {
+            self.register_pre_analyzer(Arc::new(BreakingChangeAnalyzer::new(settings(
+                "breaking_changes",
+            ))?));
+        }
+        if config.blame {
+            self.register_pre_analyzer(Arc::new(BlameAnalyzer::new(settings("blame"))?));
+        }
+        if let Some(path) = &config.coverage_report {
+            let report = Arc::new(crate::core::CoverageReport::load(path)?);
+            self.register_pre_analyzer(Arc::new(CoverageAnalyzer::new(
+                report,
+                settings("coverage"),
+            )?));
+        }
+        if config.suggestion_check {
+            self.register_post_processor(Arc::new(SuggestionValidator::new(settings(
+                "suggestion_check",
+            ))?));
         }
         if config.duplicate_filter {
-            self.register_post_processor(Arc::new(crate::plugins::builtin::DuplicateFilter::new()));
+            self.register_post_processor(Arc::new(DuplicateFilter::new(settings(
+                "duplicate_filter",
+            ))?));
+        }
+        if config.comment_clusters {
+            self.register_post_processor(Arc::new(CommentClusterer::new(settings(
+                "comment_clusters",
+            ))?));
+        }
+        if config.calibration {
+            self.register_post_processor(Arc::new(CommentCalibrator::new(settings(
+                "calibration",
+            ))?));
+        }
+        if !config.rules.is_empty() {
+            self.register_pre_analyzer(Arc::new(RulesAnalyzer::new(
+                &config.rules,
+                settings("rules"),
+            )?));
+        }
+        for command in &config.commands {
+            let plugin = Arc::new(crate::plugins::command::CommandPlugin::new(
+                command.clone(),
+            )?);
+            match plugin.stage() {
+                CommandPluginStage::PreAnalyzer => self.register_pre_analyzer(plugin),
+                CommandPluginStage::PostProcessor => self.register_post_processor(plugin),
+            }
+        }
+        if let Some(dir) = &config.plugins_dir {
+            self.load_wasm_plugins(dir)?;
+        }
+
+        let loaded: Vec<LoadedPlugin> = self.loaded_plugins();
+        for id in config.settings.keys() {
+            if !loaded.iter().any(|plugin| &plugin.id == id) {
+                tracing::warn!("Ignoring settings for plugin {}: it is not loaded", id);
+            }
         }
 
         Ok(())
     }
 
+    /// Loaded plugins in run order, pre-analyzers first.
+    pub fn loaded_plugins(&self) -> Vec<LoadedPlugin> {
+        let pre = self.pre_analyzers.iter().map(|plugin| LoadedPlugin {
+            id: plugin.id().to_string(),
+            stage: "pre_analyzer",
+            settings: plugin.settings(),
+        });
+        let post = self.post_processors.iter().map(|plugin| LoadedPlugin {
+            id: plugin.id().to_string(),
+            stage: "post_processor",
+            settings: plugin.settings(),
+        });
+        pre.chain(post).collect()
+    }
+
+    #[cfg(feature = "wasm-plugins")]
+    fn load_wasm_plugins(&mut self, dir: &Path) -> Result<()> {
+        use crate::plugins::wasm::{self, Capability, WasmPostProcessor, WasmPreAnalyzer};
+
+        for plugin in wasm::load_plugins(dir)? {
+            if plugin.has_capability(Capability::PreAnalyzer) {
+                self.register_pre_analyzer(Arc::new(WasmPreAnalyzer(plugin.clone())));
+            }
+            if plugin.has_capability(Capability::PostProcessor) {
+                self.register_post_processor(Arc::new(WasmPostProcessor(plugin)));
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(not(feature = "wasm-plugins"))]
+    fn load_wasm_plugins(&mut self, dir: &Path) -> Result<()> {
+        tracing::warn!(
+            "Ignoring plugins_dir {}: diffscope was built without the `wasm-plugins` feature",
+            dir.display()
+        );
+        Ok(())
+    }
+
     pub fn register_pre_analyzer(&mut self, analyzer: Arc<dyn PreAnalyzer>) {
         self.pre_analyzers.push(analyzer);
     }
@@ -54,23 +215,51 @@ impl PluginManager {
         self.post_processors.push(processor);
     }
 
+    pub async fn begin_review(&self, diffs: &[UnifiedDiff]) {
+        for analyzer in &self.pre_analyzers {
+            analyzer.begin_review(diffs).await;
+        }
+    }
+
     pub async fn run_pre_analyzers(
         &self,
         diff: &UnifiedDiff,
         repo_path: &str,
-    ) -> Result<Vec<LLMContextChunk>> {
-        let mut all_chunks = Vec::new();
+    ) -> Result<PreAnalysis> {
+        let mut combined = PreAnalysis::default();
 
         for analyzer in &self.pre_analyzers {
-            match analyzer.run(diff, repo_path).await {
-                Ok(chunks) => all_chunks.extend(chunks),
+            match analyzer.analyze(diff, repo_path).await {
+                Ok(analysis) => {
+                    combined.chunks.extend(analysis.chunks);
+                    combined.findings.extend(analysis.findings);
+                }
                 Err(e) => {
                     tracing::warn!("Pre-analyzer {} failed: {}", analyzer.id(), e);
                 }
             }
         }
 
-        Ok(all_chunks)
+        Ok(combined)
+    }
+
+    /// Findings of the analyzers that audit lockfiles, for a lockfile left out of the model
+    /// review.
+    pub async fn run_lockfile_analyzers(
+        &self,
+        diff: &UnifiedDiff,
+        repo_path: &str,
+    ) -> Vec<Comment> {
+        let mut findings = Vec::new();
+        for analyzer in self.pre_analyzers.iter().filter(|a| a.audits_lockfiles()) {
+            match analyzer.analyze(diff, repo_path).await {
+                Ok(analysis) => findings.extend(analysis.findings),
+                Err(e) => {
+                    tracing::warn!("Pre-analyzer {} failed: {}", analyzer.id(), e);
+                }
+            }
+        }
+        findings
     }
 
     pub async fn run_post_processors(
@@ -103,12 +292,36 @@ mod tests {
         let config = PluginConfig {
             eslint: false,
             semgrep: true,
+            ruff: false,
+            golangci: false,
+            iac: false,
+            complexity: false,
+            missing_tests: false,
+            doc_drift: false,
+            spelling: false,
+            secret_scan: false,
+            secrets: false,
             duplicate_filter: false,
+            comment_clusters: false,
+            calibration: false,
+            suggestion_check: false,
+            dependency_impact: false,
+            dependency_audit: false,
+            breaking_changes: false,
+            blame: false,
+            coverage_report: None,
+            plugins_dir: None,
+            commands: Vec::new(),
+            rules: Vec::new(),
+            settings: serde_yaml::from_str("semgrep: { config: p/owasp-top-ten }").unwrap(),
         };
 
         manager.load_builtin_plugins(&config).await.unwrap();
 
         assert_eq!(manager.pre_analyzers.len(), 1);
         assert_eq!(manager.post_processors.len(), 0);
+        let loaded = manager.loaded_plugins();
+        assert_eq!(loaded[0].id, "semgrep");
+        assert_eq!(loaded[0].settings["config"], "p/owasp-top-ten");
     }
 }
@@ -5,5 +5,10 @@ use async_trait::async_trait;
 #[async_trait]
 pub trait PostProcessor: Send + Sync {
     fn id(&self) -> &str;
+
+    /// Effective settings, shown by `diffscope plugins list`.
+    fn settings(&self) -> serde_json::Value {
+        serde_json::Value::Null
+    }
     async fn run(&self, comments: Vec<Comment>, repo_path: &str) -> Result<Vec<Comment>>;
 }
@@ -1,9 +1,41 @@
-use crate::core::{LLMContextChunk, UnifiedDiff};
+use crate::core::{Comment, LLMContextChunk, UnifiedDiff};
 use anyhow::Result;
 use async_trait::async_trait;
 
+/// Output of a pre-analyzer: prompt context, plus deterministic findings (e.g. linter
+/// diagnostics on changed lines) that are posted directly alongside the model's comments.
+#[derive(Debug, Default)]
+pub struct PreAnalysis {
+    pub chunks: Vec<LLMContextChunk>,
+    pub findings: Vec<Comment>,
+}
+
 #[async_trait]
 pub trait PreAnalyzer: Send + Sync {
     fn id(&self) -> &str;
+
+    /// Effective settings, shown by `diffscope plugins list`.
+    fn settings(&self) -> serde_json::Value {
+        serde_json::Value::Null
+    }
+
+    /// Called once with every file in the review before any `analyze` call, for analyzers
+    /// that reason across files.
+    async fn begin_review(&self, _diffs: &[UnifiedDiff]) {}
+
+    /// Whether `analyze` also runs on lockfiles that `languages.auto_skip` keeps out of the
+    /// model review, for analyzers whose findings come from the lockfile itself.
+    fn audits_lockfiles(&self) -> bool {
+        false
+    }
+
     async fn run(&self, diff: &UnifiedDiff, repo_path: &str) -> Result<Vec<LLMContextChunk>>;
+
+    /// Analyzers that produce findings override this; the default only contributes context.
+    async fn analyze(&self, diff: &UnifiedDiff, repo_path: &str) -> Result<PreAnalysis> {
+        Ok(PreAnalysis {
+            chunks: self.run(diff, repo_path).await?,
+            findings: Vec::new(),
+        })
+    }
 }
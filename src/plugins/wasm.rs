@@ -0,0 +1,249 @@
+//! Third-party analyzers compiled to WebAssembly.
+//!
+//! Each plugin lives in its own directory under `plugins.plugins_dir` with a `plugin.yml`
+//! manifest next to the module. Modules run sandboxed (no WASI, so no file system or network)
+//! and exchange JSON through their linear memory:
+//!
+//! - `memory`: the exported linear memory.
+//! - `alloc(len: i32) -> i32`: returns a buffer diffscope writes the input into.
+//! - `pre_analyze(ptr: i32, len: i32) -> i64`: input is a `UnifiedDiff`, output a list of
+//!   `LLMContextChunk`s (`pre_analyzer` capability).
+//! - `post_process(ptr: i32, len: i32) -> i64`: input and output are lists of `Comment`s
+//!   (`post_processor` capability).
+//!
+//! Entry points return the output location packed as `(ptr << 32) | len`.
+
+use crate::core::{Comment, LLMContextChunk, UnifiedDiff};
+use crate::plugins::{PostProcessor, PreAnalyzer};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use wasmtime::{Engine, Instance, Module, Store};
+
+const MANIFEST_FILE: &str = "plugin.yml";
+
+/// Instructions a single call may execute before it is aborted.
+const FUEL_PER_CALL: u64 = 5_000_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Capability {
+    PreAnalyzer,
+    PostProcessor,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginManifest {
+    pub id: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub version: Option<String>,
+    /// Module path, relative to the manifest.
+    pub module: PathBuf,
+    pub capabilities: Vec<Capability>,
+}
+
+pub struct WasmPlugin {
+    manifest: PluginManifest,
+    engine: Engine,
+    module: Module,
+}
+
+impl WasmPlugin {
+    pub fn load(manifest_path: &Path) -> Result<Self> {
+        let manifest: PluginManifest = serde_yaml::from_str(
+            &std::fs::read_to_string(manifest_path)
+                .with_context(|| format!("Failed to read {}", manifest_path.display()))?,
+        )
+        .with_context(|| format!("Invalid plugin manifest {}", manifest_path.display()))?;
+        let module_path = manifest_path
+            .parent()
+            .unwrap_or_else(|| Path::new("."))
+            .join(&manifest.module);
+
+        let mut config = wasmtime::Config::new();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config)?;
+        let module = Module::from_file(&engine, &module_path)
+            .with_context(|| format!("Failed to compile {}", module_path.display()))?;
+
+        for (capability, export) in [
+            (Capability::PreAnalyzer, "pre_analyze"),
+            (Capability::PostProcessor, "post_process"),
+        ] {
+            if manifest.capabilities.contains(&capability) && module.get_export(export).is_none() {
+                anyhow::bail!(
+                    "Plugin {} declares {:?} but does not export `{}`",
+                    manifest.id,
+                    capability,
+                    export
+                );
+            }
+        }
+
+        Ok(Self {
+            manifest,
+            engine,
+            module,
+        })
+    }
+
+    pub fn id(&self) -> &str {
+        &self.manifest.id
+    }
+
+    pub fn has_capability(&self, capability: Capability) -> bool {
+        self.manifest.capabilities.contains(&capability)
+    }
+
+    /// Runs `export` on a fresh instance, so no state leaks between calls.
+    fn call<I: Serialize, O: DeserializeOwned>(&self, export: &str, input: &I) -> Result<O> {
+        let input = serde_json::to_vec(input)?;
+        let mut store = Store::new(&self.engine, ());
+        store.set_fuel(FUEL_PER_CALL)?;
+        let instance = Instance::new(&mut store, &self.module, &[])?;
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .context("plugin does not export `memory`")?;
+        let alloc = instance.get_typed_func::<i32, i32>(&mut store, "alloc")?;
+        let entry = instance.get_typed_func::<(i32, i32), i64>(&mut store, export)?;
+
+        let len = i32::try_from(input.len()).context("plugin input too large")?;
+        let ptr = alloc.call(&mut store, len)?;
+        memory.write(&mut store, ptr as u32 as usize, &input)?;
+        let packed = entry.call(&mut store, (ptr, len))? as u64;
+
+        let (out_ptr, out_len) = ((packed >> 32) as usize, (packed & 0xffff_ffff) as usize);
+        let mut output = vec![0u8; out_len];
+        memory.read(&store, out_ptr, &mut output)?;
+        serde_json::from_slice(&output)
+            .with_context(|| format!("plugin returned invalid JSON from `{}`", export))
+    }
+}
+
+/// Loads every `*/plugin.yml` under `dir`. Broken plugins are skipped with a warning.
+pub fn load_plugins(dir: &Path) -> Result<Vec<Arc<WasmPlugin>>> {
+    let mut plugins = Vec::new();
+    let entries = std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read plugins_dir {}", dir.display()))?;
+    let mut manifests: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path().join(MANIFEST_FILE))
+        .filter(|path| path.is_file())
+        .collect();
+    manifests.sort();
+
+    for manifest in manifests {
+        match WasmPlugin::load(&manifest) {
+            Ok(plugin) => {
+                tracing::info!("Loaded WASM plugin {}", plugin.id());
+                plugins.push(Arc::new(plugin));
+            }
+            Err(err) => tracing::warn!("Skipping plugin {}: {:#}", manifest.display(), err),
+        }
+    }
+    Ok(plugins)
+}
+
+pub struct WasmPreAnalyzer(pub Arc<WasmPlugin>);
+
+#[async_trait]
+impl PreAnalyzer for WasmPreAnalyzer {
+    fn id(&self) -> &str {
+        self.0.id()
+    }
+
+    fn settings(&self) -> serde_json::Value {
+        serde_json::to_value(&self.0.manifest).unwrap_or_default()
+    }
+
+    async fn run(&self, diff: &UnifiedDiff, _repo_path: &str) -> Result<Vec<LLMContextChunk>> {
+        let plugin = self.0.clone();
+        let diff = diff.clone();
+        tokio::task::spawn_blocking(move || plugin.call("pre_analyze", &diff)).await?
+    }
+}
+
+pub struct WasmPostProcessor(pub Arc<WasmPlugin>);
+
+#[async_trait]
+impl PostProcessor for WasmPostProcessor {
+    fn id(&self) -> &str {
+        self.0.id()
+    }
+
+    fn settings(&self) -> serde_json::Value {
+        serde_json::to_value(&self.0.manifest).unwrap_or_default()
+    }
+
+    async fn run(&self, comments: Vec<Comment>, _repo_path: &str) -> Result<Vec<Comment>> {
+        let plugin = self.0.clone();
+        tokio::task::spawn_blocking(move || plugin.call("post_process", &comments)).await?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::comment::test_comment;
+
+    // Bump allocator; `pre_analyze` returns a fixed chunk and `post_process` echoes its input.
+    const TEST_MODULE: &str = r#"
+(module
+  (memory (export "memory") 1)
+  (global $next (mut i32) (i32.const 1024))
+  (data (i32.const 0) "[{\"file_path\":\"NOTES.md\",\"content\":\"plugin context\",\"context_type\":\"Reference\",\"line_range\":null}]")
+  (func (export "alloc") (param $len i32) (result i32)
+    (local $ptr i32)
+    (local.set $ptr (global.get $next))
+    (global.set $next (i32.add (global.get $next) (local.get $len)))
+    (local.get $ptr))
+  (func (export "pre_analyze") (param i32 i32) (result i64)
+    (i64.const 98))
+  (func (export "post_process") (param $ptr i32) (param $len i32) (result i64)
+    (i64.or
+      (i64.shl (i64.extend_i32_u (local.get $ptr)) (i64.const 32))
+      (i64.extend_i32_u (local.get $len)))))
+"#;
+
+    #[tokio::test]
+    async fn loads_manifest_and_exchanges_json_with_module() {
+        let dir = tempfile::tempdir().unwrap();
+        let plugin_dir = dir.path().join("echo");
+        std::fs::create_dir(&plugin_dir).unwrap();
+        std::fs::write(plugin_dir.join("echo.wat"), TEST_MODULE).unwrap();
+        std::fs::write(
+            plugin_dir.join(MANIFEST_FILE),
+            "id: echo\nmodule: echo.wat\ncapabilities: [pre_analyzer, post_processor]\n",
+        )
+        .unwrap();
+
+        let plugins = load_plugins(dir.path()).unwrap();
+        assert_eq!(plugins.len(), 1);
+        let plugin = plugins[0].clone();
+        assert!(plugin.has_capability(Capability::PostProcessor));
+
+        let diff = crate::core::DiffParser::parse_unified_diff(
+            "--- a/a.rs\n+++ b/a.rs\n@@ -1 +1 @@\n-a\n+b\n",
+        )
+        .unwrap()
+        .remove(0);
+        let chunks = WasmPreAnalyzer(plugin.clone())
+            .run(&diff, ".")
+            .await
+            .unwrap();
+        assert_eq!(chunks[0].content, "plugin context");
+
+        let comments = vec![Comment {
+            severity: crate::core::comment::Severity::Info,
+            category: crate::core::comment::Category::Style,
+            confidence: 0.5,
+            ..test_comment("a.rs", 1, "kept")
+        }];
+        let processed = WasmPostProcessor(plugin).run(comments, ".").await.unwrap();
+        assert_eq!(processed[0].content, "kept");
+    }
+}
@@ -0,0 +1,337 @@
+use crate::core::diff_parser::ChangeType;
+use crate::core::position::{DiffPosition, DiffSide};
+use anyhow::{Context, Result};
+use reqwest::{Client, RequestBuilder};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+pub const BITBUCKET_CLOUD_API_URL: &str = "https://api.bitbucket.org/2.0";
+const BUILD_STATUS_KEY: &str = "diffscope";
+
+/// `provider.bitbucket` section of `.diffscope.yml`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BitbucketConfig {
+    /// API root. Defaults to Bitbucket Cloud; for Server/Data Center use the instance URL.
+    #[serde(default)]
+    pub base_url: Option<String>,
+
+    /// Talk to the Bitbucket Server/Data Center REST API instead of Bitbucket Cloud.
+    #[serde(default)]
+    pub server: bool,
+
+    /// Cloud workspace, or Server project key.
+    #[serde(default)]
+    pub workspace: Option<String>,
+
+    #[serde(default)]
+    pub repo_slug: Option<String>,
+
+    /// Username for app-password auth (Cloud). Falls back to `BITBUCKET_USERNAME`.
+    #[serde(default)]
+    pub username: Option<String>,
+
+    /// App password (with `username`) or access token. Falls back to `BITBUCKET_TOKEN`.
+    #[serde(default)]
+    pub token: Option<String>,
+
+    /// Report review progress and outcome as a build status on the PR's head commit.
+    #[serde(default = "default_true")]
+    pub build_status: bool,
+}
+
+impl Default for BitbucketConfig {
+    fn default() -> Self {
+        Self {
+            base_url: None,
+            server: false,
+            workspace: None,
+            repo_slug: None,
+            username: None,
+            token: None,
+            build_status: true,
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuildState {
+    InProgress,
+    Successful,
+    Failed,
+}
+
+impl BuildState {
+    fn as_str(&self) -> &'static str {
+        match self {
+            BuildState::InProgress => "INPROGRESS",
+            BuildState::Successful => "SUCCESSFUL",
+            BuildState::Failed => "FAILED",
+        }
+    }
+}
+
+pub struct BitbucketClient {
+    client: Client,
+    base_url: String,
+    server: bool,
+    workspace: String,
+    repo_slug: String,
+    username: Option<String>,
+    token: Option<String>,
+}
+
+impl BitbucketClient {
+    /// `repo` (`workspace/slug`, or `PROJECT/slug` on Server) overrides the configured repository.
+    pub fn new(config: &BitbucketConfig, repo: Option<&str>) -> Result<Self> {
+        let (workspace, repo_slug) = match repo.and_then(|r| r.split_once('/')) {
+            Some((workspace, slug)) => (workspace.to_string(), slug.to_string()),
+            None => (
+                config.workspace.clone().context(
+                    "Bitbucket workspace is not set. Set provider.bitbucket.workspace or pass --repo workspace/slug",
+                )?,
+                config.repo_slug.clone().context(
+                    "Bitbucket repository is not set. Set provider.bitbucket.repo_slug or pass --repo workspace/slug",
+                )?,
+            ),
+        };
+
+        let base_url = match &config.base_url {
+            Some(url) => url.trim_end_matches('/').to_string(),
+            None if config.server => {
+                anyhow::bail!("provider.bitbucket.base_url is required for Bitbucket Server")
+            }
+            None => BITBUCKET_CLOUD_API_URL.to_string(),
+        };
+
+        Ok(Self {
+            client: Client::builder()
+                .timeout(std::time::Duration::from_secs(60))
+                .build()?,
+            base_url,
+            server: config.server,
+            workspace,
+            repo_slug,
+            username: config
+                .username
+                .clone()
+                .or_else(|| std::env::var("BITBUCKET_USERNAME").ok()),
+            token: config
+                .token
+                .clone()
+                .or_else(|| std::env::var("BITBUCKET_TOKEN").ok()),
+        })
+    }
+
+    fn pull_request_url(&self, pr: u32) -> String {
+        if self.server {
+            format!(
+                "{}/rest/api/1.0/projects/{}/repos/{}/pull-requests/{}",
+                self.base_url, self.workspace, self.repo_slug, pr
+            )
+        } else {
+            format!(
+                "{}/repositories/{}/{}/pullrequests/{}",
+                self.base_url, self.workspace, self.repo_slug, pr
+            )
+        }
+    }
+
+    fn authorized(&self, request: RequestBuilder) -> RequestBuilder {
+        match (&self.username, &self.token) {
+            (Some(username), Some(token)) => request.basic_auth(username, Some(token)),
+            (None, Some(token)) => request.bearer_auth(token),
+            _ => request,
+        }
+    }
+
+    async fn send(&self, request: RequestBuilder, action: &str) -> Result<reqwest::Response> {
+        let response = self
+            .authorized(request)
+            .send()
+            .await
+            .with_context(|| format!("Failed to {} on Bitbucket", action))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            anyhow::bail!(
+                "Bitbucket failed to {} ({}): {}",
+                action,
+                status,
+                body.trim()
+            );
+        }
+        Ok(response)
+    }
+
+    pub async fn fetch_diff(&self, pr: u32) -> Result<String> {
+        let url = if self.server {
+            format!("{}.diff", self.pull_request_url(pr))
+        } else {
+            format!("{}/diff", self.pull_request_url(pr))
+        };
+        let response = self
+            .send(self.client.get(&url), "fetch the pull request diff")
+            .await?;
+        Ok(response.text().await?)
+    }
+
+    pub async fn head_commit(&self, pr: u32) -> Result<String> {
+        let response = self
+            .send(
+                self.client.get(self.pull_request_url(pr)),
+                "fetch the pull request",
+            )
+            .await?;
+        let body: serde_json::Value = response.json().await?;
+        let hash = if self.server {
+            &body["fromRef"]["latestCommit"]
+        } else {
+            &body["source"]["commit"]["hash"]
+        };
+        hash.as_str()
+            .map(str::to_string)
+            .context("Bitbucket pull request response has no head commit")
+    }
+
+    /// Posts `body` on the pull request, anchored inline when `position` is inside the diff.
+    pub async fn post_comment(
+        &self,
+        pr: u32,
+        body: &str,
+        position: Option<&DiffPosition>,
+    ) -> Result<()> {
+        let payload = if self.server {
+            server_comment_payload(body, position)
+        } else {
+            cloud_comment_payload(body, position)
+        };
+        let url = format!("{}/comments", self.pull_request_url(pr));
+        self.send(
+            self.client.post(&url).json(&payload),
+            "post a pull request comment",
+        )
+        .await?;
+        Ok(())
+    }
+
+    pub async fn set_build_status(
+        &self,
+        commit: &str,
+        state: BuildState,
+        description: &str,
+    ) -> Result<()> {
+        let url = if self.server {
+            format!("{}/rest/build-status/1.0/commits/{}", self.base_url, commit)
+        } else {
+            format!(
+                "{}/repositories/{}/{}/commit/{}/statuses/build",
+                self.base_url, self.workspace, self.repo_slug, commit
+            )
+        };
+        let payload = json!({
+            "key": BUILD_STATUS_KEY,
+            "name": "DiffScope review",
+            "state": state.as_str(),
+            "description": description,
+            "url": "https://github.com/haasonsaas/diffscope",
+        });
+        self.send(
+            self.client.post(&url).json(&payload),
+            "set the build status",
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+fn cloud_comment_payload(body: &str, position: Option<&DiffPosition>) -> serde_json::Value {
+    let mut payload = json!({ "content": { "raw": body } });
+    if let Some(position) = position {
+        let side = match position.side {
+            DiffSide::Left => "from",
+            DiffSide::Right => "to",
+        };
+        payload["inline"] = json!({
+            "path": position.path.to_string_lossy(),
+            side: position.line,
+        });
+    }
+    payload
+}
+
+fn server_comment_payload(body: &str, position: Option<&DiffPosition>) -> serde_json::Value {
+    let mut payload = json!({ "text": body });
+    if let Some(position) = position {
+        let line_type = match position.change_type {
+            ChangeType::Added => "ADDED",
+            ChangeType::Removed => "REMOVED",
+            ChangeType::Context => "CONTEXT",
+        };
+        let file_type = match position.side {
+            DiffSide::Left => "FROM",
+            DiffSide::Right => "TO",
+        };
+        payload["anchor"] = json!({
+            "path": position.path.to_string_lossy(),
+            "line": position.line,
+            "lineType": line_type,
+            "fileType": file_type,
+            "diffType": "EFFECTIVE",
+        });
+    }
+    payload
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[tokio::test]
+    async fn cloud_client_fetches_diff_and_posts_inline_comments() {
+        let mut server = mockito::Server::new_async().await;
+        let diff_mock = server
+            .mock("GET", "/repositories/acme/shop/pullrequests/7/diff")
+            .match_header("authorization", "Bearer secret")
+            .with_body("diff --git a/a.rs b/a.rs\n")
+            .create_async()
+            .await;
+        let comment_mock = server
+            .mock("POST", "/repositories/acme/shop/pullrequests/7/comments")
+            .match_body(mockito::Matcher::PartialJson(json!({
+                "content": { "raw": "Possible panic" },
+                "inline": { "path": "src/lib.rs", "to": 12 }
+            })))
+            .with_status(201)
+            .create_async()
+            .await;
+
+        let config = BitbucketConfig {
+            base_url: Some(server.url()),
+            token: Some("secret".to_string()),
+            ..Default::default()
+        };
+        let client = BitbucketClient::new(&config, Some("acme/shop")).unwrap();
+
+        let diff = client.fetch_diff(7).await.unwrap();
+        assert!(diff.starts_with("diff --git"));
+        let position = DiffPosition {
+            path: PathBuf::from("src/lib.rs"),
+            line: 12,
+            side: DiffSide::Right,
+            change_type: ChangeType::Added,
+            position: 3,
+        };
+        client
+            .post_comment(7, "Possible panic", Some(&position))
+            .await
+            .unwrap();
+
+        diff_mock.assert_async().await;
+        comment_mock.assert_async().await;
+    }
+}
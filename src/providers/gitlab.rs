@@ -0,0 +1,192 @@
+use crate::core::position::{DiffPosition, DiffSide};
+use anyhow::{Context, Result};
+use reqwest::{Client, RequestBuilder};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::path::Path;
+
+pub const GITLAB_URL: &str = "https://gitlab.com";
+
+/// `provider.gitlab` section of `.diffscope.yml`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GitLabConfig {
+    /// Instance URL; defaults to gitlab.com.
+    #[serde(default)]
+    pub url: Option<String>,
+
+    /// Personal, project, or group access token with `api` scope. Falls back to `GITLAB_TOKEN`.
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+/// Commits GitLab needs to anchor a diff note on a merge request version.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DiffRefs {
+    pub base_sha: String,
+    pub head_sha: String,
+    pub start_sha: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct MergeRequestChanges {
+    diff_refs: Option<DiffRefs>,
+    #[serde(default)]
+    changes: Vec<FileChange>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FileChange {
+    old_path: String,
+    new_path: String,
+    #[serde(default)]
+    new_file: bool,
+    #[serde(default)]
+    deleted_file: bool,
+    #[serde(default)]
+    renamed_file: bool,
+    #[serde(default)]
+    diff: String,
+}
+
+pub struct GitLabClient {
+    client: Client,
+    api_url: String,
+    token: String,
+}
+
+impl GitLabClient {
+    pub fn new(config: &GitLabConfig) -> Result<Self> {
+        let token = config
+            .token
+            .clone()
+            .or_else(|| std::env::var("GITLAB_TOKEN").ok())
+            .context("GitLab token is not set. Set provider.gitlab.token or GITLAB_TOKEN")?;
+        let url = config.url.as_deref().unwrap_or(GITLAB_URL);
+        Ok(Self {
+            client: Client::builder()
+                .timeout(std::time::Duration::from_secs(60))
+                .build()?,
+            api_url: format!("{}/api/v4", url.trim_end_matches('/')),
+            token,
+        })
+    }
+
+    async fn send(&self, builder: RequestBuilder, action: &str) -> Result<reqwest::Response> {
+        let response = builder
+            .header("PRIVATE-TOKEN", &self.token)
+            .send()
+            .await
+            .with_context(|| format!("Failed to {} on GitLab", action))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            anyhow::bail!("GitLab failed to {} ({}): {}", action, status, body.trim());
+        }
+        Ok(response)
+    }
+
+    fn merge_request_url(&self, project_id: u64, iid: u64) -> String {
+        format!(
+            "{}/projects/{}/merge_requests/{}",
+            self.api_url, project_id, iid
+        )
+    }
+
+    /// Returns the merge request as a unified diff plus the refs needed to anchor comments.
+    pub async fn fetch_merge_request(
+        &self,
+        project_id: u64,
+        iid: u64,
+    ) -> Result<(String, DiffRefs)> {
+        let url = format!("{}/changes", self.merge_request_url(project_id, iid));
+        let response = self
+            .send(self.client.get(&url), "fetch merge request changes")
+            .await?;
+        let changes: MergeRequestChanges = response.json().await?;
+        Ok((
+            to_unified_diff(&changes.changes),
+            changes.diff_refs.unwrap_or_default(),
+        ))
+    }
+
+    /// Posts a diff discussion anchored at `position`, or a plain note when it is `None`.
+    pub async fn post_comment(
+        &self,
+        project_id: u64,
+        iid: u64,
+        body: &str,
+        position: Option<(&DiffPosition, &DiffRefs)>,
+        old_path: Option<&Path>,
+    ) -> Result<()> {
+        let base = self.merge_request_url(project_id, iid);
+        let (url, payload) = match position {
+            Some((position, refs)) => {
+                let path = position.path.to_string_lossy();
+                // Renamed files are anchored at their path before the rename on the old side.
+                let old_path = old_path.map_or(path.clone(), |old| old.to_string_lossy());
+                let mut anchor = json!({
+                    "position_type": "text",
+                    "base_sha": refs.base_sha,
+                    "head_sha": refs.head_sha,
+                    "start_sha": refs.start_sha,
+                    "old_path": old_path,
+                    "new_path": path,
+                });
+                match position.side {
+                    DiffSide::Right => anchor["new_line"] = position.line.into(),
+                    DiffSide::Left => anchor["old_line"] = position.line.into(),
+                }
+                (
+                    format!("{}/discussions", base),
+                    json!({ "body": body, "position": anchor }),
+                )
+            }
+            None => (format!("{}/notes", base), json!({ "body": body })),
+        };
+        self.send(
+            self.client.post(&url).json(&payload),
+            "post a merge request comment",
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+/// GitLab returns per-file hunks without git headers; rebuild a diff `DiffParser` understands.
+fn to_unified_diff(changes: &[FileChange]) -> String {
+    let mut output = String::new();
+    for change in changes {
+        output.push_str(&format!(
+            "diff --git a/{} b/{}\n",
+            change.old_path, change.new_path
+        ));
+        if change.new_file {
+            output.push_str("new file mode 100644\n");
+        }
+        if change.deleted_file {
+            output.push_str("deleted file mode 100644\n");
+        }
+        if change.renamed_file {
+            output.push_str(&format!(
+                "rename from {}\nrename to {}\n",
+                change.old_path, change.new_path
+            ));
+        }
+        let old = if change.new_file {
+            "/dev/null".to_string()
+        } else {
+            format!("a/{}", change.old_path)
+        };
+        let new = if change.deleted_file {
+            "/dev/null".to_string()
+        } else {
+            format!("b/{}", change.new_path)
+        };
+        output.push_str(&format!("--- {}\n+++ {}\n", old, new));
+        output.push_str(&change.diff);
+        if !change.diff.ends_with('\n') {
+            output.push('\n');
+        }
+    }
+    output
+}
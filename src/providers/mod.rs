@@ -0,0 +1,3 @@
+pub mod bitbucket;
+pub mod github;
+pub mod gitlab;
@@ -0,0 +1,1663 @@
+//! The review pipeline behind `diffscope review`, for embedding in other Rust services.
+//!
+//! ```no_run
+//! # async fn run(diff: &str) -> anyhow::Result<()> {
+//! let config = diffscope::config::Config::load().unwrap_or_default();
+//! let engine = diffscope::ReviewEngine::new(config).with_repo_root("/path/to/repo");
+//! for comment in engine.review_diff(diff).await? {
+//!     println!("{}:{} {}", comment.file_path.display(), comment.line_number, comment.content);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::plugins::{PostProcessor, PreAnalyzer};
+use crate::{adapters, config, core, plugins};
+use anyhow::Result;
+use once_cell::sync::{Lazy, OnceCell};
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// The result of [`ReviewEngine::review`].
+#[derive(Debug, Default)]
+pub struct ReviewOutcome {
+    pub comments: Vec<core::Comment>,
+    /// Files the model could not review; empty when every request succeeded.
+    pub skipped: Vec<core::comment::SkippedFile>,
+}
+
+/// Reviews diffs against one repository with one configuration. The symbol index is built on
+/// first use and reused by every later review, so a long-lived engine indexes only once.
+pub struct ReviewEngine {
+    config: config::Config,
+    repo_root: PathBuf,
+    symbol_index: OnceCell<Option<core::SymbolIndex>>,
+    pre_analyzers: Vec<Arc<dyn PreAnalyzer>>,
+    post_processors: Vec<Arc<dyn PostProcessor>>,
+}
+
+impl ReviewEngine {
+    /// An engine for the repository in the current directory.
+    pub fn new(config: config::Config) -> Self {
+        Self {
+            config,
+            repo_root: PathBuf::from("."),
+            symbol_index: OnceCell::new(),
+            pre_analyzers: Vec::new(),
+            post_processors: Vec::new(),
+        }
+    }
+
+    /// Reads context, definitions, and history from the repository at `repo_root`.
+    pub fn with_repo_root(mut self, repo_root: impl Into<PathBuf>) -> Self {
+        self.repo_root = repo_root.into();
+        self.symbol_index = OnceCell::new();
+        self
+    }
+
+    /// Runs `analyzer` on every file alongside the plugins enabled in the configuration.
+    pub fn register_pre_analyzer(&mut self, analyzer: Arc<dyn PreAnalyzer>) -> &mut Self {
+        self.pre_analyzers.push(analyzer);
+        self
+    }
+
+    /// Runs `processor` on the findings after the plugins enabled in the configuration.
+    pub fn register_post_processor(&mut self, processor: Arc<dyn PostProcessor>) -> &mut Self {
+        self.post_processors.push(processor);
+        self
+    }
+
+    pub fn config(&self) -> &config::Config {
+        &self.config
+    }
+
+    pub fn repo_root(&self) -> &Path {
+        &self.repo_root
+    }
+
+    /// The repository's symbol index, built on the first call; `None` when `symbol_index` is
+    /// disabled or indexing failed.
+    pub fn symbol_index(&self) -> Option<&core::SymbolIndex> {
+        self.symbol_index
+            .get_or_init(|| build_symbol_index(&self.config, &self.repo_root))
+            .as_ref()
+    }
+
+    /// The comments for a unified diff. Files whose model request fails are logged and left
+    /// out; use [`ReviewEngine::review`] to learn which.
+    pub async fn review_diff(&self, diff_content: &str) -> Result<Vec<core::Comment>> {
+        Ok(self.review(diff_content).await?.comments)
+    }
+
+    /// Reviews a unified diff file by file. A failed model request skips the rest of that file
+    /// instead of failing the review; the file is listed in [`ReviewOutcome::skipped`]. Errors
+    /// that would fail every file, such as a rejected API key or an unknown model, fail the
+    /// review.
+    pub async fn review(&self, diff_content: &str) -> Result<ReviewOutcome> {
+        self.review_with_checkpoint(diff_content, None).await
+    }
+
+    /// Like [`ReviewEngine::review`], but reuses the comments of files `checkpoint` already
+    /// holds and records each newly reviewed file in it, so a stopped run can be resumed.
+    pub async fn review_with_checkpoint(
+        &self,
+        diff_content: &str,
+        checkpoint: Option<&core::checkpoint::ReviewCheckpoint>,
+    ) -> Result<ReviewOutcome> {
+        let config = &self.config;
+        let repo_path = self.repo_root.as_path();
+        let symbol_index = self.symbol_index();
+        let repo_map = build_repo_map(config, symbol_index);
+        let semantic_search = build_semantic_search(config, repo_path).await;
+
+        let diffs = core::DiffParser::parse_unified_diff(diff_content)?;
+        info!("Parsed {} file diffs", diffs.len());
+
+        // Initialize plugin manager and load builtin plugins
+        let mut plugin_manager = plugins::plugin::PluginManager::new();
+        plugin_manager.load_builtin_plugins(&config.plugins).await?;
+        for analyzer in &self.pre_analyzers {
+            plugin_manager.register_pre_analyzer(analyzer.clone());
+        }
+        for processor in &self.post_processors {
+            plugin_manager.register_post_processor(processor.clone());
+        }
+        plugin_manager.begin_review(&diffs).await;
+
+        let mut feedback = load_feedback(config);
+        if let Err(err) = core::feedback_remote::merge_shared(config, &mut feedback).await {
+            warn!("Ignoring shared feedback store: {:#}", err);
+        }
+        let model_config = config.model_config();
+
+        let adapter = adapters::llm::create_adapter(&model_config)?;
+        let triage_adapter = build_triage_adapter(config)?;
+        let verified_severities = config.verified_severities();
+        let base_prompt_config = base_prompt_config(config)?;
+        let mut all_comments = Vec::new();
+        let mut skipped = Vec::new();
+        let review_cache = build_review_cache(config);
+        let completed = match checkpoint {
+            Some(checkpoint) => checkpoint.completed()?,
+            None => HashMap::new(),
+        };
+
+        let repo_path_str = repo_path.to_string_lossy().to_string();
+        let context_fetcher = core::ContextFetcher::new(repo_path.to_path_buf())
+            .with_function_scope(config.function_scope_context);
+        let follow_up = build_follow_up(config, &context_fetcher, symbol_index);
+
+        let excluded = config.exclusion_filter(repo_path);
+        let hotspot_notes = load_hotspot_notes(config, repo_path, &diffs);
+        let sources = ContextSources {
+            config,
+            repo_path: &repo_path_str,
+            context_fetcher: &context_fetcher,
+            plugin_manager: &plugin_manager,
+            symbol_index,
+            repo_map: repo_map.as_ref(),
+            semantic_search: semantic_search.as_ref(),
+            hotspot_notes: &hotspot_notes,
+        };
+        let shared_context = sources.shared_context(&diffs);
+        let mut small_files = Vec::new();
+        for diff in &diffs {
+            // Check if file should be excluded
+            if excluded(&diff.file_path) {
+                info!("Skipping excluded file: {}", diff.file_path.display());
+                all_comments.extend(
+                    skipped_lockfile_findings(config, &plugin_manager, diff, &repo_path_str).await,
+                );
+                continue;
+            }
+            if diff.is_deleted {
+                info!("Skipping deleted file: {}", diff.file_path.display());
+                continue;
+            }
+            if diff.is_binary || diff.hunks.is_empty() {
+                info!("Skipping non-text diff: {}", diff.file_path.display());
+                continue;
+            }
+
+            if let Some(comments) = completed.get(&diff.file_path) {
+                info!(
+                    "Reusing checkpointed review of {}",
+                    diff.file_path.display()
+                );
+                all_comments.extend(comments.iter().cloned());
+                continue;
+            }
+
+            let FileContext {
+                chunks: context_chunks,
+                findings,
+            } = sources.for_file(diff).await?;
+            let path_config = config.get_path_config(&diff.file_path);
+            let local_prompt_config =
+                file_prompt_config(config, &base_prompt_config, &diff.file_path, path_config);
+            let prompt_fingerprint = local_prompt_config.fingerprint();
+            let (cached_comments, pending_diff) = match &review_cache {
+                Some(cache) => cache.partition(diff, &prompt_fingerprint),
+                None => (Vec::new(), diff.clone()),
+            };
+            let cached_hunks = diff.hunks.len() - pending_diff.hunks.len();
+            if cached_hunks > 0 {
+                info!(
+                    "Reusing cached review for {} of {} hunk(s) in {}",
+                    cached_hunks,
+                    diff.hunks.len(),
+                    diff.file_path.display()
+                );
+            }
+
+            let pending_diff = match &triage_adapter {
+                Some(screener) => {
+                    core::triage::triage_hunks(
+                        screener.as_ref(),
+                        pending_diff,
+                        config.triage.threshold,
+                    )
+                    .await
+                }
+                None => pending_diff,
+            };
+
+            let batches =
+                core::prompt::split_diff_into_batches(&pending_diff, &local_prompt_config.limits);
+            if !pending_diff.hunks.is_empty() && is_batchable(config, &batches) {
+                let mut carried = cached_comments;
+                carried.extend(findings);
+                small_files.push(BatchedFile {
+                    diff: pending_diff,
+                    context_chunks,
+                    prompt_config: local_prompt_config,
+                    carried,
+                });
+                continue;
+            }
+
+            let mut comments = Vec::new();
+            let mut failed = false;
+            if !pending_diff.hunks.is_empty() {
+                if batches.len() > 1 {
+                    info!(
+                        "Reviewing {} in {} batches to stay within the diff budget",
+                        diff.file_path.display(),
+                        batches.len()
+                    );
+                }
+                let local_prompt_builder = core::PromptBuilder::new(local_prompt_config);
+
+                for batch in &batches {
+                    let (system_prompt, user_prompt) =
+                        local_prompt_builder.build_prompt(batch, &context_chunks)?;
+
+                    let request = adapters::llm::LLMRequest {
+                        system_prompt,
+                        user_prompt,
+                        temperature: None,
+                        max_tokens: None,
+                        cached_context: shared_context.clone(),
+                    };
+
+                    let response = match adapter.complete(request.clone()).await {
+                        Ok(response) => response,
+                        Err(err) if adapters::llm::is_fatal(&err) => return Err(err),
+                        Err(err) => {
+                            warn!("Skipping {}: {:#}", diff.file_path.display(), err);
+                            skipped.push(core::comment::SkippedFile::new(&diff.file_path, &err));
+                            failed = true;
+                            break;
+                        }
+                    };
+
+                    let content = match &follow_up {
+                        Some(follow_up) => {
+                            follow_up
+                                .revise(
+                                    adapter.as_ref(),
+                                    &request,
+                                    batch,
+                                    &context_chunks,
+                                    response.content,
+                                )
+                                .await
+                        }
+                        None => response.content,
+                    };
+
+                    if let Ok(raw_comments) = parse_llm_response(&content, &diff.file_path) {
+                        let synthesized = core::CommentSynthesizer::synthesize(raw_comments)?;
+                        let batch_comments = filter_comments_for_diff(batch, synthesized);
+                        let batch_comments = core::verification::verify_findings(
+                            adapter.as_ref(),
+                            batch,
+                            batch_comments,
+                            &verified_severities,
+                        )
+                        .await;
+                        if let Some(cache) = &review_cache {
+                            if let Err(err) =
+                                cache.store(batch, &prompt_fingerprint, &batch_comments)
+                            {
+                                warn!("Failed to write review cache: {}", err);
+                            }
+                        }
+                        comments.extend(batch_comments);
+                    }
+                }
+            }
+            comments.extend(cached_comments);
+            comments.extend(findings);
+            apply_severity_overrides(path_config, &mut comments);
+
+            // A file that failed stays out of the checkpoint so `--resume` retries it.
+            if let Some(checkpoint) = checkpoint.filter(|_| !failed) {
+                if let Err(err) = checkpoint.record(&diff.file_path, &comments) {
+                    warn!("Failed to checkpoint {}: {}", diff.file_path.display(), err);
+                }
+            }
+            all_comments.extend(comments);
+        }
+
+        // Small files share requests, several files per prompt.
+        for group in pack_batched_files(config, small_files) {
+            if group.len() > 1 {
+                info!("Reviewing {} small files in one request", group.len());
+            }
+            let reviewed = review_batched_files(
+                adapter.as_ref(),
+                &group,
+                shared_context.as_deref(),
+                &verified_severities,
+                review_cache.as_ref(),
+            )
+            .await;
+            let failed = reviewed.is_err();
+            let reviewed = match reviewed {
+                Ok(reviewed) => reviewed,
+                Err(err) if adapters::llm::is_fatal(&err) => return Err(err),
+                Err(err) => {
+                    for file in &group {
+                        warn!("Skipping {}: {:#}", file.diff.file_path.display(), err);
+                        skipped.push(core::comment::SkippedFile::new(&file.diff.file_path, &err));
+                    }
+                    vec![Vec::new(); group.len()]
+                }
+            };
+            for (file, mut comments) in group.into_iter().zip(reviewed) {
+                let file_path = file.diff.file_path;
+                comments.extend(file.carried);
+                apply_severity_overrides(config.get_path_config(&file_path), &mut comments);
+                if let Some(checkpoint) = checkpoint.filter(|_| !failed) {
+                    if let Err(err) = checkpoint.record(&file_path, &comments) {
+                        warn!("Failed to checkpoint {}: {}", file_path.display(), err);
+                    }
+                }
+                all_comments.extend(comments);
+            }
+        }
+
+        // Run post-processors to filter and refine comments
+        let mut processed_comments = plugin_manager
+            .run_post_processors(all_comments, &repo_path_str)
+            .await?;
+        apply_confidence_calibration(config, &feedback, &mut processed_comments);
+        apply_category_weights(config, &mut processed_comments);
+        let processed_comments =
+            apply_confidence_threshold(processed_comments, config.min_confidence);
+        let processed_comments = core::policy::apply_policies(processed_comments, &config.policies);
+        let repo = core::feedback::current_repo(repo_path);
+        let mut processed_comments = apply_feedback_suppression(
+            processed_comments,
+            &feedback,
+            repo.as_deref(),
+            config.feedback_similarity,
+        );
+        apply_code_owners(config, repo_path, &mut processed_comments);
+
+        Ok(ReviewOutcome {
+            comments: processed_comments,
+            skipped,
+        })
+    }
+
+    /// A PR summary of a unified diff, using the repository's recent commits.
+    pub async fn summarize(&self, diff_content: &str) -> Result<core::pr_summary::PRSummary> {
+        let diffs = core::DiffParser::parse_unified_diff(diff_content)?;
+        let commits = core::GitIntegration::new(&self.repo_root)?.get_recent_commits(10)?;
+
+        let adapter = adapters::llm::create_adapter(&self.config.model_config())?;
+        let options = core::SummaryOptions {
+            include_diagram: self.config.smart_review_diagram,
+        };
+        core::PRSummaryGenerator::generate_summary_for_commits(
+            &diffs,
+            &commits,
+            adapter.as_ref(),
+            options,
+        )
+        .await
+    }
+}
+
+pub fn parse_llm_response(
+    content: &str,
+    file_path: &Path,
+) -> Result<Vec<core::comment::RawComment>> {
+    let mut comments = Vec::new();
+    static LINE_PATTERN: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"(?i)line\s+(\d+):\s*(.+)").unwrap());
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+
+        // Skip empty lines and common non-issue lines
+        if trimmed.is_empty()
+            || trimmed.starts_with("```")
+            || trimmed.starts_with('#')
+            || trimmed.starts_with('<')
+            || trimmed.contains("Here are")
+            || trimmed.contains("Here is")
+            || trimmed.contains("review of")
+        {
+            continue;
+        }
+
+        if let Some(caps) = LINE_PATTERN.captures(line) {
+            let line_number: usize = caps.get(1).unwrap().as_str().parse()?;
+            let comment_text = caps.get(2).unwrap().as_str().trim();
+
+            // Extract suggestion if present
+            let (content, suggestion) = if let Some(sugg_idx) = comment_text.rfind(". Consider ") {
+                (
+                    comment_text[..sugg_idx + 1].to_string(),
+                    Some(
+                        comment_text[sugg_idx + 11..]
+                            .trim_end_matches('.')
+                            .to_string(),
+                    ),
+                )
+            } else if let Some(sugg_idx) = comment_text.rfind(". Use ") {
+                (
+                    comment_text[..sugg_idx + 1].to_string(),
+                    Some(
+                        comment_text[sugg_idx + 6..]
+                            .trim_end_matches('.')
+                            .to_string(),
+                    ),
+                )
+            } else {
+                (comment_text.to_string(), None)
+            };
+
+            comments.push(core::comment::RawComment {
+                file_path: file_path.to_path_buf(),
+                line_number,
+                content,
+                suggestion,
+                severity: None,
+                category: None,
+                confidence: None,
+                fix_effort: None,
+                tags: Vec::new(),
+            });
+        }
+    }
+
+    Ok(comments)
+}
+
+/// Splits a response covering several files (see `PromptBuilder::build_multi_file_prompt`) at
+/// its `File: <path>` lines and parses each file's section. Findings under a path that is not
+/// in `paths`, or before the first `File:` line, cannot be attributed and are dropped.
+pub fn parse_multi_file_response(
+    content: &str,
+    paths: &[&Path],
+) -> Result<Vec<core::comment::RawComment>> {
+    static FILE_PATTERN: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"(?i)^[\s#*>-]*file:[\s*`]*([^\s*`]+)[*`]*\s*(.*)$").unwrap());
+
+    let mut sections: Vec<(usize, String)> = Vec::new();
+    let mut current = None;
+    for line in content.lines() {
+        // A `File:` line may carry its first finding, as in `File: src/a.rs Line 3: ...`.
+        let mut line = line;
+        if let Some(caps) = FILE_PATTERN.captures(line) {
+            let named = caps.get(1).unwrap().as_str();
+            let named_path = Path::new(named);
+            // A shortened or prefixed path names a file only when it matches whole components
+            // of exactly one of them.
+            current = paths
+                .iter()
+                .position(|path| *path == named_path)
+                .or_else(|| {
+                    let mut matches = paths.iter().enumerate().filter(|(_, path)| {
+                        path.ends_with(named_path) || named_path.ends_with(path)
+                    });
+                    match (matches.next(), matches.next()) {
+                        (Some((index, _)), None) => Some(index),
+                        _ => None,
+                    }
+                });
+            if current.is_none() {
+                warn!("Dropping findings for unknown file {:?}", named);
+            }
+            line = caps.get(2).unwrap().as_str();
+            if line.is_empty() {
+                continue;
+            }
+        }
+        if let Some(index) = current {
+            match sections.last_mut().filter(|(last, _)| *last == index) {
+                Some((_, section)) => section.push_str(line),
+                None => sections.push((index, line.to_string())),
+            }
+            sections.last_mut().unwrap().1.push('\n');
+        }
+    }
+
+    let mut comments = Vec::new();
+    for (index, section) in sections {
+        comments.extend(parse_llm_response(&section, paths[index])?);
+    }
+    Ok(comments)
+}
+
+/// A small file held back to share one model request with other small files (`batching`).
+pub struct BatchedFile {
+    /// The hunks still to review.
+    pub diff: core::UnifiedDiff,
+    pub context_chunks: Vec<core::LLMContextChunk>,
+    pub prompt_config: core::prompt::PromptConfig,
+    /// Findings that need no model request: cached hunks and analyzer results.
+    pub carried: Vec<core::Comment>,
+}
+
+/// Whether `batches` (one file's pending diff, split to the diff budget) is small enough to
+/// share a request with other files. The follow-up loop revises one file's review at a time,
+/// so files are not batched while it is enabled.
+pub fn is_batchable(config: &config::Config, batches: &[core::UnifiedDiff]) -> bool {
+    !config.follow_up.enabled
+        && config.batching.enabled
+        && config.batching.max_files > 1
+        && batches.len() == 1
+        && core::prompt::rendered_diff_len(&batches[0]) <= config.batching.max_file_chars
+}
+
+/// Groups held-back files into shared requests (see `core::prompt::pack_small_files`).
+pub fn pack_batched_files(
+    config: &config::Config,
+    files: Vec<BatchedFile>,
+) -> Vec<Vec<BatchedFile>> {
+    let groups = {
+        let fingerprints: Vec<String> = files
+            .iter()
+            .map(|file| file.prompt_config.fingerprint())
+            .collect();
+        let keys: Vec<(&str, &core::UnifiedDiff)> = fingerprints
+            .iter()
+            .map(String::as_str)
+            .zip(files.iter().map(|file| &file.diff))
+            .collect();
+        core::prompt::pack_small_files(&keys, &config.prompt_limits(), config.batching.max_files)
+    };
+    let mut files: Vec<Option<BatchedFile>> = files.into_iter().map(Some).collect();
+    groups
+        .into_iter()
+        .map(|group| {
+            group
+                .into_iter()
+                .filter_map(|index| files[index].take())
+                .collect()
+        })
+        .collect()
+}
+
+/// Reviews `files`, which share a prompt fingerprint, in one model request and returns each file's
+/// comments in order. An error means the request failed and none of the files were reviewed.
+pub async fn review_batched_files(
+    adapter: &dyn adapters::llm::LLMAdapter,
+    files: &[BatchedFile],
+    shared_context: Option<&str>,
+    verified_severities: &[core::comment::Severity],
+    review_cache: Option<&core::ReviewCache>,
+) -> Result<Vec<Vec<core::Comment>>> {
+    let prompt_builder = core::PromptBuilder::new(files[0].prompt_config.clone());
+    let (system_prompt, user_prompt) = match files {
+        [file] => prompt_builder.build_prompt(&file.diff, &file.context_chunks)?,
+        _ => {
+            let prompt_files: Vec<_> = files
+                .iter()
+                .map(|file| (&file.diff, file.context_chunks.as_slice()))
+                .collect();
+            prompt_builder.build_multi_file_prompt(&prompt_files)?
+        }
+    };
+    let response = adapter
+        .complete(adapters::llm::LLMRequest {
+            system_prompt,
+            user_prompt,
+            temperature: None,
+            max_tokens: None,
+            cached_context: shared_context.map(str::to_string),
+        })
+        .await?;
+
+    let raw_comments = match files {
+        [file] => parse_llm_response(&response.content, &file.diff.file_path),
+        _ => {
+            let paths: Vec<&Path> = files
+                .iter()
+                .map(|file| file.diff.file_path.as_path())
+                .collect();
+            parse_multi_file_response(&response.content, &paths)
+        }
+    }
+    .unwrap_or_default();
+    let mut by_file = vec![Vec::new(); files.len()];
+    for comment in core::CommentSynthesizer::synthesize(raw_comments)? {
+        if let Some(index) = files
+            .iter()
+            .position(|file| file.diff.file_path == comment.file_path)
+        {
+            by_file[index].push(comment);
+        }
+    }
+
+    let mut reviewed = Vec::with_capacity(files.len());
+    for (file, comments) in files.iter().zip(by_file) {
+        let comments = filter_comments_for_diff(&file.diff, comments);
+        let comments =
+            core::verification::verify_findings(adapter, &file.diff, comments, verified_severities)
+                .await;
+        if let Some(cache) = review_cache {
+            if let Err(err) = cache.store(&file.diff, &file.prompt_config.fingerprint(), &comments)
+            {
+                warn!("Failed to write review cache: {}", err);
+            }
+        }
+        reviewed.push(comments);
+    }
+    Ok(reviewed)
+}
+
+/// Applies the `severity_overrides` of a file's path configuration to its comments.
+pub fn apply_severity_overrides(
+    path_config: Option<&config::PathConfig>,
+    comments: &mut [core::Comment],
+) {
+    let Some(pc) = path_config else {
+        return;
+    };
+    for comment in comments {
+        for (category, severity) in &pc.severity_overrides {
+            if format!("{:?}", comment.category).to_lowercase() == category.to_lowercase() {
+                comment.severity = match severity.to_lowercase().as_str() {
+                    "error" => core::comment::Severity::Error,
+                    "warning" => core::comment::Severity::Warning,
+                    "info" => core::comment::Severity::Info,
+                    "suggestion" => core::comment::Severity::Suggestion,
+                    _ => comment.severity.clone(),
+                };
+            }
+        }
+    }
+}
+
+/// Findings of the lockfile-aware analyzers when `diff` is a lockfile that
+/// `languages.auto_skip` keeps out of the model review; empty for any other file.
+pub async fn skipped_lockfile_findings(
+    config: &config::Config,
+    plugin_manager: &plugins::plugin::PluginManager,
+    diff: &core::UnifiedDiff,
+    repo_path: &str,
+) -> Vec<core::Comment> {
+    if diff.is_deleted || diff.is_binary || !config.is_auto_skipped_lockfile(&diff.file_path) {
+        return Vec::new();
+    }
+    let mut findings = plugin_manager.run_lockfile_analyzers(diff, repo_path).await;
+    apply_severity_overrides(config.get_path_config(&diff.file_path), &mut findings);
+    findings
+}
+
+/// Line ranges to pull file context for. Brand-new files have no surrounding code to
+/// anchor on, so the whole file is included instead of just the hunks.
+pub fn context_line_ranges(diff: &core::UnifiedDiff) -> Vec<(usize, usize)> {
+    if diff.is_new {
+        return vec![(1, usize::MAX)];
+    }
+    diff.hunks
+        .iter()
+        .map(|h| (h.new_start, h.new_start + h.new_lines.saturating_sub(1)))
+        .collect()
+}
+
+pub fn extract_symbols_from_diff(diff: &core::UnifiedDiff) -> Vec<String> {
+    extract_symbols(
+        diff.hunks
+            .iter()
+            .flat_map(|hunk| &hunk.changes)
+            .filter(|line| {
+                matches!(
+                    line.change_type,
+                    core::diff_parser::ChangeType::Added | core::diff_parser::ChangeType::Removed
+                )
+            })
+            .map(|line| line.content.as_str()),
+    )
+}
+
+pub fn extract_symbols<'a>(lines: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut symbols = Vec::new();
+    static SYMBOL_REGEX: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"\b([A-Z][a-zA-Z0-9_]*|[a-z][a-zA-Z0-9_]*)\s*\(").unwrap());
+    static CLASS_REGEX: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"\b(class|struct|interface|enum)\s+([A-Z][a-zA-Z0-9_]*)").unwrap()
+    });
+
+    for line in lines {
+        // Extract function calls and references
+        for capture in SYMBOL_REGEX.captures_iter(line) {
+            if let Some(symbol) = capture.get(1) {
+                let symbol_str = symbol.as_str().to_string();
+                if symbol_str.len() > 2 && !symbols.contains(&symbol_str) {
+                    symbols.push(symbol_str);
+                }
+            }
+        }
+
+        // Also look for class/struct references
+        for capture in CLASS_REGEX.captures_iter(line) {
+            if let Some(class_name) = capture.get(2) {
+                let class_str = class_name.as_str().to_string();
+                if !symbols.contains(&class_str) {
+                    symbols.push(class_str);
+                }
+            }
+        }
+    }
+
+    symbols
+}
+
+/// Call sites of functions whose signature the diff changed, so stale callers can be flagged.
+pub async fn fetch_caller_context(
+    config: &config::Config,
+    context_fetcher: &core::ContextFetcher,
+    diff: &core::UnifiedDiff,
+) -> Result<Vec<core::LLMContextChunk>> {
+    let changed = core::function_scope::changed_function_signatures(diff);
+    if changed.is_empty() || config.max_reference_sites == 0 {
+        return Ok(Vec::new());
+    }
+    context_fetcher
+        .fetch_symbol_references(
+            &diff.file_path,
+            &changed,
+            config.symbol_index_max_files,
+            config.max_reference_sites,
+            |path| config.should_exclude(path),
+        )
+        .await
+}
+
+/// Everything a file's review prompt draws on. Every review path, and `--dry-run`'s estimate,
+/// gathers a file's context through [`ContextSources::for_file`] so they all send the same
+/// inputs.
+pub struct ContextSources<'a> {
+    pub config: &'a config::Config,
+    pub repo_path: &'a str,
+    pub context_fetcher: &'a core::ContextFetcher,
+    pub plugin_manager: &'a plugins::plugin::PluginManager,
+    pub symbol_index: Option<&'a core::SymbolIndex>,
+    pub repo_map: Option<&'a core::repo_map::RepoMap>,
+    pub semantic_search: Option<&'a core::embeddings::SemanticSearch>,
+    pub hotspot_notes: &'a HashMap<PathBuf, core::LLMContextChunk>,
+}
+
+/// The context for one file's review prompt, and the deterministic findings the pre-analyzers
+/// reported while producing it.
+pub struct FileContext {
+    pub chunks: Vec<core::LLMContextChunk>,
+    pub findings: Vec<core::Comment>,
+}
+
+impl ContextSources<'_> {
+    /// The repository map, rendered once for all of `diffs` so every request of the review
+    /// can send it as the same cacheable `cached_context`.
+    pub fn shared_context(&self, diffs: &[core::UnifiedDiff]) -> Option<String> {
+        let changed: Vec<&Path> = diffs.iter().map(|diff| diff.file_path.as_path()).collect();
+        self.repo_map
+            .and_then(|map| map.shared_context(&changed, self.config.repo_map.max_chars))
+    }
+
+    /// Surrounding code, analyzer output, related definitions and call sites, hotspot notes,
+    /// semantic matches, and path focus areas and extra context for `diff`.
+    pub async fn for_file(&self, diff: &core::UnifiedDiff) -> Result<FileContext> {
+        let config = self.config;
+        let context_fetcher = self.context_fetcher;
+        let mut chunks = context_fetcher
+            .fetch_context_for_file(&diff.file_path, &context_line_ranges(diff))
+            .await?;
+
+        // Run pre-analyzers to get additional context and deterministic findings
+        let analysis = self
+            .plugin_manager
+            .run_pre_analyzers(diff, self.repo_path)
+            .await?;
+        chunks.extend(analysis.chunks);
+
+        // Extract symbols from diff and fetch their definitions
+        let symbols = extract_symbols_from_diff(diff);
+        if !symbols.is_empty() {
+            chunks.extend(
+                context_fetcher
+                    .fetch_related_definitions(&diff.file_path, &symbols)
+                    .await?,
+            );
+            if let Some(index) = self.symbol_index {
+                chunks.extend(
+                    context_fetcher
+                        .fetch_related_definitions_with_index(
+                            &diff.file_path,
+                            &symbols,
+                            index,
+                            config.symbol_index_max_locations,
+                        )
+                        .await?,
+                );
+            }
+        }
+        chunks.extend(fetch_caller_context(config, context_fetcher, diff).await?);
+        chunks.extend(self.hotspot_notes.get(&diff.file_path).cloned());
+        if let Some(search) = self.semantic_search {
+            chunks.extend(search.related(diff).await);
+        }
+
+        // Add focus areas and extra context if configured
+        if let Some(pc) = config.get_path_config(&diff.file_path) {
+            if !pc.focus.is_empty() {
+                chunks.push(core::LLMContextChunk {
+                    content: format!("Focus areas for this file: {}", pc.focus.join(", ")),
+                    context_type: core::ContextType::Documentation,
+                    file_path: diff.file_path.clone(),
+                    line_range: None,
+                });
+            }
+            if !pc.extra_context.is_empty() {
+                chunks.extend(
+                    context_fetcher
+                        .fetch_additional_context(&pc.extra_context)
+                        .await?,
+                );
+            }
+        }
+
+        Ok(FileContext {
+            chunks,
+            findings: analysis.findings,
+        })
+    }
+}
+
+pub fn filter_comments_for_diff(
+    diff: &core::UnifiedDiff,
+    comments: Vec<core::Comment>,
+) -> Vec<core::Comment> {
+    let mut filtered = Vec::new();
+    let total = comments.len();
+    for comment in comments {
+        if is_line_in_diff(diff, comment.line_number) {
+            filtered.push(comment);
+        }
+    }
+
+    if filtered.len() != total {
+        let dropped = total.saturating_sub(filtered.len());
+        info!(
+            "Dropped {} comment(s) for {} due to unmatched line numbers",
+            dropped,
+            diff.file_path.display()
+        );
+    }
+
+    filtered
+}
+
+/// The prompt settings every file starts from: limits, the `prompt_template`, and the ticket.
+pub fn base_prompt_config(config: &config::Config) -> Result<core::prompt::PromptConfig> {
+    let custom_template = config
+        .prompt_template
+        .as_deref()
+        .map(core::prompt::load_template)
+        .transpose()?;
+    Ok(core::prompt::PromptConfig {
+        limits: config.prompt_limits(),
+        custom_template,
+        ticket: config.ticket.clone(),
+        follow_up: config.follow_up.enabled.then_some((
+            config.follow_up.max_rounds,
+            config.follow_up.max_context_tokens,
+        )),
+        ..Default::default()
+    })
+}
+
+/// `base` specialized for the file at `file_path`: its system prompt with the review guidance
+/// appended, and its review instructions as the template's conventions.
+pub fn file_prompt_config(
+    config: &config::Config,
+    base: &core::prompt::PromptConfig,
+    file_path: &Path,
+    path_config: Option<&config::PathConfig>,
+) -> core::prompt::PromptConfig {
+    let mut prompt_config = base.clone();
+    if let Some(prompt) = path_config
+        .and_then(|pc| pc.system_prompt.as_ref())
+        .or(config.system_prompt.as_ref())
+    {
+        prompt_config.system_prompt = prompt.clone();
+    }
+    if let Some(guidance) = build_review_guidance(config, file_path, path_config) {
+        prompt_config.system_prompt.push_str("\n\n");
+        prompt_config.system_prompt.push_str(&guidance);
+    }
+    let conventions: Vec<&str> = [
+        config.review_instructions.as_deref(),
+        path_config.and_then(|pc| pc.review_instructions.as_deref()),
+    ]
+    .into_iter()
+    .flatten()
+    .map(str::trim)
+    .filter(|instructions| !instructions.is_empty())
+    .collect();
+    if !conventions.is_empty() {
+        prompt_config.conventions = Some(conventions.join("\n\n"));
+    }
+    prompt_config
+}
+
+pub fn build_review_guidance(
+    config: &config::Config,
+    file_path: &Path,
+    path_config: Option<&config::PathConfig>,
+) -> Option<String> {
+    let mut sections = Vec::new();
+
+    if let Some(profile) = config.review_profile.as_deref() {
+        let guidance = match profile {
+            "chill" => Some(
+                "Be conservative and only surface high-confidence, high-impact issues. Avoid nitpicks and redundant comments.",
+            ),
+            "assertive" => Some(
+                "Be thorough and proactive. Surface edge cases, latent risks, and maintainability concerns even if they are subtle.",
+            ),
+            _ => None,
+        };
+        if let Some(text) = guidance {
+            sections.push(format!("Review profile ({}): {}", profile, text));
+        }
+    }
+
+    if let Some(instructions) = config.review_instructions.as_deref() {
+        let trimmed = instructions.trim();
+        if !trimmed.is_empty() {
+            sections.push(format!("Global review instructions:\n{}", trimmed));
+        }
+    }
+
+    if let Some(pc) = path_config {
+        if let Some(instructions) = pc.review_instructions.as_deref() {
+            let trimmed = instructions.trim();
+            if !trimmed.is_empty() {
+                sections.push(format!("Path-specific instructions:\n{}", trimmed));
+            }
+        }
+    }
+
+    if config.language_guidance.enabled {
+        if let Some((language, advice)) =
+            core::language_guidance::for_path(file_path, &config.language_guidance.overrides)
+        {
+            sections.push(format!("Language guidance ({}): {}", language, advice));
+        }
+    }
+
+    if sections.is_empty() {
+        None
+    } else {
+        Some(format!(
+            "Additional review guidance:\n{}",
+            sections.join("\n\n")
+        ))
+    }
+}
+
+/// Semantic search over `repo_root` when `embeddings` is enabled. Indexing problems disable
+/// it for the run instead of failing the review.
+pub async fn build_semantic_search(
+    config: &config::Config,
+    repo_root: &Path,
+) -> Option<core::embeddings::SemanticSearch> {
+    if !config.embeddings.enabled {
+        return None;
+    }
+    if config.llm_replay_dir.is_some() {
+        info!("Not searching embeddings while replaying recorded LLM responses");
+        return None;
+    }
+    let embedder = match adapters::embeddings::HttpEmbedder::new(config.embeddings_model_config()) {
+        Ok(embedder) => embedder,
+        Err(err) => {
+            warn!("Semantic search disabled: {:#}", err);
+            return None;
+        }
+    };
+    let excluded = config.exclusion_filter(repo_root);
+    let chunks = core::embeddings::chunk_repository(
+        repo_root,
+        config.embeddings.chunk_lines,
+        config.embeddings.max_chunks,
+        config.symbol_index_max_bytes,
+        |path| excluded(path),
+    );
+    let cache_file = config
+        .embeddings
+        .cache_dir
+        .clone()
+        .unwrap_or_else(|| core::ReviewCache::default_dir().with_file_name("embeddings"))
+        .join(format!(
+            "{}.json",
+            core::cache::stable_hash(&[&config.embeddings.model, &repo_root.to_string_lossy()])
+        ));
+    match core::embeddings::EmbeddingIndex::build(&embedder, chunks, Some(&cache_file)).await {
+        Ok(index) => {
+            info!("Semantic search over {} code chunks", index.len());
+            Some(core::embeddings::SemanticSearch {
+                embedder: Box::new(embedder),
+                index,
+                top_k: config.embeddings.top_k,
+                min_similarity: config.embeddings.min_similarity,
+            })
+        }
+        Err(err) => {
+            warn!("Semantic search disabled: {:#}", err);
+            None
+        }
+    }
+}
+
+/// The repository map when `repo_map` is enabled and there is a symbol index to draw it from.
+pub fn build_repo_map(
+    config: &config::Config,
+    symbol_index: Option<&core::SymbolIndex>,
+) -> Option<core::repo_map::RepoMap> {
+    if !config.repo_map.enabled {
+        return None;
+    }
+    let map = core::repo_map::RepoMap::from_index(symbol_index?);
+    (!map.is_empty()).then_some(map)
+}
+
+/// The follow-up loop when `follow_up` is enabled.
+pub fn build_follow_up<'a>(
+    config: &config::Config,
+    context_fetcher: &'a core::ContextFetcher,
+    symbol_index: Option<&'a core::SymbolIndex>,
+) -> Option<core::follow_up::FollowUp<'a>> {
+    config.follow_up.enabled.then(|| core::follow_up::FollowUp {
+        fetcher: context_fetcher,
+        symbol_index,
+        max_symbol_locations: config.symbol_index_max_locations,
+        max_rounds: config.follow_up.max_rounds,
+        max_context_tokens: config.follow_up.max_context_tokens,
+        limits: config.prompt_limits(),
+    })
+}
+
+/// The screening model's adapter when `triage` is enabled.
+pub fn build_triage_adapter(
+    config: &config::Config,
+) -> Result<Option<Box<dyn adapters::llm::LLMAdapter>>> {
+    if !config.triage.enabled {
+        return Ok(None);
+    }
+    info!(
+        "Triaging hunks with {} (threshold {})",
+        config.triage.model, config.triage.threshold
+    );
+    Ok(Some(adapters::llm::create_adapter(
+        &config.triage_model_config(),
+    )?))
+}
+
+pub fn build_review_cache(config: &config::Config) -> Option<core::ReviewCache> {
+    if !config.review_cache || config.llm_replay_dir.is_some() {
+        return None;
+    }
+    let dir = config
+        .review_cache_dir
+        .clone()
+        .unwrap_or_else(core::ReviewCache::default_dir);
+    Some(core::ReviewCache::new(dir, &config.model))
+}
+
+/// The language server for each extension in `symbol_index_lsp_languages`: its
+/// `symbol_index_lsp_commands` entry, else `symbol_index_lsp_command`, else the installed
+/// server detected for it.
+pub fn resolve_lsp_commands(config: &config::Config, repo_root: &Path) -> HashMap<String, String> {
+    let detected = if config.symbol_index_lsp_command.is_none() {
+        core::SymbolIndex::detect_lsp_commands(
+            repo_root,
+            config.symbol_index_max_files,
+            &config.symbol_index_lsp_languages,
+            |path| config.should_exclude(path),
+        )
+    } else {
+        HashMap::new()
+    };
+    config
+        .symbol_index_lsp_languages
+        .keys()
+        .filter_map(|ext| {
+            let command = config
+                .symbol_index_lsp_commands
+                .get(ext)
+                .or(config.symbol_index_lsp_command.as_ref())
+                .or_else(|| detected.get(ext))?;
+            Some((ext.clone(), command.clone()))
+        })
+        .collect()
+}
+
+pub fn build_symbol_index(config: &config::Config, repo_root: &Path) -> Option<core::SymbolIndex> {
+    if !config.symbol_index {
+        return None;
+    }
+
+    let excluded = config.exclusion_filter(repo_root);
+    let provider = config.symbol_index_provider.as_str();
+    let result = if provider == "lsp" {
+        let commands = resolve_lsp_commands(config, repo_root);
+        if !commands.is_empty() {
+            if config.symbol_index_lsp_command.is_none() {
+                let mut servers: Vec<String> = commands
+                    .iter()
+                    .map(|(ext, command)| format!("{}: {}", ext, command))
+                    .collect();
+                servers.sort();
+                info!("LSP servers: {}", servers.join(", "));
+            }
+
+            let lsp = core::LspIndexing {
+                commands,
+                languages: &config.symbol_index_lsp_languages,
+                workers: config.symbol_index_lsp_workers,
+                timeout: std::time::Duration::from_secs(config.symbol_index_lsp_timeout_secs),
+            };
+            match core::SymbolIndex::build_with_lsp(
+                repo_root,
+                config.symbol_index_max_files,
+                config.symbol_index_max_bytes,
+                config.symbol_index_max_locations,
+                &lsp,
+                |path| excluded(path),
+            ) {
+                Ok(index) => Ok(index),
+                Err(err) => {
+                    warn!("LSP indexer failed (falling back to regex): {}", err);
+                    core::SymbolIndex::build(
+                        repo_root,
+                        config.symbol_index_max_files,
+                        config.symbol_index_max_bytes,
+                        config.symbol_index_max_locations,
+                        |path| excluded(path),
+                    )
+                }
+            }
+        } else {
+            warn!("No LSP command configured or detected; falling back to regex indexer.");
+            core::SymbolIndex::build(
+                repo_root,
+                config.symbol_index_max_files,
+                config.symbol_index_max_bytes,
+                config.symbol_index_max_locations,
+                |path| excluded(path),
+            )
+        }
+    } else {
+        core::SymbolIndex::build(
+            repo_root,
+            config.symbol_index_max_files,
+            config.symbol_index_max_bytes,
+            config.symbol_index_max_locations,
+            |path| excluded(path),
+        )
+    };
+
+    match result {
+        Ok(index) => {
+            info!(
+                "Indexed {} symbols across {} files",
+                index.symbols_indexed(),
+                index.files_indexed()
+            );
+            Some(index)
+        }
+        Err(err) => {
+            warn!("Symbol index build failed: {}", err);
+            None
+        }
+    }
+}
+
+/// Notes for the files in `diffs` that are known hotspots, keyed by path, when
+/// `hotspots.context` is on.
+pub fn load_hotspot_notes(
+    config: &config::Config,
+    repo_root: &Path,
+    diffs: &[core::UnifiedDiff],
+) -> HashMap<PathBuf, core::LLMContextChunk> {
+    let settings = &config.hotspots;
+    if !settings.context {
+        return HashMap::new();
+    }
+    let paths: Vec<PathBuf> = diffs.iter().map(|diff| diff.file_path.clone()).collect();
+    let hotspots = core::GitIntegration::new(repo_root)
+        .and_then(|git| core::hotspots::analyze(&git, repo_root, Some(&paths), settings.days));
+    match hotspots {
+        Ok(hotspots) => hotspots
+            .into_iter()
+            .filter(|hotspot| hotspot.fixes >= settings.min_fixes)
+            .map(|hotspot| {
+                let chunk = core::LLMContextChunk {
+                    content: core::hotspots::context_note(&hotspot, settings.days),
+                    context_type: core::ContextType::Documentation,
+                    file_path: hotspot.path.clone(),
+                    line_range: None,
+                };
+                (hotspot.path, chunk)
+            })
+            .collect(),
+        Err(err) => {
+            warn!("Skipping hotspot context: {:#}", err);
+            HashMap::new()
+        }
+    }
+}
+
+/// Sets each comment's `owners` from the CODEOWNERS file in `repo_root`, if enabled.
+pub fn apply_code_owners(
+    config: &config::Config,
+    repo_root: &Path,
+    comments: &mut [core::Comment],
+) {
+    if !config.codeowners.enabled {
+        return;
+    }
+    if let Some(owners) = core::codeowners::CodeOwners::load(repo_root) {
+        owners.assign(comments);
+    }
+}
+
+/// Recalibrates confidences against the accept/reject history in `feedback` (see
+/// `core::calibration`) when `confidence_calibration` is on.
+pub fn apply_confidence_calibration(
+    config: &config::Config,
+    feedback: &core::feedback::FeedbackStore,
+    comments: &mut [core::Comment],
+) {
+    if !config.confidence_calibration {
+        return;
+    }
+    let curves = core::calibration::CalibrationCurves::from_feedback(feedback);
+    if curves.is_empty() {
+        return;
+    }
+    let changed = curves.apply(comments);
+    if changed > 0 {
+        info!(
+            "Calibrated the confidence of {} comment(s) from feedback history",
+            changed
+        );
+    }
+}
+
+/// Scales confidences by `category_weights`, capped at 1.0.
+pub fn apply_category_weights(config: &config::Config, comments: &mut [core::Comment]) {
+    if config.category_weights.is_empty() {
+        return;
+    }
+    for comment in comments {
+        let category = format!("{:?}", comment.category).to_lowercase();
+        let weight = config
+            .category_weights
+            .iter()
+            .find(|(name, _)| name.to_lowercase() == category)
+            .map(|(_, weight)| *weight);
+        if let Some(weight) = weight.filter(|weight| weight.is_finite() && *weight >= 0.0) {
+            comment.confidence = (comment.confidence * weight).min(1.0);
+        }
+    }
+}
+
+/// The local feedback store, or an empty one when it cannot be read.
+pub fn load_feedback(config: &config::Config) -> core::feedback::FeedbackStore {
+    core::feedback::FeedbackLog::open(config, None)
+        .and_then(|log| log.snapshot())
+        .unwrap_or_else(|err| {
+            warn!("Ignoring feedback store: {:#}", err);
+            Default::default()
+        })
+}
+
+pub fn apply_confidence_threshold(
+    comments: Vec<core::Comment>,
+    min_confidence: f32,
+) -> Vec<core::Comment> {
+    if min_confidence <= 0.0 {
+        return comments;
+    }
+
+    let total = comments.len();
+    let mut kept = Vec::with_capacity(total);
+
+    for comment in comments {
+        if comment.confidence >= min_confidence {
+            kept.push(comment);
+        }
+    }
+
+    if kept.len() != total {
+        let dropped = total.saturating_sub(kept.len());
+        info!(
+            "Dropped {} comment(s) below confidence threshold {}",
+            dropped, min_confidence
+        );
+    }
+
+    kept
+}
+
+/// Drops comments the feedback store says to suppress: ones rejected before, in this
+/// repository or globally.
+pub fn apply_feedback_suppression(
+    comments: Vec<core::Comment>,
+    feedback: &core::feedback::FeedbackStore,
+    repo: Option<&str>,
+    similarity: f32,
+) -> Vec<core::Comment> {
+    if feedback.suppress.is_empty() {
+        return comments;
+    }
+
+    let total = comments.len();
+    let mut kept = Vec::with_capacity(total);
+
+    for comment in comments {
+        if feedback.is_suppressed(&comment, repo, similarity) {
+            continue;
+        }
+        kept.push(comment);
+    }
+
+    if kept.len() != total {
+        let dropped = total.saturating_sub(kept.len());
+        info!(
+            "Dropped {} comment(s) due to feedback suppression rules",
+            dropped
+        );
+    }
+
+    kept
+}
+
+pub fn is_line_in_diff(diff: &core::UnifiedDiff, line_number: usize) -> bool {
+    if line_number == 0 {
+        return false;
+    }
+    diff.hunks.iter().any(|hunk| {
+        hunk.changes
+            .iter()
+            .any(|line| line.new_line_no == Some(line_number))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct CountingProcessor(AtomicUsize);
+
+    #[async_trait::async_trait]
+    impl PostProcessor for CountingProcessor {
+        fn id(&self) -> &str {
+            "counting"
+        }
+
+        async fn run(
+            &self,
+            comments: Vec<core::Comment>,
+            _repo_path: &str,
+        ) -> Result<Vec<core::Comment>> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Ok(comments)
+        }
+    }
+
+    /// Adds fixed findings, standing in for what the model would report.
+    struct FindingProcessor(Vec<core::Comment>);
+
+    #[async_trait::async_trait]
+    impl PostProcessor for FindingProcessor {
+        fn id(&self) -> &str {
+            "findings"
+        }
+
+        async fn run(
+            &self,
+            mut comments: Vec<core::Comment>,
+            _repo_path: &str,
+        ) -> Result<Vec<core::Comment>> {
+            comments.extend(self.0.iter().cloned());
+            Ok(comments)
+        }
+    }
+
+    #[tokio::test]
+    async fn runs_registered_plugins_and_reuses_the_symbol_index() {
+        let repo = tempfile::tempdir().unwrap();
+        std::fs::write(repo.path().join("lib.rs"), "pub fn total() -> u32 { 1 }\n").unwrap();
+        let config = config::Config {
+            api_key: Some("unused".to_string()),
+            symbol_index_provider: "regex".to_string(),
+            ..Default::default()
+        };
+        let processor = Arc::new(CountingProcessor(AtomicUsize::new(0)));
+        let mut engine = ReviewEngine::new(config).with_repo_root(repo.path());
+        engine.register_post_processor(processor.clone());
+
+        // A deleted file is skipped without calling the model.
+        let comments = engine
+            .review_diff("--- a/old.rs\n+++ /dev/null\n@@ -1,1 +0,0 @@\n-fn old() {}\n")
+            .await
+            .unwrap();
+        assert!(comments.is_empty());
+        assert_eq!(processor.0.load(Ordering::SeqCst), 1);
+
+        let index = engine.symbol_index().unwrap();
+        assert!(index.lookup("total").is_some());
+        assert!(std::ptr::eq(index, engine.symbol_index().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn suppresses_findings_rejected_before() {
+        let repo = tempfile::tempdir().unwrap();
+        let config = config::Config {
+            api_key: Some("unused".to_string()),
+            symbol_index: false,
+            review_cache: false,
+            feedback_path: repo.path().join("feedback.json"),
+            ..Default::default()
+        };
+        let finding = |id: &str, content: &str| core::Comment {
+            id: id.to_string(),
+            file_path: PathBuf::from("src/lib.rs"),
+            line_number: 3,
+            content: content.to_string(),
+            severity: core::comment::Severity::Warning,
+            category: core::comment::Category::Bug,
+            suggestion: None,
+            confidence: 0.9,
+            code_suggestion: None,
+            tags: Vec::new(),
+            fix_effort: core::comment::FixEffort::Low,
+            owners: Vec::new(),
+        };
+        let rejected = finding("cmt_rejected", "Avoid unwrap on user input");
+        let mut log = core::feedback::FeedbackLog::open(&config, None).unwrap();
+        log.record(&core::feedback::FeedbackEvent::for_comment(
+            &rejected,
+            core::feedback::Decision::Reject,
+            core::feedback::FeedbackScope::Global,
+            None,
+        ))
+        .unwrap();
+        log.save().unwrap();
+        let mut engine = ReviewEngine::new(config).with_repo_root(repo.path());
+        engine.register_post_processor(Arc::new(FindingProcessor(vec![
+            rejected,
+            finding("cmt_kept", "Off-by-one in the loop bound"),
+        ])));
+
+        let comments = engine
+            .review_diff("--- a/old.rs\n+++ /dev/null\n@@ -1,1 +0,0 @@\n-fn old() {}\n")
+            .await
+            .unwrap();
+
+        let ids: Vec<_> = comments.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(ids, ["cmt_kept"]);
+    }
+
+    #[tokio::test]
+    async fn audits_lockfiles_that_auto_skip_keeps_from_the_model() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/v1/querybatch")
+            .with_body(r#"{"results": [{"vulns": [{"id": "RUSTSEC-2020-0071"}]}]}"#)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/v1/vulns/RUSTSEC-2020-0071")
+            .with_body(r#"{"id": "RUSTSEC-2020-0071", "summary": "Potential segfault in the time crate", "aliases": ["CVE-2020-26235"]}"#)
+            .create_async()
+            .await;
+        let repo = tempfile::tempdir().unwrap();
+        let recordings = tempfile::tempdir().unwrap();
+        // Parsed, so every plugin and `languages.auto_skip` keep their default settings.
+        let mut config: config::Config = serde_yaml::from_str(
+            "symbol_index: false\nreview_cache: false\nplugins:\n  dependency_audit: true\n",
+        )
+        .unwrap();
+        // No recordings: a model request would skip the file instead of auditing it.
+        config.llm_replay_dir = Some(recordings.path().to_path_buf());
+        config.plugins.settings.insert(
+            "dependency_audit".to_string(),
+            serde_json::from_value(serde_json::json!({ "osv_url": server.url() })).unwrap(),
+        );
+        let engine = ReviewEngine::new(config).with_repo_root(repo.path());
+
+        let outcome = engine
+            .review("--- a/Cargo.lock\n+++ b/Cargo.lock\n@@ -10,3 +10,3 @@\n [[package]]\n name = \"time\"\n-version = \"0.1.43\"\n+version = \"0.1.44\"\n")
+            .await
+            .unwrap();
+
+        assert!(outcome.skipped.is_empty());
+        let contents: Vec<_> = outcome
+            .comments
+            .iter()
+            .map(|c| c.content.as_str())
+            .collect();
+        assert_eq!(
+            contents,
+            ["time 0.1.44 has known vulnerabilities: CVE-2020-26235. Potential segfault in the time crate"]
+        );
+    }
+
+    #[tokio::test]
+    async fn rejected_api_keys_fail_the_review() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", mockito::Matcher::Any)
+            .with_status(401)
+            .with_body(r#"{"error": {"message": "Incorrect API key provided"}}"#)
+            .create_async()
+            .await;
+        let repo = tempfile::tempdir().unwrap();
+        let config = config::Config {
+            symbol_index: false,
+            review_cache: false,
+            api_key: Some("sk-wrong".to_string()),
+            base_url: Some(server.url()),
+            ..Default::default()
+        };
+        let engine = ReviewEngine::new(config).with_repo_root(repo.path());
+
+        let err = engine
+            .review("--- a/a.rs\n+++ b/a.rs\n@@ -1 +1 @@\n-fn a() {}\n+fn a() -> u8 { 1 }\n")
+            .await
+            .unwrap_err();
+        assert!(format!("{:#}", err).contains("401 Unauthorized"));
+    }
+
+    #[tokio::test]
+    async fn failed_model_requests_skip_files_instead_of_failing() {
+        let repo = tempfile::tempdir().unwrap();
+        let recordings = tempfile::tempdir().unwrap();
+        let config = config::Config {
+            symbol_index: false,
+            review_cache: false,
+            // No recordings, so every model request fails.
+            llm_replay_dir: Some(recordings.path().to_path_buf()),
+            ..Default::default()
+        };
+        let engine = ReviewEngine::new(config).with_repo_root(repo.path());
+
+        let outcome = engine
+            .review("--- a/a.rs\n+++ b/a.rs\n@@ -1 +1 @@\n-fn a() {}\n+fn a() -> u8 { 1 }\n--- a/b.rs\n+++ b/b.rs\n@@ -1 +1 @@\n-fn b() {}\n+fn b() -> u8 { 2 }\n")
+            .await
+            .unwrap();
+
+        assert!(outcome.comments.is_empty());
+        let skipped: Vec<_> = outcome
+            .skipped
+            .iter()
+            .map(|file| file.file_path.to_str().unwrap())
+            .collect();
+        assert_eq!(skipped, vec!["a.rs", "b.rs"]);
+        assert!(outcome.skipped[0]
+            .error
+            .contains("No recorded LLM response"));
+    }
+
+    #[tokio::test]
+    async fn every_request_shares_one_cacheable_repo_map() {
+        let repo = tempfile::tempdir().unwrap();
+        std::fs::write(repo.path().join("a.rs"), "pub fn alpha() -> u8 { 1 }\n").unwrap();
+        std::fs::write(repo.path().join("b.rs"), "pub fn beta() -> u8 { 2 }\n").unwrap();
+        let recordings = tempfile::tempdir().unwrap();
+        let log = tempfile::tempdir().unwrap();
+        let config = config::Config {
+            review_cache: false,
+            batching: config::BatchingConfig {
+                enabled: false,
+                ..Default::default()
+            },
+            llm_replay_dir: Some(recordings.path().to_path_buf()),
+            llm_log_dir: Some(log.path().to_path_buf()),
+            ..Default::default()
+        };
+        let engine = ReviewEngine::new(config).with_repo_root(repo.path());
+
+        engine
+            .review("--- a/a.rs\n+++ b/a.rs\n@@ -1 +1 @@\n-pub fn alpha() {}\n+pub fn alpha() -> u8 { 1 }\n--- a/b.rs\n+++ b/b.rs\n@@ -1 +1 @@\n-pub fn beta() {}\n+pub fn beta() -> u8 { 2 }\n")
+            .await
+            .unwrap();
+
+        let requests: Vec<adapters::llm::LLMRequest> = std::fs::read_dir(log.path())
+            .unwrap()
+            .map(|entry| {
+                let content = std::fs::read_to_string(entry.unwrap().path()).unwrap();
+                serde_json::from_str::<adapters::replay::LLMExchange>(&content)
+                    .unwrap()
+                    .request
+            })
+            .collect();
+        assert_eq!(requests.len(), 2);
+        let map = requests[0].cached_context.as_deref().unwrap();
+        assert!(map.contains("a.rs: alpha") && map.contains("b.rs: beta"));
+        assert_eq!(requests[1].cached_context.as_deref(), Some(map));
+        assert!(requests
+            .iter()
+            .all(|request| !request.user_prompt.contains("Repository Map")));
+    }
+
+    #[test]
+    fn attributes_multi_file_findings_to_their_files() {
+        let response = "Here are the issues:\n\nFile: src/a.rs\nLine 3: Bug - Off by one. Use `<=`.\n\n**File: `b.rs`**\nLine 7: Security - Token logged.\nFile: src/unknown.rs\nLine 1: Bug - Dropped.\nFile: rc/b.rs\nLine 2: Bug - Partial name.\nFile: src/a.rs Line 9: Bug - Unchecked index.\n";
+        let a = Path::new("src/a.rs");
+        let b = Path::new("src/b.rs");
+
+        let comments = parse_multi_file_response(response, &[a, b]).unwrap();
+        let found: Vec<(&Path, usize)> = comments
+            .iter()
+            .map(|comment| (comment.file_path.as_path(), comment.line_number))
+            .collect();
+        assert_eq!(found, vec![(a, 3), (b, 7), (a, 9)]);
+    }
+}
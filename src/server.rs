@@ -0,0 +1,996 @@
+use crate::config::Config;
+use crate::core;
+use crate::providers::github::{stale_review_comments, GitHubClient};
+use crate::providers::gitlab::GitLabClient;
+use anyhow::Result;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use serde::Deserialize;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+use tokio::process::Command;
+use tokio::sync::Semaphore;
+use tracing::{info, warn};
+
+/// A pull/merge request that a webhook asked us to review.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReviewJob {
+    GitHub {
+        repo: String,
+        number: u64,
+    },
+    GitLab {
+        project_id: u64,
+        iid: u64,
+    },
+    /// An `@diffscope` command in a GitHub PR conversation comment, or in a review thread when
+    /// `in_review_thread` is set.
+    CommentCommand {
+        repo: String,
+        number: u64,
+        comment_id: u64,
+        body: String,
+        in_review_thread: bool,
+    },
+}
+
+struct ServerState {
+    config: Config,
+    secret: Option<String>,
+    repo_root: PathBuf,
+    /// Bounds the reviews running at once (`webhook_max_concurrent`).
+    reviews: Arc<Semaphore>,
+}
+
+/// Runs the webhook listener until the process is stopped. Reviews run in the background so
+/// webhook deliveries are acknowledged immediately. Without a webhook secret the server
+/// refuses to start unless `insecure` is set.
+pub async fn serve(
+    config: Config,
+    addr: SocketAddr,
+    repo_root: PathBuf,
+    insecure: bool,
+) -> Result<()> {
+    let secret = config
+        .webhook_secret
+        .clone()
+        .or_else(|| std::env::var("DIFFSCOPE_WEBHOOK_SECRET").ok())
+        .filter(|secret| !secret.is_empty());
+    if secret.is_none() {
+        if !insecure {
+            anyhow::bail!(
+                "No webhook secret configured. Set DIFFSCOPE_WEBHOOK_SECRET or webhook_secret, or pass --insecure to accept unsigned deliveries"
+            );
+        }
+        warn!("No webhook secret configured; accepting unsigned webhook deliveries (--insecure)");
+    }
+    let state = Arc::new(ServerState {
+        reviews: Arc::new(Semaphore::new(config.webhook_max_concurrent.max(1))),
+        config,
+        secret,
+        repo_root,
+    });
+
+    let make_service = make_service_fn(move |_| {
+        let state = state.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                handle_request(request, state.clone())
+            }))
+        }
+    });
+
+    info!("Listening for webhooks on http://{}/webhook", addr);
+    Server::try_bind(&addr)?.serve(make_service).await?;
+    Ok(())
+}
+
+async fn handle_request(
+    request: Request<Body>,
+    state: Arc<ServerState>,
+) -> Result<Response<Body>, Infallible> {
+    let response = match (request.method(), request.uri().path()) {
+        (&Method::GET, "/health") => respond(StatusCode::OK, "ok"),
+        (&Method::POST, "/webhook") => handle_webhook(request, state).await,
+        _ => respond(StatusCode::NOT_FOUND, "not found"),
+    };
+    Ok(response)
+}
+
+async fn handle_webhook(request: Request<Body>, state: Arc<ServerState>) -> Response<Body> {
+    let header = |name: &str| {
+        request
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+    };
+    let github_event = header("x-github-event");
+    let github_signature = header("x-hub-signature-256");
+    let gitlab_event = header("x-gitlab-event");
+    let gitlab_token = header("x-gitlab-token");
+
+    let body = match read_body(request.into_body()).await {
+        Ok(body) => body,
+        Err(err) => return respond(StatusCode::BAD_REQUEST, &err.to_string()),
+    };
+
+    let job = if let Some(event) = github_event {
+        if let Some(secret) = &state.secret {
+            let signed = github_signature
+                .as_deref()
+                .is_some_and(|signature| verify_github_signature(secret, &body, signature));
+            if !signed {
+                return respond(StatusCode::UNAUTHORIZED, "invalid signature");
+            }
+        }
+        parse_github_event(&event, &body, &state.config)
+    } else if let Some(event) = gitlab_event {
+        if let Some(secret) = &state.secret {
+            let valid = gitlab_token
+                .as_deref()
+                .is_some_and(|token| constant_time_eq(token.as_bytes(), secret.as_bytes()));
+            if !valid {
+                return respond(StatusCode::UNAUTHORIZED, "invalid token");
+            }
+        }
+        parse_gitlab_event(&event, &body)
+    } else {
+        return respond(StatusCode::BAD_REQUEST, "unsupported webhook");
+    };
+
+    let Some(job) = job else {
+        return respond(StatusCode::OK, "ignored");
+    };
+
+    info!("Queued review for {:?}", job);
+    tokio::spawn(async move {
+        let Ok(_permit) = state.reviews.clone().acquire_owned().await else {
+            return;
+        };
+        if let Err(err) = run_review_job(&job, &state).await {
+            warn!("Review for {:?} failed: {:#}", job, err);
+        }
+    });
+    respond(StatusCode::ACCEPTED, "review queued")
+}
+
+/// Largest request body either server reads.
+const MAX_BODY_BYTES: usize = 10 * 1024 * 1024;
+
+/// How long the local API reuses a symbol index when HEAD and the git index are unchanged,
+/// so edits that were not staged are still picked up.
+const LOCAL_INDEX_TTL: Duration = Duration::from_secs(300);
+
+/// The repository state a local API engine was built against.
+#[derive(Debug, Clone, PartialEq)]
+struct RepoState {
+    head: Option<String>,
+    index_modified: Option<SystemTime>,
+}
+
+impl RepoState {
+    fn read(repo_root: &Path) -> Self {
+        let head = std::process::Command::new("git")
+            .arg("-C")
+            .arg(repo_root)
+            .args(["rev-parse", "HEAD"])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string());
+        let index_modified = std::fs::metadata(repo_root.join(".git").join("index"))
+            .and_then(|metadata| metadata.modified())
+            .ok();
+        Self {
+            head,
+            index_modified,
+        }
+    }
+}
+
+struct LocalEngine {
+    engine: Arc<crate::ReviewEngine>,
+    state: RepoState,
+    built: Instant,
+}
+
+struct LocalState {
+    config: Config,
+    repo_root: PathBuf,
+    token: Option<String>,
+    /// The hosts a request may name: loopback, plus the address the server is bound to.
+    allowed_hosts: Vec<String>,
+    engine: tokio::sync::RwLock<LocalEngine>,
+}
+
+impl LocalState {
+    async fn new(config: Config, repo_root: PathBuf, addr: SocketAddr) -> Result<Self> {
+        let token = config
+            .local_api_token
+            .clone()
+            .or_else(|| std::env::var("DIFFSCOPE_LOCAL_TOKEN").ok())
+            .filter(|token| !token.is_empty());
+        let mut allowed_hosts: Vec<String> = ["localhost", "127.0.0.1", "[::1]"]
+            .iter()
+            .map(|host| host.to_string())
+            .collect();
+        if !addr.ip().is_loopback() {
+            if token.is_none() {
+                anyhow::bail!(
+                    "serve --local on a non-loopback address needs a token: set DIFFSCOPE_LOCAL_TOKEN or local_api_token"
+                );
+            }
+            allowed_hosts.push(match addr.ip() {
+                std::net::IpAddr::V6(ip) => format!("[{}]", ip),
+                ip => ip.to_string(),
+            });
+        }
+        let engine = build_local_engine(&config, &repo_root).await?;
+        Ok(Self {
+            config,
+            repo_root,
+            token,
+            allowed_hosts,
+            engine: tokio::sync::RwLock::new(engine),
+        })
+    }
+
+    /// The engine for the repository as it is now, re-indexed when HEAD or the git index
+    /// moved, or the index is older than `LOCAL_INDEX_TTL`.
+    async fn engine(&self) -> Result<Arc<crate::ReviewEngine>> {
+        let current = RepoState::read(&self.repo_root);
+        {
+            let engine = self.engine.read().await;
+            if engine.state == current && engine.built.elapsed() < LOCAL_INDEX_TTL {
+                return Ok(engine.engine.clone());
+            }
+        }
+        let mut engine = self.engine.write().await;
+        if engine.state != current || engine.built.elapsed() >= LOCAL_INDEX_TTL {
+            *engine = build_local_engine(&self.config, &self.repo_root).await?;
+        }
+        Ok(engine.engine.clone())
+    }
+
+    /// Why `request` may not use the API: a foreign Host (DNS rebinding), any Origin (a
+    /// browser page), or a missing or wrong bearer token.
+    fn reject(&self, request: &Request<Body>) -> Option<(StatusCode, &'static str)> {
+        let header = |name: &str| {
+            request
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+        };
+        let host = header("host").map(|host| match host.rsplit_once(':') {
+            Some((name, port)) if port.chars().all(|c| c.is_ascii_digit()) => name,
+            _ => host,
+        });
+        if !host.is_some_and(|host| {
+            self.allowed_hosts
+                .iter()
+                .any(|allowed| allowed.eq_ignore_ascii_case(host))
+        }) {
+            return Some((StatusCode::FORBIDDEN, "unexpected Host header"));
+        }
+        if header("origin").is_some() {
+            return Some((
+                StatusCode::FORBIDDEN,
+                "cross-origin requests are not allowed",
+            ));
+        }
+        if let Some(token) = &self.token {
+            let presented = header("authorization")
+                .and_then(|value| value.strip_prefix("Bearer "))
+                .unwrap_or_default();
+            if !constant_time_eq(presented.as_bytes(), token.as_bytes()) {
+                return Some((StatusCode::UNAUTHORIZED, "missing or invalid bearer token"));
+            }
+        }
+        None
+    }
+}
+
+async fn build_local_engine(config: &Config, repo_root: &Path) -> Result<LocalEngine> {
+    let state = RepoState::read(repo_root);
+    let engine =
+        Arc::new(crate::ReviewEngine::new(config.clone()).with_repo_root(repo_root.to_path_buf()));
+    let indexing = engine.clone();
+    tokio::task::spawn_blocking(move || indexing.symbol_index().is_some()).await?;
+    Ok(LocalEngine {
+        engine,
+        state,
+        built: Instant::now(),
+    })
+}
+
+#[derive(Deserialize)]
+struct DiffRequest {
+    diff: String,
+}
+
+/// Runs the JSON API used by editors and local tools until the process is stopped. The
+/// symbol index is built at startup and reused until the repository changes.
+pub async fn serve_local(config: Config, addr: SocketAddr, repo_root: PathBuf) -> Result<()> {
+    let state = Arc::new(LocalState::new(config, repo_root, addr).await?);
+
+    let make_service = make_service_fn(move |_| {
+        let state = state.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                handle_local_request(request, state.clone())
+            }))
+        }
+    });
+
+    info!("Serving the local API on http://{}", addr);
+    Server::try_bind(&addr)?.serve(make_service).await?;
+    Ok(())
+}
+
+async fn handle_local_request(
+    request: Request<Body>,
+    state: Arc<LocalState>,
+) -> Result<Response<Body>, Infallible> {
+    if request.uri().path() != "/health" {
+        if let Some((status, message)) = state.reject(&request) {
+            return Ok(respond_json(
+                status,
+                &serde_json::json!({ "error": message }),
+            ));
+        }
+    }
+    let response = match (request.method().clone(), request.uri().path()) {
+        (Method::GET, "/health") => respond(StatusCode::OK, "ok"),
+        (Method::GET, "/usage") => respond_json(
+            StatusCode::OK,
+            &serde_json::json!({ "models": crate::adapters::llm::usage_totals() }),
+        ),
+        (Method::POST, "/review") => match read_diff_request(request).await {
+            Ok(diff) => match state.engine().await {
+                Ok(engine) => match engine.review_diff(&diff).await {
+                    Ok(comments) => {
+                        respond_json(StatusCode::OK, &serde_json::json!({ "comments": comments }))
+                    }
+                    Err(err) => respond_error(StatusCode::INTERNAL_SERVER_ERROR, &err),
+                },
+                Err(err) => respond_error(StatusCode::INTERNAL_SERVER_ERROR, &err),
+            },
+            Err(err) => respond_error(StatusCode::BAD_REQUEST, &err),
+        },
+        (Method::POST, "/summary") => match read_diff_request(request).await {
+            Ok(diff) => match state.engine().await {
+                Ok(engine) => match engine.summarize(&diff).await {
+                    Ok(summary) => respond_json(
+                        StatusCode::OK,
+                        &serde_json::json!({
+                            "title": summary.title,
+                            "description": summary.description,
+                            "markdown": summary.to_markdown(),
+                        }),
+                    ),
+                    Err(err) => respond_error(StatusCode::INTERNAL_SERVER_ERROR, &err),
+                },
+                Err(err) => respond_error(StatusCode::INTERNAL_SERVER_ERROR, &err),
+            },
+            Err(err) => respond_error(StatusCode::BAD_REQUEST, &err),
+        },
+        _ => respond(StatusCode::NOT_FOUND, "not found"),
+    };
+    Ok(response)
+}
+
+/// The request body, refusing anything over `MAX_BODY_BYTES`.
+async fn read_body(body: Body) -> Result<hyper::body::Bytes> {
+    hyper::body::to_bytes(http_body::Limited::new(body, MAX_BODY_BYTES))
+        .await
+        .map_err(|err| anyhow::anyhow!("could not read the request body: {}", err))
+}
+
+/// The `diff` of a `{"diff": "..."}` request body sent as `application/json`.
+async fn read_diff_request(request: Request<Body>) -> Result<String> {
+    let json = request
+        .headers()
+        .get("content-type")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .is_some_and(|value| value.trim().eq_ignore_ascii_case("application/json"));
+    if !json {
+        anyhow::bail!("expected Content-Type: application/json");
+    }
+    let body = read_body(request.into_body()).await?;
+    let request: DiffRequest = serde_json::from_slice(&body)
+        .map_err(|err| anyhow::anyhow!("expected a JSON body with a \"diff\" field: {}", err))?;
+    if request.diff.trim().is_empty() {
+        anyhow::bail!("the diff is empty");
+    }
+    Ok(request.diff)
+}
+
+fn respond_json(status: StatusCode, value: &serde_json::Value) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header("content-type", "application/json")
+        .body(Body::from(value.to_string()))
+        .unwrap_or_default()
+}
+
+fn respond_error(status: StatusCode, err: &anyhow::Error) -> Response<Body> {
+    respond_json(
+        status,
+        &serde_json::json!({ "error": format!("{:#}", err) }),
+    )
+}
+
+fn respond(status: StatusCode, message: &str) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .body(Body::from(message.to_string()))
+        .unwrap_or_default()
+}
+
+/// Checks GitHub's `X-Hub-Signature-256: sha256=<hex hmac>` header in constant time.
+pub fn verify_github_signature(secret: &str, body: &[u8], signature: &str) -> bool {
+    let Some(hex) = signature.strip_prefix("sha256=") else {
+        return false;
+    };
+    let Some(expected) = decode_hex(hex) else {
+        return false;
+    };
+    let key = ring::hmac::Key::new(ring::hmac::HMAC_SHA256, secret.as_bytes());
+    ring::hmac::verify(&key, body, &expected).is_ok()
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Pull requests that were opened, reopened, or received new commits, and new comments that
+/// contain an `@diffscope` command from a commenter `config` answers.
+pub fn parse_github_event(event: &str, body: &[u8], config: &Config) -> Option<ReviewJob> {
+    let payload: serde_json::Value = serde_json::from_slice(body).ok()?;
+    match event {
+        "pull_request" => {}
+        "issue_comment" | "pull_request_review_comment" => {
+            return parse_github_comment_event(event, &payload, config)
+        }
+        _ => return None,
+    }
+    let action = payload["action"].as_str()?;
+    if !matches!(action, "opened" | "reopened" | "synchronize") {
+        return None;
+    }
+    if payload["pull_request"]["draft"].as_bool() == Some(true) {
+        return None;
+    }
+    Some(ReviewJob::GitHub {
+        repo: payload["repository"]["full_name"].as_str()?.to_string(),
+        number: payload["pull_request"]["number"].as_u64()?,
+    })
+}
+
+fn parse_github_comment_event(
+    event: &str,
+    payload: &serde_json::Value,
+    config: &Config,
+) -> Option<ReviewJob> {
+    if payload["action"].as_str()? != "created" || payload["sender"]["type"] == "Bot" {
+        return None;
+    }
+    let comment = &payload["comment"];
+    let body = comment["body"].as_str()?;
+    // Our replies (e.g. the help text) mention commands too; pending_commands skips them.
+    let (comment_id, _) = core::interactive::pending_commands([(comment["id"].as_u64()?, body)])
+        .into_iter()
+        .next()?;
+    let association = comment["author_association"].as_str().unwrap_or("NONE");
+    if !config.answers_commands_from(association) {
+        info!(
+            "Ignoring @diffscope command in comment {} from a {} commenter",
+            comment_id, association
+        );
+        return None;
+    }
+    let in_review_thread = event == "pull_request_review_comment";
+    let number = if in_review_thread {
+        payload["pull_request"]["number"].as_u64()?
+    } else {
+        // Issue comments fire for issues too; only PRs carry `issue.pull_request`.
+        payload["issue"].get("pull_request")?;
+        payload["issue"]["number"].as_u64()?
+    };
+    Some(ReviewJob::CommentCommand {
+        repo: payload["repository"]["full_name"].as_str()?.to_string(),
+        number,
+        comment_id,
+        body: body.to_string(),
+        in_review_thread,
+    })
+}
+
+/// Merge requests that were opened, reopened, or updated with new commits.
+pub fn parse_gitlab_event(event: &str, body: &[u8]) -> Option<ReviewJob> {
+    if event != "Merge Request Hook" {
+        return None;
+    }
+    let payload: serde_json::Value = serde_json::from_slice(body).ok()?;
+    let attributes = &payload["object_attributes"];
+    let action = attributes["action"].as_str()?;
+    // `update` also fires for title/label edits; only new commits carry `oldrev`.
+    let has_new_commits = action == "update" && attributes.get("oldrev").is_some();
+    if !matches!(action, "open" | "reopen") && !has_new_commits {
+        return None;
+    }
+    Some(ReviewJob::GitLab {
+        project_id: payload["project"]["id"].as_u64()?,
+        iid: attributes["iid"].as_u64()?,
+    })
+}
+
+/// A detached worktree of a pull request's head, removed again when dropped.
+struct HeadCheckout {
+    repo_root: PathBuf,
+    path: PathBuf,
+    _dir: tempfile::TempDir,
+}
+
+impl HeadCheckout {
+    /// Fetches `refspec` from `origin` into the clone at `repo_root` and checks out `sha`
+    /// beside it, so context comes from the code under review rather than the local branch.
+    async fn create(repo_root: &Path, refspec: &str, sha: &str) -> Result<Self> {
+        let git = |args: Vec<String>| {
+            let mut command = Command::new("git");
+            command.arg("-C").arg(repo_root).args(args);
+            command
+        };
+        let fetched = git(vec![
+            "fetch".into(),
+            "--quiet".into(),
+            "--no-tags".into(),
+            "origin".into(),
+            refspec.into(),
+        ])
+        .output()
+        .await?;
+        if !fetched.status.success() {
+            anyhow::bail!(
+                "git fetch {} failed: {}",
+                refspec,
+                String::from_utf8_lossy(&fetched.stderr).trim()
+            );
+        }
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("head");
+        let added = git(vec![
+            "worktree".into(),
+            "add".into(),
+            "--quiet".into(),
+            "--detach".into(),
+            path.to_string_lossy().into_owned(),
+            sha.into(),
+        ])
+        .output()
+        .await?;
+        if !added.status.success() {
+            anyhow::bail!(
+                "git worktree add {} failed: {}",
+                sha,
+                String::from_utf8_lossy(&added.stderr).trim()
+            );
+        }
+        Ok(Self {
+            repo_root: repo_root.to_path_buf(),
+            path,
+            _dir: dir,
+        })
+    }
+}
+
+impl Drop for HeadCheckout {
+    fn drop(&mut self) {
+        let _ = std::process::Command::new("git")
+            .arg("-C")
+            .arg(&self.repo_root)
+            .args(["worktree", "remove", "--force"])
+            .arg(&self.path)
+            .output();
+    }
+}
+
+/// The head checkout for a review, or `None` (reviewing against `repo_root`) when it cannot
+/// be made, e.g. because the server does not run inside a clone of the repository.
+async fn checkout_head(repo_root: &Path, refspec: &str, sha: &str) -> Option<HeadCheckout> {
+    match HeadCheckout::create(repo_root, refspec, sha).await {
+        Ok(checkout) => Some(checkout),
+        Err(err) => {
+            warn!(
+                "Could not check out {} ({:#}); reading context from {}",
+                sha,
+                err,
+                repo_root.display()
+            );
+            None
+        }
+    }
+}
+
+/// Where and with which configuration to review a pull request: in its head checkout when
+/// there is one, else in `repo_root`. The head may come from a fork, so a checkout is reviewed
+/// without the analyzers that run external tools in it; only the diff comes from the PR.
+fn review_setup<'a>(
+    state: &'a ServerState,
+    checkout: Option<&'a HeadCheckout>,
+) -> (&'a Path, Config) {
+    match checkout {
+        Some(checkout) => {
+            let mut config = state.config.clone();
+            config.plugins = config.plugins.without_repo_tools();
+            (&checkout.path, config)
+        }
+        None => (&state.repo_root, state.config.clone()),
+    }
+}
+
+async fn run_review_job(job: &ReviewJob, state: &ServerState) -> Result<()> {
+    let provider = &state.config.provider;
+    match job {
+        ReviewJob::GitHub { repo, number } => {
+            let client = GitHubClient::new(&provider.github)?;
+            let diff_content = client.fetch_diff(repo, *number).await?;
+            if diff_content.trim().is_empty() {
+                return Ok(());
+            }
+            let head_sha = client.head_sha(repo, *number).await?;
+            let checkout = checkout_head(
+                &state.repo_root,
+                &format!("pull/{}/head", number),
+                &head_sha,
+            )
+            .await;
+            let (review_root, config) = review_setup(state, checkout.as_ref());
+            let outcome =
+                crate::review_diff_content_raw(&diff_content, config, review_root).await?;
+            let comments = outcome.comments;
+            let diffs = core::DiffParser::parse_unified_diff(&diff_content)?;
+            let positions = core::position::PositionMap::new(&diffs);
+            // Skipped files were not re-checked, so their earlier comments stay open.
+            let reviewed: Vec<&core::UnifiedDiff> = diffs
+                .iter()
+                .filter(|diff| {
+                    !outcome
+                        .skipped
+                        .iter()
+                        .any(|file| file.file_path == diff.file_path)
+                })
+                .collect();
+            let posted = client.review_comments(repo, *number).await?;
+            for comment in stale_review_comments(&posted, &comments, &reviewed) {
+                if let Err(err) = client.mark_addressed(repo, comment).await {
+                    warn!("Failed to resolve comment {}: {:#}", comment.id, err);
+                }
+            }
+            let review = crate::build_github_review(
+                &comments,
+                &positions,
+                &head_sha,
+                state.config.codeowners.mention_on_error,
+            );
+            client.submit_review(repo, *number, &review).await?;
+            info!(
+                "Posted review with {} comments to {}#{}",
+                comments.len(),
+                repo,
+                number
+            );
+        }
+        ReviewJob::CommentCommand {
+            repo,
+            number,
+            comment_id,
+            body,
+            in_review_thread,
+        } => {
+            let Some(command) = core::interactive::InteractiveCommand::parse(body) else {
+                return Ok(());
+            };
+            let client = GitHubClient::new(&provider.github)?;
+            let diff_content = client.fetch_diff(repo, *number).await?;
+            let reply = crate::answer_interactive_command(
+                &command,
+                *comment_id,
+                &diff_content,
+                &state.config,
+            )
+            .await?;
+            if *in_review_thread {
+                client
+                    .reply_to_review_comment(repo, *number, *comment_id, &reply)
+                    .await?;
+            } else {
+                client.post_issue_comment(repo, *number, &reply).await?;
+            }
+            info!(
+                "Answered {:?} command on {}#{}",
+                command.command, repo, number
+            );
+        }
+        ReviewJob::GitLab { project_id, iid } => {
+            let client = GitLabClient::new(&provider.gitlab)?;
+            let (diff_content, refs) = client.fetch_merge_request(*project_id, *iid).await?;
+            if diff_content.trim().is_empty() {
+                return Ok(());
+            }
+            let checkout = checkout_head(
+                &state.repo_root,
+                &format!("refs/merge-requests/{}/head", iid),
+                &refs.head_sha,
+            )
+            .await;
+            let (review_root, config) = review_setup(state, checkout.as_ref());
+            let comments = crate::review_diff_content_raw(&diff_content, config, review_root)
+                .await?
+                .comments;
+            let diffs = core::DiffParser::parse_unified_diff(&diff_content)?;
+            let positions = core::position::PositionMap::new(&diffs);
+            let mut posted = 0;
+            for comment in &comments {
+                let position = positions.locate(&comment.file_path, comment.line_number);
+                let body = crate::format_github_comment_body(comment, None);
+                let body = match &position {
+                    Some(_) => body,
+                    None => format!(
+                        "`{}:{}` {}",
+                        comment.file_path.display(),
+                        comment.line_number,
+                        body
+                    ),
+                };
+                let old_path = diffs
+                    .iter()
+                    .find(|diff| diff.file_path == comment.file_path)
+                    .and_then(|diff| diff.old_path.as_deref());
+                // One rejected comment (e.g. a line GitLab will not anchor) should not cost
+                // the rest of the review.
+                match client
+                    .post_comment(
+                        *project_id,
+                        *iid,
+                        &body,
+                        position.as_ref().map(|p| (p, &refs)),
+                        old_path,
+                    )
+                    .await
+                {
+                    Ok(()) => posted += 1,
+                    Err(err) => warn!(
+                        "Failed to post comment on {}:{}: {:#}",
+                        comment.file_path.display(),
+                        comment.line_number,
+                        err
+                    ),
+                }
+            }
+            info!(
+                "Posted {} of {} comments to GitLab project {} MR !{}",
+                posted,
+                comments.len(),
+                project_id,
+                iid
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn local_api_validates_requests_and_reports_usage() {
+        let repo = tempfile::tempdir().unwrap();
+        let config = Config {
+            local_api_token: Some("s3cret".to_string()),
+            ..Config::default()
+        };
+        let addr: SocketAddr = "127.0.0.1:7878".parse().unwrap();
+        let state = Arc::new(
+            LocalState::new(config, repo.path().to_path_buf(), addr)
+                .await
+                .unwrap(),
+        );
+        let call = |method: Method, path: &str, headers: &[(&str, &str)], body: &str| {
+            let mut request = Request::builder().method(method).uri(path);
+            for (name, value) in headers {
+                request = request.header(*name, *value);
+            }
+            let request = request.body(Body::from(body.to_string())).unwrap();
+            let state = state.clone();
+            async move {
+                let response = handle_local_request(request, state).await.unwrap();
+                let status = response.status();
+                let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+                (status, String::from_utf8(body.to_vec()).unwrap())
+            }
+        };
+        let trusted = [
+            ("host", "localhost:7878"),
+            ("authorization", "Bearer s3cret"),
+            ("content-type", "application/json"),
+        ];
+
+        let (status, body) = call(Method::POST, "/review", &trusted, "--- a/x\n+++ b/x\n").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body.contains("expected a JSON body with a \\\"diff\\\" field"));
+        let (status, body) = call(Method::POST, "/summary", &trusted, r#"{"diff":"  "}"#).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body, r#"{"error":"the diff is empty"}"#);
+        let (status, body) = call(Method::POST, "/review", &trusted[..2], r#"{"diff":"x"}"#).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body.contains("application/json"));
+
+        let rebinding = [("host", "attacker.example:7878"), trusted[1], trusted[2]];
+        assert_eq!(
+            call(Method::POST, "/review", &rebinding, "{}").await.0,
+            StatusCode::FORBIDDEN
+        );
+        let cross_origin = [
+            trusted[0],
+            trusted[1],
+            ("origin", "https://attacker.example"),
+        ];
+        assert_eq!(
+            call(Method::GET, "/usage", &cross_origin, "").await.0,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            call(Method::GET, "/usage", &[trusted[0]], "").await.0,
+            StatusCode::UNAUTHORIZED
+        );
+
+        let (status, body) = call(Method::GET, "/usage", &trusted, "").await;
+        assert_eq!(status, StatusCode::OK);
+        let usage: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert!(usage["models"].is_object());
+        assert_eq!(
+            call(Method::GET, "/webhook", &trusted, "").await.0,
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            call(Method::GET, "/health", &[], "").await.0,
+            StatusCode::OK
+        );
+    }
+
+    #[tokio::test]
+    async fn refuses_to_serve_webhooks_without_a_secret() {
+        std::env::remove_var("DIFFSCOPE_WEBHOOK_SECRET");
+        let addr: SocketAddr = "127.0.0.1:0".parse().unwrap();
+        let err = serve(Config::default(), addr, PathBuf::from("."), false)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("--insecure"));
+    }
+
+    #[tokio::test]
+    async fn reviews_head_checkouts_without_external_tools() {
+        let config = Config {
+            plugins: serde_yaml::from_str(
+                "rules:\n  - id: no-unwrap\n    message: Avoid unwrap\n    ast_grep: $X.unwrap()\n  - id: todo\n    message: Resolve the TODO\n    pattern: TODO\n",
+            )
+            .unwrap(),
+            ..Config::default()
+        };
+        let state = ServerState {
+            config,
+            secret: None,
+            repo_root: PathBuf::from("."),
+            reviews: Arc::new(Semaphore::new(1)),
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let checkout = HeadCheckout {
+            repo_root: dir.path().to_path_buf(),
+            path: dir.path().join("head"),
+            _dir: tempfile::tempdir().unwrap(),
+        };
+
+        let (root, config) = review_setup(&state, None);
+        assert_eq!(root, Path::new("."));
+        assert!(config.plugins.eslint && config.plugins.rules.len() == 2);
+
+        let (root, config) = review_setup(&state, Some(&checkout));
+        assert_eq!(root, checkout.path);
+        let mut manager = crate::plugins::plugin::PluginManager::new();
+        manager.load_builtin_plugins(&config.plugins).await.unwrap();
+        let loaded = manager.loaded_plugins();
+        for tool in ["eslint", "ruff", "golangci", "semgrep"] {
+            assert!(loaded.iter().all(|plugin| plugin.id != tool), "{}", tool);
+        }
+        let iac = loaded.iter().find(|plugin| plugin.id == "iac").unwrap();
+        assert_eq!(
+            iac.settings,
+            serde_json::json!({"tflint": false, "checkov": false})
+        );
+        let rules: Vec<&str> = config.plugins.rules.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(rules, ["todo"]);
+    }
+
+    #[test]
+    fn verifies_signatures_and_parses_pull_request_events() {
+        let body = br#"{"action":"synchronize","pull_request":{"number":42,"draft":false},"repository":{"full_name":"acme/shop"}}"#;
+        let key = ring::hmac::Key::new(ring::hmac::HMAC_SHA256, b"topsecret");
+        let tag = ring::hmac::sign(&key, body);
+        let signature = format!(
+            "sha256={}",
+            tag.as_ref()
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect::<String>()
+        );
+
+        assert!(verify_github_signature("topsecret", body, &signature));
+        assert!(!verify_github_signature("wrong", body, &signature));
+        let config = Config::default();
+        assert_eq!(
+            parse_github_event("pull_request", body, &config),
+            Some(ReviewJob::GitHub {
+                repo: "acme/shop".to_string(),
+                number: 42
+            })
+        );
+        assert_eq!(parse_github_event("push", body, &config), None);
+
+        let command = |association: &str| {
+            format!(
+                r#"{{"action":"created","issue":{{"number":42,"pull_request":{{}}}},"comment":{{"id":7,"body":"@diffscope explain","author_association":"{}"}},"sender":{{"type":"User"}},"repository":{{"full_name":"acme/shop"}}}}"#,
+                association
+            )
+        };
+        assert!(matches!(
+            parse_github_event("issue_comment", command("MEMBER").as_bytes(), &config),
+            Some(ReviewJob::CommentCommand {
+                number: 42,
+                comment_id: 7,
+                in_review_thread: false,
+                ..
+            })
+        ));
+        for outsider in ["NONE", "CONTRIBUTOR"] {
+            assert_eq!(
+                parse_github_event("issue_comment", command(outsider).as_bytes(), &config),
+                None
+            );
+        }
+        let open = Config {
+            comment_command_associations: vec!["contributor".to_string()],
+            ..Config::default()
+        };
+        assert!(
+            parse_github_event("issue_comment", command("CONTRIBUTOR").as_bytes(), &open).is_some()
+        );
+
+        let label_edit = br#"{"object_attributes":{"action":"update","iid":3},"project":{"id":9}}"#;
+        assert_eq!(parse_gitlab_event("Merge Request Hook", label_edit), None);
+        let new_commits = br#"{"object_attributes":{"action":"update","iid":3,"oldrev":"abc"},"project":{"id":9}}"#;
+        assert_eq!(
+            parse_gitlab_event("Merge Request Hook", new_commits),
+            Some(ReviewJob::GitLab {
+                project_id: 9,
+                iid: 3
+            })
+        );
+    }
+}